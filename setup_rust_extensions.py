@@ -9,7 +9,8 @@ setup(
         RustExtension(
             "fortress_rust.fast_math",
             path="src/fast_math/Cargo.toml",
-            binding=Binding.PyO3
+            binding=Binding.PyO3,
+            features=["extension-module"]
         ),
         RustExtension(
             "fortress_rust.data_structures",
@@ -0,0 +1,294 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::column::{self, Series};
+use crate::linalg::{self, Fit};
+
+/// MacKinnon (1994) response surface of a unit-root statistic: its bounds, the switch
+/// point between the two fits and the polynomial coefficients of each fit
+pub struct Surface {
+    pub max: f64,
+    pub min: f64,
+    pub star: f64,
+    pub small: [f64; 3],
+    pub large: [f64; 4],
+}
+
+impl Surface {
+    /// Approximate p-value of a statistic, interpolated on the surface
+    pub fn p_value(&self, statistic: f64) -> f64 {
+        if statistic.is_nan() {
+            return f64::NAN;
+        }
+        if statistic > self.max {
+            return 1.0;
+        }
+        if statistic < self.min {
+            return 0.0;
+        }
+        let poly = |coef: &[f64]| coef.iter().rev().fold(0.0, |acc, c| acc * statistic + c);
+        let z = if statistic <= self.star { poly(&self.small) } else { poly(&self.large) };
+        Normal::new(0.0, 1.0).unwrap().cdf(z)
+    }
+}
+
+/// Deterministic terms of the test regression
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Regression {
+    /// No constant or trend
+    None,
+    Constant,
+    ConstantTrend,
+}
+
+impl Regression {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "n" | "nc" => Ok(Regression::None),
+            "c" => Ok(Regression::Constant),
+            "ct" => Ok(Regression::ConstantTrend),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown regression '{}', expected 'n', 'c' or 'ct'", name
+            ))),
+        }
+    }
+
+    fn terms(self) -> usize {
+        match self {
+            Regression::None => 0,
+            Regression::Constant => 1,
+            Regression::ConstantTrend => 2,
+        }
+    }
+
+    /// MacKinnon's response surface of the ADF statistic of one series
+    fn surface(self) -> Surface {
+        match self {
+            Regression::None => Surface {
+                max: f64::INFINITY,
+                min: -19.04,
+                star: -1.04,
+                small: [0.6344, 1.2378, 0.032496],
+                large: [0.4797, 0.93557, -0.06999, 0.033066],
+            },
+            Regression::Constant => Surface {
+                max: 2.74,
+                min: -18.83,
+                star: -1.61,
+                small: [2.1659, 1.4412, 0.038269],
+                large: [1.7339, 0.93202, -0.12745, -0.010368],
+            },
+            Regression::ConstantTrend => Surface {
+                max: 0.7,
+                min: -16.18,
+                star: -2.89,
+                small: [3.2512, 1.6047, 0.049588],
+                large: [2.5261, 0.61654, -0.37956, -0.060285],
+            },
+        }
+    }
+}
+
+/// How the number of lagged differences is chosen
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Autolag {
+    Aic,
+    Bic,
+    /// Use `max_lag` as given
+    Fixed,
+}
+
+impl Autolag {
+    pub fn parse(name: Option<&str>) -> PyResult<Self> {
+        match name.map(str::to_ascii_lowercase).as_deref() {
+            Some("aic") => Ok(Autolag::Aic),
+            Some("bic") => Ok(Autolag::Bic),
+            None => Ok(Autolag::Fixed),
+            Some(other) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown autolag '{}', expected 'aic', 'bic' or None", other
+            ))),
+        }
+    }
+}
+
+/// Design of the test regression with `lags` lagged differences, over the differences
+/// from index `first` on: the deterministic terms, the lagged level, then the lags
+fn design(values: &[f64], diffs: &[f64], regression: Regression, lags: usize, first: usize) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let rows = first..diffs.len();
+    let mut columns = Vec::with_capacity(regression.terms() + 1 + lags);
+    if regression != Regression::None {
+        columns.push(vec![1.0; rows.len()]);
+    }
+    if regression == Regression::ConstantTrend {
+        columns.push((1..=rows.len()).map(|t| t as f64).collect());
+    }
+    columns.push(rows.clone().map(|t| values[t]).collect());
+    for lag in 1..=lags {
+        columns.push(rows.clone().map(|t| diffs[t - lag]).collect());
+    }
+    (columns, rows.map(|t| diffs[t]).collect())
+}
+
+/// Information criterion of a fit, up to terms shared by every candidate lag
+fn criterion(fit: &Fit, nobs: usize, autolag: Autolag) -> f64 {
+    let n = nobs as f64;
+    let llf = -0.5 * n * ((2.0 * std::f64::consts::PI).ln() + (fit.ssr / n).ln() + 1.0);
+    let penalty = if autolag == Autolag::Bic { n.ln() } else { 2.0 };
+    -2.0 * llf + penalty * fit.params.len() as f64
+}
+
+/// The lag count to search up to for `len` values: `max_lag` if given, else
+/// `12 * (len / 100) ** 0.25`, capped at the largest lag the sample supports as in
+/// statsmodels
+pub fn max_lag_for(len: usize, regression: Regression, max_lag: Option<usize>) -> PyResult<usize> {
+    let limit = (len / 2).checked_sub(regression.terms() + 1).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "{} values are too few for an ADF test with this regression", len
+        ))
+    })?;
+    match max_lag {
+        Some(lag) if lag > limit => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "max_lag must be at most {} for {} values", limit, len
+        ))),
+        Some(lag) => Ok(lag),
+        None => Ok(((12.0 * (len as f64 / 100.0).powf(0.25)).ceil() as usize).min(limit)),
+    }
+}
+
+/// ADF statistic and the number of lagged differences used
+pub fn statistic(values: &[f64], max_lag: usize, regression: Regression, autolag: Autolag) -> (f64, usize) {
+    let diffs: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+    let lag = match autolag {
+        Autolag::Fixed => max_lag,
+        // Every candidate is fitted on the same rows, so their criteria are comparable
+        _ => (0..=max_lag)
+            .filter_map(|lags| {
+                let (columns, y) = design(values, &diffs, regression, lags, max_lag);
+                let nobs = y.len();
+                linalg::ols(columns, y).map(|fit| (criterion(&fit, nobs, autolag), lags))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+            .map_or(max_lag, |(_, lags)| lags),
+    };
+
+    let (columns, y) = design(values, &diffs, regression, lag, lag);
+    let nobs = y.len();
+    let level = regression.terms();
+    let statistic = match linalg::ols(columns, y) {
+        Some(fit) => {
+            let sigma_sq = fit.ssr / (nobs - fit.params.len()) as f64;
+            fit.params[level] / (sigma_sq * fit.inverse_diag[level]).sqrt()
+        }
+        None => f64::NAN,
+    };
+    (statistic, lag)
+}
+
+/// ADF statistic, p-value and the number of lagged differences used
+fn adf(values: &[f64], max_lag: usize, regression: Regression, autolag: Autolag) -> (f64, f64, usize) {
+    let (statistic, lag) = statistic(values, max_lag, regression, autolag);
+    (statistic, regression.surface().p_value(statistic), lag)
+}
+
+/// Augmented Dickey-Fuller unit-root test, like statsmodels' `adfuller`.
+///
+/// Regresses the differences on the lagged level, `regression` terms ("c" constant, the
+/// default; "ct" constant and trend; "n" neither) and up to `max_lag` lagged differences
+/// (default `12 * (n / 100) ** 0.25`, capped by the sample). `autolag="aic"` (or "bic")
+/// picks the lag count minimising the criterion over fits on a common sample, as
+/// statsmodels does; `autolag=None` uses `max_lag` lags. Returns `(statistic, p_value,
+/// lag)`, the p-value interpolated on MacKinnon's (1994) response surface of the critical
+/// values; a small p-value rejects the unit root, i.e. suggests stationarity. A 2D array
+/// is tested row by row in parallel, returning three arrays. Constant or NaN-holding
+/// series give a NaN statistic and p-value.
+#[pyfunction]
+#[pyo3(signature = (data, max_lag=None, regression="c", autolag=Some("aic")))]
+pub fn adf_test_rust<'py>(
+    py: Python<'py>,
+    data: Series<'py, f64>,
+    max_lag: Option<usize>,
+    regression: &str,
+    autolag: Option<&str>
+) -> PyResult<PyObject> {
+    let regression = Regression::parse(regression)?;
+    let autolag = Autolag::parse(autolag)?;
+
+    match data {
+        Series::Single(column) => {
+            let values = column.values()?;
+            let max_lag = max_lag_for(values.len(), regression, max_lag)?;
+            Ok(py.allow_threads(|| adf(&values, max_lag, regression, autolag)).into_py(py))
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            let max_lag = max_lag_for(batch.ncols(), regression, max_lag)?;
+            let results = py.allow_threads(|| {
+                column::map_rows(batch, |_, row| Ok(adf(row, max_lag, regression, autolag)))
+            })?;
+            let statistics: Vec<f64> = results.iter().map(|r| r.0).collect();
+            let p_values: Vec<f64> = results.iter().map(|r| r.1).collect();
+            let lags: Vec<i64> = results.iter().map(|r| r.2 as i64).collect();
+            Ok((
+                PyArray1::from_vec_bound(py, statistics),
+                PyArray1::from_vec_bound(py, p_values),
+                PyArray1::from_vec_bound(py, lags),
+            )
+                .into_py(py))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SplitMix;
+
+    /// Deterministic standard normal noise
+    fn noise(n: usize, seed: u64) -> Vec<f64> {
+        let mut rng = SplitMix::new(seed, 0);
+        (0..n.div_ceil(2)).flat_map(|_| <[f64; 2]>::from(rng.normal_pair())).take(n).collect()
+    }
+
+    #[test]
+    fn p_values_match_the_asymptotic_critical_values() {
+        // MacKinnon's 5% asymptotic critical values, as tabulated by statsmodels
+        for (regression, critical) in [(Regression::None, -1.9409), (Regression::Constant, -2.8621), (Regression::ConstantTrend, -3.4126)] {
+            let p = regression.surface().p_value(critical);
+            assert!((p - 0.05).abs() < 0.005, "{}", p);
+        }
+        assert_eq!(Regression::Constant.surface().p_value(5.0), 1.0);
+        assert_eq!(Regression::Constant.surface().p_value(-50.0), 0.0);
+    }
+
+    #[test]
+    fn statistic_is_the_t_ratio_of_the_lagged_level() {
+        let values: Vec<f64> = noise(60, 7).iter().scan(0.0, |y, e| { *y = 0.7 * *y + e; Some(*y) }).collect();
+        let (statistic, lag) = statistic(&values, 0, Regression::None, Autolag::Fixed);
+        assert_eq!(lag, 0);
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for t in 1..values.len() {
+            sxy += values[t - 1] * (values[t] - values[t - 1]);
+            sxx += values[t - 1] * values[t - 1];
+        }
+        let rho = sxy / sxx;
+        let ssr: f64 = (1..values.len()).map(|t| (values[t] - values[t - 1] - rho * values[t - 1]).powi(2)).sum();
+        let expected = rho / (ssr / (values.len() - 2) as f64 / sxx).sqrt();
+        assert!((statistic - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stationary_series_reject_the_unit_root() {
+        let e = noise(500, 1);
+        let ar: Vec<f64> = e.iter().scan(0.0, |y, e| { *y = 0.5 * *y + e; Some(*y) }).collect();
+        let walk: Vec<f64> = e.iter().scan(0.0, |y, e| { *y += e; Some(*y) }).collect();
+        let max_lag = max_lag_for(500, Regression::Constant, None).unwrap();
+        assert_eq!(max_lag, 18);
+        let (_, p, _) = adf(&ar, max_lag, Regression::Constant, Autolag::Aic);
+        assert!(p < 0.01, "{}", p);
+        let (_, p, _) = adf(&walk, max_lag, Regression::Constant, Autolag::Aic);
+        assert!(p > 0.1, "{}", p);
+        assert!(adf(&[1.0; 50], 2, Regression::Constant, Autolag::Fixed).0.is_nan());
+    }
+}
@@ -0,0 +1,190 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBool;
+
+use crate::column::{check_out, FloatElement};
+use crate::pool::Buffer;
+
+/// How a windowed kernel's output lines up with its input, shared by every rolling and
+/// indicator function through its `align` argument.
+///
+/// From Python, `align` is `"valid"`, `"same"` or an integer `k` for `Shifted(k)`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// Only positions with a full window: shorter than the input
+    Valid,
+    /// NaN-padded at the front to the input length, so `result[i]` is the value at
+    /// input index `i`
+    Same,
+    /// `Same`, then shifted `k` positions later like pandas' `shift(k)` (earlier for
+    /// negative `k`), so `result[i]` is the value at input index `i - k`; `Shifted(1)`
+    /// gives features that only use data before each bar
+    Shifted(isize),
+}
+
+impl Alignment {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "valid" => Ok(Alignment::Valid),
+            "same" => Ok(Alignment::Same),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown align '{}', expected 'valid', 'same' or an integer shift", name
+            ))),
+        }
+    }
+
+    /// Copy full-window `values` into `out`, which has the input's length, at their
+    /// aligned positions; positions they do not cover are `fill`
+    fn place<T: Copy>(self, values: &[T], out: &mut [T], fill: T) {
+        let shift = match self {
+            Alignment::Shifted(k) => k,
+            _ => 0,
+        };
+        out.fill(fill);
+        let offset = (out.len() - values.len().min(out.len())) as isize + shift;
+        for (j, &value) in values.iter().enumerate() {
+            let target = offset + j as isize;
+            if (0..out.len() as isize).contains(&target) {
+                out[target as usize] = value;
+            }
+        }
+    }
+
+    /// Align a kernel's full-window `values` for an input of `len` values
+    pub fn pad<T: FloatElement>(self, values: Vec<T>, len: usize) -> Vec<T> {
+        match self {
+            Alignment::Valid => values,
+            _ => {
+                let mut aligned = vec![T::nan(); len];
+                self.place(&values, &mut aligned, T::nan());
+                aligned
+            }
+        }
+    }
+
+    /// Align a kernel's output that already has a value (NaN, flat or similar) at each of
+    /// the `warmup` positions before its first full window: `Valid` drops them, `Same`
+    /// keeps the output as is and a shift moves it later, filling with `fill`
+    pub fn trim<T: Copy>(self, values: Vec<T>, warmup: usize, fill: T) -> Vec<T> {
+        match self {
+            Alignment::Valid => values[warmup.min(values.len())..].to_vec(),
+            Alignment::Same => values,
+            Alignment::Shifted(_) => {
+                let mut aligned = vec![fill; values.len()];
+                self.place(&values, &mut aligned, fill);
+                aligned
+            }
+        }
+    }
+
+    /// Index in an aligned output of length `len` of the value whose window ends at
+    /// input index `end`; None when it is shifted out, or for `Valid`, whose output has
+    /// no padding
+    pub fn position(self, end: usize, len: usize) -> Option<usize> {
+        let target = match self {
+            Alignment::Valid => return None,
+            Alignment::Same => end as isize,
+            Alignment::Shifted(k) => end as isize + k,
+        };
+        (0..len as isize).contains(&target).then_some(target as usize)
+    }
+
+    /// Run a kernel producing `valid` full-window values for an input of `len` values
+    /// so its results land in `out` at their aligned positions. `Same` lets the kernel
+    /// write into the tail of `out` directly; a shift goes through a pooled buffer.
+    pub fn write_into<T: FloatElement>(
+        self,
+        out: &mut [T],
+        len: usize,
+        valid: usize,
+        kernel: impl FnOnce(&mut [T]) -> PyResult<()>
+    ) -> PyResult<()> {
+        match self {
+            Alignment::Valid => kernel(out),
+            Alignment::Same => {
+                check_out(out, len)?;
+                let (padding, rest) = out.split_at_mut(len - valid.min(len));
+                padding.fill(T::nan());
+                kernel(rest)
+            }
+            Alignment::Shifted(_) => {
+                check_out(out, len)?;
+                let mut values = Buffer::zeroed(valid);
+                kernel(&mut values)?;
+                self.place(&values, out, T::nan());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'py> FromPyObject<'py> for Alignment {
+    fn extract_bound(value: &Bound<'py, PyAny>) -> PyResult<Self> {
+        // bool is an int subclass, but `align=True` is a mistake rather than a shift of 1
+        if value.is_instance_of::<PyBool>() {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "align must be 'valid', 'same' or an integer shift, not a bool"
+            ));
+        }
+        if let Ok(shift) = value.extract::<isize>() {
+            return Ok(Alignment::Shifted(shift));
+        }
+        let name: String = value.extract().map_err(|_| {
+            pyo3::exceptions::PyTypeError::new_err("align must be 'valid', 'same' or an integer shift")
+        })?;
+        Alignment::parse(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_places_values_by_mode() {
+        let padded = Alignment::Same.pad(vec![1.0f64, 2.0], 4);
+        assert!(padded[0].is_nan() && padded[1].is_nan() && padded[2] == 1.0);
+        assert_eq!(Alignment::Valid.pad(vec![1.0f64], 4), vec![1.0]);
+        let padded = Alignment::Shifted(1).pad(vec![1.0f64, 2.0], 4);
+        assert!(padded[2].is_nan() && padded[3] == 1.0);
+        let padded = Alignment::Shifted(-2).pad(vec![1.0f64, 2.0], 4);
+        assert!(padded[0] == 1.0 && padded[1] == 2.0 && padded[2].is_nan());
+        // A shift past the whole series leaves nothing
+        assert!(Alignment::Shifted(-9).pad(vec![1.0f64, 2.0], 4).iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn write_into_checks_the_buffer() {
+        let mut out = vec![0.0f64; 5];
+        Alignment::Same.write_into(&mut out, 5, 3, |dense| { dense.fill(1.0); Ok(()) }).unwrap();
+        assert!(out[1].is_nan() && out[2] == 1.0);
+        Alignment::Shifted(1).write_into(&mut out, 5, 3, |dense| { dense.copy_from_slice(&[1.0, 2.0, 3.0]); Ok(()) }).unwrap();
+        assert!(out[2].is_nan() && out[3] == 1.0 && out[4] == 2.0);
+        assert!(Alignment::Same.write_into(&mut [0.0f64; 3], 5, 3, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn trim_and_position_agree() {
+        let values = vec![f64::NAN, f64::NAN, 1.0, 2.0, 3.0];
+        assert_eq!(Alignment::Valid.trim(values.clone(), 2, f64::NAN), vec![1.0, 2.0, 3.0]);
+        assert_eq!(Alignment::Same.trim(values, 2, f64::NAN)[2..], [1.0, 2.0, 3.0]);
+        assert_eq!(Alignment::Shifted(1).trim(vec![0i8, 0, 1, 1, -1], 2, 0), vec![0, 0, 0, 1, 1]);
+        assert_eq!(Alignment::Shifted(-1).trim(vec![0i8, 0, 1, 1, -1], 2, 0), vec![0, 1, 1, -1, 0]);
+        assert_eq!(Alignment::Valid.trim(vec![1, 2], 5, 0), Vec::<i32>::new());
+        assert_eq!(Alignment::Same.position(3, 5), Some(3));
+        assert_eq!(Alignment::Shifted(2).position(3, 5), None);
+        assert_eq!(Alignment::Shifted(-3).position(3, 5), Some(0));
+        assert_eq!(Alignment::Valid.position(3, 5), None);
+    }
+
+    #[test]
+    fn extract_rejects_bools() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let eval = |source: &str| py.eval_bound(source, None, None).unwrap().extract::<Alignment>();
+            assert!(eval("True").is_err());
+            assert!(eval("'full'").is_err());
+            assert!(eval("1").unwrap() == Alignment::Shifted(1));
+            assert!(eval("'SAME'").unwrap() == Alignment::Same);
+        });
+    }
+}
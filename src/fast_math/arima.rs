@@ -0,0 +1,452 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+
+use crate::column::Column;
+use crate::{linalg, optimize};
+
+/// How the ARMA coefficients are estimated
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Conditional sum of squares: the first `p` values are taken as given and earlier
+    /// shocks as zero
+    Css,
+    /// Exact Gaussian likelihood, evaluated by a Kalman filter
+    Mle,
+    /// MLE started from the CSS estimates
+    CssMle,
+}
+
+impl Method {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "css" => Ok(Method::Css),
+            "mle" => Ok(Method::Mle),
+            "css-mle" => Ok(Method::CssMle),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'css', 'mle' or 'css-mle'", name
+            ))),
+        }
+    }
+}
+
+/// Whether `1 - c[0] z - c[1] z^2 - ...` has all its roots outside the unit circle, by
+/// stepping the coefficients down to partial autocorrelations (reverse Durbin-Levinson)
+fn is_stationary(coefficients: &[f64]) -> bool {
+    let mut a = coefficients.to_vec();
+    while let Some(&kappa) = a.last() {
+        if kappa.is_nan() || kappa.abs() >= 1.0 {
+            return false;
+        }
+        let k = a.len();
+        let denominator = 1.0 - kappa * kappa;
+        a = (0..k - 1).map(|j| (a[j] + kappa * a[k - 2 - j]) / denominator).collect();
+    }
+    true
+}
+
+/// ARMA(p, q) in Harvey's state-space form, with unit shock variance: the state has
+/// `max(p, q + 1)` elements, the observation is its first, the transition carries the AR
+/// coefficients down its first column and the shock enters with loadings `[1, theta..]`
+struct StateSpace {
+    ar: Vec<f64>,
+    loadings: Vec<f64>,
+}
+
+impl StateSpace {
+    fn new(ar: &[f64], ma: &[f64]) -> Self {
+        let r = ar.len().max(ma.len() + 1);
+        let mut padded = ar.to_vec();
+        padded.resize(r, 0.0);
+        let mut loadings = vec![1.0];
+        loadings.extend_from_slice(ma);
+        loadings.resize(r, 0.0);
+        StateSpace { ar: padded, loadings }
+    }
+
+    fn dim(&self) -> usize {
+        self.ar.len()
+    }
+
+    /// `T x` for a state vector `x`
+    fn transition(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.dim())
+            .map(|i| self.ar[i] * x[0] + x.get(i + 1).copied().unwrap_or(0.0))
+            .collect()
+    }
+
+    /// `T P T'` for a symmetric `P`
+    fn propagate(&self, p: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let r = self.dim();
+        // T applied to the rows of P (its columns, by symmetry) gives the columns of T P;
+        // T applied to the rows of T P then gives the rows of T P T'
+        let columns: Vec<Vec<f64>> = p.iter().map(|row| self.transition(row)).collect();
+        let tp: Vec<Vec<f64>> = (0..r).map(|i| (0..r).map(|j| columns[j][i]).collect()).collect();
+        tp.iter().map(|row| self.transition(row)).collect()
+    }
+
+    /// Unconditional state covariance, solving `P = T P T' + R R'` as a linear system in
+    /// the entries of `P`; None when singular
+    fn stationary_covariance(&self) -> Option<Vec<Vec<f64>>> {
+        let r = self.dim();
+        let t = |x: usize, y: usize| {
+            if y == 0 { self.ar[x] } else if y == x + 1 { 1.0 } else { 0.0 }
+        };
+        // Entry (a, b) of T P T' is the sum of T[a][c] P[c][d] T[b][d]
+        let system: Vec<Vec<f64>> = (0..r * r)
+            .map(|i| {
+                (0..r * r)
+                    .map(|j| {
+                        let identity = if i == j { 1.0 } else { 0.0 };
+                        identity - t(i / r, j / r) * t(i % r, j % r)
+                    })
+                    .collect()
+            })
+            .collect();
+        let rhs: Vec<f64> = (0..r * r).map(|i| self.loadings[i / r] * self.loadings[i % r]).collect();
+        let entries = linalg::solve(system, rhs)?;
+        Some(entries.chunks(r).map(<[f64]>::to_vec).collect())
+    }
+}
+
+/// Kalman filter of demeaned values: the one-step prediction errors scaled by their
+/// standard deviation, the sum of the logs of their variances and the predicted state after
+/// the last value
+fn kalman(values: &[f64], model: &StateSpace) -> Option<(Vec<f64>, f64, Vec<f64>)> {
+    let r = model.dim();
+    let mut p = model.stationary_covariance()?;
+    let mut a = vec![0.0; r];
+    let (mut scaled, mut log_det) = (Vec::with_capacity(values.len()), 0.0);
+    for &y in values {
+        let (v, f) = (y - a[0], p[0][0]);
+        if f.is_nan() || f <= 0.0 {
+            return None;
+        }
+        scaled.push(v / f.sqrt());
+        log_det += f.ln();
+        // Gain K = T P Z' / F, with Z picking the first state
+        let first: Vec<f64> = p.iter().map(|row| row[0]).collect();
+        let gain: Vec<f64> = model.transition(&first).iter().map(|k| k / f).collect();
+        a = model.transition(&a).iter().zip(&gain).map(|(x, k)| x + k * v).collect();
+        let propagated = model.propagate(&p);
+        p = (0..r)
+            .map(|i| {
+                (0..r)
+                    .map(|j| propagated[i][j] + model.loadings[i] * model.loadings[j] - gain[i] * gain[j] * f)
+                    .collect()
+            })
+            .collect();
+    }
+    Some((scaled, log_det, a))
+}
+
+/// Parameters of an ARMA fit on the differenced series
+#[derive(Clone)]
+struct Params {
+    mean: f64,
+    ar: Vec<f64>,
+    ma: Vec<f64>,
+}
+
+impl Params {
+    fn unpack(x: &[f64], p: usize, constant: bool) -> Self {
+        let (mean, rest) = if constant { (x[0], &x[1..]) } else { (0.0, x) };
+        Params { mean, ar: rest[..p].to_vec(), ma: rest[p..].to_vec() }
+    }
+
+    fn pack(&self, constant: bool) -> Vec<f64> {
+        let mut x = if constant { vec![self.mean] } else { Vec::new() };
+        x.extend(&self.ar);
+        x.extend(&self.ma);
+        x
+    }
+
+    /// Stationary AR and invertible MA
+    fn admissible(&self) -> bool {
+        let negated_ma: Vec<f64> = self.ma.iter().map(|t| -t).collect();
+        is_stationary(&self.ar) && is_stationary(&negated_ma)
+    }
+}
+
+/// Conditional residuals for `t >= p`, with earlier residuals taken as zero
+fn css_residuals(w: &[f64], params: &Params) -> Vec<f64> {
+    let (p, q) = (params.ar.len(), params.ma.len());
+    let mut e = vec![0.0; w.len()];
+    for t in p..w.len() {
+        let ar: f64 = (1..=p).map(|i| params.ar[i - 1] * (w[t - i] - params.mean)).sum();
+        let ma: f64 = (1..=q.min(t)).map(|j| params.ma[j - 1] * e[t - j]).sum();
+        e[t] = w[t] - params.mean - ar - ma;
+    }
+    e.split_off(p)
+}
+
+/// Sum of squared conditional residuals, infinite for inadmissible parameters
+fn css_objective(w: &[f64], params: &Params) -> f64 {
+    if !params.admissible() {
+        return f64::INFINITY;
+    }
+    css_residuals(w, params).iter().map(|e| e * e).sum()
+}
+
+/// Exact log-likelihood with the shock variance concentrated out, and that variance
+fn exact_likelihood(w: &[f64], params: &Params) -> Option<(f64, f64)> {
+    if !params.admissible() {
+        return None;
+    }
+    let demeaned: Vec<f64> = w.iter().map(|v| v - params.mean).collect();
+    let (scaled, log_det, _) = kalman(&demeaned, &StateSpace::new(&params.ar, &params.ma))?;
+    let n = w.len() as f64;
+    let sigma2 = scaled.iter().map(|v| v * v).sum::<f64>() / n;
+    Some((-0.5 * n * ((2.0 * std::f64::consts::PI * sigma2).ln() + 1.0) - 0.5 * log_det, sigma2))
+}
+
+/// A fitted ARIMA(p, d, q) model, as returned by `arima_fit_rust`
+#[pyclass(module = "fast_math")]
+pub struct ArimaModel {
+    order: (usize, usize, usize),
+    params: Params,
+    constant: bool,
+    sigma2: f64,
+    log_likelihood: f64,
+    /// Last value of the series at each order of differencing, lowest order first
+    tails: Vec<f64>,
+    /// Demeaned differenced series, which the forecasts continue
+    history: Vec<f64>,
+}
+
+impl ArimaModel {
+    /// Forecasts of the differenced series, from the Kalman filter's state after the last value
+    fn forecast_differenced(&self, horizon: usize) -> Vec<f64> {
+        let model = StateSpace::new(&self.params.ar, &self.params.ma);
+        let state = kalman(&self.history, &model).map(|(_, _, a)| a);
+        let Some(mut state) = state else {
+            return vec![f64::NAN; horizon];
+        };
+        (0..horizon)
+            .map(|_| {
+                let value = state[0] + self.params.mean;
+                state = model.transition(&state);
+                value
+            })
+            .collect()
+    }
+}
+
+#[pymethods]
+impl ArimaModel {
+    /// `(p, d, q)`
+    #[getter]
+    fn order(&self) -> (usize, usize, usize) {
+        self.order
+    }
+
+    /// AR coefficients `phi_1..phi_p`
+    #[getter]
+    fn ar<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice_bound(py, &self.params.ar)
+    }
+
+    /// MA coefficients `theta_1..theta_q`
+    #[getter]
+    fn ma<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice_bound(py, &self.params.ma)
+    }
+
+    /// Mean of the differenced series, 0 when the model has no constant
+    #[getter]
+    fn mean(&self) -> f64 {
+        self.params.mean
+    }
+
+    /// Variance of the shocks
+    #[getter]
+    fn sigma2(&self) -> f64 {
+        self.sigma2
+    }
+
+    #[getter]
+    fn log_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    /// Akaike information criterion, counting the shock variance as a parameter
+    #[getter]
+    fn aic(&self) -> f64 {
+        let k = self.params.pack(self.constant).len() + 1;
+        2.0 * k as f64 - 2.0 * self.log_likelihood
+    }
+
+    /// Point forecasts of the next `h` values of the original (undifferenced) series
+    fn forecast<'py>(&self, py: Python<'py>, h: usize) -> Bound<'py, PyArray1<f64>> {
+        let mut forecasts = self.forecast_differenced(h);
+        // Integrate back up one order of differencing at a time
+        for &last in self.tails.iter().rev() {
+            let mut level = last;
+            for f in forecasts.iter_mut() {
+                level += *f;
+                *f = level;
+            }
+        }
+        PyArray1::from_vec_bound(py, forecasts)
+    }
+
+    fn __repr__(&self) -> String {
+        let (p, d, q) = self.order;
+        format!("ArimaModel(order=({}, {}, {}), sigma2={}, log_likelihood={})", p, d, q, self.sigma2, self.log_likelihood)
+    }
+}
+
+/// Fit an ARIMA(p, d, q) model, like statsmodels' `ARIMA(data, order=(p, d, q)).fit()`.
+///
+/// The series is differenced `d` times and an ARMA(p, q) fitted to the result, with a
+/// mean when `constant` is true (default: only when `d == 0`). `method="css"` minimises
+/// the conditional sum of squares, `"mle"` maximises the exact Gaussian likelihood via a
+/// Kalman filter and `"css-mle"` (the default) runs the MLE from the CSS estimates; both
+/// are searched by Nelder-Mead (up to `max_iter` iterations) over stationary AR and
+/// invertible MA coefficients. Returns an `ArimaModel` with the coefficients, `sigma2`,
+/// `log_likelihood` and `aic`, whose `forecast(h)` gives the next `h` values in the
+/// original units. NaN values are rejected; the differenced series must be longer than
+/// the number of parameters.
+#[pyfunction]
+#[pyo3(signature = (data, p, d, q, constant=None, method="css-mle", max_iter=5000))]
+#[allow(clippy::too_many_arguments)]
+pub fn arima_fit_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    p: usize,
+    d: usize,
+    q: usize,
+    constant: Option<bool>,
+    method: &str,
+    max_iter: usize
+) -> PyResult<ArimaModel> {
+    let method = Method::parse(method)?;
+    let constant = constant.unwrap_or(d == 0);
+    let mut w = data.values()?.into_owned();
+    if w.iter().any(|v| v.is_nan()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "ARIMA input contains NaN"
+        ));
+    }
+    let mut tails = Vec::with_capacity(d);
+    for _ in 0..d {
+        tails.push(w.last().copied().unwrap_or(f64::NAN));
+        w = w.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    }
+    let k = p + q + constant as usize;
+    if w.len() <= k + p {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} differenced values are too few for an ARIMA({}, {}, {})", w.len(), p, d, q
+        )));
+    }
+
+    Ok(py.allow_threads(|| {
+        let initial = Params {
+            mean: if constant { w.iter().sum::<f64>() / w.len() as f64 } else { 0.0 },
+            ar: vec![0.0; p],
+            ma: vec![0.0; q],
+        };
+        let mut step = vec![0.1; k];
+        if constant {
+            let var = w.iter().map(|v| (v - initial.mean).powi(2)).sum::<f64>() / w.len() as f64;
+            step[0] = var.sqrt().max(1e-8) / 10.0;
+        }
+        let search = |objective: &dyn Fn(&[f64]) -> f64, start: &Params| -> Params {
+            if k == 0 {
+                return start.clone();
+            }
+            let (x, _) = optimize::nelder_mead(objective, &start.pack(constant), &step, 1e-10, max_iter);
+            let (x, _) = optimize::nelder_mead(objective, &x, &step, 1e-12, max_iter);
+            Params::unpack(&x, p, constant)
+        };
+        let css = |x: &[f64]| css_objective(&w, &Params::unpack(x, p, constant));
+        let mle = |x: &[f64]| exact_likelihood(&w, &Params::unpack(x, p, constant)).map_or(f64::INFINITY, |(ll, _)| -ll);
+
+        let params = match method {
+            Method::Css => search(&css, &initial),
+            Method::Mle => search(&mle, &initial),
+            Method::CssMle => search(&mle, &search(&css, &initial)),
+        };
+        let (log_likelihood, sigma2) = match method {
+            Method::Css => {
+                let ssr = css_objective(&w, &params);
+                let n = (w.len() - p) as f64;
+                let sigma2 = ssr / n;
+                (-0.5 * n * ((2.0 * std::f64::consts::PI * sigma2).ln() + 1.0), sigma2)
+            }
+            _ => exact_likelihood(&w, &params).unwrap_or((f64::NAN, f64::NAN)),
+        };
+        let history = w.iter().map(|v| v - params.mean).collect();
+        ArimaModel { order: (p, d, q), params, constant, sigma2, log_likelihood, tails, history }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::Float64Array;
+    use crate::rng::SplitMix;
+
+    fn arma(n: usize, ar: f64, ma: f64) -> Vec<f64> {
+        let mut rng = SplitMix::new(9, 0);
+        let (mut y, mut e_prev) = (0.0, 0.0);
+        (0..n)
+            .map(|_| {
+                let e = rng.normal_pair().0;
+                y = ar * y + e + ma * e_prev;
+                e_prev = e;
+                y
+            })
+            .collect()
+    }
+
+    fn fit(values: Vec<f64>, order: (usize, usize, usize), method: &str) -> ArimaModel {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let data = Column::Arrow(Float64Array::from(values));
+            arima_fit_rust(py, data, order.0, order.1, order.2, None, method, 5000).unwrap()
+        })
+    }
+
+    #[test]
+    fn fits_recover_ar_and_ma_coefficients() {
+        let model = fit(arma(3000, 0.6, 0.0), (1, 0, 0), "css-mle");
+        assert!((model.params.ar[0] - 0.6).abs() < 0.05, "{}", model.params.ar[0]);
+        assert!((model.sigma2 - 1.0).abs() < 0.1);
+        assert!(model.params.mean.abs() < 0.15);
+
+        let model = fit(arma(3000, 0.0, 0.4), (0, 0, 1), "mle");
+        assert!((model.params.ma[0] - 0.4).abs() < 0.05, "{}", model.params.ma[0]);
+
+        // A random walk differenced once is white noise
+        let walk: Vec<f64> = arma(500, 0.0, 0.0).iter().scan(10.0, |y, e| { *y += e; Some(*y) }).collect();
+        let model = fit(walk.clone(), (0, 1, 0), "css");
+        assert_eq!(model.tails, vec![*walk.last().unwrap()]);
+        assert!(!model.constant);
+    }
+
+    #[test]
+    fn ar_forecasts_decay_geometrically() {
+        let model = fit(arma(2000, 0.5, 0.0), (1, 0, 0), "css");
+        let phi = model.params.ar[0];
+        let last = *model.history.last().unwrap();
+        let forecasts = model.forecast_differenced(3);
+        for (h, f) in forecasts.iter().enumerate() {
+            let expected = model.params.mean + phi.powi(h as i32 + 1) * last;
+            assert!((f - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn white_noise_likelihood_is_closed_form() {
+        let w = arma(200, 0.0, 0.0);
+        let params = Params { mean: 0.0, ar: Vec::new(), ma: Vec::new() };
+        let (ll, sigma2) = exact_likelihood(&w, &params).unwrap();
+        let expected_sigma2 = w.iter().map(|v| v * v).sum::<f64>() / 200.0;
+        assert!((sigma2 - expected_sigma2).abs() < 1e-12);
+        assert!((ll + 100.0 * ((2.0 * std::f64::consts::PI * expected_sigma2).ln() + 1.0)).abs() < 1e-9);
+        assert!(is_stationary(&[0.5, 0.3]));
+        assert!(!is_stationary(&[0.5, 0.6]));
+        assert!(!is_stationary(&[1.0]));
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, HashMap};
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use numpy::PyArray1;
+use chrono::{NaiveDate, TimeZone};
+use chrono_tz::Tz;
+
+use crate::column::{Column, NumericColumn};
+use crate::timezone::parse_tz;
+
+/// The fills and the group label columns read from them
+struct Fills {
+    timestamps: Vec<i64>,
+    symbols: Vec<String>,
+    /// Signed quantities, positive for buys
+    quantities: Vec<f64>,
+    prices: Vec<f64>,
+    fees: Option<Vec<f64>>,
+    /// One column per group key other than "symbol" and "day"
+    labels: Vec<Vec<String>>,
+}
+
+impl Fills {
+    fn parse(fills: &Bound<'_, PyAny>, label_keys: &[&str]) -> PyResult<Self> {
+        let py = fills.py();
+        let item = |name: &str| -> PyResult<Option<Bound<'_, PyAny>>> {
+            match fills.get_item(name) {
+                Ok(values) => Ok(Some(values)),
+                Err(err) if err.is_instance_of::<pyo3::exceptions::PyKeyError>(py) => Ok(None),
+                Err(err) => Err(err),
+            }
+        };
+        let required = |name: &str| {
+            item(name)?.ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!("fills needs a '{}' column", name))
+            })
+        };
+        let floats = |values: Bound<'_, PyAny>| -> PyResult<Vec<f64>> {
+            Ok(match values.extract::<NumericColumn>()? {
+                NumericColumn::Float(column) => column.values()?.into_owned(),
+                NumericColumn::Int(column) => column.values()?.iter().map(|&v| v as f64).collect(),
+            })
+        };
+        // Lists and object arrays extract directly; Series and the like go through tolist()
+        let strings = |values: Bound<'_, PyAny>| -> PyResult<Vec<String>> {
+            match values.extract::<Vec<String>>() {
+                Ok(values) => Ok(values),
+                Err(_) if values.hasattr("tolist")? => values.call_method0("tolist")?.extract(),
+                Err(err) => Err(err),
+            }
+        };
+
+        let parsed = Fills {
+            timestamps: required("timestamp")?.extract::<Column<i64>>()?.values()?.into_owned(),
+            symbols: strings(required("symbol")?)?,
+            quantities: floats(required("qty")?)?,
+            prices: floats(required("price")?)?,
+            fees: item("fee")?.map(floats).transpose()?,
+            labels: label_keys.iter().map(|&key| strings(required(key)?)).collect::<PyResult<_>>()?,
+        };
+        let n = parsed.timestamps.len();
+        if parsed.symbols.len() != n
+            || parsed.quantities.len() != n
+            || parsed.prices.len() != n
+            || parsed.fees.as_ref().is_some_and(|f| f.len() != n)
+            || parsed.labels.iter().any(|l| l.len() != n)
+        {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "fills columns must have the same length"
+            ));
+        }
+        if parsed.quantities.iter().chain(&parsed.prices).any(|v| !v.is_finite()) {
+            return Err(crate::errors::NaNInputError::new_err(
+                "fills qty and price must be finite"
+            ));
+        }
+        Ok(parsed)
+    }
+
+    /// The fee of fill `i`; NaN or a missing column count as none
+    fn fee(&self, i: usize) -> f64 {
+        self.fees.as_ref().map_or(0.0, |f| if f[i].is_nan() { 0.0 } else { f[i] })
+    }
+}
+
+/// One symbol's mark prices, with the local date of each
+struct Marks {
+    dates: Vec<NaiveDate>,
+    prices: Vec<f64>,
+}
+
+impl Marks {
+    /// Parse a `(timestamps, prices)` tuple or a mapping with `timestamp` and `price` columns
+    fn parse(symbol: &str, data: &Bound<'_, PyAny>, tz: &Tz) -> PyResult<Self> {
+        let (timestamps, prices): (Column<'_, i64>, Column<'_, f64>) = match data.downcast::<PyTuple>() {
+            Ok(pair) => pair.extract()?,
+            Err(_) => {
+                let required = |name: &str| {
+                    data.get_item(name).map_err(|_| {
+                        pyo3::exceptions::PyKeyError::new_err(format!(
+                            "mark prices of '{}' need a '{}' column", symbol, name
+                        ))
+                    })
+                };
+                (required("timestamp")?.extract()?, required("price")?.extract()?)
+            }
+        };
+        let (timestamps, prices) = (timestamps.values()?, prices.values()?);
+        if timestamps.len() != prices.len() {
+            return Err(crate::errors::LengthMismatchError::new_err(format!(
+                "mark prices of '{}' must have one price per timestamp", symbol
+            )));
+        }
+        if timestamps.windows(2).any(|w| w[1] < w[0]) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "mark timestamps of '{}' must be sorted ascending", symbol
+            )));
+        }
+        Ok(Marks { dates: timestamps.iter().map(|&t| local_date(tz, t)).collect(), prices: prices.into_owned() })
+    }
+}
+
+fn local_date(tz: &Tz, timestamp: i64) -> NaiveDate {
+    tz.timestamp_nanos(timestamp).date_naive()
+}
+
+/// P&L and activity of one group
+#[derive(Default, Clone, Copy)]
+struct Totals {
+    pnl: f64,
+    fees: f64,
+    /// Gross quantity and notional traded
+    quantity: f64,
+    notional: f64,
+    trades: i64,
+}
+
+/// Market value of a position, 0 when flat whatever the price
+fn value(position: f64, price: f64) -> f64 {
+    if position == 0.0 { 0.0 } else { position * price }
+}
+
+/// One position's P&L on one day: its group labels, symbol and local date
+type Row<'a> = (Vec<&'a str>, &'a str, NaiveDate, Totals);
+
+/// Walk each position (a group's labels and a symbol) through the valued days, from
+/// its first fill until it is flat with no fills left
+fn daily_rows<'a>(fills: &'a Fills, marks: &HashMap<&str, Marks>, tz: &Tz) -> Vec<Row<'a>> {
+    let mut order: Vec<usize> = (0..fills.timestamps.len()).collect();
+    order.sort_by_key(|&i| fills.timestamps[i]);
+    let dates: Vec<NaiveDate> = fills.timestamps.iter().map(|&t| local_date(tz, t)).collect();
+    // The days valued: every day with a fill or a mark from the first fill on
+    let mut days: Vec<NaiveDate> = dates.clone();
+    if let Some(&first) = dates.iter().min() {
+        days.extend(marks.values().flat_map(|m| m.dates.iter().copied()).filter(|&d| d >= first));
+    }
+    days.sort_unstable();
+    days.dedup();
+
+    // Fills of each position, a group's labels and a symbol, in time order
+    let mut positions: BTreeMap<(Vec<&str>, &str), Vec<usize>> = BTreeMap::new();
+    let mut by_symbol: HashMap<&str, Vec<usize>> = HashMap::new();
+    for &i in &order {
+        let labels = fills.labels.iter().map(|l| l[i].as_str()).collect();
+        positions.entry((labels, fills.symbols[i].as_str())).or_default().push(i);
+        by_symbol.entry(fills.symbols[i].as_str()).or_default().push(i);
+    }
+    // The symbol's valuation price at the end of `day`
+    let price_at = |symbol: &str, day: NaiveDate| -> f64 {
+        if let Some(marks) = marks.get(symbol) {
+            let k = marks.dates.partition_point(|&d| d <= day);
+            if k > 0 {
+                return marks.prices[k - 1];
+            }
+        }
+        let traded = &by_symbol[symbol];
+        let k = traded.partition_point(|&i| dates[i] <= day);
+        if k > 0 { fills.prices[traded[k - 1]] } else { f64::NAN }
+    };
+
+    let mut rows: Vec<Row<'a>> = Vec::new();
+    for ((labels, symbol), traded) in &positions {
+        let (mut position, mut held_value) = (0.0, 0.0);
+        let mut next = 0;
+        for &day in &days[days.partition_point(|&d| d < dates[traded[0]])..] {
+            if position == 0.0 && next == traded.len() {
+                break;
+            }
+            let held = position != 0.0;
+            let mut totals = Totals::default();
+            let mut cash = 0.0;
+            while next < traded.len() && dates[traded[next]] == day {
+                let i = traded[next];
+                let (q, p) = (fills.quantities[i], fills.prices[i]);
+                position += q;
+                cash += q * p;
+                totals.fees += fills.fee(i);
+                totals.quantity += q.abs();
+                totals.notional += (q * p).abs();
+                totals.trades += 1;
+                next += 1;
+            }
+            if !held && totals.trades == 0 {
+                continue;
+            }
+            let end_value = value(position, price_at(symbol, day));
+            totals.pnl = end_value - held_value - cash - totals.fees;
+            held_value = end_value;
+            rows.push((labels.clone(), *symbol, day, totals));
+        }
+    }
+    rows
+}
+
+/// Attribute P&L from a trade ledger to strategies, symbols and days.
+///
+/// `fills` is a mapping (a dict of columns or a DataFrame) of `timestamp` (UTC
+/// nanoseconds), `symbol`, signed `qty` (positive buys) and `price`, with an optional
+/// `fee`, as for the blotter, plus a column for each of `group_keys` other than "symbol"
+/// and "day", such as "strategy" or "account". `mark_prices` maps symbols to their marks,
+/// a `(timestamps, prices)` tuple or a mapping with `timestamp` and `price` columns.
+/// Positions are kept per symbol within each group and valued at the end of every
+/// local day in `tz` at the last mark of that day or before, or the last fill price
+/// until the symbol is marked. Each day's P&L is the change in market value less the
+/// cash paid for the day's fills and their fees, so open positions keep earning on
+/// days without trades. `group_keys` lists the table's keys, any of "symbol", "day"
+/// (the local date as "YYYY-MM-DD") and fill columns; P&L is summed over whatever is
+/// left out. Returns a dict with one list per key and arrays of `pnl`, `fees`, gross
+/// traded `quantity` and `notional`, and the number of `trades`, one row per group in
+/// key order, every day a group held a position or traded included.
+#[pyfunction]
+#[pyo3(signature = (fills, mark_prices, group_keys, tz="UTC"))]
+pub fn attribute_pnl_rust<'py>(
+    py: Python<'py>,
+    fills: &Bound<'py, PyAny>,
+    mark_prices: Option<Bound<'py, PyDict>>,
+    group_keys: Vec<String>,
+    tz: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let tz = parse_tz(tz)?;
+    for (i, key) in group_keys.iter().enumerate() {
+        if ["pnl", "fees", "quantity", "notional", "trades"].contains(&key.as_str()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Group key '{}' clashes with an output column", key
+            )));
+        }
+        if group_keys[..i].contains(key) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Duplicate group key '{}'", key
+            )));
+        }
+    }
+    let label_keys: Vec<&str> =
+        group_keys.iter().map(String::as_str).filter(|k| *k != "symbol" && *k != "day").collect();
+    let fills = Fills::parse(fills, &label_keys)?;
+    let mut marks: HashMap<&str, Marks> = HashMap::new();
+    for symbol in &fills.symbols {
+        if marks.contains_key(symbol.as_str()) {
+            continue;
+        }
+        if let Some(data) = mark_prices.as_ref().map(|m| m.get_item(symbol)).transpose()?.flatten() {
+            marks.insert(symbol, Marks::parse(symbol, &data, &tz)?);
+        }
+    }
+
+    let rows = py.allow_threads(|| daily_rows(&fills, &marks, &tz));
+
+    let mut groups: BTreeMap<Vec<String>, Totals> = BTreeMap::new();
+    for (labels, symbol, day, totals) in rows {
+        let mut labels = labels.into_iter();
+        let key: Vec<String> = group_keys
+            .iter()
+            .map(|k| match k.as_str() {
+                "symbol" => symbol.to_string(),
+                "day" => day.format("%Y-%m-%d").to_string(),
+                _ => labels.next().unwrap_or_default().to_string(),
+            })
+            .collect();
+        let group = groups.entry(key).or_default();
+        group.pnl += totals.pnl;
+        group.fees += totals.fees;
+        group.quantity += totals.quantity;
+        group.notional += totals.notional;
+        group.trades += totals.trades;
+    }
+
+    let result = PyDict::new_bound(py);
+    for (j, key) in group_keys.iter().enumerate() {
+        result.set_item(key, groups.keys().map(|k| k[j].as_str()).collect::<Vec<_>>())?;
+    }
+    result.set_item("pnl", PyArray1::from_iter_bound(py, groups.values().map(|t| t.pnl)))?;
+    result.set_item("fees", PyArray1::from_iter_bound(py, groups.values().map(|t| t.fees)))?;
+    result.set_item("quantity", PyArray1::from_iter_bound(py, groups.values().map(|t| t.quantity)))?;
+    result.set_item("notional", PyArray1::from_iter_bound(py, groups.values().map(|t| t.notional)))?;
+    result.set_item("trades", PyArray1::from_iter_bound(py, groups.values().map(|t| t.trades)))?;
+    Ok(result)
+}
@@ -0,0 +1,320 @@
+use pyo3::prelude::*;
+use numpy::{PyArray1, PyArray2};
+use statrs::distribution::{ChiSquared, ContinuousCDF};
+
+use crate::column::{self, Column, Series};
+use crate::{fft, linalg};
+
+/// How partial autocorrelations are computed from the autocovariances
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PacfMethod {
+    /// Yule-Walker equations solved at each lag, autocovariances over `n - k`
+    YuleWalker,
+    /// Yule-Walker with the biased (`n`) autocovariances
+    YuleWalkerMle,
+    /// Durbin-Levinson recursion, autocovariances over `n - k`
+    Levinson,
+    /// Durbin-Levinson with the biased autocovariances
+    LevinsonBiased,
+}
+
+impl PacfMethod {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "yw" | "ywadjusted" => Ok(PacfMethod::YuleWalker),
+            "ywm" | "ywmle" => Ok(PacfMethod::YuleWalkerMle),
+            "ld" | "ldadjusted" => Ok(PacfMethod::Levinson),
+            "ldb" | "ldbiased" => Ok(PacfMethod::LevinsonBiased),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'yw', 'ywm', 'ld' or 'ldb'", name
+            ))),
+        }
+    }
+
+    fn adjusted(self) -> bool {
+        matches!(self, PacfMethod::YuleWalker | PacfMethod::Levinson)
+    }
+}
+
+/// Autocovariances of lags `0..=n_lags` about the mean, divided by `n - k` when
+/// `adjusted` and by `n` otherwise; NaNs propagate to every lag
+fn autocovariance(values: &[f64], n_lags: usize, adjusted: bool, use_fft: bool) -> Vec<f64> {
+    let n = values.len();
+    if values.iter().any(|v| v.is_nan()) {
+        return vec![f64::NAN; n_lags + 1];
+    }
+    let mean = values.iter().sum::<f64>() / n as f64;
+    let x: Vec<f64> = values.iter().map(|v| v - mean).collect();
+    let sums = if use_fft {
+        fft::autocorrelation_sums(&x, n_lags)
+    } else {
+        (0..=n_lags).map(|k| x[..n - k].iter().zip(&x[k..]).map(|(a, b)| a * b).sum()).collect()
+    };
+    sums.iter()
+        .enumerate()
+        .map(|(k, s)| s / if adjusted { (n - k) as f64 } else { n as f64 })
+        .collect()
+}
+
+fn acf(values: &[f64], n_lags: usize, adjusted: bool, use_fft: bool) -> Vec<f64> {
+    let acov = autocovariance(values, n_lags, adjusted, use_fft);
+    acov.iter().map(|c| c / acov[0]).collect()
+}
+
+/// Partial autocorrelations by solving the order-`k` Yule-Walker system for every `k`
+fn pacf_yule_walker(acov: &[f64]) -> Vec<f64> {
+    let mut pacf = vec![1.0];
+    for k in 1..acov.len() {
+        let toeplitz: Vec<Vec<f64>> = (0..k).map(|i| (0..k).map(|j| acov[i.abs_diff(j)]).collect()).collect();
+        let phi = linalg::solve(toeplitz, acov[1..=k].to_vec());
+        pacf.push(phi.map_or(f64::NAN, |phi| phi[k - 1]));
+    }
+    pacf
+}
+
+/// Partial autocorrelations by the Durbin-Levinson recursion
+fn pacf_levinson(acov: &[f64]) -> Vec<f64> {
+    let mut pacf = vec![1.0];
+    let (mut phi, mut variance): (Vec<f64>, f64) = (Vec::new(), acov[0]);
+    for k in 1..acov.len() {
+        let rest: f64 = phi.iter().enumerate().map(|(j, p)| p * acov[k - 1 - j]).sum();
+        let kappa = (acov[k] - rest) / variance;
+        phi = phi
+            .iter()
+            .zip(phi.iter().rev())
+            .map(|(p, q)| p - kappa * q)
+            .chain(std::iter::once(kappa))
+            .collect();
+        variance *= 1.0 - kappa * kappa;
+        pacf.push(kappa);
+    }
+    pacf
+}
+
+fn pacf(values: &[f64], n_lags: usize, method: PacfMethod, use_fft: bool) -> Vec<f64> {
+    let acov = autocovariance(values, n_lags, method.adjusted(), use_fft);
+    match method {
+        PacfMethod::YuleWalker | PacfMethod::YuleWalkerMle => pacf_yule_walker(&acov),
+        PacfMethod::Levinson | PacfMethod::LevinsonBiased => pacf_levinson(&acov),
+    }
+}
+
+/// `n_lags`, or statsmodels' default `min(10 * log10(n), limit)`; at most `limit`
+fn lags_for(len: usize, n_lags: Option<usize>, limit: usize) -> PyResult<usize> {
+    match n_lags {
+        Some(lags) if lags > limit => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "n_lags must be at most {} for {} values", limit, len
+        ))),
+        Some(lags) => Ok(lags),
+        None => Ok(((10.0 * (len as f64).log10()) as usize).min(limit)),
+    }
+}
+
+/// Apply a per-series lag profile to a single series or every row of a batch
+fn map_lags<'py, F>(py: Python<'py>, data: Series<'py, f64>, n_lags: Option<usize>, limit: fn(usize) -> usize, f: F) -> PyResult<PyObject>
+where
+    F: Fn(&[f64], usize) -> Vec<f64> + Sync,
+{
+    match data {
+        Series::Single(column) => {
+            let values = column.values()?;
+            let lags = lags_for(values.len(), n_lags, limit(values.len()))?;
+            let result = py.allow_threads(|| f(&values, lags));
+            Ok(PyArray1::from_vec_bound(py, result).into_any().unbind())
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            let lags = lags_for(batch.ncols(), n_lags, limit(batch.ncols()))?;
+            let rows = py.allow_threads(|| column::map_rows(batch, |_, row| Ok(f(row, lags))))?;
+            Ok(PyArray2::from_owned_array_bound(py, column::stack_rows(rows)?).into_any().unbind())
+        }
+    }
+}
+
+/// Autocorrelation function of `data` for lags `0..=n_lags`, like statsmodels' `acf`.
+///
+/// Autocovariances are taken about the mean and divided by `n` (or by `n - k` with
+/// `adjusted=True`), then normalised by the lag-0 value; `n_lags` defaults to
+/// `min(10 * log10(n), n - 1)`. `fft=True` computes them from the power spectrum in
+/// O(n log n) instead of O(n * n_lags), for long series and many lags. A 2D array is
+/// processed row by row in parallel, returning one row of lags per series. NaNs (and
+/// constant series) give NaN.
+#[pyfunction]
+#[pyo3(signature = (data, n_lags=None, adjusted=false, fft=false))]
+pub fn acf_rust<'py>(
+    py: Python<'py>,
+    data: Series<'py, f64>,
+    n_lags: Option<usize>,
+    adjusted: bool,
+    fft: bool
+) -> PyResult<PyObject> {
+    map_lags(py, data, n_lags, |len| len.saturating_sub(1), |values, lags| acf(values, lags, adjusted, fft))
+}
+
+/// Partial autocorrelation function of `data` for lags `0..=n_lags`, like statsmodels'
+/// `pacf`.
+///
+/// `method="yw"` (the default) solves the Yule-Walker equations of each order with
+/// autocovariances over `n - k`, "ywm" with autocovariances over `n`; "ld" and "ldb" give
+/// the same two by the O(n_lags²) Durbin-Levinson recursion. `n_lags` defaults to
+/// `min(10 * log10(n), n // 2 - 1)` and may be at most `n // 2 - 1`; `fft=True` computes the
+/// autocovariances by FFT as in `acf_rust`. A 2D array is processed row by row, returning
+/// one row of lags per series. NaNs give NaN.
+#[pyfunction]
+#[pyo3(signature = (data, n_lags=None, method="yw", fft=false))]
+pub fn pacf_rust<'py>(
+    py: Python<'py>,
+    data: Series<'py, f64>,
+    n_lags: Option<usize>,
+    method: &str,
+    fft: bool
+) -> PyResult<PyObject> {
+    let method = PacfMethod::parse(method)?;
+    map_lags(py, data, n_lags, |len| (len / 2).saturating_sub(1), |values, lags| pacf(values, lags, method, fft))
+}
+
+/// Ljung-Box and Box-Pierce statistics of lags `1..=lags` with their chi-squared p-values
+fn portmanteau(values: &[f64], lags: usize, model_df: usize) -> [Vec<f64>; 4] {
+    let n = values.len() as f64;
+    let r = acf(values, lags, false, false);
+    let (mut ljung_box, mut box_pierce) = (0.0, 0.0);
+    let mut result: [Vec<f64>; 4] = Default::default();
+    for (k, rk) in r.iter().enumerate().skip(1) {
+        ljung_box += rk * rk / (n - k as f64);
+        box_pierce += rk * rk;
+        let (lb, bp) = (n * (n + 2.0) * ljung_box, n * box_pierce);
+        // The fitted model's parameters use up degrees of freedom; none left gives NaN
+        let p_value = |q: f64| {
+            if k > model_df { ChiSquared::new((k - model_df) as f64).unwrap().sf(q) } else { f64::NAN }
+        };
+        result[0].push(lb);
+        result[1].push(p_value(lb));
+        result[2].push(bp);
+        result[3].push(p_value(bp));
+    }
+    result
+}
+
+/// Ljung-Box and Box-Pierce tests for autocorrelation in `residuals`, like statsmodels'
+/// `acorr_ljungbox(residuals, lags, boxpierce=True, model_df=model_df)`.
+///
+/// For every lag `h` in `1..=lags` (default `min(10, n // 5)`), `Q_LB = n (n + 2) sum(r_k² /
+/// (n - k))` and `Q_BP = n sum(r_k²)` over the first `h` autocorrelations, with p-values
+/// from a chi-squared distribution on `h - model_df` degrees of freedom (pass the number
+/// of fitted ARMA coefficients as `model_df` when testing a model's residuals; lags
+/// without degrees of freedom left get NaN). Returns `(lb_stat, lb_pvalue, bp_stat,
+/// bp_pvalue)`, one value per lag; small p-values reject "no autocorrelation". NaNs give NaN.
+#[pyfunction]
+#[pyo3(signature = (residuals, lags=None, model_df=0))]
+pub fn ljung_box_rust<'py>(
+    py: Python<'py>,
+    residuals: Column<'py, f64>,
+    lags: Option<usize>,
+    model_df: usize
+) -> PyResult<PyObject> {
+    let values = residuals.values()?;
+    let n = values.len();
+    let lags = lags.unwrap_or_else(|| (n / 5).min(10));
+    if lags == 0 || lags >= n {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "lags must be between 1 and {} for {} values", n.saturating_sub(1), n
+        )));
+    }
+    let [lb, lb_p, bp, bp_p] = py.allow_threads(|| portmanteau(&values, lags, model_df));
+    Ok((
+        PyArray1::from_vec_bound(py, lb),
+        PyArray1::from_vec_bound(py, lb_p),
+        PyArray1::from_vec_bound(py, bp),
+        PyArray1::from_vec_bound(py, bp_p),
+    )
+        .into_py(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ar1(n: usize, phi: f64) -> Vec<f64> {
+        let mut rng = crate::rng::SplitMix::new(5, 0);
+        let mut x = vec![0.0; n];
+        for t in 1..n {
+            x[t] = phi * x[t - 1] + rng.normal_pair().0;
+        }
+        x
+    }
+
+    #[test]
+    fn acf_matches_statsmodels_on_a_ramp() {
+        // statsmodels.tsa.stattools.acf([1, 2, 3, 4, 5], nlags=2)
+        let r = acf(&[1.0, 2.0, 3.0, 4.0, 5.0], 2, false, false);
+        assert!(r.iter().zip([1.0, 0.4, -0.1]).all(|(a, b)| (a - b).abs() < 1e-12));
+        // adjusted=True divides lag k by n - k
+        let r = acf(&[1.0, 2.0, 3.0, 4.0, 5.0], 2, true, false);
+        assert!(r.iter().zip([1.0, 0.5, -1.0 / 6.0]).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+
+    #[test]
+    fn fft_autocovariances_match_the_direct_sums() {
+        let x = ar1(300, 0.6);
+        for adjusted in [false, true] {
+            let direct = autocovariance(&x, 40, adjusted, false);
+            let fast = autocovariance(&x, 40, adjusted, true);
+            assert!(direct.iter().zip(&fast).all(|(a, b)| (a - b).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn pacf_of_an_ar1_cuts_off_after_lag_1() {
+        let x = ar1(5000, 0.7);
+        let yw = pacf(&x, 5, PacfMethod::YuleWalker, false);
+        assert!((yw[1] - 0.7).abs() < 0.03);
+        assert!(yw[2..].iter().all(|p| p.abs() < 0.05));
+        // Durbin-Levinson solves the same equations
+        let ld = pacf(&x, 5, PacfMethod::Levinson, false);
+        assert!(yw.iter().zip(&ld).all(|(a, b)| (a - b).abs() < 1e-9));
+        let ywm = pacf(&x, 5, PacfMethod::YuleWalkerMle, false);
+        let ldb = pacf(&x, 5, PacfMethod::LevinsonBiased, false);
+        assert!(ywm.iter().zip(&ldb).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+
+    #[test]
+    fn nan_and_constant_series_give_nan() {
+        assert!(acf(&[1.0, f64::NAN, 2.0, 3.0], 2, false, false).iter().all(|v| v.is_nan()));
+        assert!(acf(&[2.0; 6], 2, false, false).iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn default_lags_follow_statsmodels() {
+        assert_eq!(lags_for(1000, None, 999).unwrap(), 30);
+        assert_eq!(lags_for(10, None, 4).unwrap(), 4);
+    }
+
+    #[test]
+    fn portmanteau_statistics_match_their_formulas() {
+        // r = [1, 0.4, -0.1] for the ramp 1..=5
+        let [lb, lb_p, bp, bp_p] = portmanteau(&[1.0, 2.0, 3.0, 4.0, 5.0], 2, 0);
+        assert!((lb[0] - 35.0 * 0.16 / 4.0).abs() < 1e-12);
+        assert!((lb[1] - 35.0 * (0.16 / 4.0 + 0.01 / 3.0)).abs() < 1e-12);
+        assert!((bp[0] - 0.8).abs() < 1e-12 && (bp[1] - 0.85).abs() < 1e-12);
+        // The chi-squared survival function on 2 degrees of freedom is exp(-q / 2)
+        assert!((lb_p[1] - (-lb[1] / 2.0).exp()).abs() < 1e-12);
+        assert!((bp_p[1] - (-bp[1] / 2.0).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn model_degrees_of_freedom_shift_the_p_values() {
+        let x = ar1(200, 0.0);
+        let [lb, lb_p, _, _] = portmanteau(&x, 3, 1);
+        assert!(lb_p[0].is_nan());
+        assert!((lb_p[2] - (-lb[2] / 2.0).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn autocorrelation_is_detected_and_white_noise_passes() {
+        let [_, noise, _, _] = portmanteau(&ar1(1000, 0.0), 10, 0);
+        assert!(noise[9] > 0.01);
+        let [_, correlated, _, _] = portmanteau(&ar1(1000, 0.5), 10, 0);
+        assert!(correlated.iter().all(|p| *p < 1e-6));
+    }
+}
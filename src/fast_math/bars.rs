@@ -0,0 +1,591 @@
+use pyo3::prelude::*;
+use num_traits::AsPrimitive;
+
+use crate::column::{ArrayKind, Column, NumericColumn};
+
+/// Timestamp, open, high, low, close and volume columns returned to Python
+pub type BarArrays = (PyObject, PyObject, PyObject, PyObject, PyObject, PyObject);
+
+/// Brick open, close, direction and completing index returned by `renko_rust`
+pub type RenkoArrays = (PyObject, PyObject, PyObject, PyObject);
+
+/// Brick open, close, direction and completing index, before conversion
+type Bricks = (Vec<f64>, Vec<f64>, Vec<i8>, Vec<i64>);
+
+/// Open, high, low, close and closing index returned by `range_bars_rust`
+pub type RangeBarArrays = (PyObject, PyObject, PyObject, PyObject, PyObject);
+
+/// Bar open, high, low, close and closing index, before conversion
+type RangeBars = (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>, Vec<i64>);
+
+/// Column-oriented OHLCV bars, one entry per completed bar
+#[derive(Default)]
+pub struct Bars {
+    pub timestamps: Vec<i64>,
+    pub open: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub close: Vec<f64>,
+    pub volume: Vec<f64>,
+}
+
+impl Bars {
+    pub fn push(&mut self, timestamp: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) {
+        self.timestamps.push(timestamp);
+        self.open.push(open);
+        self.high.push(high);
+        self.low.push(low);
+        self.close.push(close);
+        self.volume.push(volume);
+    }
+
+    pub fn into_columns(self, py: Python<'_>, kind: ArrayKind) -> PyResult<BarArrays> {
+        Ok((
+            kind.wrap(py, self.timestamps)?,
+            kind.wrap(py, self.open)?,
+            kind.wrap(py, self.high)?,
+            kind.wrap(py, self.low)?,
+            kind.wrap(py, self.close)?,
+            kind.wrap(py, self.volume)?,
+        ))
+    }
+}
+
+/// Accumulate ticks into bars, closing a bar once the running `measure` reaches `threshold`.
+///
+/// The tick that crosses the threshold closes the bar; a trailing incomplete bar is dropped.
+/// Ticks with a NaN price, volume or measure are skipped, since they would keep the
+/// running measure from ever reaching the threshold.
+fn accumulate_bars<V, F>(timestamps: &[i64], prices: &[f64], volumes: &[V], threshold: f64, measure: F) -> Bars
+where
+    V: AsPrimitive<f64>,
+    F: Fn(f64, f64) -> f64,
+{
+    let mut bars = Bars::default();
+    let mut open = f64::NAN;
+    let mut high = f64::NEG_INFINITY;
+    let mut low = f64::INFINITY;
+    let mut volume = 0.0;
+    let mut accumulated = 0.0;
+    let mut in_bar = false;
+
+    for i in 0..prices.len() {
+        let price = prices[i];
+        let tick_volume: f64 = volumes[i].as_();
+        let amount = measure(price, tick_volume);
+        if price.is_nan() || tick_volume.is_nan() || amount.is_nan() {
+            continue;
+        }
+        if !in_bar {
+            open = price;
+            high = price;
+            low = price;
+            volume = 0.0;
+            accumulated = 0.0;
+            in_bar = true;
+        }
+        high = high.max(price);
+        low = low.min(price);
+        volume += tick_volume;
+        accumulated += amount;
+
+        if accumulated >= threshold {
+            bars.push(timestamps[i], open, high, low, price, volume);
+            in_bar = false;
+        }
+    }
+
+    bars
+}
+
+fn check_tick_inputs(timestamps: &[i64], prices: &[f64], n_volumes: usize, threshold: f64) -> PyResult<()> {
+    if timestamps.len() != prices.len() || prices.len() != n_volumes {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps, prices and volumes must have the same length"
+        ));
+    }
+    if threshold.is_nan() || threshold <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Bar threshold must be > 0"
+        ));
+    }
+    Ok(())
+}
+
+/// Validate tick columns and accumulate them into bars; volumes may be float64 or int64
+fn build_bars<'py, F>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    volumes: NumericColumn<'py>,
+    threshold: f64,
+    measure: F
+) -> PyResult<BarArrays>
+where
+    F: Fn(f64, f64) -> f64 + Send,
+{
+    let kind = ArrayKind::of(&[timestamps.kind(), prices.kind(), volumes.kind()]);
+    let (timestamps, prices) = (timestamps.values()?, prices.values()?);
+    let bars = match &volumes {
+        NumericColumn::Float(volumes) => {
+            let volumes = volumes.values()?;
+            check_tick_inputs(&timestamps, &prices, volumes.len(), threshold)?;
+            py.allow_threads(|| accumulate_bars(&timestamps, &prices, &volumes, threshold, measure))
+        }
+        NumericColumn::Int(volumes) => {
+            let volumes = volumes.values()?;
+            check_tick_inputs(&timestamps, &prices, volumes.len(), threshold)?;
+            py.allow_threads(|| accumulate_bars(&timestamps, &prices, &volumes, threshold, measure))
+        }
+    };
+    bars.into_columns(py, kind)
+}
+
+/// Build volume bars: a bar closes once its traded volume reaches `bar_volume`. Ticks
+/// with a NaN price or volume are skipped.
+#[pyfunction]
+pub fn ticks_to_volume_bars_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    volumes: NumericColumn<'py>,
+    bar_volume: f64
+) -> PyResult<BarArrays> {
+    build_bars(py, timestamps, prices, volumes, bar_volume, |_, volume| volume)
+}
+
+/// Build tick bars: a bar closes after every `ticks_per_bar` trades. Ticks with a NaN
+/// price or volume are skipped.
+#[pyfunction]
+pub fn ticks_to_tick_bars_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    volumes: NumericColumn<'py>,
+    ticks_per_bar: usize
+) -> PyResult<BarArrays> {
+    build_bars(py, timestamps, prices, volumes, ticks_per_bar as f64, |_, _| 1.0)
+}
+
+/// Build dollar bars: a bar closes once its traded value (price * volume) reaches
+/// `bar_value`. Ticks with a NaN price or volume are skipped.
+#[pyfunction]
+pub fn ticks_to_dollar_bars_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    volumes: NumericColumn<'py>,
+    bar_value: f64
+) -> PyResult<BarArrays> {
+    build_bars(py, timestamps, prices, volumes, bar_value, |price, volume| price * volume)
+}
+
+/// Most bricks `renko_rust` returns before failing, so a brick size far below the price
+/// scale errors instead of exhausting memory
+const MAX_BRICKS: usize = 100_000_000;
+
+/// Renko bricks of `brick_size` over finite `prices` as open, close, direction and
+/// completing index columns; None past `MAX_BRICKS`
+fn renko(prices: &[f64], brick_size: f64) -> Option<Bricks> {
+    let mut opens = Vec::new();
+    let mut closes = Vec::new();
+    let mut directions: Vec<i8> = Vec::new();
+    let mut indices = Vec::new();
+
+    if let Some(&first) = prices.first() {
+        // top/bottom span the most recent brick; both start at the first price
+        let mut top = first;
+        let mut bottom = first;
+
+        for (i, &price) in prices.iter().enumerate().skip(1) {
+            // Whole bricks crossed by this price, counted once so a jump far beyond the
+            // brick size costs one check instead of a loop per brick
+            let up = ((price - top) / brick_size).floor();
+            let down = ((bottom - price) / brick_size).floor();
+            let count = up.max(down);
+            if count < 1.0 {
+                continue;
+            }
+            if opens.len() as f64 + count > MAX_BRICKS as f64 {
+                return None;
+            }
+            for _ in 0..count as usize {
+                if up >= 1.0 {
+                    opens.push(top);
+                    closes.push(top + brick_size);
+                    directions.push(1);
+                    bottom = top;
+                    top += brick_size;
+                } else {
+                    opens.push(bottom);
+                    closes.push(bottom - brick_size);
+                    directions.push(-1);
+                    top = bottom;
+                    bottom -= brick_size;
+                }
+                indices.push(i as i64);
+            }
+        }
+    }
+    Some((opens, closes, directions, indices))
+}
+
+/// Build classic Renko bricks of `brick_size`; a reversal needs a move of two bricks.
+///
+/// Returns brick open, brick close, direction (+1 up / -1 down) and the index of the
+/// price that completed each brick. Non-finite prices raise `NaNInputError`, and more
+/// than 100 million bricks raise `ValueError`.
+#[pyfunction]
+pub fn renko_rust<'py>(
+    py: Python<'py>,
+    prices: Column<'py, f64>,
+    brick_size: f64
+) -> PyResult<RenkoArrays> {
+    let kind = prices.kind();
+    let prices = prices.values()?;
+    if !brick_size.is_finite() || brick_size <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Brick size must be a finite value > 0"
+        ));
+    }
+
+    if prices.iter().any(|p| !p.is_finite()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "Renko prices must be finite"
+        ));
+    }
+
+    let bricks = py.allow_threads(|| renko(&prices, brick_size));
+    let Some((opens, closes, directions, indices)) = bricks else {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Renko output would exceed {} bricks; use a larger brick size", MAX_BRICKS
+        )));
+    };
+
+    Ok((
+        kind.wrap(py, opens)?,
+        kind.wrap(py, closes)?,
+        kind.wrap(py, directions)?,
+        kind.wrap(py, indices)?,
+    ))
+}
+
+/// Range bars over finite `prices` as open, high, low, close and closing index columns
+fn range_bars(prices: &[f64], range_size: f64) -> RangeBars {
+    let mut opens = Vec::new();
+    let mut highs = Vec::new();
+    let mut lows = Vec::new();
+    let mut closes = Vec::new();
+    let mut indices = Vec::new();
+
+    let mut bar: Option<(f64, f64, f64)> = None;
+    for (i, &price) in prices.iter().enumerate() {
+        let (open, high, low) = match bar {
+            Some((open, high, low)) => (open, high.max(price), low.min(price)),
+            None => (price, price, price),
+        };
+
+        if high - low >= range_size {
+            opens.push(open);
+            highs.push(high);
+            lows.push(low);
+            closes.push(price);
+            indices.push(i as i64);
+            bar = None;
+        } else {
+            bar = Some((open, high, low));
+        }
+    }
+    (opens, highs, lows, closes, indices)
+}
+
+/// Build range bars: a bar closes on the tick that stretches its high-low range to `range_size`.
+///
+/// Returns open, high, low, close and the index of the closing price; a trailing
+/// incomplete bar is dropped. Non-finite prices raise `NaNInputError`.
+#[pyfunction]
+pub fn range_bars_rust<'py>(
+    py: Python<'py>,
+    prices: Column<'py, f64>,
+    range_size: f64
+) -> PyResult<RangeBarArrays> {
+    let kind = prices.kind();
+    let prices = prices.values()?;
+    if !range_size.is_finite() || range_size <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Range size must be a finite value > 0"
+        ));
+    }
+
+    if prices.iter().any(|p| !p.is_finite()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "Range bar prices must be finite"
+        ));
+    }
+
+    let (opens, highs, lows, closes, indices) = py.allow_threads(|| range_bars(&prices, range_size));
+
+    Ok((
+        kind.wrap(py, opens)?,
+        kind.wrap(py, highs)?,
+        kind.wrap(py, lows)?,
+        kind.wrap(py, closes)?,
+        kind.wrap(py, indices)?,
+    ))
+}
+
+/// Timestamp (bar open, ns), open, high, low, close and volume of one completed bar
+pub type Bar = (i64, f64, f64, f64, f64, f64);
+
+/// A time bar still accepting ticks
+struct OpenBar {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    first_ts: i64,
+    last_ts: i64,
+}
+
+/// Builds fixed-interval OHLCV bars from live (timestamp, price, size) ticks.
+///
+/// Bars are aligned to multiples of `interval_ns` and stamped with their open time. A bar
+/// is emitted once a tick at least `lateness_ns` past its end arrives, so ticks that are
+/// late by up to `lateness_ns` are still merged into their own bar; ticks for a bar that
+/// has already been emitted are dropped and counted in `late_ticks`. With `fill_gaps`, empty
+/// intervals between bars are emitted as flat zero-volume bars, except for gaps longer than
+/// `max_gap_ns`, which are treated as session breaks; `max_gap_ns` is required with
+/// `fill_gaps` so a stray timestamp cannot fill an unbounded number of bars.
+#[pyclass(module = "fast_math")]
+pub struct BarBuilder {
+    interval: i64,
+    lateness: i64,
+    /// Longest gap filled with flat bars, when gap filling is enabled
+    fill_gaps: Option<i64>,
+    /// Open bars keyed by their start time
+    open: std::collections::BTreeMap<i64, OpenBar>,
+    /// Start time and close of the most recently emitted bar
+    last_emitted: Option<(i64, f64)>,
+    watermark: i64,
+    late_ticks: usize,
+}
+
+impl BarBuilder {
+    /// Emit the bar starting at `start`, preceded by any gap-filling flat bars
+    fn emit(&mut self, start: i64, bar: OpenBar, out: &mut Vec<Bar>) {
+        if let Some((last_start, last_close)) = self.last_emitted {
+            let gap = start - last_start - self.interval;
+            if self.fill_gaps.is_some_and(|max| gap > 0 && gap <= max) {
+                let mut fill = last_start + self.interval;
+                while fill < start {
+                    out.push((fill, last_close, last_close, last_close, last_close, 0.0));
+                    fill += self.interval;
+                }
+            }
+        }
+        out.push((start, bar.open, bar.high, bar.low, bar.close, bar.volume));
+        self.last_emitted = Some((start, bar.close));
+    }
+}
+
+#[pymethods]
+impl BarBuilder {
+    #[new]
+    #[pyo3(signature = (interval_ns, lateness_ns=0, fill_gaps=false, max_gap_ns=None))]
+    fn new(interval_ns: i64, lateness_ns: i64, fill_gaps: bool, max_gap_ns: Option<i64>) -> PyResult<Self> {
+        if interval_ns <= 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Bar interval must be > 0"
+            ));
+        }
+        if lateness_ns < 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Lateness must be >= 0"
+            ));
+        }
+        if fill_gaps && max_gap_ns.is_none() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "max_gap_ns is required with fill_gaps"
+            ));
+        }
+        Ok(BarBuilder {
+            interval: interval_ns,
+            lateness: lateness_ns,
+            fill_gaps: if fill_gaps { max_gap_ns } else { None },
+            open: std::collections::BTreeMap::new(),
+            last_emitted: None,
+            watermark: i64::MIN,
+            late_ticks: 0,
+        })
+    }
+
+    /// Add a tick and return the bars it completes, oldest first
+    fn update(&mut self, timestamp: i64, price: f64, size: f64) -> Vec<Bar> {
+        let mut completed = Vec::new();
+        if price.is_nan() {
+            return completed;
+        }
+        let start = timestamp.div_euclid(self.interval) * self.interval;
+        if self.last_emitted.is_some_and(|(last, _)| start <= last) {
+            self.late_ticks += 1;
+            return completed;
+        }
+
+        let bar = self.open.entry(start).or_insert(OpenBar {
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0.0,
+            first_ts: timestamp,
+            last_ts: timestamp,
+        });
+        bar.high = bar.high.max(price);
+        bar.low = bar.low.min(price);
+        bar.volume += if size.is_nan() { 0.0 } else { size };
+        if timestamp < bar.first_ts {
+            bar.open = price;
+            bar.first_ts = timestamp;
+        }
+        if timestamp >= bar.last_ts {
+            bar.close = price;
+            bar.last_ts = timestamp;
+        }
+
+        self.watermark = self.watermark.max(timestamp);
+        while let Some(entry) = self.open.first_entry() {
+            if *entry.key() + self.interval + self.lateness > self.watermark {
+                break;
+            }
+            let (start, bar) = entry.remove_entry();
+            self.emit(start, bar, &mut completed);
+        }
+        completed
+    }
+
+    /// Emit all open bars, e.g. at the end of a session
+    fn flush(&mut self) -> Vec<Bar> {
+        let mut completed = Vec::new();
+        while let Some((start, bar)) = self.open.pop_first() {
+            self.emit(start, bar, &mut completed);
+        }
+        completed
+    }
+
+    /// The bar currently being built (the oldest open one), if any
+    #[getter]
+    fn current(&self) -> Option<Bar> {
+        self.open
+            .first_key_value()
+            .map(|(&start, bar)| (start, bar.open, bar.high, bar.low, bar.close, bar.volume))
+    }
+
+    /// Ticks dropped because their bar had already been emitted
+    #[getter]
+    fn late_ticks(&self) -> usize {
+        self.late_ticks
+    }
+
+    /// Drop all open bars and history, e.g. at a session boundary
+    fn reset(&mut self) {
+        self.open.clear();
+        self.last_emitted = None;
+        self.watermark = i64::MIN;
+        self.late_ticks = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_close_once_the_measure_reaches_the_threshold() {
+        let timestamps = [1, 2, 3, 4, 5];
+        let prices = [10.0, 11.0, 9.0, 10.5, 12.0];
+        let bars = accumulate_bars(&timestamps, &prices, &[2.0, 2.0, 1.0, 3.0, 1.0], 4.0, |_, volume| volume);
+        assert_eq!(bars.timestamps, vec![2, 4]);
+        assert_eq!((bars.open, bars.high, bars.low, bars.close), (vec![10.0, 9.0], vec![11.0, 10.5], vec![10.0, 9.0], vec![11.0, 10.5]));
+        assert_eq!(bars.volume, vec![4.0, 4.0]);
+        // The trailing partial bar is dropped
+        let bars = accumulate_bars(&timestamps, &prices, &[1i64; 5], 2.0, |_, _| 1.0);
+        assert_eq!(bars.timestamps, vec![2, 4]);
+        let bars = accumulate_bars(&timestamps, &prices, &[1.0; 5], 20.0, |price, volume| price * volume);
+        assert_eq!(bars.timestamps, vec![2, 5]);
+    }
+
+    #[test]
+    fn nan_ticks_are_skipped() {
+        let timestamps = [1, 2, 3, 4];
+        let bars = accumulate_bars(&timestamps, &[10.0, 11.0, f64::NAN, 12.0], &[2.0, f64::NAN, 1.0, 2.0], 4.0, |_, volume| volume);
+        assert_eq!(bars.timestamps, vec![4]);
+        assert_eq!((bars.open[0], bars.high[0], bars.close[0], bars.volume[0]), (10.0, 12.0, 12.0, 4.0));
+        let bars = accumulate_bars(&timestamps, &[f64::NAN, 11.0, 12.0, 13.0], &[1.0; 4], 2.0, |_, _| 1.0);
+        assert_eq!((bars.timestamps, bars.open), (vec![3], vec![11.0]));
+    }
+
+    #[test]
+    fn renko_lays_one_brick_per_step_of_a_jump() {
+        let (opens, closes, directions, indices) = renko(&[0.0, 3.5, 0.2], 1.0).unwrap();
+        assert_eq!(directions, vec![1, 1, 1, -1]);
+        assert_eq!(opens, vec![0.0, 1.0, 2.0, 2.0]);
+        assert_eq!(closes, vec![1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(indices, vec![1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn renko_refuses_unbounded_brick_counts() {
+        assert!(renko(&[0.0, 1e300], 1.0).is_none());
+        // The distance itself overflows to infinity
+        assert!(renko(&[0.0, f64::MAX, -f64::MAX], 1e300).is_none());
+    }
+
+    #[test]
+    fn range_bars_close_on_the_range_and_reject_nan() {
+        let (opens, highs, lows, closes, indices) = range_bars(&[10.0, 10.5, 9.9, 11.0, 11.5, 12.5, 12.0], 1.0);
+        assert_eq!((opens, highs, lows), (vec![10.0, 11.5], vec![11.0, 12.5], vec![9.9, 11.5]));
+        // The trailing bar opened at 12.0 never completes
+        assert_eq!((closes, indices), (vec![11.0, 12.5], vec![3, 5]));
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            for bad in [f64::NAN, f64::INFINITY] {
+                let prices = Column::Polars(arrow::array::Float64Array::from(vec![10.0, bad, 12.0]));
+                let err = range_bars_rust(py, prices, 1.0).err().unwrap();
+                assert!(err.is_instance_of::<crate::errors::NaNInputError>(py));
+            }
+        });
+    }
+
+    #[test]
+    fn bar_builder_fills_gaps_up_to_the_cap() {
+        let mut builder = BarBuilder::new(10, 0, true, Some(30)).unwrap();
+        assert!(builder.update(1, 5.0, 1.0).is_empty());
+        let bars = builder.update(41, 6.0, 2.0);
+        assert_eq!(bars, vec![(0, 5.0, 5.0, 5.0, 5.0, 1.0)]);
+        let bars = builder.update(1000, 7.0, 1.0);
+        // The 30ns gap before the bar at 40 is filled flat; the longer one after it is a break
+        let starts: Vec<i64> = bars.iter().map(|b| b.0).collect();
+        assert_eq!(starts, vec![10, 20, 30, 40]);
+        assert_eq!(bars[0], (10, 5.0, 5.0, 5.0, 5.0, 0.0));
+        assert_eq!(builder.flush(), vec![(1000, 7.0, 7.0, 7.0, 7.0, 1.0)]);
+
+        pyo3::prepare_freethreaded_python();
+        assert!(BarBuilder::new(10, 0, true, None).is_err());
+        assert!(BarBuilder::new(10, 0, false, None).is_ok());
+    }
+
+    #[test]
+    fn bar_builder_merges_ticks_within_the_lateness() {
+        let mut builder = BarBuilder::new(10, 5, false, None).unwrap();
+        builder.update(2, 1.0, 1.0);
+        assert!(builder.update(12, 2.0, 1.0).is_empty());
+        // Late but within 5ns of the first bar's end, so it is merged and becomes the close
+        assert!(builder.update(8, 3.0, 1.0).is_empty());
+        let bars = builder.update(15, 2.5, 1.0);
+        assert_eq!(bars, vec![(0, 1.0, 3.0, 1.0, 3.0, 2.0)]);
+        assert!(builder.update(9, 4.0, 1.0).is_empty());
+        assert_eq!(builder.late_ticks(), 1);
+    }
+}
@@ -0,0 +1,113 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::dispatch::Indicator;
+
+/// Deterministic random-walk prices, so timings are comparable across runs and machines
+fn random_walk(len: usize) -> Vec<f64> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut price = 100.0;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            price += ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.1;
+            price
+        })
+        .collect()
+}
+
+/// Timing statistics for one input size, in nanoseconds per input element
+struct Timing {
+    size: usize,
+    iterations: usize,
+    samples: Vec<f64>,
+}
+
+impl Timing {
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let std = (sorted.iter().map(|s| (s - mean) * (s - mean)).sum::<f64>() / n).sqrt();
+        let median = match sorted.len() % 2 {
+            0 => (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0,
+            _ => sorted[sorted.len() / 2],
+        };
+
+        let stats = PyDict::new_bound(py);
+        stats.set_item("size", self.size)?;
+        stats.set_item("iterations", self.iterations)?;
+        stats.set_item("repeat", self.samples.len())?;
+        stats.set_item("mean", mean)?;
+        stats.set_item("median", median)?;
+        stats.set_item("min", sorted[0])?;
+        stats.set_item("max", sorted[sorted.len() - 1])?;
+        stats.set_item("std", std)?;
+        Ok(stats)
+    }
+}
+
+/// Time `indicator` on `values`: one warm-up call, then enough calls per sample that a
+/// sample lasts about `sample_time`
+fn time(indicator: Indicator, values: &[f64], repeat: usize, sample_time: Duration) -> PyResult<Timing> {
+    let start = Instant::now();
+    black_box(indicator.compute(black_box(values))?);
+    let once = start.elapsed().max(Duration::from_nanos(1));
+    let iterations = (sample_time.as_nanos() / once.as_nanos()).max(1) as usize;
+
+    let mut samples = Vec::with_capacity(repeat);
+    for _ in 0..repeat {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            black_box(indicator.compute(black_box(values))?);
+        }
+        samples.push(start.elapsed().as_nanos() as f64 / (iterations * values.len()) as f64);
+    }
+    Ok(Timing { size: values.len(), iterations, samples })
+}
+
+/// Benchmark a kernel in Rust, without Python call overhead in the timed loop.
+///
+/// `indicator` and `params` are as for `compute_many_rust` (e.g. `"sma"`, `{"window": 20}`).
+/// For each input size a deterministic random walk is generated, the kernel is warmed up
+/// and calibrated so each of the `repeat` samples takes about `min_time / repeat`
+/// seconds. Returns one dict per size with `size`, `iterations`, `repeat` and the `mean`,
+/// `median`, `min`, `max` and `std` of the samples in nanoseconds per element.
+#[pyfunction]
+#[pyo3(signature = (indicator, sizes, params=None, repeat=5, min_time=0.2))]
+pub fn benchmark<'py>(
+    py: Python<'py>,
+    indicator: &str,
+    sizes: Vec<usize>,
+    params: Option<&Bound<'py, PyDict>>,
+    repeat: usize,
+    min_time: f64
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let indicator = Indicator::parse(indicator, params)?;
+    if repeat == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "repeat must be > 0"
+        ));
+    }
+    let sample_time = Duration::try_from_secs_f64(min_time / repeat as f64).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err("min_time must be a non-negative number of seconds")
+    })?;
+
+    let timings = py.allow_threads(|| {
+        sizes
+            .iter()
+            .map(|&size| time(indicator, &random_walk(size), repeat, sample_time))
+            .collect::<PyResult<Vec<_>>>()
+    })?;
+    timings.iter().map(|timing| timing.to_dict(py)).collect()
+}
+
+/// Populate the `fast_math.bench` submodule
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(benchmark, m)?)?;
+    Ok(())
+}
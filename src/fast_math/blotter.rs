@@ -0,0 +1,373 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::column::{Column, NumericColumn};
+use crate::{fix, threads};
+
+/// Output format of the blotter
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// One ExecutionReport (35=8) per trade and line
+    Fix,
+    /// A header row, then one row per trade
+    Csv,
+}
+
+impl Format {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fix" => Ok(Format::Fix),
+            "csv" => Ok(Format::Csv),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown format '{}', expected 'fix' or 'csv'", name
+            ))),
+        }
+    }
+}
+
+const CSV_HEADER: &str = "timestamp,symbol,side,quantity,price,notional,fee,exec_id,order_id,account";
+
+/// The trade ledger's columns, all of one length
+struct Ledger {
+    timestamps: Vec<i64>,
+    symbols: Vec<String>,
+    /// Signed quantities, positive for buys
+    quantities: Vec<f64>,
+    prices: Vec<f64>,
+    exec_ids: Option<Vec<String>>,
+    order_ids: Option<Vec<String>>,
+    accounts: Option<Vec<String>>,
+    fees: Option<Vec<f64>>,
+}
+
+impl Ledger {
+    fn parse(trades: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let py = trades.py();
+        let item = |name: &str| -> PyResult<Option<Bound<'_, PyAny>>> {
+            match trades.get_item(name) {
+                Ok(values) => Ok(Some(values)),
+                Err(err) if err.is_instance_of::<pyo3::exceptions::PyKeyError>(py) => Ok(None),
+                Err(err) => Err(err),
+            }
+        };
+        let required = |name: &str| {
+            item(name)?.ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!("trades needs a '{}' column", name))
+            })
+        };
+        let floats = |values: Bound<'_, PyAny>| -> PyResult<Vec<f64>> {
+            Ok(match values.extract::<NumericColumn>()? {
+                NumericColumn::Float(column) => column.values()?.into_owned(),
+                NumericColumn::Int(column) => column.values()?.iter().map(|&v| v as f64).collect(),
+            })
+        };
+        // Lists and object arrays extract directly; Series and the like go through tolist()
+        let strings = |values: Bound<'_, PyAny>| -> PyResult<Vec<String>> {
+            let values = match values.extract::<Vec<String>>() {
+                Ok(values) => values,
+                Err(_) if values.hasattr("tolist")? => values.call_method0("tolist")?.extract()?,
+                Err(err) => return Err(err),
+            };
+            if values.iter().any(|v| v.contains('\x01')) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "trades text columns must not contain SOH"
+                ));
+            }
+            Ok(values)
+        };
+
+        let ledger = Ledger {
+            timestamps: required("timestamp")?.extract::<Column<i64>>()?.values()?.into_owned(),
+            symbols: strings(required("symbol")?)?,
+            quantities: floats(required("qty")?)?,
+            prices: floats(required("price")?)?,
+            exec_ids: item("exec_id")?.map(strings).transpose()?,
+            order_ids: item("order_id")?.map(strings).transpose()?,
+            accounts: item("account")?.map(strings).transpose()?,
+            fees: item("fee")?.map(floats).transpose()?,
+        };
+        ledger.validate()?;
+        Ok(ledger)
+    }
+
+    /// Check the columns line up and every trade has a usable quantity and price
+    fn validate(&self) -> PyResult<()> {
+        let n = self.timestamps.len();
+        let optional = [&self.exec_ids, &self.order_ids, &self.accounts];
+        if self.symbols.len() != n
+            || self.quantities.len() != n
+            || self.prices.len() != n
+            || optional.iter().any(|c| c.as_ref().is_some_and(|c| c.len() != n))
+            || self.fees.as_ref().is_some_and(|f| f.len() != n)
+        {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "trades columns must have the same length"
+            ));
+        }
+        if self.quantities.iter().chain(&self.prices).any(|v| v.is_nan()) {
+            return Err(crate::errors::NaNInputError::new_err(
+                "trades qty and price contain NaN"
+            ));
+        }
+        if let Some(i) = self.quantities.iter().position(|&q| q == 0.0 || q.is_infinite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "trade {} has a zero or infinite qty", i
+            )));
+        }
+        if let Some(i) = self.prices.iter().position(|p| p.is_infinite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "trade {} has an infinite price", i
+            )));
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Execution id of trade `i`, its 1-based row number when the ledger has none
+    fn exec_id(&self, i: usize) -> String {
+        self.exec_ids.as_ref().map_or_else(|| (i + 1).to_string(), |ids| ids[i].clone())
+    }
+
+    fn order_id(&self, i: usize) -> String {
+        self.order_ids.as_ref().map_or_else(|| self.exec_id(i), |ids| ids[i].clone())
+    }
+
+    /// The fee of trade `i`, if the ledger has one; NaN counts as none
+    fn fee(&self, i: usize) -> Option<f64> {
+        self.fees.as_ref().map(|f| f[i]).filter(|f| !f.is_nan())
+    }
+}
+
+/// Civil date `(year, month, day)` of a count of days since 1970-01-01 (Hinnant's
+/// civil_from_days)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + (month <= 2) as i64, month, day)
+}
+
+/// Nanoseconds since the epoch split into the civil date, the time of day and the
+/// nanoseconds within the second
+fn split_timestamp(ns: i64) -> ((i64, i64, i64), (i64, i64, i64), i64) {
+    let (days, time) = (ns.div_euclid(86_400_000_000_000), ns.rem_euclid(86_400_000_000_000));
+    let seconds = time / 1_000_000_000;
+    (civil_from_days(days), (seconds / 3600, seconds / 60 % 60, seconds % 60), time % 1_000_000_000)
+}
+
+/// FIX UTCTimestamp, `YYYYMMDD-HH:MM:SS.sss`
+fn fix_timestamp(ns: i64) -> String {
+    let ((year, month, day), (hour, minute, second), nanos) = split_timestamp(ns);
+    format!(
+        "{:04}{:02}{:02}-{:02}:{:02}:{:02}.{:03}",
+        year, month, day, hour, minute, second, nanos / 1_000_000
+    )
+}
+
+/// ISO 8601 in UTC with nanoseconds, `YYYY-MM-DDTHH:MM:SS.fffffffffZ`
+fn iso_timestamp(ns: i64) -> String {
+    let ((year, month, day), (hour, minute, second), nanos) = split_timestamp(ns);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+        year, month, day, hour, minute, second, nanos
+    )
+}
+
+/// Quote a CSV field when it holds a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Session fields of the FIX header
+struct Session<'a> {
+    begin_string: &'a str,
+    sender_comp_id: &'a str,
+    target_comp_id: &'a str,
+}
+
+impl<'a> Session<'a> {
+    /// FIX output needs both comp ids, as empty SenderCompID and TargetCompID tags are
+    /// invalid; CSV output ignores them
+    fn new(format: Format, begin_string: &'a str, sender_comp_id: &'a str, target_comp_id: &'a str) -> PyResult<Self> {
+        if [begin_string, sender_comp_id, target_comp_id].iter().any(|s| s.contains('\x01')) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Session fields must not contain SOH"
+            ));
+        }
+        if format == Format::Fix && (sender_comp_id.is_empty() || target_comp_id.is_empty()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "format='fix' needs a sender_comp_id and a target_comp_id"
+            ));
+        }
+        Ok(Session { begin_string, sender_comp_id, target_comp_id })
+    }
+}
+
+/// Trade `i` as a filled ExecutionReport with MsgSeqNum `i + 1`
+fn fix_row(ledger: &Ledger, session: &Session<'_>, i: usize) -> String {
+    let qty = ledger.quantities[i].abs();
+    let price = ledger.prices[i];
+    let time = fix_timestamp(ledger.timestamps[i]);
+    let side = if ledger.quantities[i] > 0.0 { "1" } else { "2" };
+    let mut body = vec![
+        (35, "8".to_string()),
+        (49, session.sender_comp_id.to_string()),
+        (56, session.target_comp_id.to_string()),
+        (34, (i + 1).to_string()),
+        (52, time.clone()),
+        (37, ledger.order_id(i)),
+        (17, ledger.exec_id(i)),
+        (150, "F".to_string()),
+        (39, "2".to_string()),
+    ];
+    if let Some(accounts) = &ledger.accounts {
+        body.push((1, accounts[i].clone()));
+    }
+    body.extend([
+        (55, ledger.symbols[i].clone()),
+        (54, side.to_string()),
+        (38, qty.to_string()),
+        (32, qty.to_string()),
+        (31, price.to_string()),
+        (151, "0".to_string()),
+        (14, qty.to_string()),
+        (6, price.to_string()),
+        (60, time),
+    ]);
+    if let Some(fee) = ledger.fee(i) {
+        // CommType 3: an absolute amount
+        body.extend([(12, fee.to_string()), (13, "3".to_string())]);
+    }
+    fix::encode_message(session.begin_string, &body)
+}
+
+fn csv_row(ledger: &Ledger, i: usize) -> String {
+    let (qty, price) = (ledger.quantities[i], ledger.prices[i]);
+    let text = |column: &Option<Vec<String>>| column.as_ref().map_or_else(String::new, |c| csv_field(&c[i]));
+    [
+        iso_timestamp(ledger.timestamps[i]),
+        csv_field(&ledger.symbols[i]),
+        if qty > 0.0 { "BUY" } else { "SELL" }.to_string(),
+        qty.abs().to_string(),
+        price.to_string(),
+        (qty.abs() * price).to_string(),
+        ledger.fee(i).map_or_else(String::new, |f| f.to_string()),
+        csv_field(&ledger.exec_id(i)),
+        csv_field(&ledger.order_id(i)),
+        text(&ledger.accounts),
+    ]
+    .join(",")
+}
+
+/// Export a trade ledger as FIX execution reports or CSV, for reconciliation with the
+/// clearing broker.
+///
+/// `trades` is a mapping of columns (a dict or DataFrame) with `timestamp` (integer
+/// nanoseconds since the epoch, UTC), `symbol`, the signed `qty` (positive for buys) and
+/// a finite `price`, and optionally `exec_id`, `order_id`, `account` and `fee` (NaN for
+/// none). Missing execution ids default to the 1-based row number, and order ids to the
+/// execution id. `format="fix"` writes one filled ExecutionReport (35=8, ExecType F,
+/// OrdStatus 2) per line, SOH-delimited with a correct BodyLength and CheckSum,
+/// MsgSeqNum counting from 1, millisecond UTCTimestamps and fees as an absolute
+/// Commission (12, CommType 3); the session fields come from `begin_string`,
+/// `sender_comp_id` and `target_comp_id`, both comp ids being required. `format="csv"` writes a header row and one row
+/// per trade with ISO 8601 UTC timestamps, a BUY or SELL side, the absolute quantity and
+/// the notional, quoting text that holds commas or quotes. Numbers are written in full,
+/// without exponents. Rows are rendered in parallel and written to `path`, replacing
+/// it; returns the number of trades written.
+#[pyfunction]
+#[pyo3(signature = (trades, format, path, begin_string="FIX.4.4", sender_comp_id="", target_comp_id=""))]
+pub fn export_blotter_rust(
+    py: Python<'_>,
+    trades: &Bound<'_, PyAny>,
+    format: &str,
+    path: &str,
+    begin_string: &str,
+    sender_comp_id: &str,
+    target_comp_id: &str
+) -> PyResult<usize> {
+    let format = Format::parse(format)?;
+    let ledger = Ledger::parse(trades)?;
+    let session = Session::new(format, begin_string, sender_comp_id, target_comp_id)?;
+
+    py.allow_threads(|| {
+        let rows: Vec<String> = threads::install(|| {
+            (0..ledger.len())
+                .into_par_iter()
+                .map(|i| match format {
+                    Format::Fix => fix_row(&ledger, &session, i),
+                    Format::Csv => csv_row(&ledger, i),
+                })
+                .collect()
+        });
+        let mut out = BufWriter::new(File::create(path)?);
+        if format == Format::Csv {
+            writeln!(out, "{}", CSV_HEADER)?;
+        }
+        for row in &rows {
+            writeln!(out, "{}", row)?;
+        }
+        out.flush()?;
+        Ok(rows.len())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger(prices: Vec<f64>) -> Ledger {
+        Ledger {
+            timestamps: vec![1_700_000_000_123_000_000; prices.len()],
+            symbols: vec!["INFY".to_string(); prices.len()],
+            quantities: vec![25.0; prices.len()],
+            prices,
+            exec_ids: None,
+            order_ids: None,
+            accounts: None,
+            fees: None,
+        }
+    }
+
+    #[test]
+    fn infinite_prices_are_rejected() {
+        pyo3::prepare_freethreaded_python();
+        assert!(ledger(vec![1502.5, 1503.0]).validate().is_ok());
+        for price in [f64::INFINITY, f64::NEG_INFINITY] {
+            let err = ledger(vec![1502.5, price]).validate().unwrap_err();
+            assert!(err.to_string().contains("trade 1 has an infinite price"));
+        }
+    }
+
+    #[test]
+    fn fix_output_needs_both_comp_ids() {
+        pyo3::prepare_freethreaded_python();
+        for (sender, target) in [("", "BROKER"), ("DESK", ""), ("", "")] {
+            assert!(Session::new(Format::Fix, "FIX.4.4", sender, target).is_err());
+            assert!(Session::new(Format::Csv, "FIX.4.4", sender, target).is_ok());
+        }
+        assert!(Session::new(Format::Fix, "FIX.4.4", "DE\x01SK", "BROKER").is_err());
+
+        let session = Session::new(Format::Fix, "FIX.4.4", "DESK", "BROKER").unwrap();
+        let row = fix_row(&ledger(vec![1502.5]), &session, 0);
+        let fields: Vec<_> = fix::FixFields::new(row.as_bytes()).collect::<Result<_, _>>().unwrap();
+        assert!(fields.contains(&(49, &b"DESK"[..])));
+        assert!(fields.contains(&(56, &b"BROKER"[..])));
+        assert!(fields.contains(&(31, &b"1502.5"[..])));
+    }
+}
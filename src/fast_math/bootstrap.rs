@@ -0,0 +1,244 @@
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::column::Column;
+use crate::config::FastMathConfig;
+use crate::rng::{self, SplitMix};
+use crate::threads;
+
+/// Performance metric of a return series
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Mean,
+    /// Sample standard deviation (`ddof=1`)
+    Volatility,
+    /// Mean over volatility, per period
+    Sharpe,
+    /// Mean over the downside deviation `sqrt(mean(min(r, 0)**2))`, per period
+    Sortino,
+    /// Largest fall of the compounded equity curve from its peak, as a fraction
+    MaxDrawdown,
+    /// Compounded return over the whole series
+    TotalReturn,
+}
+
+impl Metric {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mean" => Ok(Metric::Mean),
+            "volatility" | "std" => Ok(Metric::Volatility),
+            "sharpe" => Ok(Metric::Sharpe),
+            "sortino" => Ok(Metric::Sortino),
+            "max_drawdown" => Ok(Metric::MaxDrawdown),
+            "total_return" => Ok(Metric::TotalReturn),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown metric '{}', expected 'mean', 'volatility', 'sharpe', 'sortino', 'max_drawdown' or 'total_return'",
+                name
+            ))),
+        }
+    }
+
+    fn compute(self, returns: &[f64]) -> f64 {
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let std = || (returns.iter().map(|r| (r - mean) * (r - mean)).sum::<f64>() / (n - 1.0)).sqrt();
+        let ratio = |denominator: f64| if denominator > 0.0 { mean / denominator } else { f64::NAN };
+        match self {
+            Metric::Mean => mean,
+            Metric::Volatility => std(),
+            Metric::Sharpe => ratio(std()),
+            Metric::Sortino => ratio((returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt()),
+            Metric::MaxDrawdown => {
+                let (mut equity, mut peak, mut worst) = (1.0, 1.0f64, 0.0f64);
+                for r in returns {
+                    equity *= 1.0 + r;
+                    peak = peak.max(equity);
+                    worst = worst.max((peak - equity) / peak);
+                }
+                worst
+            }
+            Metric::TotalReturn => returns.iter().map(|r| 1.0 + r).product::<f64>() - 1.0,
+        }
+    }
+}
+
+/// One resample of `returns`: IID draws for `block_size == 1`, else consecutive blocks
+/// starting at random points and wrapping around the end (the circular block bootstrap)
+fn resample(returns: &[f64], block_size: usize, rng: &mut SplitMix) -> Vec<f64> {
+    let n = returns.len();
+    let mut sample = Vec::with_capacity(n);
+    while sample.len() < n {
+        let start = rng.below(n);
+        let take = block_size.min(n - sample.len());
+        sample.extend((start..start + take).map(|i| returns[i % n]));
+    }
+    sample
+}
+
+/// Linearly interpolated quantile of sorted values, as numpy's default
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (low, fraction) = (position.floor() as usize, position.fract());
+    let high = (low + 1).min(sorted.len() - 1);
+    sorted[low] + fraction * (sorted[high] - sorted[low])
+}
+
+/// Acceleration of the BCa interval from the jackknife, deleting one value for the IID
+/// bootstrap or one non-overlapping block for the block bootstrap
+fn acceleration(returns: &[f64], metric: Metric, block_size: usize) -> f64 {
+    let n = returns.len();
+    let blocks = n.div_ceil(block_size);
+    let jackknife: Vec<f64> = (0..blocks)
+        .into_par_iter()
+        .map(|b| {
+            let (start, end) = (b * block_size, ((b + 1) * block_size).min(n));
+            let kept: Vec<f64> = returns[..start].iter().chain(&returns[end..]).copied().collect();
+            metric.compute(&kept)
+        })
+        .collect();
+    let mean = jackknife.iter().sum::<f64>() / blocks as f64;
+    let (cubes, squares) = jackknife
+        .iter()
+        .fold((0.0, 0.0), |(c, s), v| (c + (mean - v).powi(3), s + (mean - v).powi(2)));
+    if squares == 0.0 { 0.0 } else { cubes / (6.0 * squares.powf(1.5)) }
+}
+
+/// The estimate with its percentile and BCa intervals
+type Interval = (f64, (f64, f64), (f64, f64));
+
+fn bootstrap(returns: &[f64], metric: Metric, n_boot: usize, block_size: usize, confidence: f64, seed: u64) -> Interval {
+    let nan = (f64::NAN, f64::NAN);
+    let estimate = metric.compute(returns);
+    if returns.len() < 2 || estimate.is_nan() {
+        return (estimate, nan, nan);
+    }
+    let mut stats: Vec<f64> = (0..n_boot as u64)
+        .into_par_iter()
+        .map(|i| metric.compute(&resample(returns, block_size, &mut SplitMix::new(seed, i))))
+        .filter(|v| !v.is_nan())
+        .collect();
+    if stats.is_empty() {
+        return (estimate, nan, nan);
+    }
+    stats.sort_by(f64::total_cmp);
+
+    let tail = (1.0 - confidence) / 2.0;
+    let percentile = (quantile(&stats, tail), quantile(&stats, 1.0 - tail));
+
+    let normal = Normal::new(0.0, 1.0).unwrap();
+    let below = stats.iter().filter(|&&v| v < estimate).count() as f64 / stats.len() as f64;
+    let bca = if below > 0.0 && below < 1.0 {
+        let z0 = normal.inverse_cdf(below);
+        let a = acceleration(returns, metric, block_size);
+        let adjusted = |q: f64| {
+            let z = z0 + normal.inverse_cdf(q);
+            normal.cdf(z0 + z / (1.0 - a * z))
+        };
+        (quantile(&stats, adjusted(tail)), quantile(&stats, adjusted(1.0 - tail)))
+    } else {
+        // Every replicate on one side of the estimate leaves the bias correction infinite
+        nan
+    };
+    (estimate, percentile, bca)
+}
+
+/// Bootstrap confidence interval of a performance metric of `returns`.
+///
+/// `metric` is "mean", "volatility" (or "std"), "sharpe", "sortino" (both per period, not
+/// annualised), "max_drawdown" or "total_return". `n_boot` resamples are drawn in
+/// parallel: IID draws when `block_size` is 1 (the default), else the circular block
+/// bootstrap with blocks of `block_size` consecutive returns, which keeps the serial
+/// dependence within blocks. Each resample is seeded from `seed` (default: the seed given
+/// to `set_seed`) and its index, so results are reproducible and independent of the
+/// thread count. Returns `(estimate, (low, high),
+/// (bca_low, bca_high))`: the metric of the full series, the percentile interval and the
+/// bias-corrected and accelerated interval at `confidence`, the acceleration taken from a
+/// delete-one (or delete-block) jackknife. Resamples whose metric is undefined are skipped;
+/// NaN inputs, fewer than 2 returns or an undefined estimate give NaN intervals, and the
+/// BCa interval is NaN when every resample falls on one side of the estimate.
+#[pyfunction]
+#[pyo3(signature = (returns, metric="sharpe", n_boot=1000, block_size=1, confidence=0.95, seed=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn bootstrap_ci_rust<'py>(
+    py: Python<'py>,
+    returns: Column<'py, f64>,
+    metric: &str,
+    n_boot: usize,
+    block_size: usize,
+    confidence: f64,
+    seed: Option<u64>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Interval> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    let metric = Metric::parse(metric)?;
+    if n_boot == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_boot must be > 0"
+        ));
+    }
+    if block_size == 0 {
+        return Err(crate::errors::InvalidWindowError::new_err(
+            "block_size must be > 0"
+        ));
+    }
+    if confidence.is_nan() || confidence <= 0.0 || confidence >= 1.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "confidence must be between 0 and 1"
+        ));
+    }
+    let returns = returns.values()?;
+    Ok(py.allow_threads(|| threads::install(|| bootstrap(&returns, metric, n_boot, block_size, confidence, seed))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(n: usize) -> Vec<f64> {
+        let mut rng = SplitMix::new(11, 0);
+        (0..n).map(|_| 0.001 + 0.01 * rng.normal_pair().0).collect()
+    }
+
+    #[test]
+    fn metrics_match_their_definitions() {
+        let returns = [0.1, -0.05, 0.02, -0.1, 0.03];
+        assert!((Metric::Mean.compute(&returns) - 0.0).abs() < 1e-12);
+        assert!((Metric::TotalReturn.compute(&returns) - (1.1 * 0.95 * 1.02 * 0.9 * 1.03 - 1.0)).abs() < 1e-12);
+        // The peak of 1.1 after the first return falls to 1.1 * 0.95 * 1.02 * 0.9
+        assert!((Metric::MaxDrawdown.compute(&returns) - (1.0 - 0.95 * 1.02 * 0.9)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn resamples_keep_the_length_and_blocks_stay_consecutive() {
+        let returns: Vec<f64> = (0..10).map(f64::from).collect();
+        let mut rng = SplitMix::new(1, 0);
+        let blocks = resample(&returns, 4, &mut rng);
+        assert_eq!(blocks.len(), 10);
+        for chunk in blocks.chunks(4) {
+            assert!(chunk.windows(2).all(|w| w[1] == (w[0] + 1.0) % 10.0));
+        }
+    }
+
+    #[test]
+    fn intervals_cover_the_estimate_and_depend_only_on_the_seed() {
+        let returns = sample(250);
+        let (estimate, (low, high), (bca_low, bca_high)) = bootstrap(&returns, Metric::Mean, 500, 1, 0.95, 7);
+        assert!(low < estimate && estimate < high);
+        assert!(bca_low < estimate && estimate < bca_high);
+        // The percentile interval of the mean is close to the normal interval
+        let se = Metric::Volatility.compute(&returns) / 250f64.sqrt();
+        assert!(((high - low) / (2.0 * 1.96 * se) - 1.0).abs() < 0.2);
+
+        assert_eq!(bootstrap(&returns, Metric::Mean, 500, 1, 0.95, 7), bootstrap(&returns, Metric::Mean, 500, 1, 0.95, 7));
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        assert_eq!(pool.install(|| bootstrap(&returns, Metric::Mean, 500, 1, 0.95, 7)).1, (low, high));
+    }
+
+    #[test]
+    fn too_few_returns_give_nan_intervals() {
+        let (estimate, percentile, _) = bootstrap(&[0.01], Metric::Sharpe, 100, 1, 0.9, 1);
+        assert!(estimate.is_nan() && percentile.0.is_nan());
+    }
+}
@@ -0,0 +1,584 @@
+// Result cache for research sessions: `fast_math.cache.enable_cache()` swaps every function
+// of the module and its submodules for a wrapper that fingerprints the call's arguments
+// (the bytes of each array, the repr of each scalar) and returns the stored result when
+// the same kernel was already called on the same data with the same parameters. Entries
+// are evicted least recently used first beyond the entry and byte limits.
+// `disable_cache()` puts the original functions back.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::sync::Mutex;
+
+use arrow::array::{Array, ArrayData};
+use numpy::{PyArrayDescrMethods, PyUntypedArray, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBool, PyBytes, PyCFunction, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+
+use crate::column;
+use crate::config::{self, FastMathConfig};
+use crate::telemetry::TracedFunction;
+
+/// Size charged for a result that holds no array
+const OBJECT_BYTES: usize = 64;
+
+/// Functions called for their side effects (writing files), which must run on every call
+const SIDE_EFFECTS: [&str; 3] = ["write_parquet_rust", "write_ticks_rust", "export_blotter_rust"];
+
+/// A stored result
+struct Entry {
+    value: PyObject,
+    bytes: usize,
+    /// Tick of its last use, for least-recently-used eviction
+    used: u64,
+}
+
+struct Cache {
+    entries: HashMap<(String, u64), Entry>,
+    max_entries: usize,
+    max_bytes: usize,
+    bytes: usize,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    /// Calls passed through because an argument could not be fingerprinted
+    uncached: u64,
+}
+
+impl Cache {
+    fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Cache { entries: HashMap::new(), max_entries, max_bytes, bytes: 0, tick: 0, hits: 0, misses: 0, uncached: 0 }
+    }
+
+    fn get(&mut self, py: Python<'_>, key: &(String, u64)) -> Option<PyObject> {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.used = self.tick;
+                self.hits += 1;
+                Some(entry.value.clone_ref(py))
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store `value`, evicting the least recently used entries to stay within the limits;
+    /// a result larger than `max_bytes` on its own is not kept
+    fn insert(&mut self, key: (String, u64), value: PyObject, bytes: usize) {
+        if bytes > self.max_bytes || self.max_entries == 0 {
+            return;
+        }
+        if let Some(old) = self.entries.remove(&key) {
+            self.bytes -= old.bytes;
+        }
+        while self.entries.len() >= self.max_entries || self.bytes + bytes > self.max_bytes {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, e)| e.used).map(|(k, _)| k.clone()) else { break };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.bytes;
+            }
+        }
+        self.tick += 1;
+        self.bytes += bytes;
+        self.entries.insert(key, Entry { value, bytes, used: self.tick });
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+/// A function replaced by a wrapper: `(module, attribute, wrapper)`
+pub type Installed = (Py<PyModule>, String, PyObject);
+
+/// The functions replaced by cache wrappers
+static INSTALLED: Mutex<Vec<Installed>> = Mutex::new(Vec::new());
+/// The `fast_math` module, whose functions get cached
+static ROOT: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+
+fn write_len(hasher: &mut DefaultHasher, n: usize) {
+    hasher.write_u64(n as u64);
+}
+
+fn write_str(hasher: &mut DefaultHasher, s: &str) {
+    write_len(hasher, s.len());
+    hasher.write(s.as_bytes());
+}
+
+/// Feed an Arrow array's type, extent and buffers, children included
+fn fingerprint_arrow(hasher: &mut DefaultHasher, data: &ArrayData) {
+    write_str(hasher, &data.data_type().to_string());
+    write_len(hasher, data.offset());
+    write_len(hasher, data.len());
+    for buffer in data.buffers() {
+        write_len(hasher, buffer.len());
+        hasher.write(buffer.as_slice());
+    }
+    if let Some(nulls) = data.nulls() {
+        hasher.write(nulls.buffer().as_slice());
+    }
+    for child in data.child_data() {
+        fingerprint_arrow(hasher, child);
+    }
+}
+
+/// Feed `value` to `hasher`, setting `any_array` if it holds an array; false when it
+/// cannot be fingerprinted by content (object arrays, frames and other Python objects),
+/// so the call must not be cached
+fn fingerprint(hasher: &mut DefaultHasher, value: &Bound<'_, PyAny>, any_array: &mut bool) -> PyResult<bool> {
+    if let Ok(array) = value.downcast::<PyUntypedArray>() {
+        let dtype = array.dtype();
+        if dtype.kind() == b'O' {
+            return Ok(false);
+        }
+        if !array.is_c_contiguous() {
+            let contiguous = value.py().import_bound("numpy")?.call_method1("ascontiguousarray", (value,))?;
+            return fingerprint(hasher, &contiguous, any_array);
+        }
+        write_str(hasher, "ndarray");
+        write_str(hasher, &dtype.getattr("str")?.extract::<String>()?);
+        for &dim in array.shape() {
+            write_len(hasher, dim);
+        }
+        let n_bytes = array.len() * dtype.itemsize();
+        // Safety: the array is C-contiguous, so its data is `len * itemsize` bytes from the
+        // data pointer, and it stays alive and unmodified while we hold the GIL
+        let data = unsafe { std::slice::from_raw_parts((*array.as_array_ptr()).data as *const u8, n_bytes) };
+        hasher.write(data);
+        *any_array = true;
+        return Ok(true);
+    }
+    if value.is_none()
+        || value.is_instance_of::<PyBool>()
+        || value.is_instance_of::<PyLong>()
+        || value.is_instance_of::<PyFloat>()
+        || value.is_instance_of::<PyString>()
+        || value.is_instance_of::<PyBytes>()
+    {
+        write_str(hasher, &value.get_type().name()?);
+        write_str(hasher, &value.repr()?.to_cow()?);
+        return Ok(true);
+    }
+    if let Ok(items) = value.downcast::<PyTuple>() {
+        write_str(hasher, "tuple");
+        return fingerprint_items(hasher, items.iter(), any_array);
+    }
+    if let Ok(items) = value.downcast::<PyList>() {
+        write_str(hasher, "list");
+        return fingerprint_items(hasher, items.iter(), any_array);
+    }
+    if let Ok(dict) = value.downcast::<PyDict>() {
+        write_str(hasher, "dict");
+        for (key, item) in dict.iter() {
+            if !fingerprint(hasher, &key, any_array)? || !fingerprint(hasher, &item, any_array)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+    if let Ok(config) = value.downcast::<FastMathConfig>() {
+        write_str(hasher, "config");
+        write_str(hasher, &config::result_key(Some(&config.borrow())));
+        return Ok(true);
+    }
+    if let Some((array, _)) = column::import_arrow_like(value)? {
+        write_str(hasher, "arrow");
+        fingerprint_arrow(hasher, &array.to_data());
+        *any_array = true;
+        return Ok(true);
+    }
+    if let Some((batch, _)) = column::import_table(value)? {
+        write_str(hasher, "table");
+        write_len(hasher, batch.num_columns());
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            write_str(hasher, field.name());
+            fingerprint_arrow(hasher, &array.to_data());
+        }
+        *any_array = true;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn fingerprint_items<'py>(
+    hasher: &mut DefaultHasher,
+    items: impl ExactSizeIterator<Item = Bound<'py, PyAny>>,
+    any_array: &mut bool
+) -> PyResult<bool> {
+    write_len(hasher, items.len());
+    for item in items {
+        if !fingerprint(hasher, &item, any_array)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Fingerprint of a call, None when it should not be cached: an argument cannot be
+/// fingerprinted, or no argument is an array (parsers, constructors, file readers). The
+/// configured defaults the call would fill in are part of it, so a result computed under
+/// one `FastMathConfig` is not returned under another
+fn call_key(args: &Bound<'_, PyTuple>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Option<u64>> {
+    let mut hasher = DefaultHasher::new();
+    write_str(&mut hasher, &config::result_key(None));
+    let mut any_array = false;
+    for arg in args.iter() {
+        if !fingerprint(&mut hasher, &arg, &mut any_array)? {
+            return Ok(None);
+        }
+    }
+    if let Some(kwargs) = kwargs {
+        // Keyword order does not change the call
+        let mut items: Vec<(String, Bound<'_, PyAny>)> =
+            kwargs.iter().map(|(k, v)| Ok((k.extract()?, v))).collect::<PyResult<_>>()?;
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, value) in items {
+            write_str(&mut hasher, &name);
+            if !fingerprint(&mut hasher, &value, &mut any_array)? {
+                return Ok(None);
+            }
+        }
+    }
+    Ok(any_array.then(|| hasher.finish()))
+}
+
+/// Bytes held by the arrays of a result, looking inside tuples, lists and dicts
+fn result_bytes(value: &Bound<'_, PyAny>) -> usize {
+    if let Ok(array) = value.downcast::<PyUntypedArray>() {
+        return OBJECT_BYTES + array.len() * array.dtype().itemsize();
+    }
+    if let Ok(items) = value.downcast::<PyTuple>() {
+        return OBJECT_BYTES + items.iter().map(|v| result_bytes(&v)).sum::<usize>();
+    }
+    if let Ok(items) = value.downcast::<PyList>() {
+        return OBJECT_BYTES + items.iter().map(|v| result_bytes(&v)).sum::<usize>();
+    }
+    if let Ok(dict) = value.downcast::<PyDict>() {
+        return OBJECT_BYTES + dict.iter().map(|(k, v)| result_bytes(&k) + result_bytes(&v)).sum::<usize>();
+    }
+    OBJECT_BYTES
+}
+
+/// A copy of a stored result, so callers modifying their arrays in place do not change
+/// the cache; objects that cannot be deep-copied are shared
+fn copy_result(py: Python<'_>, value: PyObject) -> PyResult<PyObject> {
+    let copy = py.import_bound("copy")?;
+    Ok(copy.call_method1("deepcopy", (value.bind(py),)).map(|v| v.unbind()).unwrap_or(value))
+}
+
+/// Position of the `out` parameter of `function`, if it has one
+fn out_position(py: Python<'_>, function: &Bound<'_, PyAny>) -> Option<usize> {
+    let signature = py.import_bound("inspect").ok()?.call_method1("signature", (function,)).ok()?;
+    let names: Vec<String> = signature.getattr("parameters").ok()?.call_method0("keys").ok()?.iter().ok()?
+        .map(|name| name.and_then(|n| n.extract()))
+        .collect::<PyResult<_>>()
+        .ok()?;
+    names.iter().position(|name| name == "out")
+}
+
+/// A caching stand-in for one `fast_math` function
+#[pyclass(module = "fast_math.cache")]
+pub struct CachedFunction {
+    kernel: String,
+    pub(crate) inner: PyObject,
+    /// Position of the function's `out` parameter; calls writing into a buffer bypass the
+    /// cache, since a hit would leave the buffer untouched
+    out: Option<usize>,
+}
+
+impl CachedFunction {
+    fn writes_out(&self, args: &Bound<'_, PyTuple>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<bool> {
+        if let Some(out) = kwargs.map(|kwargs| kwargs.get_item("out")).transpose()?.flatten() {
+            return Ok(!out.is_none());
+        }
+        match self.out {
+            Some(i) if i < args.len() => Ok(!args.get_item(i)?.is_none()),
+            _ => Ok(false),
+        }
+    }
+}
+
+#[pymethods]
+impl CachedFunction {
+    #[pyo3(signature = (*args, **kwargs))]
+    fn __call__(&self, py: Python<'_>, args: &Bound<'_, PyTuple>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+        let key = if self.writes_out(args, kwargs)? { None } else { call_key(args, kwargs)? };
+        let Some(fingerprint) = key else {
+            if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+                cache.uncached += 1;
+            }
+            return self.inner.call_bound(py, args, kwargs);
+        };
+        let key = (self.kernel.clone(), fingerprint);
+        let stored = CACHE.lock().unwrap().as_mut().and_then(|cache| cache.get(py, &key));
+        if let Some(value) = stored {
+            return copy_result(py, value);
+        }
+        // Computed without holding the lock, so other threads can use the cache meanwhile
+        let value = self.inner.call_bound(py, args, kwargs)?;
+        let bytes = result_bytes(value.bind(py));
+        // Copied before taking the lock: other threads may run while `deepcopy` executes
+        let stored = copy_result(py, value.clone_ref(py))?;
+        if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+            cache.insert(key, stored, bytes);
+        }
+        Ok(value)
+    }
+
+    /// The original function
+    #[getter]
+    fn __wrapped__(&self, py: Python<'_>) -> PyObject {
+        self.inner.clone_ref(py)
+    }
+
+    fn __getattr__(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        self.inner.getattr(py, name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CachedFunction({})", self.kernel)
+    }
+}
+
+/// Replace the functions of `module` (and, from the root, its submodules other than this
+/// one and `tracing`) by wrappers, except those named in `exclude`, remembering the
+/// wrappers. Functions already wrapped for tracing are wrapped again, so both apply
+fn install(
+    py: Python<'_>,
+    module: &Bound<'_, PyModule>,
+    prefix: &str,
+    exclude: &[String],
+    installed: &mut Vec<Installed>
+) -> PyResult<()> {
+    for name in module.dir().iter() {
+        let name: String = name.extract()?;
+        let value = module.getattr(name.as_str())?;
+        let kernel = format!("{}{}", prefix, name);
+        if value.is_instance_of::<PyCFunction>() || value.is_instance_of::<TracedFunction>() {
+            if exclude.contains(&kernel) || SIDE_EFFECTS.contains(&kernel.as_str()) {
+                continue;
+            }
+            let out = out_position(py, &value);
+            let wrapper = Py::new(py, CachedFunction { kernel, inner: value.clone().unbind(), out })?.into_any();
+            module.setattr(name.as_str(), &wrapper)?;
+            installed.push((module.clone().unbind(), name, wrapper));
+        } else if prefix.is_empty() && name != "cache" && name != "tracing" {
+            if let Ok(submodule) = value.downcast::<PyModule>() {
+                install(py, submodule, &format!("{}.", name), exclude, installed)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply `f` to the function a cache or tracing wrapper calls; None for other values
+fn with_inner<R>(value: &Bound<'_, PyAny>, f: impl FnOnce(&mut PyObject) -> R) -> PyResult<Option<R>> {
+    if let Ok(cached) = value.downcast::<CachedFunction>() {
+        return Ok(Some(f(&mut cached.try_borrow_mut()?.inner)));
+    }
+    if let Ok(traced) = value.downcast::<TracedFunction>() {
+        return Ok(Some(f(&mut traced.try_borrow_mut()?.inner)));
+    }
+    Ok(None)
+}
+
+/// Take `wrapper` out, putting the function it calls in its place. When the cache or
+/// tracing has wrapped the function again since, `wrapper` is taken out of the middle of
+/// the chain and the later wrapper kept; a function the user has reassigned is left alone
+pub fn restore(py: Python<'_>, (module, name, wrapper): Installed) -> PyResult<()> {
+    let wrapper = wrapper.bind(py);
+    let Some(original) = with_inner(wrapper, |inner| inner.clone_ref(py))? else { return Ok(()) };
+    let module = module.bind(py);
+    let mut current = module.getattr(name.as_str())?;
+    if current.is(wrapper) {
+        return module.setattr(name.as_str(), original);
+    }
+    loop {
+        let next = with_inner(&current, |inner| {
+            if inner.is(wrapper) {
+                *inner = original.clone_ref(py);
+                None
+            } else {
+                Some(inner.clone_ref(py))
+            }
+        })?;
+        match next {
+            Some(Some(next)) => current = next.into_bound(py),
+            _ => return Ok(()),
+        }
+    }
+}
+
+fn uninstall(py: Python<'_>) -> PyResult<()> {
+    for installed in INSTALLED.lock().unwrap().drain(..) {
+        restore(py, installed)?;
+    }
+    Ok(())
+}
+
+/// Cache the results of `fast_math` kernels, so repeated calls on the same data return
+/// instantly.
+///
+/// Each call is keyed by the kernel and a fingerprint of its arguments: the dtype, shape
+/// and bytes of numpy arrays (and the buffers of pyarrow arrays and record batches and
+/// of Polars Series and DataFrames), so an array modified in place gets a new key, and
+/// the repr of scalars and strings, along with the `FastMathConfig` settings in effect
+/// other than the thread count. Calls with no array argument (file readers, parsers) or
+/// an argument that cannot be fingerprinted (object arrays, an `OhlcvFrame`, model
+/// objects) are passed through. Calls passing an `out=` buffer and functions that write
+/// files (`write_parquet_rust`, `write_ticks_rust`, `export_blotter_rust`) always run.
+/// Hits return a copy of the stored result. At most `max_entries` results and
+/// `max_bytes` bytes of arrays are kept, the least recently used evicted first. Kernels
+/// whose results differ between identical calls, such as simulations run without a
+/// `seed`, should be listed in `exclude` by name ("options.bs_price_rust" for submodule
+/// functions). Functions are cached by replacing the module attributes with
+/// wrappers, so names imported with `from fast_math import ...` before enabling stay
+/// uncached; the wrappers stack with `fast_math.tracing` in either order. Calling again
+/// replaces the limits and `exclude` and empties the cache.
+#[pyfunction]
+#[pyo3(signature = (max_entries=256, max_bytes=1 << 30, exclude=None))]
+fn enable_cache(py: Python<'_>, max_entries: usize, max_bytes: usize, exclude: Option<Vec<String>>) -> PyResult<()> {
+    uninstall(py)?;
+    *CACHE.lock().unwrap() = Some(Cache::new(max_entries, max_bytes));
+    let Some(root) = ROOT.get(py) else { return Ok(()) };
+    let mut installed = INSTALLED.lock().unwrap();
+    install(py, root.bind(py), "", &exclude.unwrap_or_default(), &mut installed)
+}
+
+/// Stop caching: restore the original functions and drop every stored result
+#[pyfunction]
+fn disable_cache(py: Python<'_>) -> PyResult<()> {
+    uninstall(py)?;
+    *CACHE.lock().unwrap() = None;
+    Ok(())
+}
+
+/// Drop every stored result, keeping the cache enabled
+#[pyfunction]
+fn clear_cache() {
+    if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+        cache.clear();
+    }
+}
+
+/// Cache statistics: a dict with `enabled`, the `hits` and `misses` since enabling,
+/// `uncached` calls passed through, the stored `entries` and their `bytes`, and the
+/// `max_entries` and `max_bytes` limits
+#[pyfunction]
+fn cache_info<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+    let result = PyDict::new_bound(py);
+    let guard = CACHE.lock().unwrap();
+    result.set_item("enabled", guard.is_some())?;
+    let empty = Cache::new(0, 0);
+    let cache = guard.as_ref().unwrap_or(&empty);
+    result.set_item("hits", cache.hits)?;
+    result.set_item("misses", cache.misses)?;
+    result.set_item("uncached", cache.uncached)?;
+    result.set_item("entries", cache.entries.len())?;
+    result.set_item("bytes", cache.bytes)?;
+    result.set_item("max_entries", cache.max_entries)?;
+    result.set_item("max_bytes", cache.max_bytes)?;
+    Ok(result)
+}
+
+/// Populate the `fast_math.cache` submodule; `root` is the module whose functions
+/// `enable_cache` wraps
+pub fn register(m: &Bound<'_, PyModule>, root: &Bound<'_, PyModule>) -> PyResult<()> {
+    let _ = ROOT.set(m.py(), root.clone().unbind());
+    m.add_class::<CachedFunction>()?;
+    m.add_function(wrap_pyfunction!(enable_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(disable_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(cache_info, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::Float64Array;
+
+    fn key(n: u64) -> (String, u64) {
+        ("kernel".to_string(), n)
+    }
+
+    fn arrow_key(values: &Float64Array) -> (String, u64) {
+        let mut hasher = DefaultHasher::new();
+        fingerprint_arrow(&mut hasher, &values.to_data());
+        ("kernel".to_string(), hasher.finish())
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted_first() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut cache = Cache::new(2, 100);
+            cache.insert(key(1), py.None(), 10);
+            cache.insert(key(2), py.None(), 10);
+            // Using 1 makes 2 the oldest
+            assert!(cache.get(py, &key(1)).is_some());
+            cache.insert(key(3), py.None(), 10);
+            assert!(cache.get(py, &key(2)).is_none());
+            assert!(cache.get(py, &key(1)).is_some() && cache.get(py, &key(3)).is_some());
+            assert_eq!((cache.hits, cache.misses, cache.bytes), (3, 1, 20));
+
+            // The byte limit evicts too, and a result over it on its own is not kept
+            cache.insert(key(4), py.None(), 85);
+            assert_eq!((cache.entries.len(), cache.bytes), (2, 95));
+            assert!(cache.get(py, &key(1)).is_none() && cache.get(py, &key(3)).is_some());
+            cache.insert(key(5), py.None(), 101);
+            assert!(cache.get(py, &key(5)).is_none() && cache.get(py, &key(4)).is_some());
+        });
+    }
+
+    #[test]
+    fn arrays_changed_in_place_miss() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut cache = Cache::new(16, 1 << 20);
+            let mut values = vec![1.0, 2.0, 3.0];
+            let stored = arrow_key(&Float64Array::from(values.clone()));
+            cache.insert(stored.clone(), 6.0f64.into_py(py), OBJECT_BYTES);
+            assert_eq!(arrow_key(&Float64Array::from(values.clone())), stored);
+            assert!(cache.get(py, &arrow_key(&Float64Array::from(values.clone()))).is_some());
+
+            values[1] = 5.0;
+            assert!(cache.get(py, &arrow_key(&Float64Array::from(values.clone()))).is_none());
+            // Nulls change the key; a slice holding the same values does not
+            assert_ne!(arrow_key(&Float64Array::from(vec![Some(1.0), None, Some(3.0)])), stored);
+            assert_eq!(arrow_key(&Float64Array::from(vec![0.0, 1.0, 2.0, 3.0]).slice(1, 3)), stored);
+            assert_eq!((cache.hits, cache.misses), (1, 1));
+        });
+    }
+
+    #[test]
+    fn calls_writing_into_out_bypass_the_cache() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(py, "def kernel(values, out=None):\n    return values\n", "kernels.py", "kernels")
+                .unwrap();
+            let kernel = module.getattr("kernel").unwrap();
+            let function = CachedFunction { kernel: "kernel".to_string(), inner: kernel.clone().unbind(), out: out_position(py, &kernel) };
+            assert_eq!(function.out, Some(1));
+
+            let buffer = PyList::empty_bound(py).into_any();
+            let args = |items: Vec<Bound<'_, PyAny>>| PyTuple::new_bound(py, items);
+            let one = 1.0f64.into_py(py).into_bound(py);
+            assert!(!function.writes_out(&args(vec![one.clone()]), None).unwrap());
+            assert!(function.writes_out(&args(vec![one.clone(), buffer.clone()]), None).unwrap());
+            assert!(!function.writes_out(&args(vec![one.clone(), py.None().into_bound(py)]), None).unwrap());
+
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("out", &buffer).unwrap();
+            assert!(function.writes_out(&args(vec![one.clone()]), Some(&kwargs)).unwrap());
+            kwargs.set_item("out", py.None()).unwrap();
+            assert!(!function.writes_out(&args(vec![one]), Some(&kwargs)).unwrap());
+        });
+    }
+}
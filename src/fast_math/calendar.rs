@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::csv_reader::NAT;
+use crate::timezone::{self, parse_time, Ambiguous, Nonexistent};
+use crate::threads;
+
+fn parse_date(value: &str) -> PyResult<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid date '{}', expected YYYY-MM-DD", value))
+    })
+}
+
+/// An exchange's regular trading sessions, without pandas-market-calendars.
+///
+/// Sessions run from `open` to `close` local time in `timezone` (an IANA name such as
+/// "America/New_York", so daylight saving is followed) on the `weekdays` (0 = Monday,
+/// default Monday to Friday) that are not `holidays`; on `half_days` they close at
+/// `early_close` instead. Dates are "YYYY-MM-DD" strings and times "HH:MM[:SS]". When
+/// `close` is not after `open` the session is overnight: the session of a trading day
+/// opens the evening before, as on futures exchanges. A session covers `[open, close)`;
+/// a local time skipped by a daylight-saving change moves to the end of the gap.
+/// Timestamps are integer nanoseconds since the epoch, UTC.
+#[pyclass(module = "fast_math")]
+#[derive(Clone)]
+pub struct TradingCalendar {
+    tz: Tz,
+    open: NaiveTime,
+    close: NaiveTime,
+    early_close: NaiveTime,
+    weekdays: [bool; 7],
+    holidays: HashSet<NaiveDate>,
+    half_days: HashSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    fn overnight(&self) -> bool {
+        self.close <= self.open
+    }
+
+    /// UTC nanoseconds of a local time
+    fn instant(&self, local: NaiveDateTime) -> Option<i64> {
+        let utc = timezone::to_utc(&self.tz, local, Ambiguous::Earliest, Nonexistent::ShiftForward);
+        (utc != NAT).then_some(utc)
+    }
+
+    /// Open and close of the session of trading day `date`, if it has one
+    pub fn session(&self, date: NaiveDate) -> Option<(i64, i64)> {
+        if !self.weekdays[date.weekday().num_days_from_monday() as usize] || self.holidays.contains(&date) {
+            return None;
+        }
+        let close = if self.half_days.contains(&date) { self.early_close } else { self.close };
+        let open_date = if self.overnight() { date.pred_opt()? } else { date };
+        Some((self.instant(open_date.and_time(self.open))?, self.instant(date.and_time(close))?))
+    }
+
+    /// The session containing `timestamp`: that of its local date or, overnight, the next
+    pub fn session_at(&self, timestamp: i64) -> Option<(i64, i64)> {
+        let date = self.tz.timestamp_nanos(timestamp).date_naive();
+        [Some(date), date.succ_opt()]
+            .into_iter()
+            .flatten()
+            .filter_map(|d| self.session(d))
+            .find(|&(open, close)| open <= timestamp && timestamp < close)
+    }
+
+    pub fn is_open(&self, timestamp: i64) -> bool {
+        self.session_at(timestamp).is_some()
+    }
+
+    /// Sessions overlapping `[start, end]`, in order
+    pub fn sessions_between(&self, start: i64, end: i64) -> Vec<(i64, i64)> {
+        let first = self.tz.timestamp_nanos(start).date_naive();
+        let last = self.tz.timestamp_nanos(end).date_naive().succ_opt().unwrap_or(NaiveDate::MAX);
+        first
+            .iter_days()
+            .take_while(|&d| d <= last)
+            .filter_map(|d| self.session(d))
+            .filter(|&(open, close)| open <= end && close > start)
+            .collect()
+    }
+}
+
+#[pymethods]
+impl TradingCalendar {
+    #[new]
+    #[pyo3(signature = (
+        timezone="UTC",
+        open="09:30",
+        close="16:00",
+        holidays=Vec::new(),
+        half_days=Vec::new(),
+        early_close="13:00",
+        weekdays=None
+    ))]
+    fn new(
+        timezone: &str,
+        open: &str,
+        close: &str,
+        holidays: Vec<String>,
+        half_days: Vec<String>,
+        early_close: &str,
+        weekdays: Option<Vec<u32>>
+    ) -> PyResult<Self> {
+        let tz = timezone::parse_tz(timezone)?;
+        let weekdays = weekdays.unwrap_or_else(|| (0..5).collect());
+        if weekdays.iter().any(|&d| d > 6) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "weekdays must be 0 (Monday) to 6 (Sunday)"
+            ));
+        }
+        let calendar = TradingCalendar {
+            tz,
+            open: parse_time("open", open)?,
+            close: parse_time("close", close)?,
+            early_close: parse_time("early_close", early_close)?,
+            weekdays: std::array::from_fn(|d| weekdays.contains(&(d as u32))),
+            holidays: holidays.iter().map(|d| parse_date(d)).collect::<PyResult<_>>()?,
+            half_days: half_days.iter().map(|d| parse_date(d)).collect::<PyResult<_>>()?,
+        };
+        let early = calendar.early_close;
+        let valid = if calendar.overnight() {
+            early <= calendar.close || early > calendar.open
+        } else {
+            early > calendar.open && early <= calendar.close
+        };
+        if !valid {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "early_close must fall within the session"
+            ));
+        }
+        Ok(calendar)
+    }
+
+    /// Whether the exchange is in session at `timestamp`
+    #[pyo3(name = "is_open")]
+    fn py_is_open(&self, timestamp: i64) -> bool {
+        self.is_open(timestamp)
+    }
+
+    /// Whether `date` is a trading day
+    fn is_session(&self, date: &str) -> PyResult<bool> {
+        Ok(self.session(parse_date(date)?).is_some())
+    }
+
+    /// `(open, close)` of the session of trading day `date`, None on a non-trading day
+    fn session_bounds(&self, date: &str) -> PyResult<Option<(i64, i64)>> {
+        Ok(self.session(parse_date(date)?))
+    }
+
+    /// The trading days from `start` to `end` inclusive, as a dict of their `date`
+    /// strings and `open` and `close` timestamps
+    fn sessions<'py>(&self, py: Python<'py>, start: &str, end: &str) -> PyResult<Bound<'py, PyDict>> {
+        let (start, end) = (parse_date(start)?, parse_date(end)?);
+        let (mut dates, mut opens, mut closes) = (Vec::new(), Vec::new(), Vec::new());
+        for date in start.iter_days().take_while(|d| *d <= end) {
+            if let Some((open, close)) = self.session(date) {
+                dates.push(date.format("%Y-%m-%d").to_string());
+                opens.push(open);
+                closes.push(close);
+            }
+        }
+        let result = PyDict::new_bound(py);
+        result.set_item("date", dates)?;
+        result.set_item("open", PyArray1::from_vec_bound(py, opens))?;
+        result.set_item("close", PyArray1::from_vec_bound(py, closes))?;
+        Ok(result)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TradingCalendar(timezone={}, open={}, close={}, holidays={}, half_days={})",
+            self.tz.name(),
+            self.open,
+            self.close,
+            self.holidays.len(),
+            self.half_days.len()
+        )
+    }
+}
+
+/// Indices of the `timestamps` that fall within a session of `calendar`.
+///
+/// Drops pre- and post-market prints, weekends, holidays and the time after a half
+/// day's early close, so session-aware indicators and bar builders see regular trading
+/// hours only. Timestamps are integer nanoseconds since the epoch, UTC, in any order;
+/// the indices are ascending.
+#[pyfunction]
+pub fn filter_sessions_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    calendar: PyRef<'py, TradingCalendar>
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    let timestamps = timestamps.values()?;
+    let calendar = calendar.clone();
+    let indices: Vec<i64> = py.allow_threads(|| {
+        threads::install(|| {
+            timestamps
+                .par_iter()
+                .enumerate()
+                .filter(|&(_, &t)| calendar.is_open(t))
+                .map(|(i, _)| i as i64)
+                .collect()
+        })
+    });
+    Ok(PyArray1::from_vec_bound(py, indices))
+}
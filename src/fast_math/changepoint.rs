@@ -0,0 +1,209 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Symmetric CUSUM filter: indices where the running sum of up (or down) moves since the
+/// last event exceeds `threshold`
+fn cusum_filter(values: &[f64], threshold: f64) -> Vec<i64> {
+    let (mut up, mut down) = (0.0f64, 0.0f64);
+    let mut events = Vec::new();
+    for (t, w) in values.windows(2).enumerate() {
+        let change = w[1] - w[0];
+        if change.is_nan() {
+            continue;
+        }
+        up = (up + change).max(0.0);
+        down = (down + change).min(0.0);
+        if up > threshold {
+            up = 0.0;
+            events.push(t as i64 + 1);
+        } else if down < -threshold {
+            down = 0.0;
+            events.push(t as i64 + 1);
+        }
+    }
+    events
+}
+
+/// Symmetric CUSUM filter of `data` for event sampling, as in López de Prado's *Advances
+/// in Financial Machine Learning* (section 2.5.2.1).
+///
+/// Tracks the cumulative up and down changes of `data` (e.g. log prices), each reset to 0
+/// whenever it moves against its direction, and emits an event whenever either exceeds
+/// `threshold` in magnitude, resetting it. Returns the indices of the events, sampling
+/// bars after a move of about `threshold` rather than at a fixed frequency; pass them as
+/// `events` to `triple_barrier_rust`. NaN changes are skipped.
+#[pyfunction]
+pub fn cusum_filter_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    threshold: f64
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    if threshold.is_nan() || threshold <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "threshold must be > 0"
+        ));
+    }
+    let values = data.values()?;
+    let events = py.allow_threads(|| cusum_filter(&values, threshold));
+    Ok(PyArray1::from_vec_bound(py, events))
+}
+
+/// Segment cost minimised by the change-point search
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cost {
+    /// Squared deviations from the segment mean, for shifts in level
+    Mean,
+    /// Gaussian negative log-likelihood with the segment's own mean and variance, for
+    /// shifts in level or volatility
+    Normal,
+}
+
+impl Cost {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mean" | "l2" => Ok(Cost::Mean),
+            "normal" => Ok(Cost::Normal),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown cost '{}', expected 'mean' or 'normal'", name
+            ))),
+        }
+    }
+
+    /// Parameters each change point adds, its location and the new segment's fit, which
+    /// the default BIC-style penalty charges for
+    fn params(self) -> f64 {
+        match self {
+            Cost::Mean => 2.0,
+            Cost::Normal => 3.0,
+        }
+    }
+}
+
+/// Segment costs of a series in O(1) each, from prefix sums of its values and squares
+struct Segments {
+    sums: Vec<f64>,
+    squares: Vec<f64>,
+    cost: Cost,
+    /// Variance the mean cost is scaled by, so it is on the log-likelihood scale
+    scale: f64,
+    /// Smallest variance a normal segment may have, so constant stretches keep a finite cost
+    floor: f64,
+}
+
+impl Segments {
+    fn new(values: &[f64], cost: Cost) -> Self {
+        let mut sums = vec![0.0];
+        let mut squares = vec![0.0];
+        for v in values {
+            sums.push(sums[sums.len() - 1] + v);
+            squares.push(squares[squares.len() - 1] + v * v);
+        }
+        let n = values.len() as f64;
+        let variance = (squares[values.len()] / n - (sums[values.len()] / n).powi(2)).max(0.0);
+        // Noise level from the first differences, which level shifts barely affect
+        let mut changes: Vec<f64> = values.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+        let scale = if changes.is_empty() {
+            0.0
+        } else {
+            let middle = changes.len() / 2;
+            let (_, median, _) = changes.select_nth_unstable_by(middle, f64::total_cmp);
+            (*median / (0.6745 * std::f64::consts::SQRT_2)).powi(2)
+        };
+        let scale = if scale > 0.0 { scale } else if variance > 0.0 { variance } else { 1.0 };
+        Segments { sums, squares, cost, scale, floor: (variance * 1e-12).max(f64::MIN_POSITIVE) }
+    }
+
+    /// Cost of the segment `start..end`
+    fn cost(&self, start: usize, end: usize) -> f64 {
+        let n = (end - start) as f64;
+        let sum = self.sums[end] - self.sums[start];
+        let deviations = (self.squares[end] - self.squares[start] - sum * sum / n).max(0.0);
+        match self.cost {
+            Cost::Mean => deviations / self.scale,
+            Cost::Normal => n * (deviations / n).max(self.floor).ln(),
+        }
+    }
+}
+
+/// Optimal partition by PELT: dynamic programming over the last change point, dropping
+/// candidates that can no longer start the optimal last segment
+fn pelt(values: &[f64], cost: Cost, penalty: f64, min_size: usize) -> Vec<i64> {
+    let n = values.len();
+    if n < 2 * min_size {
+        return Vec::new();
+    }
+    let segments = Segments::new(values, cost);
+    let mut best = vec![f64::INFINITY; n + 1];
+    let mut previous = vec![0usize; n + 1];
+    best[0] = -penalty;
+    let mut candidates = vec![0usize];
+    for end in min_size..=n {
+        // A change point becomes usable once a whole segment fits after it
+        if end >= 2 * min_size {
+            candidates.push(end - min_size);
+        }
+        let totals: Vec<f64> = candidates.iter().map(|&s| best[s] + segments.cost(s, end)).collect();
+        let (arg, total) = totals
+            .iter()
+            .enumerate()
+            .fold((0, f64::INFINITY), |(arg, low), (i, &t)| if t < low { (i, t) } else { (arg, low) });
+        best[end] = total + penalty;
+        previous[end] = candidates[arg];
+        let bound = best[end];
+        candidates = candidates.iter().zip(&totals).filter(|&(_, &t)| t <= bound).map(|(&s, _)| s).collect();
+    }
+
+    let mut changes = Vec::new();
+    let mut end = n;
+    while previous[end] > 0 {
+        end = previous[end];
+        changes.push(end as i64);
+    }
+    changes.reverse();
+    changes
+}
+
+/// Change points of `data` by PELT (Killick, Fearnhead and Eckley, 2012), for segmenting
+/// a series into regimes.
+///
+/// Finds the partition minimising the total segment cost plus `penalty` per change point,
+/// exactly, in close to linear time. `cost="normal"` (the default) fits each segment its
+/// own mean and variance, so it catches volatility regimes in returns as well as level
+/// shifts; "mean" only looks for shifts in level, scaling squared deviations by a noise
+/// variance estimated from the median absolute first difference. `penalty` defaults to
+/// the BIC-style `params * ln(n)`, counting each change point's location and the new
+/// segment's mean and (for "normal") variance; raise it for fewer changes. Segments hold
+/// at least `min_size` values. Returns the index at which each new segment starts,
+/// ascending; no change points give an empty array. Raises `NaNInputError` on NaN input.
+#[pyfunction]
+#[pyo3(signature = (data, penalty=None, cost="normal", min_size=2))]
+pub fn pelt_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    penalty: Option<f64>,
+    cost: &str,
+    min_size: usize
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    let cost = Cost::parse(cost)?;
+    if min_size == 0 {
+        return Err(crate::errors::InvalidWindowError::new_err(
+            "min_size must be > 0"
+        ));
+    }
+    let values = data.values()?;
+    if values.iter().any(|v| v.is_nan()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "Change-point input contains NaN"
+        ));
+    }
+    let penalty = penalty.unwrap_or_else(|| cost.params() * (values.len().max(1) as f64).ln());
+    if penalty.is_nan() || penalty < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "penalty must be >= 0"
+        ));
+    }
+    let changes = py.allow_threads(|| pelt(&values, cost, penalty, min_size));
+    Ok(PyArray1::from_vec_bound(py, changes))
+}
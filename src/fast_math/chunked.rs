@@ -0,0 +1,86 @@
+use pyo3::prelude::*;
+
+use crate::column::Column;
+
+/// Rolling indicators that can be computed chunk by chunk
+#[derive(Clone, Copy)]
+enum Indicator {
+    MovingAverage(usize),
+    Rsi(usize),
+}
+
+impl Indicator {
+    /// Values that must be carried over from earlier chunks
+    fn history(self) -> usize {
+        match self {
+            Indicator::MovingAverage(window) => window - 1,
+            Indicator::Rsi(period) => period,
+        }
+    }
+
+    fn compute(self, values: &[f64]) -> PyResult<Vec<f64>> {
+        match self {
+            Indicator::MovingAverage(window) => crate::moving_average(values, window),
+            Indicator::Rsi(period) => crate::rsi(values, period),
+        }
+    }
+}
+
+/// Computes a rolling indicator over data that arrives in sequential chunks (e.g. from a
+/// Parquet scan), carrying the window state across chunk boundaries.
+///
+/// `update` returns the values completed by each chunk, so concatenating the outputs
+/// gives the same result as running the indicator on the whole series at once.
+/// Supported indicators are "sma" (`window`) and "rsi" (`window` = period).
+#[pyclass(module = "fast_math")]
+pub struct ChunkedProcessor {
+    indicator: Indicator,
+    tail: Vec<f64>,
+    processed: usize,
+}
+
+#[pymethods]
+impl ChunkedProcessor {
+    #[new]
+    fn new(indicator: &str, window: usize) -> PyResult<Self> {
+        crate::errors::require_window(window)?;
+        let indicator = match indicator {
+            "sma" | "moving_average" => Indicator::MovingAverage(window),
+            "rsi" => Indicator::Rsi(window),
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown indicator '{}', expected 'sma' or 'rsi'", other
+                )))
+            }
+        };
+        Ok(ChunkedProcessor { indicator, tail: Vec::new(), processed: 0 })
+    }
+
+    /// Feed the next chunk and return the indicator values it completes
+    fn update<'py>(&mut self, py: Python<'py>, chunk: Column<'py, f64>) -> PyResult<PyObject> {
+        let kind = chunk.kind();
+        let chunk = chunk.values()?;
+
+        let mut values = std::mem::take(&mut self.tail);
+        values.extend_from_slice(&chunk);
+        let result = self.indicator.compute(&values)?;
+
+        let keep = values.len().min(self.indicator.history());
+        values.drain(..values.len() - keep);
+        self.tail = values;
+        self.processed += chunk.len();
+        kind.wrap(py, result)
+    }
+
+    /// Forget all carried state
+    fn reset(&mut self) {
+        self.tail.clear();
+        self.processed = 0;
+    }
+
+    /// Number of input values seen so far
+    #[getter]
+    fn processed(&self) -> usize {
+        self.processed
+    }
+}
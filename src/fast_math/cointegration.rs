@@ -0,0 +1,355 @@
+use pyo3::prelude::*;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{s, Array1, Array2, ArrayView2, Axis};
+
+use crate::adf::{self, Autolag, Regression, Surface};
+use crate::column::Column;
+use crate::linalg;
+
+/// MacKinnon's (1994) response surface of the Engle-Granger statistic for two series with
+/// a constant in the cointegrating regression
+const ENGLE_GRANGER: Surface = Surface {
+    max: 0.92,
+    min: -18.86,
+    star: -2.62,
+    small: [2.92, 1.5012, 0.039796],
+    large: [2.1945, 0.64695, -0.29198, -0.042377],
+};
+
+/// Engle-Granger cointegration test of two series, like statsmodels' `coint(y, x)`.
+///
+/// Regresses `y` on a constant and `x`, then runs an ADF test without deterministic terms
+/// on the residuals, choosing up to `max_lag` lagged differences (default
+/// `12 * (n / 100) ** 0.25`) by `autolag` as `adf_test_rust` does. Returns
+/// `(statistic, p_value, hedge_ratio)`: the p-value comes from MacKinnon's response
+/// surface for two variables, so a small one suggests the pair is cointegrated, and the
+/// hedge ratio is the slope of `y` on `x`. NaN-holding inputs, a constant `x` or a perfect
+/// fit give NaN.
+#[pyfunction]
+#[pyo3(signature = (x, y, max_lag=None, autolag=Some("aic")))]
+pub fn engle_granger_rust<'py>(
+    py: Python<'py>,
+    x: Column<'py, f64>,
+    y: Column<'py, f64>,
+    max_lag: Option<usize>,
+    autolag: Option<&str>
+) -> PyResult<(f64, f64, f64)> {
+    let autolag = Autolag::parse(autolag)?;
+    let (x, y) = (x.values()?, y.values()?);
+    if x.len() != y.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "Arrays must have the same length"
+        ));
+    }
+    let max_lag = adf::max_lag_for(x.len(), Regression::None, max_lag)?;
+
+    Ok(py.allow_threads(|| {
+        let fit = match linalg::ols(vec![vec![1.0; x.len()], x.to_vec()], y.to_vec()) {
+            Some(fit) => fit,
+            None => return (f64::NAN, f64::NAN, f64::NAN),
+        };
+        let (intercept, hedge_ratio) = (fit.params[0], fit.params[1]);
+        let residuals: Vec<f64> = x.iter().zip(y.iter()).map(|(x, y)| y - intercept - hedge_ratio * x).collect();
+        let (statistic, _) = adf::statistic(&residuals, max_lag, Regression::None, autolag);
+        (statistic, ENGLE_GRANGER.p_value(statistic), hedge_ratio)
+    }))
+}
+
+/// Residuals of the columns of `y` regressed on the columns of `z`; None when `z` is rank
+/// deficient
+fn residualize(y: &Array2<f64>, z: &Array2<f64>) -> Option<Array2<f64>> {
+    if z.ncols() == 0 {
+        return Some(y.clone());
+    }
+    let columns: Vec<Vec<f64>> = z.columns().into_iter().map(|c| c.to_vec()).collect();
+    let mut residuals = y.clone();
+    for mut column in residuals.columns_mut() {
+        let fit = linalg::ols(columns.clone(), column.to_vec())?;
+        column -= &z.dot(&Array1::from(fit.params));
+    }
+    Some(residuals)
+}
+
+/// `y` with a polynomial time trend of degree `order` removed; `order = -1` leaves it as is
+fn detrend(y: &Array2<f64>, order: i32) -> Option<Array2<f64>> {
+    let t = y.nrows();
+    let mut trend = Array2::zeros((t, (order + 1).max(0) as usize));
+    for (degree, mut column) in trend.columns_mut().into_iter().enumerate() {
+        column.iter_mut().enumerate().for_each(|(i, v)| *v = (i as f64).powi(degree as i32));
+    }
+    residualize(y, &trend)
+}
+
+/// 90%, 95% and 99% critical values of the Johansen trace statistic for 1 to 12 series
+/// (Osterwald-Lenum (1992), as tabulated by statsmodels), for `det_order` -1, 0 and 1
+const TRACE_CRITICAL: [[[f64; 3]; 12]; 3] = [
+    [
+        [2.9762, 4.1296, 6.9406],
+        [10.4741, 12.3212, 16.3640],
+        [21.7781, 24.2761, 29.5147],
+        [37.0339, 40.1749, 46.5716],
+        [56.2839, 60.0627, 67.6367],
+        [79.5329, 83.9383, 92.7136],
+        [106.7351, 111.7797, 121.7375],
+        [137.9954, 143.6691, 154.7977],
+        [173.2292, 179.5199, 191.8122],
+        [212.4721, 219.4051, 232.8291],
+        [255.6732, 263.2603, 277.9962],
+        [302.9054, 311.1288, 326.9716],
+    ],
+    [
+        [2.7055, 3.8415, 6.6349],
+        [13.4294, 15.4943, 19.9349],
+        [27.0669, 29.7961, 35.4628],
+        [44.4929, 47.8545, 54.6815],
+        [65.8202, 69.8189, 77.8202],
+        [91.1090, 95.7542, 104.9637],
+        [120.3673, 125.6185, 135.9825],
+        [153.6341, 159.5290, 171.0905],
+        [190.8714, 197.3772, 210.0366],
+        [232.1030, 239.2468, 253.2526],
+        [277.3740, 285.1402, 300.2821],
+        [326.5354, 334.9795, 351.2150],
+    ],
+    [
+        [2.7055, 3.8415, 6.6349],
+        [16.1619, 18.3985, 23.1485],
+        [32.0645, 35.0116, 41.0815],
+        [51.6492, 55.2459, 62.5202],
+        [75.1027, 79.3422, 87.7748],
+        [102.4674, 107.3429, 116.9829],
+        [133.7852, 139.2780, 150.0778],
+        [169.0618, 175.1584, 187.1891],
+        [208.3582, 215.1268, 228.2226],
+        [251.6293, 259.0267, 273.3838],
+        [298.8836, 306.8988, 322.4264],
+        [350.1125, 358.7190, 375.3203],
+    ],
+];
+
+/// Critical values of the Johansen maximum eigenvalue statistic, laid out as
+/// `TRACE_CRITICAL`
+const MAX_EIG_CRITICAL: [[[f64; 3]; 12]; 3] = [
+    [
+        [2.9762, 4.1296, 6.9406],
+        [9.4748, 11.2246, 15.0923],
+        [15.7175, 17.7961, 22.2526],
+        [21.8370, 24.1592, 29.0609],
+        [27.9160, 30.4428, 35.7359],
+        [33.9271, 36.6301, 42.2333],
+        [39.9085, 42.7679, 48.6606],
+        [45.8930, 48.8795, 55.0335],
+        [51.8528, 54.9629, 61.3449],
+        [57.7954, 61.0404, 67.6415],
+        [63.7248, 67.0756, 73.8856],
+        [69.6513, 73.0946, 80.0937],
+    ],
+    [
+        [2.7055, 3.8415, 6.6349],
+        [12.2971, 14.2639, 18.5200],
+        [18.8928, 21.1314, 25.8650],
+        [25.1236, 27.5858, 32.7172],
+        [31.2379, 33.8777, 39.3693],
+        [37.2786, 40.0763, 45.8662],
+        [43.2947, 46.2299, 52.3069],
+        [49.2855, 52.3622, 58.6634],
+        [55.2412, 58.4332, 64.9960],
+        [61.2041, 64.5040, 71.2525],
+        [67.1307, 70.5392, 77.4877],
+        [73.0563, 76.5734, 83.7105],
+    ],
+    [
+        [2.7055, 3.8415, 6.6349],
+        [15.0006, 17.1481, 21.7465],
+        [21.8731, 24.2522, 29.2631],
+        [28.2398, 30.8151, 36.1930],
+        [34.4202, 37.1646, 42.8612],
+        [40.5244, 43.4183, 49.4095],
+        [46.5583, 49.5875, 55.8171],
+        [52.5858, 55.7302, 62.1741],
+        [58.5316, 61.8051, 68.5030],
+        [64.5292, 67.9040, 74.7434],
+        [70.4630, 73.9355, 81.0678],
+        [76.4081, 79.9878, 87.2395],
+    ],
+];
+
+/// Critical values for each hypothesis `r` of `neqs` series (the row for `neqs - r`
+/// series), NaN beyond the tables' 12 series
+fn critical_values(table: &[[[f64; 3]; 12]; 3], neqs: usize, det_order: i32) -> Array2<f64> {
+    let table = &table[(det_order + 1) as usize];
+    Array2::from_shape_fn((neqs, 3), |(r, level)| table.get(neqs - r - 1).map_or(f64::NAN, |row| row[level]))
+}
+
+/// Result of a Johansen test, strongest cointegrating relation first
+struct Johansen {
+    eigenvalues: Vec<f64>,
+    trace: Vec<f64>,
+    max_eig: Vec<f64>,
+    eigenvectors: Array2<f64>,
+}
+
+/// Johansen test of the columns of `endog`, following statsmodels' `coint_johansen`; None
+/// when a regression is rank deficient or NaNs reach the moment matrices
+fn johansen(endog: ArrayView2<'_, f64>, det_order: i32, k_ar_diff: usize) -> Option<Johansen> {
+    let (t, neqs) = endog.dim();
+    // Demeaning only, after the trend of `det_order` is taken out of the levels
+    let f = if det_order > -1 { 0 } else { -1 };
+    let x = detrend(&endog.to_owned(), det_order)?;
+    let dx = &x.slice(s![1.., ..]) - &x.slice(s![..-1, ..]);
+
+    let rows = t - 1 - k_ar_diff;
+    let mut z = Array2::zeros((rows, neqs * k_ar_diff));
+    for lag in 1..=k_ar_diff {
+        z.slice_mut(s![.., (lag - 1) * neqs..lag * neqs])
+            .assign(&dx.slice(s![k_ar_diff - lag..k_ar_diff - lag + rows, ..]));
+    }
+    let z = detrend(&z, f)?;
+    let r0t = residualize(&detrend(&dx.slice(s![k_ar_diff.., ..]).to_owned(), f)?, &z)?;
+    let levels = x.slice(s![1..t - k_ar_diff, ..]).to_owned();
+    let rkt = residualize(&detrend(&levels, f)?, &z)?;
+
+    let n = rows as f64;
+    let skk = rkt.t().dot(&rkt) / n;
+    let sk0 = rkt.t().dot(&r0t) / n;
+    let s00 = r0t.t().dot(&r0t) / n;
+
+    // sig v = λ skk v, with sig = sk0 s00^-1 sk0' kept symmetric through the Cholesky
+    // factors of s00 and skk
+    let l00 = linalg::cholesky(&s00)?;
+    let half = linalg::solve_lower(&l00, &sk0.t().to_owned());
+    let sig = half.t().dot(&half);
+    let lkk = linalg::cholesky(&skk)?;
+    let reduced = linalg::solve_lower(&lkk, &linalg::solve_lower(&lkk, &sig).t().to_owned());
+    let (values, vectors) = linalg::symmetric_eigen(reduced);
+    // Scaled so that v' skk v = I, as statsmodels does
+    let vectors = linalg::solve_upper(&lkk, &vectors);
+
+    let mut order: Vec<usize> = (0..neqs).collect();
+    order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+    let eigenvalues: Vec<f64> = order.iter().map(|&i| values[i]).collect();
+    let mut eigenvectors = vectors.select(Axis(1), &order);
+    for mut column in eigenvectors.columns_mut() {
+        if column.iter().find(|v| **v != 0.0).is_some_and(|v| *v < 0.0) {
+            column.mapv_inplace(|v| -v);
+        }
+    }
+    let max_eig: Vec<f64> = eigenvalues.iter().map(|l| -n * (1.0 - l).ln()).collect();
+    let trace: Vec<f64> = (0..neqs).map(|i| max_eig[i..].iter().sum()).collect();
+    Some(Johansen { eigenvalues, trace, max_eig, eigenvectors })
+}
+
+/// Johansen cointegration test of the columns of `data_2d` (observations in rows, one
+/// series per column), like statsmodels' `coint_johansen(data_2d, det_order, k_ar_diff)`.
+///
+/// `det_order` is -1 for no deterministic terms, 0 for a constant (the default) or 1 for
+/// a linear trend; `k_ar_diff` is the number of lagged differences in the VECM. Returns
+/// `(eigenvalues, trace, max_eig, eigenvectors, trace_crit, max_eig_crit)` with the
+/// eigenvalues in descending order, `trace[r]` and `max_eig[r]` the statistics for the
+/// hypothesis of at most `r` cointegrating vectors, and the eigenvectors in the matching
+/// columns, normalised as in statsmodels up to sign (the first non-zero weight is made
+/// positive). `trace_crit` and `max_eig_crit` are (n_series, 3) arrays of the 90%, 95%
+/// and 99% critical values for each `r`, statsmodels' `cvt` and `cvm`; they are NaN for
+/// more than 12 series. NaN inputs or collinear series give all-NaN statistics.
+#[pyfunction]
+#[pyo3(signature = (data_2d, k_ar_diff=1, det_order=0))]
+pub fn johansen_rust<'py>(
+    py: Python<'py>,
+    data_2d: PyReadonlyArray2<'py, f64>,
+    k_ar_diff: usize,
+    det_order: i32
+) -> PyResult<PyObject> {
+    if !(-1..=1).contains(&det_order) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "det_order must be -1, 0 or 1"
+        ));
+    }
+    let endog = data_2d.as_array();
+    let (t, neqs) = endog.dim();
+    // Every regression needs more rows than regressors
+    let regressors = neqs * k_ar_diff + 1;
+    if neqs == 0 || t < k_ar_diff + 2 || t - 1 - k_ar_diff <= regressors.max(neqs) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} observations are too few for a Johansen test of {} series with k_ar_diff={}", t, neqs, k_ar_diff
+        )));
+    }
+
+    let result = py.allow_threads(|| johansen(endog, det_order, k_ar_diff)).unwrap_or_else(|| {
+        let nan = vec![f64::NAN; neqs];
+        Johansen {
+            eigenvalues: nan.clone(),
+            trace: nan.clone(),
+            max_eig: nan,
+            eigenvectors: Array2::from_elem((neqs, neqs), f64::NAN),
+        }
+    });
+    Ok((
+        PyArray1::from_vec_bound(py, result.eigenvalues),
+        PyArray1::from_vec_bound(py, result.trace),
+        PyArray1::from_vec_bound(py, result.max_eig),
+        PyArray2::from_owned_array_bound(py, result.eigenvectors),
+        PyArray2::from_owned_array_bound(py, critical_values(&TRACE_CRITICAL, neqs, det_order)),
+        PyArray2::from_owned_array_bound(py, critical_values(&MAX_EIG_CRITICAL, neqs, det_order)),
+    )
+        .into_py(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::Float64Array;
+
+    /// A random walk and a series cointegrated with it (`y = 2x + 1 + noise`)
+    fn pair(n: usize) -> (Vec<f64>, Vec<f64>) {
+        let mut state = 11u64;
+        let mut uniform = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let x: Vec<f64> = (0..n).scan(0.0, |x, _| { *x += uniform(); Some(*x) }).collect();
+        let y = x.iter().map(|x| 2.0 * x + 1.0 + 0.2 * uniform()).collect();
+        (x, y)
+    }
+
+    #[test]
+    fn critical_values_follow_statsmodels_rows() {
+        let trace = critical_values(&TRACE_CRITICAL, 2, 0);
+        assert_eq!(trace.row(0).to_vec(), vec![13.4294, 15.4943, 19.9349]);
+        assert_eq!(trace.row(1).to_vec(), vec![2.7055, 3.8415, 6.6349]);
+        let max_eig = critical_values(&MAX_EIG_CRITICAL, 3, 1);
+        assert_eq!(max_eig.row(0).to_vec(), vec![21.8731, 24.2522, 29.2631]);
+        assert_eq!(critical_values(&TRACE_CRITICAL, 1, -1).row(0).to_vec(), vec![2.9762, 4.1296, 6.9406]);
+        let wide = critical_values(&TRACE_CRITICAL, 13, 0);
+        assert!(wide.row(0).iter().all(|v| v.is_nan()));
+        assert_eq!(wide.row(12).to_vec(), vec![2.7055, 3.8415, 6.6349]);
+    }
+
+    #[test]
+    fn johansen_finds_one_relation_in_a_cointegrated_pair() {
+        let (x, y) = pair(400);
+        let data = Array2::from_shape_fn((400, 2), |(t, j)| if j == 0 { y[t] } else { x[t] });
+        let result = johansen(data.view(), 0, 1).unwrap();
+        let crit = critical_values(&TRACE_CRITICAL, 2, 0);
+        assert!(result.trace[0] > crit[[0, 2]]);
+        assert!(result.trace[1] < crit[[1, 1]]);
+        assert!((result.trace[0] - result.max_eig[0] - result.max_eig[1]).abs() < 1e-9);
+        assert!(result.eigenvalues[0] > result.eigenvalues[1]);
+        // The strongest relation is y - 2x
+        let v = result.eigenvectors.column(0);
+        assert!((v[1] / v[0] + 2.0).abs() < 0.05, "{}", v[1] / v[0]);
+    }
+
+    #[test]
+    fn engle_granger_recovers_the_hedge_ratio() {
+        assert!((ENGLE_GRANGER.p_value(-3.3377) - 0.05).abs() < 0.01);
+        let (x, y) = pair(400);
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let column = |v: Vec<f64>| Column::Arrow(Float64Array::from(v));
+            let (statistic, p, hedge) = engle_granger_rust(py, column(x), column(y), None, Some("aic")).unwrap();
+            assert!(statistic < -4.0 && p < 0.01);
+            assert!((hedge - 2.0).abs() < 0.01);
+        });
+    }
+}
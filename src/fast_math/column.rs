@@ -0,0 +1,650 @@
+use std::borrow::Cow;
+
+use pyo3::prelude::*;
+use numpy::{Element, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView2, Axis, CowArray, Ix2};
+use rayon::prelude::*;
+use arrow::array::{make_array, Array, ArrayData, ArrayRef, AsArray, PrimitiveArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use arrow::datatypes::{ArrowNativeType, ArrowPrimitiveType, Float32Type, Float64Type, Int64Type, Int8Type};
+use num_traits::Float;
+use arrow::pyarrow::{FromPyArrow, ToPyArrow};
+
+use crate::frame::OhlcvFrame;
+use crate::pool::{Buffer, Poolable};
+use crate::{simd, threads};
+
+/// Element types that can cross the boundary as either numpy or Arrow columns
+pub trait ColumnElement: Element + ArrowNativeType {
+    type ArrowType: ArrowPrimitiveType<Native = Self>;
+
+    /// Value substituted for Arrow nulls, or `None` if nulls are rejected
+    const NULL_FILL: Option<Self>;
+}
+
+impl ColumnElement for f64 {
+    type ArrowType = Float64Type;
+    const NULL_FILL: Option<f64> = Some(f64::NAN);
+}
+
+impl ColumnElement for f32 {
+    type ArrowType = Float32Type;
+    const NULL_FILL: Option<f32> = Some(f32::NAN);
+}
+
+impl ColumnElement for i64 {
+    type ArrowType = Int64Type;
+    const NULL_FILL: Option<i64> = None;
+}
+
+impl ColumnElement for i8 {
+    type ArrowType = Int8Type;
+    const NULL_FILL: Option<i8> = None;
+}
+
+/// Floating-point element types the series kernels are generic over
+pub trait FloatElement: ColumnElement + Float + Poolable + std::iter::Sum + Send + Sync {
+    /// Sum of `values`, SIMD-accelerated where the CPU supports it
+    fn sum_slice(values: &[Self]) -> Self;
+
+    /// Sum of `values` accumulated in f64, so float32 inputs keep full precision
+    fn sum_f64(values: &[Self]) -> f64;
+}
+
+impl FloatElement for f64 {
+    fn sum_slice(values: &[f64]) -> f64 {
+        simd::sum_f64(values)
+    }
+
+    fn sum_f64(values: &[f64]) -> f64 {
+        simd::sum_f64(values)
+    }
+}
+
+impl FloatElement for f32 {
+    fn sum_slice(values: &[f32]) -> f32 {
+        simd::sum_f32(values)
+    }
+
+    fn sum_f64(values: &[f32]) -> f64 {
+        values.iter().map(|&v| f64::from(v)).sum()
+    }
+}
+
+/// Where a kernel's inputs came from, and therefore what its outputs are returned as
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArrayKind {
+    Numpy,
+    Arrow,
+    Polars,
+}
+
+impl ArrayKind {
+    /// Polars if any input was a Polars Series, else Arrow if any was an Arrow array, else numpy
+    pub fn of(kinds: &[ArrayKind]) -> ArrayKind {
+        if kinds.contains(&ArrayKind::Polars) {
+            ArrayKind::Polars
+        } else if kinds.contains(&ArrayKind::Arrow) {
+            ArrayKind::Arrow
+        } else {
+            ArrayKind::Numpy
+        }
+    }
+
+    /// Hand a result vector to Python without copying it
+    pub fn wrap<T: ColumnElement>(self, py: Python<'_>, values: Vec<T>) -> PyResult<PyObject> {
+        match self {
+            ArrayKind::Numpy => Ok(PyArray1::from_vec_bound(py, values).into_any().unbind()),
+            ArrayKind::Arrow => {
+                let array = PrimitiveArray::<T::ArrowType>::new(values.into(), None);
+                array.into_data().to_pyarrow(py)
+            }
+            ArrayKind::Polars => {
+                let array = ArrayKind::Arrow.wrap(py, values)?;
+                let series = py.import_bound("polars")?.call_method1("from_arrow", (array,))?;
+                Ok(series.unbind())
+            }
+        }
+    }
+}
+
+/// A 1D input column: a numpy array, a pyarrow `Array` / `ChunkedArray` or a Polars
+/// `Series`, the latter two imported through the Arrow C data interface. An `OhlcvFrame`
+/// stands for its close column. int64 columns also accept `datetime64` arrays and Arrow
+/// timestamps, as nanoseconds since the epoch.
+pub enum Column<'py, T: ColumnElement> {
+    Numpy(PyReadonlyArray1<'py, T>),
+    Arrow(PrimitiveArray<T::ArrowType>),
+    Polars(PrimitiveArray<T::ArrowType>),
+}
+
+/// Whether `ob` is an instance of the Polars class `name`
+fn is_polars(ob: &Bound<'_, PyAny>, name: &str) -> PyResult<bool> {
+    let ty = ob.get_type();
+    let module: String = ty.getattr("__module__")?.extract()?;
+    Ok(module.starts_with("polars") && ty.name()? == name)
+}
+
+fn is_polars_series(ob: &Bound<'_, PyAny>) -> PyResult<bool> {
+    is_polars(ob, "Series")
+}
+
+/// Whether `ob` is a pyarrow `RecordBatch` or `Table`, which also export
+/// `__arrow_c_array__` / `_export_to_c` but hold several columns
+fn is_arrow_table(ob: &Bound<'_, PyAny>) -> PyResult<bool> {
+    Ok(ob.hasattr("column_names")? && ob.hasattr("schema")?)
+}
+
+/// Import a pyarrow `RecordBatch` or `Table` or a Polars `DataFrame` as one record batch;
+/// `None` for anything else. A batch crosses the C data interface without copying; a table
+/// is only copied when it has several chunks, and a DataFrame goes through `to_arrow`
+pub fn import_table(ob: &Bound<'_, PyAny>) -> PyResult<Option<(RecordBatch, ArrayKind)>> {
+    if is_polars(ob, "DataFrame")? {
+        let Some((batch, _)) = import_table(&ob.call_method0("to_arrow")?)? else { return Ok(None) };
+        return Ok(Some((batch, ArrayKind::Polars)));
+    }
+    if !is_arrow_table(ob)? {
+        return Ok(None);
+    }
+    if !ob.hasattr("to_batches")? {
+        return Ok(Some((RecordBatch::from_pyarrow_bound(ob)?, ArrayKind::Arrow)));
+    }
+    let batches = ob.call_method0("combine_chunks")?.call_method0("to_batches")?;
+    let batch = match batches.len()? {
+        0 => RecordBatch::new_empty(std::sync::Arc::new(Schema::from_pyarrow_bound(&ob.getattr("schema")?)?)),
+        _ => RecordBatch::from_pyarrow_bound(&batches.get_item(0)?)?,
+    };
+    Ok(Some((batch, ArrayKind::Arrow)))
+}
+
+/// Import a Polars `Series`, a pyarrow `Array` / `ChunkedArray`, or anything exporting
+/// `__arrow_c_array__` as an Arrow array; `None` if the object is none of these (record
+/// batches and tables included)
+pub fn import_arrow_like(ob: &Bound<'_, PyAny>) -> PyResult<Option<(ArrayRef, ArrayKind)>> {
+    if is_arrow_table(ob)? {
+        return Ok(None);
+    }
+    let (ob, kind) = if is_polars_series(ob)? {
+        // A single-chunk Series exports its buffers to pyarrow without copying
+        (ob.call_method0("to_arrow")?, ArrayKind::Polars)
+    } else if ob.hasattr("__arrow_c_array__")? || ob.hasattr("_export_to_c")? || ob.hasattr("combine_chunks")? {
+        (ob.clone(), ArrayKind::Arrow)
+    } else {
+        return Ok(None);
+    };
+    // Chunked arrays are only copied when they actually consist of several chunks
+    let ob = if ob.hasattr("combine_chunks")? { ob.call_method0("combine_chunks")? } else { ob };
+    Ok(Some((make_array(ArrayData::from_pyarrow_bound(&ob)?), kind)))
+}
+
+/// View a numpy / pandas `datetime64` column (any unit) as int64 nanoseconds since the
+/// epoch, NaT becoming `i64::MIN`; `None` for anything else
+fn datetime64_as_ns<'py>(ob: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let Ok(dtype) = ob.getattr("dtype") else { return Ok(None) };
+    if !dtype.getattr("kind").is_ok_and(|kind| kind.eq("M").unwrap_or(false)) {
+        return Ok(None);
+    }
+    // Already-ns arrays are viewed without copying; other units are converted once
+    let numpy = ob.py().import_bound("numpy")?;
+    let ns = numpy.call_method1("asarray", (ob, "datetime64[ns]"))?;
+    Ok(Some(ns.call_method1("view", ("int64",))?))
+}
+
+/// Cast an imported Arrow array to `T` unless it already has that type; timestamps of
+/// any unit become nanoseconds first
+fn cast_primitive<T: ColumnElement>(array: &ArrayRef) -> PyResult<PrimitiveArray<T::ArrowType>> {
+    let target = <T::ArrowType as ArrowPrimitiveType>::DATA_TYPE;
+    if array.data_type() == &target {
+        return Ok(array.as_primitive::<T::ArrowType>().clone());
+    }
+    let cast = |array: &ArrayRef, to: &DataType| {
+        arrow::compute::cast(array, to).map_err(|e| pyo3::exceptions::PyTypeError::new_err(e.to_string()))
+    };
+    let array = match array.data_type() {
+        DataType::Timestamp(unit, _) if *unit != TimeUnit::Nanosecond => {
+            cast(array, &DataType::Timestamp(TimeUnit::Nanosecond, None))?
+        }
+        _ => array.clone(),
+    };
+    let array = cast(&array, &target)?;
+    Ok(array.as_primitive::<T::ArrowType>().clone())
+}
+
+impl<'py, T: ColumnElement> FromPyObject<'py> for Column<'py, T> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = ob.extract::<PyReadonlyArray1<'py, T>>() {
+            return Ok(Column::Numpy(array));
+        }
+        if let Some(ns) = datetime64_as_ns(ob)? {
+            if let Ok(array) = ns.extract::<PyReadonlyArray1<'py, T>>() {
+                return Ok(Column::Numpy(array));
+            }
+        }
+        if let Ok(frame) = ob.downcast::<OhlcvFrame>() {
+            return Ok(Column::Numpy(OhlcvFrame::close_view(frame)?.extract()?));
+        }
+
+        match import_arrow_like(ob)? {
+            Some((array, ArrayKind::Polars)) => return Ok(Column::Polars(cast_primitive::<T>(&array)?)),
+            Some((array, _)) => return Ok(Column::Arrow(cast_primitive::<T>(&array)?)),
+            None => {}
+        }
+
+        Err(pyo3::exceptions::PyTypeError::new_err(format!(
+            "Expected a 1D numpy array, pyarrow array or Polars Series of {}",
+            <T::ArrowType as ArrowPrimitiveType>::DATA_TYPE
+        )))
+    }
+}
+
+impl<'py, T: ColumnElement> Column<'py, T> {
+    pub fn kind(&self) -> ArrayKind {
+        match self {
+            Column::Numpy(_) => ArrayKind::Numpy,
+            Column::Arrow(_) => ArrayKind::Arrow,
+            Column::Polars(_) => ArrayKind::Polars,
+        }
+    }
+
+    /// Borrow the column's values; only strided numpy views (e.g. a column of a 2D array)
+    /// and Arrow columns containing nulls are copied. A borrowed numpy buffer is read with
+    /// the GIL released by most kernels, so its contents are only stable as long as no
+    /// other Python thread writes to the array (see the module docs). Copying a strided view emits a
+    /// `fast_math.errors.PerformanceWarning`, since the caller can usually pass a
+    /// contiguous array instead. `NumericColumn` inputs are read through here as well
+    pub fn values(&self) -> PyResult<Cow<'_, [T]>> {
+        match self {
+            Column::Numpy(array) => match array.as_slice() {
+                Ok(slice) => Ok(Cow::Borrowed(slice)),
+                Err(_) => {
+                    let py = array.py();
+                    PyErr::warn_bound(
+                        py,
+                        &py.get_type_bound::<crate::errors::PerformanceWarning>(),
+                        "Input array is not contiguous and was copied; pass a contiguous array to avoid the copy",
+                        1,
+                    )?;
+                    Ok(Cow::Owned(array.as_array().to_vec()))
+                }
+            },
+            Column::Arrow(array) | Column::Polars(array) => {
+                if array.null_count() == 0 {
+                    return Ok(Cow::Borrowed(&array.values()[..]));
+                }
+                let fill = T::NULL_FILL.ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Null values are not supported in {} columns",
+                        <T::ArrowType as ArrowPrimitiveType>::DATA_TYPE
+                    ))
+                })?;
+                Ok(Cow::Owned(array.iter().map(|v| v.unwrap_or(fill)).collect()))
+            }
+        }
+    }
+}
+
+/// A numeric column whose integer-ness is preserved: int64, datetime64 and Arrow integer
+/// or timestamp inputs stay integral, everything else is read as float64
+pub enum NumericColumn<'py> {
+    Float(Column<'py, f64>),
+    Int(Column<'py, i64>),
+}
+
+impl<'py> NumericColumn<'py> {
+    pub fn kind(&self) -> ArrayKind {
+        match self {
+            NumericColumn::Float(column) => column.kind(),
+            NumericColumn::Int(column) => column.kind(),
+        }
+    }
+}
+
+impl<'py> FromPyObject<'py> for NumericColumn<'py> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = ob.extract::<PyReadonlyArray1<'py, i64>>() {
+            return Ok(NumericColumn::Int(Column::Numpy(array)));
+        }
+        if let Ok(array) = ob.extract::<PyReadonlyArray1<'py, f64>>() {
+            return Ok(NumericColumn::Float(Column::Numpy(array)));
+        }
+        if let Some(ns) = datetime64_as_ns(ob)? {
+            return Ok(NumericColumn::Int(Column::Numpy(ns.extract()?)));
+        }
+        if let Some((array, kind)) = import_arrow_like(ob)? {
+            let integral = array.data_type().is_integer() || matches!(array.data_type(), DataType::Timestamp(_, _));
+            return Ok(match (integral, kind) {
+                (true, ArrayKind::Polars) => NumericColumn::Int(Column::Polars(cast_primitive::<i64>(&array)?)),
+                (true, _) => NumericColumn::Int(Column::Arrow(cast_primitive::<i64>(&array)?)),
+                (false, ArrayKind::Polars) => NumericColumn::Float(Column::Polars(cast_primitive::<f64>(&array)?)),
+                (false, _) => NumericColumn::Float(Column::Arrow(cast_primitive::<f64>(&array)?)),
+            });
+        }
+        // Reports the usual type error for unsupported inputs
+        Ok(NumericColumn::Float(ob.extract::<Column<'py, f64>>()?))
+    }
+}
+
+/// The named columns of a record batch or DataFrame, one series per column
+pub struct Table<'py, T: ColumnElement> {
+    names: Vec<String>,
+    columns: Vec<Column<'py, T>>,
+    kind: ArrayKind,
+}
+
+impl<'py, T: ColumnElement> Table<'py, T> {
+    fn import(batch: RecordBatch, kind: ArrayKind) -> PyResult<Self> {
+        let names = batch.schema().fields().iter().map(|f| f.name().clone()).collect();
+        let columns = batch
+            .columns()
+            .iter()
+            .map(|array| {
+                let array = cast_primitive::<T>(array)?;
+                Ok(if kind == ArrayKind::Polars { Column::Polars(array) } else { Column::Arrow(array) })
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(Table { names, columns, kind })
+    }
+
+    /// A record batch (a DataFrame for DataFrame input) of `results`, one per column and
+    /// under the same names
+    fn wrap(&self, py: Python<'_>, results: Vec<Vec<T>>) -> PyResult<PyObject> {
+        let fields: Vec<Field> = self
+            .names
+            .iter()
+            .map(|name| Field::new(name, <T::ArrowType as ArrowPrimitiveType>::DATA_TYPE, false))
+            .collect();
+        let arrays: Vec<ArrayRef> = results
+            .into_iter()
+            .map(|values| std::sync::Arc::new(PrimitiveArray::<T::ArrowType>::new(values.into(), None)) as ArrayRef)
+            .collect();
+        let batch = RecordBatch::try_new(std::sync::Arc::new(Schema::new(fields)), arrays)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let batch = batch.to_pyarrow(py)?;
+        match self.kind {
+            ArrayKind::Polars => Ok(py.import_bound("polars")?.call_method1("from_arrow", (batch,))?.unbind()),
+            _ => Ok(batch),
+        }
+    }
+}
+
+/// A single series, or a 2D batch holding one series per row (rows = symbols, columns = time),
+/// or a record batch or Polars DataFrame holding one series per column
+pub enum Series<'py, T: ColumnElement> {
+    Batch(PyReadonlyArray2<'py, T>),
+    Table(Table<'py, T>),
+    Single(Column<'py, T>),
+}
+
+impl<'py, T: ColumnElement> FromPyObject<'py> for Series<'py, T> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(batch) = ob.extract::<PyReadonlyArray2<'py, T>>() {
+            return Ok(Series::Batch(batch));
+        }
+        if let Some((batch, kind)) = import_table(ob)? {
+            return Ok(Series::Table(Table::import(batch, kind)?));
+        }
+        Ok(Series::Single(ob.extract()?))
+    }
+}
+
+impl<'py, T: ColumnElement> Series<'py, T> {
+    /// The series as a 2D array with one row per series: a numpy batch is viewed as is,
+    /// a record batch or DataFrame is copied into rows and a single series is one row
+    pub fn rows(&self) -> PyResult<CowArray<'_, T, Ix2>> {
+        match self {
+            Series::Batch(batch) => Ok(CowArray::from(batch.as_array())),
+            Series::Table(table) => {
+                let columns = table.columns.iter().map(Column::values).collect::<PyResult<Vec<_>>>()?;
+                let len = columns.first().map_or(0, |c| c.len());
+                let rows = Array2::from_shape_vec((columns.len(), len), columns.concat())
+                    .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+                Ok(CowArray::from(rows))
+            }
+            Series::Single(column) => {
+                let values = column.values()?.into_owned();
+                let row = Array2::from_shape_vec((1, values.len()), values)
+                    .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+                Ok(CowArray::from(row))
+            }
+        }
+    }
+}
+
+impl<'py, T: FloatElement> Series<'py, T> {
+    /// Apply a per-series kernel; batches are computed row by row in parallel and
+    /// returned as a 2D numpy array with one output row per input row. The kernel runs
+    /// without the GIL on the inputs' memory, under the module's aliasing contract
+    pub fn map<F>(&self, py: Python<'py>, f: F) -> PyResult<PyObject>
+    where
+        F: Fn(&[T]) -> PyResult<Vec<T>> + Sync,
+    {
+        match self {
+            Series::Single(column) => {
+                let values = column.values()?;
+                let result = py.allow_threads(|| f(&values))?;
+                column.kind().wrap(py, result)
+            }
+            Series::Batch(batch) => {
+                let batch = batch.as_array();
+                let rows = py.allow_threads(|| map_rows(batch, |_, row| f(row)))?;
+                Ok(PyArray2::from_owned_array_bound(py, stack_rows(rows)?).into_any().unbind())
+            }
+            Series::Table(table) => {
+                let columns = table.columns.iter().map(Column::values).collect::<PyResult<Vec<_>>>()?;
+                let results = py.allow_threads(|| {
+                    threads::install(|| columns.par_iter().map(|values| f(values)).collect::<PyResult<Vec<_>>>())
+                })?;
+                table.wrap(py, results)
+            }
+        }
+    }
+
+    /// Like `map`, but the kernel writes into a caller-provided, C-contiguous numpy array
+    /// of the input's dtype (1D for a series, 2D with one row per input row for a batch
+    /// or per column for a record batch), which is returned
+    pub fn map_into<F>(&self, py: Python<'py>, out: &Bound<'py, PyAny>, f: F) -> PyResult<PyObject>
+    where
+        F: Fn(&[T], &mut [T]) -> PyResult<()> + Sync,
+    {
+        let not_contiguous = || crate::errors::NonContiguousInputError::new_err("out must be C-contiguous");
+        match self {
+            Series::Single(column) => {
+                let array = out.downcast::<PyArray1<T>>().map_err(|_| {
+                    pyo3::exceptions::PyTypeError::new_err(
+                        "out must be a 1D numpy array with the input's dtype"
+                    )
+                })?;
+                let mut guard = array.try_readwrite()?;
+                let dst = guard.as_slice_mut().map_err(|_| not_contiguous())?;
+                let values = column.values()?;
+                py.allow_threads(|| f(&values, dst))?;
+            }
+            Series::Batch(_) | Series::Table(_) => {
+                let array = out.downcast::<PyArray2<T>>().map_err(|_| {
+                    pyo3::exceptions::PyTypeError::new_err(
+                        "out must be a 2D numpy array with the input's dtype"
+                    )
+                })?;
+                let mut guard = array.try_readwrite()?;
+                let mut dst = guard.as_array_mut();
+                let batch = self.rows()?;
+                let batch = batch.view();
+                if dst.nrows() != batch.nrows() {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "out must have one row per input series"
+                    ));
+                }
+                py.allow_threads(|| {
+                    threads::install(|| {
+                        dst.axis_iter_mut(Axis(0))
+                            .into_par_iter()
+                            .zip(batch.axis_iter(Axis(0)).into_par_iter())
+                            .map(|(mut dst_row, row)| {
+                                let dst_row = dst_row.as_slice_mut().ok_or_else(not_contiguous)?;
+                                match row.as_slice() {
+                                    Some(slice) => f(slice, dst_row),
+                                    None => f(&row.iter().copied().collect::<Buffer<T>>(), dst_row),
+                                }
+                            })
+                            .collect::<PyResult<()>>()
+                    })
+                })?;
+            }
+        }
+        Ok(out.clone().unbind())
+    }
+}
+
+/// Check that an output buffer has the kernel's result length
+pub fn check_out<T>(out: &[T], len: usize) -> PyResult<()> {
+    if out.len() != len {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "out must have length {}, got {}", len, out.len()
+        )));
+    }
+    Ok(())
+}
+
+/// A float64 or float32 series; float32 numpy inputs are computed in float32 without
+/// an upcast copy, everything else is read as float64
+#[derive(FromPyObject)]
+pub enum SeriesInput<'py> {
+    F64(Series<'py, f64>),
+    F32(Series<'py, f32>),
+}
+
+impl SeriesInput<'_> {
+    /// Whether any value of the series or batch is NaN
+    pub fn has_nan(&self) -> PyResult<bool> {
+        fn any_nan<T: FloatElement>(series: &Series<'_, T>) -> PyResult<bool> {
+            Ok(match series {
+                Series::Single(column) => column.values()?.iter().any(|v| v.is_nan()),
+                Series::Batch(batch) => batch.as_array().iter().any(|v| v.is_nan()),
+                Series::Table(table) => {
+                    for column in &table.columns {
+                        if column.values()?.iter().any(|v| v.is_nan()) {
+                            return Ok(true);
+                        }
+                    }
+                    false
+                }
+            })
+        }
+        match self {
+            SeriesInput::F64(series) => any_nan(series),
+            SeriesInput::F32(series) => any_nan(series),
+        }
+    }
+}
+
+/// Apply a kernel generic over `FloatElement` to whichever float type a `SeriesInput` holds;
+/// with an `out` array, the kernel writes into it instead (see `Series::map_into`)
+macro_rules! map_series {
+    ($py:expr, $data:expr, |$slice:ident| $body:expr) => {
+        match $data {
+            $crate::column::SeriesInput::F64(series) => series.map($py, |$slice| $body),
+            $crate::column::SeriesInput::F32(series) => series.map($py, |$slice| $body),
+        }
+    };
+    ($py:expr, $data:expr, $out:expr, |$slice:ident, $dst:ident| $body:expr) => {
+        match $data {
+            $crate::column::SeriesInput::F64(series) => series.map_into($py, $out, |$slice, $dst| $body),
+            $crate::column::SeriesInput::F32(series) => series.map_into($py, $out, |$slice, $dst| $body),
+        }
+    };
+}
+pub(crate) use map_series;
+
+/// Run `f(row_index, row)` over every row of `batch` in parallel; non-contiguous rows are copied into pooled buffers first
+pub fn map_rows<T, R, F>(batch: ArrayView2<T>, f: F) -> PyResult<Vec<R>>
+where
+    T: Poolable + Send + Sync,
+    R: Send,
+    F: Fn(usize, &[T]) -> PyResult<R> + Sync,
+{
+    threads::install(|| {
+        (0..batch.nrows())
+            .into_par_iter()
+            .map(|i| {
+                let row = batch.row(i);
+                match row.as_slice() {
+                    Some(slice) => f(i, slice),
+                    None => f(i, &row.iter().copied().collect::<Buffer<T>>()),
+                }
+            })
+            .collect()
+    })
+}
+
+/// Stack equal-length per-row results into a 2D array
+pub fn stack_rows<T: Clone>(rows: Vec<Vec<T>>) -> PyResult<Array2<T>> {
+    let width = rows.first().map_or(0, Vec::len);
+    Array2::from_shape_vec((rows.len(), width), rows.concat())
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::{Float64Array, Int64Array};
+
+    #[test]
+    fn polars_columns_borrow_their_values_and_win_the_output_kind() {
+        let column: Column<f64> = Column::Polars(Float64Array::from(vec![1.0, 2.0]));
+        assert_eq!(column.kind(), ArrayKind::Polars);
+        assert!(matches!(column.values().unwrap(), Cow::Borrowed([1.0, 2.0])));
+        assert_eq!(ArrayKind::of(&[ArrayKind::Numpy, ArrayKind::Polars, ArrayKind::Arrow]), ArrayKind::Polars);
+        assert_eq!(ArrayKind::of(&[ArrayKind::Numpy, ArrayKind::Arrow]), ArrayKind::Arrow);
+        assert_eq!(ArrayKind::of(&[]), ArrayKind::Numpy);
+    }
+
+    #[test]
+    fn polars_nulls_read_as_nan_but_are_rejected_in_integer_columns() {
+        let column: Column<f64> = Column::Polars(Float64Array::from(vec![Some(1.0), None]));
+        let values = column.values().unwrap();
+        assert_eq!(values[0], 1.0);
+        assert!(values[1].is_nan());
+
+        pyo3::prepare_freethreaded_python();
+        let column: Column<i64> = Column::Polars(Int64Array::from(vec![Some(1), None]));
+        assert!(column.values().is_err());
+    }
+
+    #[test]
+    fn table_columns_read_as_rows() {
+        let a: ArrayRef = std::sync::Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0]));
+        let b: ArrayRef = std::sync::Arc::new(Float64Array::from(vec![Some(4.0), None, Some(6.0)]));
+        let batch = RecordBatch::try_from_iter(vec![("a", a), ("b", b)]).unwrap();
+        let table = Table::<f64>::import(batch, ArrayKind::Polars).unwrap();
+        assert!(matches!(table.columns[0], Column::Polars(_)));
+        assert_eq!(table.names, vec!["a".to_string(), "b".to_string()]);
+        let series = Series::Table(table);
+        let rows = series.rows().unwrap();
+        assert_eq!(rows.shape(), &[2, 3]);
+        assert_eq!(rows.row(0).to_vec(), vec![1.0, 2.0, 3.0]);
+        // Nulls read as NaN
+        assert!(rows[[1, 1]].is_nan());
+        drop(rows);
+        assert!(SeriesInput::F64(series).has_nan().unwrap());
+    }
+
+    #[test]
+    fn map_rows_reads_strided_rows_in_order() {
+        let batch = ndarray::array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
+        // Rows of the transpose are strided views of the columns
+        let sums = map_rows(batch.t(), |i, row: &[f64]| Ok((i, row.iter().sum::<f64>()))).unwrap();
+        assert_eq!(sums, vec![(0, 9.0), (1, 12.0)]);
+
+        let stacked = stack_rows(map_rows(batch.view(), |_, row: &[f64]| Ok(row.to_vec())).unwrap()).unwrap();
+        assert_eq!(stacked, batch);
+    }
+
+    #[test]
+    fn stack_rows_rejects_ragged_rows() {
+        pyo3::prepare_freethreaded_python();
+        assert!(stack_rows(vec![vec![1.0, 2.0], vec![3.0]]).is_err());
+        assert_eq!(stack_rows::<f64>(vec![]).unwrap().shape(), &[0, 0]);
+    }
+}
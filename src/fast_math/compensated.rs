@@ -0,0 +1,179 @@
+use pyo3::prelude::*;
+
+use crate::column::{check_out, FloatElement};
+use crate::DRIFT_INTERVAL;
+
+/// Kahan-Babuska-Neumaier running sum: the rounding error of every addition is carried in
+/// a second term, so long sliding sums stay accurate to the last bits
+#[derive(Clone, Copy)]
+pub struct Neumaier<T> {
+    sum: T,
+    compensation: T,
+}
+
+impl<T: FloatElement> Neumaier<T> {
+    pub fn new() -> Self {
+        Neumaier { sum: T::zero(), compensation: T::zero() }
+    }
+
+    pub fn add(&mut self, value: T) {
+        let total = self.sum + value;
+        self.compensation = self.compensation + if self.sum.abs() >= value.abs() {
+            (self.sum - total) + value
+        } else {
+            (value - total) + self.sum
+        };
+        self.sum = total;
+    }
+
+    pub fn value(&self) -> T {
+        self.sum + self.compensation
+    }
+}
+
+/// Compensated sum of the non-NaN values of `values`
+fn sum<T: FloatElement>(values: impl Iterator<Item = T>) -> T {
+    let mut acc = Neumaier::new();
+    values.filter(|v| !v.is_nan()).for_each(|v| acc.add(v));
+    acc.value()
+}
+
+/// `skipna::moving_average` with compensated sliding sums; `min_periods == window` gives
+/// the NaN-propagating moving average
+pub fn moving_average<T: FloatElement>(slice: &[T], window: usize, min_periods: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); (slice.len() + 1).saturating_sub(window)];
+    moving_average_into(slice, window, min_periods, &mut result)?;
+    Ok(result)
+}
+
+/// `moving_average` written into `out`, which must hold `len - window + 1` values
+pub fn moving_average_into<T: FloatElement>(
+    slice: &[T],
+    window: usize,
+    min_periods: usize,
+    out: &mut [T]
+) -> PyResult<()> {
+    let n = slice.len();
+    let valid = crate::errors::check_window(window, n)?;
+    check_out(out, valid)?;
+    if window == 1 {
+        // A one-value window is the input itself, which sliding sums would round
+        out.copy_from_slice(slice);
+        return Ok(());
+    }
+
+    let mut acc = Neumaier::new();
+    let mut count = 0usize;
+    for i in 0..n {
+        if !slice[i].is_nan() {
+            acc.add(slice[i]);
+            count += 1;
+        }
+        if i >= window && !slice[i - window].is_nan() {
+            acc.add(-slice[i - window]);
+            count -= 1;
+        }
+        if i + 1 >= window {
+            out[i + 1 - window] = if count >= min_periods { acc.value() / T::from(count).unwrap() } else { T::nan() };
+        }
+    }
+    crate::validate::check("compensated.moving_average", out, || {
+        crate::validate::reference::moving_average(slice, window, min_periods)
+    });
+    Ok(())
+}
+
+/// `rolling_std` with compensated sliding sums of deviations from a recent value
+pub fn rolling_std<T: FloatElement>(slice: &[T], window: usize, ddof: usize) -> PyResult<Vec<T>> {
+    let n = slice.len();
+    let valid = crate::errors::check_window(window, n)?;
+    crate::errors::check_ddof(ddof, window)?;
+    if valid == 0 {
+        return Ok(Vec::new());
+    }
+    if window == 1 {
+        // One value has no spread, but sliding sums would leave rounding residue
+        return Ok(slice.iter().map(|v| if v.is_nan() { T::nan() } else { T::zero() }).collect());
+    }
+
+    let (count, dof) = (T::from(window).unwrap(), T::from(window - ddof).unwrap());
+    let mut result = Vec::with_capacity(valid);
+    let (mut shift, mut sum, mut sum_sq) = (T::zero(), Neumaier::new(), Neumaier::new());
+    let mut nans = slice[..window].iter().filter(|v| v.is_nan()).count();
+    for i in 0..=n - window {
+        if i > 0 {
+            let (old, new) = (slice[i - 1], slice[i + window - 1]);
+            nans = nans + new.is_nan() as usize - old.is_nan() as usize;
+        }
+        if i % DRIFT_INTERVAL == 0 {
+            // Re-centre on the window so the sum of squares stays well conditioned
+            let values = &slice[i..i + window];
+            shift = values.iter().copied().find(|v| !v.is_nan()).unwrap_or(T::zero());
+            sum = Neumaier::new();
+            sum_sq = Neumaier::new();
+            for &v in values.iter().filter(|v| !v.is_nan()) {
+                sum.add(v - shift);
+                sum_sq.add((v - shift) * (v - shift));
+            }
+        } else {
+            let (old, new) = (slice[i - 1], slice[i + window - 1]);
+            if !new.is_nan() {
+                sum.add(new - shift);
+                sum_sq.add((new - shift) * (new - shift));
+            }
+            if !old.is_nan() {
+                sum.add(-(old - shift));
+                sum_sq.add(-(old - shift) * (old - shift));
+            }
+        }
+        result.push(if nans > 0 {
+            T::nan()
+        } else {
+            let (s, s2) = (sum.value(), sum_sq.value());
+            ((s2 - s * s / count) / dof).max(T::zero()).sqrt()
+        });
+    }
+    crate::validate::check("compensated.rolling_std", &result, || {
+        crate::validate::reference::rolling_std(slice, window, ddof, window)
+    });
+    Ok(result)
+}
+
+/// Two-pass Pearson correlation with compensated f64 sums. With `min_periods`, only pairs
+/// where both values are valid are used (NaN with fewer such pairs); without, any NaN
+/// makes the result NaN.
+pub fn correlation<T: FloatElement>(x: &[T], y: &[T], min_periods: Option<usize>) -> PyResult<T> {
+    if x.len() != y.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "Arrays must have the same length"
+        ));
+    }
+
+    // Accumulated in f64 whatever the input precision
+    let pairs = || {
+        x.iter()
+            .zip(y.iter())
+            .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+            .map(|(a, b)| (a.to_f64().unwrap(), b.to_f64().unwrap()))
+    };
+    let count = pairs().count();
+    match min_periods {
+        None if count < x.len() => return Ok(T::nan()),
+        Some(min_periods) if count < min_periods.max(2) => return Ok(T::nan()),
+        _ if count < 2 => return Ok(T::nan()),
+        _ => {}
+    }
+    let n = count as f64;
+    let mean_x = sum(pairs().map(|(a, _)| a)) / n;
+    let mean_y = sum(pairs().map(|(_, b)| b)) / n;
+    let cov = sum(pairs().map(|(a, b)| (a - mean_x) * (b - mean_y)));
+    let var_x = sum(pairs().map(|(a, _)| (a - mean_x) * (a - mean_x)));
+    let var_y = sum(pairs().map(|(_, b)| (b - mean_y) * (b - mean_y)));
+
+    let denominator = (var_x * var_y).sqrt();
+    let result = if denominator == 0.0 { T::zero() } else { T::from(cov / denominator).unwrap() };
+    crate::validate::check("compensated.correlation", &[result], || {
+        crate::validate::reference::correlation(x, y, min_periods)
+    });
+    Ok(result)
+}
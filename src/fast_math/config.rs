@@ -0,0 +1,346 @@
+use std::cell::RefCell;
+use std::sync::RwLock;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::alignment::Alignment;
+use crate::skipna::NanPolicy;
+use crate::{rng, threads};
+
+/// Version of the format written by `FastMathConfig.to_dict`; bumped whenever a field is
+/// added or changes meaning
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Whether kernels with a compensated variant use it when called without `precise=`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Fast,
+    /// Compensated (Kahan-Neumaier) sums, matching pandas to the last bits
+    Precise,
+}
+
+impl Precision {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fast" => Ok(Precision::Fast),
+            "precise" => Ok(Precision::Precise),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown precision '{}', expected 'fast' or 'precise'", name
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Precision::Fast => "fast",
+            Precision::Precise => "precise",
+        }
+    }
+}
+
+/// Defaults for the arguments a call leaves out
+#[derive(Clone, Copy)]
+struct Defaults {
+    align: Alignment,
+    nan_policy: NanPolicy,
+    precision: Precision,
+}
+
+/// Defaults set by `set_config`; the thread count and seed live with the thread pool and
+/// the RNG
+static GLOBAL: RwLock<Defaults> =
+    RwLock::new(Defaults { align: Alignment::Valid, nan_policy: NanPolicy::Propagate, precision: Precision::Fast });
+
+thread_local! {
+    /// Configs entered with `with` on this thread, innermost last
+    static SCOPES: RefCell<Vec<FastMathConfig>> = const { RefCell::new(Vec::new()) };
+}
+
+fn defaults() -> Defaults {
+    SCOPES.with(|scopes| scopes.borrow().last().map(|c| c.defaults)).unwrap_or_else(|| *GLOBAL.read().unwrap())
+}
+
+/// `align`, or the configured default when the call did not give one
+pub fn resolve_align(align: Option<Alignment>) -> Alignment {
+    align.unwrap_or_else(|| defaults().align)
+}
+
+/// `precise`, or whether the configured precision is "precise" when not given
+pub fn resolve_precise(precise: Option<bool>) -> bool {
+    precise.unwrap_or_else(|| defaults().precision == Precision::Precise)
+}
+
+/// Policy of calls given neither `nan_policy` nor `skipna`
+pub fn default_nan_policy() -> NanPolicy {
+    defaults().nan_policy
+}
+
+/// Seed of the innermost `with` config on this thread, if any
+pub fn scoped_seed() -> Option<u64> {
+    SCOPES.with(|scopes| scopes.borrow().last().map(|c| c.seed))
+}
+
+/// Apply `config` to this thread's calls until the matching `pop`
+fn push(config: &FastMathConfig) -> PyResult<()> {
+    threads::enter_scope(config.threads)?;
+    SCOPES.with(|scopes| scopes.borrow_mut().push(config.clone()));
+    Ok(())
+}
+
+fn pop() {
+    SCOPES.with(|scopes| scopes.borrow_mut().pop());
+    threads::exit_scope();
+}
+
+/// The `config=` of one kernel call, applied like a `with` block until the scope is
+/// dropped at the end of the call
+pub struct CallScope(bool);
+
+impl CallScope {
+    pub fn enter(config: Option<PyRef<'_, FastMathConfig>>) -> PyResult<Self> {
+        match config {
+            Some(config) => push(&config).map(|()| CallScope(true)),
+            None => Ok(CallScope(false)),
+        }
+    }
+}
+
+impl Drop for CallScope {
+    fn drop(&mut self) {
+        if self.0 {
+            pop();
+        }
+    }
+}
+
+fn align_repr(align: Alignment) -> String {
+    match align {
+        Alignment::Valid => "'valid'".to_string(),
+        Alignment::Same => "'same'".to_string(),
+        Alignment::Shifted(k) => k.to_string(),
+    }
+}
+
+/// The settings that can change a result: everything but the thread count, from
+/// `config` or else the defaults calls on this thread fill in, for keying cached results
+pub fn result_key(config: Option<&FastMathConfig>) -> String {
+    let (defaults, seed) = match config {
+        Some(config) => (config.defaults, config.seed),
+        None => (defaults(), rng::resolve_seed(None)),
+    };
+    format!(
+        "align={} nan_policy={} precision={} seed={}",
+        align_repr(defaults.align), defaults.nan_policy.name(), defaults.precision.name(), seed
+    )
+}
+
+fn align_object(py: Python<'_>, align: Alignment) -> PyObject {
+    match align {
+        Alignment::Valid => "valid".into_py(py),
+        Alignment::Same => "same".into_py(py),
+        Alignment::Shifted(k) => k.into_py(py),
+    }
+}
+
+/// Crate-wide settings pinned in one object: worker `threads` (0 for the default), the
+/// default `align` and `nan_policy` of windowed kernels, `precision` ("fast" or
+/// "precise", the default of `precise=`) and the RNG `seed` of stochastic functions.
+///
+/// `set_config(config)` makes it the process default, `with config:` applies it to the
+/// calls made on the current thread inside the block, so one pipeline can pin its own
+/// settings, and kernels whose results depend on a setting (the windowed kernels taking
+/// `align`, `nan_policy` or `precise`, and the seeded simulations) also take it for a
+/// single call as `config=`. Arguments passed to a call always win; the config only
+/// fills in the ones left out. `to_dict` records the settings with the config format `version` and
+/// the `crate_version`, and `FastMathConfig.from_dict` restores them, so research and
+/// production runs can log and reproduce exactly how they were configured.
+#[pyclass(module = "fast_math")]
+#[derive(Clone)]
+pub struct FastMathConfig {
+    threads: usize,
+    defaults: Defaults,
+    seed: u64,
+}
+
+#[pymethods]
+impl FastMathConfig {
+    #[new]
+    #[pyo3(signature = (threads=0, align=Alignment::Valid, nan_policy=NanPolicy::Propagate, precision="fast", seed=0))]
+    fn new(threads: usize, align: Alignment, nan_policy: NanPolicy, precision: &str, seed: u64) -> PyResult<Self> {
+        let precision = Precision::parse(precision)?;
+        Ok(FastMathConfig { threads, defaults: Defaults { align, nan_policy, precision }, seed })
+    }
+
+    /// Restore a config from `to_dict` output. Missing settings take their defaults;
+    /// unknown ones and configs written by a newer format version are rejected
+    #[staticmethod]
+    fn from_dict(settings: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let version: u32 = match settings.get_item("version")? {
+            Some(version) => version.extract()?,
+            None => CONFIG_VERSION,
+        };
+        if version > CONFIG_VERSION {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Config format version {} is newer than this build supports ({})", version, CONFIG_VERSION
+            )));
+        }
+        let known = ["version", "crate_version", "threads", "align", "nan_policy", "precision", "seed"];
+        for key in settings.keys() {
+            let key: String = key.extract()?;
+            if !known.contains(&key.as_str()) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown config setting '{}'", key
+                )));
+            }
+        }
+        let get = |name: &str| settings.get_item(name);
+        FastMathConfig::new(
+            get("threads")?.map(|v| v.extract()).transpose()?.unwrap_or(0),
+            get("align")?.map(|v| v.extract()).transpose()?.unwrap_or(Alignment::Valid),
+            get("nan_policy")?.map(|v| v.extract()).transpose()?.unwrap_or(NanPolicy::Propagate),
+            &get("precision")?.map(|v| v.extract::<String>()).transpose()?.unwrap_or_else(|| "fast".to_string()),
+            get("seed")?.map(|v| v.extract()).transpose()?.unwrap_or(0),
+        )
+    }
+
+    /// The settings with the format `version` and `crate_version`, for audit logs
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new_bound(py);
+        result.set_item("version", CONFIG_VERSION)?;
+        result.set_item("crate_version", env!("CARGO_PKG_VERSION"))?;
+        result.set_item("threads", self.threads)?;
+        result.set_item("align", align_object(py, self.defaults.align))?;
+        result.set_item("nan_policy", self.defaults.nan_policy.name())?;
+        result.set_item("precision", self.defaults.precision.name())?;
+        result.set_item("seed", self.seed)?;
+        Ok(result)
+    }
+
+    #[getter]
+    fn threads(&self) -> usize {
+        self.threads
+    }
+
+    #[getter]
+    fn align(&self, py: Python<'_>) -> PyObject {
+        align_object(py, self.defaults.align)
+    }
+
+    #[getter]
+    fn nan_policy(&self) -> &'static str {
+        self.defaults.nan_policy.name()
+    }
+
+    #[getter]
+    fn precision(&self) -> &'static str {
+        self.defaults.precision.name()
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    #[getter]
+    fn version(&self) -> u32 {
+        CONFIG_VERSION
+    }
+
+    #[getter]
+    fn crate_version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        push(&slf)?;
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, _args: &Bound<'_, pyo3::types::PyTuple>) -> bool {
+        pop();
+        false
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FastMathConfig(threads={}, align={}, nan_policy='{}', precision='{}', seed={})",
+            self.threads, align_repr(self.defaults.align), self.defaults.nan_policy.name(), self.defaults.precision.name(), self.seed
+        )
+    }
+}
+
+/// Make `config` the process-wide default: it sets the thread pool size and seed as
+/// `set_num_threads` and `set_seed` do, and the defaults of `align`, `nan_policy` and
+/// `precise` for calls that leave them out.
+#[pyfunction]
+pub fn set_config(config: PyRef<'_, FastMathConfig>) -> PyResult<()> {
+    threads::set_num_threads(config.threads)?;
+    rng::set_seed(config.seed);
+    *GLOBAL.write().unwrap() = config.defaults;
+    Ok(())
+}
+
+/// The settings calls on this thread currently run with: the innermost `with` config, or
+/// the process defaults, with `threads` as the worker count actually in use.
+#[pyfunction]
+pub fn get_config() -> FastMathConfig {
+    FastMathConfig { threads: threads::get_num_threads(), defaults: defaults(), seed: rng::resolve_seed(None) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(align: Alignment, precision: &str, seed: u64) -> FastMathConfig {
+        FastMathConfig::new(0, align, NanPolicy::Propagate, precision, seed).unwrap()
+    }
+
+    #[test]
+    fn nested_scopes_restore_the_outer_settings() {
+        assert_eq!(scoped_seed(), None);
+        push(&config(Alignment::Same, "precise", 1)).unwrap();
+        push(&config(Alignment::Shifted(2), "fast", 2)).unwrap();
+        assert!(resolve_align(None) == Alignment::Shifted(2) && !resolve_precise(None));
+        assert_eq!(scoped_seed(), Some(2));
+        // Arguments given to the call win over the config
+        assert!(resolve_precise(Some(true)));
+        pop();
+        assert!(resolve_align(None) == Alignment::Same && resolve_precise(None));
+        assert_eq!(scoped_seed(), Some(1));
+        pop();
+        assert_eq!(scoped_seed(), None);
+    }
+
+    #[test]
+    fn dicts_round_trip() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let original = FastMathConfig::new(2, Alignment::Shifted(3), NanPolicy::Propagate, "precise", 42).unwrap();
+            let settings = original.to_dict(py).unwrap();
+            assert_eq!(settings.get_item("version").unwrap().unwrap().extract::<u32>().unwrap(), CONFIG_VERSION);
+            let restored = FastMathConfig::from_dict(&settings).unwrap();
+            assert_eq!(restored.__repr__(), original.__repr__());
+            assert_eq!(result_key(Some(&restored)), result_key(Some(&original)));
+        });
+    }
+
+    #[test]
+    fn newer_versions_and_unknown_settings_are_rejected() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let settings = PyDict::new_bound(py);
+            settings.set_item("version", CONFIG_VERSION + 1).unwrap();
+            assert!(FastMathConfig::from_dict(&settings).err().unwrap().to_string().contains("newer"));
+
+            let settings = PyDict::new_bound(py);
+            settings.set_item("threds", 4).unwrap();
+            assert!(FastMathConfig::from_dict(&settings).err().unwrap().to_string().contains("threds"));
+
+            // Settings left out take their defaults
+            let restored = FastMathConfig::from_dict(&PyDict::new_bound(py)).unwrap();
+            assert_eq!(restored.__repr__(), config(Alignment::Valid, "fast", 0).__repr__());
+        });
+    }
+}
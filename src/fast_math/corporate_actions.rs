@@ -0,0 +1,195 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use numpy::{PyArray1, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView2, Axis};
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// How dividends are taken out of the prices before their ex-date
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Scale by `1 - dividend / close` on the bar before the ex-date, keeping returns
+    Proportional,
+    /// Subtract the dividend, keeping price differences
+    Absolute,
+}
+
+impl Method {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "proportional" | "ratio" => Ok(Method::Proportional),
+            "absolute" | "difference" => Ok(Method::Absolute),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'proportional' or 'absolute'", name
+            ))),
+        }
+    }
+}
+
+/// Corporate actions of one kind: ex-date timestamps and the split ratio or dividend of each
+fn events(argument: &str, column: &str, data: Option<Bound<'_, PyAny>>) -> PyResult<Vec<(i64, f64)>> {
+    let Some(data) = data.filter(|d| !d.is_none()) else {
+        return Ok(Vec::new());
+    };
+    let (timestamps, values): (Column<'_, i64>, Column<'_, f64>) = match data.downcast::<PyTuple>() {
+        Ok(pair) => pair.extract()?,
+        Err(_) => {
+            let required = |name: &str| {
+                data.get_item(name).map_err(|_| {
+                    pyo3::exceptions::PyKeyError::new_err(format!(
+                        "{} needs a '{}' column", argument, name
+                    ))
+                })
+            };
+            (required("timestamp")?.extract()?, required(column)?.extract()?)
+        }
+    };
+    let (timestamps, values) = (timestamps.values()?, values.values()?);
+    if timestamps.len() != values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "{} must have one {} per timestamp", argument, column
+        )));
+    }
+    Ok(timestamps.iter().copied().zip(values.iter().copied()).collect())
+}
+
+/// Per-bar adjustment: prices become `price * factor - offset` and volume `volume / split`
+struct Adjustment {
+    factor: Vec<f64>,
+    offset: Vec<f64>,
+    split: Vec<f64>,
+}
+
+/// Walk the bars backwards from the latest, folding in each action as its ex-date is
+/// passed, so every bar is restated on the basis of the last one. Actions on one date
+/// take the dividend first, quoted in post-split units, then the split
+fn adjustment(timestamps: &[i64], close: &[f64], splits: &[(i64, f64)], dividends: &[(i64, f64)], method: Method) -> PyResult<Adjustment> {
+    let n = timestamps.len();
+    let mut dates: Vec<i64> = splits.iter().chain(dividends).map(|e| e.0).collect();
+    dates.sort_unstable();
+    dates.dedup();
+
+    let (mut factor, mut offset, mut split) = (1.0, 0.0, 1.0);
+    let mut result = Adjustment { factor: vec![1.0; n], offset: vec![0.0; n], split: vec![1.0; n] };
+    let mut end = n;
+    for &date in dates.iter().rev() {
+        // Bars before the ex-date are affected
+        let before = timestamps.partition_point(|&t| t < date);
+        for i in before..end {
+            (result.factor[i], result.offset[i], result.split[i]) = (factor, offset, split);
+        }
+        end = end.min(before);
+        if before == 0 {
+            continue;
+        }
+        let ratio: f64 = splits.iter().filter(|e| e.0 == date).map(|e| e.1).product();
+        for &(_, dividend) in dividends.iter().filter(|e| e.0 == date) {
+            match method {
+                Method::Absolute => offset += dividend * factor,
+                Method::Proportional => {
+                    let Some(&last) = close[..before].iter().rev().find(|c| c.is_finite()) else {
+                        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                            "The dividend at {} has no close before it", date
+                        )));
+                    };
+                    let scale = 1.0 - dividend * ratio / last;
+                    if scale <= 0.0 {
+                        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                            "The dividend at {} is not below the close before it", date
+                        )));
+                    }
+                    factor *= scale;
+                }
+            }
+        }
+        factor /= ratio;
+        split /= ratio;
+    }
+    for i in 0..end {
+        (result.factor[i], result.offset[i], result.split[i]) = (factor, offset, split);
+    }
+    Ok(result)
+}
+
+/// Back-adjust OHLCV bars for splits and dividends.
+///
+/// `timestamps` are the ascending bar times and `ohlcv` a 2D array with open, high, low,
+/// close and optionally volume columns. `splits` and `dividends` are `(timestamps,
+/// values)` tuples or mappings with `timestamp` and `ratio` (2.0 for a 2-for-1 split) or
+/// `amount` columns, stamped with their ex-dates: every bar before an ex-date is adjusted,
+/// so the history is restated on the basis of the latest bar. Splits divide earlier
+/// prices by their ratio and multiply volume by it. `method="proportional"` (the
+/// default) scales earlier prices by `1 - dividend / close` using the last close before
+/// the ex-date, preserving returns; "absolute" subtracts the dividend, preserving price
+/// differences. Dividends are in the units of their own date, after any split on the
+/// same date. Returns a dict with the adjusted `open`, `high`, `low`, `close` (and
+/// `volume`) and the per-bar `factor` and `offset`, such that the adjusted price is
+/// `price * factor - offset`.
+#[pyfunction]
+#[pyo3(signature = (timestamps, ohlcv, splits=None, dividends=None, method="proportional"))]
+pub fn adjust_prices_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    ohlcv: PyReadonlyArray2<'py, f64>,
+    splits: Option<Bound<'py, PyAny>>,
+    dividends: Option<Bound<'py, PyAny>>,
+    method: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let method = Method::parse(method)?;
+    let timestamps = timestamps.values()?;
+    let ohlcv: ArrayView2<'_, f64> = ohlcv.as_array();
+    if !(4..=5).contains(&ohlcv.ncols()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "ohlcv must have open, high, low, close and optionally volume columns"
+        ));
+    }
+    if ohlcv.nrows() != timestamps.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "ohlcv must have one row per timestamp"
+        ));
+    }
+    if timestamps.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Timestamps must be sorted ascending"
+        ));
+    }
+    let splits = events("splits", "ratio", splits)?;
+    let dividends = events("dividends", "amount", dividends)?;
+    if splits.iter().any(|e| !e.1.is_finite() || e.1 <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Split ratios must be finite and > 0"
+        ));
+    }
+    if dividends.iter().any(|e| !e.1.is_finite() || e.1 < 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Dividends must be finite and >= 0"
+        ));
+    }
+
+    let close = ohlcv.column(3).to_vec();
+    let adjustment = py.allow_threads(|| adjustment(&timestamps, &close, &splits, &dividends, method))?;
+    let adjusted = py.allow_threads(|| {
+        let mut adjusted = Array2::zeros(ohlcv.dim());
+        threads::install(|| {
+            adjusted.axis_iter_mut(Axis(0)).into_par_iter().enumerate().for_each(|(i, mut row)| {
+                for j in 0..4 {
+                    row[j] = ohlcv[[i, j]] * adjustment.factor[i] - adjustment.offset[i];
+                }
+                if ohlcv.ncols() > 4 {
+                    row[4] = ohlcv[[i, 4]] / adjustment.split[i];
+                }
+            })
+        });
+        adjusted
+    });
+
+    let result = PyDict::new_bound(py);
+    for (j, name) in ["open", "high", "low", "close", "volume"].iter().enumerate().take(ohlcv.ncols()) {
+        result.set_item(name, PyArray1::from_iter_bound(py, adjusted.column(j).iter().copied()))?;
+    }
+    result.set_item("factor", PyArray1::from_vec_bound(py, adjustment.factor))?;
+    result.set_item("offset", PyArray1::from_vec_bound(py, adjustment.offset))?;
+    Ok(result)
+}
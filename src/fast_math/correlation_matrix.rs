@@ -0,0 +1,180 @@
+use pyo3::prelude::*;
+use numpy::{PyArray2, PyArray3, PyReadonlyArray2};
+use ndarray::{s, Array2, Array3, ArrayView2, Axis};
+use rayon::prelude::*;
+
+use crate::alignment::Alignment;
+use crate::config::FastMathConfig;
+use crate::gpu::{self, Device};
+use crate::{simd, threads, validate};
+
+/// Symbols per tile side; a tile pair's slice of both row blocks stays in L2
+const TILE: usize = 64;
+/// Observations per block of the inner dot products
+const BLOCK: usize = 512;
+
+/// Centre and scale each column to unit norm, returned as one contiguous row per column so
+/// every correlation becomes a dot product. Constant columns become all zeros.
+fn standardize(returns: ArrayView2<'_, f64>) -> Array2<f64> {
+    let mut z = Array2::zeros((returns.ncols(), returns.nrows()));
+    z.assign(&returns.t());
+    z.axis_iter_mut(Axis(0)).into_par_iter().for_each(|mut row| {
+        let row = row.as_slice_mut().unwrap();
+        let mean = simd::sum_f64(row) / row.len() as f64;
+        row.iter_mut().for_each(|v| *v -= mean);
+        let norm = simd::dot_f64(row, row).sqrt();
+        let scale = if norm == 0.0 { 0.0 } else { 1.0 / norm };
+        row.iter_mut().for_each(|v| *v *= scale);
+    });
+    z
+}
+
+/// Correlations between the symbols of row tile `a` and column tile `b`, row-major
+fn tile(z: &Array2<f64>, a: usize, b: usize) -> Vec<f64> {
+    let (n, t) = z.dim();
+    let rows = a * TILE..((a + 1) * TILE).min(n);
+    let cols = b * TILE..((b + 1) * TILE).min(n);
+    let width = cols.len();
+    let z = z.as_slice().unwrap();
+    let mut out = vec![0.0; rows.len() * width];
+    for start in (0..t).step_by(BLOCK) {
+        let end = (start + BLOCK).min(t);
+        for (i, row) in rows.clone().enumerate() {
+            let x = &z[row * t + start..row * t + end];
+            for (j, col) in cols.clone().enumerate() {
+                // Only the upper triangle of a diagonal tile is needed
+                if a == b && col < row {
+                    continue;
+                }
+                out[i * width + j] += simd::dot_f64(x, &z[col * t + start..col * t + end]);
+            }
+        }
+    }
+    out
+}
+
+/// Gram matrix `z · zᵀ` of the standardized rows, computed tile by tile in parallel
+fn tiled_gram(z: &Array2<f64>) -> Array2<f64> {
+    let n = z.nrows();
+    let tiles = n.div_ceil(TILE);
+    let pairs: Vec<(usize, usize)> = (0..tiles).flat_map(|a| (a..tiles).map(move |b| (a, b))).collect();
+    let blocks: Vec<Vec<f64>> = pairs.par_iter().map(|&(a, b)| tile(z, a, b)).collect();
+
+    let mut matrix = Array2::zeros((n, n));
+    for (&(a, b), block) in pairs.iter().zip(blocks) {
+        let width = ((b + 1) * TILE).min(n) - b * TILE;
+        for (k, &value) in block.iter().enumerate() {
+            let (row, col) = (a * TILE + k / width, b * TILE + k % width);
+            if col >= row {
+                matrix[[row, col]] = value;
+                matrix[[col, row]] = value;
+            }
+        }
+    }
+    matrix
+}
+
+/// Full Pearson correlation matrix of the columns of `returns_2d` (observations in rows,
+/// one symbol per column), like `numpy.corrcoef(returns_2d, rowvar=False)`.
+///
+/// The columns are standardized once, then the upper triangle is computed in tiles of
+/// symbol pairs, split across threads, with the observations walked in cache-sized
+/// blocks. Pairs involving a constant column are 0, as in `correlation_rust`; NaNs
+/// propagate to every pair involving that column, and fewer than 2 observations give an
+/// all-NaN matrix. `device="gpu"` computes the products in float32 on the GPU when the
+/// `gpu` feature is built and a device is available, falling back to the CPU otherwise.
+#[pyfunction]
+#[pyo3(signature = (returns_2d, device="cpu"))]
+pub fn correlation_matrix_rust<'py>(
+    py: Python<'py>,
+    returns_2d: PyReadonlyArray2<'py, f64>,
+    device: &str
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let device = Device::parse(device)?;
+    let returns = returns_2d.as_array();
+    if returns.nrows() < 2 {
+        let n = returns.ncols();
+        return Ok(PyArray2::from_owned_array_bound(py, Array2::from_elem((n, n), f64::NAN)));
+    }
+
+    let matrix = py.allow_threads(|| {
+        threads::install(|| {
+            let z = standardize(returns);
+            let gram = match device {
+                Device::Gpu => gpu::gram(&z),
+                Device::Cpu => None,
+            };
+            // Rounding can push perfectly correlated pairs just past 1
+            let matrix = gram.unwrap_or_else(|| tiled_gram(&z)).mapv_into(|v| v.clamp(-1.0, 1.0));
+            validate::check("correlation_matrix", matrix.as_slice().unwrap(), || {
+                validate::reference::correlation_matrix(returns)
+            });
+            matrix
+        })
+    });
+    Ok(PyArray2::from_owned_array_bound(py, matrix))
+}
+
+/// Correlation matrix of the columns of `returns_2d` over the last `window` rows, every
+/// `stride` rows, for monitoring regime shifts.
+///
+/// Returns an array of shape `(slices, symbols, symbols)` where slice `k` covers rows
+/// `k * stride .. k * stride + window` (so rows left over after the last full window are
+/// not used, and fewer than `window` rows give no slices). With `align="same"` there is
+/// one slice per row instead, holding the window ending at that row and NaN for rows
+/// with no full window or skipped by `stride`; an integer `align=k` shifts the slices `k`
+/// rows later. Slices are computed in parallel, and the symbol pairs of each slice are
+/// tiled across threads as in `correlation_matrix_rust`, whose conventions for constant
+/// columns and NaNs apply per window.
+#[pyfunction]
+#[pyo3(signature = (returns_2d, window, stride=1, align=None, config=None))]
+pub fn rolling_correlation_matrix_rust<'py>(
+    py: Python<'py>,
+    returns_2d: PyReadonlyArray2<'py, f64>,
+    window: usize,
+    stride: usize,
+    align: Option<Alignment>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyArray3<f64>>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let returns = returns_2d.as_array();
+    let (t, n) = returns.dim();
+    if window < 2 {
+        return Err(crate::errors::InvalidWindowError::new_err(
+            "Window size must be >= 2 for a correlation"
+        ));
+    }
+    let valid = crate::errors::check_window(window, t)?;
+    if stride == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "stride must be > 0"
+        ));
+    }
+
+    let slices = valid.div_ceil(stride);
+    let matrices = py.allow_threads(|| {
+        threads::install(|| {
+            let mut matrices = Array3::zeros((slices, n, n));
+            matrices.axis_iter_mut(Axis(0)).into_par_iter().enumerate().for_each(|(k, mut matrix)| {
+                let start = k * stride;
+                let z = standardize(returns.slice(s![start..start + window, ..]));
+                matrix.assign(&tiled_gram(&z).mapv_into(|v| v.clamp(-1.0, 1.0)));
+                validate::check("rolling_correlation_matrix", matrix.as_slice().unwrap(), || {
+                    validate::reference::correlation_matrix(returns.slice(s![start..start + window, ..]))
+                });
+            });
+            if align == Alignment::Valid {
+                return matrices;
+            }
+            let mut aligned = Array3::from_elem((t, n, n), f64::NAN);
+            for (k, matrix) in matrices.axis_iter(Axis(0)).enumerate() {
+                if let Some(row) = align.position(k * stride + window - 1, t) {
+                    aligned.index_axis_mut(Axis(0), row).assign(&matrix);
+                }
+            }
+            aligned
+        })
+    });
+    Ok(PyArray3::from_owned_array_bound(py, matrices))
+}
@@ -0,0 +1,213 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Fee rates by traded notional: `(min_volume, rate)` with ascending thresholds, the
+/// first applying from 0
+fn check_tiers(name: &str, tiers: &[(f64, f64)]) -> PyResult<()> {
+    if tiers.iter().any(|(v, r)| !v.is_finite() || !r.is_finite())
+        || tiers.windows(2).any(|w| w[1].0 <= w[0].0)
+    {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} must be (min_volume, rate) pairs with finite values and ascending volumes", name
+        )));
+    }
+    Ok(())
+}
+
+/// The rate of the highest tier reached by `volume`
+fn tier_rate(tiers: &[(f64, f64)], volume: f64) -> f64 {
+    match tiers.partition_point(|&(threshold, _)| threshold <= volume) {
+        0 => tiers.first().map_or(0.0, |t| t.1),
+        i => tiers[i - 1].1,
+    }
+}
+
+/// Trading and carrying costs, shared by the backtester and `Portfolio`.
+///
+/// A fill pays its notional times the maker or taker rate of the tier reached by the
+/// notional traded so far (rates may be negative for maker rebates), plus `per_share` per
+/// unit, and at least `min_fee` when it pays anything; the traded notional accumulates
+/// across fills until `reset_volume`, e.g. at the start of each month. Tiers are lists
+/// of `(min_volume, rate)` pairs with ascending volumes, the first applying from 0; a
+/// single rate can be given as `[(0, rate)]`. Holding a position costs `funding_rate` of
+/// its signed notional per year (perpetual-futures funding: longs pay when positive,
+/// shorts receive), and short positions additionally pay `borrow_rate` of their notional
+/// per year.
+#[pyclass(module = "fast_math")]
+#[derive(Clone)]
+pub struct CostModel {
+    maker: Vec<(f64, f64)>,
+    taker: Vec<(f64, f64)>,
+    per_share: f64,
+    min_fee: f64,
+    funding_rate: f64,
+    borrow_rate: f64,
+    volume: f64,
+}
+
+impl CostModel {
+    /// Fee of a fill of signed `qty` at `price`, counting its notional towards the tiers
+    pub fn fee(&mut self, qty: f64, price: f64, maker: bool) -> f64 {
+        let notional = (qty * price).abs();
+        let rate = tier_rate(if maker { &self.maker } else { &self.taker }, self.volume);
+        self.volume += notional;
+        let fee = notional * rate + qty.abs() * self.per_share;
+        if fee > 0.0 { fee.max(self.min_fee) } else { fee }
+    }
+
+    /// Funding and borrow cost of holding signed `position` at `price` for `years`
+    pub fn holding_cost(&self, position: f64, price: f64, years: f64) -> f64 {
+        let notional = position * price;
+        let borrow = if notional < 0.0 { -notional * self.borrow_rate } else { 0.0 };
+        (notional * self.funding_rate + borrow) * years
+    }
+}
+
+#[pymethods]
+impl CostModel {
+    #[new]
+    #[pyo3(signature = (
+        taker_tiers=Vec::new(),
+        maker_tiers=None,
+        per_share=0.0,
+        min_fee=0.0,
+        funding_rate=0.0,
+        borrow_rate=0.0
+    ))]
+    fn new(
+        taker_tiers: Vec<(f64, f64)>,
+        maker_tiers: Option<Vec<(f64, f64)>>,
+        per_share: f64,
+        min_fee: f64,
+        funding_rate: f64,
+        borrow_rate: f64
+    ) -> PyResult<Self> {
+        let maker_tiers = maker_tiers.unwrap_or_else(|| taker_tiers.clone());
+        check_tiers("taker_tiers", &taker_tiers)?;
+        check_tiers("maker_tiers", &maker_tiers)?;
+        let finite = [per_share, min_fee, funding_rate, borrow_rate].iter().all(|v| v.is_finite());
+        if !finite || min_fee < 0.0 || borrow_rate < 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Costs must be finite, and min_fee and borrow_rate >= 0"
+            ));
+        }
+        Ok(CostModel {
+            maker: maker_tiers,
+            taker: taker_tiers,
+            per_share,
+            min_fee,
+            funding_rate,
+            borrow_rate,
+            volume: 0.0,
+        })
+    }
+
+    /// Fee of one fill of signed `qty` at `price`, as maker (passive) or taker, counting
+    /// its notional towards the tiers
+    #[pyo3(name = "fee", signature = (qty, price, maker=false))]
+    fn py_fee(&mut self, qty: f64, price: f64, maker: bool) -> f64 {
+        self.fee(qty, price, maker)
+    }
+
+    /// Fees of a sequence of fills, in order, each counting towards the tiers of the next;
+    /// `maker` flags passive fills (default all taker)
+    #[pyo3(signature = (qty, price, maker=None))]
+    fn fees<'py>(
+        &mut self,
+        py: Python<'py>,
+        qty: Column<'py, f64>,
+        price: Column<'py, f64>,
+        maker: Option<Vec<bool>>
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let qty = qty.values()?;
+        let price = price.values()?;
+        if price.len() != qty.len() || maker.as_ref().is_some_and(|m| m.len() != qty.len()) {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "qty, price and maker must have the same length"
+            ));
+        }
+        let fees = (0..qty.len())
+            .map(|i| self.fee(qty[i], price[i], maker.as_ref().is_some_and(|m| m[i])))
+            .collect();
+        Ok(PyArray1::from_vec_bound(py, fees))
+    }
+
+    /// Funding and borrow cost of holding signed `position` at `price` for `years`
+    #[pyo3(name = "holding_cost")]
+    fn py_holding_cost(&self, position: f64, price: f64, years: f64) -> f64 {
+        self.holding_cost(position, price, years)
+    }
+
+    /// Notional traded since creation or the last `reset_volume`, which sets the tiers
+    #[getter]
+    fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// Restart the tier volume, e.g. at the start of a new fee period
+    fn reset_volume(&mut self) {
+        self.volume = 0.0;
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CostModel(taker_tiers={:?}, maker_tiers={:?}, per_share={}, funding_rate={}, borrow_rate={})",
+            self.taker, self.maker, self.per_share, self.funding_rate, self.borrow_rate
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(min_fee: f64) -> CostModel {
+        let taker = vec![(0.0, 0.001), (1_000.0, 0.0005), (5_000.0, 0.0002)];
+        let maker = vec![(0.0, 0.0), (1_000.0, -0.0001)];
+        CostModel::new(taker, Some(maker), 0.0, min_fee, 0.1, 0.05).unwrap()
+    }
+
+    #[test]
+    fn tiers_apply_from_their_threshold() {
+        let tiers = [(0.0, 0.3), (100.0, 0.2), (500.0, 0.1)];
+        let rates: Vec<f64> = [0.0, 99.99, 100.0, 499.0, 500.0, 1e9].iter().map(|&v| tier_rate(&tiers, v)).collect();
+        assert_eq!(rates, vec![0.3, 0.3, 0.2, 0.2, 0.1, 0.1]);
+
+        // Each fill pays the rate of the volume traded before it
+        let mut model = model(0.0);
+        assert_eq!(model.fee(10.0, 100.0, false), 1.0);
+        assert_eq!(model.volume, 1_000.0);
+        assert_eq!(model.fee(-40.0, 100.0, false), 2.0);
+        assert_eq!(model.fee(10.0, 100.0, false), 0.2);
+        // The maker side has its own tiers and may rebate
+        assert_eq!(model.fee(10.0, 100.0, true), -0.1);
+    }
+
+    #[test]
+    fn min_fee_floors_only_positive_fees() {
+        let mut model = model(0.5);
+        assert_eq!(model.fee(1.0, 100.0, false), 0.5);
+        assert_eq!(model.fee(3.0, 100.0, false), 0.5);
+        assert_eq!(model.fee(20.0, 100.0, false), 2.0);
+        // A rebate or a free fill is not raised to the minimum
+        assert!(model.fee(10.0, 100.0, true) < 0.0);
+        let mut free = model.clone();
+        free.maker = vec![(0.0, 0.0)];
+        assert_eq!(free.fee(10.0, 100.0, true), 0.0);
+    }
+
+    #[test]
+    fn holding_costs_accrue_with_time() {
+        let model = model(0.0);
+        let days = |n: f64| n / 365.0;
+        // Longs pay funding; shorts receive it but pay the borrow rate
+        let long = model.holding_cost(100.0, 50.0, days(73.0));
+        assert!((long - 5_000.0 * 0.1 * 0.2).abs() < 1e-9);
+        let short = model.holding_cost(-100.0, 50.0, days(73.0));
+        assert!((short - 5_000.0 * (0.05 - 0.1) * 0.2).abs() < 1e-9);
+        assert!((model.holding_cost(100.0, 50.0, days(146.0)) - 2.0 * long).abs() < 1e-9);
+        assert_eq!(model.holding_cost(0.0, 50.0, days(365.0)), 0.0);
+    }
+}
@@ -0,0 +1,196 @@
+use pyo3::prelude::*;
+use numpy::{PyArray2, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView2, ArrayViewMut1, Axis};
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// Apply `transform` to every row of `matrix` in parallel; each row is one timestamp's
+/// cross-section
+fn per_row(matrix: ArrayView2<'_, f64>, transform: impl Fn(ArrayViewMut1<'_, f64>) + Sync) -> Array2<f64> {
+    per_lane(matrix, 1, transform)
+}
+
+/// Apply `transform` in parallel to every lane of `matrix` along `axis`, as in numpy:
+/// 0 for each column, 1 for each row
+fn per_lane(matrix: ArrayView2<'_, f64>, axis: usize, transform: impl Fn(ArrayViewMut1<'_, f64>) + Sync) -> Array2<f64> {
+    let mut out = matrix.to_owned();
+    threads::install(|| out.axis_iter_mut(Axis(1 - axis)).into_par_iter().for_each(&transform));
+    out
+}
+
+fn check_axis(axis: usize) -> PyResult<()> {
+    if axis > 1 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "axis must be 0 (each column) or 1 (each row)"
+        ));
+    }
+    Ok(())
+}
+
+/// The non-NaN values of `lane`, sorted
+fn sorted_valid(lane: &ArrayViewMut1<'_, f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = lane.iter().copied().filter(|v| !v.is_nan()).collect();
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+/// Linearly interpolated quantile of sorted values, as numpy's default
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (low, fraction) = (position.floor() as usize, position.fract());
+    let high = (low + 1).min(sorted.len() - 1);
+    sorted[low] + fraction * (sorted[high] - sorted[low])
+}
+
+/// Clamp `lane` in place to its `lower_q` and `upper_q` quantiles over the non-NaN values
+fn winsorize_lane(mut lane: ArrayViewMut1<'_, f64>, lower_q: f64, upper_q: f64) {
+    let sorted = sorted_valid(&lane);
+    if sorted.is_empty() {
+        return;
+    }
+    let (low, high) = (quantile(&sorted, lower_q), quantile(&sorted, upper_q));
+    lane.mapv_inplace(|v| v.clamp(low, high));
+}
+
+/// Clamp `lane` in place to its median ± `n_mad` median absolute deviations
+fn clip_lane(mut lane: ArrayViewMut1<'_, f64>, n_mad: f64) {
+    let sorted = sorted_valid(&lane);
+    if sorted.is_empty() {
+        return;
+    }
+    let median = quantile(&sorted, 0.5);
+    let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    let width = n_mad * quantile(&deviations, 0.5);
+    lane.mapv_inplace(|v| v.clamp(median - width, median + width));
+}
+
+/// Average ranks (1-based) of the non-NaN values of `row`, in place, as fractions of
+/// their count when `pct`
+fn rank_row(mut row: ArrayViewMut1<'_, f64>, pct: bool) {
+    let mut order: Vec<(usize, f64)> = row.iter().copied().enumerate().filter(|(_, v)| !v.is_nan()).collect();
+    order.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let n = order.len() as f64;
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && order[end].1 == order[start].1 {
+            end += 1;
+        }
+        // Tied values share the mean of the ranks start + 1 ..= end
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &(i, _) in &order[start..end] {
+            row[i] = if pct { rank / n } else { rank };
+        }
+        start = end;
+    }
+}
+
+/// Standardize the non-NaN values of `row` in place; fewer than `ddof + 1` values or a
+/// constant row give NaN
+fn zscore_row(mut row: ArrayViewMut1<'_, f64>, ddof: usize) {
+    let (count, sum) = row.iter().filter(|v| !v.is_nan()).fold((0usize, 0.0), |(n, s), v| (n + 1, s + v));
+    let mean = sum / count as f64;
+    let squares: f64 = row.iter().filter(|v| !v.is_nan()).map(|v| (v - mean) * (v - mean)).sum();
+    let std = if count > ddof { (squares / (count - ddof) as f64).sqrt() } else { f64::NAN };
+    let valid = std > 0.0;
+    row.mapv_inplace(|v| if valid { (v - mean) / std } else { v * f64::NAN });
+}
+
+/// Rank each timestamp's values across symbols, for factor-model signals.
+///
+/// `matrix_2d` holds one row per timestamp and one column per symbol, as in
+/// `correlation_matrix_rust`. Every row is ranked independently, in parallel: 1 for the
+/// smallest value, ties sharing their average rank, NaN staying NaN and not counted.
+/// `pct=True` divides the ranks by the number of valid values in the row, giving ranks
+/// in `(0, 1]`.
+#[pyfunction]
+#[pyo3(signature = (matrix_2d, pct=false))]
+pub fn cross_sectional_rank_rust<'py>(
+    py: Python<'py>,
+    matrix_2d: PyReadonlyArray2<'py, f64>,
+    pct: bool
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let matrix = matrix_2d.as_array();
+    let ranks = py.allow_threads(|| per_row(matrix, |row| rank_row(row, pct)));
+    Ok(PyArray2::from_owned_array_bound(py, ranks))
+}
+
+/// Z-score each timestamp's values across symbols, for factor-model signals.
+///
+/// `matrix_2d` holds one row per timestamp and one column per symbol. Each row, in
+/// parallel, has its mean subtracted and is divided by its standard deviation with
+/// `ddof` (default 1, like pandas), both over the row's non-NaN values; NaN stays NaN.
+/// Rows with a zero standard deviation or no more than `ddof` valid values are all NaN.
+#[pyfunction]
+#[pyo3(signature = (matrix_2d, ddof=1))]
+pub fn cross_sectional_zscore_rust<'py>(
+    py: Python<'py>,
+    matrix_2d: PyReadonlyArray2<'py, f64>,
+    ddof: usize
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let matrix = matrix_2d.as_array();
+    let scores = py.allow_threads(|| per_row(matrix, |row| zscore_row(row, ddof)));
+    Ok(PyArray2::from_owned_array_bound(py, scores))
+}
+
+/// Winsorize a matrix: clamp each column's (or row's) values to its quantiles.
+///
+/// With `axis=0` (the default) every column of `matrix_2d` is processed on its own, in
+/// parallel, as numpy reductions do; `axis=1` does each row, i.e. each timestamp's
+/// cross-section of a factor. Values below the `lower_q` quantile are raised to it and
+/// values above the `upper_q` quantile lowered to it, the quantiles linearly interpolated
+/// over the lane's non-NaN values as numpy's default; NaN stays NaN.
+#[pyfunction]
+#[pyo3(signature = (matrix_2d, lower_q=0.01, upper_q=0.99, axis=0))]
+pub fn winsorize_rust<'py>(
+    py: Python<'py>,
+    matrix_2d: PyReadonlyArray2<'py, f64>,
+    lower_q: f64,
+    upper_q: f64,
+    axis: usize
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    check_axis(axis)?;
+    if !(0.0..=1.0).contains(&lower_q) || !(0.0..=1.0).contains(&upper_q) || lower_q > upper_q {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Quantiles must satisfy 0 <= lower_q <= upper_q <= 1"
+        ));
+    }
+    let matrix = matrix_2d.as_array();
+    let clamped = py.allow_threads(|| per_lane(matrix, axis, |lane| winsorize_lane(lane, lower_q, upper_q)));
+    Ok(PyArray2::from_owned_array_bound(py, clamped))
+}
+
+/// Clip outliers to the median ± `n_mad` median absolute deviations.
+///
+/// `data` is a 1D series, returned as the same array type, or a 2D matrix whose columns
+/// (`axis=0`, the default) or rows (`axis=1`) are clipped independently in parallel. The
+/// median and MAD are taken over the non-NaN values and the MAD is not rescaled, so for
+/// normal data `n_mad=3` clips at about two standard deviations (one is 1.4826 MAD).
+/// NaN stays NaN; a lane whose MAD is 0 is clipped to its median.
+#[pyfunction]
+#[pyo3(signature = (data, n_mad=3.0, axis=0))]
+pub fn clip_outliers_rust<'py>(
+    py: Python<'py>,
+    data: &Bound<'py, PyAny>,
+    n_mad: f64,
+    axis: usize
+) -> PyResult<PyObject> {
+    check_axis(axis)?;
+    if !n_mad.is_finite() || n_mad < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_mad must be a finite value >= 0"
+        ));
+    }
+    if let Ok(matrix) = data.extract::<PyReadonlyArray2<'py, f64>>() {
+        let matrix = matrix.as_array();
+        let clipped = py.allow_threads(|| per_lane(matrix, axis, |lane| clip_lane(lane, n_mad)));
+        return Ok(PyArray2::from_owned_array_bound(py, clipped).into_any().unbind());
+    }
+    let series: Column<'py, f64> = data.extract()?;
+    let mut values = ndarray::Array1::from(series.values()?.into_owned());
+    py.allow_threads(|| clip_lane(values.view_mut(), n_mad));
+    series.kind().wrap(py, values.to_vec())
+}
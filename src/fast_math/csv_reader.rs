@@ -0,0 +1,463 @@
+use std::fs::File;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use chrono::{DateTime, TimeZone};
+use chrono_tz::Tz;
+use memchr::{memchr, memchr_iter};
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::threads;
+
+/// numpy's NaT sentinel, used for local times that do not exist in the target timezone
+pub const NAT: i64 = i64::MIN;
+
+/// Target size of the chunks parsed in parallel
+const CHUNK_BYTES: usize = 4 << 20;
+
+/// Column positions resolved from the header (or the default layout)
+struct Layout {
+    timestamp: usize,
+    /// Separate time-of-day column when the file splits date and time
+    time: Option<usize>,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+}
+
+impl Layout {
+    fn default_columns(n_fields: usize) -> Self {
+        let volume = if n_fields > 5 { Some(5) } else { None };
+        Layout { timestamp: 0, time: None, open: 1, high: 2, low: 3, close: 4, volume }
+    }
+
+    fn from_header(names: &[String]) -> Option<Self> {
+        let find = |aliases: &[&str]| names.iter().position(|n| aliases.contains(&n.as_str()));
+        let date = find(&["date"]);
+        let time = find(&["time"]);
+        let (timestamp, time) = match find(&["timestamp", "datetime", "date_time", "ts"]) {
+            Some(ts) => (ts, None),
+            None => match (date, time) {
+                (Some(d), Some(t)) => (d, Some(t)),
+                (Some(d), None) => (d, None),
+                (None, Some(t)) => (t, None),
+                (None, None) => return None,
+            },
+        };
+        Some(Layout {
+            timestamp,
+            time,
+            open: find(&["open", "o"])?,
+            high: find(&["high", "h"])?,
+            low: find(&["low", "l"])?,
+            close: find(&["close", "c", "last"])?,
+            volume: find(&["volume", "vol", "v"]),
+        })
+    }
+
+    fn max_index(&self) -> usize {
+        [Some(self.timestamp), self.time, Some(self.open), Some(self.high), Some(self.low), Some(self.close), self.volume]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Default)]
+struct Columns {
+    timestamp: Vec<i64>,
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+    volume: Vec<f64>,
+}
+
+/// A parsed timestamp: nanoseconds since the epoch, and whether it still needs localizing
+enum Stamp {
+    Utc(i64),
+    Naive(i64),
+}
+
+fn trim(field: &[u8]) -> &[u8] {
+    let start = field.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(field.len());
+    let end = field.iter().rposition(|b| !b.is_ascii_whitespace()).map_or(start, |e| e + 1);
+    let field = &field[start..end];
+    match field {
+        [b'"', inner @ .., b'"'] => inner,
+        _ => field,
+    }
+}
+
+fn parse_float(field: &[u8]) -> Option<f64> {
+    let field = trim(field);
+    if field.is_empty() {
+        return Some(f64::NAN);
+    }
+    fast_float::parse(field).ok()
+}
+
+fn digits(bytes: &[u8]) -> Option<i64> {
+    if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    // More digits than an i64 holds make the field unparsable rather than wrapping
+    bytes.iter().try_fold(0i64, |acc, b| acc.checked_mul(10)?.checked_add((b - b'0') as i64))
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Hinnant's days_from_civil)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Parse `HH:MM[:SS[.fff]]` into nanoseconds since midnight, returning the unparsed rest
+fn parse_time_of_day(s: &[u8]) -> Option<(i64, &[u8])> {
+    if s.len() < 5 || s[2] != b':' {
+        return None;
+    }
+    let hour = digits(&s[0..2])?;
+    let minute = digits(&s[3..5])?;
+    let mut rest = &s[5..];
+    let mut second = 0;
+    let mut nanos = 0;
+    if rest.len() >= 3 && rest[0] == b':' {
+        second = digits(&rest[1..3])?;
+        rest = &rest[3..];
+        if let Some(b'.') = rest.first() {
+            let frac_len = rest[1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            let frac = &rest[1..1 + frac_len];
+            let scale = 10i64.pow(9 - frac.len().min(9) as u32);
+            nanos = digits(&frac[..frac.len().min(9)]).unwrap_or(0) * scale;
+            rest = &rest[1 + frac_len..];
+        }
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(((hour * 3600 + minute * 60 + second) * 1_000_000_000 + nanos, rest))
+}
+
+/// Parse a trailing `Z`, `+HH:MM` or `+HHMM` offset into seconds east of UTC
+fn parse_offset(s: &[u8]) -> Option<Option<i64>> {
+    match s {
+        [] => Some(None),
+        [b'Z'] | [b'z'] => Some(Some(0)),
+        [sign @ (b'+' | b'-'), rest @ ..] => {
+            let rest: Vec<u8> = rest.iter().copied().filter(|&b| b != b':').collect();
+            if rest.len() != 4 {
+                return None;
+            }
+            let offset = digits(&rest[0..2])? * 3600 + digits(&rest[2..4])? * 60;
+            Some(Some(if *sign == b'-' { -offset } else { offset }))
+        }
+        _ => None,
+    }
+}
+
+/// Scale an epoch value to nanoseconds, inferring s / ms / us / ns from its magnitude;
+/// None when the nanoseconds overflow an i64 (e.g. a hugely negative epoch)
+pub fn epoch_to_ns(value: i64) -> Option<i64> {
+    match value {
+        v if v < 100_000_000_000 => v.checked_mul(1_000_000_000),
+        v if v < 100_000_000_000_000 => v.checked_mul(1_000_000),
+        v if v < 100_000_000_000_000_000 => v.checked_mul(1_000),
+        v => Some(v),
+    }
+}
+
+/// Parse an epoch or ISO-like timestamp to UTC nanoseconds, treating naive times as UTC
+pub fn parse_utc_timestamp(field: &[u8]) -> Option<i64> {
+    match parse_stamp(field)? {
+        Stamp::Utc(ns) | Stamp::Naive(ns) => Some(ns),
+    }
+}
+
+/// Parse an epoch number (s / ms / us / ns by magnitude) or an ISO-like date-time
+fn parse_stamp(field: &[u8]) -> Option<Stamp> {
+    let field = trim(field);
+    if let Some(value) = digits(field) {
+        return epoch_to_ns(value).map(Stamp::Utc);
+    }
+
+    if field.len() < 10 || !matches!(field[4], b'-' | b'/') || field[7] != field[4] {
+        return None;
+    }
+    let days = days_from_civil(digits(&field[0..4])?, digits(&field[5..7])?, digits(&field[8..10])?);
+    let mut rest = &field[10..];
+    let mut time_ns = 0;
+    if let Some(b'T' | b' ') = rest.first() {
+        let (t, tail) = parse_time_of_day(&rest[1..])?;
+        time_ns = t;
+        rest = tail;
+    }
+    let ns = days * 86_400_000_000_000 + time_ns;
+    match parse_offset(trim(rest))? {
+        Some(offset) => Some(Stamp::Utc(ns - offset * 1_000_000_000)),
+        None => Some(Stamp::Naive(ns)),
+    }
+}
+
+/// Convert a naive local timestamp to UTC nanoseconds in `tz`; ambiguous times take the
+/// earlier instant and non-existent ones (DST gaps) become NaT
+fn localize(naive_ns: i64, tz: Option<&Tz>) -> i64 {
+    let Some(tz) = tz else { return naive_ns };
+    let secs = naive_ns.div_euclid(1_000_000_000);
+    let nanos = naive_ns.rem_euclid(1_000_000_000) as u32;
+    let Some(naive) = DateTime::from_timestamp(secs, nanos).map(|dt| dt.naive_utc()) else {
+        return NAT;
+    };
+    match tz.from_local_datetime(&naive).earliest() {
+        Some(local) => local.timestamp_nanos_opt().unwrap_or(NAT),
+        None => NAT,
+    }
+}
+
+fn split_fields(line: &[u8], delimiter: u8, max_index: usize, fields: &mut Vec<(usize, usize)>) {
+    fields.clear();
+    let mut start = 0;
+    while fields.len() <= max_index {
+        match memchr(delimiter, &line[start..]) {
+            Some(pos) => {
+                fields.push((start, start + pos));
+                start += pos + 1;
+            }
+            None => {
+                fields.push((start, line.len()));
+                break;
+            }
+        }
+    }
+}
+
+/// Parse one newline-aligned chunk; errors carry the offending line's index within the chunk
+fn parse_chunk(chunk: &[u8], layout: &Layout, delimiter: u8, tz: Option<&Tz>) -> Result<Columns, (usize, String)> {
+    let mut columns = Columns::default();
+    let mut fields = Vec::new();
+    let max_index = layout.max_index();
+
+    let mut line_start = 0;
+    for (line_no, line_end) in memchr_iter(b'\n', chunk).chain(std::iter::once(chunk.len())).enumerate() {
+        let line = &chunk[line_start..line_end.max(line_start)];
+        line_start = line_end + 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if trim(line).is_empty() {
+            continue;
+        }
+
+        split_fields(line, delimiter, max_index, &mut fields);
+        if fields.len() <= max_index {
+            return Err((line_no, format!("expected at least {} fields", max_index + 1)));
+        }
+        let field = |i: usize| &line[fields[i].0..fields[i].1];
+
+        let stamp = match layout.time {
+            Some(time) => {
+                let mut combined = trim(field(layout.timestamp)).to_vec();
+                combined.push(b' ');
+                combined.extend_from_slice(trim(field(time)));
+                parse_stamp(&combined)
+            }
+            None => parse_stamp(field(layout.timestamp)),
+        };
+        let timestamp = match stamp {
+            Some(Stamp::Utc(ns)) => ns,
+            Some(Stamp::Naive(ns)) => localize(ns, tz),
+            None => return Err((line_no, "invalid timestamp".to_string())),
+        };
+
+        let float = |i: usize, name: &str| parse_float(field(i)).ok_or_else(|| (line_no, format!("invalid {} value", name)));
+        columns.timestamp.push(timestamp);
+        columns.open.push(float(layout.open, "open")?);
+        columns.high.push(float(layout.high, "high")?);
+        columns.low.push(float(layout.low, "low")?);
+        columns.close.push(float(layout.close, "close")?);
+        columns.volume.push(match layout.volume {
+            Some(volume) => float(volume, "volume")?,
+            None => f64::NAN,
+        });
+    }
+
+    Ok(columns)
+}
+
+/// Split `body` into roughly `CHUNK_BYTES`-sized pieces that end on line boundaries
+fn chunk_bounds(body: &[u8]) -> Vec<(usize, usize)> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    while start < body.len() {
+        let target = (start + CHUNK_BYTES).min(body.len());
+        let end = match memchr(b'\n', &body[target..]) {
+            Some(pos) if target < body.len() => target + pos + 1,
+            _ => body.len(),
+        };
+        bounds.push((start, end));
+        start = end;
+    }
+    bounds
+}
+
+/// Read an OHLCV CSV file into numpy arrays.
+///
+/// The header is optional: columns are matched by name (`timestamp`/`datetime`, or separate
+/// `date` and `time` columns, then `open`, `high`, `low`, `close`, `volume`) and default to
+/// that order when the file has no header. Timestamps may be epoch numbers (unit inferred
+/// from magnitude) or ISO-like date-times; naive date-times are interpreted in `tz` (UTC if
+/// omitted). Returns a dict with an int64 `timestamp` column in UTC nanoseconds and float64
+/// price/volume columns; `volume` is NaN when the file has none.
+#[pyfunction]
+#[pyo3(signature = (path, tz=None, delimiter=","))]
+pub fn read_ohlcv_csv_rust<'py>(
+    py: Python<'py>,
+    path: &str,
+    tz: Option<&str>,
+    delimiter: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let tz: Option<Tz> = tz
+        .map(|name| name.parse::<Tz>())
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Unknown timezone: {}", e)))?;
+    let delimiter = match delimiter.as_bytes() {
+        [b] => *b,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Delimiter must be a single byte"
+            ))
+        }
+    };
+
+    let file = File::open(path)?;
+    // SAFETY: the file is only read, and callers must not truncate it while it is mapped
+    let mmap = unsafe { Mmap::map(&file)? };
+    let data = mmap.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&mmap[..]);
+
+    // Decide whether the first line is a header
+    let first_end = memchr(b'\n', data).unwrap_or(data.len());
+    let first_line = data[..first_end].strip_suffix(b"\r").unwrap_or(&data[..first_end]);
+    let first_fields: Vec<&[u8]> = first_line.split(|&b| b == delimiter).collect();
+    let has_header = parse_stamp(first_fields[0]).is_none()
+        && !matches!(first_fields.get(1), Some(f) if parse_float(f).is_some());
+
+    let (layout, body) = if has_header {
+        let names: Vec<String> = first_fields
+            .iter()
+            .map(|f| String::from_utf8_lossy(trim(f)).to_lowercase())
+            .collect();
+        let layout = Layout::from_header(&names).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(
+                "CSV header must name timestamp (or date/time), open, high, low and close columns"
+            )
+        })?;
+        (layout, &data[(first_end + 1).min(data.len())..])
+    } else {
+        (Layout::default_columns(first_fields.len()), data)
+    };
+
+    let bounds = chunk_bounds(body);
+    let chunks: Vec<Result<Columns, (usize, String)>> = py.allow_threads(|| {
+        threads::install(|| {
+            bounds
+                .par_iter()
+                .map(|&(start, end)| parse_chunk(&body[start..end], &layout, delimiter, tz.as_ref()))
+                .collect()
+        })
+    });
+
+    let mut columns = Columns::default();
+    for ((start, _), chunk) in bounds.iter().zip(chunks) {
+        match chunk {
+            Ok(chunk) => {
+                columns.timestamp.extend(chunk.timestamp);
+                columns.open.extend(chunk.open);
+                columns.high.extend(chunk.high);
+                columns.low.extend(chunk.low);
+                columns.close.extend(chunk.close);
+                columns.volume.extend(chunk.volume);
+            }
+            Err((line_in_chunk, message)) => {
+                let line = memchr_iter(b'\n', &body[..*start]).count() + line_in_chunk + 1 + has_header as usize;
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "{}: line {}: {}", path, line, message
+                )));
+            }
+        }
+    }
+
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_vec_bound(py, columns.timestamp))?;
+    result.set_item("open", PyArray1::from_vec_bound(py, columns.open))?;
+    result.set_item("high", PyArray1::from_vec_bound(py, columns.high))?;
+    result.set_item("low", PyArray1::from_vec_bound(py, columns.low))?;
+    result.set_item("close", PyArray1::from_vec_bound(py, columns.close))?;
+    result.set_item("volume", PyArray1::from_vec_bound(py, columns.volume))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_in_each_format() {
+        let tz: Tz = "Asia/Kolkata".parse().unwrap();
+        match parse_stamp(b"2024-01-02 09:15:00") {
+            Some(Stamp::Naive(ns)) => assert_eq!(localize(ns, Some(&tz)), 1_704_167_100_000_000_000),
+            _ => panic!("expected a naive stamp"),
+        }
+        match parse_stamp(b"2024-01-02T03:45:00.5Z") {
+            Some(Stamp::Utc(ns)) => assert_eq!(ns, 1_704_167_100_500_000_000),
+            _ => panic!("expected a UTC stamp"),
+        }
+        match parse_stamp(b"2024-01-02T09:15:00+05:30") {
+            Some(Stamp::Utc(ns)) => assert_eq!(ns, 1_704_167_100_000_000_000),
+            _ => panic!("expected a UTC stamp"),
+        }
+        match parse_stamp(b"1704167100") {
+            Some(Stamp::Utc(ns)) => assert_eq!(ns, 1_704_167_100_000_000_000),
+            _ => panic!("expected a UTC stamp"),
+        }
+    }
+
+    #[test]
+    fn epoch_overflow_is_unparsable() {
+        assert_eq!(epoch_to_ns(1_700_000_000), Some(1_700_000_000_000_000_000));
+        assert_eq!(epoch_to_ns(-10_000_000_000_000), None);
+        assert_eq!(digits(b"99999999999999999999999"), None);
+        assert!(parse_utc_timestamp(b"99999999999999999999999").is_none());
+    }
+
+    #[test]
+    fn chunks_parse_rows_and_report_bad_ones() {
+        let layout = Layout::default_columns(6);
+        let body = b"2024-01-02 09:15,1,2,0.5,1.5,100\r\n2024-01-02 09:16,1,2,0.5,1.5,\n";
+        let columns = parse_chunk(body, &layout, b',', None).unwrap();
+        assert_eq!(columns.timestamp.len(), 2);
+        assert!(columns.volume[1].is_nan());
+        assert!(parse_chunk(b"x,1,2,3,4,5\n", &layout, b',', None).is_err());
+    }
+
+    #[test]
+    fn chunks_cover_the_body_and_end_on_line_breaks() {
+        let line = b"2024-01-02 09:15,1,2,0.5,1.5,100\n";
+        let body: Vec<u8> = line.iter().copied().cycle().take(line.len() * (CHUNK_BYTES / line.len() * 2 + 3)).collect();
+        let bounds = chunk_bounds(&body);
+        assert!(bounds.len() >= 2);
+        assert_eq!(bounds[0].0, 0);
+        assert_eq!(bounds.last().unwrap().1, body.len());
+        for pair in bounds.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+            assert_eq!(body[pair[0].1 - 1], b'\n');
+        }
+        assert!(chunk_bounds(b"").is_empty());
+    }
+}
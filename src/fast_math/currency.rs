@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView2};
+
+use crate::column::Column;
+use crate::merge::{asof_index, Direction};
+
+/// One currency's FX rate history, in base currency per unit, timestamps ascending
+struct RateSeries {
+    timestamps: Vec<i64>,
+    rates: Vec<f64>,
+}
+
+impl RateSeries {
+    /// Parse a `(timestamps, rates)` tuple or a mapping with `timestamp` and `rate` columns
+    fn parse(currency: &str, data: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let (timestamps, rates): (Column<'_, i64>, Column<'_, f64>) = match data.downcast::<PyTuple>() {
+            Ok(pair) => pair.extract()?,
+            Err(_) => {
+                let required = |name: &str| {
+                    data.get_item(name).map_err(|_| {
+                        pyo3::exceptions::PyKeyError::new_err(format!(
+                            "fx rates of '{}' need a '{}' column", currency, name
+                        ))
+                    })
+                };
+                (required("timestamp")?.extract()?, required("rate")?.extract()?)
+            }
+        };
+        let (timestamps, rates) = (timestamps.values()?.into_owned(), rates.values()?.into_owned());
+        if timestamps.len() != rates.len() {
+            return Err(crate::errors::LengthMismatchError::new_err(format!(
+                "fx rates of '{}' must have one rate per timestamp", currency
+            )));
+        }
+        if timestamps.windows(2).any(|w| w[1] < w[0]) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "fx rate timestamps of '{}' must be sorted ascending", currency
+            )));
+        }
+        Ok(RateSeries { timestamps, rates })
+    }
+
+    /// The prevailing rate at each of `timestamps`, NaN before the first or beyond `tolerance`
+    fn asof(&self, timestamps: &[i64], tolerance: Option<i64>) -> Vec<f64> {
+        timestamps
+            .iter()
+            .map(|&t| {
+                asof_index(&self.timestamps, t, Direction::Backward, tolerance, true).map_or(f64::NAN, |i| self.rates[i])
+            })
+            .collect()
+    }
+}
+
+/// One currency's P&L: per row in its own units and converted, and the converted value
+/// of the P&L accumulated in it so far
+struct CurrencyPnl {
+    local: Vec<f64>,
+    base: Vec<f64>,
+    exposure: Vec<f64>,
+}
+
+/// Convert the P&L `columns` of one currency at `rates` into the same columns of
+/// `converted`. NaN P&L (no position) counts as 0; a missing rate gives NaN wherever
+/// there is P&L to convert
+fn convert(pnl: ArrayView2<'_, f64>, columns: &[usize], rates: &[f64], converted: &mut Array2<f64>) -> CurrencyPnl {
+    let to_base = |value: f64, rate: f64| if value.is_nan() || value == 0.0 { 0.0 } else { value * rate };
+    for &j in columns {
+        for (t, out) in converted.column_mut(j).iter_mut().enumerate() {
+            *out = to_base(pnl[[t, j]], rates[t]);
+        }
+    }
+    let n = pnl.nrows();
+    let local: Vec<f64> =
+        (0..n).map(|t| columns.iter().map(|&j| pnl[[t, j]]).filter(|v| !v.is_nan()).sum()).collect();
+    let base = (0..n).map(|t| columns.iter().map(|&j| converted[[t, j]]).sum()).collect();
+    let mut balance = 0.0;
+    let exposure = local
+        .iter()
+        .zip(rates)
+        .map(|(&value, &rate)| {
+            balance += value;
+            to_base(balance, rate)
+        })
+        .collect();
+    CurrencyPnl { local, base, exposure }
+}
+
+/// Convert per-symbol P&L in several currencies into one base currency.
+///
+/// `pnl_by_symbol` holds one row per timestamp of `timestamps` and one column per symbol,
+/// each in the currency named by the matching entry of `symbol_ccy`. `fx_rates_ts` maps
+/// each currency other than `base_ccy` to its rate history in base currency per unit
+/// (e.g. 1.08 for EUR with a USD base), as a `(timestamps, rates)` tuple or a mapping
+/// with `timestamp` and `rate` columns. Every row is converted at the last rate at or
+/// before its timestamp, no older than `tolerance` when given. NaN P&L counts as 0,
+/// while P&L without a rate converts to NaN, so a missing rate shows in the totals
+/// rather than dropping out. Returns a dict with the base currency `pnl` per row, the
+/// converted `pnl_by_symbol` and `by_currency`, mapping each currency to its `rate`, its
+/// `local_pnl` (summed in its own units) and converted `pnl` per row, and its `exposure`,
+/// the P&L accumulated in that currency so far valued at the current rate.
+#[pyfunction]
+#[pyo3(signature = (pnl_by_symbol, symbol_ccy, fx_rates_ts, timestamps, base_ccy="USD", tolerance=None))]
+pub fn convert_pnl_rust<'py>(
+    py: Python<'py>,
+    pnl_by_symbol: PyReadonlyArray2<'py, f64>,
+    symbol_ccy: Bound<'py, PyAny>,
+    fx_rates_ts: Bound<'py, PyDict>,
+    timestamps: Column<'py, i64>,
+    base_ccy: &str,
+    tolerance: Option<i64>
+) -> PyResult<Bound<'py, PyDict>> {
+    let pnl = pnl_by_symbol.as_array();
+    let timestamps = timestamps.values()?;
+    // Lists and object arrays extract directly; Series and the like go through tolist()
+    let symbol_ccy: Vec<String> = match symbol_ccy.extract() {
+        Ok(values) => values,
+        Err(_) if symbol_ccy.hasattr("tolist")? => symbol_ccy.call_method0("tolist")?.extract()?,
+        Err(err) => return Err(err),
+    };
+    if symbol_ccy.len() != pnl.ncols() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "symbol_ccy must have one currency per symbol (column)"
+        ));
+    }
+    if timestamps.len() != pnl.nrows() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "pnl_by_symbol must have one row per timestamp"
+        ));
+    }
+    if tolerance.is_some_and(|t| t < 0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tolerance must be >= 0"
+        ));
+    }
+
+    let mut currencies: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (j, currency) in symbol_ccy.iter().enumerate() {
+        currencies.entry(currency.as_str()).or_default().push(j);
+    }
+    let mut rates: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for &currency in currencies.keys() {
+        let series = if currency == base_ccy {
+            vec![1.0; timestamps.len()]
+        } else {
+            let data = fx_rates_ts.get_item(currency)?.ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!(
+                    "fx_rates_ts has no rates for '{}'", currency
+                ))
+            })?;
+            let series = RateSeries::parse(currency, &data)?;
+            py.allow_threads(|| series.asof(&timestamps, tolerance))
+        };
+        rates.insert(currency, series);
+    }
+
+    let (converted, by_currency) = py.allow_threads(|| {
+        let mut converted = Array2::zeros(pnl.dim());
+        let mut by_currency = Vec::with_capacity(currencies.len());
+        for (currency, columns) in &currencies {
+            by_currency.push((*currency, convert(pnl, columns, &rates[currency], &mut converted)));
+        }
+        (converted, by_currency)
+    });
+    let total: Vec<f64> = converted.rows().into_iter().map(|row| row.sum()).collect();
+
+    let result = PyDict::new_bound(py);
+    result.set_item("pnl", PyArray1::from_vec_bound(py, total))?;
+    result.set_item("pnl_by_symbol", PyArray2::from_owned_array_bound(py, converted))?;
+    let currencies = PyDict::new_bound(py);
+    for (currency, totals) in by_currency {
+        let entry = PyDict::new_bound(py);
+        entry.set_item("rate", PyArray1::from_slice_bound(py, &rates[currency]))?;
+        entry.set_item("local_pnl", PyArray1::from_vec_bound(py, totals.local))?;
+        entry.set_item("pnl", PyArray1::from_vec_bound(py, totals.base))?;
+        entry.set_item("exposure", PyArray1::from_vec_bound(py, totals.exposure))?;
+        currencies.set_item(currency, entry)?;
+    }
+    result.set_item("by_currency", currencies)?;
+    Ok(result)
+}
@@ -0,0 +1,221 @@
+use std::ops::Range;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use numpy::{PyArray1, PyArray2};
+use ndarray::Array2;
+
+use crate::column::Column;
+
+/// Sample spans `[start, end]` in time, checked to start in order and end no earlier than
+/// they start. Without `start_times` sample `i` starts at `i`, for bar-indexed events.
+fn sample_spans(event_times: &[i64], start_times: Option<&[i64]>) -> PyResult<(Vec<i64>, Vec<i64>)> {
+    let starts: Vec<i64> = match start_times {
+        Some(starts) if starts.len() != event_times.len() => {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "start_times and event_times must have the same length"
+            ))
+        }
+        Some(starts) => starts.to_vec(),
+        None => (0..event_times.len() as i64).collect(),
+    };
+    if starts.windows(2).any(|w| w[0] > w[1]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Samples must be sorted by start time"
+        ));
+    }
+    if let Some(i) = (0..starts.len()).find(|&i| event_times[i] < starts[i]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Sample {} ends at {} before it starts at {}", i, event_times[i], starts[i]
+        )));
+    }
+    Ok((starts, event_times.to_vec()))
+}
+
+/// Contiguous groups of `0..n` as `numpy.array_split`: the first `n % k` one longer
+fn groups(n: usize, k: usize) -> Vec<Range<usize>> {
+    let (size, extra) = (n / k, n % k);
+    let mut start = 0;
+    (0..k)
+        .map(|g| {
+            let end = start + size + usize::from(g < extra);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .collect()
+}
+
+fn embargo_size(n: usize, embargo_pct: f64) -> PyResult<usize> {
+    if !(0.0..1.0).contains(&embargo_pct) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "embargo_pct must be in [0, 1)"
+        ));
+    }
+    Ok((n as f64 * embargo_pct) as usize)
+}
+
+/// Training samples for the test groups `test`: those outside them whose spans end before
+/// each test group starts or, once past it, start after every test span has ended and
+/// the following `embargo` samples
+fn purged_train(starts: &[i64], ends: &[i64], test: &[Range<usize>], embargo: usize) -> Vec<usize> {
+    let n = starts.len();
+    // Per test group: its start time, and the first sample after it free to train on
+    let bounds: Vec<(Range<usize>, i64, usize)> = test
+        .iter()
+        .map(|group| {
+            let last_end = ends[group.clone()].iter().copied().max().unwrap_or(i64::MIN);
+            let after = (group.end..n).find(|&i| starts[i] > last_end).unwrap_or(n);
+            (group.clone(), starts[group.start], after + embargo)
+        })
+        .collect();
+    (0..n)
+        .filter(|&i| {
+            bounds.iter().all(|(group, test_start, resume)| {
+                if i < group.start { ends[i] < *test_start } else { i >= group.end && i >= *resume }
+            })
+        })
+        .collect()
+}
+
+fn split_tuple(py: Python<'_>, train: Vec<usize>, test: &[Range<usize>]) -> PyObject {
+    let test = test.iter().flat_map(|group| group.clone().map(|i| i as i64));
+    (
+        PyArray1::from_iter_bound(py, train.into_iter().map(|i| i as i64)),
+        PyArray1::from_iter_bound(py, test),
+    )
+        .into_py(py)
+}
+
+/// Purged k-fold cross-validation splits (López de Prado's `PurgedKFold`, *Advances in
+/// Financial Machine Learning* section 7.4).
+///
+/// Sample `i` is a label spanning `start_times[i]` to `event_times[i]`, the time its
+/// outcome is known (such as the touch indices of `triple_barrier_rust`); without
+/// `start_times` sample `i` starts at bar `i`. Samples must be sorted by start. The
+/// samples are cut into `n_splits` contiguous test folds, as scikit-learn's `KFold`
+/// without shuffling; training samples whose spans overlap the test fold are purged, and
+/// the `int(embargo_pct * n)` samples after it are embargoed too, since their features
+/// can still carry information from the test period. Returns a list of `(train_indices,
+/// test_indices)` pairs of arrays, one per fold.
+#[pyfunction]
+#[pyo3(signature = (event_times, n_splits=5, embargo_pct=0.0, start_times=None))]
+pub fn purged_kfold_rust<'py>(
+    py: Python<'py>,
+    event_times: Column<'py, i64>,
+    n_splits: usize,
+    embargo_pct: f64,
+    start_times: Option<Column<'py, i64>>
+) -> PyResult<Bound<'py, PyList>> {
+    let event_times = event_times.values()?;
+    let start_times = start_times.as_ref().map(|s| s.values()).transpose()?;
+    let (starts, ends) = sample_spans(&event_times, start_times.as_deref())?;
+    let n = starts.len();
+    if n_splits < 2 || n_splits > n {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "n_splits must be between 2 and the number of samples ({})", n
+        )));
+    }
+    let embargo = embargo_size(n, embargo_pct)?;
+    let folds = groups(n, n_splits);
+    let trains: Vec<Vec<usize>> = py.allow_threads(|| {
+        folds.iter().map(|fold| purged_train(&starts, &ends, std::slice::from_ref(fold), embargo)).collect()
+    });
+    let result = PyList::empty_bound(py);
+    for (train, fold) in trains.into_iter().zip(&folds) {
+        result.append(split_tuple(py, train, std::slice::from_ref(fold)))?;
+    }
+    Ok(result)
+}
+
+/// The `k`-element subsets of `0..n` in lexicographic order, as `itertools.combinations`
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut current: Vec<usize> = (0..k).collect();
+    loop {
+        result.push(current.clone());
+        // Advance the rightmost element that can still move
+        let Some(i) = (0..k).rev().find(|&i| current[i] < n - k + i) else {
+            return result;
+        };
+        current[i] += 1;
+        for j in i + 1..k {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
+
+/// Combinatorial purged cross-validation splits (López de Prado, *Advances in Financial
+/// Machine Learning* section 12.4).
+///
+/// Samples are given as for `purged_kfold_rust` and cut into `n_groups` contiguous
+/// groups. Every choice of `n_test_groups` of them is tested once, training on the
+/// remaining samples after purging those overlapping any test group and embargoing the
+/// `int(embargo_pct * n)` samples after each. Stitching the test predictions together
+/// gives `n_test_groups / n_groups * C(n_groups, n_test_groups)` complete backtest paths
+/// rather than the single path of walk-forward testing. Returns a dict with `splits`, a
+/// list of `(train_indices, test_indices)` pairs in `itertools.combinations` order,
+/// `test_groups`, the groups each split tests (one row per split), `groups`, the group
+/// of each sample, and `paths`, one row per backtest path giving for each group the split
+/// whose predictions the path uses there.
+#[pyfunction]
+#[pyo3(signature = (event_times, n_groups=6, n_test_groups=2, embargo_pct=0.0, start_times=None))]
+pub fn combinatorial_purged_cv_rust<'py>(
+    py: Python<'py>,
+    event_times: Column<'py, i64>,
+    n_groups: usize,
+    n_test_groups: usize,
+    embargo_pct: f64,
+    start_times: Option<Column<'py, i64>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let event_times = event_times.values()?;
+    let start_times = start_times.as_ref().map(|s| s.values()).transpose()?;
+    let (starts, ends) = sample_spans(&event_times, start_times.as_deref())?;
+    let n = starts.len();
+    if n_groups < 2 || n_groups > n {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "n_groups must be between 2 and the number of samples ({})", n
+        )));
+    }
+    if n_test_groups == 0 || n_test_groups >= n_groups {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_test_groups must be between 1 and n_groups - 1"
+        ));
+    }
+    let embargo = embargo_size(n, embargo_pct)?;
+    let ranges = groups(n, n_groups);
+    let chosen = combinations(n_groups, n_test_groups);
+    let trains: Vec<Vec<usize>> = py.allow_threads(|| {
+        chosen
+            .iter()
+            .map(|c| {
+                let test: Vec<Range<usize>> = c.iter().map(|&g| ranges[g].clone()).collect();
+                purged_train(&starts, &ends, &test, embargo)
+            })
+            .collect()
+    });
+    // Path p takes, for each group, the p-th split testing it
+    let n_paths = chosen.len() * n_test_groups / n_groups;
+    let mut paths = Array2::<i64>::zeros((n_paths, n_groups));
+    for g in 0..n_groups {
+        let testing = chosen.iter().enumerate().filter(|(_, c)| c.contains(&g)).map(|(s, _)| s as i64);
+        for (p, split) in testing.enumerate() {
+            paths[[p, g]] = split;
+        }
+    }
+
+    let splits = PyList::empty_bound(py);
+    for (train, c) in trains.into_iter().zip(&chosen) {
+        let test: Vec<Range<usize>> = c.iter().map(|&g| ranges[g].clone()).collect();
+        splits.append(split_tuple(py, train, &test))?;
+    }
+    let test_groups = Array2::from_shape_fn((chosen.len(), n_test_groups), |(s, j)| chosen[s][j] as i64);
+    let sample_groups = ranges.iter().enumerate().flat_map(|(g, r)| r.clone().map(move |_| g as i64));
+
+    let result = PyDict::new_bound(py);
+    result.set_item("splits", splits)?;
+    result.set_item("test_groups", PyArray2::from_owned_array_bound(py, test_groups))?;
+    result.set_item("groups", PyArray1::from_iter_bound(py, sample_groups))?;
+    result.set_item("paths", PyArray2::from_owned_array_bound(py, paths))?;
+    Ok(result)
+}
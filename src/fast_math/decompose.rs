@@ -0,0 +1,257 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// How `detrend_rust` estimates the trend
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Least-squares line
+    Linear,
+    /// Hodrick-Prescott filter
+    Hp,
+    /// Centred moving average
+    MovingAverage,
+}
+
+impl Method {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "linear" => Ok(Method::Linear),
+            "hp" => Ok(Method::Hp),
+            "moving_average" | "ma" => Ok(Method::MovingAverage),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'linear', 'hp' or 'moving_average'", name
+            ))),
+        }
+    }
+}
+
+/// Least-squares line through the non-NaN values, evaluated at every position
+fn linear_trend(values: &[f64]) -> Vec<f64> {
+    let points: Vec<(f64, f64)> =
+        values.iter().enumerate().filter(|(_, v)| !v.is_nan()).map(|(t, &v)| (t as f64, v)).collect();
+    let n = points.len() as f64;
+    let (mean_t, mean_v) = points.iter().fold((0.0, 0.0), |(a, b), (t, v)| (a + t / n, b + v / n));
+    let (sxy, sxx) = points.iter().fold((0.0, 0.0), |(sxy, sxx), (t, v)| {
+        (sxy + (t - mean_t) * (v - mean_v), sxx + (t - mean_t) * (t - mean_t))
+    });
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    (0..values.len()).map(|t| mean_v + slope * (t as f64 - mean_t)).collect()
+}
+
+/// Hodrick-Prescott trend: the solution of `(I + lamb D'D) trend = values` for the
+/// second-difference operator `D`, by a banded Cholesky factorisation in linear time
+fn hp_trend(values: &[f64], lamb: f64) -> Vec<f64> {
+    let n = values.len();
+    if n < 3 || values.iter().any(|v| v.is_nan()) {
+        return if n < 3 { values.to_vec() } else { vec![f64::NAN; n] };
+    }
+    // band[i][k] holds entry (i, i - k) of the symmetric pentadiagonal system
+    let mut band = vec![[1.0, 0.0, 0.0]; n];
+    let d = [1.0, -2.0, 1.0];
+    for r in 0..n - 2 {
+        for a in 0..3 {
+            for b in 0..=a {
+                band[r + a][a - b] += lamb * d[a] * d[b];
+            }
+        }
+    }
+    // Factor in place: band[i][k] becomes entry (i, i - k) of the lower factor
+    for i in 0..n {
+        for k in (1..=2).rev() {
+            if k > i {
+                continue;
+            }
+            let mut sum = band[i][k];
+            for m in k + 1..=2 {
+                if m <= i {
+                    sum -= band[i][m] * band[i - k][m - k];
+                }
+            }
+            band[i][k] = sum / band[i - k][0];
+        }
+        let diagonal = band[i][0] - band[i][1] * band[i][1] - band[i][2] * band[i][2];
+        band[i][0] = diagonal.sqrt();
+    }
+    let mut x = values.to_vec();
+    for i in 0..n {
+        for k in 1..=2.min(i) {
+            x[i] -= band[i][k] * x[i - k];
+        }
+        x[i] /= band[i][0];
+    }
+    for i in (0..n).rev() {
+        for k in 1..=2.min(n - 1 - i) {
+            x[i] -= band[i + k][k] * x[i + k];
+        }
+        x[i] /= band[i][0];
+    }
+    x
+}
+
+/// Centred moving average over `window` values, a 2 x `window` average for even windows
+/// so it stays centred, skipping NaN and averaging whatever part of the window exists at
+/// the ends
+fn centred_average(values: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+    let weight = |j: usize| if window.is_multiple_of(2) && (j == 0 || j == 2 * half) { 0.5 } else { 1.0 };
+    (0..values.len())
+        .map(|t| {
+            let (mut sum, mut total) = (0.0, 0.0);
+            for j in 0..=2 * half {
+                let Some(i) = (t + j).checked_sub(half).filter(|&i| i < values.len()) else { continue };
+                if !values[i].is_nan() {
+                    sum += weight(j) * values[i];
+                    total += weight(j);
+                }
+            }
+            if total > 0.0 { sum / total } else { f64::NAN }
+        })
+        .collect()
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let n = values.len();
+    if n == 0 {
+        f64::NAN
+    } else if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        0.5 * (values[n / 2 - 1] + values[n / 2])
+    }
+}
+
+/// Trend, seasonal and residual components of `values` with a season of `period` values
+struct Decomposition {
+    trend: Vec<f64>,
+    seasonal: Vec<f64>,
+    /// The seasonal value at each of the `period` phases
+    pattern: Vec<f64>,
+}
+
+/// Alternately estimate the trend as a centred moving average over one period of the
+/// deseasonalised values and the seasonal pattern as the per-phase mean (median when
+/// `robust`) of the detrended ones, normalised to sum to zero
+fn decompose(values: &[f64], period: usize, iterations: usize, robust: bool) -> Decomposition {
+    let n = values.len();
+    let mut seasonal = vec![0.0; n];
+    let mut trend = Vec::new();
+    let mut pattern = vec![0.0; period];
+    for _ in 0..iterations {
+        let deseasonalised: Vec<f64> = values.iter().zip(&seasonal).map(|(v, s)| v - s).collect();
+        trend = centred_average(&deseasonalised, period);
+        for (phase, value) in pattern.iter_mut().enumerate() {
+            let mut detrended: Vec<f64> = (phase..n)
+                .step_by(period)
+                .map(|t| values[t] - trend[t])
+                .filter(|v| !v.is_nan())
+                .collect();
+            *value = if robust {
+                median(&mut detrended)
+            } else {
+                detrended.iter().sum::<f64>() / detrended.len() as f64
+            };
+        }
+        let valid: Vec<f64> = pattern.iter().copied().filter(|v| !v.is_nan()).collect();
+        let mean = valid.iter().sum::<f64>() / valid.len().max(1) as f64;
+        pattern.iter_mut().for_each(|v| *v -= mean);
+        seasonal = (0..n).map(|t| pattern[t % period]).collect();
+    }
+    Decomposition { trend, seasonal, pattern }
+}
+
+/// Remove the trend from a series.
+///
+/// `method="linear"` (the default) fits a least-squares line; "hp" applies the
+/// Hodrick-Prescott filter with smoothing `lamb` (1600 for quarterly data, larger for
+/// finer bars; solved in linear time, NaN input gives NaN); "moving_average" takes a
+/// centred moving average over `window` values (required; even windows use a 2 x window
+/// average to stay centred), averaging the part of the window that exists at the ends so
+/// there is no warm-up. NaN values are skipped by the linear fit and the moving average
+/// and stay NaN in the residual. Returns a dict with the `trend` and the `residual`,
+/// `data - trend`.
+#[pyfunction]
+#[pyo3(signature = (data, method="linear", lamb=1600.0, window=None))]
+pub fn detrend_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    method: &str,
+    lamb: f64,
+    window: Option<usize>
+) -> PyResult<Bound<'py, PyDict>> {
+    let method = Method::parse(method)?;
+    if lamb.is_nan() || lamb < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "lamb must be >= 0"
+        ));
+    }
+    let window = match (method, window) {
+        (Method::MovingAverage, None) => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "method='moving_average' needs a window"
+            ))
+        }
+        (_, Some(window)) => {
+            crate::errors::require_window(window)?;
+            window
+        }
+        (_, None) => 0,
+    };
+    let values = data.values()?;
+    let trend = py.allow_threads(|| match method {
+        Method::Linear => linear_trend(&values),
+        Method::Hp => hp_trend(&values, lamb),
+        Method::MovingAverage => centred_average(&values, window),
+    });
+    let residual: Vec<f64> = values.iter().zip(&trend).map(|(v, t)| v - t).collect();
+    let result = PyDict::new_bound(py);
+    result.set_item("trend", PyArray1::from_vec_bound(py, trend))?;
+    result.set_item("residual", PyArray1::from_vec_bound(py, residual))?;
+    Ok(result)
+}
+
+/// Split a series into trend, seasonal and residual parts, e.g. intraday seasonality.
+///
+/// `period` is the season length in bars, such as the bars in a trading day, and bar `t`
+/// is at phase `t % period`. Like STL, the decomposition alternates for `iterations`
+/// (default 2) passes between a trend, the centred moving average over one period of the
+/// deseasonalised series (with a 2 x period average for even periods, and shrinking to
+/// the available values at the ends), and a seasonal pattern, the mean of the detrended
+/// values at each phase, or their median with `robust=True` to resist outliers, shifted
+/// to average zero. NaN values are skipped. Returns a dict with the `trend`, `seasonal`
+/// and `residual` components, which add up to the input, and the seasonal `pattern`, one
+/// value per phase.
+#[pyfunction]
+#[pyo3(signature = (data, period, iterations=2, robust=false))]
+pub fn seasonal_decompose_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    period: usize,
+    iterations: usize,
+    robust: bool
+) -> PyResult<Bound<'py, PyDict>> {
+    crate::errors::require_period(period)?;
+    if iterations == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "iterations must be > 0"
+        ));
+    }
+    let values = data.values()?;
+    if values.len() < 2 * period {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Series must span at least two periods"
+        ));
+    }
+    let parts = py.allow_threads(|| decompose(&values, period, iterations, robust));
+    let residual: Vec<f64> =
+        values.iter().zip(&parts.trend).zip(&parts.seasonal).map(|((v, t), s)| v - t - s).collect();
+    let result = PyDict::new_bound(py);
+    result.set_item("trend", PyArray1::from_vec_bound(py, parts.trend))?;
+    result.set_item("seasonal", PyArray1::from_vec_bound(py, parts.seasonal))?;
+    result.set_item("residual", PyArray1::from_vec_bound(py, residual))?;
+    result.set_item("pattern", PyArray1::from_vec_bound(py, parts.pattern))?;
+    Ok(result)
+}
@@ -0,0 +1,157 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::prelude::*;
+
+use crate::alignment::Alignment;
+use crate::column::Column;
+use crate::config::FastMathConfig;
+use crate::skipna::{self, NanPolicy};
+use crate::{threads, Smoothing};
+
+/// An indicator kernel with its parameters resolved; `min_periods` is set when NaNs are
+/// omitted
+#[derive(Clone, Copy)]
+enum Kernel {
+    MovingAverage { window: usize, min_periods: Option<usize> },
+    RollingStd { window: usize, ddof: usize, min_periods: Option<usize> },
+    Rsi { period: usize, min_periods: Option<usize>, smoothing: Smoothing },
+}
+
+/// A vectorized indicator with its parameters and NaN policy resolved
+#[derive(Clone, Copy)]
+pub struct Indicator {
+    kernel: Kernel,
+    nan_policy: NanPolicy,
+}
+
+impl Indicator {
+    /// Resolve `name` and a dict of keyword parameters, rejecting keys the indicator
+    /// does not take
+    pub fn parse(name: &str, params: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let name = name.to_ascii_lowercase();
+        let allowed: &[&str] = match name.as_str() {
+            "sma" | "moving_average" => &["window", "skipna", "min_periods", "nan_policy"],
+            "std" | "rolling_std" => &["window", "ddof", "min_periods", "nan_policy"],
+            "rsi" => &["period", "skipna", "min_periods", "smoothing", "nan_policy"],
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown indicator '{}', expected 'sma', 'std' or 'rsi'", name
+                )))
+            }
+        };
+        if let Some(params) = params {
+            for key in params.keys() {
+                let key: String = key.extract()?;
+                if !allowed.contains(&key.as_str()) {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "Indicator '{}' has no parameter '{}'", name, key
+                    )));
+                }
+            }
+        }
+        let get = |key: &str| -> PyResult<Option<Bound<'_, PyAny>>> {
+            match params {
+                Some(params) => params.get_item(key),
+                None => Ok(None),
+            }
+        };
+        let required = |key: &str| -> PyResult<usize> {
+            get(key)?.ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Indicator '{}' needs a '{}' parameter", name, key
+                ))
+            })?.extract()
+        };
+        let skipna = get("skipna")?.map(|v| v.extract()).transpose()?.unwrap_or(false);
+        let nan_policy = NanPolicy::resolve(get("nan_policy")?.map(|v| v.extract()).transpose()?, skipna)?;
+        let min_periods = |length: usize| -> PyResult<Option<usize>> {
+            let min_periods = get("min_periods")?.map(|v| v.extract()).transpose()?;
+            (nan_policy == NanPolicy::Omit).then(|| skipna::min_periods(min_periods, length, length)).transpose()
+        };
+
+        let kernel = match name.as_str() {
+            "sma" | "moving_average" => {
+                let window = required("window")?;
+                Kernel::MovingAverage { window, min_periods: min_periods(window)? }
+            }
+            "std" | "rolling_std" => {
+                let window = required("window")?;
+                Kernel::RollingStd {
+                    window,
+                    ddof: get("ddof")?.map(|v| v.extract()).transpose()?.unwrap_or(1),
+                    min_periods: min_periods(window)?,
+                }
+            }
+            _ => {
+                let period = required("period")?;
+                let smoothing = get("smoothing")?.map(|v| v.extract::<String>()).transpose()?;
+                Kernel::Rsi {
+                    period,
+                    min_periods: min_periods(period)?,
+                    smoothing: Smoothing::parse(smoothing.as_deref().unwrap_or("wilder"))?,
+                }
+            }
+        };
+        Ok(Indicator { kernel, nan_policy })
+    }
+
+    pub fn compute(self, values: &[f64]) -> PyResult<Vec<f64>> {
+        self.nan_policy.check(values)?;
+        match self.kernel {
+            Kernel::MovingAverage { window, min_periods: None } => crate::moving_average(values, window),
+            Kernel::MovingAverage { window, min_periods: Some(min_periods) } => {
+                skipna::moving_average(values, window, min_periods)
+            }
+            Kernel::RollingStd { window, ddof, min_periods: None } => crate::rolling_std(values, window, ddof),
+            Kernel::RollingStd { window, ddof, min_periods: Some(min_periods) } => {
+                skipna::rolling_std(values, window, ddof, min_periods)
+            }
+            Kernel::Rsi { period, min_periods: None, smoothing: Smoothing::Wilder } => crate::wilder_rsi(values, period),
+            Kernel::Rsi { period, min_periods: Some(min_periods), smoothing: Smoothing::Wilder } => {
+                skipna::wilder_rsi(values, period, min_periods)
+            }
+            Kernel::Rsi { period, min_periods: None, smoothing: Smoothing::Simple } => crate::rsi(values, period),
+            Kernel::Rsi { period, min_periods: Some(min_periods), smoothing: Smoothing::Simple } => {
+                skipna::rsi(values, period, min_periods)
+            }
+        }
+    }
+}
+
+/// Compute one indicator over many independent series in a single call.
+///
+/// `indicator` is "sma" (`window`, `skipna`, `min_periods`), "std" (`window`, `ddof`,
+/// `min_periods`) or "rsi" (`period`, `skipna`, `min_periods`, `smoothing`), with
+/// parameters given as the `params` dict; each also takes `nan_policy`.
+/// The series may have different lengths; they are spread across the thread pool with
+/// the GIL released, and the results come back as a list in input order, each matching
+/// the output of the single-series function with the same `align`.
+#[pyfunction]
+#[pyo3(signature = (indicator, list_of_arrays, params=None, align=None, config=None))]
+pub fn compute_many_rust<'py>(
+    py: Python<'py>,
+    indicator: &str,
+    list_of_arrays: Vec<Column<'py, f64>>,
+    params: Option<&Bound<'py, PyDict>>,
+    align: Option<Alignment>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Vec<PyObject>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let indicator = Indicator::parse(indicator, params)?;
+    let inputs = list_of_arrays.iter().map(|c| c.values()).collect::<PyResult<Vec<_>>>()?;
+
+    let results = py.allow_threads(|| {
+        threads::install(|| {
+            inputs
+                .par_iter()
+                .map(|values| indicator.compute(values).map(|r| align.pad(r, values.len())))
+                .collect::<PyResult<Vec<_>>>()
+        })
+    })?;
+    list_of_arrays
+        .iter()
+        .zip(results)
+        .map(|(column, result)| column.kind().wrap(py, result))
+        .collect()
+}
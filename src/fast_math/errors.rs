@@ -0,0 +1,103 @@
+use pyo3::create_exception;
+use pyo3::exceptions::{PyUserWarning, PyValueError};
+use pyo3::prelude::*;
+
+create_exception!(
+    fast_math.errors,
+    FastMathError,
+    PyValueError,
+    "Base class of fast_math's input errors; a ValueError, so existing handlers keep working"
+);
+create_exception!(
+    fast_math.errors,
+    LengthMismatchError,
+    FastMathError,
+    "Inputs (or an `out` array) that must line up have different lengths or shapes"
+);
+create_exception!(
+    fast_math.errors,
+    InvalidWindowError,
+    FastMathError,
+    "A window or period that covers no values (0), or too few for the statistic"
+);
+create_exception!(
+    fast_math.errors,
+    NonContiguousInputError,
+    FastMathError,
+    "An array that is written in place is not C-contiguous"
+);
+create_exception!(
+    fast_math.errors,
+    NaNInputError,
+    FastMathError,
+    "The input contains NaN and `nan_policy` is 'raise'"
+);
+create_exception!(
+    fast_math.errors,
+    InvalidTransitionError,
+    FastMathError,
+    "An order event its current state does not allow, such as a fill after a cancel"
+);
+create_exception!(
+    fast_math.errors,
+    PerformanceWarning,
+    PyUserWarning,
+    "An input had to be copied before a kernel could read it, such as a strided numpy view"
+);
+
+/// Reject a window of 0 values
+pub fn require_window(window: usize) -> PyResult<()> {
+    if window == 0 {
+        return Err(InvalidWindowError::new_err(
+            "Window size must be > 0"
+        ));
+    }
+    Ok(())
+}
+
+/// Reject a period of 0 values
+pub fn require_period(period: usize) -> PyResult<()> {
+    if period == 0 {
+        return Err(InvalidWindowError::new_err(
+            "Period must be > 0"
+        ));
+    }
+    Ok(())
+}
+
+/// Reject a `ddof` that leaves a window of `window` values no degrees of freedom
+pub fn check_ddof(ddof: usize, window: usize) -> PyResult<()> {
+    if ddof >= window {
+        return Err(InvalidWindowError::new_err(format!(
+            "ddof must be less than the window size, got ddof={} for window {}", ddof, window
+        )));
+    }
+    Ok(())
+}
+
+/// Check `window` and return how many full windows `len` values hold: none when the
+/// input is shorter than the window, which kernels answer with an empty result
+pub fn check_window(window: usize, len: usize) -> PyResult<usize> {
+    require_window(window)?;
+    Ok((len + 1).saturating_sub(window))
+}
+
+/// Check `period` and return how many windows of `period` changes, which take
+/// `period + 1` values, `len` values hold
+pub fn check_period(period: usize, len: usize) -> PyResult<usize> {
+    require_period(period)?;
+    Ok(len.saturating_sub(period))
+}
+
+/// Populate the `fast_math.errors` submodule
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("FastMathError", py.get_type_bound::<FastMathError>())?;
+    m.add("LengthMismatchError", py.get_type_bound::<LengthMismatchError>())?;
+    m.add("InvalidWindowError", py.get_type_bound::<InvalidWindowError>())?;
+    m.add("NonContiguousInputError", py.get_type_bound::<NonContiguousInputError>())?;
+    m.add("NaNInputError", py.get_type_bound::<NaNInputError>())?;
+    m.add("InvalidTransitionError", py.get_type_bound::<InvalidTransitionError>())?;
+    m.add("PerformanceWarning", py.get_type_bound::<PerformanceWarning>())?;
+    Ok(())
+}
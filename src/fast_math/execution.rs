@@ -0,0 +1,415 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Split `qty >= 0` over slices in proportion to `weights`, capping each slice at `caps`:
+/// quantity a capped slice cannot take goes to the uncapped ones, still in proportion to
+/// their weights, until every slice fits or all are full
+fn allocate(qty: f64, weights: &[f64], caps: Option<&[f64]>) -> Vec<f64> {
+    let mut sizes = vec![0.0; weights.len()];
+    let Some(caps) = caps else {
+        let total: f64 = weights.iter().sum();
+        return weights.iter().map(|w| qty * w / total).collect();
+    };
+    let mut free: Vec<bool> = caps.iter().map(|&c| c > 0.0).collect();
+    let mut remaining = qty;
+    while remaining > 0.0 {
+        let total: f64 = weights.iter().zip(&free).filter(|&(_, &f)| f).map(|(w, _)| w).sum();
+        if total <= 0.0 {
+            break;
+        }
+        let mut overflow = false;
+        for i in 0..sizes.len() {
+            if free[i] && sizes[i] + remaining * weights[i] / total >= caps[i] {
+                sizes[i] = caps[i];
+                free[i] = false;
+                overflow = true;
+            }
+        }
+        if !overflow {
+            for i in 0..sizes.len() {
+                if free[i] {
+                    sizes[i] += remaining * weights[i] / total;
+                }
+            }
+            break;
+        }
+        remaining = qty - sizes.iter().sum::<f64>();
+    }
+    sizes
+}
+
+/// Round `sizes` to multiples of `lot`, keeping their total (rounded to a lot) by giving
+/// the leftover lots to the slices with the largest remainders that stay under their cap
+fn round_lots(sizes: &mut [f64], lot: f64, caps: Option<&[f64]>) {
+    let target = (sizes.iter().sum::<f64>() / lot).round();
+    let mut remainders: Vec<(usize, f64)> = Vec::with_capacity(sizes.len());
+    let mut lots = 0.0;
+    for (i, size) in sizes.iter_mut().enumerate() {
+        let whole = (*size / lot + 1e-9).floor();
+        remainders.push((i, *size / lot - whole));
+        *size = whole * lot;
+        lots += whole;
+    }
+    remainders.sort_by(|a, b| b.1.total_cmp(&a.1));
+    for (i, _) in remainders {
+        if lots >= target {
+            break;
+        }
+        if caps.is_none_or(|caps| sizes[i] + lot <= caps[i] * (1.0 + 1e-12)) {
+            sizes[i] += lot;
+            lots += 1.0;
+        }
+    }
+}
+
+/// Validated `max_participation` caps: that share of each slice's expected volume
+fn participation_caps(max_participation: Option<f64>, volumes: Option<&[f64]>) -> PyResult<Option<Vec<f64>>> {
+    match (max_participation, volumes) {
+        (None, _) => Ok(None),
+        (Some(rate), _) if rate.is_nan() || rate <= 0.0 || rate > 1.0 => Err(pyo3::exceptions::PyValueError::new_err(
+            "max_participation must be in (0, 1]"
+        )),
+        (Some(_), None) => Err(pyo3::exceptions::PyValueError::new_err(
+            "max_participation needs the expected volume of each slice"
+        )),
+        (Some(rate), Some(volumes)) => Ok(Some(volumes.iter().map(|v| rate * v.max(0.0)).collect())),
+    }
+}
+
+/// Sizes of a schedule of `qty` (either sign) over `weights`, and what the caps left over
+fn schedule(qty: f64, weights: &[f64], caps: Option<&[f64]>, lot_size: Option<f64>) -> (Vec<f64>, f64) {
+    let mut sizes = allocate(qty.abs(), weights, caps);
+    if let Some(lot) = lot_size {
+        round_lots(&mut sizes, lot, caps);
+    }
+    let unfilled = qty.abs() - sizes.iter().sum::<f64>();
+    let sign = if qty < 0.0 { -1.0 } else { 1.0 };
+    (sizes.into_iter().map(|s| sign * s).collect(), sign * unfilled)
+}
+
+fn check_order(qty: f64, lot_size: Option<f64>) -> PyResult<()> {
+    if !qty.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "qty must be finite"
+        ));
+    }
+    if lot_size.is_some_and(|lot| lot.is_nan() || lot <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "lot_size must be > 0"
+        ));
+    }
+    Ok(())
+}
+
+/// TWAP execution schedule: `qty` split evenly in time into child orders.
+///
+/// Slices start at `start`, `start + interval`, ... before `end` (integer timestamps in
+/// any unit), each sized in proportion to its length, so only a final partial slice gets
+/// less. With `max_participation` and the `expected_volume` of each slice, no slice takes
+/// more than that share of its volume; what a capped slice cannot take moves to the
+/// others. `lot_size` rounds every slice to whole lots, keeping the total. `qty` may be
+/// negative for a sell. Returns a dict with the slices' `timestamp` and `quantity`, and
+/// the `unfilled` quantity the caps left unscheduled (0 without caps).
+#[pyfunction]
+#[pyo3(signature = (qty, start, end, interval, max_participation=None, expected_volume=None, lot_size=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn twap_schedule_rust<'py>(
+    py: Python<'py>,
+    qty: f64,
+    start: i64,
+    end: i64,
+    interval: i64,
+    max_participation: Option<f64>,
+    expected_volume: Option<Column<'py, f64>>,
+    lot_size: Option<f64>
+) -> PyResult<Bound<'py, PyDict>> {
+    check_order(qty, lot_size)?;
+    if interval <= 0 || end <= start {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "interval must be > 0 and end after start"
+        ));
+    }
+    let timestamps: Vec<i64> = (start..end).step_by(interval as usize).collect();
+    let weights: Vec<f64> = timestamps.iter().map(|&t| ((t + interval).min(end) - t) as f64).collect();
+    let volumes = expected_volume.as_ref().map(|v| v.values()).transpose()?;
+    if volumes.as_ref().is_some_and(|v| v.len() != timestamps.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "expected_volume must have one value per slice ({})", timestamps.len()
+        )));
+    }
+    let caps = participation_caps(max_participation, volumes.as_deref())?;
+    let (quantity, unfilled) = schedule(qty, &weights, caps.as_deref(), lot_size);
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_vec_bound(py, timestamps))?;
+    result.set_item("quantity", PyArray1::from_vec_bound(py, quantity))?;
+    result.set_item("unfilled", unfilled)?;
+    Ok(result)
+}
+
+/// VWAP execution schedule: `qty` split over buckets in proportion to their expected
+/// volume.
+///
+/// `volume_profile` holds the expected volume of each bucket (e.g. the average volume per
+/// time-of-day bucket over recent sessions); only its shape matters, unless
+/// `max_participation` caps each bucket at that share of its volume, in which case it must
+/// be in the units of `qty`. Quantity a capped bucket cannot take moves to the others in
+/// proportion to their volume. `lot_size` rounds every bucket to whole lots, keeping the
+/// total, and `qty` may be negative for a sell. Returns a dict with each bucket's
+/// `quantity` and the `unfilled` quantity the caps left unscheduled. NaN or negative
+/// volumes count as 0.
+#[pyfunction]
+#[pyo3(signature = (qty, volume_profile, max_participation=None, lot_size=None))]
+pub fn vwap_schedule_rust<'py>(
+    py: Python<'py>,
+    qty: f64,
+    volume_profile: Column<'py, f64>,
+    max_participation: Option<f64>,
+    lot_size: Option<f64>
+) -> PyResult<Bound<'py, PyDict>> {
+    check_order(qty, lot_size)?;
+    let profile: Vec<f64> = volume_profile
+        .values()?
+        .iter()
+        .map(|v| if v.is_nan() { 0.0 } else { v.max(0.0) })
+        .collect();
+    if profile.iter().sum::<f64>() <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "volume_profile must have some positive volume"
+        ));
+    }
+    let caps = participation_caps(max_participation, Some(&profile))?;
+    let (quantity, unfilled) = schedule(qty, &profile, caps.as_deref(), lot_size);
+    let result = PyDict::new_bound(py);
+    result.set_item("quantity", PyArray1::from_vec_bound(py, quantity))?;
+    result.set_item("unfilled", unfilled)?;
+    Ok(result)
+}
+
+/// Optimal trajectory of the discrete Almgren-Chriss model: holdings at each of the
+/// `n_steps + 1` times, from `qty` down to 0
+fn almgren_chriss(qty: f64, horizon: f64, vol: f64, eta: f64, risk_aversion: f64, n_steps: usize) -> Vec<f64> {
+    let tau = horizon / n_steps as f64;
+    let kappa_sq = risk_aversion * vol * vol / eta;
+    let kappa = (1.0 + 0.5 * kappa_sq * tau * tau).acosh() / tau;
+    (0..=n_steps)
+        .map(|j| {
+            let t = j as f64 * tau;
+            if kappa * horizon < 1e-8 {
+                // Risk-neutral limit: a straight line
+                qty * (1.0 - t / horizon)
+            } else {
+                // sinh(kappa (T - t)) / sinh(kappa T), without overflowing for large kappa T
+                let left = -(-2.0 * kappa * (horizon - t)).exp_m1();
+                qty * (-kappa * t).exp() * left / -(-2.0 * kappa * horizon).exp_m1()
+            }
+        })
+        .collect()
+}
+
+/// Almgren-Chriss (2000) optimal execution of `qty` over `horizon`, trading off market
+/// impact against price risk.
+///
+/// The horizon is split into `n_steps` equal intervals. The price moves with volatility
+/// `vol` (in price units per square root of the horizon's time unit) and a permanent
+/// impact of `perm_impact` per unit traded, and each trade also pays a temporary impact of
+/// `temp_impact` times its rate. The trajectory minimises `E[cost] + risk_aversion *
+/// Var[cost]`: 0 risk aversion gives the straight-line (TWAP) schedule, higher values
+/// front-load trading to cut risk. Returns a dict with the interval boundaries `time`,
+/// the `holdings` left at each (from `qty` to 0), the `trades` in each interval, and the
+/// `expected_cost` (the implementation shortfall from impact) and its `variance`. A sell
+/// program uses a positive `qty`; a negative one buys, with the same costs. Raises when
+/// the temporary impact, net of the permanent impact's discretisation term, is not
+/// positive.
+#[pyfunction]
+#[pyo3(signature = (qty, horizon, vol, temp_impact, perm_impact, risk_aversion, n_steps=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn almgren_chriss_rust<'py>(
+    py: Python<'py>,
+    qty: f64,
+    horizon: f64,
+    vol: f64,
+    temp_impact: f64,
+    perm_impact: f64,
+    risk_aversion: f64,
+    n_steps: usize
+) -> PyResult<Bound<'py, PyDict>> {
+    if !qty.is_finite() || !horizon.is_finite() || horizon <= 0.0 || n_steps == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "qty must be finite, horizon > 0 and n_steps > 0"
+        ));
+    }
+    if [vol, perm_impact, risk_aversion].iter().any(|v| !v.is_finite() || *v < 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "vol, perm_impact and risk_aversion must be >= 0"
+        ));
+    }
+    let tau = horizon / n_steps as f64;
+    let eta = temp_impact - 0.5 * perm_impact * tau;
+    if eta.is_nan() || eta <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "temp_impact must exceed perm_impact * horizon / (2 * n_steps)"
+        ));
+    }
+
+    let holdings = almgren_chriss(qty, horizon, vol, eta, risk_aversion, n_steps);
+    let trades: Vec<f64> = holdings.windows(2).map(|w| w[0] - w[1]).collect();
+    let expected_cost = 0.5 * perm_impact * qty * qty + eta * trades.iter().map(|n| n * n).sum::<f64>() / tau;
+    let variance = vol * vol * tau * holdings[1..].iter().map(|x| x * x).sum::<f64>();
+    let time: Vec<f64> = (0..=n_steps).map(|j| j as f64 * tau).collect();
+    let result = PyDict::new_bound(py);
+    result.set_item("time", PyArray1::from_vec_bound(py, time))?;
+    result.set_item("holdings", PyArray1::from_vec_bound(py, holdings))?;
+    result.set_item("trades", PyArray1::from_vec_bound(py, trades))?;
+    result.set_item("expected_cost", expected_cost)?;
+    result.set_item("variance", variance)?;
+    Ok(result)
+}
+
+/// Market impact model of `estimate_tcost_rust`
+#[derive(Clone, Copy)]
+enum ImpactModel {
+    /// Impact grows with the square root of participation
+    SquareRoot,
+    /// Impact grows in proportion to participation
+    Linear,
+}
+
+impl ImpactModel {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sqrt" | "square_root" => Ok(ImpactModel::SquareRoot),
+            "linear" => Ok(ImpactModel::Linear),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown model '{}', expected 'sqrt' or 'linear'", name
+            ))),
+        }
+    }
+
+    /// Impact, as a fraction of price, of trading `participation` of daily volume
+    fn impact(self, participation: f64, vol: f64, coef: f64) -> f64 {
+        match self {
+            ImpactModel::SquareRoot => coef * vol * participation.sqrt(),
+            ImpactModel::Linear => coef * vol * participation,
+        }
+    }
+}
+
+/// `value` as one entry per order: a number repeated, or an array of length `n`
+fn per_order(name: &str, value: &Bound<'_, PyAny>, n: usize) -> PyResult<Vec<f64>> {
+    let values = match value.extract::<f64>() {
+        Ok(v) => vec![v; n],
+        Err(_) => value.extract::<Column<'_, f64>>()?.values()?.to_vec(),
+    };
+    if values.len() != n {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "{} must be a number or have one entry per order", name
+        )));
+    }
+    if values.iter().any(|&v| v < 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} must be >= 0", name
+        )));
+    }
+    Ok(values)
+}
+
+/// Pre-trade transaction cost estimates for candidate orders.
+///
+/// Each order of `order_size` units (either sign) crosses half the `spread` and moves the
+/// price by its market impact, set by its participation `|order_size| / adv` in the
+/// average daily volume and the daily volatility `vol`: `impact_coef * vol * sqrt(p)` for
+/// the square-root model (`model="sqrt"`, the default) or `impact_coef * vol * p` for
+/// the linear one. `adv`, `spread` (relative to price, e.g. 0.0005 for 5 bps) and `vol`
+/// may be numbers or one value per order. Returns a dict of per-order arrays of the
+/// `participation`, `spread_cost`, `impact_cost` and `total_cost`, all fractions of the
+/// traded notional, so multiplying by the notional gives the cost in currency. Orders
+/// with zero `adv` get NaN costs.
+#[pyfunction]
+#[pyo3(signature = (order_size, adv, spread, vol, model="sqrt", impact_coef=1.0))]
+pub fn estimate_tcost_rust<'py>(
+    py: Python<'py>,
+    order_size: Column<'py, f64>,
+    adv: Bound<'py, PyAny>,
+    spread: Bound<'py, PyAny>,
+    vol: Bound<'py, PyAny>,
+    model: &str,
+    impact_coef: f64
+) -> PyResult<Bound<'py, PyDict>> {
+    let model = ImpactModel::parse(model)?;
+    if impact_coef.is_nan() || impact_coef < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "impact_coef must be >= 0"
+        ));
+    }
+    let orders = order_size.values()?;
+    let n = orders.len();
+    let (adv, spread, vol) = (per_order("adv", &adv, n)?, per_order("spread", &spread, n)?, per_order("vol", &vol, n)?);
+    let participation: Vec<f64> =
+        orders.iter().zip(&adv).map(|(q, &a)| if a > 0.0 { q.abs() / a } else { f64::NAN }).collect();
+    let spread_cost: Vec<f64> = (0..n).map(|i| if participation[i].is_nan() { f64::NAN } else { 0.5 * spread[i] }).collect();
+    let impact_cost: Vec<f64> = (0..n).map(|i| model.impact(participation[i], vol[i], impact_coef)).collect();
+    let total_cost = spread_cost.iter().zip(&impact_cost).map(|(s, i)| s + i);
+
+    let result = PyDict::new_bound(py);
+    result.set_item("participation", PyArray1::from_vec_bound(py, participation))?;
+    result.set_item("spread_cost", PyArray1::from_slice_bound(py, &spread_cost))?;
+    result.set_item("impact_cost", PyArray1::from_slice_bound(py, &impact_cost))?;
+    result.set_item("total_cost", PyArray1::from_iter_bound(py, total_cost))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lots_keep_the_total_and_break_ties_by_position() {
+        let (sizes, unfilled) = schedule(10.0, &[1.0, 1.0, 1.0], None, Some(1.0));
+        assert_eq!((sizes, unfilled), (vec![4.0, 3.0, 3.0], 0.0));
+        // The largest remainders take the leftover lots
+        let (sizes, _) = schedule(1000.0, &[1.0, 2.0, 4.0], None, Some(100.0));
+        assert_eq!(sizes, vec![100.0, 300.0, 600.0]);
+        // 7 rounds to 4 lots of 2; the small slices have the larger remainders
+        let (sizes, _) = schedule(7.0, &[2.0, 2.0, 1.0, 1.0], None, Some(2.0));
+        assert_eq!(sizes, vec![2.0, 2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn caps_move_quantity_to_the_other_slices() {
+        let caps = [2.0, 10.0, 10.0];
+        let (sizes, unfilled) = schedule(10.0, &[1.0, 1.0, 1.0], Some(&caps), Some(1.0));
+        assert_eq!((sizes, unfilled), (vec![2.0, 4.0, 4.0], 0.0));
+        let (sizes, unfilled) = schedule(30.0, &[1.0, 1.0, 1.0], Some(&caps), None);
+        assert_eq!((sizes, unfilled), (vec![2.0, 10.0, 10.0], 8.0));
+    }
+
+    #[test]
+    fn zero_and_negative_quantities() {
+        let (sizes, unfilled) = schedule(0.0, &[1.0, 3.0], Some(&[5.0, 5.0]), Some(1.0));
+        assert_eq!((sizes, unfilled), (vec![0.0, 0.0], 0.0));
+        let (sizes, unfilled) = schedule(-10.0, &[1.0, 1.0, 1.0], None, Some(1.0));
+        assert_eq!((sizes, unfilled), (vec![-4.0, -3.0, -3.0], 0.0));
+        let (sizes, unfilled) = schedule(-30.0, &[1.0, 1.0, 1.0], Some(&[2.0, 10.0, 10.0]), None);
+        assert_eq!((sizes, unfilled), (vec![-2.0, -10.0, -10.0], -8.0));
+    }
+
+    #[test]
+    fn almgren_chriss_runs_from_qty_to_zero() {
+        let linear: Vec<f64> = (0..=10).map(|j| 1000.0 * (1.0 - j as f64 / 10.0)).collect();
+        for risk_aversion in [0.0, 1e-6, 0.1, 10.0] {
+            let holdings = almgren_chriss(1000.0, 1.0, 0.3, 0.01, risk_aversion, 10);
+            assert_eq!((holdings[0], holdings[10]), (1000.0, 0.0));
+            assert!(holdings.windows(2).all(|w| w[1] <= w[0]));
+            // More risk aversion trades faster than the straight line
+            let gap = holdings.iter().zip(&linear).map(|(h, l)| l - h).fold(0.0, f64::max);
+            if risk_aversion <= 1e-6 {
+                assert!(gap < 1e-3, "{}: {}", risk_aversion, gap);
+            } else {
+                assert!(gap > 1.0, "{}: {}", risk_aversion, gap);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Net and gross market value and beta-adjusted exposure of a group of positions
+#[derive(Default, Clone, Copy)]
+struct Exposure {
+    net: f64,
+    gross: f64,
+    beta_net: f64,
+    beta_gross: f64,
+}
+
+impl Exposure {
+    fn add(&mut self, market_value: f64, beta_exposure: f64) {
+        self.net += market_value;
+        self.gross += market_value.abs();
+        self.beta_net += beta_exposure;
+        self.beta_gross += beta_exposure.abs();
+    }
+
+    fn to_dict<'py>(self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new_bound(py);
+        result.set_item("net", self.net)?;
+        result.set_item("gross", self.gross)?;
+        result.set_item("beta_net", self.beta_net)?;
+        result.set_item("beta_gross", self.beta_gross)?;
+        Ok(result)
+    }
+}
+
+/// Beta-adjusted exposure of a portfolio snapshot, overall and per sector.
+///
+/// `positions` (signed quantities), `betas` and `prices` are parallel columns, one entry
+/// per holding, and `sectors`, when given, a same-length list (or Series) of sector
+/// names. A holding's market value is `position * price` and its beta exposure that times
+/// `beta`. Returns a dict with the per-holding `market_value` and `beta_exposure`, the
+/// portfolio's `net` and `gross` market value and `beta_net` and `beta_gross` exposure,
+/// and with sectors `by_sector`, a dict mapping each sector to the same four totals. NaN
+/// in a holding carries into every total it belongs to, so a missing beta or price shows
+/// rather than silently understating the risk.
+#[pyfunction]
+#[pyo3(signature = (positions, betas, prices, sectors=None))]
+pub fn beta_weighted_exposure_rust<'py>(
+    py: Python<'py>,
+    positions: Column<'py, f64>,
+    betas: Column<'py, f64>,
+    prices: Column<'py, f64>,
+    sectors: Option<Bound<'py, PyAny>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let (positions, betas, prices) = (positions.values()?, betas.values()?, prices.values()?);
+    // Lists and object arrays extract directly; Series and the like go through tolist()
+    let sectors = sectors
+        .map(|values| match values.extract::<Vec<String>>() {
+            Ok(values) => Ok(values),
+            Err(_) if values.hasattr("tolist")? => values.call_method0("tolist")?.extract(),
+            Err(err) => Err(err),
+        })
+        .transpose()?;
+    let n = positions.len();
+    if betas.len() != n || prices.len() != n || sectors.as_ref().is_some_and(|s| s.len() != n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "positions, betas, prices and sectors must have the same length"
+        ));
+    }
+
+    let market_values: Vec<f64> = positions.iter().zip(prices.iter()).map(|(q, p)| q * p).collect();
+    let beta_exposures: Vec<f64> = market_values.iter().zip(betas.iter()).map(|(v, b)| v * b).collect();
+    let mut total = Exposure::default();
+    let mut by_sector: BTreeMap<&str, Exposure> = BTreeMap::new();
+    for i in 0..n {
+        total.add(market_values[i], beta_exposures[i]);
+        if let Some(sectors) = &sectors {
+            by_sector.entry(sectors[i].as_str()).or_default().add(market_values[i], beta_exposures[i]);
+        }
+    }
+
+    let result = total.to_dict(py)?;
+    result.set_item("market_value", PyArray1::from_vec_bound(py, market_values))?;
+    result.set_item("beta_exposure", PyArray1::from_vec_bound(py, beta_exposures))?;
+    if sectors.is_some() {
+        let sectors = PyDict::new_bound(py);
+        for (sector, exposure) in by_sector {
+            sectors.set_item(sector, exposure.to_dict(py)?)?;
+        }
+        result.set_item("by_sector", sectors)?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,757 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+use pyo3::prelude::*;
+use numpy::PyArray1;
+
+use crate::column::{ArrayKind, Column};
+use crate::pool::Buffer;
+use crate::streaming::StreamingEMA;
+use crate::DRIFT_INTERVAL;
+
+/// Rows evaluated per block; every intermediate is one block long, so it stays in cache
+const BLOCK: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Unary {
+    Neg,
+    Abs,
+    Log,
+    Exp,
+    Sqrt,
+    Not,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Binary {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    And,
+    Or,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Rolling {
+    Mean,
+    Sum,
+    Std(usize),
+    Min,
+    Max,
+}
+
+/// Comparisons and logical operators produce 1.0 / 0.0; NaN operands are false
+fn truth(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+impl Unary {
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Unary::Neg => -x,
+            Unary::Abs => x.abs(),
+            Unary::Log => x.ln(),
+            Unary::Exp => x.exp(),
+            Unary::Sqrt => x.sqrt(),
+            Unary::Not => truth(x != 1.0),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Unary::Neg => "neg",
+            Unary::Abs => "abs",
+            Unary::Log => "log",
+            Unary::Exp => "exp",
+            Unary::Sqrt => "sqrt",
+            Unary::Not => "not_",
+        }
+    }
+}
+
+impl Binary {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            Binary::Add => a + b,
+            Binary::Sub => a - b,
+            Binary::Mul => a * b,
+            Binary::Div => a / b,
+            Binary::Gt => truth(a > b),
+            Binary::Ge => truth(a >= b),
+            Binary::Lt => truth(a < b),
+            Binary::Le => truth(a <= b),
+            Binary::And => truth(a == 1.0 && b == 1.0),
+            Binary::Or => truth(a == 1.0 || b == 1.0),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Binary::Add => "add",
+            Binary::Sub => "sub",
+            Binary::Mul => "mul",
+            Binary::Div => "div",
+            Binary::Gt => "gt",
+            Binary::Ge => "ge",
+            Binary::Lt => "lt",
+            Binary::Le => "le",
+            Binary::And => "and_",
+            Binary::Or => "or_",
+        }
+    }
+}
+
+enum Node {
+    Column(String),
+    Literal(f64),
+    Unary(Unary, Arc<Node>),
+    Binary(Binary, Arc<Node>, Arc<Node>),
+    Rolling(Rolling, usize, Arc<Node>),
+    Shift(usize, Arc<Node>),
+    Ema(usize, Arc<Node>),
+}
+
+impl Node {
+    /// Whether the node evaluates to a mask rather than a float series
+    fn is_bool(&self) -> bool {
+        match self {
+            Node::Unary(Unary::Not, _) => true,
+            Node::Binary(op, _, _) => !matches!(op, Binary::Add | Binary::Sub | Binary::Mul | Binary::Div),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Column(name) => write!(f, "col({:?})", name),
+            Node::Literal(value) => write!(f, "lit({:?})", value),
+            Node::Unary(op, a) => write!(f, "{}.{}()", a, op.name()),
+            Node::Binary(op, a, b) => write!(f, "{}.{}({})", a, op.name(), b),
+            Node::Rolling(Rolling::Mean, window, a) => write!(f, "{}.rolling_mean({})", a, window),
+            Node::Rolling(Rolling::Sum, window, a) => write!(f, "{}.rolling_sum({})", a, window),
+            Node::Rolling(Rolling::Std(ddof), window, a) => write!(f, "{}.rolling_std({}, ddof={})", a, window, ddof),
+            Node::Rolling(Rolling::Min, window, a) => write!(f, "{}.rolling_min({})", a, window),
+            Node::Rolling(Rolling::Max, window, a) => write!(f, "{}.rolling_max({})", a, window),
+            Node::Shift(n, a) => write!(f, "{}.shift({})", a, n),
+            Node::Ema(period, a) => write!(f, "{}.ema({})", a, period),
+        }
+    }
+}
+
+/// Sliding window state for the rolling operators. Windows holding NaN are NaN; sums are
+/// kept relative to a recent value and recomputed periodically to bound drift.
+struct Window {
+    values: VecDeque<f64>,
+    capacity: usize,
+    pushed: usize,
+    nans: usize,
+    shift: f64,
+    sum: f64,
+    sum_sq: f64,
+    /// Monotonic (position, value) queues for the running min and max
+    mins: VecDeque<(usize, f64)>,
+    maxs: VecDeque<(usize, f64)>,
+}
+
+impl Window {
+    fn new(capacity: usize) -> Self {
+        Window {
+            values: VecDeque::with_capacity(capacity + 1),
+            capacity,
+            pushed: 0,
+            nans: 0,
+            shift: 0.0,
+            sum: 0.0,
+            sum_sq: 0.0,
+            mins: VecDeque::new(),
+            maxs: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if self.values.len() == self.capacity {
+            match self.values.pop_front() {
+                Some(old) if old.is_nan() => self.nans -= 1,
+                Some(old) => {
+                    self.sum -= old - self.shift;
+                    self.sum_sq -= (old - self.shift) * (old - self.shift);
+                }
+                None => {}
+            }
+        }
+        if value.is_nan() {
+            self.nans += 1;
+        } else {
+            if self.values.len() == self.nans {
+                // No valid values left, so re-centre on this one
+                (self.shift, self.sum, self.sum_sq) = (value, 0.0, 0.0);
+            }
+            self.sum += value - self.shift;
+            self.sum_sq += (value - self.shift) * (value - self.shift);
+            while self.mins.back().is_some_and(|&(_, v)| v >= value) {
+                self.mins.pop_back();
+            }
+            self.mins.push_back((self.pushed, value));
+            while self.maxs.back().is_some_and(|&(_, v)| v <= value) {
+                self.maxs.pop_back();
+            }
+            self.maxs.push_back((self.pushed, value));
+        }
+        self.values.push_back(value);
+
+        let oldest = (self.pushed + 1).saturating_sub(self.capacity);
+        while self.mins.front().is_some_and(|&(p, _)| p < oldest) {
+            self.mins.pop_front();
+        }
+        while self.maxs.front().is_some_and(|&(p, _)| p < oldest) {
+            self.maxs.pop_front();
+        }
+        self.pushed += 1;
+
+        if self.pushed.is_multiple_of(DRIFT_INTERVAL) {
+            if let Some(&first) = self.values.iter().find(|v| !v.is_nan()) {
+                self.shift = first;
+            }
+            let valid = self.values.iter().filter(|v| !v.is_nan());
+            self.sum = valid.clone().map(|v| v - self.shift).sum();
+            self.sum_sq = valid.map(|v| (v - self.shift) * (v - self.shift)).sum();
+        }
+    }
+
+    fn value(&self, op: Rolling) -> f64 {
+        if self.values.len() < self.capacity || self.nans > 0 {
+            return f64::NAN;
+        }
+        let n = self.capacity as f64;
+        match op {
+            Rolling::Sum => self.sum + n * self.shift,
+            Rolling::Mean => self.shift + self.sum / n,
+            Rolling::Std(ddof) if ddof < self.capacity => {
+                ((self.sum_sq - self.sum * self.sum / n) / (n - ddof as f64)).max(0.0).sqrt()
+            }
+            Rolling::Std(_) => f64::NAN,
+            Rolling::Min => self.mins.front().map_or(f64::NAN, |&(_, v)| v),
+            Rolling::Max => self.maxs.front().map_or(f64::NAN, |&(_, v)| v),
+        }
+    }
+}
+
+/// One step of a compiled expression; operands are indexes of earlier steps' registers
+enum Op {
+    Load(usize),
+    Const(f64),
+    Unary(Unary, usize),
+    Binary(Binary, usize, usize),
+    Rolling(Rolling, usize, Window),
+    Shift(usize, usize, VecDeque<f64>),
+    Ema(usize, StreamingEMA),
+}
+
+/// A step identified by its operation and operand registers, so equal subexpressions
+/// built separately map to the same register
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    Column(String),
+    Literal(u64),
+    Unary(Unary, usize),
+    Binary(Binary, usize, usize),
+    Rolling(Rolling, usize, usize),
+    Shift(usize, usize),
+    Ema(usize, usize),
+}
+
+/// An expression graph flattened into evaluation order, with shared subexpressions
+/// (including repeated column reads) computed once
+struct Program {
+    columns: Vec<String>,
+    ops: Vec<Op>,
+    seen: HashMap<Key, usize>,
+    /// Registers of the nodes already compiled, by address, so a node reused across the
+    /// graph is visited once however often it is referenced
+    visited: HashMap<usize, usize>,
+}
+
+impl Program {
+    fn compile(root: &Node) -> PyResult<Self> {
+        let mut program =
+            Program { columns: Vec::new(), ops: Vec::new(), seen: HashMap::new(), visited: HashMap::new() };
+        program.add(root)?;
+        Ok(program)
+    }
+
+    /// Append `node` after its operands and return its register
+    fn add(&mut self, node: &Node) -> PyResult<usize> {
+        let address = node as *const Node as usize;
+        if let Some(&register) = self.visited.get(&address) {
+            return Ok(register);
+        }
+        let key = match node {
+            Node::Column(name) => Key::Column(name.clone()),
+            Node::Literal(value) => Key::Literal(value.to_bits()),
+            Node::Unary(op, a) => Key::Unary(*op, self.add(a)?),
+            Node::Binary(op, a, b) => {
+                let a = self.add(a)?;
+                Key::Binary(*op, a, self.add(b)?)
+            }
+            Node::Rolling(op, window, a) => Key::Rolling(*op, *window, self.add(a)?),
+            Node::Shift(n, a) => Key::Shift(*n, self.add(a)?),
+            Node::Ema(period, a) => Key::Ema(*period, self.add(a)?),
+        };
+        let register = match self.seen.get(&key) {
+            Some(&register) => register,
+            None => {
+                let op = match &key {
+                    Key::Column(name) => {
+                        self.columns.push(name.clone());
+                        Op::Load(self.columns.len() - 1)
+                    }
+                    Key::Literal(bits) => Op::Const(f64::from_bits(*bits)),
+                    Key::Unary(op, a) => Op::Unary(*op, *a),
+                    Key::Binary(op, a, b) => Op::Binary(*op, *a, *b),
+                    Key::Rolling(op, window, a) => Op::Rolling(*op, *a, Window::new(*window)),
+                    Key::Shift(n, a) => Op::Shift(*n, *a, VecDeque::with_capacity(n + 1)),
+                    Key::Ema(period, a) => Op::Ema(*a, StreamingEMA::new(*period)?),
+                };
+                self.ops.push(op);
+                self.seen.insert(key, self.ops.len() - 1);
+                self.ops.len() - 1
+            }
+        };
+        self.visited.insert(address, register);
+        Ok(register)
+    }
+
+    /// Evaluate block by block, every step of a block before moving to the next
+    fn run(mut self, columns: &[&[f64]], len: usize) -> Vec<f64> {
+        let mut registers: Vec<Buffer<f64>> = (0..self.ops.len()).map(|_| Buffer::zeroed(BLOCK)).collect();
+        let root = self.ops.len() - 1;
+        let mut result = Vec::with_capacity(len);
+        for start in (0..len).step_by(BLOCK) {
+            let rows = BLOCK.min(len - start);
+            for (i, op) in self.ops.iter_mut().enumerate() {
+                let (done, rest) = registers.split_at_mut(i);
+                let dst = &mut rest[0][..rows];
+                match op {
+                    Op::Load(c) => dst.copy_from_slice(&columns[*c][start..start + rows]),
+                    Op::Const(value) => dst.fill(*value),
+                    Op::Unary(op, a) => {
+                        for (d, &x) in dst.iter_mut().zip(&done[*a][..rows]) {
+                            *d = op.apply(x);
+                        }
+                    }
+                    Op::Binary(op, a, b) => {
+                        for ((d, &x), &y) in dst.iter_mut().zip(&done[*a][..rows]).zip(&done[*b][..rows]) {
+                            *d = op.apply(x, y);
+                        }
+                    }
+                    Op::Rolling(op, a, window) => {
+                        for (d, &x) in dst.iter_mut().zip(&done[*a][..rows]) {
+                            window.push(x);
+                            *d = window.value(*op);
+                        }
+                    }
+                    Op::Shift(n, a, history) => {
+                        for (d, &x) in dst.iter_mut().zip(&done[*a][..rows]) {
+                            history.push_back(x);
+                            *d = if history.len() > *n { history.pop_front().unwrap() } else { f64::NAN };
+                        }
+                    }
+                    Op::Ema(a, ema) => {
+                        for (d, &x) in dst.iter_mut().zip(&done[*a][..rows]) {
+                            *d = if x.is_nan() { f64::NAN } else { ema.update(x) };
+                        }
+                    }
+                }
+            }
+            result.extend_from_slice(&registers[root][..rows]);
+        }
+        result
+    }
+}
+
+/// A lazy column expression.
+///
+/// Built from `col(name)` and `lit(value)` with arithmetic (`add`, `sub`, `mul`, `div`
+/// and the usual operators), comparisons (`gt`, `ge`, `lt`, `le`), logic (`and_`, `or_`,
+/// `not_` or `&`, `|`, `~`), `shift` / `diff` / `pct_change`, rolling `mean`, `sum`,
+/// `std`, `min` and `max`, and `ema`. Nothing is computed until `evaluate(data)`, which
+/// runs the whole graph in one pass over the rows in cache-sized blocks, without
+/// materialising intermediate columns. Rolling outputs are NaN until their window is
+/// full (or contains NaN); comparisons with NaN are false.
+#[pyclass(module = "fast_math", name = "Expr")]
+#[derive(Clone)]
+pub struct Expr {
+    node: Arc<Node>,
+}
+
+/// An expression or a constant, as accepted by the binary operators
+#[derive(FromPyObject)]
+enum Operand {
+    Expr(Expr),
+    Value(f64),
+}
+
+impl Operand {
+    fn node(self) -> Arc<Node> {
+        match self {
+            Operand::Expr(expr) => expr.node,
+            Operand::Value(value) => Arc::new(Node::Literal(value)),
+        }
+    }
+}
+
+impl Expr {
+    fn new(node: Node) -> Self {
+        Expr { node: Arc::new(node) }
+    }
+
+    fn unary(&self, op: Unary) -> Self {
+        Expr::new(Node::Unary(op, self.node.clone()))
+    }
+
+    fn binary(&self, op: Binary, other: Operand) -> Self {
+        Expr::new(Node::Binary(op, self.node.clone(), other.node()))
+    }
+
+    fn reversed(&self, op: Binary, other: Operand) -> Self {
+        Expr::new(Node::Binary(op, other.node(), self.node.clone()))
+    }
+
+    fn rolling(&self, op: Rolling, window: usize) -> PyResult<Self> {
+        crate::errors::require_window(window)?;
+        Ok(Expr::new(Node::Rolling(op, window, self.node.clone())))
+    }
+}
+
+#[pymethods]
+impl Expr {
+    fn add(&self, other: Operand) -> Self {
+        self.binary(Binary::Add, other)
+    }
+
+    fn sub(&self, other: Operand) -> Self {
+        self.binary(Binary::Sub, other)
+    }
+
+    fn mul(&self, other: Operand) -> Self {
+        self.binary(Binary::Mul, other)
+    }
+
+    fn div(&self, other: Operand) -> Self {
+        self.binary(Binary::Div, other)
+    }
+
+    fn gt(&self, other: Operand) -> Self {
+        self.binary(Binary::Gt, other)
+    }
+
+    fn ge(&self, other: Operand) -> Self {
+        self.binary(Binary::Ge, other)
+    }
+
+    fn lt(&self, other: Operand) -> Self {
+        self.binary(Binary::Lt, other)
+    }
+
+    fn le(&self, other: Operand) -> Self {
+        self.binary(Binary::Le, other)
+    }
+
+    fn and_(&self, other: Operand) -> Self {
+        self.binary(Binary::And, other)
+    }
+
+    fn or_(&self, other: Operand) -> Self {
+        self.binary(Binary::Or, other)
+    }
+
+    fn not_(&self) -> Self {
+        self.unary(Unary::Not)
+    }
+
+    fn neg(&self) -> Self {
+        self.unary(Unary::Neg)
+    }
+
+    fn abs(&self) -> Self {
+        self.unary(Unary::Abs)
+    }
+
+    fn log(&self) -> Self {
+        self.unary(Unary::Log)
+    }
+
+    fn exp(&self) -> Self {
+        self.unary(Unary::Exp)
+    }
+
+    fn sqrt(&self) -> Self {
+        self.unary(Unary::Sqrt)
+    }
+
+    /// Value `n` rows earlier; NaN for the first `n` rows
+    #[pyo3(signature = (n=1))]
+    fn shift(&self, n: usize) -> Self {
+        Expr::new(Node::Shift(n, self.node.clone()))
+    }
+
+    /// Change over `n` rows
+    #[pyo3(signature = (n=1))]
+    fn diff(&self, n: usize) -> Self {
+        self.sub(Operand::Expr(self.shift(n)))
+    }
+
+    /// Fractional change over `n` rows
+    #[pyo3(signature = (n=1))]
+    fn pct_change(&self, n: usize) -> Self {
+        self.div(Operand::Expr(self.shift(n))).sub(Operand::Value(1.0))
+    }
+
+    fn rolling_mean(&self, window: usize) -> PyResult<Self> {
+        self.rolling(Rolling::Mean, window)
+    }
+
+    fn rolling_sum(&self, window: usize) -> PyResult<Self> {
+        self.rolling(Rolling::Sum, window)
+    }
+
+    #[pyo3(signature = (window, ddof=1))]
+    fn rolling_std(&self, window: usize, ddof: usize) -> PyResult<Self> {
+        self.rolling(Rolling::Std(ddof), window)
+    }
+
+    fn rolling_min(&self, window: usize) -> PyResult<Self> {
+        self.rolling(Rolling::Min, window)
+    }
+
+    fn rolling_max(&self, window: usize) -> PyResult<Self> {
+        self.rolling(Rolling::Max, window)
+    }
+
+    /// Exponential moving average, as `StreamingEMA`; NaN inputs give NaN and are skipped
+    fn ema(&self, period: usize) -> PyResult<Self> {
+        StreamingEMA::new(period)?;
+        Ok(Expr::new(Node::Ema(period, self.node.clone())))
+    }
+
+    /// Evaluate against `data`, anything indexable by column name (a dict of arrays, an
+    /// `OhlcvFrame` or a DataFrame). Returns a bool numpy array for comparisons and
+    /// logical expressions, else a float64 array (or Arrow / Polars, like the inputs).
+    fn evaluate<'py>(&self, py: Python<'py>, data: &Bound<'py, PyAny>) -> PyResult<PyObject> {
+        let program = Program::compile(&self.node)?;
+        let inputs = program
+            .columns
+            .iter()
+            .map(|name| data.get_item(name)?.extract::<Column<'py, f64>>())
+            .collect::<PyResult<Vec<_>>>()?;
+        let kind = ArrayKind::of(&inputs.iter().map(|c| c.kind()).collect::<Vec<_>>());
+        let values = inputs.iter().map(|c| c.values()).collect::<PyResult<Vec<_>>>()?;
+        let len = match values.first() {
+            Some(first) => first.len(),
+            None => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Expression must reference at least one column"
+                ))
+            }
+        };
+        if values.iter().any(|v| v.len() != len) {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "All columns must have the same length"
+            ));
+        }
+
+        let columns: Vec<&[f64]> = values.iter().map(|v| v.as_ref()).collect();
+        let result = py.allow_threads(|| program.run(&columns, len));
+        if self.node.is_bool() {
+            let mask: Vec<bool> = result.iter().map(|&v| v == 1.0).collect();
+            return Ok(PyArray1::from_vec_bound(py, mask).into_any().unbind());
+        }
+        kind.wrap(py, result)
+    }
+
+    fn __add__(&self, other: Operand) -> Self {
+        self.binary(Binary::Add, other)
+    }
+
+    fn __radd__(&self, other: Operand) -> Self {
+        self.reversed(Binary::Add, other)
+    }
+
+    fn __sub__(&self, other: Operand) -> Self {
+        self.binary(Binary::Sub, other)
+    }
+
+    fn __rsub__(&self, other: Operand) -> Self {
+        self.reversed(Binary::Sub, other)
+    }
+
+    fn __mul__(&self, other: Operand) -> Self {
+        self.binary(Binary::Mul, other)
+    }
+
+    fn __rmul__(&self, other: Operand) -> Self {
+        self.reversed(Binary::Mul, other)
+    }
+
+    fn __truediv__(&self, other: Operand) -> Self {
+        self.binary(Binary::Div, other)
+    }
+
+    fn __rtruediv__(&self, other: Operand) -> Self {
+        self.reversed(Binary::Div, other)
+    }
+
+    fn __and__(&self, other: Operand) -> Self {
+        self.binary(Binary::And, other)
+    }
+
+    fn __or__(&self, other: Operand) -> Self {
+        self.binary(Binary::Or, other)
+    }
+
+    fn __invert__(&self) -> Self {
+        self.unary(Unary::Not)
+    }
+
+    fn __neg__(&self) -> Self {
+        self.unary(Unary::Neg)
+    }
+
+    fn __abs__(&self) -> Self {
+        self.unary(Unary::Abs)
+    }
+
+    fn __repr__(&self) -> String {
+        self.node.to_string()
+    }
+}
+
+/// A lazy reference to the column `name`
+#[pyfunction]
+pub fn col(name: String) -> Expr {
+    Expr::new(Node::Column(name))
+}
+
+/// A lazy constant
+#[pyfunction]
+pub fn lit(value: f64) -> Expr {
+    Expr::new(Node::Literal(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(expr: &Expr, x: &[f64]) -> Vec<f64> {
+        let program = Program::compile(&expr.node).unwrap();
+        program.run(&[x], x.len())
+    }
+
+    fn same(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x == y || (x.is_nan() && y.is_nan()))
+    }
+
+    #[test]
+    fn windows_are_nan_until_full_and_while_holding_nan() {
+        let mut window = Window::new(3);
+        let mut means = Vec::new();
+        for x in [1.0, 2.0, 3.0, f64::NAN, 4.0, 5.0, 6.0, 7.0] {
+            window.push(x);
+            means.push(window.value(Rolling::Mean));
+        }
+        let nan = f64::NAN;
+        assert!(same(&means, &[nan, nan, 2.0, nan, nan, nan, 5.0, 6.0]));
+        assert_eq!(window.value(Rolling::Sum), 18.0);
+        assert_eq!(window.value(Rolling::Std(1)), 1.0);
+        // Too few values for the degrees of freedom
+        assert!(window.value(Rolling::Std(3)).is_nan());
+    }
+
+    #[test]
+    fn min_and_max_queues_track_the_window() {
+        let x = [5.0, 1.0, 4.0, 2.0, 8.0, 3.0, 3.0, 0.0, 9.0];
+        let mut window = Window::new(3);
+        for (i, &value) in x.iter().enumerate() {
+            window.push(value);
+            if i >= 2 {
+                let slice = &x[i - 2..=i];
+                assert_eq!(window.value(Rolling::Min), slice.iter().copied().fold(f64::INFINITY, f64::min));
+                assert_eq!(window.value(Rolling::Max), slice.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+            }
+        }
+    }
+
+    #[test]
+    fn sums_re_centre_and_stay_accurate_far_from_zero() {
+        let mut window = Window::new(4);
+        let mut last = 0.0;
+        for i in 0..3 * DRIFT_INTERVAL + 7 {
+            last = 1e9 + (i % 4) as f64;
+            window.push(last);
+        }
+        // Every full window holds one each of 1e9 + 0..=3
+        assert_eq!(window.value(Rolling::Mean), 1e9 + 1.5);
+        assert!((window.value(Rolling::Std(0)) - 1.25f64.sqrt()).abs() < 1e-9);
+
+        // A window emptied of valid values restarts from the next one
+        for _ in 0..4 {
+            window.push(f64::NAN);
+        }
+        for _ in 0..4 {
+            window.push(-last);
+        }
+        assert_eq!(window.value(Rolling::Mean), -last);
+        assert_eq!(window.value(Rolling::Std(1)), 0.0);
+    }
+
+    #[test]
+    fn shift_diff_and_pct_change() {
+        let x = col("x".to_string());
+        let values = [1.0, 2.0, 4.0, 8.0];
+        let nan = f64::NAN;
+        assert!(same(&evaluate(&x.shift(1), &values), &[nan, 1.0, 2.0, 4.0]));
+        assert!(same(&evaluate(&x.shift(0), &values), &values));
+        assert!(same(&evaluate(&x.diff(2), &values), &[nan, nan, 3.0, 6.0]));
+        assert!(same(&evaluate(&x.pct_change(1), &values), &[nan, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn state_carries_across_block_boundaries() {
+        let x = col("x".to_string());
+        let expr = x.rolling_mean(5).unwrap().add(Operand::Expr(x.shift(3)));
+        for len in [BLOCK - 1, BLOCK, BLOCK + 1, 2 * BLOCK + 1] {
+            let values: Vec<f64> = (0..len).map(|i| (i * i % 97) as f64).collect();
+            let expected: Vec<f64> = (0..len)
+                .map(|i| if i < 4 { f64::NAN } else { values[i - 4..=i].iter().sum::<f64>() / 5.0 + values[i - 3] })
+                .collect();
+            let result = evaluate(&expr, &values);
+            assert!(result.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-9 || (a.is_nan() && b.is_nan())));
+            assert_eq!(result.len(), len);
+        }
+    }
+
+    #[test]
+    fn shared_subexpressions_compile_once() {
+        let mut e = col("x".to_string());
+        for _ in 0..60 {
+            e = e.add(Operand::Expr(e.clone()));
+        }
+        let program = Program::compile(&e.node).unwrap();
+        assert_eq!(program.ops.len(), 61);
+        assert_eq!(evaluate(&e, &[1.0, 0.5]), vec![2f64.powi(60), 2f64.powi(59)]);
+
+        // Equal subexpressions built separately share a register too
+        let a = col("x".to_string()).rolling_sum(2).unwrap();
+        let b = col("x".to_string()).rolling_sum(2).unwrap();
+        let program = Program::compile(&a.sub(Operand::Expr(b)).node).unwrap();
+        assert_eq!((program.ops.len(), program.columns.len()), (3, 1));
+    }
+}
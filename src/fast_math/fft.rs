@@ -0,0 +1,107 @@
+// Iterative radix-2 FFT over split real and imaginary parts, for the convolution-style
+// kernels (autocovariances) that would be quadratic when computed directly, and a DFT of
+// any length on top of it for spectral analysis.
+
+/// In-place FFT of a power-of-two length signal; `inverse` also divides by the length
+pub fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+    if n <= 1 {
+        return;
+    }
+    // Bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                // Twiddles are computed directly rather than by repeated multiplication,
+                // which would accumulate rounding over long transforms
+                let (s, c) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let (tr, ti) = (re[b] * c - im[b] * s, re[b] * s + im[b] * c);
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len *= 2;
+    }
+    if inverse {
+        let scale = 1.0 / n as f64;
+        re.iter_mut().chain(im.iter_mut()).for_each(|v| *v *= scale);
+    }
+}
+
+/// Sums `sum_t x[t] * x[t + k]` for `k = 0..=max_lag`, through the power spectrum of the
+/// zero-padded signal
+pub fn autocorrelation_sums(x: &[f64], max_lag: usize) -> Vec<f64> {
+    let size = (2 * x.len()).next_power_of_two();
+    let mut re = x.to_vec();
+    re.resize(size, 0.0);
+    let mut im = vec![0.0; size];
+    fft(&mut re, &mut im, false);
+    for (r, i) in re.iter_mut().zip(im.iter_mut()) {
+        *r = *r * *r + *i * *i;
+        *i = 0.0;
+    }
+    fft(&mut re, &mut im, true);
+    re.truncate(max_lag + 1);
+    re
+}
+
+/// DFT of a signal of any length, in place: power-of-two lengths go straight to `fft`,
+/// others through Bluestein's chirp-z algorithm as a power-of-two convolution
+pub fn dft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    debug_assert_eq!(im.len(), n);
+    if n.is_power_of_two() || n == 0 {
+        fft(re, im, false);
+        return;
+    }
+    // Chirp exp(-i pi k^2 / n), with k^2 reduced mod 2n so the angle stays exact for long
+    // signals
+    let chirp: Vec<(f64, f64)> = (0..n)
+        .map(|k| {
+            let angle = std::f64::consts::PI * ((k * k) % (2 * n)) as f64 / n as f64;
+            let (s, c) = angle.sin_cos();
+            (c, -s)
+        })
+        .collect();
+    let size = (2 * n - 1).next_power_of_two();
+    let (mut a_re, mut a_im) = (vec![0.0; size], vec![0.0; size]);
+    for (k, &(c, s)) in chirp.iter().enumerate() {
+        a_re[k] = re[k] * c - im[k] * s;
+        a_im[k] = re[k] * s + im[k] * c;
+    }
+    // The conjugate chirp at lags -(n - 1)..n, negative lags wrapping to the end
+    let (mut b_re, mut b_im) = (vec![0.0; size], vec![0.0; size]);
+    for (k, &(c, s)) in chirp.iter().enumerate() {
+        b_re[k] = c;
+        b_im[k] = -s;
+        if k > 0 {
+            b_re[size - k] = c;
+            b_im[size - k] = -s;
+        }
+    }
+    fft(&mut a_re, &mut a_im, false);
+    fft(&mut b_re, &mut b_im, false);
+    for (((ar, ai), br), bi) in a_re.iter_mut().zip(a_im.iter_mut()).zip(&b_re).zip(&b_im) {
+        (*ar, *ai) = (*ar * br - *ai * bi, *ar * bi + *ai * br);
+    }
+    fft(&mut a_re, &mut a_im, true);
+    for (k, &(c, s)) in chirp.iter().enumerate() {
+        re[k] = a_re[k] * c - a_im[k] * s;
+        im[k] = a_re[k] * s + a_im[k] * c;
+    }
+}
@@ -0,0 +1,519 @@
+use std::fs::File;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use memchr::{memchr, memchr_iter, memmem};
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::threads;
+
+/// Standard FIX field separator
+const SOH: u8 = 0x01;
+
+/// Iterator over the `(tag, value)` fields of a raw FIX message; values borrow the input
+pub struct FixFields<'a> {
+    data: &'a [u8],
+    pos: usize,
+    delimiter: u8,
+}
+
+impl<'a> FixFields<'a> {
+    /// Fields are separated by SOH, or by '|' for human-readable logs without SOH bytes
+    pub fn new(data: &'a [u8]) -> Self {
+        let delimiter = if memchr(SOH, data).is_some() { SOH } else { b'|' };
+        FixFields { data: data.trim_ascii_end(), pos: 0, delimiter }
+    }
+}
+
+impl<'a> Iterator for FixFields<'a> {
+    type Item = Result<(u32, &'a [u8]), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.data[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+        let start = self.pos;
+        let end = memchr(self.delimiter, rest).unwrap_or(rest.len());
+        let field = &rest[..end];
+        self.pos += (end + 1).min(rest.len());
+
+        let Some(eq) = memchr(b'=', field) else {
+            return Some(Err(format!("Malformed FIX field at byte {}: missing '='", start)));
+        };
+        match parse_tag(&field[..eq]) {
+            Some(tag) => Some(Ok((tag, &field[eq + 1..]))),
+            None => Some(Err(format!("Malformed FIX field at byte {}: invalid tag", start))),
+        }
+    }
+}
+
+fn parse_tag(bytes: &[u8]) -> Option<u32> {
+    if bytes.is_empty() || bytes.len() > 9 {
+        return None;
+    }
+    bytes.iter().try_fold(0u32, |acc, &b| b.is_ascii_digit().then(|| acc * 10 + (b - b'0') as u32))
+}
+
+fn parse_f64(value: &[u8]) -> Option<f64> {
+    fast_float::parse(value).ok()
+}
+
+fn parse_i64(value: &[u8]) -> Option<i64> {
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+fn to_string(value: &[u8]) -> String {
+    String::from_utf8_lossy(value).into_owned()
+}
+
+/// Verify the CheckSum (10) field: the byte sum of everything before it, modulo 256.
+/// In '|'-delimited logs each separator counts as the SOH it stands for
+fn verify_checksum(data: &[u8]) -> Result<(), String> {
+    let delimiter = if memchr(SOH, data).is_some() { SOH } else { b'|' };
+    let marker = [delimiter, b'1', b'0', b'='];
+    let pos = memmem::rfind(data, &marker).ok_or("Missing CheckSum (10) field")?;
+    let body_end = pos + 1;
+    let value_end = memchr(delimiter, &data[body_end + 3..]).map_or(data.len(), |e| body_end + 3 + e);
+    let expected = parse_i64(data[body_end + 3..value_end].trim_ascii()).ok_or("Invalid CheckSum (10) value")?;
+    let actual = data[..body_end].iter().map(|&b| if b == delimiter { SOH } else { b } as u32).sum::<u32>() % 256;
+    if actual as i64 != expected {
+        return Err(format!("CheckSum mismatch: message has {:03}, computed {:03}", expected, actual));
+    }
+    Ok(())
+}
+
+/// Execution report (35=8)
+#[pyclass(module = "fast_math.fix")]
+#[derive(Clone, Default)]
+pub struct ExecutionReport {
+    #[pyo3(get)]
+    pub begin_string: String,
+    #[pyo3(get)]
+    pub sender_comp_id: String,
+    #[pyo3(get)]
+    pub target_comp_id: String,
+    #[pyo3(get)]
+    pub msg_seq_num: Option<i64>,
+    #[pyo3(get)]
+    pub sending_time: Option<String>,
+    #[pyo3(get)]
+    pub order_id: Option<String>,
+    #[pyo3(get)]
+    pub cl_ord_id: Option<String>,
+    #[pyo3(get)]
+    pub exec_id: Option<String>,
+    #[pyo3(get)]
+    pub exec_type: Option<String>,
+    #[pyo3(get)]
+    pub ord_status: Option<String>,
+    #[pyo3(get)]
+    pub symbol: Option<String>,
+    #[pyo3(get)]
+    pub side: Option<String>,
+    #[pyo3(get)]
+    pub order_qty: Option<f64>,
+    #[pyo3(get)]
+    pub price: Option<f64>,
+    #[pyo3(get)]
+    pub last_qty: Option<f64>,
+    #[pyo3(get)]
+    pub last_px: Option<f64>,
+    #[pyo3(get)]
+    pub leaves_qty: Option<f64>,
+    #[pyo3(get)]
+    pub cum_qty: Option<f64>,
+    #[pyo3(get)]
+    pub avg_px: Option<f64>,
+    #[pyo3(get)]
+    pub transact_time: Option<String>,
+    #[pyo3(get)]
+    pub text: Option<String>,
+}
+
+#[pymethods]
+impl ExecutionReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "ExecutionReport(exec_id={:?}, symbol={:?}, side={:?}, ord_status={:?}, last_qty={:?}, last_px={:?})",
+            self.exec_id, self.symbol, self.side, self.ord_status, self.last_qty, self.last_px
+        )
+    }
+}
+
+/// One entry of a market data repeating group (NoMDEntries, 268)
+#[pyclass(module = "fast_math.fix")]
+#[derive(Clone, Default)]
+pub struct MdEntry {
+    /// MDUpdateAction (279); only present in incremental refreshes
+    #[pyo3(get)]
+    pub update_action: Option<String>,
+    #[pyo3(get)]
+    pub entry_type: Option<String>,
+    #[pyo3(get)]
+    pub entry_id: Option<String>,
+    #[pyo3(get)]
+    pub symbol: Option<String>,
+    #[pyo3(get)]
+    pub price: Option<f64>,
+    #[pyo3(get)]
+    pub size: Option<f64>,
+    #[pyo3(get)]
+    pub position: Option<i64>,
+}
+
+#[pymethods]
+impl MdEntry {
+    fn __repr__(&self) -> String {
+        format!(
+            "MdEntry(update_action={:?}, entry_type={:?}, price={:?}, size={:?})",
+            self.update_action, self.entry_type, self.price, self.size
+        )
+    }
+}
+
+/// Market data snapshot (35=W) or incremental refresh (35=X)
+#[pyclass(module = "fast_math.fix")]
+#[derive(Clone, Default)]
+pub struct MarketData {
+    #[pyo3(get)]
+    pub msg_type: String,
+    #[pyo3(get)]
+    pub begin_string: String,
+    #[pyo3(get)]
+    pub sender_comp_id: String,
+    #[pyo3(get)]
+    pub target_comp_id: String,
+    #[pyo3(get)]
+    pub msg_seq_num: Option<i64>,
+    #[pyo3(get)]
+    pub sending_time: Option<String>,
+    #[pyo3(get)]
+    pub md_req_id: Option<String>,
+    #[pyo3(get)]
+    pub symbol: Option<String>,
+    #[pyo3(get)]
+    pub entries: Vec<MdEntry>,
+}
+
+#[pymethods]
+impl MarketData {
+    fn __repr__(&self) -> String {
+        format!(
+            "MarketData(msg_type={:?}, symbol={:?}, entries={})",
+            self.msg_type, self.symbol, self.entries.len()
+        )
+    }
+}
+
+/// A parsed message, built without the GIL so logs can be parsed in parallel
+enum Message {
+    Execution(Box<ExecutionReport>),
+    MarketData(Box<MarketData>),
+    Fields(Vec<(u32, String)>),
+}
+
+impl Message {
+    fn into_py(self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(match self {
+            Message::Execution(report) => Py::new(py, *report)?.into_any(),
+            Message::MarketData(data) => Py::new(py, *data)?.into_any(),
+            Message::Fields(fields) => fields_to_dict(py, fields)?.into_any().unbind(),
+        })
+    }
+}
+
+fn fields_to_dict(py: Python<'_>, fields: Vec<(u32, String)>) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (tag, value) in fields {
+        // Tags repeated inside repeating groups collect their values into a list
+        match dict.get_item(tag)? {
+            None => dict.set_item(tag, value)?,
+            Some(existing) => match existing.downcast::<PyList>() {
+                Ok(list) => list.append(value)?,
+                Err(_) => dict.set_item(tag, PyList::new_bound(py, [existing, value.into_py(py).into_bound(py)]))?,
+            },
+        }
+    }
+    Ok(dict)
+}
+
+/// Render a message: `8=begin_string`, the BodyLength (9) of `body` (which starts with
+/// MsgType, 35), the body and its CheckSum (10), all SOH-delimited
+pub fn encode_message(begin_string: &str, body: &[(u32, String)]) -> String {
+    let body: String = body.iter().map(|(tag, value)| format!("{}={}\x01", tag, value)).collect();
+    let head = format!("8={}\x019={}\x01", begin_string, body.len());
+    let checksum = head.bytes().chain(body.bytes()).map(|b| b as u32).sum::<u32>() % 256;
+    format!("{}{}10={:03}\x01", head, body, checksum)
+}
+
+fn parse_execution_report(data: &[u8]) -> Result<ExecutionReport, String> {
+    let mut report = ExecutionReport::default();
+    for field in FixFields::new(data) {
+        let (tag, value) = field?;
+        match tag {
+            8 => report.begin_string = to_string(value),
+            49 => report.sender_comp_id = to_string(value),
+            56 => report.target_comp_id = to_string(value),
+            34 => report.msg_seq_num = parse_i64(value),
+            52 => report.sending_time = Some(to_string(value)),
+            37 => report.order_id = Some(to_string(value)),
+            11 => report.cl_ord_id = Some(to_string(value)),
+            17 => report.exec_id = Some(to_string(value)),
+            150 => report.exec_type = Some(to_string(value)),
+            39 => report.ord_status = Some(to_string(value)),
+            55 => report.symbol = Some(to_string(value)),
+            54 => report.side = Some(to_string(value)),
+            38 => report.order_qty = parse_f64(value),
+            44 => report.price = parse_f64(value),
+            32 => report.last_qty = parse_f64(value),
+            31 => report.last_px = parse_f64(value),
+            151 => report.leaves_qty = parse_f64(value),
+            14 => report.cum_qty = parse_f64(value),
+            6 => report.avg_px = parse_f64(value),
+            60 => report.transact_time = Some(to_string(value)),
+            58 => report.text = Some(to_string(value)),
+            _ => {}
+        }
+    }
+    Ok(report)
+}
+
+fn parse_market_data(data: &[u8], msg_type: &str) -> Result<MarketData, String> {
+    let mut message = MarketData { msg_type: msg_type.to_string(), ..Default::default() };
+    let mut in_group = false;
+
+    for field in FixFields::new(data) {
+        let (tag, value) = field?;
+        match tag {
+            8 => message.begin_string = to_string(value),
+            49 => message.sender_comp_id = to_string(value),
+            56 => message.target_comp_id = to_string(value),
+            34 => message.msg_seq_num = parse_i64(value),
+            52 => message.sending_time = Some(to_string(value)),
+            262 => message.md_req_id = Some(to_string(value)),
+            268 => in_group = true,
+            55 if !in_group => message.symbol = Some(to_string(value)),
+            // Each entry starts with its first field: MDUpdateAction in refreshes, MDEntryType in snapshots
+            279 if in_group => message.entries.push(MdEntry { update_action: Some(to_string(value)), ..Default::default() }),
+            269 if in_group => match message.entries.last_mut() {
+                Some(entry) if entry.entry_type.is_none() => entry.entry_type = Some(to_string(value)),
+                _ => message.entries.push(MdEntry { entry_type: Some(to_string(value)), ..Default::default() }),
+            },
+            _ if in_group => {
+                let Some(entry) = message.entries.last_mut() else { continue };
+                match tag {
+                    278 => entry.entry_id = Some(to_string(value)),
+                    55 => entry.symbol = Some(to_string(value)),
+                    270 => entry.price = parse_f64(value),
+                    271 => entry.size = parse_f64(value),
+                    290 => entry.position = parse_i64(value),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for entry in &mut message.entries {
+        if entry.symbol.is_none() {
+            entry.symbol = message.symbol.clone();
+        }
+    }
+    Ok(message)
+}
+
+/// Parse one message: execution reports and market data become typed objects when
+/// `typed`, everything else a `{tag: value}` dict
+fn parse_message(data: &[u8], typed: bool, validate_checksum: bool) -> Result<Message, String> {
+    if validate_checksum {
+        verify_checksum(data)?;
+    }
+
+    let mut msg_type = None;
+    for field in FixFields::new(data) {
+        let (tag, value) = field?;
+        if tag == 35 {
+            msg_type = Some(value);
+            break;
+        }
+    }
+
+    match msg_type {
+        Some(b"8") if typed => Ok(Message::Execution(Box::new(parse_execution_report(data)?))),
+        Some(t @ (b"W" | b"X")) if typed => {
+            Ok(Message::MarketData(Box::new(parse_market_data(data, if t == b"W" { "W" } else { "X" })?)))
+        }
+        Some(_) => FixFields::new(data)
+            .map(|field| field.map(|(tag, value)| (tag, to_string(value))))
+            .collect::<Result<_, _>>()
+            .map(Message::Fields),
+        None => Err("Missing MsgType (35) field".to_string()),
+    }
+}
+
+fn message_bytes<'a>(message: &'a Bound<'_, PyAny>) -> PyResult<&'a [u8]> {
+    if let Ok(bytes) = message.downcast::<pyo3::types::PyBytes>() {
+        return Ok(bytes.as_bytes());
+    }
+    if let Ok(text) = message.downcast::<pyo3::types::PyString>() {
+        return Ok(text.to_str()?.as_bytes());
+    }
+    Err(pyo3::exceptions::PyTypeError::new_err(
+        "FIX message must be bytes or str"
+    ))
+}
+
+/// Parse a single FIX 4.2/4.4 message (bytes or str, SOH- or '|'-delimited).
+///
+/// Execution reports (35=8) are returned as `ExecutionReport`, market data snapshots
+/// and incremental refreshes (35=W / 35=X) as `MarketData`; other message types, or
+/// all messages when `typed=False`, are returned as a `{tag: value}` dict.
+#[pyfunction]
+#[pyo3(signature = (message, typed=true, validate_checksum=false))]
+pub fn parse_fix_rust(
+    py: Python<'_>,
+    message: &Bound<'_, PyAny>,
+    typed: bool,
+    validate_checksum: bool
+) -> PyResult<PyObject> {
+    let data = message_bytes(message)?;
+    parse_message(data, typed, validate_checksum)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?
+        .into_py(py)
+}
+
+/// Parse every FIX message in a log file, in file order.
+///
+/// Each line may carry a prefix (e.g. a capture timestamp); the message starts at its
+/// `8=FIX` marker. Lines without a marker are skipped. Lines are parsed in parallel.
+#[pyfunction]
+#[pyo3(signature = (path, typed=true, validate_checksum=false))]
+pub fn parse_fix_log_rust<'py>(
+    py: Python<'py>,
+    path: &str,
+    typed: bool,
+    validate_checksum: bool
+) -> PyResult<Bound<'py, PyList>> {
+    let file = File::open(path)?;
+    // Safety: the log is opened read-only; concurrent truncation by another process is not supported
+    let mmap = unsafe { Mmap::map(&file)? };
+    let data = &mmap[..];
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for end in memchr_iter(b'\n', data).chain(std::iter::once(data.len())) {
+        if end > start {
+            lines.push((lines.len() + 1, &data[start..end]));
+        }
+        start = end + 1;
+    }
+
+    let finder = memmem::Finder::new(b"8=FIX");
+    let messages: Vec<Message> = py.allow_threads(|| {
+        threads::install(|| {
+            lines
+                .par_iter()
+                .filter_map(|&(line_no, line)| {
+                    finder.find(line).map(|pos| {
+                        parse_message(&line[pos..], typed, validate_checksum)
+                            .map_err(|e| format!("{} (line {})", e, line_no))
+                    })
+                })
+                .collect::<Result<_, String>>()
+        })
+    }).map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+    let result = PyList::empty_bound(py);
+    for message in messages {
+        result.append(message.into_py(py)?)?;
+    }
+    Ok(result)
+}
+
+/// Populate the `fast_math.fix` submodule
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ExecutionReport>()?;
+    m.add_class::<MarketData>()?;
+    m.add_class::<MdEntry>()?;
+    m.add_function(wrap_pyfunction!(parse_fix_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_fix_log_rust, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execution_report() -> String {
+        encode_message("FIX.4.4", &[
+            (35, "8".to_string()),
+            (49, "BROKER".to_string()),
+            (17, "E1".to_string()),
+            (55, "INFY".to_string()),
+            (54, "1".to_string()),
+            (32, "25".to_string()),
+            (31, "1502.5".to_string()),
+        ])
+    }
+
+    #[test]
+    fn fields_split_into_tags_and_values() {
+        let fields: Vec<_> = FixFields::new(b"8=FIX.4.2|35=D|58=a=b|\n").collect::<Result<_, _>>().unwrap();
+        assert_eq!(fields, vec![(8, &b"FIX.4.2"[..]), (35, &b"D"[..]), (58, &b"a=b"[..])]);
+        assert!(FixFields::new(b"8=FIX.4.2\x01bad\x01").nth(1).unwrap().is_err());
+        assert!(FixFields::new(b"x8=FIX.4.2").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn execution_reports_are_typed() {
+        let Ok(Message::Execution(report)) = parse_message(execution_report().as_bytes(), true, true) else {
+            panic!("expected an execution report");
+        };
+        assert_eq!(report.begin_string, "FIX.4.4");
+        assert_eq!(report.sender_comp_id, "BROKER");
+        assert_eq!((report.exec_id.as_deref(), report.symbol.as_deref()), (Some("E1"), Some("INFY")));
+        assert_eq!((report.last_qty, report.last_px), (Some(25.0), Some(1502.5)));
+        assert!(matches!(parse_message(execution_report().as_bytes(), false, true), Ok(Message::Fields(_))));
+    }
+
+    #[test]
+    fn snapshot_entries_start_at_each_entry_type() {
+        let data = b"8=FIX.4.4|35=W|55=NIFTY|268=2|269=0|270=100.5|271=10|269=1|270=101|271=5|";
+        let message = parse_market_data(data, "W").unwrap();
+        assert_eq!(message.symbol.as_deref(), Some("NIFTY"));
+        assert_eq!(message.entries.len(), 2);
+        assert_eq!(message.entries[0].entry_type.as_deref(), Some("0"));
+        assert_eq!((message.entries[0].price, message.entries[0].size), (Some(100.5), Some(10.0)));
+        assert_eq!((message.entries[1].price, message.entries[1].size), (Some(101.0), Some(5.0)));
+        // Entries inherit the message symbol
+        assert!(message.entries.iter().all(|e| e.symbol.as_deref() == Some("NIFTY")));
+    }
+
+    #[test]
+    fn refresh_entries_start_at_each_update_action() {
+        let data = b"8=FIX.4.4|35=X|268=2|279=0|269=0|55=A|270=10|279=2|269=1|55=B|278=7|";
+        let message = parse_market_data(data, "X").unwrap();
+        assert_eq!(message.entries.len(), 2);
+        assert_eq!(message.entries[0].update_action.as_deref(), Some("0"));
+        assert_eq!(message.entries[0].entry_type.as_deref(), Some("0"));
+        assert_eq!((message.entries[0].symbol.as_deref(), message.entries[0].price), (Some("A"), Some(10.0)));
+        assert_eq!(message.entries[1].update_action.as_deref(), Some("2"));
+        assert_eq!(message.entries[1].entry_type.as_deref(), Some("1"));
+        assert_eq!(message.entries[1].entry_id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn checksums_verify_with_either_delimiter() {
+        let soh = execution_report();
+        assert!(verify_checksum(soh.as_bytes()).is_ok());
+        let pipe = soh.replace('\x01', "|");
+        assert!(verify_checksum(pipe.as_bytes()).is_ok());
+
+        let tampered = soh.replace("1502.5", "1502.6");
+        assert!(verify_checksum(tampered.as_bytes()).unwrap_err().starts_with("CheckSum mismatch"));
+        assert!(verify_checksum(tampered.replace('\x01', "|").as_bytes()).is_err());
+        assert!(verify_checksum(b"8=FIX.4.4|35=0|").is_err());
+    }
+}
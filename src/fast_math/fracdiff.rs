@@ -0,0 +1,78 @@
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::alignment::Alignment;
+use crate::column::{map_series, FloatElement, SeriesInput};
+use crate::config::FastMathConfig;
+use crate::threads;
+
+/// Weights of the fractional difference `(1 - B)^d`, `w_0 = 1` and `w_k = -w_{k-1} (d - k
+/// + 1) / k`, up to the last one not below `threshold` in magnitude; at most `limit` of them
+fn weights(d: f64, threshold: f64, limit: usize) -> Vec<f64> {
+    let mut weights = vec![1.0];
+    while weights.len() < limit {
+        let k = weights.len() as f64;
+        let next = -weights[weights.len() - 1] * (d - k + 1.0) / k;
+        if next.abs() < threshold {
+            break;
+        }
+        weights.push(next);
+    }
+    weights
+}
+
+/// Fixed-width fractional difference at every position with a full window of weights
+fn frac_diff<T: FloatElement>(slice: &[T], d: f64, threshold: f64) -> PyResult<Vec<T>> {
+    // A window longer than the series has no full-window output, however many weights follow
+    let weights = weights(d, threshold, slice.len() + 1);
+    let width = weights.len();
+    if width > slice.len() {
+        return Ok(Vec::new());
+    }
+    let values: Vec<f64> = slice.iter().map(|v| v.to_f64().unwrap()).collect();
+    Ok(threads::install(|| {
+        (width - 1..values.len())
+            .into_par_iter()
+            .map(|t| {
+                let sum: f64 = weights.iter().zip(values[..=t].iter().rev()).map(|(w, x)| w * x).sum();
+                T::from(sum).unwrap()
+            })
+            .collect()
+    }))
+}
+
+/// Fractional differencing of `data` with a fixed-width window, as in López de Prado's
+/// *Advances in Financial Machine Learning* (section 5.5).
+///
+/// Each output is `sum_k w_k * x[t - k]` over the weights of `(1 - B)^d`, truncated at the
+/// first weight below `threshold` in magnitude, so every position uses the same window and
+/// the result stays stationary for a memory-preserving `d` such as 0.3-0.5; `d=1` gives
+/// plain first differences. The first `width - 1` positions lack a full window, so the
+/// result is that much shorter than the input (empty when the window exceeds the series);
+/// `align` is "valid", "same" or an integer shift, as for `moving_average_rust`. Smaller
+/// thresholds keep more memory at the cost of longer windows. Accepts a single series or a
+/// 2D array with one series per row; NaNs propagate to every window holding them.
+#[pyfunction]
+#[pyo3(signature = (data, d, threshold=1e-5, align=None, config=None))]
+pub fn frac_diff_rust<'py>(
+    py: Python<'py>,
+    data: SeriesInput<'py>,
+    d: f64,
+    threshold: f64,
+    align: Option<Alignment>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    if !d.is_finite() || d < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "d must be a finite value >= 0"
+        ));
+    }
+    if threshold.is_nan() || threshold <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "threshold must be > 0"
+        ));
+    }
+    map_series!(py, data, |slice| frac_diff(slice, d, threshold).map(|r| align.pad(r, slice.len())))
+}
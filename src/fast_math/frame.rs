@@ -0,0 +1,382 @@
+use std::sync::Arc;
+
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict, PySlice};
+use numpy::{Element, PyArray1};
+use ndarray::{s, Array1, ArrayView1, Axis};
+
+use crate::column::Column;
+use crate::streaming::{StreamingATR, StreamingEMA, StreamingRSI, StreamingSMA};
+use crate::telemetry;
+
+/// Column storage shared by a frame and all slices taken from it
+struct FrameData {
+    timestamp: Array1<i64>,
+    open: Array1<f64>,
+    high: Array1<f64>,
+    low: Array1<f64>,
+    close: Array1<f64>,
+    volume: Array1<f64>,
+}
+
+impl FrameData {
+    /// A copy of rows `start..end`
+    fn rows(&self, start: usize, end: usize) -> Self {
+        let copy = |c: &Array1<f64>| c.slice(s![start..end]).to_owned();
+        FrameData {
+            timestamp: self.timestamp.slice(s![start..end]).to_owned(),
+            open: copy(&self.open),
+            high: copy(&self.high),
+            low: copy(&self.low),
+            close: copy(&self.close),
+            volume: copy(&self.volume),
+        }
+    }
+}
+
+type FloatColumn = fn(&FrameData) -> &Array1<f64>;
+
+fn float_column(name: &str) -> PyResult<FloatColumn> {
+    Ok(match name {
+        "open" => |d| &d.open,
+        "high" => |d| &d.high,
+        "low" => |d| &d.low,
+        "close" => |d| &d.close,
+        "volume" => |d| &d.volume,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown column '{}', expected 'open', 'high', 'low', 'close' or 'volume'", name
+            )))
+        }
+    })
+}
+
+/// Streaming state of an indicator kept up to date by `append_and_update`
+enum Incremental {
+    Sma(StreamingSMA),
+    Ema(StreamingEMA),
+    Rsi(StreamingRSI),
+    Atr(StreamingATR),
+}
+
+impl Incremental {
+    fn parse(name: &str, period: usize) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sma" => Ok(Incremental::Sma(StreamingSMA::new(period)?)),
+            "ema" => Ok(Incremental::Ema(StreamingEMA::new(period)?)),
+            "rsi" => Ok(Incremental::Rsi(StreamingRSI::new(period)?)),
+            "atr" => Ok(Incremental::Atr(StreamingATR::new(period)?)),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown indicator '{}', expected 'sma', 'ema', 'rsi' or 'atr'", name
+            ))),
+        }
+    }
+}
+
+/// An indicator tracked by a frame, with its value at each of the frame's rows
+struct Tracked {
+    name: String,
+    indicator: Incremental,
+    column: FloatColumn,
+    values: Vec<f64>,
+}
+
+impl Tracked {
+    /// Feed rows `start..end` of `data`, returning the values after each
+    fn extend(&mut self, data: &FrameData, start: usize, end: usize) -> Vec<f64> {
+        let source = (self.column)(data);
+        let new: Vec<f64> = (start..end)
+            .map(|i| match &mut self.indicator {
+                Incremental::Sma(sma) => sma.update(source[i]),
+                Incremental::Ema(ema) => ema.update(source[i]),
+                Incremental::Rsi(rsi) => rsi.update(source[i]),
+                Incremental::Atr(atr) => atr.update(data.high[i], data.low[i], data.close[i]),
+            })
+            .collect();
+        self.values.extend_from_slice(&new);
+        new
+    }
+}
+
+/// OHLCV bars held in contiguous Rust memory.
+///
+/// Columns are exposed as read-only numpy views without copying, slicing shares the
+/// underlying buffers, and a frame can be passed wherever a float64 series is expected,
+/// in which case its close column is used.
+#[pyclass(module = "fast_math")]
+pub struct OhlcvFrame {
+    data: Arc<FrameData>,
+    start: usize,
+    end: usize,
+    tracked: Vec<Tracked>,
+}
+
+impl OhlcvFrame {
+    fn share(&self, start: usize, end: usize) -> Self {
+        OhlcvFrame { data: self.data.clone(), start, end, tracked: Vec::new() }
+    }
+
+    /// Read-only numpy view over this frame's rows of `column`
+    fn view<'py, T: Element>(slf: &Bound<'py, Self>, column: fn(&FrameData) -> &Array1<T>) -> PyResult<Bound<'py, PyArray1<T>>> {
+        let frame = slf.borrow();
+        let array = column(&frame.data).slice(s![frame.start..frame.end]);
+        // The base is a frame of its own sharing the buffer, so an append moving this
+        // frame to new storage leaves the view valid
+        let owner = Bound::new(slf.py(), frame.share(frame.start, frame.end))?;
+        // Safety: the view's base object owns the buffer through its Arc
+        let view = unsafe { PyArray1::borrow_from_array_bound(&array, owner.into_any()) };
+        view.call_method("setflags", (), Some(&[("write", false)].into_py_dict_bound(slf.py())))?;
+        Ok(view)
+    }
+
+    /// View of the close column, used when a frame is passed as a series
+    pub fn close_view<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, |d| &d.close)
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+#[pymethods]
+impl OhlcvFrame {
+    /// Build a frame from equal-length timestamp and OHLCV columns, copying them once
+    #[new]
+    fn new<'py>(
+        timestamp: Column<'py, i64>,
+        open: Column<'py, f64>,
+        high: Column<'py, f64>,
+        low: Column<'py, f64>,
+        close: Column<'py, f64>,
+        volume: Column<'py, f64>
+    ) -> PyResult<Self> {
+        telemetry::traced("OhlcvFrame.new", || {
+            let owned = |c: &Column<'py, f64>| -> PyResult<Array1<f64>> { Ok(Array1::from(c.values()?.into_owned())) };
+            let timestamp = Array1::from(timestamp.values()?.into_owned());
+            let (open, high, low, close, volume) = (owned(&open)?, owned(&high)?, owned(&low)?, owned(&close)?, owned(&volume)?);
+
+            let n = timestamp.len();
+            if [&open, &high, &low, &close, &volume].iter().any(|c| c.len() != n) {
+                return Err(crate::errors::LengthMismatchError::new_err(
+                    "All columns must have the same length"
+                ));
+            }
+            Ok(OhlcvFrame {
+                data: Arc::new(FrameData { timestamp, open, high, low, close, volume }),
+                start: 0,
+                end: n,
+                tracked: Vec::new(),
+            })
+        })
+    }
+
+    /// Build a frame from the "timestamp", "open", "high", "low", "close" and "volume"
+    /// columns of a Polars DataFrame or a pyarrow record batch or table, selected by name;
+    /// other columns are ignored
+    #[staticmethod]
+    fn from_table<'py>(table: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let column = |name: &str| -> PyResult<Bound<'py, PyAny>> {
+            table.get_item(name).map_err(|_| {
+                pyo3::exceptions::PyKeyError::new_err(format!("table has no '{}' column", name))
+            })
+        };
+        OhlcvFrame::new(
+            column("timestamp")?.extract()?,
+            column("open")?.extract()?,
+            column("high")?.extract()?,
+            column("low")?.extract()?,
+            column("close")?.extract()?,
+            column("volume")?.extract()?,
+        )
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("OhlcvFrame(rows={})", self.len())
+    }
+
+    /// `frame[a:b]` returns a frame sharing this one's memory, without its tracked
+    /// indicators; `frame["close"]` a column view
+    fn __getitem__<'py>(slf: &Bound<'py, Self>, key: &Bound<'py, PyAny>) -> PyResult<PyObject> {
+        let py = slf.py();
+        if let Ok(slice) = key.downcast::<PySlice>() {
+            let frame = slf.borrow();
+            let indices = slice.indices(frame.len() as std::os::raw::c_long)?;
+            if indices.step != 1 {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "OhlcvFrame slices must have step 1"
+                ));
+            }
+            let start = frame.start + indices.start as usize;
+            let end = (frame.start + indices.stop.max(indices.start) as usize).min(frame.end);
+            return Ok(frame.share(start, end).into_py(py));
+        }
+        let name: String = key.extract()?;
+        Ok(match name.as_str() {
+            "timestamp" => Self::view(slf, |d| &d.timestamp)?.into_any().unbind(),
+            "open" => Self::view(slf, |d| &d.open)?.into_any().unbind(),
+            "high" => Self::view(slf, |d| &d.high)?.into_any().unbind(),
+            "low" => Self::view(slf, |d| &d.low)?.into_any().unbind(),
+            "close" => Self::view(slf, |d| &d.close)?.into_any().unbind(),
+            "volume" => Self::view(slf, |d| &d.volume)?.into_any().unbind(),
+            _ => return Err(pyo3::exceptions::PyKeyError::new_err(name)),
+        })
+    }
+
+    #[getter]
+    fn timestamp<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<i64>>> {
+        Self::view(slf, |d| &d.timestamp)
+    }
+
+    #[getter]
+    fn open<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, |d| &d.open)
+    }
+
+    #[getter]
+    fn high<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, |d| &d.high)
+    }
+
+    #[getter]
+    fn low<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, |d| &d.low)
+    }
+
+    #[getter]
+    fn close<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, |d| &d.close)
+    }
+
+    #[getter]
+    fn volume<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, |d| &d.volume)
+    }
+
+    /// All columns as a dict of numpy views
+    fn to_dict<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new_bound(slf.py());
+        result.set_item("timestamp", Self::view(slf, |d| &d.timestamp)?)?;
+        result.set_item("open", Self::view(slf, |d| &d.open)?)?;
+        result.set_item("high", Self::view(slf, |d| &d.high)?)?;
+        result.set_item("low", Self::view(slf, |d| &d.low)?)?;
+        result.set_item("close", Self::view(slf, |d| &d.close)?)?;
+        result.set_item("volume", Self::view(slf, |d| &d.volume)?)?;
+        Ok(result)
+    }
+
+    /// Start keeping `indicator` ('sma', 'ema', 'rsi' or 'atr') of `period` bars on
+    /// `column` up to date as bars are appended, under `name`; returns its values so far.
+    ///
+    /// Values match the streaming classes of the same indicator fed bar by bar, so RSI
+    /// follows `rsi_rust(smoothing="simple")`; ATR reads the high, low and close columns
+    /// whatever `column` is. Tracking a `name` again replaces the previous indicator.
+    #[pyo3(signature = (name, indicator, period, column="close"))]
+    fn track<'py>(
+        &mut self,
+        py: Python<'py>,
+        name: String,
+        indicator: &str,
+        period: usize,
+        column: &str
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        telemetry::traced("OhlcvFrame.track", || {
+            let mut tracked = Tracked {
+                name,
+                indicator: Incremental::parse(indicator, period)?,
+                column: float_column(column)?,
+                values: Vec::with_capacity(self.len()),
+            };
+            tracked.extend(&self.data, self.start, self.end);
+            let values = PyArray1::from_slice_bound(py, &tracked.values);
+            self.tracked.retain(|t| t.name != tracked.name);
+            self.tracked.push(tracked);
+            Ok(values)
+        })
+    }
+
+    /// Current values of the tracked indicator `name`, one per row
+    fn indicator<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        match self.tracked.iter().find(|t| t.name == name) {
+            Some(tracked) => Ok(PyArray1::from_slice_bound(py, &tracked.values)),
+            None => Err(pyo3::exceptions::PyKeyError::new_err(name.to_string())),
+        }
+    }
+
+    /// Names of the tracked indicators
+    #[getter]
+    fn tracked(&self) -> Vec<String> {
+        self.tracked.iter().map(|t| t.name.clone()).collect()
+    }
+
+    /// Append new bars and extend every tracked indicator over them only, instead of
+    /// recomputing whole arrays, for bar-close research loops.
+    ///
+    /// Timestamps must not precede the last bar. The columns grow in place when nothing
+    /// else shares them; otherwise the frame moves to fresh storage, leaving slices and
+    /// column views taken earlier unchanged. Returns a dict of each tracked indicator's
+    /// values at the new bars.
+    #[allow(clippy::too_many_arguments)]
+    fn append_and_update<'py>(
+        &mut self,
+        py: Python<'py>,
+        timestamp: Column<'py, i64>,
+        open: Column<'py, f64>,
+        high: Column<'py, f64>,
+        low: Column<'py, f64>,
+        close: Column<'py, f64>,
+        volume: Column<'py, f64>
+    ) -> PyResult<Bound<'py, PyDict>> {
+        telemetry::traced("OhlcvFrame.append_and_update", || {
+            let timestamp = timestamp.values()?;
+            let (open, high, low, close, volume) = (open.values()?, high.values()?, low.values()?, close.values()?, volume.values()?);
+            let n = timestamp.len();
+            if [&open, &high, &low, &close, &volume].iter().any(|c| c.len() != n) {
+                return Err(crate::errors::LengthMismatchError::new_err(
+                    "All columns must have the same length"
+                ));
+            }
+            let last = (self.end > self.start).then(|| self.data.timestamp[self.end - 1]);
+            let mut previous = last.unwrap_or(i64::MIN);
+            for &t in timestamp.iter() {
+                if t < previous {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "Appended timestamps must not precede the last bar"
+                    ));
+                }
+                previous = t;
+            }
+
+            let (start, end) = (self.start, self.end);
+            if end != self.data.timestamp.len() || Arc::get_mut(&mut self.data).is_none() {
+                self.data = Arc::new(self.data.rows(start, end));
+                (self.start, self.end) = (0, end - start);
+            }
+            let data = Arc::get_mut(&mut self.data).expect("frame data is not shared");
+            let append = |c: &mut Array1<f64>, new: &[f64]| c.append(Axis(0), ArrayView1::from(new));
+            data.timestamp.append(Axis(0), ArrayView1::from(&timestamp[..])).expect("1-d append");
+            for (column, new) in [
+                (&mut data.open, &open),
+                (&mut data.high, &high),
+                (&mut data.low, &low),
+                (&mut data.close, &close),
+                (&mut data.volume, &volume),
+            ] {
+                append(column, new).expect("1-d append");
+            }
+            let (old_end, new_end) = (self.end, self.end + n);
+            self.end = new_end;
+
+            let result = PyDict::new_bound(py);
+            for tracked in self.tracked.iter_mut() {
+                let new = tracked.extend(&self.data, old_end, new_end);
+                result.set_item(&tracked.name, PyArray1::from_vec_bound(py, new))?;
+            }
+            Ok(result)
+        })
+    }
+}
@@ -0,0 +1,268 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::{Column, NumericColumn};
+
+/// When the continuous series moves from one contract to the next
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RollRule {
+    /// Once the next contract trades more volume
+    Volume,
+    /// Once the next contract has more open interest
+    OpenInterest,
+    /// `roll_offset` before the current contract's expiry
+    Calendar,
+}
+
+impl RollRule {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "volume" => Ok(RollRule::Volume),
+            "open_interest" | "oi" => Ok(RollRule::OpenInterest),
+            "calendar" => Ok(RollRule::Calendar),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown roll_rule '{}', expected 'volume', 'open_interest' or 'calendar'", name
+            ))),
+        }
+    }
+}
+
+/// How prices before a roll are shifted to remove the gap between contracts
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Adjustment {
+    None,
+    /// Add the roll gap (Panama canal), keeping point moves
+    Difference,
+    /// Scale by the price ratio at the roll, keeping returns
+    Ratio,
+}
+
+impl Adjustment {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Ok(Adjustment::None),
+            "difference" | "panama" => Ok(Adjustment::Difference),
+            "ratio" | "proportional" => Ok(Adjustment::Ratio),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown adjust '{}', expected 'none', 'difference' or 'ratio'", name
+            ))),
+        }
+    }
+}
+
+/// One contract's bars, timestamps strictly ascending
+struct Contract {
+    timestamps: Vec<i64>,
+    close: Vec<f64>,
+    volume: Option<Vec<f64>>,
+    open_interest: Option<Vec<f64>>,
+    /// Last tradable timestamp, from `expiries` or the contract's last bar
+    expiry: i64,
+}
+
+impl Contract {
+    fn parse(index: usize, data: &Bound<'_, PyAny>, expiry: Option<i64>) -> PyResult<Self> {
+        let py = data.py();
+        let item = |name: &str| -> PyResult<Option<Bound<'_, PyAny>>> {
+            match data.get_item(name) {
+                Ok(values) => Ok(Some(values)),
+                Err(err) if err.is_instance_of::<pyo3::exceptions::PyKeyError>(py) => Ok(None),
+                Err(err) => Err(err),
+            }
+        };
+        let required = |name: &str| {
+            item(name)?.ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!("contract {} needs a '{}' column", index, name))
+            })
+        };
+        let floats = |values: Bound<'_, PyAny>| -> PyResult<Vec<f64>> {
+            Ok(match values.extract::<NumericColumn>()? {
+                NumericColumn::Float(column) => column.values()?.into_owned(),
+                NumericColumn::Int(column) => column.values()?.iter().map(|&v| v as f64).collect(),
+            })
+        };
+        let timestamps = required("timestamp")?.extract::<Column<i64>>()?.values()?.into_owned();
+        let close = required("close")?.extract::<Column<f64>>()?.values()?.into_owned();
+        let volume = item("volume")?.map(floats).transpose()?;
+        let open_interest = item("open_interest")?.map(floats).transpose()?;
+        if close.len() != timestamps.len()
+            || volume.as_ref().is_some_and(|v| v.len() != timestamps.len())
+            || open_interest.as_ref().is_some_and(|v| v.len() != timestamps.len())
+        {
+            return Err(crate::errors::LengthMismatchError::new_err(format!(
+                "contract {} columns must have the same length", index
+            )));
+        }
+        if timestamps.is_empty() || timestamps.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "contract {} timestamps must be non-empty and strictly ascending", index
+            )));
+        }
+        let expiry = expiry.unwrap_or(timestamps[timestamps.len() - 1]);
+        Ok(Contract { timestamps, close, volume, open_interest, expiry })
+    }
+
+    /// Position of the bar at `t`, if the contract has one
+    fn bar(&self, t: i64) -> Option<usize> {
+        self.timestamps.binary_search(&t).ok()
+    }
+
+    /// Close of the last bar at or before `t`
+    fn close_asof(&self, t: i64) -> f64 {
+        match self.timestamps.partition_point(|&ts| ts <= t) {
+            0 => f64::NAN,
+            i => self.close[i - 1],
+        }
+    }
+
+    /// Volume or open interest at `t`, 0 without a bar or column
+    fn activity(&self, rule: RollRule, t: i64) -> f64 {
+        let column = match rule {
+            RollRule::OpenInterest => &self.open_interest,
+            _ => &self.volume,
+        };
+        match (column, self.bar(t)) {
+            (Some(values), Some(i)) => values[i],
+            _ => 0.0,
+        }
+    }
+}
+
+/// The continuous series' bars and the gaps at its rolls
+struct Continuous {
+    timestamps: Vec<i64>,
+    raw: Vec<f64>,
+    contracts: Vec<i64>,
+    /// Output index of the first bar after each roll, with the old and new contracts'
+    /// prices at the roll
+    rolls: Vec<(usize, f64, f64)>,
+}
+
+fn stitch(contracts: &[Contract], rule: RollRule, roll_offset: i64) -> Continuous {
+    let mut times: Vec<i64> = contracts.iter().flat_map(|c| c.timestamps.iter().copied()).collect();
+    times.sort_unstable();
+    times.dedup();
+
+    let mut out = Continuous { timestamps: Vec::new(), raw: Vec::new(), contracts: Vec::new(), rolls: Vec::new() };
+    let mut active = 0;
+    for t in times {
+        // Roll forward as long as the next contract trades now and the rule (or the
+        // current contract's expiry) says so
+        while active + 1 < contracts.len() {
+            let (current, next) = (&contracts[active], &contracts[active + 1]);
+            if next.bar(t).is_none() {
+                break;
+            }
+            let expired = t > current.timestamps[current.timestamps.len() - 1] || t > current.expiry;
+            let due = match rule {
+                RollRule::Calendar => t >= current.expiry.saturating_sub(roll_offset),
+                _ => next.activity(rule, t) > current.activity(rule, t),
+            };
+            if !(expired || due) {
+                break;
+            }
+            if !out.timestamps.is_empty() {
+                out.rolls.push((out.timestamps.len(), current.close_asof(t), next.close[next.bar(t).unwrap()]));
+            }
+            active += 1;
+        }
+        let contract = &contracts[active];
+        if let Some(i) = contract.bar(t) {
+            out.timestamps.push(t);
+            out.raw.push(contract.close[i]);
+            out.contracts.push(active as i64);
+        }
+    }
+    out
+}
+
+/// Shift every price before each roll by the gaps of the rolls after it
+fn back_adjust(series: &Continuous, adjust: Adjustment) -> Vec<f64> {
+    let mut adjusted = series.raw.clone();
+    let (mut offset, mut factor) = (0.0, 1.0);
+    let mut rolls = series.rolls.iter().rev().peekable();
+    for i in (0..adjusted.len()).rev() {
+        while let Some(&&(start, old, new)) = rolls.peek() {
+            if start <= i {
+                break;
+            }
+            offset += new - old;
+            factor *= new / old;
+            rolls.next();
+        }
+        match adjust {
+            Adjustment::None => {}
+            Adjustment::Difference => adjusted[i] += offset,
+            Adjustment::Ratio => adjusted[i] *= factor,
+        }
+    }
+    adjusted
+}
+
+/// Stitch individual futures contracts into one continuous series.
+///
+/// `contracts` is a list of mappings (dicts or DataFrames) in expiry order, each with
+/// `timestamp` (strictly ascending integers) and `close` columns and, for the matching roll
+/// rules, `volume` and `open_interest`. `roll_rule="volume"` (the default) moves to the
+/// next contract at the first bar where it trades more volume than the current one,
+/// "open_interest" likewise by open interest, and "calendar" at the first bar of the next
+/// contract at or after `roll_offset` (in timestamp units) before the current contract's
+/// expiry: `expiries[i]` when given, else the contract's last bar. Rolls only go forward,
+/// and always happen once the current contract has expired. `adjust="none"` keeps the
+/// raw prices; "difference" adds each roll's gap (new minus old contract price) to every
+/// earlier price, preserving point moves, and "ratio" multiplies them by the price ratio,
+/// preserving returns. Returns a dict with `timestamp`, the adjusted `close`,
+/// `raw_close` and the index of the active `contract`, one row per bar of the active
+/// contract.
+#[pyfunction]
+#[pyo3(signature = (contracts, roll_rule="volume", adjust="none", roll_offset=0, expiries=None))]
+pub fn build_continuous_rust<'py>(
+    py: Python<'py>,
+    contracts: Vec<Bound<'py, PyAny>>,
+    roll_rule: &str,
+    adjust: &str,
+    roll_offset: i64,
+    expiries: Option<Vec<i64>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let rule = RollRule::parse(roll_rule)?;
+    let adjust = Adjustment::parse(adjust)?;
+    if contracts.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "contracts must not be empty"
+        ));
+    }
+    if expiries.as_ref().is_some_and(|e| e.len() != contracts.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "expiries must have one value per contract"
+        ));
+    }
+    let contracts = contracts
+        .iter()
+        .enumerate()
+        .map(|(i, data)| Contract::parse(i, data, expiries.as_ref().map(|e| e[i])))
+        .collect::<PyResult<Vec<_>>>()?;
+    if rule != RollRule::Calendar {
+        let column = if rule == RollRule::Volume { "volume" } else { "open_interest" };
+        let missing = contracts.iter().position(|c| {
+            if rule == RollRule::Volume { c.volume.is_none() } else { c.open_interest.is_none() }
+        });
+        if let Some(i) = missing {
+            return Err(pyo3::exceptions::PyKeyError::new_err(format!(
+                "contract {} needs a '{}' column for roll_rule='{}'", i, column, roll_rule
+            )));
+        }
+    }
+
+    let (series, adjusted) = py.allow_threads(|| {
+        let series = stitch(&contracts, rule, roll_offset);
+        let adjusted = back_adjust(&series, adjust);
+        (series, adjusted)
+    });
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_vec_bound(py, series.timestamps))?;
+    result.set_item("close", PyArray1::from_vec_bound(py, adjusted))?;
+    result.set_item("raw_close", PyArray1::from_vec_bound(py, series.raw))?;
+    result.set_item("contract", PyArray1::from_vec_bound(py, series.contracts))?;
+    Ok(result)
+}
@@ -0,0 +1,233 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+
+use crate::column::Column;
+use crate::optimize;
+
+/// Observations of the exponentially weighted backcast of the initial variance
+const BACKCAST: usize = 75;
+/// Decay of the backcast weights
+const BACKCAST_DECAY: f64 = 0.94;
+
+/// Mean of the returns in the model
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mean {
+    /// A constant mean `mu`, estimated with the variance parameters
+    Constant,
+    /// Returns already have zero mean; `mu` is fixed at 0
+    Zero,
+}
+
+impl Mean {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "constant" => Ok(Mean::Constant),
+            "zero" => Ok(Mean::Zero),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown mean '{}', expected 'constant' or 'zero'", name
+            ))),
+        }
+    }
+}
+
+/// Initial variance, as in the `arch` package: an exponentially weighted mean of the first
+/// squared residuals
+fn backcast(residuals: impl Iterator<Item = f64>) -> f64 {
+    let (mut total, mut weights, mut w) = (0.0, 0.0, 1.0);
+    for e in residuals.take(BACKCAST) {
+        total += w * e * e;
+        weights += w;
+        w *= BACKCAST_DECAY;
+    }
+    total / weights
+}
+
+/// Conditional variances `sigma2[t]` of every return, followed by the one-step-ahead
+/// variance after the last
+fn variances(returns: &[f64], mu: f64, omega: f64, alpha: f64, beta: f64) -> Vec<f64> {
+    let mut sigma2 = Vec::with_capacity(returns.len() + 1);
+    let (mut variance, mut last_sq) = (backcast(returns.iter().map(|r| r - mu)), f64::NAN);
+    for (t, r) in returns.iter().enumerate() {
+        if t > 0 {
+            variance = omega + alpha * last_sq + beta * variance;
+        }
+        sigma2.push(variance);
+        last_sq = (r - mu) * (r - mu);
+    }
+    sigma2.push(omega + alpha * last_sq + beta * variance);
+    sigma2
+}
+
+/// Gaussian log-likelihood of the returns under the parameters, minus infinity outside
+/// the stationary region
+fn log_likelihood(returns: &[f64], mu: f64, omega: f64, alpha: f64, beta: f64) -> f64 {
+    if omega <= 0.0 || alpha < 0.0 || beta < 0.0 || alpha + beta >= 1.0 {
+        return f64::NEG_INFINITY;
+    }
+    let sigma2 = variances(returns, mu, omega, alpha, beta);
+    -0.5 * returns
+        .iter()
+        .zip(&sigma2)
+        .map(|(r, s)| (2.0 * std::f64::consts::PI).ln() + s.ln() + (r - mu) * (r - mu) / s)
+        .sum::<f64>()
+}
+
+/// Maximum-likelihood `[mu, omega, alpha, beta]` and the log-likelihood
+fn fit(returns: &[f64], mean: Mean, max_iter: usize) -> ([f64; 4], f64) {
+    let n = returns.len() as f64;
+    let mu0 = if mean == Mean::Constant { returns.iter().sum::<f64>() / n } else { 0.0 };
+    let variance = returns.iter().map(|r| (r - mu0) * (r - mu0)).sum::<f64>() / n;
+    // Rounding in the mean leaves constant returns a variance of a few ulps
+    if returns.len() < 3 || variance.is_nan() || variance <= f64::EPSILON * mu0 * mu0 {
+        return ([f64::NAN; 4], f64::NAN);
+    }
+
+    // The search runs over ln(omega), so omega stays positive and is on the returns' scale
+    let unpack = |x: &[f64]| match mean {
+        Mean::Constant => (x[0], x[1].exp(), x[2], x[3]),
+        Mean::Zero => (0.0, x[0].exp(), x[1], x[2]),
+    };
+    let objective = |x: &[f64]| {
+        let (mu, omega, alpha, beta) = unpack(x);
+        -log_likelihood(returns, mu, omega, alpha, beta)
+    };
+
+    // Start from the best of a small grid of persistences, as `arch` does
+    let start = [(0.05, 0.9), (0.1, 0.8), (0.1, 0.85), (0.2, 0.7), (0.05, 0.7)]
+        .into_iter()
+        .map(|(alpha, beta): (f64, f64)| {
+            let ln_omega = (variance * (1.0 - alpha - beta)).ln();
+            match mean {
+                Mean::Constant => vec![mu0, ln_omega, alpha, beta],
+                Mean::Zero => vec![ln_omega, alpha, beta],
+            }
+        })
+        .min_by(|a, b| objective(a).total_cmp(&objective(b)))
+        .unwrap();
+    let mut step = vec![0.5, 0.05, 0.05];
+    if mean == Mean::Constant {
+        step.insert(0, variance.sqrt() / 10.0);
+    }
+
+    // A restart from the first optimum keeps the simplex from stalling when it collapses early
+    let (x, _) = optimize::nelder_mead(objective, &start, &step, 1e-10, max_iter);
+    let (x, value) = optimize::nelder_mead(objective, &x, &step, 1e-12, max_iter);
+    let (mu, omega, alpha, beta) = unpack(&x);
+    ([mu, omega, alpha, beta], -value)
+}
+
+/// Fit a GARCH(1,1) model with normal innovations by maximum likelihood, like
+/// `arch.arch_model(returns, mean=..., vol="GARCH", p=1, q=1).fit()`.
+///
+/// The model is `r[t] = mu + e[t]` with `sigma2[t] = omega + alpha * e[t-1]**2 + beta *
+/// sigma2[t-1]`, the first variance backcast from the first 75 squared residuals as `arch`
+/// does. `mean="constant"` (the default) estimates `mu`, `"zero"` fixes it at 0. The
+/// likelihood is maximised by Nelder-Mead under `omega > 0`, `alpha, beta >= 0` and
+/// `alpha + beta < 1`, in up to `max_iter` iterations per pass. Returns `(params,
+/// log_likelihood, variances, next_var)` with `params = [mu, omega, alpha, beta]`, the
+/// conditional variance of every return and the variance forecast for the next one, which
+/// `garch_forecast_rust` takes as `last_var`. Series with NaNs, fewer than 3 values or no
+/// variance give NaN.
+#[pyfunction]
+#[pyo3(signature = (returns, mean="constant", max_iter=2000))]
+pub fn garch_fit_rust<'py>(
+    py: Python<'py>,
+    returns: Column<'py, f64>,
+    mean: &str,
+    max_iter: usize
+) -> PyResult<PyObject> {
+    let mean = Mean::parse(mean)?;
+    let returns = returns.values()?;
+    let (params, llf, mut sigma2) = py.allow_threads(|| {
+        let (params, llf) = fit(&returns, mean, max_iter);
+        let [mu, omega, alpha, beta] = params;
+        let sigma2 = if llf.is_nan() {
+            vec![f64::NAN; returns.len() + 1]
+        } else {
+            variances(&returns, mu, omega, alpha, beta)
+        };
+        (params, llf, sigma2)
+    });
+    let next_var = sigma2.pop().unwrap();
+    Ok((
+        PyArray1::from_vec_bound(py, params.to_vec()),
+        llf,
+        PyArray1::from_vec_bound(py, sigma2),
+        next_var,
+    )
+        .into_py(py))
+}
+
+/// Variance forecasts of a fitted GARCH(1,1) for the next `horizon` returns.
+///
+/// `params` is `[mu, omega, alpha, beta]` as returned by `garch_fit_rust` and `last_var`
+/// the one-step-ahead variance (its `next_var`); later steps follow `sigma2[h + 1] =
+/// omega + (alpha + beta) * sigma2[h]`, decaying towards the unconditional variance
+/// `omega / (1 - alpha - beta)`.
+#[pyfunction]
+pub fn garch_forecast_rust<'py>(
+    py: Python<'py>,
+    params: Vec<f64>,
+    last_var: f64,
+    horizon: usize
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let [_, omega, alpha, beta] = params[..] else {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "params must be [mu, omega, alpha, beta]"
+        ));
+    };
+    let forecasts: Vec<f64> = std::iter::successors(Some(last_var), |v| Some(omega + (alpha + beta) * v))
+        .take(horizon)
+        .collect();
+    Ok(PyArray1::from_vec_bound(py, forecasts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rng::SplitMix;
+
+    fn simulate(n: usize, omega: f64, alpha: f64, beta: f64) -> Vec<f64> {
+        let mut rng = SplitMix::new(3, 0);
+        let mut variance = omega / (1.0 - alpha - beta);
+        (0..n)
+            .map(|_| {
+                let r = variance.sqrt() * rng.normal_pair().0;
+                variance = omega + alpha * r * r + beta * variance;
+                r
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_recovers_simulated_parameters() {
+        let returns = simulate(5000, 0.1, 0.1, 0.8);
+        let ([mu, omega, alpha, beta], llf) = fit(&returns, Mean::Constant, 2000);
+        assert!(mu.abs() < 0.05, "{}", mu);
+        assert!((alpha - 0.1).abs() < 0.04, "{}", alpha);
+        assert!((beta - 0.8).abs() < 0.08, "{}", beta);
+        assert!((omega / (1.0 - alpha - beta) - 1.0).abs() < 0.2);
+        assert!(llf > log_likelihood(&returns, 0.0, 0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn variances_follow_the_recursion() {
+        let returns = [0.5, -1.0, 2.0];
+        let sigma2 = variances(&returns, 0.0, 0.1, 0.2, 0.7);
+        let first = backcast(returns.iter().copied());
+        assert_eq!(sigma2.len(), 4);
+        assert_eq!(sigma2[0], first);
+        assert!((sigma2[1] - (0.1 + 0.2 * 0.25 + 0.7 * first)).abs() < 1e-15);
+        assert!((sigma2[3] - (0.1 + 0.2 * 4.0 + 0.7 * sigma2[2])).abs() < 1e-15);
+        assert_eq!(backcast([2.0; 100].into_iter()), 4.0);
+        assert_eq!(log_likelihood(&returns, 0.0, 0.1, 0.5, 0.5), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn degenerate_returns_give_nan() {
+        assert!(fit(&[0.1; 50], Mean::Constant, 100).1.is_nan());
+        assert!(fit(&[0.1, -0.1], Mean::Zero, 100).1.is_nan());
+        assert!(fit(&[0.1, f64::NAN, 0.2, 0.3], Mean::Zero, 100).1.is_nan());
+    }
+}
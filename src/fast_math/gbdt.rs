@@ -0,0 +1,712 @@
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView1, ArrayView2, Axis};
+use rayon::prelude::*;
+use serde_json::Value;
+
+use crate::threads;
+
+fn invalid(message: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("Invalid model file: {}", message))
+}
+
+/// How a split sends a missing (NaN) feature value
+#[derive(Clone, Copy)]
+enum Missing {
+    /// NaN takes the default branch (XGBoost; LightGBM `missing_type=NaN`)
+    Nan,
+    /// Zero and NaN take the default branch (LightGBM `missing_type=Zero`)
+    Zero,
+    /// NaN is compared as 0 (LightGBM `missing_type=None`)
+    None,
+}
+
+struct Split {
+    feature: usize,
+    threshold: f64,
+    left: usize,
+    right: usize,
+    default_left: bool,
+    missing: Missing,
+    /// `x <= threshold` goes left (LightGBM) rather than `x < threshold` (XGBoost)
+    inclusive: bool,
+    /// Compare in single precision, as XGBoost does
+    float32: bool,
+}
+
+impl Split {
+    fn next(&self, value: f64) -> usize {
+        let default = if self.default_left { self.left } else { self.right };
+        let value = match self.missing {
+            Missing::Nan if value.is_nan() => return default,
+            Missing::Zero if value.is_nan() || value.abs() <= 1e-35 => return default,
+            Missing::None if value.is_nan() => 0.0,
+            _ => value,
+        };
+        let value = if self.float32 { value as f32 as f64 } else { value };
+        let left = if self.inclusive { value <= self.threshold } else { value < self.threshold };
+        if left { self.left } else { self.right }
+    }
+}
+
+enum Node {
+    Split(Split),
+    Leaf(f64),
+}
+
+struct Tree {
+    /// Node 0 is the root; children always come after their parent
+    nodes: Vec<Node>,
+    /// Which output (class) the tree adds to
+    output: usize,
+    /// Scale of the tree's leaves (DART drop weights), 1 otherwise
+    weight: f64,
+}
+
+impl Tree {
+    fn new(nodes: Vec<Node>, output: usize) -> PyResult<Self> {
+        if nodes.is_empty() {
+            return Err(invalid("empty tree"));
+        }
+        for (i, node) in nodes.iter().enumerate() {
+            if let Node::Split(split) = node {
+                // Children after their parent means every walk ends at a leaf
+                if [split.left, split.right].iter().any(|&c| c <= i || c >= nodes.len()) {
+                    return Err(invalid(format!("node {} has an invalid child", i)));
+                }
+            }
+        }
+        Ok(Tree { nodes, output, weight: 1.0 })
+    }
+
+    fn score(&self, row: ArrayView1<'_, f64>) -> f64 {
+        let mut i = 0;
+        loop {
+            match &self.nodes[i] {
+                Node::Leaf(value) => return *value,
+                Node::Split(split) => i = split.next(row[split.feature]),
+            }
+        }
+    }
+
+    fn max_feature(&self) -> Option<usize> {
+        self.nodes
+            .iter()
+            .filter_map(|n| match n {
+                Node::Split(split) => Some(split.feature),
+                Node::Leaf(_) => None,
+            })
+            .max()
+    }
+}
+
+/// Map from summed margins to predictions
+#[derive(Clone, Copy)]
+enum Transform {
+    Identity,
+    /// `1 / (1 + exp(-scale * margin))`
+    Sigmoid(f64),
+    Softmax,
+    Exp,
+}
+
+impl Transform {
+    fn apply(self, margins: &mut [f64]) {
+        match self {
+            Transform::Identity => {}
+            Transform::Sigmoid(scale) => margins.iter_mut().for_each(|m| *m = 1.0 / (1.0 + (-scale * *m).exp())),
+            Transform::Exp => margins.iter_mut().for_each(|m| *m = m.exp()),
+            Transform::Softmax => {
+                let max = margins.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                margins.iter_mut().for_each(|m| *m = (*m - max).exp());
+                let total: f64 = margins.iter().sum();
+                margins.iter_mut().for_each(|m| *m /= total);
+            }
+        }
+    }
+}
+
+/// A gradient-boosted tree ensemble loaded by `load_model_rust`
+#[pyclass(module = "fast_math")]
+pub struct TreeModel {
+    trees: Vec<Tree>,
+    /// Starting margin of each output
+    base: Vec<f64>,
+    transform: Transform,
+    /// The tree sums are divided by this (LightGBM random forests average their trees)
+    divisor: f64,
+    n_features: usize,
+    /// Whether `n_features` was declared by the model, so inputs must match it exactly
+    declared: bool,
+    format: &'static str,
+    objective: String,
+}
+
+impl TreeModel {
+    fn new(
+        trees: Vec<Tree>,
+        base: Vec<f64>,
+        transform: Transform,
+        declared: Option<usize>,
+        format: &'static str,
+        objective: String
+    ) -> PyResult<Self> {
+        if let Some(tree) = trees.iter().find(|t| t.output >= base.len()) {
+            return Err(invalid(format!("tree for output {} of {}", tree.output, base.len())));
+        }
+        let used = trees.iter().filter_map(Tree::max_feature).max().map_or(0, |f| f + 1);
+        if declared.is_some_and(|n| used > n) {
+            return Err(invalid(format!("splits on feature {} of {}", used - 1, declared.unwrap_or(0))));
+        }
+        Ok(TreeModel {
+            trees,
+            base,
+            transform,
+            divisor: 1.0,
+            n_features: declared.unwrap_or(used),
+            declared: declared.is_some(),
+            format,
+            objective,
+        })
+    }
+
+    fn predict_row(&self, row: ArrayView1<'_, f64>, output_margin: bool) -> Vec<f64> {
+        let mut sums = vec![0.0; self.base.len()];
+        for tree in &self.trees {
+            sums[tree.output] += tree.weight * tree.score(row);
+        }
+        let mut margins: Vec<f64> = self.base.iter().zip(&sums).map(|(b, s)| b + s / self.divisor).collect();
+        if !output_margin {
+            self.transform.apply(&mut margins);
+        }
+        margins
+    }
+
+    fn predict_matrix(&self, x: ArrayView2<'_, f64>, output_margin: bool) -> PyResult<Array2<f64>> {
+        let columns = x.ncols();
+        if columns < self.n_features || (self.declared && columns != self.n_features) {
+            return Err(crate::errors::LengthMismatchError::new_err(format!(
+                "features_2d has {} columns but the model expects {}", columns, self.n_features
+            )));
+        }
+        let rows: Vec<Vec<f64>> = threads::install(|| {
+            x.axis_iter(Axis(0)).into_par_iter().map(|row| self.predict_row(row, output_margin)).collect()
+        });
+        let outputs = self.base.len();
+        Ok(Array2::from_shape_fn((rows.len(), outputs), |(i, j)| rows[i][j]))
+    }
+}
+
+#[pymethods]
+impl TreeModel {
+    #[getter]
+    fn n_trees(&self) -> usize {
+        self.trees.len()
+    }
+
+    #[getter]
+    fn n_features(&self) -> usize {
+        self.n_features
+    }
+
+    /// Number of outputs per row: the classes of a multi-class model, 1 otherwise
+    #[getter]
+    fn n_outputs(&self) -> usize {
+        self.base.len()
+    }
+
+    /// The training objective, as named in the model file
+    #[getter]
+    fn objective(&self) -> &str {
+        &self.objective
+    }
+
+    /// Scores for the rows of `features_2d`; the same as `predict_rust(model, ...)`
+    #[pyo3(signature = (features_2d, output_margin=false))]
+    fn predict<'py>(&self, py: Python<'py>, features_2d: PyReadonlyArray2<'py, f64>, output_margin: bool) -> PyResult<PyObject> {
+        predict(py, self, features_2d, output_margin)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TreeModel(format='{}', objective='{}', n_trees={}, n_features={}, n_outputs={})",
+            self.format, self.objective, self.trees.len(), self.n_features, self.base.len()
+        )
+    }
+}
+
+/// Parse an XGBoost base score, a number or (XGBoost 3) a bracketed list in a string
+fn base_scores(value: &Value) -> PyResult<Vec<f64>> {
+    if let Some(v) = value.as_f64() {
+        return Ok(vec![v]);
+    }
+    let text = value.as_str().ok_or_else(|| invalid("base_score is not a number"))?;
+    text.trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .map(|v| v.trim().parse::<f64>().map_err(|_| invalid(format!("base_score '{}'", text))))
+        .collect()
+}
+
+/// A model parameter XGBoost writes as a number in a string
+fn count(value: &Value) -> usize {
+    value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok())).unwrap_or(0) as usize
+}
+
+fn array<'a>(tree: &'a Value, key: &str) -> PyResult<&'a Vec<Value>> {
+    tree[key].as_array().ok_or_else(|| invalid(format!("tree without '{}'", key)))
+}
+
+/// A tree of XGBoost's JSON model (`save_model("model.json")`), stored as parallel arrays
+fn xgboost_tree(tree: &Value, output: usize) -> PyResult<Tree> {
+    let left = array(tree, "left_children")?;
+    let right = array(tree, "right_children")?;
+    let features = array(tree, "split_indices")?;
+    let conditions = array(tree, "split_conditions")?;
+    let default_left = array(tree, "default_left")?;
+    let n = left.len();
+    if [right.len(), features.len(), conditions.len(), default_left.len()].iter().any(|&l| l != n) {
+        return Err(invalid("tree arrays of different lengths"));
+    }
+    if tree["split_type"].as_array().is_some_and(|types| types.iter().any(|t| t.as_u64() != Some(0))) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Categorical splits are not supported"
+        ));
+    }
+    let child = |v: &Value| v.as_i64().ok_or_else(|| invalid("non-integer child"));
+    let nodes = (0..n)
+        .map(|i| {
+            let condition = conditions[i].as_f64().ok_or_else(|| invalid("non-numeric split condition"))?;
+            if child(&left[i])? < 0 {
+                return Ok(Node::Leaf(condition));
+            }
+            Ok(Node::Split(Split {
+                feature: features[i].as_u64().ok_or_else(|| invalid("non-integer split index"))? as usize,
+                threshold: condition as f32 as f64,
+                left: child(&left[i])? as usize,
+                right: child(&right[i])?.max(0) as usize,
+                default_left: default_left[i].as_bool().unwrap_or(default_left[i].as_u64() == Some(1)),
+                missing: Missing::Nan,
+                inclusive: false,
+                float32: true,
+            }))
+        })
+        .collect::<PyResult<Vec<Node>>>()?;
+    Tree::new(nodes, output)
+}
+
+fn xgboost_model(root: &Value) -> PyResult<TreeModel> {
+    let learner = &root["learner"];
+    let param = &learner["learner_model_param"];
+    let outputs = count(&param["num_class"]).max(count(&param["num_target"])).max(1);
+    let objective = learner["objective"]["name"].as_str().unwrap_or("reg:squarederror").to_string();
+    let booster = &learner["gradient_booster"];
+    let (model, drop_weights) = match booster["name"].as_str() {
+        Some("gbtree") => (&booster["model"], None),
+        Some("dart") => (&booster["gbtree"]["model"], booster["weight_drop"].as_array()),
+        Some(name) => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported XGBoost booster '{}', expected 'gbtree' or 'dart'", name
+            )))
+        }
+        None => return Err(invalid("no gradient_booster")),
+    };
+    let tree_info = array(model, "tree_info")?;
+    let mut trees = array(model, "trees")?
+        .iter()
+        .zip(tree_info)
+        .map(|(tree, output)| xgboost_tree(tree, count(output)))
+        .collect::<PyResult<Vec<Tree>>>()?;
+    if let Some(weights) = drop_weights {
+        for (tree, weight) in trees.iter_mut().zip(weights) {
+            tree.weight = weight.as_f64().unwrap_or(1.0);
+        }
+    }
+    // The base score is saved on the prediction scale; the links are XGBoost's ProbToMargin
+    let (transform, link): (Transform, fn(f64) -> f64) = match objective.as_str() {
+        "binary:logistic" | "reg:logistic" => (Transform::Sigmoid(1.0), |p| -(1.0 / p - 1.0).ln()),
+        "binary:logitraw" => (Transform::Identity, |p| -(1.0 / p - 1.0).ln()),
+        "multi:softprob" | "multi:softmax" => (Transform::Softmax, |p| p),
+        "count:poisson" | "reg:gamma" | "reg:tweedie" | "survival:cox" | "survival:aft" => (Transform::Exp, f64::ln),
+        _ => (Transform::Identity, |p| p),
+    };
+    let mut base: Vec<f64> = base_scores(&param["base_score"])?.into_iter().map(link).collect();
+    if base.len() == 1 {
+        base = vec![base[0]; outputs];
+    }
+    if base.len() != outputs {
+        return Err(invalid(format!("{} base scores for {} outputs", base.len(), outputs)));
+    }
+    let declared = Some(count(&param["num_feature"])).filter(|&n| n > 0);
+    TreeModel::new(trees, base, transform, declared, "xgboost", objective)
+}
+
+/// Feature index of an XGBoost dump's `fN` feature name
+fn dump_feature(name: &str) -> PyResult<usize> {
+    name.strip_prefix('f').and_then(|i| i.parse().ok()).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Dump splits on named feature '{}'; dump without feature names or save the model as JSON", name
+        ))
+    })
+}
+
+/// Nodes indexed by their id, which must run from 0 without gaps
+fn dense(nodes: Vec<Option<Node>>) -> PyResult<Vec<Node>> {
+    nodes.into_iter().enumerate().map(|(i, n)| n.ok_or_else(|| invalid(format!("tree has no node {}", i)))).collect()
+}
+
+fn place(nodes: &mut Vec<Option<Node>>, id: usize, node: Node) {
+    if nodes.len() <= id {
+        nodes.resize_with(id + 1, || None);
+    }
+    nodes[id] = Some(node);
+}
+
+/// Split of an XGBoost dump: `feature < condition`, NaN going to `missing`
+fn dump_split(feature: usize, condition: f64, yes: usize, no: usize, missing: usize) -> Node {
+    Node::Split(Split {
+        feature,
+        threshold: condition as f32 as f64,
+        left: yes,
+        right: no,
+        default_left: missing == yes,
+        missing: Missing::Nan,
+        inclusive: false,
+        float32: true,
+    })
+}
+
+/// Nodes of one tree of an XGBoost JSON dump (`dump_model(path, dump_format="json")`)
+fn xgboost_dump_node(node: &Value, nodes: &mut Vec<Option<Node>>) -> PyResult<()> {
+    let id = node["nodeid"].as_u64().ok_or_else(|| invalid("node without nodeid"))? as usize;
+    if let Some(leaf) = node["leaf"].as_f64() {
+        place(nodes, id, Node::Leaf(leaf));
+        return Ok(());
+    }
+    let field = |key: &str| node[key].as_u64().map(|v| v as usize).ok_or_else(|| invalid(format!("node {} without '{}'", id, key)));
+    let feature = dump_feature(node["split"].as_str().ok_or_else(|| invalid("node without split"))?)?;
+    let condition = node["split_condition"].as_f64().ok_or_else(|| invalid("node without split_condition"))?;
+    place(nodes, id, dump_split(feature, condition, field("yes")?, field("no")?, field("missing")?));
+    for child in node["children"].as_array().ok_or_else(|| invalid(format!("node {} without children", id)))? {
+        xgboost_dump_node(child, nodes)?;
+    }
+    Ok(())
+}
+
+fn xgboost_json_dump(trees: &[Value]) -> PyResult<TreeModel> {
+    let trees = trees
+        .iter()
+        .map(|tree| {
+            let mut nodes = Vec::new();
+            xgboost_dump_node(tree, &mut nodes)?;
+            Tree::new(dense(nodes)?, 0)
+        })
+        .collect::<PyResult<Vec<Tree>>>()?;
+    TreeModel::new(trees, vec![0.0], Transform::Identity, None, "xgboost-dump", String::new())
+}
+
+/// One node line of an XGBoost text dump: `0:[f2<2.45] yes=1,no=2,missing=1` or `1:leaf=0.4`,
+/// either possibly followed by `,gain=...,cover=...`
+fn xgboost_text_node(line: &str) -> PyResult<(usize, Node)> {
+    let bad = || invalid(format!("line '{}'", line));
+    let (id, rest) = line.split_once(':').ok_or_else(bad)?;
+    let id = id.parse().map_err(|_| bad())?;
+    if let Some(leaf) = rest.strip_prefix("leaf=") {
+        let value = leaf.split(',').next().unwrap_or("").parse().map_err(|_| bad())?;
+        return Ok((id, Node::Leaf(value)));
+    }
+    let (split, fields) = rest.strip_prefix('[').and_then(|r| r.split_once(']')).ok_or_else(bad)?;
+    let (feature, condition) = split.split_once('<').ok_or_else(bad)?;
+    let fields: HashMap<&str, &str> = fields.trim().split(',').filter_map(|f| f.split_once('=')).collect();
+    let field = |key: &str| fields.get(key).and_then(|v| v.parse().ok()).ok_or_else(bad);
+    let condition = condition.parse().map_err(|_| bad())?;
+    Ok((id, dump_split(dump_feature(feature)?, condition, field("yes")?, field("no")?, field("missing")?)))
+}
+
+/// XGBoost text dump (`dump_model("dump.txt")`): a `booster[i]:` line, then one tab-indented
+/// line per node
+fn xgboost_text_dump(text: &str) -> PyResult<TreeModel> {
+    let mut tree_nodes: Vec<Vec<Option<Node>>> = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if line.starts_with("booster[") {
+            tree_nodes.push(Vec::new());
+            continue;
+        }
+        let nodes = tree_nodes.last_mut().ok_or_else(|| invalid("node before the first booster"))?;
+        let (id, node) = xgboost_text_node(line)?;
+        place(nodes, id, node);
+    }
+    let trees = tree_nodes.into_iter().map(|nodes| Tree::new(dense(nodes)?, 0)).collect::<PyResult<Vec<Tree>>>()?;
+    TreeModel::new(trees, vec![0.0], Transform::Identity, None, "xgboost-dump", String::new())
+}
+
+fn numbers<T: std::str::FromStr>(block: &HashMap<&str, &str>, key: &str) -> PyResult<Vec<T>> {
+    let text = block.get(key).ok_or_else(|| invalid(format!("tree without '{}'", key)))?;
+    text.split_whitespace().map(|v| v.parse().map_err(|_| invalid(format!("{} value '{}'", key, v)))).collect()
+}
+
+/// A tree of a LightGBM model file: splits `0..num_leaves - 1` by arrays, children below
+/// zero being leaves `-child - 1`, which are placed after the splits
+fn lightgbm_tree(block: &HashMap<&str, &str>, output: usize) -> PyResult<Tree> {
+    let leaves: Vec<f64> = numbers(block, "leaf_value")?;
+    if leaves.len() == 1 {
+        return Tree::new(vec![Node::Leaf(leaves[0])], output);
+    }
+    let features: Vec<usize> = numbers(block, "split_feature")?;
+    let thresholds: Vec<f64> = numbers(block, "threshold")?;
+    let decisions: Vec<u8> = numbers(block, "decision_type")?;
+    let left: Vec<i64> = numbers(block, "left_child")?;
+    let right: Vec<i64> = numbers(block, "right_child")?;
+    let splits = leaves.len() - 1;
+    if [features.len(), thresholds.len(), decisions.len(), left.len(), right.len()].iter().any(|&l| l != splits) {
+        return Err(invalid("tree arrays of the wrong length"));
+    }
+    let child = |c: i64| if c >= 0 { c as usize } else { splits + (-c - 1) as usize };
+    let mut nodes = Vec::with_capacity(2 * splits + 1);
+    for i in 0..splits {
+        let decision = decisions[i];
+        if decision & 1 != 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Categorical splits are not supported"
+            ));
+        }
+        nodes.push(Node::Split(Split {
+            feature: features[i],
+            threshold: thresholds[i],
+            left: child(left[i]),
+            right: child(right[i]),
+            default_left: decision & 2 != 0,
+            missing: match (decision >> 2) & 3 {
+                0 => Missing::None,
+                1 => Missing::Zero,
+                _ => Missing::Nan,
+            },
+            inclusive: true,
+            float32: false,
+        }));
+    }
+    nodes.extend(leaves.into_iter().map(Node::Leaf));
+    Tree::new(nodes, output)
+}
+
+/// LightGBM model file (`save_model("model.txt")`): `key=value` header lines, then a
+/// `Tree=i` block per tree up to `end of trees`
+fn lightgbm_model(text: &str) -> PyResult<TreeModel> {
+    let mut header: HashMap<&str, &str> = HashMap::new();
+    let mut blocks: Vec<HashMap<&str, &str>> = Vec::new();
+    let mut average = false;
+    for line in text.lines().map(str::trim) {
+        if line == "end of trees" {
+            break;
+        }
+        if line.starts_with("Tree=") {
+            blocks.push(HashMap::new());
+        } else if let Some((key, value)) = line.split_once('=') {
+            blocks.last_mut().unwrap_or(&mut header).insert(key, value);
+        } else if line == "average_output" {
+            average = true;
+        }
+    }
+    let per_iteration: usize = header
+        .get("num_tree_per_iteration")
+        .or(header.get("num_class"))
+        .map_or(Ok(1), |v| v.parse().map_err(|_| invalid("num_tree_per_iteration")))?;
+    let per_iteration = per_iteration.max(1);
+    let trees = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| lightgbm_tree(block, i % per_iteration))
+        .collect::<PyResult<Vec<Tree>>>()?;
+    let objective = header.get("objective").copied().unwrap_or("regression").to_string();
+    let mut words = objective.split_whitespace();
+    let name = words.next().unwrap_or("");
+    let sigmoid = words.find_map(|w| w.strip_prefix("sigmoid:")).and_then(|s| s.parse().ok()).unwrap_or(1.0);
+    let transform = match name {
+        "binary" | "multiclassova" | "multiclass_ova" | "ova" | "ovr" => Transform::Sigmoid(sigmoid),
+        "cross_entropy" | "xentropy" => Transform::Sigmoid(1.0),
+        "multiclass" | "softmax" => Transform::Softmax,
+        "poisson" | "gamma" | "tweedie" => Transform::Exp,
+        _ => Transform::Identity,
+    };
+    let declared = header.get("max_feature_idx").and_then(|v| v.parse::<usize>().ok()).map(|i| i + 1);
+    let iterations = trees.len().div_ceil(per_iteration).max(1);
+    let mut model = TreeModel::new(trees, vec![0.0; per_iteration], transform, declared, "lightgbm", objective)?;
+    if average {
+        model.divisor = iterations as f64;
+    }
+    Ok(model)
+}
+
+fn parse(text: &str) -> PyResult<TreeModel> {
+    let body = text.trim_start();
+    let json = || serde_json::from_str::<Value>(body).map_err(invalid);
+    if body.starts_with('{') {
+        xgboost_model(&json()?)
+    } else if body.starts_with('[') {
+        match json()? {
+            Value::Array(trees) => xgboost_json_dump(&trees),
+            _ => Err(invalid("expected a list of trees")),
+        }
+    } else if body.starts_with("booster[") {
+        xgboost_text_dump(body)
+    } else if body.lines().next().is_some_and(|l| l.trim() == "tree") {
+        lightgbm_model(body)
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(
+            "Unrecognised model file, expected an XGBoost JSON model or dump, an XGBoost text dump or a LightGBM model"
+        ))
+    }
+}
+
+/// Load a gradient-boosted tree model exported by XGBoost or LightGBM, for native scoring.
+///
+/// The format is detected from the file: an XGBoost JSON model (`save_model("model.json")`,
+/// gbtree or dart), an XGBoost dump (`dump_model`, text or `dump_format="json"`, with
+/// features named `f0`, `f1`, ...) or a LightGBM model file (`save_model("model.txt")`).
+/// Full models carry their objective, base score and classes, so predictions match
+/// `predict()` of the library (probabilities for logistic and softmax objectives,
+/// `exp` of the margin for Poisson, gamma and Tweedie); dumps hold only the trees and
+/// score the sum of their leaves, without the base score. Missing values follow each
+/// split's default direction. Categorical splits are not supported. Returns a
+/// `TreeModel`, scored by `predict_rust` or its `predict` method.
+#[pyfunction]
+pub fn load_model_rust(py: Python<'_>, path: &str) -> PyResult<TreeModel> {
+    let text = std::fs::read_to_string(path)?;
+    py.allow_threads(|| parse(&text))
+}
+
+fn predict(py: Python<'_>, model: &TreeModel, features_2d: PyReadonlyArray2<'_, f64>, output_margin: bool) -> PyResult<PyObject> {
+    let x = features_2d.as_array();
+    let scores = py.allow_threads(|| model.predict_matrix(x, output_margin))?;
+    if scores.ncols() == 1 {
+        Ok(PyArray1::from_iter_bound(py, scores).into_any().unbind())
+    } else {
+        Ok(PyArray2::from_owned_array_bound(py, scores).into_any().unbind())
+    }
+}
+
+/// Score the rows of a feature matrix with a model from `load_model_rust`.
+///
+/// `features_2d` holds one row per sample, with the model's features as columns in
+/// training order; NaN marks a missing value. Rows are scored in parallel with the GIL
+/// released. Returns one prediction per row, or a `(rows, classes)` array of class
+/// probabilities for multi-class models; with `output_margin=True` the raw margins, the
+/// base score plus the sum of the trees, before the objective's link function.
+#[pyfunction]
+#[pyo3(signature = (model, features_2d, output_margin=false))]
+pub fn predict_rust<'py>(
+    py: Python<'py>,
+    model: PyRef<'py, TreeModel>,
+    features_2d: PyReadonlyArray2<'py, f64>,
+    output_margin: bool
+) -> PyResult<PyObject> {
+    predict(py, &model, features_2d, output_margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    /// Node 0 splits on f0 < 0.5 (NaN left), node 1 on f1 < 0.1 (NaN right)
+    const XGBOOST: &str = r#"{"learner": {
+        "learner_model_param": {"base_score": "5E-1", "num_class": "0", "num_feature": "2"},
+        "objective": {"name": "reg:squarederror"},
+        "gradient_booster": {"name": "gbtree", "model": {"tree_info": [0], "trees": [{
+            "left_children": [1, 3, -1, -1, -1],
+            "right_children": [2, 4, -1, -1, -1],
+            "split_indices": [0, 1, 0, 0, 0],
+            "split_conditions": [0.5, 0.1, 3.0, 1.0, 2.0],
+            "default_left": [1, 0, 0, 0, 0]
+        }]}}
+    }}"#;
+
+    /// Split 0 on f0 <= 0.5 (missing None, default left), split 1 on f1 <= 1.0 (missing
+    /// Zero, default right), split 2 on f1 <= 2.0 (missing NaN, default left)
+    const LIGHTGBM: &str = "tree
+version=v4
+num_class=1
+num_tree_per_iteration=1
+max_feature_idx=1
+objective=regression
+
+Tree=0
+num_leaves=4
+split_feature=0 1 1
+threshold=0.5 1.0 2.0
+decision_type=2 4 10
+left_child=1 -1 -3
+right_child=2 -2 -4
+leaf_value=10 20 30 40
+shrinkage=1
+
+end of trees
+";
+
+    fn score(model: &TreeModel, row: [f64; 2]) -> f64 {
+        model.predict_row(ndarray::aview1(&row), false)[0]
+    }
+
+    #[test]
+    fn xgboost_splits_are_strict_float32_and_send_nan_the_default_way() {
+        pyo3::prepare_freethreaded_python();
+        let model = parse(XGBOOST).unwrap();
+        assert_eq!((model.n_features, model.format), (2, "xgboost"));
+        assert_eq!(score(&model, [0.2, 0.05]), 1.5);
+        // 0.5 is not < 0.5
+        assert_eq!(score(&model, [0.5, 0.05]), 3.5);
+        // 0.1 rounds to the float32 threshold, so it is not below it either
+        assert_eq!(score(&model, [0.2, 0.1]), 2.5);
+        assert_eq!(score(&model, [0.2, 0.099]), 1.5);
+        assert_eq!(score(&model, [f64::NAN, 0.0]), 1.5);
+        assert_eq!(score(&model, [0.2, f64::NAN]), 2.5);
+    }
+
+    #[test]
+    fn lightgbm_splits_are_inclusive_and_follow_the_missing_type() {
+        pyo3::prepare_freethreaded_python();
+        let model = parse(LIGHTGBM).unwrap();
+        assert_eq!((model.n_features, model.format), (2, "lightgbm"));
+        assert_eq!(score(&model, [0.5, 1.0]), 10.0);
+        assert_eq!(score(&model, [0.2, -0.5]), 10.0);
+        // Missing None: NaN is compared as 0
+        assert_eq!(score(&model, [f64::NAN, 5.0]), 20.0);
+        // Missing Zero: zero and NaN take the default (right) branch
+        assert_eq!(score(&model, [0.2, 0.0]), 20.0);
+        assert_eq!(score(&model, [0.2, f64::NAN]), 20.0);
+        // Missing NaN: only NaN takes the default (left) branch
+        assert_eq!(score(&model, [0.7, f64::NAN]), 30.0);
+        assert_eq!(score(&model, [0.7, 0.0]), 30.0);
+        assert_eq!(score(&model, [0.7, 2.5]), 40.0);
+
+        let x = array![[0.5, 1.0], [0.7, 2.5]];
+        assert_eq!(model.predict_matrix(x.view(), false).unwrap().column(0).to_vec(), vec![10.0, 40.0]);
+        // The model declares two features
+        assert!(model.predict_matrix(array![[0.5, 1.0, 0.0]].view(), false).is_err());
+    }
+
+    fn split(left: usize, right: usize) -> Node {
+        Node::Split(Split {
+            feature: 0,
+            threshold: 0.0,
+            left,
+            right,
+            default_left: true,
+            missing: Missing::Nan,
+            inclusive: false,
+            float32: false,
+        })
+    }
+
+    #[test]
+    fn children_must_follow_their_parent() {
+        pyo3::prepare_freethreaded_python();
+        assert!(Tree::new(vec![split(1, 2), Node::Leaf(1.0), Node::Leaf(2.0)], 0).is_ok());
+        // A child before its parent or the parent itself could loop forever
+        assert!(Tree::new(vec![split(1, 2), split(0, 2), Node::Leaf(2.0)], 0).is_err());
+        assert!(Tree::new(vec![split(0, 1), Node::Leaf(1.0)], 0).is_err());
+        assert!(Tree::new(vec![split(1, 3), Node::Leaf(1.0), Node::Leaf(2.0)], 0).is_err());
+        assert!(Tree::new(Vec::new(), 0).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+// Optional GPU backend for the 2D batch kernels, built with `--features gpu` (wgpu, so it
+// runs on Vulkan, Metal or DX12). Kernels compute in float32 and are only used when an
+// adapter is found, the inputs fit the device's buffer limits and contain no NaN; every
+// other case returns `None` and the caller falls back to the CPU path.
+
+use pyo3::prelude::*;
+use numpy::PyArray2;
+use ndarray::{Array2, ArrayView2};
+
+use crate::column::{FloatElement, Series, SeriesInput};
+
+/// Where a batch computation should run
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Cpu,
+    Gpu,
+}
+
+impl Device {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(Device::Cpu),
+            "gpu" | "cuda" => Ok(Device::Gpu),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown device '{}', expected 'cpu' or 'gpu'", other
+            ))),
+        }
+    }
+}
+
+/// Parameters passed to every shader as a uniform
+type Params = [u32; 4];
+
+const MOVING_AVERAGE: &str = r#"
+struct Params { rows: u32, cols: u32, window: u32, unused: u32 }
+@group(0) @binding(0) var<storage, read> input: array<f32>;
+@group(0) @binding(1) var<storage, read_write> output: array<f32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_cols = params.cols - params.window + 1u;
+    if (id.x >= out_cols || id.y >= params.rows) { return; }
+    let base = id.y * params.cols + id.x;
+    var sum = 0.0;
+    for (var i = 0u; i < params.window; i++) { sum += input[base + i]; }
+    output[id.y * out_cols + id.x] = sum / f32(params.window);
+}
+"#;
+
+const ROLLING_STD: &str = r#"
+struct Params { rows: u32, cols: u32, window: u32, ddof: u32 }
+@group(0) @binding(0) var<storage, read> input: array<f32>;
+@group(0) @binding(1) var<storage, read_write> output: array<f32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_cols = params.cols - params.window + 1u;
+    if (id.x >= out_cols || id.y >= params.rows) { return; }
+    let base = id.y * params.cols + id.x;
+    var sum = 0.0;
+    for (var i = 0u; i < params.window; i++) { sum += input[base + i]; }
+    let mean = sum / f32(params.window);
+    var sum_sq = 0.0;
+    for (var i = 0u; i < params.window; i++) {
+        let d = input[base + i] - mean;
+        sum_sq += d * d;
+    }
+    output[id.y * out_cols + id.x] = sqrt(sum_sq / f32(params.window - params.ddof));
+}
+"#;
+
+const GRAM: &str = r#"
+struct Params { rows: u32, cols: u32, unused: u32, unused2: u32 }
+@group(0) @binding(0) var<storage, read> input: array<f32>;
+@group(0) @binding(1) var<storage, read_write> output: array<f32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.rows || id.y >= params.rows || id.x < id.y) { return; }
+    var dot = 0.0;
+    for (var k = 0u; k < params.cols; k++) {
+        dot += input[id.y * params.cols + k] * input[id.x * params.cols + k];
+    }
+    output[id.y * params.rows + id.x] = dot;
+}
+"#;
+
+#[cfg(feature = "gpu")]
+mod backend {
+    use std::sync::OnceLock;
+
+    use wgpu::util::DeviceExt;
+
+    use super::Params;
+
+    struct Context {
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+    }
+
+    /// The first high-performance adapter, initialised on first use
+    fn context() -> Option<&'static Context> {
+        static CONTEXT: OnceLock<Option<Context>> = OnceLock::new();
+        CONTEXT
+            .get_or_init(|| {
+                pollster::block_on(async {
+                    let instance = wgpu::Instance::default();
+                    let adapter = instance
+                        .request_adapter(&wgpu::RequestAdapterOptions {
+                            power_preference: wgpu::PowerPreference::HighPerformance,
+                            ..Default::default()
+                        })
+                        .await?;
+                    let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor::default(), None).await.ok()?;
+                    Some(Context { device, queue })
+                })
+            })
+            .as_ref()
+    }
+
+    pub fn available() -> bool {
+        context().is_some()
+    }
+
+    pub fn run(source: &str, input: &[f32], params: Params, output_len: usize, workgroups: (u32, u32)) -> Option<Vec<f32>> {
+        let Context { device, queue } = context()?;
+        let limits = device.limits();
+        let too_large = 4 * input.len().max(output_len) > limits.max_storage_buffer_binding_size as usize
+            || workgroups.0.max(workgroups.1) > limits.max_compute_workgroups_per_dimension;
+        if input.is_empty() || output_len == 0 || too_large {
+            return None;
+        }
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            compilation_options: Default::default(),
+        });
+        let input = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(input),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let size = (4 * output_len) as u64;
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: input.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: output.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: params.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, size);
+        queue.submit(Some(encoder.finish()));
+        if pollster::block_on(device.pop_error_scope()).is_some() {
+            return None;
+        }
+
+        let slice = staging.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        rx.recv().ok()?.ok()?;
+        let result = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        Some(result)
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+mod backend {
+    use super::Params;
+
+    pub fn available() -> bool {
+        false
+    }
+
+    pub fn run(_source: &str, _input: &[f32], _params: Params, _output_len: usize, _workgroups: (u32, u32)) -> Option<Vec<f32>> {
+        None
+    }
+}
+
+/// Run a rolling-window shader over every full window of each row of `batch`
+fn rolling(source: &str, batch: ArrayView2<'_, f32>, window: usize, extra: u32) -> Option<Array2<f32>> {
+    let (rows, cols) = batch.dim();
+    if window == 0 || window > cols {
+        return None;
+    }
+    let out_cols = cols - window + 1;
+    let input = batch.as_standard_layout();
+    let params = [rows as u32, cols as u32, window as u32, extra];
+    let workgroups = (out_cols.div_ceil(64) as u32, rows as u32);
+    let output = backend::run(source, input.as_slice()?, params, rows * out_cols, workgroups)?;
+    Array2::from_shape_vec((rows, out_cols), output).ok()
+}
+
+/// Gram matrix `z · zᵀ` of the rows of `z`, the correlation matrix of standardized rows
+pub fn gram(z: &Array2<f64>) -> Option<Array2<f64>> {
+    let (rows, cols) = z.dim();
+    if !backend::available() || z.iter().any(|v| v.is_nan()) {
+        return None;
+    }
+    let input: Vec<f32> = z.iter().map(|&v| v as f32).collect();
+    let groups = rows.div_ceil(8) as u32;
+    let upper = backend::run(GRAM, &input, [rows as u32, cols as u32, 0, 0], rows * rows, (groups, groups))?;
+    Some(Array2::from_shape_fn((rows, rows), |(i, j)| upper[i.min(j) * rows + i.max(j)] as f64))
+}
+
+/// Convert a NaN-free batch to float32, run `kernel` on the GPU and convert back
+fn map_batch<T: FloatElement>(
+    batch: ArrayView2<'_, T>,
+    kernel: impl FnOnce(ArrayView2<'_, f32>) -> Option<Array2<f32>>
+) -> Option<Array2<T>> {
+    if !backend::available() || batch.iter().any(|v| v.is_nan()) {
+        return None;
+    }
+    let input = batch.mapv(|v| v.to_f32().unwrap());
+    Some(kernel(input.view())?.mapv(|v| T::from(v).unwrap()))
+}
+
+/// Run a batch kernel on the GPU; `None` for single series or when the GPU path is unavailable
+fn try_batch<'py>(
+    py: Python<'py>,
+    data: &SeriesInput<'py>,
+    kernel: impl Fn(ArrayView2<'_, f32>) -> Option<Array2<f32>> + Send + Sync
+) -> Option<PyObject> {
+    match data {
+        SeriesInput::F64(Series::Batch(batch)) => {
+            let batch = batch.as_array();
+            let result = py.allow_threads(|| map_batch(batch, &kernel))?;
+            Some(PyArray2::from_owned_array_bound(py, result).into_any().unbind())
+        }
+        SeriesInput::F32(Series::Batch(batch)) => {
+            let batch = batch.as_array();
+            let result = py.allow_threads(|| map_batch(batch, &kernel))?;
+            Some(PyArray2::from_owned_array_bound(py, result).into_any().unbind())
+        }
+        _ => None,
+    }
+}
+
+/// Moving average of each row of a batch on the GPU
+pub fn moving_average<'py>(py: Python<'py>, data: &SeriesInput<'py>, window: usize) -> Option<PyObject> {
+    try_batch(py, data, |batch| rolling(MOVING_AVERAGE, batch, window, 0))
+}
+
+/// Rolling standard deviation of each row of a batch on the GPU
+pub fn rolling_std<'py>(py: Python<'py>, data: &SeriesInput<'py>, window: usize, ddof: usize) -> Option<PyObject> {
+    if ddof >= window {
+        return None;
+    }
+    try_batch(py, data, |batch| rolling(ROLLING_STD, batch, window, ddof as u32))
+}
+
+/// Whether the GPU backend is compiled in and found a usable device
+#[pyfunction]
+pub fn gpu_available() -> bool {
+    backend::available()
+}
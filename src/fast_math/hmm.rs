@@ -0,0 +1,328 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2};
+use ndarray::Array2;
+
+use crate::column::Column;
+
+/// Gaussian hidden Markov model with one mean and variance per state
+struct Hmm {
+    start: Vec<f64>,
+    /// `transition[i][j]` is the probability of moving from state `i` to state `j`
+    transition: Vec<Vec<f64>>,
+    means: Vec<f64>,
+    variances: Vec<f64>,
+}
+
+/// Forward pass results: per-bar state probabilities given the bars so far, and the log
+/// likelihood
+struct Filtered {
+    alpha: Vec<Vec<f64>>,
+    scales: Vec<f64>,
+    log_likelihood: f64,
+}
+
+impl Hmm {
+    fn n_states(&self) -> usize {
+        self.means.len()
+    }
+
+    fn log_emission(&self, x: f64, state: usize) -> f64 {
+        let variance = self.variances[state];
+        let z = x - self.means[state];
+        -0.5 * ((2.0 * std::f64::consts::PI * variance).ln() + z * z / variance)
+    }
+
+    /// Emission likelihoods of `x` scaled by a common factor, and the log of that factor,
+    /// so returns far out in every state's tail do not underflow to all zeros
+    fn emissions(&self, x: f64) -> (Vec<f64>, f64) {
+        let logs: Vec<f64> = (0..self.n_states()).map(|s| self.log_emission(x, s)).collect();
+        let max = logs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (logs.iter().map(|l| (l - max).exp()).collect(), max)
+    }
+
+    /// Scaled forward algorithm
+    fn forward(&self, x: &[f64]) -> Filtered {
+        let k = self.n_states();
+        let mut alpha = Vec::with_capacity(x.len());
+        let mut scales = Vec::with_capacity(x.len());
+        let mut log_likelihood = 0.0;
+        for (t, &value) in x.iter().enumerate() {
+            let (emission, offset) = self.emissions(value);
+            let mut a: Vec<f64> = (0..k)
+                .map(|j| {
+                    let prior = if t == 0 {
+                        self.start[j]
+                    } else {
+                        let previous: &Vec<f64> = &alpha[t - 1];
+                        (0..k).map(|i| previous[i] * self.transition[i][j]).sum()
+                    };
+                    prior * emission[j]
+                })
+                .collect();
+            let scale: f64 = a.iter().sum();
+            a.iter_mut().for_each(|v| *v /= scale);
+            log_likelihood += scale.ln() + offset;
+            alpha.push(a);
+            scales.push(scale);
+        }
+        Filtered { alpha, scales, log_likelihood }
+    }
+
+    /// One Baum-Welch re-estimation; returns the log likelihood under the current model
+    fn improve(&mut self, x: &[f64], variance_floor: f64) -> f64 {
+        let (n, k) = (x.len(), self.n_states());
+        let filtered = self.forward(x);
+        let emissions: Vec<Vec<f64>> = x.iter().map(|&v| self.emissions(v).0).collect();
+        // Backward pass under the forward pass's scaling
+        let mut beta = vec![vec![1.0; k]; n];
+        for t in (0..n - 1).rev() {
+            for i in 0..k {
+                beta[t][i] = (0..k).map(|j| self.transition[i][j] * emissions[t + 1][j] * beta[t + 1][j]).sum::<f64>()
+                    / filtered.scales[t + 1];
+            }
+        }
+        let gamma: Vec<Vec<f64>> = (0..n)
+            .map(|t| {
+                let g: Vec<f64> = (0..k).map(|i| filtered.alpha[t][i] * beta[t][i]).collect();
+                let total: f64 = g.iter().sum();
+                g.iter().map(|v| v / total).collect()
+            })
+            .collect();
+        let mut transitions = vec![vec![0.0; k]; k];
+        for t in 0..n - 1 {
+            for (i, row) in transitions.iter_mut().enumerate() {
+                for (j, cell) in row.iter_mut().enumerate() {
+                    *cell += filtered.alpha[t][i] * self.transition[i][j] * emissions[t + 1][j] * beta[t + 1][j]
+                        / filtered.scales[t + 1];
+                }
+            }
+        }
+
+        self.start = gamma[0].clone();
+        for (i, row) in transitions.iter().enumerate() {
+            let total: f64 = row.iter().sum();
+            if total > 0.0 {
+                self.transition[i] = row.iter().map(|v| v / total).collect();
+            }
+        }
+        for s in 0..k {
+            let weight: f64 = gamma.iter().map(|g| g[s]).sum();
+            if weight <= 0.0 {
+                continue;
+            }
+            let mean = gamma.iter().zip(x).map(|(g, v)| g[s] * v).sum::<f64>() / weight;
+            let variance = gamma.iter().zip(x).map(|(g, v)| g[s] * (v - mean) * (v - mean)).sum::<f64>() / weight;
+            self.means[s] = mean;
+            self.variances[s] = variance.max(variance_floor);
+        }
+        filtered.log_likelihood
+    }
+
+    /// Most likely state path by the Viterbi algorithm, and its log probability
+    fn viterbi(&self, x: &[f64]) -> (Vec<usize>, f64) {
+        let k = self.n_states();
+        let log = |p: f64| if p > 0.0 { p.ln() } else { f64::NEG_INFINITY };
+        let mut score: Vec<f64> = (0..k).map(|s| log(self.start[s]) + self.log_emission(x[0], s)).collect();
+        let mut back: Vec<Vec<usize>> = Vec::with_capacity(x.len());
+        for &value in &x[1..] {
+            let mut next = vec![0.0; k];
+            let mut from = vec![0; k];
+            for j in 0..k {
+                let (best, value_from) = (0..k)
+                    .map(|i| (i, score[i] + log(self.transition[i][j])))
+                    .fold((0, f64::NEG_INFINITY), |best, c| if c.1 > best.1 { c } else { best });
+                from[j] = best;
+                next[j] = value_from + self.log_emission(value, j);
+            }
+            back.push(from);
+            score = next;
+        }
+        let (mut state, log_probability) =
+            score.iter().copied().enumerate().fold((0, f64::NEG_INFINITY), |best, c| if c.1 > best.1 { c } else { best });
+        let mut path = vec![state; x.len()];
+        for (t, from) in back.iter().enumerate().rev() {
+            state = from[state];
+            path[t] = state;
+        }
+        (path, log_probability)
+    }
+
+    /// Relabel states by ascending variance, so state 0 is the calmest regime
+    fn sort_states(&mut self) {
+        let mut order: Vec<usize> = (0..self.n_states()).collect();
+        order.sort_by(|&a, &b| self.variances[a].total_cmp(&self.variances[b]));
+        self.start = order.iter().map(|&s| self.start[s]).collect();
+        self.means = order.iter().map(|&s| self.means[s]).collect();
+        self.variances = order.iter().map(|&s| self.variances[s]).collect();
+        self.transition = order.iter().map(|&i| order.iter().map(|&j| self.transition[i][j]).collect()).collect();
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let k = self.n_states();
+        let transition = Array2::from_shape_fn((k, k), |(i, j)| self.transition[i][j]);
+        let result = PyDict::new_bound(py);
+        result.set_item("means", PyArray1::from_slice_bound(py, &self.means))?;
+        result.set_item("variances", PyArray1::from_slice_bound(py, &self.variances))?;
+        result.set_item("transition", PyArray2::from_owned_array_bound(py, transition))?;
+        result.set_item("start_prob", PyArray1::from_slice_bound(py, &self.start))?;
+        Ok(result)
+    }
+}
+
+/// Starting model: states split the sorted returns into equal groups, with
+/// their means and variances, a uniform start and sticky transitions
+fn initial_model(x: &[f64], k: usize, variance_floor: f64) -> Hmm {
+    let mut sorted = x.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let chunk = sorted.len().div_ceil(k);
+    let groups: Vec<&[f64]> = sorted.chunks(chunk).collect();
+    let stats = |s: usize| {
+        let group = groups[s.min(groups.len() - 1)];
+        let mean = group.iter().sum::<f64>() / group.len() as f64;
+        let variance = group.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / group.len() as f64;
+        (mean, variance.max(variance_floor))
+    };
+    let stay = if k == 1 { 1.0 } else { 0.9 };
+    Hmm {
+        start: vec![1.0 / k as f64; k],
+        transition: (0..k)
+            .map(|i| (0..k).map(|j| if i == j { stay } else { (1.0 - stay) / (k - 1) as f64 }).collect())
+            .collect(),
+        means: (0..k).map(|s| stats(s).0).collect(),
+        variances: (0..k).map(|s| stats(s).1).collect(),
+    }
+}
+
+fn check_returns(returns: &[f64]) -> PyResult<()> {
+    if returns.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "returns must not be empty"
+        ));
+    }
+    if returns.iter().any(|v| !v.is_finite()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "returns must be finite"
+        ));
+    }
+    Ok(())
+}
+
+/// Fit a Gaussian hidden Markov model to a return series by Baum-Welch, like hmmlearn's
+/// `GaussianHMM(n_components=n_states).fit`, for regime detection.
+///
+/// Each hidden state (regime) draws returns from its own normal distribution and the
+/// states follow a Markov chain. Starting from the sorted returns split into `n_states`
+/// equal groups, expectation-maximisation runs for up to `max_iter` iterations until the
+/// log likelihood improves by less than `tol`; variances are kept above a small fraction
+/// of the sample variance so a state cannot collapse onto one return. States are
+/// numbered by ascending variance, so state 0 is the calmest regime. Returns a dict with
+/// the `means`, `variances`, `transition` matrix (rows sum to 1) and `start_prob`, which
+/// `hmm_decode_rust` takes as `params`, plus the `log_likelihood` and `n_iter`.
+/// Non-finite returns raise `NaNInputError`.
+#[pyfunction]
+#[pyo3(signature = (returns, n_states=2, max_iter=100, tol=1e-6))]
+pub fn hmm_fit_rust<'py>(
+    py: Python<'py>,
+    returns: Column<'py, f64>,
+    n_states: usize,
+    max_iter: usize,
+    tol: f64
+) -> PyResult<Bound<'py, PyDict>> {
+    let returns = returns.values()?;
+    check_returns(&returns)?;
+    if n_states == 0 || n_states > returns.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_states must be between 1 and the number of returns"
+        ));
+    }
+    let (model, log_likelihood, iterations) = py.allow_threads(|| {
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+        let floor = (1e-6 * variance).max(f64::MIN_POSITIVE);
+        let mut model = initial_model(&returns, n_states, floor);
+        let mut previous = f64::NEG_INFINITY;
+        let mut iterations = 0;
+        while iterations < max_iter {
+            iterations += 1;
+            let log_likelihood = model.improve(&returns, floor);
+            if log_likelihood - previous < tol {
+                break;
+            }
+            previous = log_likelihood;
+        }
+        model.sort_states();
+        let log_likelihood = model.forward(&returns).log_likelihood;
+        (model, log_likelihood, iterations)
+    });
+    let result = model.to_dict(py)?;
+    result.set_item("log_likelihood", log_likelihood)?;
+    result.set_item("n_iter", iterations)?;
+    Ok(result)
+}
+
+/// Classify returns into the regimes of a fitted Gaussian hidden Markov model.
+///
+/// `params` is a dict with `means`, `variances`, `transition` and `start_prob`, as
+/// returned by `hmm_fit_rust`. Returns a dict with the Viterbi `states`, the most likely
+/// regime sequence given the whole series, and its `log_probability`; the `filtered`
+/// probabilities (one row per return, one column per state) of each regime given the
+/// returns up to and including that bar, which unlike the Viterbi path never change as
+/// later bars arrive and so suit live classification; and the `log_likelihood` of the
+/// series under the model.
+#[pyfunction]
+pub fn hmm_decode_rust<'py>(
+    py: Python<'py>,
+    returns: Column<'py, f64>,
+    params: &Bound<'py, PyDict>
+) -> PyResult<Bound<'py, PyDict>> {
+    let required = |key: &str| {
+        params.get_item(key)?.ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("params needs a '{}' key", key))
+        })
+    };
+    // Lists extract directly; numpy arrays (as hmm_fit_rust returns) go through tolist()
+    fn extract<'py, T: FromPyObject<'py>>(value: Bound<'py, PyAny>) -> PyResult<T> {
+        match value.extract() {
+            Ok(value) => Ok(value),
+            Err(_) if value.hasattr("tolist")? => value.call_method0("tolist")?.extract(),
+            Err(err) => Err(err),
+        }
+    }
+    let model = Hmm {
+        start: extract(required("start_prob")?)?,
+        transition: extract(required("transition")?)?,
+        means: extract(required("means")?)?,
+        variances: extract(required("variances")?)?,
+    };
+    let k = model.n_states();
+    let valid = k > 0
+        && model.variances.len() == k
+        && model.start.len() == k
+        && model.transition.len() == k
+        && model.transition.iter().all(|row| row.len() == k && row.iter().all(|&p| p >= 0.0))
+        && model.start.iter().all(|&p| p >= 0.0)
+        && model.variances.iter().all(|&v| v > 0.0)
+        && model.means.iter().all(|m| m.is_finite());
+    if !valid {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "params must hold n_states means, positive variances, non-negative start_prob \
+             and an n_states x n_states transition matrix"
+        ));
+    }
+    let returns = returns.values()?;
+    check_returns(&returns)?;
+    let (path, log_probability, filtered) = py.allow_threads(|| {
+        let (path, log_probability) = model.viterbi(&returns);
+        (path, log_probability, model.forward(&returns))
+    });
+    let probabilities = Array2::from_shape_fn((returns.len(), k), |(t, s)| filtered.alpha[t][s]);
+
+    let result = PyDict::new_bound(py);
+    result.set_item("states", PyArray1::from_iter_bound(py, path.iter().map(|&s| s as i64)))?;
+    result.set_item("log_probability", log_probability)?;
+    result.set_item("filtered", PyArray2::from_owned_array_bound(py, probabilities))?;
+    result.set_item("log_likelihood", filtered.log_likelihood)?;
+    Ok(result)
+}
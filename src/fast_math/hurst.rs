@@ -0,0 +1,221 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+use rayon::prelude::*;
+
+use crate::column::{self, Series};
+use crate::threads;
+
+/// Scales the window sizes are spread over, log-spaced
+const SCALES: usize = 20;
+
+/// How the scaling of each window size is measured
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Rescaled range: mean R/S of the windows' cumulative deviations
+    RescaledRange,
+    /// Detrended fluctuation analysis: RMS of the linearly detrended profile
+    Dfa,
+}
+
+impl Method {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rs" => Ok(Method::RescaledRange),
+            "dfa" => Ok(Method::Dfa),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'rs' or 'dfa'", name
+            ))),
+        }
+    }
+
+    /// Largest window used by default for `len` values
+    fn default_max_window(self, len: usize) -> usize {
+        match self {
+            Method::RescaledRange => len / 2,
+            Method::Dfa => len / 4,
+        }
+    }
+}
+
+/// Distinct window sizes from `min` to `max`, log-spaced
+fn scales(min: usize, max: usize) -> Vec<usize> {
+    if max < min {
+        return Vec::new();
+    }
+    let ratio = (max as f64 / min as f64).ln();
+    let mut sizes: Vec<usize> = (0..SCALES)
+        .map(|k| (min as f64 * (ratio * k as f64 / (SCALES - 1) as f64).exp()).round() as usize)
+        .map(|n| n.clamp(min, max))
+        .collect();
+    sizes.dedup();
+    sizes
+}
+
+/// Mean rescaled range of the non-overlapping windows of `n` values; windows with no
+/// spread are left out
+fn rescaled_range(values: &[f64], n: usize) -> Option<f64> {
+    let ratios: Vec<f64> = values
+        .chunks_exact(n)
+        .filter_map(|w| {
+            let mean = w.iter().sum::<f64>() / n as f64;
+            let (mut cumulative, mut low, mut high) = (0.0, 0.0f64, 0.0f64);
+            for v in w {
+                cumulative += v - mean;
+                low = low.min(cumulative);
+                high = high.max(cumulative);
+            }
+            let std = (w.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n as f64).sqrt();
+            (std > 0.0).then(|| (high - low) / std)
+        })
+        .collect();
+    (!ratios.is_empty()).then(|| ratios.iter().sum::<f64>() / ratios.len() as f64)
+}
+
+/// Root mean square residual of a least-squares line through each non-overlapping window of
+/// `n` points of the `profile`
+fn fluctuation(profile: &[f64], n: usize) -> Option<f64> {
+    // The abscissae 0..n are the same in every window
+    let t_mean = (n - 1) as f64 / 2.0;
+    let t_ss: f64 = (0..n).map(|t| (t as f64 - t_mean).powi(2)).sum();
+    let windows = profile.chunks_exact(n);
+    let count = windows.len();
+    let ss: f64 = windows
+        .map(|w| {
+            let mean = w.iter().sum::<f64>() / n as f64;
+            let slope = w.iter().enumerate().map(|(t, v)| (t as f64 - t_mean) * (v - mean)).sum::<f64>() / t_ss;
+            w.iter()
+                .enumerate()
+                .map(|(t, v)| (v - mean - slope * (t as f64 - t_mean)).powi(2))
+                .sum::<f64>()
+        })
+        .sum();
+    let f = (ss / (count * n) as f64).sqrt();
+    (f > 0.0).then_some(f)
+}
+
+/// Slope of `ln(statistic)` on `ln(window)` over the measurable scales, NaN with fewer than two
+fn hurst(values: &[f64], method: Method, min_window: usize, max_window: Option<usize>) -> f64 {
+    if values.iter().any(|v| v.is_nan()) {
+        return f64::NAN;
+    }
+    let max_window = max_window.unwrap_or_else(|| method.default_max_window(values.len())).min(values.len());
+    let profile: Vec<f64> = match method {
+        Method::RescaledRange => Vec::new(),
+        Method::Dfa => {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values
+                .iter()
+                .scan(0.0, |total, v| {
+                    *total += v - mean;
+                    Some(*total)
+                })
+                .collect()
+        }
+    };
+    let points: Vec<(f64, f64)> = scales(min_window, max_window)
+        .into_par_iter()
+        .filter_map(|n| {
+            let statistic = match method {
+                Method::RescaledRange => rescaled_range(values, n),
+                Method::Dfa => fluctuation(&profile, n),
+            };
+            statistic.map(|s| ((n as f64).ln(), s.ln()))
+        })
+        .collect();
+
+    if points.len() < 2 {
+        return f64::NAN;
+    }
+    let k = points.len() as f64;
+    let x_mean = points.iter().map(|p| p.0).sum::<f64>() / k;
+    let y_mean = points.iter().map(|p| p.1).sum::<f64>() / k;
+    let sxy: f64 = points.iter().map(|(x, y)| (x - x_mean) * (y - y_mean)).sum();
+    let sxx: f64 = points.iter().map(|(x, _)| (x - x_mean) * (x - x_mean)).sum();
+    sxy / sxx
+}
+
+/// Hurst exponent of a series of increments (e.g. returns): about 0.5 for a random walk's
+/// steps, above for persistent (trending) and below for anti-persistent (mean-reverting)
+/// series.
+///
+/// `method="rs"` (the default) uses rescaled-range analysis, `"dfa"` detrended fluctuation
+/// analysis of the cumulative sum. Either measures the statistic for up to 20 log-spaced
+/// window sizes from `min_window` (at least 4) to `max_window` (default a half of the
+/// length for R/S, a quarter for DFA), in parallel, and returns the slope of its logarithm
+/// on the logarithm of the window size. A 2D array is processed row by row, returning an
+/// array. NaN-holding series, and series too short or flat to measure at two scales, give NaN.
+#[pyfunction]
+#[pyo3(signature = (data, method="rs", min_window=10, max_window=None))]
+pub fn hurst_rust<'py>(
+    py: Python<'py>,
+    data: Series<'py, f64>,
+    method: &str,
+    min_window: usize,
+    max_window: Option<usize>
+) -> PyResult<PyObject> {
+    let method = Method::parse(method)?;
+    if min_window < 4 {
+        return Err(crate::errors::InvalidWindowError::new_err(
+            "min_window must be >= 4"
+        ));
+    }
+
+    match data {
+        Series::Single(column) => {
+            let values = column.values()?;
+            let h = py.allow_threads(|| threads::install(|| hurst(&values, method, min_window, max_window)));
+            Ok(h.into_py(py))
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            let rows = py.allow_threads(|| {
+                column::map_rows(batch, |_, row| Ok(hurst(row, method, min_window, max_window)))
+            })?;
+            Ok(PyArray1::from_vec_bound(py, rows).into_any().unbind())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rng::SplitMix;
+
+    fn noise(n: usize) -> Vec<f64> {
+        let mut rng = SplitMix::new(5, 0);
+        (0..n).map(|_| rng.normal_pair().0).collect()
+    }
+
+    #[test]
+    fn white_noise_scales_like_a_random_walk() {
+        let x = noise(4096);
+        let dfa = hurst(&x, Method::Dfa, 10, None);
+        assert!((dfa - 0.5).abs() < 0.08, "{}", dfa);
+        let rs = hurst(&x, Method::RescaledRange, 10, None);
+        // R/S is biased upwards at small windows
+        assert!((0.45..0.7).contains(&rs), "{}", rs);
+    }
+
+    #[test]
+    fn persistence_and_anti_persistence_move_the_exponent() {
+        let x = noise(4097);
+        let differenced: Vec<f64> = x.windows(2).map(|w| w[1] - w[0]).collect();
+        let walk: Vec<f64> = x.iter().scan(0.0, |total, v| { *total += v; Some(*total) }).collect();
+        assert!(hurst(&differenced, Method::Dfa, 10, None) < 0.2);
+        assert!(hurst(&walk, Method::Dfa, 10, None) > 1.3);
+    }
+
+    #[test]
+    fn unmeasurable_series_give_nan() {
+        let mut x = noise(200);
+        assert!(hurst(&[1.0; 200], Method::RescaledRange, 10, None).is_nan());
+        assert!(hurst(&x[..15], Method::Dfa, 10, None).is_nan());
+        x[3] = f64::NAN;
+        assert!(hurst(&x, Method::Dfa, 10, None).is_nan());
+        assert_eq!(scales(10, 10), vec![10]);
+        assert!(scales(10, 9).is_empty());
+        assert_eq!(scales(4, 1000).len(), SCALES);
+    }
+}
@@ -0,0 +1,173 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView1, ArrayView2, Axis};
+use rayon::prelude::*;
+
+use crate::config::FastMathConfig;
+use crate::rng::{self, SplitMix};
+use crate::threads;
+
+fn squared_distance(a: ArrayView1<'_, f64>, b: ArrayView1<'_, f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Nearest centre of `point` and its squared distance
+fn nearest(point: ArrayView1<'_, f64>, centers: &Array2<f64>) -> (usize, f64) {
+    centers
+        .axis_iter(Axis(0))
+        .map(|c| squared_distance(point, c))
+        .enumerate()
+        .fold((0, f64::INFINITY), |best, (j, d)| if d < best.1 { (j, d) } else { best })
+}
+
+/// k-means++ seeding: the first centre uniformly, each next one with probability
+/// proportional to the squared distance to the nearest centre chosen so far
+fn seed_centers(x: ArrayView2<'_, f64>, k: usize, rng: &mut SplitMix) -> Array2<f64> {
+    let n = x.nrows();
+    let mut centers = Array2::zeros((k, x.ncols()));
+    centers.row_mut(0).assign(&x.row(rng.below(n)));
+    let mut closest: Vec<f64> = x.axis_iter(Axis(0)).map(|p| squared_distance(p, centers.row(0))).collect();
+    for j in 1..k {
+        let total: f64 = closest.iter().sum();
+        let chosen = if total > 0.0 {
+            let mut target = rng.uniform() * total;
+            closest.iter().position(|&d| {
+                target -= d;
+                target < 0.0
+            })
+            .unwrap_or(n - 1)
+        } else {
+            rng.below(n)
+        };
+        centers.row_mut(j).assign(&x.row(chosen));
+        for (i, d) in closest.iter_mut().enumerate() {
+            *d = d.min(squared_distance(x.row(i), centers.row(j)));
+        }
+    }
+    centers
+}
+
+/// Result of one k-means run
+struct Clustering {
+    labels: Vec<usize>,
+    centers: Array2<f64>,
+    inertia: f64,
+    iterations: usize,
+}
+
+/// Lloyd's algorithm from k-means++ centres, assigning points in parallel, until the
+/// centres move less than `tol` in total squared distance or `max_iter` passes. A
+/// cluster left empty takes the point farthest from its own centre.
+fn lloyd(x: ArrayView2<'_, f64>, k: usize, max_iter: usize, tol: f64, rng: &mut SplitMix) -> Clustering {
+    let (n, dims) = x.dim();
+    let mut centers = seed_centers(x, k, rng);
+    let mut iterations = 0;
+    while iterations < max_iter {
+        iterations += 1;
+        let mut assigned: Vec<(usize, f64)> = x.axis_iter(Axis(0)).into_par_iter().map(|p| nearest(p, &centers)).collect();
+        let mut sums = Array2::<f64>::zeros((k, dims));
+        let mut counts = vec![0usize; k];
+        for (i, &(label, _)) in assigned.iter().enumerate() {
+            let mut row = sums.row_mut(label);
+            row += &x.row(i);
+            counts[label] += 1;
+        }
+        for j in 0..k {
+            if counts[j] > 0 {
+                continue;
+            }
+            let far = (0..n)
+                .filter(|&i| counts[assigned[i].0] > 1)
+                .max_by(|&a, &b| assigned[a].1.total_cmp(&assigned[b].1));
+            if let Some(far) = far {
+                let mut old = sums.row_mut(assigned[far].0);
+                old -= &x.row(far);
+                counts[assigned[far].0] -= 1;
+                sums.row_mut(j).assign(&x.row(far));
+                counts[j] = 1;
+                assigned[far] = (j, 0.0);
+            }
+        }
+        for (mut row, &count) in sums.axis_iter_mut(Axis(0)).zip(&counts) {
+            if count > 0 {
+                row.mapv_inplace(|v| v / count as f64);
+            }
+        }
+        let shift: f64 =
+            centers.axis_iter(Axis(0)).zip(sums.axis_iter(Axis(0))).map(|(a, b)| squared_distance(a, b)).sum();
+        centers = sums;
+        if shift <= tol {
+            break;
+        }
+    }
+    // Labels and inertia against the final centres
+    let assigned: Vec<(usize, f64)> = x.axis_iter(Axis(0)).into_par_iter().map(|p| nearest(p, &centers)).collect();
+    Clustering {
+        labels: assigned.iter().map(|a| a.0).collect(),
+        inertia: assigned.iter().map(|a| a.1).sum(),
+        centers,
+        iterations,
+    }
+}
+
+/// K-means clustering of the rows of a feature matrix, e.g. for market regimes.
+///
+/// `features_2d` holds one observation per row and one feature per column. Each of the
+/// `n_init` runs seeds `k` centres by k-means++ and then alternates assigning every row
+/// to its nearest centre (in parallel) and moving each centre to the mean of its rows,
+/// for at most `max_iter` passes or until the centres move less than `tol` times the
+/// mean feature variance (in total squared distance, as scikit-learn); the run with the
+/// lowest inertia wins. Runs are seeded from `seed` (or `set_seed`'s) and their index,
+/// so results are reproducible. Features on different scales should be standardised
+/// first. Returns a dict with the `labels` of the rows, the `centers` (`k` rows), the
+/// `inertia` (total squared distance of rows to their centres) and `n_iter`, the passes
+/// of the winning run.
+#[pyfunction]
+#[pyo3(signature = (features_2d, k, max_iter=300, seed=None, n_init=10, tol=1e-4, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn kmeans_rust<'py>(
+    py: Python<'py>,
+    features_2d: PyReadonlyArray2<'py, f64>,
+    k: usize,
+    max_iter: usize,
+    seed: Option<u64>,
+    n_init: usize,
+    tol: f64,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let x = features_2d.as_array();
+    if k == 0 || k > x.nrows() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "k must be between 1 and the number of rows ({})", x.nrows()
+        )));
+    }
+    if max_iter == 0 || n_init == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "max_iter and n_init must be > 0"
+        ));
+    }
+    if x.iter().any(|v| !v.is_finite()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "features_2d must be finite"
+        ));
+    }
+    let seed = rng::resolve_seed(seed);
+    let best = py.allow_threads(|| {
+        threads::install(|| {
+            let variance = x.var_axis(Axis(0), 0.0).mean().unwrap_or(0.0);
+            (0..n_init as u64)
+                .map(|run| lloyd(x, k, max_iter, tol * variance, &mut SplitMix::new(seed, run)))
+                .min_by(|a, b| a.inertia.total_cmp(&b.inertia))
+                .unwrap()
+        })
+    });
+
+    let result = PyDict::new_bound(py);
+    result.set_item("labels", PyArray1::from_iter_bound(py, best.labels.iter().map(|&l| l as i64)))?;
+    result.set_item("centers", PyArray2::from_owned_array_bound(py, best.centers))?;
+    result.set_item("inertia", best.inertia)?;
+    result.set_item("n_iter", best.iterations)?;
+    Ok(result)
+}
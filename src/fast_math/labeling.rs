@@ -0,0 +1,231 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// Outcome of one event: which barrier was touched first, where, and the return there
+struct Touch {
+    /// 1 for the profit-taking barrier, -1 for the stop-loss, 0 for the vertical barrier
+    label: i8,
+    index: i64,
+    ret: f64,
+}
+
+/// Walk forward from `start` to the first bar whose return from it reaches `upper` or
+/// falls to `lower` (either disabled when `None`), or to the vertical barrier at `end`
+fn first_touch(prices: &[f64], start: usize, end: usize, upper: Option<f64>, lower: Option<f64>) -> Touch {
+    let entry = prices[start];
+    for (t, price) in prices.iter().enumerate().take(end + 1).skip(start + 1) {
+        let ret = price / entry - 1.0;
+        if upper.is_some_and(|upper| ret >= upper) {
+            return Touch { label: 1, index: t as i64, ret };
+        }
+        if lower.is_some_and(|lower| ret <= lower) {
+            return Touch { label: -1, index: t as i64, ret };
+        }
+    }
+    Touch { label: 0, index: end as i64, ret: prices[end] / entry - 1.0 }
+}
+
+/// Triple-barrier labels of `events`, as in López de Prado's *Advances in Financial
+/// Machine Learning* (section 3.4).
+///
+/// `events` are indices into `prices`. From each event the path is walked forward to the
+/// first of three barriers: the return from the event price reaching `pt_sl[0] * target`
+/// (profit taking, label 1), falling to `-pt_sl[1] * target` (stop loss, label -1), or
+/// `vertical_barrier` bars passing (label 0, or fewer bars at the end of the series).
+/// `target` gives one barrier width per event, typically a volatility estimate at the
+/// event, and defaults to 1 so `pt_sl` are plain returns; a 0 in `pt_sl` disables that
+/// horizontal barrier. Returns `(labels, touch_indices, returns)`: the label, the index in
+/// `prices` of the bar that touched first (index a timestamp column with it for touch
+/// times) and the return there. Events are searched in parallel. NaN prices never touch a
+/// horizontal barrier.
+#[pyfunction]
+#[pyo3(signature = (prices, events, pt_sl, vertical_barrier, target=None))]
+pub fn triple_barrier_rust<'py>(
+    py: Python<'py>,
+    prices: Column<'py, f64>,
+    events: Column<'py, i64>,
+    pt_sl: (f64, f64),
+    vertical_barrier: usize,
+    target: Option<Column<'py, f64>>
+) -> PyResult<PyObject> {
+    let prices = prices.values()?;
+    let events = events.values()?;
+    let target = target.as_ref().map(|t| t.values()).transpose()?;
+    let (profit_taking, stop_loss) = pt_sl;
+    if profit_taking.is_nan() || stop_loss.is_nan() || profit_taking < 0.0 || stop_loss < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "pt_sl must be two multipliers >= 0"
+        ));
+    }
+    if vertical_barrier == 0 {
+        return Err(crate::errors::InvalidWindowError::new_err(
+            "vertical_barrier must be > 0"
+        ));
+    }
+    if target.as_ref().is_some_and(|t| t.len() != events.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "target must have one value per event"
+        ));
+    }
+    if let Some(&event) = events.iter().find(|&&e| e < 0 || e as usize >= prices.len()) {
+        return Err(pyo3::exceptions::PyIndexError::new_err(format!(
+            "event index {} is out of bounds for {} prices", event, prices.len()
+        )));
+    }
+
+    let touches: Vec<Touch> = py.allow_threads(|| {
+        threads::install(|| {
+            events
+                .par_iter()
+                .enumerate()
+                .map(|(i, &event)| {
+                    let start = event as usize;
+                    let end = start.saturating_add(vertical_barrier).min(prices.len() - 1);
+                    let width = target.as_ref().map_or(1.0, |t| t[i]);
+                    let barrier = |multiplier: f64| (multiplier > 0.0).then_some(multiplier * width);
+                    first_touch(&prices, start, end, barrier(profit_taking), barrier(stop_loss).map(|b| -b))
+                })
+                .collect()
+        })
+    });
+    Ok((
+        PyArray1::from_iter_bound(py, touches.iter().map(|t| t.label)),
+        PyArray1::from_iter_bound(py, touches.iter().map(|t| t.index)),
+        PyArray1::from_iter_bound(py, touches.iter().map(|t| t.ret)),
+    )
+        .into_py(py))
+}
+
+/// Check that every `[start, end]` event span lies within `n_bars` bars and starts no
+/// later than it ends, returning the spans as indices
+fn event_spans(event_start: &[i64], event_end: &[i64], n_bars: usize) -> PyResult<Vec<(usize, usize)>> {
+    if event_start.len() != event_end.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "event_start and event_end must have the same length"
+        ));
+    }
+    event_start
+        .iter()
+        .zip(event_end)
+        .map(|(&start, &end)| {
+            if start < 0 || start > end || end as usize >= n_bars {
+                return Err(pyo3::exceptions::PyIndexError::new_err(format!(
+                    "event span [{}, {}] is not within {} bars", start, end, n_bars
+                )));
+            }
+            Ok((start as usize, end as usize))
+        })
+        .collect()
+}
+
+/// Number of events spanning each bar, by a difference array over the span bounds
+fn concurrency(spans: &[(usize, usize)], n_bars: usize) -> Vec<i64> {
+    let mut changes = vec![0i64; n_bars + 1];
+    for &(start, end) in spans {
+        changes[start] += 1;
+        changes[end + 1] -= 1;
+    }
+    changes
+        .iter()
+        .take(n_bars)
+        .scan(0, |count, change| {
+            *count += change;
+            Some(*count)
+        })
+        .collect()
+}
+
+/// Prefix sums of the uniqueness `1 / c_t`, so any span's sum is a difference
+fn uniqueness_sums(counts: &[i64]) -> Vec<f64> {
+    let mut sums = vec![0.0];
+    for &count in counts {
+        let term = if count > 0 { 1.0 / count as f64 } else { 0.0 };
+        sums.push(sums[sums.len() - 1] + term);
+    }
+    sums
+}
+
+/// Number of concurrent events at each of `n_bars` bars (López de Prado's
+/// `mpNumCoEvents`).
+///
+/// Event `i` spans bars `event_start[i]` to `event_end[i]` inclusive, e.g. the event
+/// indices passed to `triple_barrier_rust` and the touch indices it returns. Bars no event
+/// spans count 0. Raises `IndexError` for spans outside `0..n_bars` or ending before they
+/// start.
+#[pyfunction]
+pub fn concurrency_weights_rust<'py>(
+    py: Python<'py>,
+    event_start: Column<'py, i64>,
+    event_end: Column<'py, i64>,
+    n_bars: usize
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    let spans = event_spans(&event_start.values()?, &event_end.values()?, n_bars)?;
+    let counts = py.allow_threads(|| concurrency(&spans, n_bars));
+    Ok(PyArray1::from_vec_bound(py, counts))
+}
+
+/// Average uniqueness of each event over its lifespan (López de Prado's
+/// `mpSampleTW`).
+///
+/// The uniqueness of an event at a bar is `1 / c_t`, `c_t` the number of events spanning
+/// the bar (see `concurrency_weights_rust`), and each event's weight is its mean over the
+/// bars it spans: 1 for an event overlapping no other, smaller the more labels share its
+/// returns. Suited as sample weights, or their mean as the fraction of samples to draw
+/// per bagged tree.
+#[pyfunction]
+pub fn average_uniqueness_rust<'py>(
+    py: Python<'py>,
+    event_start: Column<'py, i64>,
+    event_end: Column<'py, i64>,
+    n_bars: usize
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let spans = event_spans(&event_start.values()?, &event_end.values()?, n_bars)?;
+    let uniqueness = py.allow_threads(|| {
+        let counts = concurrency(&spans, n_bars);
+        let sums = uniqueness_sums(&counts);
+        spans
+            .iter()
+            .map(|&(start, end)| (sums[end + 1] - sums[start]) / (end + 1 - start) as f64)
+            .collect::<Vec<f64>>()
+    });
+    Ok(PyArray1::from_vec_bound(py, uniqueness))
+}
+
+/// Sample weights of events by return attribution (López de Prado's `mpSampleW`).
+///
+/// Each event gets `|sum_t r_t / c_t|` over the bars it spans, where `r_t` is the log
+/// return of `prices` into bar `t` (0 at the first bar) and `c_t` the number of events
+/// spanning it, so returns shared by overlapping labels are split between them and events
+/// over large absolute moves weigh more. The weights are scaled to sum to the number of
+/// events. Spans are as for `concurrency_weights_rust`, over the bars of `prices`; NaN
+/// prices give NaN weights to the events spanning them, which are left out of the scaling.
+#[pyfunction]
+pub fn attribution_weights_rust<'py>(
+    py: Python<'py>,
+    event_start: Column<'py, i64>,
+    event_end: Column<'py, i64>,
+    prices: Column<'py, f64>
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let prices = prices.values()?;
+    let spans = event_spans(&event_start.values()?, &event_end.values()?, prices.len())?;
+    let weights = py.allow_threads(|| {
+        let counts = concurrency(&spans, prices.len());
+        let attributed: Vec<f64> = std::iter::once(0.0)
+            .chain(prices.windows(2).map(|w| (w[1] / w[0]).ln()))
+            .zip(&counts)
+            .map(|(r, &count)| if count > 0 { r / count as f64 } else { 0.0 })
+            .collect();
+        // Summed span by span rather than by prefix sums, so a NaN stays in its own spans
+        let weights: Vec<f64> = threads::install(|| {
+            spans.par_iter().map(|&(start, end)| attributed[start..=end].iter().sum::<f64>().abs()).collect()
+        });
+        let total: f64 = weights.iter().filter(|w| !w.is_nan()).sum();
+        let scale = if total > 0.0 { weights.len() as f64 / total } else { 1.0 };
+        weights.into_iter().map(|w| w * scale).collect::<Vec<f64>>()
+    });
+    Ok(PyArray1::from_vec_bound(py, weights))
+}
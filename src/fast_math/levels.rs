@@ -0,0 +1,114 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Indices of the swing points of `values`: bars greater than the `lookback` bars before
+/// them and at least the `lookback` after (so a flat top counts once), by `cmp`, which
+/// is `>` for highs and `<` for lows. Only bars with `lookback` bars after them qualify.
+fn swings(values: &[f64], lookback: usize, cmp: impl Fn(f64, f64) -> bool) -> Vec<usize> {
+    let n = values.len();
+    (lookback..n.saturating_sub(lookback))
+        .filter(|&i| {
+            let v = values[i];
+            !v.is_nan()
+                && values[i - lookback..i].iter().all(|&w| w.is_nan() || cmp(v, w))
+                && values[i + 1..=i + lookback].iter().all(|&w| w.is_nan() || !cmp(w, v))
+        })
+        .collect()
+}
+
+/// A price level formed by nearby swing points
+struct Level {
+    price: f64,
+    touches: usize,
+    strength: f64,
+    first: usize,
+    last: usize,
+}
+
+/// Group swing points `(index, price)` into levels: in price order, a point joins the
+/// current cluster while within `tolerance` (a fraction of the price) of its mean. A
+/// touch at bar `i` of `n` adds `(i + 1) / n` to the strength, so recent touches count
+/// most.
+fn cluster(mut points: Vec<(usize, f64)>, tolerance: f64, n: usize) -> Vec<Level> {
+    points.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let mut levels: Vec<Level> = Vec::new();
+    let mut sum = 0.0;
+    for (i, price) in points {
+        let weight = (i + 1) as f64 / n as f64;
+        match levels.last_mut() {
+            Some(level) if (price - level.price).abs() <= tolerance * level.price.abs() => {
+                sum += price;
+                level.touches += 1;
+                level.price = sum / level.touches as f64;
+                level.strength += weight;
+                level.first = level.first.min(i);
+                level.last = level.last.max(i);
+            }
+            _ => {
+                sum = price;
+                levels.push(Level { price, touches: 1, strength: weight, first: i, last: i });
+            }
+        }
+    }
+    levels
+}
+
+/// Support and resistance levels from clustered swing highs and lows.
+///
+/// A swing high is a bar whose `high` exceeds those of the `lookback` bars before it and
+/// is not exceeded by the `lookback` bars after it, and a swing low likewise for `low`,
+/// so the last `lookback` bars cannot confirm one yet. Swing prices within `tolerance` (a
+/// fraction of the price, e.g. 0.005 for 0.5%) of a level's mean join it, whether highs
+/// or lows, since broken resistance often becomes support. Levels formed by fewer than
+/// `min_touches` swings are dropped. Returns a dict of arrays, strongest level first:
+/// the `level` price, its number of `touches`, its `strength` (each touch at bar `i` of
+/// `n` adding `(i + 1) / n`, so recent and repeated touches rank highest), the `first`
+/// and `last` bar that touched it and its `kind`, -1 for support (below the last `close`)
+/// or 1 for resistance.
+#[pyfunction]
+#[pyo3(signature = (high, low, close, lookback=5, tolerance=0.005, min_touches=1))]
+pub fn support_resistance_rust<'py>(
+    py: Python<'py>,
+    high: Column<'py, f64>,
+    low: Column<'py, f64>,
+    close: Column<'py, f64>,
+    lookback: usize,
+    tolerance: f64,
+    min_touches: usize
+) -> PyResult<Bound<'py, PyDict>> {
+    crate::errors::require_window(lookback)?;
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tolerance must be >= 0"
+        ));
+    }
+    let (high, low, close) = (high.values()?, low.values()?, close.values()?);
+    let n = high.len();
+    if low.len() != n || close.len() != n {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "high, low and close must have the same length"
+        ));
+    }
+    let last_close = close.iter().rev().copied().find(|c| !c.is_nan()).unwrap_or(f64::NAN);
+    let levels = py.allow_threads(|| {
+        let highs = swings(&high, lookback, |a, b| a > b).into_iter().map(|i| (i, high[i]));
+        let lows = swings(&low, lookback, |a, b| a < b).into_iter().map(|i| (i, low[i]));
+        let mut levels = cluster(highs.chain(lows).collect(), tolerance, n);
+        levels.retain(|level| level.touches >= min_touches);
+        levels.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+        levels
+    });
+
+    let result = PyDict::new_bound(py);
+    result.set_item("level", PyArray1::from_iter_bound(py, levels.iter().map(|l| l.price)))?;
+    result.set_item("touches", PyArray1::from_iter_bound(py, levels.iter().map(|l| l.touches as i64)))?;
+    result.set_item("strength", PyArray1::from_iter_bound(py, levels.iter().map(|l| l.strength)))?;
+    result.set_item("first", PyArray1::from_iter_bound(py, levels.iter().map(|l| l.first as i64)))?;
+    result.set_item("last", PyArray1::from_iter_bound(py, levels.iter().map(|l| l.last as i64)))?;
+    let kind = levels.iter().map(|l| if l.price < last_close { -1i8 } else { 1 });
+    result.set_item("kind", PyArray1::from_iter_bound(py, kind))?;
+    Ok(result)
+}
@@ -1,124 +1,924 @@
-
 use pyo3::prelude::*;
-use numpy::{PyArray1, PyArrayMethods};
-use ndarray::{Array1, ArrayView1};
-use rayon::prelude::*;
 
-/// Calculate moving average using Rust + Rayon for parallel processing
+use alignment::Alignment;
+use column::{check_out, map_series, FloatElement, Series, SeriesInput};
+use config::FastMathConfig;
+use pool::Buffer;
+use skipna::NanPolicy;
+
+mod adf;
+mod alignment;
+mod arima;
+mod attribution;
+mod autocorrelation;
+mod bars;
+mod bench;
+mod blotter;
+mod bootstrap;
+mod cache;
+mod calendar;
+mod changepoint;
+mod chunked;
+mod cointegration;
+mod column;
+mod compensated;
+mod config;
+mod corporate_actions;
+mod correlation_matrix;
+mod costs;
+mod cross_section;
+mod csv_reader;
+mod currency;
+mod cv;
+mod decompose;
+mod dispatch;
+mod errors;
+mod execution;
+mod expr;
+mod exposure;
+mod fft;
+mod fix;
+mod fracdiff;
+mod frame;
+mod futures;
+mod garch;
+mod gbdt;
+mod gpu;
+mod hmm;
+mod hurst;
+mod kmeans;
+mod labeling;
+mod levels;
+mod limits;
+mod linalg;
+mod merge;
+mod microstructure;
+mod montecarlo;
+mod netting;
+mod normality;
+mod oms;
+mod optimize;
+mod options;
+mod orderbook;
+mod ou;
+mod parquet_io;
+mod pairs;
+mod pca;
+mod peaks;
+mod pipeline;
+mod pool;
+mod portfolio;
+mod quality;
+mod registry;
+mod replay;
+mod resample;
+mod results;
+mod regression;
+mod returns;
+mod risk;
+mod routing;
+mod rng;
+mod seasonality;
+mod series_store;
+mod signals;
+mod simd;
+mod similarity;
+mod skipna;
+mod spectral;
+mod strategy;
+mod streaming;
+mod svi;
+mod tdigest;
+mod telemetry;
+mod threads;
+mod tick_file;
+mod tick_json;
+mod ticks;
+mod timezone;
+mod validate;
+mod volume_profile;
+mod wavelet;
+mod ws_client;
+
+/// Steps between exact recomputations of the sliding sum, bounding rounding drift
+const DRIFT_INTERVAL: usize = 1024;
+
+/// Simple moving average over every full window of `slice`, in O(n) with a sliding sum
+fn moving_average<T: FloatElement>(slice: &[T], window: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); (slice.len() + 1).saturating_sub(window)];
+    moving_average_into(slice, window, &mut result)?;
+    Ok(result)
+}
+
+/// `moving_average` written into `out`, which must hold `len - window + 1` values
+fn moving_average_into<T: FloatElement>(slice: &[T], window: usize, out: &mut [T]) -> PyResult<()> {
+    let n = slice.len();
+
+    let valid = errors::check_window(window, n)?;
+    check_out(out, valid)?;
+    if valid == 0 {
+        return Ok(());
+    }
+    if window == 1 {
+        // A one-value window is the input itself, which sliding sums would round
+        out.copy_from_slice(slice);
+        return Ok(());
+    }
+
+    // The sum covers the window's finite values, so an infinity leaving the window cannot
+    // turn it into inf - inf; windows holding NaN or infinite values are averaged directly
+    let window_sum = |values: &[T]| match T::sum_slice(values) {
+        sum if !sum.is_finite() => values.iter().copied().filter(|v| v.is_finite()).sum::<T>(),
+        sum => sum,
+    };
+    let scale = T::from(window).unwrap();
+    let average = |sum: T, nonfinite: usize, values: &[T]| {
+        if nonfinite > 0 { T::sum_slice(values) / scale } else { sum / scale }
+    };
+    let mut sum = window_sum(&slice[..window]);
+    let mut nonfinite = slice[..window].iter().filter(|v| !v.is_finite()).count();
+    out[0] = average(sum, nonfinite, &slice[..window]);
+    for i in 1..=n - window {
+        let (old, new) = (slice[i - 1], slice[i + window - 1]);
+        nonfinite = nonfinite + !new.is_finite() as usize - !old.is_finite() as usize;
+        sum = if i % DRIFT_INTERVAL == 0 {
+            window_sum(&slice[i..i + window])
+        } else {
+            let added = if new.is_finite() { new } else { T::zero() };
+            let removed = if old.is_finite() { old } else { T::zero() };
+            sum + added - removed
+        };
+        out[i] = average(sum, nonfinite, &slice[i..i + window]);
+    }
+
+    validate::check("moving_average", out, || validate::reference::moving_average(slice, window, window));
+    Ok(())
+}
+
+/// Calculate moving average using an O(n) sliding sum
+///
+/// Accepts a single series, a 2D array with one series per row or a pyarrow record batch
+/// or Polars DataFrame with one per column (returned as the same type with the same column
+/// names); series are processed in parallel with Rayon. `nan_policy` is "propagate"
+/// (windows holding a NaN are NaN), "raise" (NaN inputs are a `ValueError`) or "omit", in
+/// which case windows average their valid values and are NaN when fewer than `min_periods`
+/// (default `window`) are valid; `skipna=True` is the older spelling of "omit". With
+/// `out`, results are written into that preallocated numpy array (length
+/// `len - window + 1`), which is returned.
+/// `align="same"` NaN-pads the first `window - 1` positions so the result (and `out`)
+/// has the input's length and lines up with its index; an integer `align=k` also shifts
+/// it `k` positions later (see `Alignment`). Series shorter than the window (including
+/// empty ones) have no full windows, so the result is empty, or all NaN when aligned.
+/// `window=1` returns the input exactly; `window=0` raises `InvalidWindowError`.
+/// `precise` uses compensated (Kahan-Neumaier) sliding sums, matching pandas to the last
+/// bits on long series at a small speed cost.
+/// `device="gpu"` computes NaN-free 2D batches in float32 on the GPU when the `gpu`
+/// feature is built and a device is available, falling back to the CPU otherwise.
 #[pyfunction]
+#[pyo3(signature = (data, window, skipna=false, min_periods=None, out=None, device="cpu", precise=None, align=None, nan_policy=None, config=None))]
+#[allow(clippy::too_many_arguments)]
 fn moving_average_rust<'py>(
     py: Python<'py>,
-    data: PyArray1<f64>,
-    window: usize
-) -> PyResult<Bound<'py, PyArray1<f64>>> {
-    let data_view = data.readonly();
-    let slice = data_view.as_slice().unwrap();
+    data: SeriesInput<'py>,
+    window: usize,
+    skipna: bool,
+    min_periods: Option<usize>,
+    out: Option<Bound<'py, PyAny>>,
+    device: &str,
+    precise: Option<bool>,
+    align: Option<Alignment>,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let precise = crate::config::resolve_precise(precise);
+    let policy = NanPolicy::resolve(nan_policy, skipna)?;
+    policy.check_input(&data)?;
+    let skipna = policy == NanPolicy::Omit;
+    if gpu::Device::parse(device)? == gpu::Device::Gpu && out.is_none() && !precise && align == Alignment::Valid {
+        // Without NaNs the skipna result is the plain moving average
+        if let Some(result) = gpu::moving_average(py, &data, window) {
+            return Ok(result);
+        }
+    }
+    let min_periods = if skipna { Some(skipna::min_periods(min_periods, window, window)?) } else { None };
+    let valid = |len: usize| (len + 1).saturating_sub(window);
+    if precise {
+        // Requiring every value to be valid gives the NaN-propagating average
+        let min_periods = min_periods.unwrap_or(window);
+        return match out {
+            Some(out) => map_series!(py, data, &out, |slice, dst| {
+                align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                    compensated::moving_average_into(slice, window, min_periods, dst)
+                })
+            }),
+            None => map_series!(py, data, |slice| {
+                compensated::moving_average(slice, window, min_periods).map(|r| align.pad(r, slice.len()))
+            }),
+        };
+    }
+    match (out, min_periods) {
+        (Some(out), Some(min_periods)) => map_series!(py, data, &out, |slice, dst| {
+            align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                skipna::moving_average_into(slice, window, min_periods, dst)
+            })
+        }),
+        (Some(out), None) => map_series!(py, data, &out, |slice, dst| {
+            align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                moving_average_into(slice, window, dst)
+            })
+        }),
+        (None, Some(min_periods)) => map_series!(py, data, |slice| {
+            skipna::moving_average(slice, window, min_periods).map(|r| align.pad(r, slice.len()))
+        }),
+        (None, None) => map_series!(py, data, |slice| moving_average(slice, window).map(|r| align.pad(r, slice.len()))),
+    }
+}
+
+/// Rolling standard deviation with `ddof` delta degrees of freedom over every full window
+/// of `slice`, from sliding sums of deviations from a recent value
+fn rolling_std<T: FloatElement>(slice: &[T], window: usize, ddof: usize) -> PyResult<Vec<T>> {
     let n = slice.len();
 
-    if window > n {
-        return Err(pyo3::exceptions::PyValueError::new(
-            "Window size must be <= data length"
-        ));
+    let valid = errors::check_window(window, n)?;
+    errors::check_ddof(ddof, window)?;
+    if valid == 0 {
+        return Ok(Vec::new());
+    }
+    if window == 1 {
+        // One value has no spread, but sliding sums would leave rounding residue
+        return Ok(slice.iter().map(|v| if v.is_finite() { T::zero() } else { T::nan() }).collect());
     }
 
-    let result_len = n - window + 1;
-    let mut result = Vec::with_capacity(result_len);
+    // Shifting by a value near the window keeps the sum of squares well conditioned. The
+    // sums cover finite values only; windows holding NaN or infinite values are NaN
+    let shift_for = |values: &[T]| values.iter().copied().find(|v| v.is_finite()).unwrap_or(T::zero());
+    let sums = |values: &[T], shift: T| {
+        values.iter().filter(|v| v.is_finite()).fold((T::zero(), T::zero()), |(sum, sum_sq), &v| {
+            let d = v - shift;
+            (sum + d, sum_sq + d * d)
+        })
+    };
+    let (count, dof) = (T::from(window).unwrap(), T::from(window - ddof).unwrap());
+    let std = |sum: T, sum_sq: T, nonfinite: usize| {
+        if nonfinite > 0 {
+            T::nan()
+        } else {
+            ((sum_sq - sum * sum / count) / dof).max(T::zero()).sqrt()
+        }
+    };
 
-    // Parallel calculation using Rayon
-    (0..result_len).into_par_iter().for_each(|i| {
-        let sum: f64 = slice[i..i + window].iter().sum();
-        result[i] = sum / window as f64;
-    });
+    let mut shift = shift_for(&slice[..window]);
+    let (mut sum, mut sum_sq) = sums(&slice[..window], shift);
+    let mut nonfinite = slice[..window].iter().filter(|v| !v.is_finite()).count();
+    let mut result = Vec::with_capacity(valid);
+    result.push(std(sum, sum_sq, nonfinite));
+    for i in 1..=n - window {
+        let (old, new) = (slice[i - 1], slice[i + window - 1]);
+        nonfinite = nonfinite + !new.is_finite() as usize - !old.is_finite() as usize;
+        if i % DRIFT_INTERVAL == 0 {
+            shift = shift_for(&slice[i..i + window]);
+            (sum, sum_sq) = sums(&slice[i..i + window], shift);
+        } else {
+            if new.is_finite() {
+                sum = sum + (new - shift);
+                sum_sq = sum_sq + (new - shift) * (new - shift);
+            }
+            if old.is_finite() {
+                sum = sum - (old - shift);
+                sum_sq = sum_sq - (old - shift) * (old - shift);
+            }
+        }
+        result.push(std(sum, sum_sq, nonfinite));
+    }
 
-    Ok(PyArray1::from_vec_bound(py, result))
+    validate::check("rolling_std", &result, || validate::reference::rolling_std(slice, window, ddof, window));
+    Ok(result)
 }
 
-/// Calculate RSI using optimized Rust implementation
+/// Rolling standard deviation over every full window (pandas' `rolling(window).std(ddof)`)
+///
+/// Accepts a single series, a 2D array with one series per row or a pyarrow record batch
+/// or Polars DataFrame with one per column (returned as the same type). With the default
+/// `nan_policy="propagate"` windows containing NaN are NaN; "raise" rejects NaN inputs
+/// and "omit" uses the valid values, NaN where fewer than `min_periods` (default
+/// `window`) are valid. `precise` uses compensated (Kahan-Neumaier) sliding sums for
+/// propagated NaNs.
+/// `align` is "valid", "same" (NaN-padded to the input length) or an integer shift;
+/// series shorter than the window give an empty result, or all NaN when aligned.
+/// `window=1` with `ddof=0` gives zeros; `window=0` or `ddof >= window` raises
+/// `InvalidWindowError`.
+/// `device="gpu"` computes NaN-free 2D batches in float32 on the GPU when the `gpu`
+/// feature is built and a device is available, falling back to the CPU otherwise.
 #[pyfunction]
-fn rsi_rust<'py>(
+#[pyo3(signature = (data, window, ddof=1, device="cpu", precise=None, align=None, min_periods=None, nan_policy=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+fn rolling_std_rust<'py>(
     py: Python<'py>,
-    data: PyArray1<f64>,
-    period: usize
-) -> PyResult<Bound<'py, PyArray1<f64>>> {
-    let data_view = data.readonly();
-    let slice = data_view.as_slice().unwrap();
-    let n = slice.len();
-
-    if period >= n {
-        return Err(pyo3::exceptions::PyValueError::new(
-            "Period must be < data length"
-        ));
+    data: SeriesInput<'py>,
+    window: usize,
+    ddof: usize,
+    device: &str,
+    precise: Option<bool>,
+    align: Option<Alignment>,
+    min_periods: Option<usize>,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let precise = crate::config::resolve_precise(precise);
+    let policy = NanPolicy::resolve(nan_policy, false)?;
+    policy.check_input(&data)?;
+    if policy == NanPolicy::Omit {
+        let min_periods = skipna::min_periods(min_periods, window, window)?;
+        return map_series!(py, data, |slice| {
+            skipna::rolling_std(slice, window, ddof, min_periods).map(|r| align.pad(r, slice.len()))
+        });
+    }
+    if gpu::Device::parse(device)? == gpu::Device::Gpu && !precise && align == Alignment::Valid {
+        if let Some(result) = gpu::rolling_std(py, &data, window, ddof) {
+            return Ok(result);
+        }
+    }
+    if precise {
+        return map_series!(py, data, |slice| {
+            compensated::rolling_std(slice, window, ddof).map(|r| align.pad(r, slice.len()))
+        });
     }
+    map_series!(py, data, |slice| rolling_std(slice, window, ddof).map(|r| align.pad(r, slice.len())))
+}
+
+/// RSI over simple averages of the last `period` changes
+fn rsi<T: FloatElement>(slice: &[T], period: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); slice.len().saturating_sub(period)];
+    rsi_into(slice, period, &mut result)?;
+    Ok(result)
+}
 
-    let result_len = n - period;
-    let mut result = Vec::with_capacity(result_len);
+/// `rsi` written into `out`, which must hold `len - period` values
+fn rsi_into<T: FloatElement>(slice: &[T], period: usize, out: &mut [T]) -> PyResult<()> {
+    let n = slice.len();
+
+    let valid = errors::check_period(period, n)?;
 
+    check_out(out, valid)?;
     for i in period..n {
-        let mut gains = 0.0;
-        let mut losses = 0.0;
+        let mut gains = T::zero();
+        let mut losses = T::zero();
 
         for j in (i - period + 1)..=i {
             let change = slice[j] - slice[j - 1];
-            if change > 0.0 {
-                gains += change;
+            if change > T::zero() {
+                gains = gains + change;
             } else {
-                losses -= change;
+                losses = losses - change;
             }
         }
 
-        let avg_gain = gains / period as f64;
-        let avg_loss = losses / period as f64;
+        let avg_gain = gains / T::from(period).unwrap();
+        let avg_loss = losses / T::from(period).unwrap();
+        let hundred = T::from(100.0).unwrap();
 
-        if avg_loss == 0.0 {
-            result.push(100.0);
+        out[i - period] = if avg_loss == T::zero() {
+            hundred
         } else {
             let rs = avg_gain / avg_loss;
-            result.push(100.0 - (100.0 / (1.0 + rs)));
+            hundred - (hundred / (T::one() + rs))
+        };
+    }
+
+    validate::check("rsi", out, || validate::reference::rsi(slice, period, period));
+    Ok(())
+}
+
+/// RSI with Wilder's smoothing, as on charting platforms: the average gain and loss are
+/// seeded with the simple average of the first `period` changes, then updated as
+/// `(avg * (period - 1) + change) / period`. A NaN change restarts the seeding.
+fn wilder_rsi<T: FloatElement>(slice: &[T], period: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); slice.len().saturating_sub(period)];
+    wilder_rsi_into(slice, period, &mut result)?;
+    Ok(result)
+}
+
+/// `wilder_rsi` written into `out`, which must hold `len - period` values
+fn wilder_rsi_into<T: FloatElement>(slice: &[T], period: usize, out: &mut [T]) -> PyResult<()> {
+    let n = slice.len();
+    let valid = errors::check_period(period, n)?;
+    check_out(out, valid)?;
+
+    let hundred = T::from(100.0).unwrap();
+    let (mut avg_gain, mut avg_loss, mut seen) = (T::zero(), T::zero(), 0usize);
+    for i in 1..n {
+        let change = slice[i] - slice[i - 1];
+        if change.is_nan() {
+            seen = 0;
+        } else {
+            let (gain, loss) = (change.max(T::zero()), (-change).max(T::zero()));
+            seen += 1;
+            // Running mean while seeding; the first change after a restart overwrites
+            let weight = T::from(seen.min(period)).unwrap();
+            avg_gain = avg_gain + (gain - avg_gain) / weight;
+            avg_loss = avg_loss + (loss - avg_loss) / weight;
+        }
+        if i >= period {
+            out[i - period] = if seen < period {
+                T::nan()
+            } else if avg_loss <= T::zero() {
+                hundred
+            } else {
+                hundred - hundred / (T::one() + avg_gain / avg_loss)
+            };
         }
     }
+    validate::check("wilder_rsi", out, || validate::reference::wilder_rsi(slice, period, period, true));
+    Ok(())
+}
 
-    Ok(PyArray1::from_vec_bound(py, result))
+/// RSI averaging scheme
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Smoothing {
+    Wilder,
+    Simple,
 }
 
-/// Fast correlation calculation
+impl Smoothing {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "wilder" => Ok(Smoothing::Wilder),
+            "simple" => Ok(Smoothing::Simple),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown smoothing '{}', expected 'wilder' or 'simple'", name
+            ))),
+        }
+    }
+}
+
+/// Calculate RSI using optimized Rust implementation
+///
+/// `smoothing="wilder"` (the default) uses Wilder's recursive averages, matching
+/// charting platforms and TA-Lib; `"simple"` averages the last `period` changes.
+/// Accepts a single series, a 2D array with one series per row or a pyarrow record batch
+/// or Polars DataFrame with one per column (returned as the same type). With the default
+/// `nan_policy="propagate"` NaNs propagate and restart Wilder's seeding; "raise" rejects
+/// NaN inputs; with "omit" (or `skipna=True`) changes touching a NaN are ignored and the
+/// RSI is NaN until `min_periods` (default `period`) valid changes count: of the last
+/// `period` changes for simple smoothing, of all changes so far for Wilder's. With `out`,
+/// results are written into that preallocated numpy array (length `len - period`), which
+/// is returned. `align="same"` NaN-pads the first `period` positions so the result (and
+/// `out`) has the input's length, and an integer `align=k` also shifts it `k` positions
+/// later. Series of `period` values or fewer give an empty result, or all NaN when
+/// aligned. `period=1` rates each change alone (0 after a fall, else 100); `period=0`
+/// raises `InvalidWindowError`.
 #[pyfunction]
-fn correlation_rust<'py>(
+#[pyo3(signature = (data, period, skipna=false, min_periods=None, out=None, align=None, smoothing="wilder", nan_policy=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+fn rsi_rust<'py>(
     py: Python<'py>,
-    x: PyArray1<f64>,
-    y: PyArray1<f64>
-) -> PyResult<f64> {
-    let x_view = x.readonly();
-    let y_view = y.readonly();
-    let x_slice = x_view.as_slice().unwrap();
-    let y_slice = y_view.as_slice().unwrap();
+    data: SeriesInput<'py>,
+    period: usize,
+    skipna: bool,
+    min_periods: Option<usize>,
+    out: Option<Bound<'py, PyAny>>,
+    align: Option<Alignment>,
+    smoothing: &str,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let smoothing = Smoothing::parse(smoothing)?;
+    let policy = NanPolicy::resolve(nan_policy, skipna)?;
+    policy.check_input(&data)?;
+    let skipna = policy == NanPolicy::Omit;
+    let min_periods = if skipna { Some(skipna::min_periods(min_periods, period, period)?) } else { None };
+    let valid = |len: usize| len.saturating_sub(period);
+    match (smoothing, out, min_periods) {
+        (Smoothing::Wilder, Some(out), Some(min_periods)) => map_series!(py, data, &out, |slice, dst| {
+            align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                skipna::wilder_rsi_into(slice, period, min_periods, dst)
+            })
+        }),
+        (Smoothing::Wilder, Some(out), None) => map_series!(py, data, &out, |slice, dst| {
+            align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                wilder_rsi_into(slice, period, dst)
+            })
+        }),
+        (Smoothing::Wilder, None, Some(min_periods)) => map_series!(py, data, |slice| {
+            skipna::wilder_rsi(slice, period, min_periods).map(|r| align.pad(r, slice.len()))
+        }),
+        (Smoothing::Wilder, None, None) => {
+            map_series!(py, data, |slice| wilder_rsi(slice, period).map(|r| align.pad(r, slice.len())))
+        }
+        (Smoothing::Simple, Some(out), Some(min_periods)) => map_series!(py, data, &out, |slice, dst| {
+            align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                skipna::rsi_into(slice, period, min_periods, dst)
+            })
+        }),
+        (Smoothing::Simple, Some(out), None) => map_series!(py, data, &out, |slice, dst| {
+            align.write_into(dst, slice.len(), valid(slice.len()), |dst| {
+                rsi_into(slice, period, dst)
+            })
+        }),
+        (Smoothing::Simple, None, Some(min_periods)) => map_series!(py, data, |slice| {
+            skipna::rsi(slice, period, min_periods).map(|r| align.pad(r, slice.len()))
+        }),
+        (Smoothing::Simple, None, None) => map_series!(py, data, |slice| rsi(slice, period).map(|r| align.pad(r, slice.len()))),
+    }
+}
 
+/// Pearson correlation of two equal-length slices, in two passes: the means, then the
+/// products of deviations from them, so a large common price level cannot cancel away
+/// the variance as it does in the sum-of-products formula. Deviations and sums are f64
+/// for float32 inputs too.
+fn correlation<T: FloatElement>(x_slice: &[T], y_slice: &[T]) -> PyResult<T> {
     if x_slice.len() != y_slice.len() {
-        return Err(pyo3::exceptions::PyValueError::new(
+        return Err(errors::LengthMismatchError::new_err(
             "Arrays must have the same length"
         ));
     }
+    if x_slice.len() < 2 {
+        return Ok(T::nan());
+    }
 
     let n = x_slice.len() as f64;
-    let sum_x: f64 = x_slice.iter().sum();
-    let sum_y: f64 = y_slice.iter().sum();
-    let sum_xy: f64 = x_slice.iter().zip(y_slice.iter()).map(|(a, b)| a * b).sum();
-    let sum_x2: f64 = x_slice.iter().map(|x| x * x).sum();
-    let sum_y2: f64 = y_slice.iter().map(|y| y * y).sum();
+    let (mean_x, mean_y) = (T::sum_f64(x_slice) / n, T::sum_f64(y_slice) / n);
+    let dx: Buffer<f64> = x_slice.iter().map(|v| v.to_f64().unwrap() - mean_x).collect();
+    let dy: Buffer<f64> = y_slice.iter().map(|v| v.to_f64().unwrap() - mean_y).collect();
+    let cov = simd::dot_f64(&dx, &dy);
+    let denominator = (simd::dot_f64(&dx, &dx) * simd::dot_f64(&dy, &dy)).sqrt();
 
-    let numerator = n * sum_xy - sum_x * sum_y;
-    let denominator = ((n * sum_x2 - sum_x * sum_x) * (n * sum_y2 - sum_y * sum_y)).sqrt();
+    let result = if denominator == 0.0 { T::zero() } else { T::from(cov / denominator).unwrap() };
+    validate::check("correlation", &[result], || validate::reference::correlation(x_slice, y_slice, None));
+    Ok(result)
+}
+
+/// Correlate two series, or two equal-shape batches row by row; `min_periods` selects
+/// the NaN-skipping kernel and `precise` the compensated one. The inputs are read in
+/// place without the GIL, like every kernel (see the module docs)
+fn correlate<'py, T: FloatElement>(
+    py: Python<'py>,
+    x: &Series<'py, T>,
+    y: &Series<'py, T>,
+    min_periods: Option<usize>,
+    precise: bool
+) -> PyResult<PyObject> {
+    let correlation = |x: &[T], y: &[T]| match min_periods {
+        _ if precise => compensated::correlation(x, y, min_periods),
+        Some(min_periods) => skipna::correlation(x, y, min_periods),
+        None => correlation(x, y),
+    };
+    match (x, y) {
+        (Series::Single(x), Series::Single(y)) => {
+            let (x, y) = (x.values()?, y.values()?);
+            Ok(py.allow_threads(|| correlation(&x, &y))?.to_f64().into_py(py))
+        }
+        (Series::Single(_), _) | (_, Series::Single(_)) => Err(pyo3::exceptions::PyTypeError::new_err(
+            "x and y must both be 1D series or both be 2D batches"
+        )),
+        (x, y) => {
+            let (x, y) = (x.rows()?, y.rows()?);
+            let (x, y) = (x.view(), y.view());
+            if x.shape() != y.shape() {
+                return Err(errors::LengthMismatchError::new_err(
+                    "Arrays must have the same shape"
+                ));
+            }
+            let rows = py.allow_threads(|| {
+                column::map_rows(x, |i, x_row| {
+                    let y_row = y.row(i);
+                    match y_row.as_slice() {
+                        Some(y_row) => correlation(x_row, y_row),
+                        None => correlation(x_row, &y_row.iter().copied().collect::<Buffer<T>>()),
+                    }
+                })
+            })?;
+            Ok(numpy::PyArray1::from_vec_bound(py, rows).into_any().unbind())
+        }
+    }
+}
 
-    if denominator == 0.0 {
-        Ok(0.0)
-    } else {
-        Ok(numerator / denominator)
+/// Fast correlation calculation
+///
+/// Two 2D arrays are correlated row by row, returning one coefficient per row. With the
+/// default `nan_policy="propagate"` any NaN makes the result NaN; "raise" rejects NaN
+/// inputs; with "omit" (or `skipna=True`) only pairs where both values are valid are
+/// used and the result is NaN with fewer than `min_periods` (default 2) such pairs.
+/// Fewer than 2 observations (including empty inputs) always give NaN.
+/// `precise` uses a two-pass algorithm with compensated (Kahan-Neumaier) sums.
+#[pyfunction]
+#[pyo3(signature = (x, y, skipna=false, min_periods=None, precise=None, nan_policy=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+fn correlation_rust<'py>(
+    py: Python<'py>,
+    x: SeriesInput<'py>,
+    y: SeriesInput<'py>,
+    skipna: bool,
+    min_periods: Option<usize>,
+    precise: Option<bool>,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let precise = crate::config::resolve_precise(precise);
+    let policy = NanPolicy::resolve(nan_policy, skipna)?;
+    policy.check_input(&x)?;
+    policy.check_input(&y)?;
+    let min_periods = (policy == NanPolicy::Omit).then(|| min_periods.unwrap_or(2));
+    match (&x, &y) {
+        (SeriesInput::F64(x), SeriesInput::F64(y)) => correlate(py, x, y, min_periods, precise),
+        (SeriesInput::F32(x), SeriesInput::F32(y)) => correlate(py, x, y, min_periods, precise),
+        _ => Err(pyo3::exceptions::PyTypeError::new_err(
+            "x and y must have the same dtype"
+        )),
     }
 }
 
+/// Rust kernels for Fortress.
+///
+/// Kernels release the GIL while they compute, reading numpy inputs in place rather than
+/// copying them. Python code in other threads must not write to an array while a kernel
+/// is reading it; pass a copy (`arr.copy()`) if it might. Arrow and Polars inputs are
+/// immutable and need no such care.
 #[pymodule]
-fn fast_math(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
+fn fast_math(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(moving_average_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_std_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(tdigest::rolling_quantile_approx_rust, m)?)?;
     m.add_function(wrap_pyfunction!(rsi_rust, m)?)?;
     m.add_function(wrap_pyfunction!(correlation_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(correlation_matrix::correlation_matrix_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(correlation_matrix::rolling_correlation_matrix_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(adf::adf_test_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cointegration::engle_granger_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cointegration::johansen_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(ou::ou_fit_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(ou::ou_half_life_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(ou::half_life_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(ou::ou_simulate_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(hurst::hurst_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(garch::garch_fit_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(garch::garch_forecast_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(arima::arima_fit_rust, m)?)?;
+    m.add_class::<arima::ArimaModel>()?;
+    m.add_function(wrap_pyfunction!(regression::ols_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(regression::ridge_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(regression::factor_regression_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pca::pca_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(bootstrap::bootstrap_ci_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(autocorrelation::acf_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(autocorrelation::pacf_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(autocorrelation::ljung_box_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(normality::jarque_bera_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(normality::ks_test_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(normality::anderson_darling_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(fracdiff::frac_diff_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(labeling::triple_barrier_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(labeling::concurrency_weights_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(labeling::average_uniqueness_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(labeling::attribution_weights_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(changepoint::cusum_filter_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(changepoint::pelt_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(futures::build_continuous_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(execution::twap_schedule_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(execution::vwap_schedule_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(execution::almgren_chriss_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(execution::estimate_tcost_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(routing::route_order_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(blotter::export_blotter_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(calendar::filter_sessions_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(merge::asof_join_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(merge::align_series_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(merge::events_to_grid_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(quality::quality_check_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(quality::fill_gaps_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(quality::repair_ticks_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(resample::resample_ohlcv_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(timezone::convert_tz_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(timezone::localize_session_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cross_section::cross_sectional_rank_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cross_section::cross_sectional_zscore_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cross_section::winsorize_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cross_section::clip_outliers_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pairs::spread_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pairs::log_spread_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pairs::pair_signal_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(exposure::beta_weighted_exposure_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(returns::simple_returns_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(returns::log_returns_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(returns::cumulative_returns_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(spectral::power_spectrum_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(spectral::dominant_cycle_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(wavelet::dwt_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(wavelet::wavelet_denoise_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(decompose::detrend_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(decompose::seasonal_decompose_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(levels::support_resistance_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(peaks::find_peaks_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(similarity::dtw_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(similarity::find_analogs_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(kmeans::kmeans_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(hmm::hmm_fit_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(hmm::hmm_decode_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(gbdt::load_model_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(gbdt::predict_rust, m)?)?;
+    m.add_class::<gbdt::TreeModel>()?;
+    m.add_function(wrap_pyfunction!(cv::purged_kfold_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cv::combinatorial_purged_cv_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(signals::signals_to_positions_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(netting::net_orders_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_profile::volume_profile_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(seasonality::seasonality_profile_rust, m)?)?;
+    m.add_class::<series_store::SharedSeriesStore>()?;
+    m.add_function(wrap_pyfunction!(currency::convert_pnl_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(corporate_actions::adjust_prices_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(attribution::attribute_pnl_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(limits::flag_limit_moves_rust, m)?)?;
+    m.add_class::<strategy::NativeStrategy>()?;
+    m.add_function(wrap_pyfunction!(strategy::available_strategies, m)?)?;
+    m.add_class::<replay::Replayer>()?;
+    m.add_class::<results::ResultSet>()?;
+    m.add_function(wrap_pyfunction!(ticks::round_to_tick_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(ticks::tick_sizes_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(ticks::round_to_lot_rust, m)?)?;
+    m.add_class::<costs::CostModel>()?;
+    m.add_class::<portfolio::Portfolio>()?;
+    m.add_class::<oms::OrderManager>()?;
+    m.add_class::<risk::RiskGate>()?;
+    m.add_class::<risk::RiskDecision>()?;
+    m.add_class::<calendar::TradingCalendar>()?;
+    m.add_function(wrap_pyfunction!(bars::ticks_to_volume_bars_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(bars::ticks_to_tick_bars_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(bars::ticks_to_dollar_bars_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(bars::renko_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(bars::range_bars_rust, m)?)?;
+    m.add_class::<bars::BarBuilder>()?;
+    m.add_function(wrap_pyfunction!(microstructure::classify_trades_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(microstructure::vpin_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(microstructure::aggregate_trades_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(orderbook::diff_book_snapshots_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(orderbook::apply_book_deltas_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(orderbook::book_features_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(csv_reader::read_ohlcv_csv_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(parquet_io::read_parquet_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(parquet_io::write_parquet_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(tick_file::write_ticks_rust, m)?)?;
+    m.add_class::<tick_file::TickFile>()?;
+    m.add_function(wrap_pyfunction!(tick_json::parse_ticks_json_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(tick_json::parse_ticks_msgpack_rust, m)?)?;
+    m.add_class::<ws_client::MarketDataClient>()?;
+    m.add_class::<frame::OhlcvFrame>()?;
+    m.add_class::<chunked::ChunkedProcessor>()?;
+    m.add_class::<streaming::RollingWindow>()?;
+    m.add_class::<streaming::StreamingSMA>()?;
+    m.add_class::<streaming::StreamingEMA>()?;
+    m.add_class::<streaming::StreamingRSI>()?;
+    m.add_class::<streaming::StreamingATR>()?;
+    m.add_class::<streaming::StreamingVWAP>()?;
+    m.add_class::<streaming::StreamingCovariance>()?;
+    m.add_class::<streaming::StreamingCorrelation>()?;
+    m.add_class::<streaming::StreamingDrawdown>()?;
+    m.add_class::<streaming::StreamingOutlierDetector>()?;
+    m.add_class::<tdigest::TDigest>()?;
+    m.add_class::<pipeline::IndicatorPipeline>()?;
+    m.add_function(wrap_pyfunction!(pipeline::process_stream_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::compute_features_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::compute_features_batch_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::build_feature_matrix_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::mtf_indicator_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(dispatch::compute_many_rust, m)?)?;
+    m.add_class::<expr::Expr>()?;
+    m.add_function(wrap_pyfunction!(expr::col, m)?)?;
+    m.add_function(wrap_pyfunction!(expr::lit, m)?)?;
+    m.add_function(wrap_pyfunction!(registry::create_indicator, m)?)?;
+    m.add_function(wrap_pyfunction!(registry::available_indicators, m)?)?;
+
+    m.add_function(wrap_pyfunction!(threads::set_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(threads::get_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(rng::set_seed, m)?)?;
+    m.add_function(wrap_pyfunction!(rng::get_seed, m)?)?;
+    m.add_class::<config::FastMathConfig>()?;
+    m.add_function(wrap_pyfunction!(config::set_config, m)?)?;
+    m.add_function(wrap_pyfunction!(config::get_config, m)?)?;
+    m.add_function(wrap_pyfunction!(gpu::gpu_available, m)?)?;
+
+    let fix_module = PyModule::new_bound(py, "fix")?;
+    fix::register(&fix_module)?;
+    m.add_submodule(&fix_module)?;
+
+    let bench_module = PyModule::new_bound(py, "bench")?;
+    bench::register(&bench_module)?;
+    m.add_submodule(&bench_module)?;
+
+    let options_module = PyModule::new_bound(py, "options")?;
+    options::register(&options_module)?;
+    m.add_submodule(&options_module)?;
+
+    let errors_module = PyModule::new_bound(py, "errors")?;
+    errors::register(&errors_module)?;
+    m.add_submodule(&errors_module)?;
+
+    let tracing_module = PyModule::new_bound(py, "tracing")?;
+    telemetry::register(&tracing_module, m)?;
+    m.add_submodule(&tracing_module)?;
+
+    let cache_module = PyModule::new_bound(py, "cache")?;
+    cache::register(&cache_module, m)?;
+    m.add_submodule(&cache_module)?;
+
+    #[cfg(feature = "validate")]
+    {
+        let validation_module = PyModule::new_bound(py, "validation")?;
+        validate::register(&validation_module)?;
+        m.add_submodule(&validation_module)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infinities_leave_the_sliding_sums_intact() {
+        let values = [1.0, f64::INFINITY, 1.0, 1.0, 1.0];
+        assert_eq!(moving_average(&values, 2).unwrap(), vec![f64::INFINITY, f64::INFINITY, 1.0, 1.0]);
+        let std = rolling_std(&values, 2, 1).unwrap();
+        assert!(std[0].is_nan() && std[1].is_nan());
+        assert_eq!(&std[2..], &[0.0, 0.0]);
+
+        let values = [1.0f32, f32::INFINITY, f32::NEG_INFINITY, 2.0, 4.0];
+        let means = moving_average(&values, 2).unwrap();
+        assert!(means[1].is_nan());
+        assert_eq!(means[3], 3.0);
+    }
+
+    fn series(n: usize) -> Vec<f64> {
+        (0..n).map(|i| 100.0 + (i as f64 * 0.37).sin() * 5.0 + i as f64 * 0.01).collect()
+    }
+
+    #[test]
+    fn moving_average_writes_every_window() {
+        let mut x = series(5000);
+        x[2000] = f64::NAN;
+        let result = moving_average(&x, 7).unwrap();
+        assert_eq!(result.len(), x.len() - 6);
+        for (i, &value) in result.iter().enumerate() {
+            let expected = x[i..i + 7].iter().sum::<f64>() / 7.0;
+            if expected.is_nan() {
+                assert!(value.is_nan(), "window {}", i);
+            } else {
+                assert!((value - expected).abs() < 1e-9, "window {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn same_alignment_lines_windows_up_with_their_last_row() {
+        let x = series(20);
+        let same = Alignment::Same.pad(moving_average(&x, 5).unwrap(), x.len());
+        assert_eq!(same.len(), x.len());
+        assert!(same[..4].iter().all(|v| v.is_nan()));
+        for i in 4..x.len() {
+            assert!((same[i] - x[i - 4..=i].iter().sum::<f64>() / 5.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn short_inputs_give_empty_results() {
+        for n in 0..4 {
+            let x = series(n);
+            let windows = (n + 1).saturating_sub(3);
+            assert_eq!(moving_average(&x, 3).unwrap().len(), windows);
+            assert_eq!(rolling_std(&x, 3, 1).unwrap().len(), windows);
+            assert_eq!(skipna::moving_average(&x, 3, 1).unwrap().len(), windows);
+            assert_eq!(compensated::rolling_std(&x, 3, 1).unwrap().len(), windows);
+            assert_eq!(rsi(&x, 3).unwrap().len(), n.saturating_sub(3));
+            assert_eq!(wilder_rsi(&x, 3).unwrap().len(), n.saturating_sub(3));
+            let same = Alignment::Same.pad(moving_average(&x, 3).unwrap(), n);
+            assert_eq!(same.len(), n);
+            assert!(correlation(&x[..n.min(1)], &x[..n.min(1)]).unwrap().is_nan());
+        }
+    }
+
+    #[test]
+    fn degenerate_windows() {
+        let x = series(100);
+        assert_eq!(moving_average(&x, 1).unwrap(), x);
+        assert_eq!(skipna::moving_average(&x, 1, 1).unwrap(), x);
+        assert!(rolling_std(&x, 1, 0).unwrap().iter().all(|&v| v == 0.0));
+        assert_eq!(rsi(&[1.0, 2.0, 1.0], 1).unwrap(), vec![100.0, 0.0]);
+        assert_eq!(wilder_rsi(&[1.0, 2.0, 1.0], 1).unwrap(), vec![100.0, 0.0]);
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let window_error = |result: PyResult<Vec<f64>>| result.unwrap_err().is_instance_of::<errors::InvalidWindowError>(py);
+            assert!(window_error(moving_average(&x, 0)));
+            assert!(window_error(rolling_std(&x, 0, 0)));
+            assert!(window_error(rolling_std(&x, 1, 1)));
+            assert!(window_error(rsi(&x, 0)));
+            assert!(window_error(wilder_rsi(&x, 0)));
+        });
+    }
+}
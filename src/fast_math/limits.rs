@@ -0,0 +1,152 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use numpy::{PyArray1, PyReadonlyArray2};
+
+use crate::column::Column;
+
+/// Price limits of each bar, before they are resolved against the reference prices
+enum Limits {
+    /// Allowed move as a fraction of the reference price, one for every bar or per bar
+    Pct(Vec<f64>),
+    /// Explicit lower and upper bands per bar
+    Bands(Vec<f64>, Vec<f64>),
+}
+
+impl Limits {
+    /// A number or per-bar column of fractions, a `(lower, upper)` tuple of columns or a
+    /// 2D array with lower and upper columns
+    fn parse(value: &Bound<'_, PyAny>, n: usize) -> PyResult<Self> {
+        if let Ok(pct) = value.extract::<f64>() {
+            return Ok(Limits::Pct(vec![pct; n]));
+        }
+        if let Ok(pair) = value.downcast::<PyTuple>() {
+            let (lower, upper): (Column<'_, f64>, Column<'_, f64>) = pair.extract()?;
+            return Ok(Limits::Bands(lower.values()?.into_owned(), upper.values()?.into_owned()));
+        }
+        if let Ok(bands) = value.extract::<PyReadonlyArray2<'_, f64>>() {
+            let bands = bands.as_array();
+            if bands.ncols() != 2 {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Limit bands must have lower and upper columns"
+                ));
+            }
+            return Ok(Limits::Bands(bands.column(0).to_vec(), bands.column(1).to_vec()));
+        }
+        Ok(Limits::Pct(value.extract::<Column<'_, f64>>()?.values()?.into_owned()))
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Limits::Pct(pct) => pct.len(),
+            Limits::Bands(lower, _) => lower.len(),
+        }
+    }
+}
+
+/// Limit flags of one bar
+#[derive(Default, Clone, Copy)]
+struct BarFlags {
+    limit_up: bool,
+    limit_down: bool,
+    /// The whole bar traded at or beyond the limit, leaving no price to fill the other side
+    locked_up: bool,
+    locked_down: bool,
+    halted: bool,
+}
+
+/// Flag bars that hit exchange price limits or were halted, so backtests can skip
+/// fills that could not have happened.
+///
+/// `ohlc` is a 2D array with open, high, low, close and optionally volume columns.
+/// `limit_pct_or_bands` is either the allowed move as a fraction of the reference price
+/// (0.1 for ±10%), one number or one per bar, or the limit prices themselves as a
+/// `(lower, upper)` tuple of columns or a 2D array with those two columns. The reference
+/// is the previous bar's close unless `reference` (such as the prior settlement) is
+/// given, so the first bar has no percentage limits. A bar is `limit_up` when its high
+/// reaches the upper limit, within `tick_size`, and `locked_up` when its low does too, so
+/// it only traded at the limit and nothing could be bought; `limit_down` and
+/// `locked_down` likewise at the lower limit. Bars with NaN prices or zero volume are
+/// `halted`. Returns a dict of bool arrays of those five flags and `unfillable` (locked
+/// either way or halted), plus the `lower` and `upper` limits of each bar, NaN where
+/// there are none.
+#[pyfunction]
+#[pyo3(signature = (ohlc, limit_pct_or_bands, reference=None, tick_size=0.0))]
+pub fn flag_limit_moves_rust<'py>(
+    py: Python<'py>,
+    ohlc: PyReadonlyArray2<'py, f64>,
+    limit_pct_or_bands: &Bound<'py, PyAny>,
+    reference: Option<Column<'py, f64>>,
+    tick_size: f64
+) -> PyResult<Bound<'py, PyDict>> {
+    let ohlc = ohlc.as_array();
+    if !(4..=5).contains(&ohlc.ncols()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "ohlc must have open, high, low, close and optionally volume columns"
+        ));
+    }
+    if !tick_size.is_finite() || tick_size < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tick_size must be a finite value >= 0"
+        ));
+    }
+    let n = ohlc.nrows();
+    let limits = Limits::parse(limit_pct_or_bands, n)?;
+    let reference = reference.as_ref().map(|r| r.values()).transpose()?;
+    if limits.len() != n || reference.as_ref().is_some_and(|r| r.len() != n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "limit_pct_or_bands and reference must have one entry per bar"
+        ));
+    }
+    if let Limits::Pct(pct) = &limits {
+        if pct.iter().any(|&p| p <= 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Limit percentages must be > 0"
+            ));
+        }
+    }
+
+    let (lower, upper, flags): (Vec<f64>, Vec<f64>, Vec<BarFlags>) = py.allow_threads(|| {
+        let mut lower = Vec::with_capacity(n);
+        let mut upper = Vec::with_capacity(n);
+        let mut flags = Vec::with_capacity(n);
+        for i in 0..n {
+            let (low_limit, high_limit) = match &limits {
+                Limits::Bands(lo, hi) => (lo[i], hi[i]),
+                Limits::Pct(pct) => {
+                    let base = match &reference {
+                        Some(reference) => reference[i],
+                        None if i > 0 => ohlc[[i - 1, 3]],
+                        None => f64::NAN,
+                    };
+                    (base * (1.0 - pct[i]), base * (1.0 + pct[i]))
+                }
+            };
+            let row = ohlc.row(i);
+            let (high, low) = (row[1], row[2]);
+            let halted = row.iter().take(4).any(|v| v.is_nan()) || (ohlc.ncols() > 4 && row[4] == 0.0);
+            // NaN limits compare false, leaving bars without limits unflagged
+            flags.push(BarFlags {
+                limit_up: high >= high_limit - tick_size,
+                limit_down: low <= low_limit + tick_size,
+                locked_up: low >= high_limit - tick_size,
+                locked_down: high <= low_limit + tick_size,
+                halted,
+            });
+            lower.push(low_limit);
+            upper.push(high_limit);
+        }
+        (lower, upper, flags)
+    });
+
+    let result = PyDict::new_bound(py);
+    let flag = |f: fn(&BarFlags) -> bool| PyArray1::from_iter_bound(py, flags.iter().map(f));
+    result.set_item("limit_up", flag(|f| f.limit_up))?;
+    result.set_item("limit_down", flag(|f| f.limit_down))?;
+    result.set_item("locked_up", flag(|f| f.locked_up))?;
+    result.set_item("locked_down", flag(|f| f.locked_down))?;
+    result.set_item("halted", flag(|f| f.halted))?;
+    result.set_item("unfillable", flag(|f| f.locked_up || f.locked_down || f.halted))?;
+    result.set_item("lower", PyArray1::from_vec_bound(py, lower))?;
+    result.set_item("upper", PyArray1::from_vec_bound(py, upper))?;
+    Ok(result)
+}
@@ -0,0 +1,216 @@
+// Small dense linear algebra shared by the regression and time-series models: least
+// squares by Householder QR, Cholesky factors with triangular solves, symmetric
+// eigendecomposition and a general solver. The matrices involved are a few columns wide,
+// so these favour accuracy over blocking.
+
+use ndarray::Array2;
+
+/// Least-squares fit of a regression
+pub struct Fit {
+    pub params: Vec<f64>,
+    pub ssr: f64,
+    /// Diagonal of `(X'X)^-1`, which scales the variance of each coefficient
+    pub inverse_diag: Vec<f64>,
+}
+
+/// Least squares of `y` on `columns` by Householder QR, which stays accurate for the
+/// badly scaled trend and level columns; None when the design is rank deficient
+pub fn ols(mut columns: Vec<Vec<f64>>, mut y: Vec<f64>) -> Option<Fit> {
+    let (n, k) = (y.len(), columns.len());
+    if n <= k {
+        return None;
+    }
+    let scale: Vec<f64> = columns.iter().map(|c| c.iter().map(|v| v * v).sum::<f64>().sqrt()).collect();
+    for j in 0..k {
+        let norm = columns[j][j..].iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm.is_nan() || norm <= 1e-10 * scale[j] {
+            return None;
+        }
+        let alpha = if columns[j][j] > 0.0 { -norm } else { norm };
+        let mut v = columns[j][j..].to_vec();
+        v[0] -= alpha;
+        let v_norm_sq: f64 = v.iter().map(|x| x * x).sum();
+        let reflect = |values: &mut [f64]| {
+            let d = 2.0 * v.iter().zip(values.iter()).map(|(a, b)| a * b).sum::<f64>() / v_norm_sq;
+            values.iter_mut().zip(&v).for_each(|(x, a)| *x -= d * a);
+        };
+        for column in &mut columns[j..] {
+            reflect(&mut column[j..]);
+        }
+        reflect(&mut y[j..]);
+    }
+
+    // R[i][j] is columns[j][i] for i <= j
+    let mut params = vec![0.0; k];
+    for i in (0..k).rev() {
+        let rest: f64 = (i + 1..k).map(|j| columns[j][i] * params[j]).sum();
+        params[i] = (y[i] - rest) / columns[i][i];
+    }
+    let ssr = y[k..].iter().map(|v| v * v).sum();
+
+    // (X'X)^-1 = R^-1 R^-T, so its diagonal is the squared row norms of R^-1, solved
+    // here column by column
+    let inverse: Vec<Vec<f64>> = (0..k)
+        .map(|c| {
+            let mut column = vec![0.0; c + 1];
+            for i in (0..=c).rev() {
+                let identity = if i == c { 1.0 } else { 0.0 };
+                let rest: f64 = (i + 1..=c).map(|j| columns[j][i] * column[j]).sum();
+                column[i] = (identity - rest) / columns[i][i];
+            }
+            column
+        })
+        .collect();
+    let inverse_diag = (0..k).map(|i| inverse[i..].iter().map(|column| column[i] * column[i]).sum()).collect();
+    Some(Fit { params, ssr, inverse_diag })
+}
+
+/// Lower-triangular Cholesky factor of a symmetric positive definite matrix
+pub fn cholesky(a: &Array2<f64>) -> Option<Array2<f64>> {
+    let n = a.nrows();
+    let mut l = Array2::zeros((n, n));
+    for i in 0..n {
+        for j in 0..=i {
+            let rest: f64 = (0..j).map(|k| l[[i, k]] * l[[j, k]]).sum();
+            if i == j {
+                let d = a[[i, i]] - rest;
+                if d.is_nan() || d <= 0.0 {
+                    return None;
+                }
+                l[[i, i]] = d.sqrt();
+            } else {
+                l[[i, j]] = (a[[i, j]] - rest) / l[[j, j]];
+            }
+        }
+    }
+    Some(l)
+}
+
+/// `L^-1 b` for a lower-triangular `L`
+pub fn solve_lower(l: &Array2<f64>, b: &Array2<f64>) -> Array2<f64> {
+    let mut x = b.clone();
+    for mut column in x.columns_mut() {
+        for i in 0..l.nrows() {
+            let rest: f64 = (0..i).map(|k| l[[i, k]] * column[k]).sum();
+            column[i] = (column[i] - rest) / l[[i, i]];
+        }
+    }
+    x
+}
+
+/// `L^-T b` for a lower-triangular `L`
+pub fn solve_upper(l: &Array2<f64>, b: &Array2<f64>) -> Array2<f64> {
+    let n = l.nrows();
+    let mut x = b.clone();
+    for mut column in x.columns_mut() {
+        for i in (0..n).rev() {
+            let rest: f64 = (i + 1..n).map(|k| l[[k, i]] * column[k]).sum();
+            column[i] = (column[i] - rest) / l[[i, i]];
+        }
+    }
+    x
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix by cyclic Jacobi
+/// rotations, which are accurate for the small matrices of a basket
+pub fn symmetric_eigen(mut a: Array2<f64>) -> (Vec<f64>, Array2<f64>) {
+    let n = a.nrows();
+    let mut vectors = Array2::eye(n);
+    for _ in 0..100 {
+        let off: f64 = (0..n).flat_map(|i| (0..i).map(move |j| (i, j))).map(|(i, j)| a[[i, j]] * a[[i, j]]).sum();
+        let diag: f64 = (0..n).map(|i| a[[i, i]] * a[[i, i]]).sum();
+        if off <= 1e-30 * diag || off == 0.0 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]] == 0.0 {
+                    continue;
+                }
+                let theta = (a[[q, q]] - a[[p, p]]) / (2.0 * a[[p, q]]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[[k, p]], a[[k, q]]);
+                    a[[k, p]] = c * akp - s * akq;
+                    a[[k, q]] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[[p, k]], a[[q, k]]);
+                    a[[p, k]] = c * apk - s * aqk;
+                    a[[q, k]] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (vectors[[k, p]], vectors[[k, q]]);
+                    vectors[[k, p]] = c * vkp - s * vkq;
+                    vectors[[k, q]] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[[i, i]]).collect(), vectors)
+}
+
+/// Solve `a x = b` by Gaussian elimination with partial pivoting; None when singular
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            if factor != 0.0 {
+                a[row][col..].iter_mut().zip(&pivot_row[col..]).for_each(|(x, p)| *x -= factor * p);
+                b[row] -= factor * b[col];
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let rest: f64 = (i + 1..n).map(|k| a[i][k] * x[k]).sum();
+        x[i] = (b[i] - rest) / a[i][i];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::array;
+
+    #[test]
+    fn cholesky_reconstructs_and_solves() {
+        let a = array![[4.0, 2.0, 0.6], [2.0, 5.0, 1.0], [0.6, 1.0, 3.0]];
+        let l = cholesky(&a).unwrap();
+        assert!((l.dot(&l.t()) - &a).iter().all(|d| d.abs() < 1e-12));
+        let inverse = solve_upper(&l, &solve_lower(&l, &Array2::eye(3)));
+        assert!((a.dot(&inverse) - Array2::<f64>::eye(3)).iter().all(|d| d.abs() < 1e-12));
+        assert!(cholesky(&array![[1.0, 2.0], [2.0, 1.0]]).is_none());
+    }
+
+    #[test]
+    fn symmetric_eigen_diagonalises() {
+        let a = array![[2.0, 1.0, 0.0], [1.0, 3.0, 1.0], [0.0, 1.0, 4.0]];
+        let (values, vectors) = symmetric_eigen(a.clone());
+        for (i, value) in values.iter().enumerate() {
+            let v = vectors.column(i);
+            assert!((a.dot(&v) - &v * *value).iter().all(|d| d.abs() < 1e-10));
+        }
+        assert!((values.iter().sum::<f64>() - 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn solve_pivots_and_detects_singular_systems() {
+        let x = solve(vec![vec![0.0, 1.0], vec![2.0, 1.0]], vec![3.0, 7.0]).unwrap();
+        assert!((x[0] - 2.0).abs() < 1e-12 && (x[1] - 3.0).abs() < 1e-12);
+        assert!(solve(vec![vec![1.0, 2.0], vec![2.0, 4.0]], vec![1.0, 2.0]).is_none());
+    }
+}
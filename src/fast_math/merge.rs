@@ -0,0 +1,366 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2};
+use ndarray::Array2;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// Which right row an as-of join matches each left row to
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// The last row at or before the left timestamp
+    Backward,
+    /// The first row at or after it
+    Forward,
+    /// Whichever of the two is closer, the earlier on a tie
+    Nearest,
+}
+
+impl Direction {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "backward" => Ok(Direction::Backward),
+            "forward" => Ok(Direction::Forward),
+            "nearest" => Ok(Direction::Nearest),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown direction '{}', expected 'backward', 'forward' or 'nearest'", name
+            ))),
+        }
+    }
+}
+
+/// Index into the ascending `right` matched to `t`, if any lies within `tolerance`
+pub(crate) fn asof_index(right: &[i64], t: i64, direction: Direction, tolerance: Option<i64>, exact: bool) -> Option<usize> {
+    let before = if exact { right.partition_point(|&r| r <= t) } else { right.partition_point(|&r| r < t) };
+    let after = if exact { right.partition_point(|&r| r < t) } else { right.partition_point(|&r| r <= t) };
+    let backward = before.checked_sub(1);
+    let forward = (after < right.len()).then_some(after);
+    let index = match direction {
+        Direction::Backward => backward,
+        Direction::Forward => forward,
+        Direction::Nearest => match (backward, forward) {
+            (Some(b), Some(f)) => Some(if right[f].abs_diff(t) < right[b].abs_diff(t) { f } else { b }),
+            (b, f) => b.or(f),
+        },
+    };
+    index.filter(|&i| tolerance.is_none_or(|tol| right[i].abs_diff(t) <= tol as u64))
+}
+
+/// As-of join of `right_values` onto the `left_ts` timestamps, like pandas' `merge_asof`.
+///
+/// Each left timestamp is matched to the last right row at or before it
+/// (`direction="backward"`, the default, e.g. the prevailing quote of each trade), the
+/// first at or after it ("forward") or the closer of the two ("nearest", the earlier on
+/// a tie). With duplicate right timestamps "backward" takes the last of them and
+/// "forward" the first. `right_ts` must be sorted ascending; `left_ts` may be in any
+/// order. Matches further than `tolerance` (in timestamp units) away are dropped, and
+/// `allow_exact_matches=False` only matches strictly earlier or later rows. Returns
+/// `(values, index)`: the matched right values, NaN where nothing matched, and the
+/// matched right row indices, -1 where nothing matched, for joining further columns.
+#[pyfunction]
+#[pyo3(signature = (left_ts, right_ts, right_values, tolerance=None, direction="backward", allow_exact_matches=true))]
+pub fn asof_join_rust<'py>(
+    py: Python<'py>,
+    left_ts: Column<'py, i64>,
+    right_ts: Column<'py, i64>,
+    right_values: Column<'py, f64>,
+    tolerance: Option<i64>,
+    direction: &str,
+    allow_exact_matches: bool
+) -> PyResult<PyObject> {
+    let direction = Direction::parse(direction)?;
+    let left = left_ts.values()?;
+    let right = right_ts.values()?;
+    let values = right_values.values()?;
+    if right.len() != values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "right_ts and right_values must have the same length"
+        ));
+    }
+    if right.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "right_ts must be sorted ascending"
+        ));
+    }
+    if tolerance.is_some_and(|t| t < 0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tolerance must be >= 0"
+        ));
+    }
+
+    let (joined, index): (Vec<f64>, Vec<i64>) = py.allow_threads(|| {
+        threads::install(|| {
+            left.par_iter()
+                .map(|&t| match asof_index(&right, t, direction, tolerance, allow_exact_matches) {
+                    Some(i) => (values[i], i as i64),
+                    None => (f64::NAN, -1),
+                })
+                .unzip()
+        })
+    });
+    Ok((PyArray1::from_vec_bound(py, joined), PyArray1::from_vec_bound(py, index)).into_py(py))
+}
+
+/// How `align_series_rust` builds the common timeline and fills it
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Timestamps present in every series
+    Inner,
+    /// Timestamps present in any series, NaN where a series has none
+    Outer,
+    /// Timestamps present in any series, each carrying its last value forward
+    Ffill,
+}
+
+impl Method {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "inner" => Ok(Method::Inner),
+            "outer" => Ok(Method::Outer),
+            "ffill" | "pad" => Ok(Method::Ffill),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'inner', 'outer' or 'ffill'", name
+            ))),
+        }
+    }
+}
+
+/// The common timeline of strictly ascending series
+fn timeline(series: &[&[i64]], method: Method) -> Vec<i64> {
+    let mut times: Vec<i64> = series.iter().flat_map(|ts| ts.iter().copied()).collect();
+    times.sort_unstable();
+    if method != Method::Inner {
+        times.dedup();
+        return times;
+    }
+    // Each series holds a time at most once, so it is in all of them when it occurs
+    // `series.len()` times
+    times
+        .chunk_by(|a, b| a == b)
+        .filter(|run| run.len() == series.len())
+        .map(|run| run[0])
+        .collect()
+}
+
+/// One series' values on `times`, which contains all of its timestamps unless inner
+fn reindex(ts: &[i64], values: &[f64], times: &[i64], method: Method) -> Vec<f64> {
+    let mut out = Vec::with_capacity(times.len());
+    let (mut j, mut last) = (0, f64::NAN);
+    for &t in times {
+        while j < ts.len() && ts[j] < t {
+            last = values[j];
+            j += 1;
+        }
+        if j < ts.len() && ts[j] == t {
+            out.push(values[j]);
+        } else if method == Method::Ffill {
+            out.push(last);
+        } else {
+            out.push(f64::NAN);
+        }
+    }
+    out
+}
+
+/// Align several time series onto one timeline, for multi-asset work.
+///
+/// `list_of_ts[i]` are the strictly ascending timestamps of `list_of_values[i]`.
+/// `method="inner"` keeps the timestamps present in every series; "outer" keeps those
+/// present in any, with NaN where a series has no value; "ffill" (the default) does the
+/// same but carries each series' last value forward, leaving NaN before its first.
+/// Returns a dict with the common `timestamp` and `values`, a 2D array with one row per
+/// timestamp and one column per series.
+#[pyfunction]
+#[pyo3(signature = (list_of_ts, list_of_values, method="ffill"))]
+pub fn align_series_rust<'py>(
+    py: Python<'py>,
+    list_of_ts: Vec<Column<'py, i64>>,
+    list_of_values: Vec<Column<'py, f64>>,
+    method: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let method = Method::parse(method)?;
+    if list_of_ts.len() != list_of_values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "list_of_ts and list_of_values must have the same number of series"
+        ));
+    }
+    let timestamps = list_of_ts.iter().map(|c| c.values()).collect::<PyResult<Vec<_>>>()?;
+    let values = list_of_values.iter().map(|c| c.values()).collect::<PyResult<Vec<_>>>()?;
+    for (i, (ts, v)) in timestamps.iter().zip(&values).enumerate() {
+        if ts.len() != v.len() {
+            return Err(crate::errors::LengthMismatchError::new_err(format!(
+                "series {} has {} timestamps but {} values", i, ts.len(), v.len()
+            )));
+        }
+        if ts.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "series {} timestamps must be strictly ascending", i
+            )));
+        }
+    }
+
+    let (times, aligned) = py.allow_threads(|| {
+        let series: Vec<&[i64]> = timestamps.iter().map(|ts| &ts[..]).collect();
+        let times = timeline(&series, method);
+        let columns: Vec<Vec<f64>> = threads::install(|| {
+            series
+                .par_iter()
+                .zip(values.par_iter())
+                .map(|(ts, v)| reindex(ts, v, &times, method))
+                .collect()
+        });
+        let aligned = Array2::from_shape_fn((times.len(), columns.len()), |(i, j)| columns[j][i]);
+        (times, aligned)
+    });
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_vec_bound(py, times))?;
+    result.set_item("values", PyArray2::from_owned_array_bound(py, aligned))?;
+    Ok(result)
+}
+
+/// How `events_to_grid_rust` combines the events falling in one bar
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Agg {
+    First,
+    Last,
+    Sum,
+    Mean,
+    Count,
+    Min,
+    Max,
+}
+
+impl Agg {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "first" => Ok(Agg::First),
+            "last" => Ok(Agg::Last),
+            "sum" => Ok(Agg::Sum),
+            "mean" => Ok(Agg::Mean),
+            "count" => Ok(Agg::Count),
+            "min" => Ok(Agg::Min),
+            "max" => Ok(Agg::Max),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown agg '{}', expected 'last', 'first', 'sum', 'mean', 'count', 'min' or 'max'", name
+            ))),
+        }
+    }
+}
+
+/// The events of one bar so far; `first` and `last` keep the value with its time
+#[derive(Clone, Copy)]
+struct Bucket {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+    first: (i64, f64),
+    last: (i64, f64),
+}
+
+impl Bucket {
+    const EMPTY: Bucket = Bucket {
+        count: 0,
+        sum: 0.0,
+        min: f64::INFINITY,
+        max: f64::NEG_INFINITY,
+        first: (i64::MAX, f64::NAN),
+        last: (i64::MIN, f64::NAN),
+    };
+
+    fn add(&mut self, t: i64, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if t < self.first.0 {
+            self.first = (t, value);
+        }
+        if t >= self.last.0 {
+            self.last = (t, value);
+        }
+    }
+
+    fn value(&self, agg: Agg) -> f64 {
+        if self.count == 0 {
+            return if agg == Agg::Sum { 0.0 } else { f64::NAN };
+        }
+        match agg {
+            Agg::First => self.first.1,
+            Agg::Last => self.last.1,
+            Agg::Sum => self.sum,
+            Agg::Mean => self.sum / self.count as f64,
+            Agg::Count => self.count as f64,
+            Agg::Min => self.min,
+            Agg::Max => self.max,
+        }
+    }
+}
+
+/// Bucket irregular events onto a bar grid, such as news scores or funding prints.
+///
+/// `bar_times` are the strictly ascending bar timestamps. With `closed="left"` (the
+/// default, bars stamped with their open) an event at `t` belongs to the last bar at or
+/// before it, with "right" (bars stamped with their close) to the first bar at or after
+/// it; events before the first bar, or after the last with "right", are dropped, and
+/// the last bar, or the first with "right", is open-ended. The events of each bar are
+/// combined by `agg`: "last" (the default) or "first" by event time, the later array
+/// position winning ties, "sum", "mean", "count", "min" or "max", all ignoring NaN
+/// values. `event_times` need not be sorted. Returns one value per bar: NaN for bars
+/// without events, except 0 for "sum" and "count", the count as int64.
+#[pyfunction]
+#[pyo3(signature = (event_times, event_values, bar_times, agg="last", closed="left"))]
+pub fn events_to_grid_rust<'py>(
+    py: Python<'py>,
+    event_times: Column<'py, i64>,
+    event_values: Column<'py, f64>,
+    bar_times: Column<'py, i64>,
+    agg: &str,
+    closed: &str
+) -> PyResult<PyObject> {
+    let agg = Agg::parse(agg)?;
+    let right = match closed.to_ascii_lowercase().as_str() {
+        "left" => false,
+        "right" => true,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown closed '{}', expected 'left' or 'right'", closed
+            )))
+        }
+    };
+    let (times, values, bars) = (event_times.values()?, event_values.values()?, bar_times.values()?);
+    if times.len() != values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "event_times and event_values must have the same length"
+        ));
+    }
+    if bars.windows(2).any(|w| w[1] <= w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "bar_times must be strictly ascending"
+        ));
+    }
+
+    let buckets = py.allow_threads(|| {
+        let mut buckets = vec![Bucket::EMPTY; bars.len()];
+        for (&t, &value) in times.iter().zip(values.iter()) {
+            if value.is_nan() {
+                continue;
+            }
+            let bar = if right {
+                Some(bars.partition_point(|&b| b < t)).filter(|&i| i < bars.len())
+            } else {
+                bars.partition_point(|&b| b <= t).checked_sub(1)
+            };
+            if let Some(i) = bar {
+                buckets[i].add(t, value);
+            }
+        }
+        buckets
+    });
+    if agg == Agg::Count {
+        return Ok(PyArray1::from_iter_bound(py, buckets.iter().map(|b| b.count as i64)).into_any().unbind());
+    }
+    Ok(PyArray1::from_iter_bound(py, buckets.iter().map(|b| b.value(agg))).into_any().unbind())
+}
@@ -0,0 +1,416 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use num_traits::AsPrimitive;
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::column::{ArrayKind, Column, NumericColumn};
+
+/// VPIN per bucket and the index of the bar that completed each bucket
+pub type VpinArrays = (PyObject, PyObject);
+
+/// Tick rule: +1 on an uptick, -1 on a downtick, sign of the last price change on a zero tick
+fn tick_rule(price: f64, last_price: Option<f64>, last_tick_sign: i8) -> i8 {
+    match last_price {
+        Some(prev) if price > prev => 1,
+        Some(prev) if price < prev => -1,
+        _ => last_tick_sign,
+    }
+}
+
+/// Classify trades as buyer- (+1) or seller-initiated (-1) with the Lee-Ready algorithm.
+///
+/// Each trade is matched to the last quote with `quote_time <= trade_time - quote_lag`.
+/// Trades above the quote midpoint are buys, below are sells; trades at the midpoint
+/// or without a prevailing quote fall back to the tick rule. Trades that cannot be
+/// classified (e.g. the first trade at the midpoint) are 0. Both time columns must be
+/// sorted ascending.
+#[pyfunction]
+#[pyo3(signature = (trade_prices, trade_times, bid, ask, quote_times, quote_lag=0))]
+pub fn classify_trades_rust<'py>(
+    py: Python<'py>,
+    trade_prices: Column<'py, f64>,
+    trade_times: Column<'py, i64>,
+    bid: Column<'py, f64>,
+    ask: Column<'py, f64>,
+    quote_times: Column<'py, i64>,
+    quote_lag: i64
+) -> PyResult<PyObject> {
+    let kind = ArrayKind::of(&[trade_prices.kind(), trade_times.kind(), bid.kind(), ask.kind(), quote_times.kind()]);
+    let trade_prices = trade_prices.values()?;
+    let trade_times = trade_times.values()?;
+    let bid = bid.values()?;
+    let ask = ask.values()?;
+    let quote_times = quote_times.values()?;
+
+    if trade_prices.len() != trade_times.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "trade_prices and trade_times must have the same length"
+        ));
+    }
+    if bid.len() != ask.len() || ask.len() != quote_times.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "bid, ask and quote_times must have the same length"
+        ));
+    }
+
+    let result = py.allow_threads(|| {
+        let mut result = Vec::with_capacity(trade_prices.len());
+        let mut quote_idx = 0;
+        let mut last_price: Option<f64> = None;
+        let mut last_tick_sign = 0i8;
+
+        for (&price, &time) in trade_prices.iter().zip(trade_times.iter()) {
+            // As-of match: advance to the last quote at or before the lagged trade time
+            let cutoff = time.saturating_sub(quote_lag);
+            while quote_idx < quote_times.len() && quote_times[quote_idx] <= cutoff {
+                quote_idx += 1;
+            }
+
+            let quote_sign = if quote_idx > 0 {
+                let mid = 0.5 * (bid[quote_idx - 1] + ask[quote_idx - 1]);
+                if price > mid {
+                    1
+                } else if price < mid {
+                    -1
+                } else {
+                    0
+                }
+            } else {
+                0
+            };
+
+            let tick_sign = tick_rule(price, last_price, last_tick_sign);
+            result.push(if quote_sign != 0 { quote_sign } else { tick_sign });
+
+            last_price = Some(price);
+            last_tick_sign = tick_sign;
+        }
+        result
+    });
+
+    kind.wrap(py, result)
+}
+
+/// Fraction of the bucket volume below which a bucket counts as full
+const FILL_TOLERANCE: f64 = 1e-9;
+
+/// VPIN per completed bucket and the index of the bar that completed it
+fn vpin<V: AsPrimitive<f64>>(prices: &[f64], volumes: &[V], bucket_volume: f64, window: usize) -> PyResult<(Vec<f64>, Vec<i64>)> {
+    if prices.len() != volumes.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "prices and volumes must have the same length"
+        ));
+    }
+
+    let changes: Vec<f64> = prices.windows(2).map(|w| w[1] - w[0]).collect();
+    let normal = Normal::new(0.0, 1.0).unwrap();
+    // Welford count, mean and sum of squared deviations of the changes before the bar
+    // being classified, so no bucket is classified with later prices
+    let (mut seen, mut mean, mut m2) = (0usize, 0.0, 0.0);
+
+    let mut imbalances = Vec::new();
+    let mut bucket_ends = Vec::new();
+    let mut filled = 0.0;
+    let mut buy = 0.0;
+    let mut sell = 0.0;
+
+    for (i, volume) in volumes.iter().enumerate() {
+        // The first bar has no price change to classify it by, so it splits evenly, as do
+        // bars before two earlier changes give a volatility
+        let change = i.checked_sub(1).map(|j| changes[j]);
+        let sigma = if seen > 1 { (m2 / (seen - 1) as f64).sqrt() } else { 0.0 };
+        let buy_fraction = match change {
+            Some(change) if sigma > 0.0 && change.is_finite() => normal.cdf(change / sigma),
+            _ => 0.5,
+        };
+        if let Some(change) = change.filter(|c| c.is_finite()) {
+            seen += 1;
+            let delta = change - mean;
+            mean += delta / seen as f64;
+            m2 += delta * (change - mean);
+        }
+        let mut remaining: f64 = volume.as_();
+        if remaining.is_infinite() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Volumes must be finite"
+            ));
+        }
+
+        while remaining > 0.0 {
+            let space = bucket_volume - filled;
+            let take = remaining.min(space);
+            buy += take * buy_fraction;
+            sell += take * (1.0 - buy_fraction);
+            filled += take;
+            remaining -= take;
+
+            // Rounding in `filled` must not leave a bucket a hair short of closing
+            if space - take <= FILL_TOLERANCE * bucket_volume {
+                imbalances.push((buy - sell).abs());
+                bucket_ends.push(i as i64);
+                filled = 0.0;
+                buy = 0.0;
+                sell = 0.0;
+            }
+        }
+    }
+
+    let mut vpin = vec![f64::NAN; imbalances.len()];
+    let mut rolling = 0.0;
+    for k in 0..imbalances.len() {
+        rolling += imbalances[k];
+        if k >= window {
+            rolling -= imbalances[k - window];
+        }
+        if k + 1 >= window {
+            vpin[k] = rolling / (window as f64 * bucket_volume);
+        }
+    }
+
+    Ok((vpin, bucket_ends))
+}
+
+/// Compute VPIN over equal-volume buckets using bulk volume classification.
+///
+/// Each bar's volume is split into buy/sell volume with `Phi(dP / sigma(dP))` and poured
+/// into buckets of `bucket_volume`, splitting bars across bucket boundaries. `sigma(dP)`
+/// is the standard deviation of the price changes before the bar, so buckets only use
+/// prices known when they fill; bars before two such changes, the first among them,
+/// and bars whose price change is not finite (a NaN price on either side) count as half
+/// buy and half sell. VPIN for a bucket is the mean absolute order imbalance of the last
+/// `window` buckets divided by the bucket volume. Returns the VPIN per completed bucket (NaN until `window` buckets
+/// exist) and the index of the bar that completed each bucket. Volumes may be float64
+/// or int64.
+#[pyfunction]
+pub fn vpin_rust<'py>(
+    py: Python<'py>,
+    prices: Column<'py, f64>,
+    volumes: NumericColumn<'py>,
+    bucket_volume: f64,
+    window: usize
+) -> PyResult<VpinArrays> {
+    let kind = ArrayKind::of(&[prices.kind(), volumes.kind()]);
+    let prices = prices.values()?;
+
+    if !bucket_volume.is_finite() || bucket_volume <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Bucket volume must be a finite value > 0"
+        ));
+    }
+    crate::errors::require_window(window)?;
+
+    let (vpin, bucket_ends) = match &volumes {
+        NumericColumn::Float(volumes) => {
+            let volumes = volumes.values()?;
+            py.allow_threads(|| vpin(&prices, &volumes, bucket_volume, window))?
+        }
+        NumericColumn::Int(volumes) => {
+            let volumes = volumes.values()?;
+            py.allow_threads(|| vpin(&prices, &volumes, bucket_volume, window))?
+        }
+    };
+    Ok((kind.wrap(py, vpin)?, kind.wrap(py, bucket_ends)?))
+}
+
+/// Fills merged into one parent trade by `aggregate_trades_rust`
+struct ParentTrade {
+    first: usize,
+    start: i64,
+    end: i64,
+    fills: usize,
+    size: f64,
+    notional: f64,
+    first_price: f64,
+    last_price: f64,
+    /// +1 buy, -1 sell; 0 while the price has not moved, when no sides are given
+    side: i8,
+    /// Tick-rule sign of the first fill, the side of a parent whose price never moved
+    entry_tick: i8,
+    levels: usize,
+}
+
+impl ParentTrade {
+    fn new(first: usize, time: i64, price: f64, size: f64, side: i8, entry_tick: i8) -> Self {
+        ParentTrade {
+            first,
+            start: time,
+            end: time,
+            fills: 1,
+            size,
+            notional: price * size,
+            first_price: price,
+            last_price: price,
+            side,
+            entry_tick,
+            levels: 1,
+        }
+    }
+
+    /// Whether a fill continues this parent: within `max_gap` of its last fill and either
+    /// on the same given side or, without sides, not moving the price against it
+    fn continues(&self, time: i64, price: f64, side: Option<i8>, max_gap: i64) -> bool {
+        if time - self.end > max_gap {
+            return false;
+        }
+        match side {
+            Some(side) => side == self.side,
+            None => {
+                let direction = tick_rule(price, Some(self.last_price), 0);
+                direction == 0 || self.side == 0 || direction == self.side
+            }
+        }
+    }
+
+    fn add(&mut self, time: i64, price: f64, size: f64, infer_side: bool) {
+        if infer_side && self.side == 0 {
+            self.side = tick_rule(price, Some(self.last_price), 0);
+        }
+        if price != self.last_price {
+            self.levels += 1;
+        }
+        self.end = time;
+        self.fills += 1;
+        self.size += size;
+        self.notional += price * size;
+        self.last_price = price;
+    }
+}
+
+/// Merge consecutive fills into parent trades, see `aggregate_trades_rust`
+fn aggregate_trades(timestamps: &[i64], prices: &[f64], sizes: &[f64], sides: Option<&[i8]>, max_gap: i64) -> Vec<ParentTrade> {
+    let mut parents: Vec<ParentTrade> = Vec::new();
+    let mut tick = 0i8;
+    for i in 0..timestamps.len() {
+        let (time, price, size) = (timestamps[i], prices[i], sizes[i]);
+        tick = tick_rule(price, i.checked_sub(1).map(|j| prices[j]), tick);
+        let side = sides.map(|s| s[i]);
+        match parents.last_mut() {
+            Some(parent) if parent.continues(time, price, side, max_gap) => parent.add(time, price, size, side.is_none()),
+            _ => parents.push(ParentTrade::new(i, time, price, size, side.unwrap_or(0), tick)),
+        }
+    }
+    if sides.is_none() {
+        for parent in parents.iter_mut().filter(|p| p.side == 0) {
+            parent.side = parent.entry_tick;
+        }
+    }
+    parents
+}
+
+/// Merge child fills into parent trades and flag sweeps and blocks, for order flow
+/// analysis.
+///
+/// Fills (sorted by `timestamps`, in nanoseconds) join the current parent while they
+/// arrive within `max_gap_us` microseconds of its previous fill and trade on the same
+/// side: the given `sides` (+1 buy, -1 sell, e.g. from `classify_trades_rust`), or
+/// without them, while the price does not move against the parent's direction, so a buy
+/// sweeping up the book stays one parent until the price ticks down. Parents whose
+/// price never moved take the tick-rule side of their first fill. A parent trading at
+/// `min_levels` or more prices is a sweep, and one of at least `block_size` in total
+/// size a block. Returns a dict of arrays with one entry per parent: `start_time`,
+/// `end_time`, the `first_index` of its fills and their count `n_fills`, total `size`,
+/// `vwap`, `first_price`, `last_price`, `side`, the number of price `levels` and the
+/// `is_sweep` and `is_block` flags.
+#[pyfunction]
+#[pyo3(signature = (timestamps, prices, sizes, max_gap_us=0, sides=None, min_levels=2, block_size=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn aggregate_trades_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    sizes: Column<'py, f64>,
+    max_gap_us: i64,
+    sides: Option<Column<'py, i8>>,
+    min_levels: usize,
+    block_size: Option<f64>
+) -> PyResult<Bound<'py, PyDict>> {
+    let (timestamps, prices, sizes) = (timestamps.values()?, prices.values()?, sizes.values()?);
+    let sides = sides.as_ref().map(|s| s.values()).transpose()?;
+    let n = timestamps.len();
+    if prices.len() != n || sizes.len() != n || sides.as_ref().is_some_and(|s| s.len() != n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps, prices, sizes and sides must have the same length"
+        ));
+    }
+    if max_gap_us < 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "max_gap_us must be >= 0"
+        ));
+    }
+    if timestamps.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "timestamps must be sorted ascending"
+        ));
+    }
+    if prices.iter().chain(sizes.iter()).any(|v| v.is_nan()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "prices and sizes must not contain NaN"
+        ));
+    }
+    let max_gap = max_gap_us.saturating_mul(1000);
+    let parents = py.allow_threads(|| aggregate_trades(&timestamps, &prices, &sizes, sides.as_deref(), max_gap));
+
+    let result = PyDict::new_bound(py);
+    result.set_item("start_time", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.start)))?;
+    result.set_item("end_time", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.end)))?;
+    result.set_item("first_index", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.first as i64)))?;
+    result.set_item("n_fills", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.fills as i64)))?;
+    result.set_item("size", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.size)))?;
+    result.set_item("vwap", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.notional / p.size)))?;
+    result.set_item("first_price", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.first_price)))?;
+    result.set_item("last_price", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.last_price)))?;
+    result.set_item("side", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.side)))?;
+    result.set_item("levels", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.levels as i64)))?;
+    result.set_item("is_sweep", PyArray1::from_iter_bound(py, parents.iter().map(|p| p.levels >= min_levels)))?;
+    let is_block = parents.iter().map(|p| block_size.is_some_and(|block| p.size >= block));
+    result.set_item("is_block", PyArray1::from_iter_bound(py, is_block))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vpin_buckets_the_first_bar() {
+        let (values, ends) = vpin(&[1.0, 2.0, 3.0], &[10.0, 5.0, 5.0], 10.0, 1).unwrap();
+        // The first bar fills a bucket on its own, split evenly between buyers and sellers
+        assert_eq!(ends, vec![0, 2]);
+        assert_eq!(values[0], 0.0);
+    }
+
+    #[test]
+    fn vpin_rejects_infinite_volume() {
+        pyo3::prepare_freethreaded_python();
+        assert!(vpin(&[1.0, 2.0], &[1.0, f64::INFINITY], 10.0, 1).is_err());
+    }
+
+    #[test]
+    fn vpin_closes_buckets_left_short_by_rounding() {
+        let (_, ends) = vpin(&[1.0, 2.0], &[0.7, 0.3], 1.0, 1).unwrap();
+        assert_eq!(ends, vec![1]);
+    }
+
+    #[test]
+    fn vpin_classifies_with_a_trailing_sigma() {
+        let prices = [0.0, 1.0, 3.0, 4.0, 20.0];
+        let (full, _) = vpin(&prices, &[1.0; 5], 1.0, 1).unwrap();
+        let (prefix, _) = vpin(&prices[..4], &[1.0; 4], 1.0, 1).unwrap();
+        // Later prices must not change how earlier bars were classified
+        assert_eq!(&full[..4], &prefix[..]);
+        // Bar 3 moves by 1 against a trailing sigma of 1/sqrt(2), so its imbalance is erf(1)
+        assert!((full[3] - 0.842_700_792_949_715).abs() < 1e-9);
+        assert_eq!(&full[..3], &[0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn vpin_splits_bars_around_a_nan_price_evenly() {
+        let prices = [0.0, 1.0, 3.0, f64::NAN, 4.0, 6.0, 5.0];
+        let (values, _) = vpin(&prices, &[1.0; 7], 1.0, 2).unwrap();
+        assert!(values[1..].iter().all(|v| v.is_finite()), "{:?}", values);
+        // Both changes touching the NaN split evenly, so buckets 3 and 4 are balanced
+        assert_eq!(values[4], 0.0);
+        assert!(values[5] > 0.0 && values[6] > 0.0);
+    }
+}
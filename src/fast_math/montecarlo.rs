@@ -0,0 +1,875 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray2, PyArray3, PyReadonlyArray2};
+use ndarray::{Array2, Array3};
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::config::FastMathConfig;
+use crate::linalg;
+use crate::options::OptionType;
+use crate::rng::{self, SplitMix};
+use crate::threads;
+
+/// Antithetic pairs (or single paths) simulated per batch, each batch on its own stream
+const BATCH: usize = 256;
+
+/// How an Asian option averages the monitored prices
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Average {
+    Arithmetic,
+    Geometric,
+}
+
+/// Knock-in or knock-out barrier, above or below the spot
+#[derive(Clone, Copy)]
+struct Barrier {
+    level: f64,
+    up: bool,
+    knock_in: bool,
+}
+
+impl Barrier {
+    fn parse(level: f64, kind: &str) -> PyResult<Self> {
+        let (up, knock_in) = match kind.to_ascii_lowercase().replace('_', "-").as_str() {
+            "up-and-out" => (true, false),
+            "up-and-in" => (true, true),
+            "down-and-out" => (false, false),
+            "down-and-in" => (false, true),
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown barrier_type '{}', expected 'up-and-out', 'up-and-in', 'down-and-out' or 'down-and-in'",
+                    kind
+                )))
+            }
+        };
+        Ok(Barrier { level, up, knock_in })
+    }
+
+    fn crossed(&self, spot: f64) -> bool {
+        if self.up { spot >= self.level } else { spot <= self.level }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    European,
+    Asian(Average),
+    Barrier(Barrier),
+}
+
+/// Payoff of the simulated option
+#[derive(Clone, Copy)]
+struct Payoff {
+    style: Style,
+    strike: f64,
+    option_type: OptionType,
+}
+
+/// Value of `key` in `dict`, which must be present
+fn required<'py, T: FromPyObject<'py>>(dict: &Bound<'py, PyDict>, key: &str, name: &str) -> PyResult<T> {
+    match dict.get_item(key)? {
+        Some(value) => value.extract(),
+        None => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} needs a '{}' key", name, key
+        ))),
+    }
+}
+
+impl Payoff {
+    /// Parse a dict with `type` ("european", "asian" or "barrier"), `strike`, an optional
+    /// `option_type` ("call" by default), `average` for Asian options and `barrier` with
+    /// `barrier_type` for barrier options
+    fn parse(spec: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let kind: String = required(spec, "type", "payoff_spec")?;
+        let strike: f64 = required(spec, "strike", "payoff_spec")?;
+        let option_type = match spec.get_item("option_type")? {
+            Some(name) => OptionType::parse(&name.extract::<String>()?)?,
+            None => OptionType::Call,
+        };
+        let style = match kind.to_ascii_lowercase().as_str() {
+            "european" => Style::European,
+            "asian" => {
+                let average = match spec.get_item("average")? {
+                    Some(name) => name.extract::<String>()?.to_ascii_lowercase(),
+                    None => "arithmetic".to_string(),
+                };
+                match average.as_str() {
+                    "arithmetic" => Style::Asian(Average::Arithmetic),
+                    "geometric" => Style::Asian(Average::Geometric),
+                    _ => {
+                        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                            "Unknown average '{}', expected 'arithmetic' or 'geometric'", average
+                        )))
+                    }
+                }
+            }
+            "barrier" => {
+                let level = required(spec, "barrier", "A barrier payoff_spec")?;
+                let kind: String = required(spec, "barrier_type", "A barrier payoff_spec")?;
+                Style::Barrier(Barrier::parse(level, &kind)?)
+            }
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown payoff type '{}', expected 'european', 'asian' or 'barrier'", kind
+                )))
+            }
+        };
+        if !(strike >= 0.0 && strike.is_finite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "payoff_spec strike must be a finite value >= 0"
+            ));
+        }
+        Ok(Payoff { style, strike, option_type })
+    }
+
+    fn value(&self, spot: f64) -> f64 {
+        (self.option_type.sign() * (spot - self.strike)).max(0.0)
+    }
+}
+
+/// Geometric Brownian motion under the risk-neutral measure
+#[derive(Clone, Copy)]
+struct Model {
+    spot: f64,
+    rate: f64,
+    volatility: f64,
+    /// Time to expiry in years
+    tte: f64,
+    dividend: f64,
+}
+
+impl Model {
+    /// Parse a dict with `spot`, `rate`, `volatility`, `tte` and an optional `dividend`
+    fn parse(params: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let model = Model {
+            spot: required(params, "spot", "model_params")?,
+            rate: required(params, "rate", "model_params")?,
+            volatility: required(params, "volatility", "model_params")?,
+            tte: required(params, "tte", "model_params")?,
+            dividend: params.get_item("dividend")?.map(|d| d.extract()).transpose()?.unwrap_or(0.0),
+        };
+        if model.spot.is_nan() || model.spot <= 0.0 || model.tte.is_nan() || model.tte <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "model_params spot and tte must be > 0"
+            ));
+        }
+        if model.volatility.is_nan() || model.volatility < 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "model_params volatility must be >= 0"
+            ));
+        }
+        Ok(model)
+    }
+}
+
+/// Undiscounted payoff of one path driven by the standard normal `shocks`, one per step,
+/// each multiplied by `sign` (-1 for the antithetic path)
+fn path_payoff(payoff: &Payoff, model: &Model, shocks: &[f64], sign: f64) -> f64 {
+    let dt = model.tte / shocks.len() as f64;
+    let drift = (model.rate - model.dividend - 0.5 * model.volatility * model.volatility) * dt;
+    let diffusion = model.volatility * dt.sqrt();
+    let mut log_spot = model.spot.ln();
+    let (mut sum, mut log_sum, mut crossed) = (0.0, 0.0, false);
+    for z in shocks {
+        log_spot += drift + diffusion * sign * z;
+        let spot = log_spot.exp();
+        sum += spot;
+        log_sum += log_spot;
+        if let Style::Barrier(barrier) = payoff.style {
+            crossed |= barrier.crossed(spot);
+        }
+    }
+    let n = shocks.len() as f64;
+    match payoff.style {
+        Style::European => payoff.value(log_spot.exp()),
+        Style::Asian(Average::Arithmetic) => payoff.value(sum / n),
+        Style::Asian(Average::Geometric) => payoff.value((log_sum / n).exp()),
+        Style::Barrier(barrier) => {
+            if crossed == barrier.knock_in { payoff.value(log_spot.exp()) } else { 0.0 }
+        }
+    }
+}
+
+/// Overwrite `shocks` with independent standard normals
+fn fill_normals(rng: &mut SplitMix, shocks: &mut [f64]) {
+    for pair in shocks.chunks_mut(2) {
+        let (a, b) = rng.normal_pair();
+        pair[0] = a;
+        if let Some(second) = pair.get_mut(1) {
+            *second = b;
+        }
+    }
+}
+
+/// Sum, sum of squares and count of the samples of batch `batch`: antithetic pair means,
+/// or single paths
+fn simulate_batch(
+    payoff: &Payoff,
+    model: &Model,
+    samples: usize,
+    n_steps: usize,
+    antithetic: bool,
+    seed: u64,
+    batch: usize
+) -> (f64, f64, usize) {
+    let mut rng = SplitMix::new(seed, batch as u64);
+    let mut shocks = vec![0.0; n_steps];
+    let count = BATCH.min(samples - batch * BATCH);
+    let (mut sum, mut squares) = (0.0, 0.0);
+    for _ in 0..count {
+        fill_normals(&mut rng, &mut shocks);
+        let sample = if antithetic {
+            0.5 * (path_payoff(payoff, model, &shocks, 1.0) + path_payoff(payoff, model, &shocks, -1.0))
+        } else {
+            path_payoff(payoff, model, &shocks, 1.0)
+        };
+        sum += sample;
+        squares += sample * sample;
+    }
+    (sum, squares, count)
+}
+
+/// Monte Carlo price of a European, Asian or barrier option under geometric Brownian
+/// motion.
+///
+/// `payoff_spec` is a dict with `type` ("european", "asian" or "barrier"), `strike` and
+/// `option_type` ("call", the default, or "put"); Asian options add `average`
+/// ("arithmetic", the default, or "geometric") over the `n_steps` simulated prices after
+/// the start, and barrier options `barrier` and `barrier_type` ("up-and-out", "up-and-in",
+/// "down-and-out" or "down-and-in"), monitored at each step (so a discretely monitored
+/// barrier; use many steps to approach a continuous one). `model_params` is a dict with
+/// `spot`, `rate`, `volatility`, `tte` (years) and an optional continuous `dividend`
+/// yield. `n_paths` paths are simulated in parallel batches, each seeded from `seed`
+/// (default: the `set_seed` seed) and its index so results are reproducible whatever the
+/// thread count; with `antithetic`
+/// (the default) half of them mirror the other half's shocks. Returns `(price,
+/// std_error)`, the discounted mean payoff and its standard error.
+#[pyfunction]
+#[pyo3(signature = (payoff_spec, model_params, n_paths=100_000, n_steps=252, seed=None, antithetic=true, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn mc_price_rust(
+    py: Python<'_>,
+    payoff_spec: &Bound<'_, PyDict>,
+    model_params: &Bound<'_, PyDict>,
+    n_paths: usize,
+    n_steps: usize,
+    seed: Option<u64>,
+    antithetic: bool,
+    config: Option<PyRef<'_, FastMathConfig>>
+) -> PyResult<(f64, f64)> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    let payoff = Payoff::parse(payoff_spec)?;
+    let model = Model::parse(model_params)?;
+    if n_steps == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_steps must be > 0"
+        ));
+    }
+    let samples = if antithetic { n_paths / 2 } else { n_paths };
+    if samples < 2 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_paths must give at least 2 samples (4 paths with antithetic=True)"
+        ));
+    }
+
+    let (sum, squares, count) = py.allow_threads(|| {
+        let batches: Vec<(f64, f64, usize)> = threads::install(|| {
+            (0..samples.div_ceil(BATCH))
+                .into_par_iter()
+                .map(|batch| simulate_batch(&payoff, &model, samples, n_steps, antithetic, seed, batch))
+                .collect()
+        });
+        // Summed in batch order, as a parallel reduction would round differently
+        // depending on how the batches were split between threads
+        batches.into_iter().fold((0.0, 0.0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
+    });
+    let n = count as f64;
+    let mean = sum / n;
+    let variance = ((squares - n * mean * mean) / (n - 1.0)).max(0.0);
+    let discount = (-model.rate * model.tte).exp();
+    Ok((discount * mean, discount * (variance / n).sqrt()))
+}
+
+/// One simulated path per index, in parallel, each on its own stream of `seed`
+pub fn simulate_paths<R: Send>(n_paths: usize, seed: u64, path: impl Fn(&mut SplitMix) -> R + Sync) -> Vec<R> {
+    threads::install(|| {
+        (0..n_paths)
+            .into_par_iter()
+            .map(|index| path(&mut SplitMix::new(seed, index as u64)))
+            .collect()
+    })
+}
+
+/// Paths of `width` values each as the rows of one array
+pub fn stack_paths(paths: Vec<Vec<f64>>, width: usize) -> Array2<f64> {
+    Array2::from_shape_vec((paths.len(), width), paths.concat()).unwrap()
+}
+
+/// Geometric Brownian motion path from `s0`, exact at each of the `n_steps` steps
+fn gbm_path(s0: f64, mu: f64, sigma: f64, n_steps: usize, dt: f64, rng: &mut SplitMix) -> Vec<f64> {
+    let drift = (mu - 0.5 * sigma * sigma) * dt;
+    let diffusion = sigma * dt.sqrt();
+    let mut path = Vec::with_capacity(n_steps + 1);
+    let mut log_spot = s0.ln();
+    path.push(s0);
+    while path.len() <= n_steps {
+        let (a, b) = rng.normal_pair();
+        for z in [a, b].into_iter().take(n_steps + 1 - path.len()) {
+            log_spot += drift + diffusion * z;
+            path.push(log_spot.exp());
+        }
+    }
+    path
+}
+
+/// Simulate geometric Brownian motion paths.
+///
+/// Each of the `n_paths` paths starts at `s0` and takes `n_steps` steps of `dt` years
+/// with drift `mu` and volatility `sigma` (both annualised), sampled exactly from the
+/// log-normal transition. Paths are generated in parallel, each on its own random stream
+/// seeded from `seed` (by default the one given to `set_seed`) and the path's index, so
+/// results are reproducible whatever the thread count. Returns an array of shape
+/// `(n_paths, n_steps + 1)` with `s0` in the first column.
+#[pyfunction]
+#[pyo3(signature = (s0, mu, sigma, n_paths=10_000, n_steps=252, dt=1.0 / 252.0, seed=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn gbm_paths_rust<'py>(
+    py: Python<'py>,
+    s0: f64,
+    mu: f64,
+    sigma: f64,
+    n_paths: usize,
+    n_steps: usize,
+    dt: f64,
+    seed: Option<u64>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    if s0.is_nan() || s0 <= 0.0 || sigma.is_nan() || sigma < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "s0 must be > 0 and sigma >= 0"
+        ));
+    }
+    if !mu.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "mu must be finite"
+        ));
+    }
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    let paths = py.allow_threads(|| {
+        stack_paths(simulate_paths(n_paths, seed, |rng| gbm_path(s0, mu, sigma, n_steps, dt, rng)), n_steps + 1)
+    });
+    Ok(PyArray2::from_owned_array_bound(py, paths))
+}
+
+/// Correlated geometric Brownian motions: one path of every asset, flattened step-major
+fn correlated_path(
+    s0: &[f64],
+    drift: &[f64],
+    factor: &Array2<f64>,
+    n_steps: usize,
+    dt: f64,
+    rng: &mut SplitMix
+) -> Vec<f64> {
+    let n = s0.len();
+    let mut log_spot: Vec<f64> = s0.iter().map(|s| s.ln()).collect();
+    let mut shocks = vec![0.0; n];
+    let mut path = Vec::with_capacity((n_steps + 1) * n);
+    path.extend_from_slice(s0);
+    for _ in 0..n_steps {
+        fill_normals(rng, &mut shocks);
+        for (i, log_spot) in log_spot.iter_mut().enumerate() {
+            let shock: f64 = (0..=i).map(|k| factor[[i, k]] * shocks[k]).sum();
+            *log_spot += drift[i] * dt + dt.sqrt() * shock;
+            path.push(log_spot.exp());
+        }
+    }
+    path
+}
+
+/// Simulate correlated geometric Brownian motion paths of several assets, for portfolio
+/// Monte Carlo.
+///
+/// `s0_vec` and `mu_vec` hold each asset's starting price and annualised drift, and
+/// `cov_matrix` the annualised covariance of their log returns, which must be symmetric
+/// positive definite. Its Cholesky factor turns independent normals into correlated
+/// shocks, and each of the `n_steps` steps of `dt` years is sampled exactly from the
+/// joint log-normal transition. Paths are generated in parallel, each on its own random
+/// stream seeded from `seed` (or `set_seed`'s) and the path's index. Returns an array of
+/// shape `(n_paths, n_steps + 1, n_assets)` with `s0_vec` at step 0.
+#[pyfunction]
+#[pyo3(signature = (s0_vec, mu_vec, cov_matrix, n_paths=10_000, n_steps=252, seed=None, dt=1.0 / 252.0, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn correlated_paths_rust<'py>(
+    py: Python<'py>,
+    s0_vec: Column<'py, f64>,
+    mu_vec: Column<'py, f64>,
+    cov_matrix: PyReadonlyArray2<'py, f64>,
+    n_paths: usize,
+    n_steps: usize,
+    seed: Option<u64>,
+    dt: f64,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyArray3<f64>>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    let s0 = s0_vec.values()?;
+    let mu = mu_vec.values()?;
+    let cov = cov_matrix.as_array().to_owned();
+    let n = s0.len();
+    if mu.len() != n || cov.dim() != (n, n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "mu_vec must match s0_vec, and cov_matrix be n_assets x n_assets"
+        ));
+    }
+    if s0.iter().any(|s| s.is_nan() || *s <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "s0_vec must be > 0"
+        ));
+    }
+    if mu.iter().any(|m| !m.is_finite()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "mu_vec must be finite"
+        ));
+    }
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    let symmetric = (0..n).all(|i| {
+        (0..i).all(|j| (cov[[i, j]] - cov[[j, i]]).abs() <= 1e-12 * (cov[[i, i]] * cov[[j, j]]).sqrt())
+    });
+    let factor = match linalg::cholesky(&cov) {
+        Some(factor) if symmetric => factor,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "cov_matrix must be symmetric positive definite"
+            ))
+        }
+    };
+    let drift: Vec<f64> = (0..n).map(|i| mu[i] - 0.5 * cov[[i, i]]).collect();
+
+    let paths = py.allow_threads(|| {
+        let paths = simulate_paths(n_paths, seed, |rng| correlated_path(&s0, &drift, &factor, n_steps, dt, rng));
+        Array3::from_shape_vec((n_paths, n_steps + 1, n), paths.concat()).unwrap()
+    });
+    Ok(PyArray3::from_owned_array_bound(py, paths))
+}
+
+/// Merton (1976) jump diffusion: geometric Brownian motion plus jumps arriving at rate
+/// `intensity`, each multiplying the price by a log-normal factor
+#[derive(Clone, Copy)]
+struct Merton {
+    spot: f64,
+    mu: f64,
+    sigma: f64,
+    intensity: f64,
+    jump_mean: f64,
+    jump_std: f64,
+}
+
+impl Merton {
+    /// Parse a dict with `spot`, `mu`, `sigma`, `intensity`, `jump_mean` and `jump_std`
+    fn parse(params: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let model = Merton {
+            spot: required(params, "spot", "params")?,
+            mu: required(params, "mu", "params")?,
+            sigma: required(params, "sigma", "params")?,
+            intensity: required(params, "intensity", "params")?,
+            jump_mean: required(params, "jump_mean", "params")?,
+            jump_std: required(params, "jump_std", "params")?,
+        };
+        if model.spot.is_nan() || model.spot <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "params spot must be > 0"
+            ));
+        }
+        if [model.sigma, model.intensity, model.jump_std].iter().any(|v| v.is_nan() || *v < 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "params sigma, intensity and jump_std must be >= 0"
+            ));
+        }
+        if !model.mu.is_finite() || !model.jump_mean.is_finite() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "params mu and jump_mean must be finite"
+            ));
+        }
+        Ok(model)
+    }
+
+    /// Prices of one path, `n_steps + 1` from the start, exact at each step: the diffusion
+    /// and the sum of the step's Poisson number of log-normal jumps are both normal
+    fn path(&self, n_steps: usize, dt: f64, rng: &mut SplitMix) -> Vec<f64> {
+        // The drift is compensated so the expected return is `mu` with the jumps
+        let compensator = self.intensity * ((self.jump_mean + 0.5 * self.jump_std * self.jump_std).exp() - 1.0);
+        let drift = (self.mu - 0.5 * self.sigma * self.sigma - compensator) * dt;
+        let diffusion = self.sigma * dt.sqrt();
+        let mut path = Vec::with_capacity(n_steps + 1);
+        let mut log_spot = self.spot.ln();
+        path.push(self.spot);
+        for _ in 0..n_steps {
+            let jumps = rng.poisson(self.intensity * dt) as f64;
+            let (z, jump_z) = rng.normal_pair();
+            log_spot += drift + diffusion * z;
+            if jumps > 0.0 {
+                log_spot += jumps * self.jump_mean + jumps.sqrt() * self.jump_std * jump_z;
+            }
+            path.push(log_spot.exp());
+        }
+        path
+    }
+}
+
+/// Simulate Merton jump-diffusion price paths, for tail-risk scenarios that geometric
+/// Brownian motion cannot produce.
+///
+/// `params` is a dict with `spot`, the annualised expected return `mu` and diffusion
+/// volatility `sigma`, the jump `intensity` (expected jumps per year) and the mean
+/// `jump_mean` and standard deviation `jump_std` of each jump's log size. The drift is
+/// compensated for the jumps, so the expected price grows at `mu`. Each of the `n_paths`
+/// paths takes `n_steps` steps of `dt` years, sampled exactly, and is seeded from `seed`
+/// (or `set_seed`'s) and its index, so results are reproducible whatever the thread
+/// count. Returns an array of shape `(n_paths, n_steps + 1)` with the spot in the first
+/// column.
+#[pyfunction]
+#[pyo3(signature = (params, n_paths=10_000, n_steps=252, seed=None, dt=1.0 / 252.0, config=None))]
+pub fn jump_diffusion_paths_rust<'py>(
+    py: Python<'py>,
+    params: &Bound<'py, PyDict>,
+    n_paths: usize,
+    n_steps: usize,
+    seed: Option<u64>,
+    dt: f64,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    let model = Merton::parse(params)?;
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    let paths = py.allow_threads(|| {
+        stack_paths(simulate_paths(n_paths, seed, |rng| model.path(n_steps, dt, rng)), n_steps + 1)
+    });
+    Ok(PyArray2::from_owned_array_bound(py, paths))
+}
+
+/// Heston stochastic-volatility dynamics: the variance mean-reverts to `theta` at speed
+/// `kappa` with volatility `xi`, its shocks correlated `rho` with the price's
+#[derive(Clone, Copy)]
+struct Heston {
+    spot: f64,
+    v0: f64,
+    kappa: f64,
+    theta: f64,
+    xi: f64,
+    rho: f64,
+    rate: f64,
+    dividend: f64,
+}
+
+impl Heston {
+    /// Parse a dict with `spot`, `v0`, `kappa`, `theta`, `xi`, `rho` and optional `rate`
+    /// and `dividend`
+    fn parse(params: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let optional = |key: &str| -> PyResult<f64> {
+            Ok(params.get_item(key)?.map(|v| v.extract()).transpose()?.unwrap_or(0.0))
+        };
+        let model = Heston {
+            spot: required(params, "spot", "params")?,
+            v0: required(params, "v0", "params")?,
+            kappa: required(params, "kappa", "params")?,
+            theta: required(params, "theta", "params")?,
+            xi: required(params, "xi", "params")?,
+            rho: required(params, "rho", "params")?,
+            rate: optional("rate")?,
+            dividend: optional("dividend")?,
+        };
+        if model.spot.is_nan() || model.spot <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "params spot must be > 0"
+            ));
+        }
+        if [model.v0, model.kappa, model.theta, model.xi].iter().any(|v| v.is_nan() || *v < 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "params v0, kappa, theta and xi must be >= 0"
+            ));
+        }
+        if model.rho.is_nan() || model.rho.abs() > 1.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "params rho must be in [-1, 1]"
+            ));
+        }
+        Ok(model)
+    }
+
+    /// Prices and variances of one path, `n_steps + 1` each from the start, by Euler steps
+    /// on the log price and full truncation of the variance at 0
+    fn path(&self, n_steps: usize, dt: f64, rng: &mut SplitMix) -> (Vec<f64>, Vec<f64>) {
+        let mut prices = Vec::with_capacity(n_steps + 1);
+        let mut variances = Vec::with_capacity(n_steps + 1);
+        let (mut log_spot, mut variance) = (self.spot.ln(), self.v0);
+        let orthogonal = (1.0 - self.rho * self.rho).sqrt();
+        prices.push(self.spot);
+        variances.push(variance);
+        for _ in 0..n_steps {
+            let (z1, z2) = rng.normal_pair();
+            let positive = variance.max(0.0);
+            let shock = (positive * dt).sqrt();
+            log_spot += (self.rate - self.dividend - 0.5 * positive) * dt + shock * z1;
+            variance += self.kappa * (self.theta - positive) * dt + self.xi * shock * (self.rho * z1 + orthogonal * z2);
+            prices.push(log_spot.exp());
+            variances.push(variance.max(0.0));
+        }
+        (prices, variances)
+    }
+}
+
+/// Simulate correlated price and variance paths of the Heston (1993) stochastic-volatility
+/// model, for pricing exotics and scenario analysis.
+///
+/// `params` is a dict with `spot`, the initial variance `v0`, the mean-reversion speed
+/// `kappa`, the long-run variance `theta`, the volatility of variance `xi`, the correlation
+/// `rho` between the price and variance shocks, and optional continuous `rate` and
+/// `dividend` yield (default 0, so the price drifts at `rate - dividend`). Each of the
+/// `n_paths` paths takes `n_steps` steps of `dt` years, Euler on the log price with the
+/// variance fully truncated at 0 (Lord, Koekkoek and van Dijk), and is seeded from `seed`
+/// (or `set_seed`'s) and its index, so results are reproducible whatever the thread
+/// count. Returns `(prices, variances)`, each of shape `(n_paths, n_steps + 1)` with the starting values
+/// in the first column.
+#[pyfunction]
+#[pyo3(signature = (params, n_paths=10_000, n_steps=252, dt=1.0 / 252.0, seed=None, config=None))]
+pub fn heston_paths_rust<'py>(
+    py: Python<'py>,
+    params: &Bound<'py, PyDict>,
+    n_paths: usize,
+    n_steps: usize,
+    dt: f64,
+    seed: Option<u64>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    let model = Heston::parse(params)?;
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+
+    let (prices, variances) = py.allow_threads(|| {
+        let paths = simulate_paths(n_paths, seed, |rng| model.path(n_steps, dt, rng));
+        let (prices, variances): (Vec<Vec<f64>>, Vec<Vec<f64>>) = paths.into_iter().unzip();
+        (stack_paths(prices, n_steps + 1), stack_paths(variances, n_steps + 1))
+    });
+    Ok((PyArray2::from_owned_array_bound(py, prices), PyArray2::from_owned_array_bound(py, variances)).into_py(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use statrs::distribution::{ContinuousCDF, Normal};
+
+    fn dict<'py>(py: Python<'py>, items: &[(&str, PyObject)]) -> Bound<'py, PyDict> {
+        let dict = PyDict::new_bound(py);
+        for (key, value) in items {
+            dict.set_item(key, value).unwrap();
+        }
+        dict
+    }
+
+    fn model(py: Python<'_>) -> Bound<'_, PyDict> {
+        dict(py, &[
+            ("spot", 100.0.into_py(py)),
+            ("rate", 0.05.into_py(py)),
+            ("volatility", 0.2.into_py(py)),
+            ("tte", 1.0.into_py(py)),
+            ("dividend", 0.02.into_py(py)),
+        ])
+    }
+
+    fn payoff<'py>(py: Python<'py>, kind: &str, option_type: &str, extra: &[(&str, PyObject)]) -> Bound<'py, PyDict> {
+        let spec = dict(py, &[("type", kind.into_py(py)), ("strike", 105.0.into_py(py)), ("option_type", option_type.into_py(py))]);
+        for (key, value) in extra {
+            spec.set_item(key, value).unwrap();
+        }
+        spec
+    }
+
+    /// Black-Scholes-Merton price of the option `model` describes, struck at 105
+    fn black_scholes(call: bool) -> f64 {
+        let (spot, strike, rate, volatility, tte, dividend) = (100.0f64, 105.0f64, 0.05, 0.2, 1.0f64, 0.02);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let d1 = ((spot / strike).ln() + (rate - dividend + 0.5 * volatility * volatility) * tte) / (volatility * tte.sqrt());
+        let d2 = d1 - volatility * tte.sqrt();
+        let (spot_pv, strike_pv) = (spot * (-dividend * tte).exp(), strike * (-rate * tte).exp());
+        if call {
+            spot_pv * normal.cdf(d1) - strike_pv * normal.cdf(d2)
+        } else {
+            strike_pv * normal.cdf(-d2) - spot_pv * normal.cdf(-d1)
+        }
+    }
+
+    fn price(py: Python<'_>, spec: &Bound<'_, PyDict>, n_steps: usize, seed: u64, antithetic: bool) -> (f64, f64) {
+        mc_price_rust(py, spec, &model(py), 40_000, n_steps, Some(seed), antithetic, None).unwrap()
+    }
+
+    #[test]
+    fn european_prices_converge_to_black_scholes() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            for (option_type, call) in [("call", true), ("put", false)] {
+                for antithetic in [true, false] {
+                    let (mc, std_error) = price(py, &payoff(py, "european", option_type, &[]), 4, 3, antithetic);
+                    let exact = black_scholes(call);
+                    assert!(std_error > 0.0 && std_error < 0.1);
+                    assert!((mc - exact).abs() < 4.0 * std_error, "{} {}: {} vs {}", option_type, antithetic, mc, exact);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn knock_in_and_knock_out_add_up_to_the_european() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let barrier = |kind: &str| {
+                let extra = [("barrier", 120.0.into_py(py)), ("barrier_type", kind.into_py(py))];
+                price(py, &payoff(py, "barrier", "call", &extra), 12, 5, true).0
+            };
+            let european = price(py, &payoff(py, "european", "call", &[]), 12, 5, true).0;
+            assert!((barrier("up-and-in") + barrier("up-and-out") - european).abs() < 1e-9);
+        });
+    }
+
+    #[test]
+    fn prices_depend_only_on_the_seed() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let spec = payoff(py, "asian", "put", &[("average", "geometric".into_py(py))]);
+            let mut results = Vec::new();
+            for n in [1, 3] {
+                threads::enter_scope(n).unwrap();
+                results.push(price(py, &spec, 8, 11, true));
+                threads::exit_scope();
+            }
+            assert_eq!(results[0], results[1]);
+            assert_ne!(price(py, &spec, 8, 12, true), results[0]);
+        });
+    }
+
+    /// Sample mean and standard error of `values`
+    fn mean_and_error(values: impl Iterator<Item = f64>) -> (f64, f64) {
+        let values: Vec<f64> = values.collect();
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (mean, (variance / n).sqrt())
+    }
+
+    fn assert_near(name: &str, (mean, error): (f64, f64), expected: f64) {
+        assert!((mean - expected).abs() < 4.0 * error, "{}: {} +- {} vs {}", name, mean, error, expected);
+    }
+
+    #[test]
+    fn gbm_terminal_mean_grows_at_mu() {
+        let paths = simulate_paths(20_000, 7, |rng| gbm_path(100.0, 0.1, 0.3, 4, 0.25, rng));
+        assert!(paths.iter().all(|p| p.len() == 5 && p[0] == 100.0));
+        assert_near("gbm", mean_and_error(paths.iter().map(|p| p[4])), 100.0 * 0.1f64.exp());
+        // The log price is exactly normal with mean (mu - sigma^2 / 2) T
+        assert_near("log gbm", mean_and_error(paths.iter().map(|p| (p[4] / 100.0).ln())), 0.1 - 0.045);
+    }
+
+    #[test]
+    fn correlated_returns_follow_the_covariance() {
+        let cov = ndarray::array![[0.04, 0.018, -0.012], [0.018, 0.09, 0.0], [-0.012, 0.0, 0.16]];
+        let factor = linalg::cholesky(&cov).unwrap();
+        let (s0, drift) = ([100.0, 50.0, 20.0], [0.0; 3]);
+        let paths = simulate_paths(40_000, 3, |rng| correlated_path(&s0, &drift, &factor, 1, 1.0, rng));
+        let returns: Vec<[f64; 3]> = paths.iter().map(|p| std::array::from_fn(|i| (p[3 + i] / s0[i]).ln())).collect();
+        let n = returns.len() as f64;
+        let covariance = |i: usize, j: usize| returns.iter().map(|r| r[i] * r[j]).sum::<f64>() / n;
+        for i in 0..3 {
+            for j in 0..3 {
+                let correlation = covariance(i, j) / (covariance(i, i) * covariance(j, j)).sqrt();
+                let expected = cov[[i, j]] / (cov[[i, i]] * cov[[j, j]]).sqrt();
+                assert!((correlation - expected).abs() < 0.02, "({}, {}): {} vs {}", i, j, correlation, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn jump_diffusion_moments_match_merton() {
+        let model = Merton { spot: 100.0, mu: 0.08, sigma: 0.2, intensity: 3.0, jump_mean: -0.05, jump_std: 0.1 };
+        let paths = simulate_paths(40_000, 9, |rng| model.path(10, 0.1, rng));
+        // The compensated drift keeps E[S_T] = S0 exp(mu T) whatever the jumps
+        assert_near("merton", mean_and_error(paths.iter().map(|p| p[10])), 100.0 * 0.08f64.exp());
+        let compensator = 3.0 * ((-0.05f64 + 0.005).exp() - 1.0);
+        let log_mean = 0.08 - 0.02 - compensator + 3.0 * -0.05;
+        assert_near("log merton", mean_and_error(paths.iter().map(|p| (p[10] / 100.0).ln())), log_mean);
+    }
+
+    #[test]
+    fn heston_price_is_a_martingale_and_variance_reverts() {
+        let model = Heston { spot: 100.0, v0: 0.09, kappa: 2.0, theta: 0.04, xi: 0.3, rho: -0.7, rate: 0.03, dividend: 0.01 };
+        let paths = simulate_paths(40_000, 5, |rng| model.path(100, 0.01, rng));
+        assert_near("heston", mean_and_error(paths.iter().map(|(p, _)| p[100])), 100.0 * 0.02f64.exp());
+        // E[v_T] = theta + (v0 - theta) exp(-kappa T); truncation bias is negligible
+        // when the Feller condition holds, as here
+        let expected = 0.04 + 0.05 * (-2.0f64).exp();
+        assert_near("variance", mean_and_error(paths.iter().map(|(_, v)| v[100])), expected);
+    }
+
+    #[test]
+    fn paths_depend_only_on_the_seed() {
+        let merton = Merton { spot: 100.0, mu: 0.05, sigma: 0.2, intensity: 2.0, jump_mean: 0.0, jump_std: 0.1 };
+        let heston = Heston { spot: 100.0, v0: 0.04, kappa: 1.0, theta: 0.04, xi: 0.5, rho: -0.5, rate: 0.0, dividend: 0.0 };
+        let simulate = |threads: usize| {
+            threads::enter_scope(threads).unwrap();
+            let paths = (
+                simulate_paths(500, 21, |rng| gbm_path(100.0, 0.05, 0.2, 30, 0.01, rng)),
+                simulate_paths(500, 21, |rng| merton.path(30, 0.01, rng)),
+                simulate_paths(500, 21, |rng| heston.path(30, 0.01, rng)),
+            );
+            threads::exit_scope();
+            paths
+        };
+        assert!(simulate(1) == simulate(4));
+        assert_ne!(simulate_paths(2, 22, |rng| gbm_path(100.0, 0.05, 0.2, 3, 0.01, rng))[0], simulate(1).0[0]);
+    }
+
+    #[test]
+    fn non_finite_drifts_and_strikes_are_rejected() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            assert!(gbm_paths_rust(py, 100.0, f64::NAN, 0.2, 10, 5, 0.1, Some(1), None).is_err());
+            let params = |mu: f64, jump_mean: f64| {
+                dict(py, &[
+                    ("spot", 100.0.into_py(py)),
+                    ("mu", mu.into_py(py)),
+                    ("sigma", 0.2.into_py(py)),
+                    ("intensity", 1.0.into_py(py)),
+                    ("jump_mean", jump_mean.into_py(py)),
+                    ("jump_std", 0.1.into_py(py)),
+                ])
+            };
+            assert!(Merton::parse(&params(0.05, 0.0)).is_ok());
+            assert!(Merton::parse(&params(f64::INFINITY, 0.0)).is_err());
+            assert!(Merton::parse(&params(0.05, f64::NAN)).is_err());
+            for strike in [f64::NAN, f64::INFINITY, -1.0] {
+                let spec = dict(py, &[("type", "european".into_py(py)), ("strike", strike.into_py(py))]);
+                assert!(Payoff::parse(&spec).is_err());
+            }
+        });
+    }
+}
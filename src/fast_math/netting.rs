@@ -0,0 +1,131 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView2};
+
+use crate::column::Column;
+
+/// Netting of one symbol across strategies
+struct Netted {
+    /// Signed external order, buys positive; 0 when none is sent
+    order: f64,
+    /// Quantity crossed internally between buying and selling strategies
+    crossed: f64,
+    /// Position change each strategy receives, from the crossing and the order
+    fills: Vec<f64>,
+}
+
+/// Net the position changes `deltas` of the strategies in one symbol: the smaller side is
+/// crossed internally against the larger, which also shares the external order (rounded
+/// to `lot`, dropped below `min_trade`) in proportion to its changes
+fn net(deltas: &[f64], lot: Option<f64>, min_trade: f64) -> Netted {
+    let buys: f64 = deltas.iter().filter(|&&d| d > 0.0).sum();
+    let sells: f64 = -deltas.iter().filter(|&&d| d < 0.0).sum::<f64>();
+    let crossed = buys.min(sells);
+    let mut order = buys - sells;
+    if let Some(lot) = lot {
+        order = (order / lot).round() * lot;
+    }
+    if order.abs() < min_trade.max(f64::MIN_POSITIVE) {
+        order = 0.0;
+    }
+    // Each side gets what it crossed plus its part of the order, shared by size
+    let sides = [(buys, crossed + order.max(0.0)), (sells, crossed + (-order).max(0.0))];
+    let fills = deltas
+        .iter()
+        .map(|&d| {
+            let (total, received) = if d > 0.0 { sides[0] } else { sides[1] };
+            if total > 0.0 { d * received / total } else { 0.0 }
+        })
+        .collect();
+    Netted { order, crossed, fills }
+}
+
+fn lot_sizes(lot_size: Option<Bound<'_, PyAny>>, n_symbols: usize) -> PyResult<Option<Vec<f64>>> {
+    let Some(lot_size) = lot_size else {
+        return Ok(None);
+    };
+    let lots = match lot_size.extract::<f64>() {
+        Ok(lot) => vec![lot; n_symbols],
+        Err(_) => lot_size.extract::<Column<'_, f64>>()?.values()?.to_vec(),
+    };
+    if lots.len() != n_symbols {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "lot_size must have one entry per symbol (column)"
+        ));
+    }
+    if lots.iter().any(|&lot| lot.is_nan() || lot <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "lot_size must be > 0"
+        ));
+    }
+    Ok(Some(lots))
+}
+
+fn check_positions(target: ArrayView2<'_, f64>, current: ArrayView2<'_, f64>) -> PyResult<()> {
+    if target.dim() != current.dim() {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "target_positions is {:?} but current_positions is {:?}", target.dim(), current.dim()
+        )));
+    }
+    if target.iter().chain(current.iter()).any(|v| !v.is_finite()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "Positions must be finite"
+        ));
+    }
+    Ok(())
+}
+
+/// Net the trades of several strategies into the fewest external orders.
+///
+/// `target_positions` and `current_positions` hold one row per strategy and one column
+/// per symbol. In each symbol, strategies buying are crossed internally against those
+/// selling, and only the difference is sent out: rounded to the nearest multiple of
+/// `lot_size` (a number or one per symbol) and dropped when smaller than `min_trade`.
+/// Returns a dict with the orders as `symbol` (column indices) and signed `quantity`
+/// (buys positive), the `crossed` quantity of each symbol, `fills`, the position change
+/// of each strategy and symbol from the crossing and the orders, shared between the
+/// strategies on the order's side in proportion to their trades, and `residual`, the
+/// trade each strategy still lacks (target minus current minus fills) because of
+/// rounding or `min_trade`.
+#[pyfunction]
+#[pyo3(signature = (target_positions, current_positions, min_trade=0.0, lot_size=None))]
+pub fn net_orders_rust<'py>(
+    py: Python<'py>,
+    target_positions: PyReadonlyArray2<'py, f64>,
+    current_positions: PyReadonlyArray2<'py, f64>,
+    min_trade: f64,
+    lot_size: Option<Bound<'py, PyAny>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let (target, current) = (target_positions.as_array(), current_positions.as_array());
+    check_positions(target, current)?;
+    if min_trade.is_nan() || min_trade < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "min_trade must be >= 0"
+        ));
+    }
+    let (n_strategies, n_symbols) = target.dim();
+    let lots = lot_sizes(lot_size, n_symbols)?;
+    let netted: Vec<Netted> = py.allow_threads(|| {
+        (0..n_symbols)
+            .map(|j| {
+                let deltas: Vec<f64> = (0..n_strategies).map(|s| target[[s, j]] - current[[s, j]]).collect();
+                net(&deltas, lots.as_ref().map(|lots| lots[j]), min_trade)
+            })
+            .collect()
+    });
+    let fills = Array2::from_shape_fn((n_strategies, n_symbols), |(s, j)| netted[j].fills[s]);
+    let residual = Array2::from_shape_fn((n_strategies, n_symbols), |(s, j)| {
+        target[[s, j]] - current[[s, j]] - fills[[s, j]]
+    });
+    let orders: Vec<(i64, f64)> =
+        netted.iter().enumerate().filter(|(_, n)| n.order != 0.0).map(|(j, n)| (j as i64, n.order)).collect();
+
+    let result = PyDict::new_bound(py);
+    result.set_item("symbol", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.0)))?;
+    result.set_item("quantity", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.1)))?;
+    result.set_item("crossed", PyArray1::from_iter_bound(py, netted.iter().map(|n| n.crossed)))?;
+    result.set_item("fills", PyArray2::from_owned_array_bound(py, fills))?;
+    result.set_item("residual", PyArray2::from_owned_array_bound(py, residual))?;
+    Ok(result)
+}
@@ -0,0 +1,209 @@
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use numpy::PyArray1;
+use statrs::distribution::{ChiSquared, ContinuousCDF, Normal};
+
+use crate::column::{self, Series};
+
+/// Anderson-Darling critical values of the normal with estimated parameters at the 15%,
+/// 10%, 5%, 2.5% and 1% levels, before the small-sample adjustment (as scipy's `anderson`)
+const AD_CRITICAL: [f64; 5] = [0.576, 0.656, 0.787, 0.918, 1.092];
+
+/// Mean and sample standard deviation (`ddof=1`)
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
+}
+
+/// Jarque-Bera statistic, p-value, skewness and (non-excess) kurtosis, the moments biased
+/// as in statsmodels
+fn jarque_bera(values: &[f64]) -> [f64; 4] {
+    let n = values.len() as f64;
+    if values.len() < 2 {
+        return [f64::NAN; 4];
+    }
+    let mean = values.iter().sum::<f64>() / n;
+    let moment = |k: i32| values.iter().map(|v| (v - mean).powi(k)).sum::<f64>() / n;
+    let m2 = moment(2);
+    let skew = moment(3) / m2.powf(1.5);
+    let kurtosis = moment(4) / (m2 * m2);
+    let statistic = n / 6.0 * (skew * skew + (kurtosis - 3.0).powi(2) / 4.0);
+    let p_value = if statistic.is_nan() { f64::NAN } else { ChiSquared::new(2.0).unwrap().sf(statistic) };
+    [statistic, p_value, skew, kurtosis]
+}
+
+/// Survival function of the Kolmogorov distribution, by whichever of its two series
+/// converges fast at `lambda`
+fn kolmogorov_sf(lambda: f64) -> f64 {
+    if lambda <= 0.0 {
+        return 1.0;
+    }
+    if lambda < 1.18 {
+        let factor = (2.0 * std::f64::consts::PI).sqrt() / lambda;
+        let cdf: f64 = (1..=20)
+            .map(|j| {
+                let odd = (2 * j - 1) as f64;
+                (-odd * odd * std::f64::consts::PI.powi(2) / (8.0 * lambda * lambda)).exp()
+            })
+            .sum::<f64>()
+            * factor;
+        (1.0 - cdf).clamp(0.0, 1.0)
+    } else {
+        let sf: f64 = (1..=100)
+            .map(|j| {
+                let sign = if j % 2 == 1 { 1.0 } else { -1.0 };
+                sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp()
+            })
+            .sum::<f64>()
+            * 2.0;
+        sf.clamp(0.0, 1.0)
+    }
+}
+
+/// Kolmogorov-Smirnov distance from the normal with the given (or estimated) parameters
+/// and its asymptotic p-value with Stephens' small-sample correction
+fn kolmogorov_smirnov(values: &[f64], loc: Option<f64>, scale: Option<f64>) -> [f64; 2] {
+    if values.is_empty() || values.iter().any(|v| v.is_nan()) {
+        return [f64::NAN; 2];
+    }
+    let (mean, std) = if loc.is_none() || scale.is_none() { mean_std(values) } else { (0.0, 0.0) };
+    let Ok(normal) = Normal::new(loc.unwrap_or(mean), scale.unwrap_or(std)) else {
+        return [f64::NAN; 2];
+    };
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let distance = sorted.iter().enumerate().fold(0.0f64, |d, (i, &v)| {
+        let cdf = normal.cdf(v);
+        d.max((i + 1) as f64 / n - cdf).max(cdf - i as f64 / n)
+    });
+    let root = n.sqrt();
+    [distance, kolmogorov_sf((root + 0.12 + 0.11 / root) * distance)]
+}
+
+/// Anderson-Darling statistic against the normal with estimated parameters, its p-value
+/// (D'Agostino and Stephens' approximation) and the five adjusted critical values
+fn anderson_darling(values: &[f64]) -> [f64; 7] {
+    let mut result = [f64::NAN; 7];
+    if values.len() < 2 || values.iter().any(|v| v.is_nan()) {
+        return result;
+    }
+    let (mean, std) = mean_std(values);
+    let Ok(normal) = Normal::new(mean, std) else {
+        return result;
+    };
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+    let nf = n as f64;
+    // ln F(x_i) and ln(1 - F(x_{n+1-i})), by the survival function to keep the tails exact
+    let sum: f64 = (0..n)
+        .map(|i| (2 * i + 1) as f64 * (normal.cdf(sorted[i]).ln() + normal.sf(sorted[n - 1 - i]).ln()))
+        .sum();
+    let statistic = -nf - sum / nf;
+
+    let adjusted = statistic * (1.0 + 0.75 / nf + 2.25 / (nf * nf));
+    let p_value = if adjusted >= 0.6 {
+        (1.2937 - 5.709 * adjusted + 0.0186 * adjusted * adjusted).exp()
+    } else if adjusted >= 0.34 {
+        (0.9177 - 4.279 * adjusted - 1.38 * adjusted * adjusted).exp()
+    } else if adjusted >= 0.2 {
+        1.0 - (-8.318 + 42.796 * adjusted - 59.938 * adjusted * adjusted).exp()
+    } else {
+        1.0 - (-13.436 + 101.14 * adjusted - 223.73 * adjusted * adjusted).exp()
+    };
+    result[0] = statistic;
+    result[1] = p_value.clamp(0.0, 1.0);
+    let correction = 1.0 + 4.0 / nf - 25.0 / (nf * nf);
+    for (slot, critical) in result[2..].iter_mut().zip(AD_CRITICAL) {
+        *slot = critical / correction;
+    }
+    result
+}
+
+/// Run a test on one series, returning its values as a tuple, or on every row of a batch in
+/// parallel, returning a tuple of arrays
+fn per_series<'py, const K: usize, F>(py: Python<'py>, data: Series<'py, f64>, test: F) -> PyResult<PyObject>
+where
+    F: Fn(&[f64]) -> [f64; K] + Sync,
+{
+    match data {
+        Series::Single(column) => {
+            let values = column.values()?;
+            let result = py.allow_threads(|| test(&values));
+            Ok(PyTuple::new_bound(py, result).into_any().unbind())
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            let rows = py.allow_threads(|| column::map_rows(batch, |_, row| Ok(test(row))))?;
+            let arrays = (0..K).map(|k| PyArray1::from_iter_bound(py, rows.iter().map(|r| r[k])));
+            Ok(PyTuple::new_bound(py, arrays).into_any().unbind())
+        }
+    }
+}
+
+/// Jarque-Bera normality test of `returns`, like statsmodels' `jarque_bera`.
+///
+/// Returns `(statistic, p_value, skew, kurtosis)` with `statistic = n / 6 * (S² + (K - 3)²
+/// / 4)` from the biased sample skewness `S` and (non-excess) kurtosis `K`, and its
+/// asymptotic chi-squared p-value on 2 degrees of freedom. A 2D array is tested row by row in
+/// parallel, returning a tuple of arrays. NaNs and fewer than 2 values give NaN.
+#[pyfunction]
+pub fn jarque_bera_rust<'py>(py: Python<'py>, returns: Series<'py, f64>) -> PyResult<PyObject> {
+    per_series(py, returns, jarque_bera)
+}
+
+/// Kolmogorov-Smirnov test of `returns` against a normal distribution.
+///
+/// Compares against the normal with mean `loc` and standard deviation `scale`, each
+/// estimated from the data when not given; with estimated parameters the p-value is
+/// conservative (the Lilliefors situation), so prefer `anderson_darling_rust` for a
+/// composite test. Returns `(statistic, p_value)`: the largest gap between the empirical
+/// and normal CDFs and the asymptotic Kolmogorov p-value with Stephens' correction
+/// `(sqrt(n) + 0.12 + 0.11 / sqrt(n)) * D`. A 2D array is tested row by row, returning two
+/// arrays. NaNs, empty series and a zero scale give NaN.
+#[pyfunction]
+#[pyo3(signature = (returns, loc=None, scale=None))]
+pub fn ks_test_rust<'py>(
+    py: Python<'py>,
+    returns: Series<'py, f64>,
+    loc: Option<f64>,
+    scale: Option<f64>
+) -> PyResult<PyObject> {
+    per_series(py, returns, |values| kolmogorov_smirnov(values, loc, scale))
+}
+
+/// Anderson-Darling normality test of `returns`, mean and standard deviation estimated,
+/// like scipy's `anderson(returns, "norm")`, but with a p-value.
+///
+/// Returns `(statistic, p_value, critical_values)` where the critical values are those of
+/// the 15%, 10%, 5%, 2.5% and 1% levels adjusted for the sample size as scipy does, and the
+/// p-value is D'Agostino and Stephens' approximation for the size-adjusted statistic.
+/// Weighting the tails more than Kolmogorov-Smirnov, it is the better check for fat-tailed
+/// returns. A 2D array is tested row by row, returning the statistics, p-values and an
+/// array of critical values per row. NaNs, fewer than 2 values or constant series give NaN.
+#[pyfunction]
+pub fn anderson_darling_rust<'py>(py: Python<'py>, returns: Series<'py, f64>) -> PyResult<PyObject> {
+    match returns {
+        Series::Single(column) => {
+            let values = column.values()?;
+            let result = py.allow_threads(|| anderson_darling(&values));
+            Ok((result[0], result[1], PyArray1::from_slice_bound(py, &result[2..])).into_py(py))
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            let rows = py.allow_threads(|| column::map_rows(batch, |_, row| Ok(anderson_darling(row))))?;
+            let critical: Vec<Vec<f64>> = rows.iter().map(|r| r[2..].to_vec()).collect();
+            Ok((
+                PyArray1::from_iter_bound(py, rows.iter().map(|r| r[0])),
+                PyArray1::from_iter_bound(py, rows.iter().map(|r| r[1])),
+                numpy::PyArray2::from_owned_array_bound(py, column::stack_rows(critical)?),
+            )
+                .into_py(py))
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, HashSet};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::errors::InvalidTransitionError;
+
+/// Fills within this fraction of the order quantity complete it
+const TOLERANCE: f64 = 1e-9;
+
+/// Lifecycle state of an order
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    New,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl Status {
+    fn name(self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::PartiallyFilled => "partially_filled",
+            Status::Filled => "filled",
+            Status::Cancelled => "cancelled",
+            Status::Rejected => "rejected",
+        }
+    }
+
+    fn is_open(self) -> bool {
+        matches!(self, Status::New | Status::PartiallyFilled)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "buy" | "b" => Ok(Side::Buy),
+            "sell" | "s" => Ok(Side::Sell),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown side '{}', expected 'buy' or 'sell'", name
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        }
+    }
+}
+
+struct Order {
+    symbol: String,
+    side: Side,
+    qty: f64,
+    /// Limit price, None for a market order
+    price: Option<f64>,
+    filled: f64,
+    /// Quantity-weighted fill price, NaN before the first fill
+    average_price: f64,
+    status: Status,
+    reason: Option<String>,
+    /// (event type, event id) of the events applied to the order while it is open
+    applied: HashSet<(&'static str, String)>,
+}
+
+impl Order {
+    fn leaves(&self) -> f64 {
+        if self.status.is_open() { self.qty - self.filled } else { 0.0 }
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>, order_id: &str) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("order_id", order_id)?;
+        dict.set_item("symbol", &self.symbol)?;
+        dict.set_item("side", self.side.name())?;
+        dict.set_item("qty", self.qty)?;
+        dict.set_item("price", self.price)?;
+        dict.set_item("filled_qty", self.filled)?;
+        dict.set_item("leaves_qty", self.leaves())?;
+        dict.set_item("average_price", self.average_price)?;
+        dict.set_item("status", self.status.name())?;
+        dict.set_item("reason", &self.reason)?;
+        Ok(dict)
+    }
+}
+
+/// Order lifecycle tracking shared by the backtester and the live engine.
+///
+/// Orders move from "new" through "partially_filled" to "filled", or end "cancelled" or
+/// "rejected"; any other move (such as a fill after a cancel, or more fills than the order
+/// quantity) raises `InvalidTransitionError`, leaving the state untouched. Events may carry
+/// an `event_id` (e.g. the execution id of a fill): an event whose id was already applied
+/// to the same order for the same event type is ignored, so replaying a feed or receiving
+/// a duplicate report is harmless. The ids are forgotten once an order is filled,
+/// cancelled or rejected, after which late duplicates raise like any other event on a
+/// closed order. Submitting an existing order id again with the same details, and
+/// cancelling or rejecting an order already in that state, are no-ops as well. Every event
+/// method returns the order's status after the event.
+#[pyclass(module = "fast_math")]
+pub struct OrderManager {
+    orders: BTreeMap<String, Order>,
+}
+
+impl OrderManager {
+    fn order_mut(&mut self, order_id: &str) -> PyResult<&mut Order> {
+        self.orders.get_mut(order_id).ok_or_else(|| {
+            pyo3::exceptions::PyKeyError::new_err(format!("Unknown order '{}'", order_id))
+        })
+    }
+
+    fn get(&self, order_id: &str) -> PyResult<&Order> {
+        self.orders.get(order_id).ok_or_else(|| {
+            pyo3::exceptions::PyKeyError::new_err(format!("Unknown order '{}'", order_id))
+        })
+    }
+
+    /// Run `apply` unless a `kind` event with `event_id` was applied to the order before,
+    /// then remember it while the order stays open
+    fn once(
+        &mut self,
+        kind: &'static str,
+        order_id: &str,
+        event_id: Option<String>,
+        apply: impl FnOnce(&mut Self) -> PyResult<()>
+    ) -> PyResult<&'static str> {
+        let key = event_id.map(|id| (kind, id));
+        if key.as_ref().is_some_and(|key| self.get(order_id).is_ok_and(|o| o.applied.contains(key))) {
+            return Ok(self.get(order_id)?.status.name());
+        }
+        apply(self)?;
+        let order = self.order_mut(order_id)?;
+        if !order.status.is_open() {
+            order.applied = HashSet::new();
+        } else if let Some(key) = key {
+            order.applied.insert(key);
+        }
+        Ok(order.status.name())
+    }
+
+    /// End an open order in `status`; a no-op when it is already there
+    fn close(&mut self, order_id: &str, status: Status, reason: Option<String>) -> PyResult<()> {
+        let order = self.order_mut(order_id)?;
+        if order.status == status {
+            return Ok(());
+        }
+        if !order.status.is_open() {
+            return Err(InvalidTransitionError::new_err(format!(
+                "Order '{}' is {} and cannot become {}", order_id, order.status.name(), status.name()
+            )));
+        }
+        order.status = status;
+        order.reason = reason;
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl OrderManager {
+    #[new]
+    fn new() -> Self {
+        OrderManager { orders: BTreeMap::new() }
+    }
+
+    /// Register a new order of `qty > 0` on `side` ("buy" or "sell"), a limit order at
+    /// `price` or a market order without one
+    #[pyo3(signature = (order_id, symbol, side, qty, price=None, event_id=None))]
+    fn submit(
+        &mut self,
+        order_id: &str,
+        symbol: &str,
+        side: &str,
+        qty: f64,
+        price: Option<f64>,
+        event_id: Option<String>
+    ) -> PyResult<&'static str> {
+        let side = Side::parse(side)?;
+        if !qty.is_finite() || qty <= 0.0 || price.is_some_and(|p| !p.is_finite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Order qty must be > 0 and price finite"
+            ));
+        }
+        if let Some(order) = self.orders.get(order_id) {
+            if order.symbol == symbol && order.side == side && order.qty == qty && order.price == price {
+                return Ok(order.status.name());
+            }
+            return Err(InvalidTransitionError::new_err(format!(
+                "Order '{}' already exists with different details", order_id
+            )));
+        }
+        let order = Order {
+            symbol: symbol.to_string(),
+            side,
+            qty,
+            price,
+            filled: 0.0,
+            average_price: f64::NAN,
+            status: Status::New,
+            reason: None,
+            applied: event_id.map(|id| ("new", id)).into_iter().collect(),
+        };
+        self.orders.insert(order_id.to_string(), order);
+        Ok(Status::New.name())
+    }
+
+    /// Apply a fill of `qty > 0` at `price`
+    #[pyo3(signature = (order_id, qty, price, event_id=None))]
+    fn fill(&mut self, order_id: &str, qty: f64, price: f64, event_id: Option<String>) -> PyResult<&'static str> {
+        if !qty.is_finite() || qty <= 0.0 || !price.is_finite() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Fill qty must be > 0 and price finite"
+            ));
+        }
+        self.once("fill", order_id, event_id, |manager| {
+            let order = manager.order_mut(order_id)?;
+            if !order.status.is_open() {
+                return Err(InvalidTransitionError::new_err(format!(
+                    "Order '{}' is {} and cannot be filled", order_id, order.status.name()
+                )));
+            }
+            let filled = order.filled + qty;
+            if filled > order.qty * (1.0 + TOLERANCE) {
+                return Err(InvalidTransitionError::new_err(format!(
+                    "Fill of {} overfills order '{}' ({} of {} filled)", qty, order_id, order.filled, order.qty
+                )));
+            }
+            order.average_price = if order.filled == 0.0 {
+                price
+            } else {
+                (order.average_price * order.filled + price * qty) / filled
+            };
+            order.filled = filled;
+            order.status = if filled >= order.qty * (1.0 - TOLERANCE) {
+                Status::Filled
+            } else {
+                Status::PartiallyFilled
+            };
+            Ok(())
+        })
+    }
+
+    /// Cancel an open order, keeping what was already filled
+    #[pyo3(signature = (order_id, event_id=None))]
+    fn cancel(&mut self, order_id: &str, event_id: Option<String>) -> PyResult<&'static str> {
+        self.once("cancel", order_id, event_id, |manager| manager.close(order_id, Status::Cancelled, None))
+    }
+
+    /// Reject an open order, e.g. on a venue or risk rejection
+    #[pyo3(signature = (order_id, reason=None, event_id=None))]
+    fn reject(&mut self, order_id: &str, reason: Option<String>, event_id: Option<String>) -> PyResult<&'static str> {
+        self.once("reject", order_id, event_id, |manager| manager.close(order_id, Status::Rejected, reason))
+    }
+
+    /// Apply an event dict with `type` ("new", "fill", "cancel" or "reject"), `order_id`,
+    /// an optional `event_id` and the arguments of the matching method
+    fn apply(&mut self, event: &Bound<'_, PyDict>) -> PyResult<&'static str> {
+        let item = |key: &str| event.get_item(key);
+        let required = |key: &str| -> PyResult<Bound<'_, PyAny>> {
+            item(key)?.ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!("Order event needs a '{}' key", key))
+            })
+        };
+        let kind: String = required("type")?.extract()?;
+        let order_id: String = required("order_id")?.extract()?;
+        let event_id: Option<String> = item("event_id")?.map(|v| v.extract()).transpose()?;
+        match kind.to_ascii_lowercase().as_str() {
+            "new" | "submit" => self.submit(
+                &order_id,
+                &required("symbol")?.extract::<String>()?,
+                &required("side")?.extract::<String>()?,
+                required("qty")?.extract()?,
+                item("price")?.map(|v| v.extract()).transpose()?,
+                event_id,
+            ),
+            "fill" => self.fill(&order_id, required("qty")?.extract()?, required("price")?.extract()?, event_id),
+            "cancel" => self.cancel(&order_id, event_id),
+            "reject" => self.reject(&order_id, item("reason")?.map(|v| v.extract()).transpose()?, event_id),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown order event type '{}', expected 'new', 'fill', 'cancel' or 'reject'", kind
+            ))),
+        }
+    }
+
+    /// Status of an order
+    fn status(&self, order_id: &str) -> PyResult<&'static str> {
+        Ok(self.get(order_id)?.status.name())
+    }
+
+    /// An order's details and fill state as a dict
+    fn order<'py>(&self, py: Python<'py>, order_id: &str) -> PyResult<Bound<'py, PyDict>> {
+        self.get(order_id)?.to_dict(py, order_id)
+    }
+
+    /// Ids of the open (new or partially filled) orders, of `symbol` or all, sorted
+    #[pyo3(signature = (symbol=None))]
+    fn open_orders(&self, symbol: Option<&str>) -> Vec<String> {
+        self.orders
+            .iter()
+            .filter(|(_, o)| o.status.is_open() && symbol.is_none_or(|s| o.symbol == s))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Signed unfilled quantity of the open orders in `symbol`, buys positive
+    fn open_quantity(&self, symbol: &str) -> f64 {
+        self.orders
+            .values()
+            .filter(|o| o.symbol == symbol)
+            .map(|o| if o.side == Side::Buy { o.leaves() } else { -o.leaves() })
+            .sum()
+    }
+
+    fn __len__(&self) -> usize {
+        self.orders.len()
+    }
+
+    fn __contains__(&self, order_id: &str) -> bool {
+        self.orders.contains_key(order_id)
+    }
+
+    fn __repr__(&self) -> String {
+        let open = self.orders.values().filter(|o| o.status.is_open()).count();
+        format!("OrderManager(orders={}, open={})", self.orders.len(), open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> OrderManager {
+        let mut manager = OrderManager::new();
+        manager.submit("a", "ES", "buy", 10.0, Some(100.0), Some("e0".into())).unwrap();
+        manager
+    }
+
+    #[test]
+    fn partial_fills_complete_the_order() {
+        pyo3::prepare_freethreaded_python();
+        let mut manager = manager();
+        assert_eq!(manager.fill("a", 4.0, 100.0, None).unwrap(), "partially_filled");
+        assert_eq!(manager.open_quantity("ES"), 6.0);
+        assert_eq!(manager.fill("a", 6.0, 101.0, None).unwrap(), "filled");
+        let order = manager.get("a").unwrap();
+        assert_eq!((order.filled, order.leaves()), (10.0, 0.0));
+        assert!((order.average_price - 100.6).abs() < 1e-12);
+        assert!(manager.open_orders(None).is_empty());
+    }
+
+    #[test]
+    fn invalid_moves_raise_and_leave_the_state() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut manager = manager();
+            manager.fill("a", 4.0, 100.0, None).unwrap();
+            let err = manager.fill("a", 7.0, 100.0, None).unwrap_err();
+            assert!(err.is_instance_of::<InvalidTransitionError>(py));
+            assert_eq!(manager.get("a").unwrap().filled, 4.0);
+
+            assert_eq!(manager.cancel("a", None).unwrap(), "cancelled");
+            let err = manager.fill("a", 1.0, 100.0, None).unwrap_err();
+            assert!(err.is_instance_of::<InvalidTransitionError>(py));
+            assert!(manager.reject("a", None, None).unwrap_err().is_instance_of::<InvalidTransitionError>(py));
+            assert_eq!(manager.status("a").unwrap(), "cancelled");
+            assert_eq!(manager.get("a").unwrap().filled, 4.0);
+        });
+    }
+
+    #[test]
+    fn duplicate_event_ids_are_ignored_per_type() {
+        pyo3::prepare_freethreaded_python();
+        let mut manager = manager();
+        manager.fill("a", 4.0, 100.0, Some("x1".into())).unwrap();
+        assert_eq!(manager.fill("a", 4.0, 100.0, Some("x1".into())).unwrap(), "partially_filled");
+        assert_eq!(manager.get("a").unwrap().filled, 4.0);
+        // The same id on another event type is a different event
+        assert_eq!(manager.fill("a", 1.0, 100.0, Some("e0".into())).unwrap(), "partially_filled");
+        assert_eq!(manager.cancel("a", Some("x1".into())).unwrap(), "cancelled");
+        assert_eq!(manager.get("a").unwrap().filled, 5.0);
+    }
+
+    #[test]
+    fn event_ids_are_kept_per_order() {
+        pyo3::prepare_freethreaded_python();
+        let mut manager = manager();
+        manager.submit("b", "ES", "buy", 10.0, Some(100.0), None).unwrap();
+        // Two venues reusing an execution id fill different orders
+        manager.fill("a", 4.0, 100.0, Some("x1".into())).unwrap();
+        assert_eq!(manager.fill("b", 3.0, 100.0, Some("x1".into())).unwrap(), "partially_filled");
+        assert_eq!(manager.get("b").unwrap().filled, 3.0);
+        manager.fill("b", 3.0, 100.0, Some("x1".into())).unwrap();
+        assert_eq!(manager.get("b").unwrap().filled, 3.0);
+
+        // A closed order forgets its ids
+        manager.fill("a", 6.0, 100.0, Some("x2".into())).unwrap();
+        manager.cancel("b", Some("c1".into())).unwrap();
+        assert!(manager.orders.values().all(|o| o.applied.is_empty()));
+    }
+}
@@ -0,0 +1,62 @@
+// Derivative-free minimisation for the model fits (GARCH and ARIMA likelihoods), whose
+// objectives are cheap to evaluate but awkward to differentiate. Constraints are
+// expressed by returning infinity outside the feasible region.
+
+/// Minimise `f` from `x0` with the Nelder-Mead simplex, starting from steps of `step` along
+/// each axis. Stops once the simplex's values agree within `tolerance` (relative) or after
+/// `max_iter` iterations; returns the best point and its value.
+pub fn nelder_mead<F: Fn(&[f64]) -> f64>(f: F, x0: &[f64], step: &[f64], tolerance: f64, max_iter: usize) -> (Vec<f64>, f64) {
+    let n = x0.len();
+    let value = |x: &[f64]| {
+        let v = f(x);
+        if v.is_nan() { f64::INFINITY } else { v }
+    };
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+        .map(|i| {
+            let mut x = x0.to_vec();
+            if i > 0 {
+                x[i - 1] += step[i - 1];
+            }
+            let v = value(&x);
+            (x, v)
+        })
+        .collect();
+
+    for _ in 0..max_iter {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[n].1);
+        if (worst - best).abs() <= tolerance * (best.abs() + tolerance) {
+            break;
+        }
+        let centroid: Vec<f64> = (0..n).map(|j| simplex[..n].iter().map(|p| p.0[j]).sum::<f64>() / n as f64).collect();
+        let towards = |t: f64| -> Vec<f64> {
+            centroid.iter().zip(&simplex[n].0).map(|(c, w)| c + t * (w - c)).collect()
+        };
+
+        let reflected = towards(-1.0);
+        let reflected_value = value(&reflected);
+        if reflected_value < best {
+            let expanded = towards(-2.0);
+            let expanded_value = value(&expanded);
+            simplex[n] = if expanded_value < reflected_value { (expanded, expanded_value) } else { (reflected, reflected_value) };
+        } else if reflected_value < simplex[n - 1].1 {
+            simplex[n] = (reflected, reflected_value);
+        } else {
+            let outside = reflected_value < worst;
+            let contracted = towards(if outside { -0.5 } else { 0.5 });
+            let contracted_value = value(&contracted);
+            if contracted_value < reflected_value.min(worst) {
+                simplex[n] = (contracted, contracted_value);
+            } else {
+                // Shrink every point towards the best
+                let anchor = simplex[0].0.clone();
+                for point in &mut simplex[1..] {
+                    point.0.iter_mut().zip(&anchor).for_each(|(x, a)| *x = a + 0.5 * (*x - a));
+                    point.1 = value(&point.0);
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex.swap_remove(0)
+}
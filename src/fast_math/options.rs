@@ -0,0 +1,767 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use rayon::prelude::*;
+use statrs::distribution::{Continuous, ContinuousCDF, Normal};
+
+use crate::column::Column;
+use crate::threads;
+
+/// Call or put
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "call" | "c" => Ok(OptionType::Call),
+            "put" | "p" => Ok(OptionType::Put),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown option_type '{}', expected 'call' or 'put'", name
+            ))),
+        }
+    }
+
+    /// +1 for calls, -1 for puts, the sign the put-call formulas differ by
+    pub fn sign(self) -> f64 {
+        match self {
+            OptionType::Call => 1.0,
+            OptionType::Put => -1.0,
+        }
+    }
+}
+
+/// A pricing input: one value for every option, or one per option
+#[derive(FromPyObject)]
+pub enum Param<'py> {
+    Value(f64),
+    Array(Column<'py, f64>),
+}
+
+/// Inputs of one option
+#[derive(Clone, Copy)]
+pub struct Contract {
+    pub spot: f64,
+    pub strike: f64,
+    /// Time to expiry in years
+    pub expiry: f64,
+    pub rate: f64,
+    pub volatility: f64,
+    /// Continuous dividend yield
+    pub dividend: f64,
+}
+
+/// `N` pricing inputs broadcast against each other: scalars repeat, arrays must share a
+/// length. `None` for the length when every input is a scalar.
+pub struct Chain<const N: usize> {
+    columns: Vec<Vec<f64>>,
+    pub len: Option<usize>,
+}
+
+impl<const N: usize> Chain<N> {
+    pub fn new(params: [(&str, Param<'_>); N]) -> PyResult<Self> {
+        let mut len: Option<(usize, &str)> = None;
+        let mut columns = Vec::with_capacity(N);
+        for (name, param) in params {
+            columns.push(match param {
+                Param::Value(value) => vec![value],
+                Param::Array(column) => {
+                    let values = column.values()?.into_owned();
+                    match len {
+                        Some((n, first)) if n != values.len() => {
+                            return Err(crate::errors::LengthMismatchError::new_err(format!(
+                                "{} has length {} but {} has length {}", name, values.len(), first, n
+                            )))
+                        }
+                        Some(_) => {}
+                        None => len = Some((values.len(), name)),
+                    }
+                    values
+                }
+            });
+        }
+        Ok(Chain { columns, len: len.map(|(n, _)| n) })
+    }
+
+    /// The inputs of the `i`th option, in the order they were given
+    pub fn row(&self, i: usize) -> [f64; N] {
+        std::array::from_fn(|k| {
+            let column = &self.columns[k];
+            if column.len() == 1 { column[0] } else { column[i] }
+        })
+    }
+
+    /// Evaluate `f` on every option's inputs in parallel
+    pub fn map<R: Send, F: Fn([f64; N]) -> R + Sync>(&self, f: F) -> Vec<R> {
+        threads::install(|| (0..self.len.unwrap_or(1)).into_par_iter().map(|i| f(self.row(i))).collect())
+    }
+
+    /// One float when every input is a scalar, else an array
+    pub fn wrap(&self, py: Python<'_>, values: Vec<f64>) -> PyObject {
+        match self.len {
+            None => values[0].into_py(py),
+            Some(_) => PyArray1::from_vec_bound(py, values).into_any().unbind(),
+        }
+    }
+}
+
+impl Contract {
+    /// A contract from the inputs `[spot, strike, expiry, rate, volatility, dividend]`
+    pub fn from_row([spot, strike, expiry, rate, volatility, dividend]: [f64; 6]) -> Self {
+        Contract { spot, strike, expiry, rate, volatility, dividend }
+    }
+
+    /// The no-arbitrage bounds of the option's price: its discounted intrinsic value and
+    /// the discounted spot (calls) or strike (puts)
+    fn price_bounds(&self, option_type: OptionType) -> (f64, f64) {
+        let spot_pv = self.spot * (-self.dividend * self.expiry).exp();
+        let strike_pv = self.strike * (-self.rate * self.expiry).exp();
+        match option_type {
+            OptionType::Call => ((spot_pv - strike_pv).max(0.0), spot_pv),
+            OptionType::Put => ((strike_pv - spot_pv).max(0.0), strike_pv),
+        }
+    }
+}
+
+/// Black-Scholes-Merton price and sensitivities of one option
+#[derive(Clone, Copy)]
+struct Valuation {
+    price: f64,
+    delta: f64,
+    gamma: f64,
+    vega: f64,
+    theta: f64,
+    rho: f64,
+}
+
+fn value(c: Contract, option_type: OptionType) -> Valuation {
+    let nan = Valuation { price: f64::NAN, delta: f64::NAN, gamma: f64::NAN, vega: f64::NAN, theta: f64::NAN, rho: f64::NAN };
+    if c.spot.is_nan() || c.spot <= 0.0 || c.strike.is_nan() || c.strike <= 0.0 {
+        return nan;
+    }
+    if c.expiry.is_nan() || c.expiry < 0.0 || c.volatility.is_nan() || c.volatility < 0.0 {
+        return nan;
+    }
+    let phi = option_type.sign();
+    let spot_pv = c.spot * (-c.dividend * c.expiry).exp();
+    let strike_pv = c.strike * (-c.rate * c.expiry).exp();
+    let deviation = c.volatility * c.expiry.sqrt();
+    if deviation == 0.0 {
+        // No uncertainty left: the option is its discounted forward payoff
+        if phi * (spot_pv - strike_pv) <= 0.0 {
+            return Valuation { price: 0.0, delta: 0.0, gamma: 0.0, vega: 0.0, theta: 0.0, rho: 0.0 };
+        }
+        return Valuation {
+            price: phi * (spot_pv - strike_pv),
+            delta: phi * spot_pv / c.spot,
+            gamma: 0.0,
+            vega: 0.0,
+            theta: phi * (c.dividend * spot_pv - c.rate * strike_pv),
+            rho: phi * c.expiry * strike_pv,
+        };
+    }
+
+    let normal = Normal::new(0.0, 1.0).unwrap();
+    let d1 = ((spot_pv / strike_pv).ln() + 0.5 * deviation * deviation) / deviation;
+    let d2 = d1 - deviation;
+    let (nd1, nd2) = (normal.cdf(phi * d1), normal.cdf(phi * d2));
+    let density = normal.pdf(d1);
+    Valuation {
+        price: phi * (spot_pv * nd1 - strike_pv * nd2),
+        delta: phi * spot_pv / c.spot * nd1,
+        gamma: spot_pv * density / (c.spot * c.spot * deviation),
+        vega: spot_pv * density * c.expiry.sqrt(),
+        theta: -spot_pv * density * c.volatility / (2.0 * c.expiry.sqrt())
+            + phi * (c.dividend * spot_pv * nd1 - c.rate * strike_pv * nd2),
+        rho: phi * c.expiry * strike_pv * nd2,
+    }
+}
+
+/// Black-Scholes-Merton price of European options.
+///
+/// Each of `spot`, `strike`, `expiry` (in years), `rate` and `volatility` (both continuous,
+/// annualised) and the continuous `dividend` yield is a number or an array, arrays sharing
+/// one length and numbers applying to every option, so a whole chain of strikes and
+/// expiries prices in one parallel call. `option_type` is "call" or "put". Returns a float
+/// when every input is a number, else an array. A zero expiry or volatility gives the
+/// discounted intrinsic value; non-positive prices and negative expiries or volatilities
+/// give NaN.
+#[pyfunction]
+#[pyo3(signature = (spot, strike, expiry, rate, volatility, option_type="call", dividend=Param::Value(0.0)))]
+#[allow(clippy::too_many_arguments)]
+pub fn bs_price_rust<'py>(
+    py: Python<'py>,
+    spot: Param<'py>,
+    strike: Param<'py>,
+    expiry: Param<'py>,
+    rate: Param<'py>,
+    volatility: Param<'py>,
+    option_type: &str,
+    dividend: Param<'py>
+) -> PyResult<PyObject> {
+    let option_type = OptionType::parse(option_type)?;
+    let chain = Chain::new([
+        ("spot", spot),
+        ("strike", strike),
+        ("expiry", expiry),
+        ("rate", rate),
+        ("volatility", volatility),
+        ("dividend", dividend),
+    ])?;
+    let prices = py.allow_threads(|| chain.map(|row| value(Contract::from_row(row), option_type).price));
+    Ok(chain.wrap(py, prices))
+}
+
+/// Black-Scholes-Merton Greeks of European options.
+///
+/// Inputs broadcast as for `bs_price_rust`. Returns a dict with "delta", "gamma", "vega",
+/// "theta" and "rho", each a float or an array: the raw partial derivatives, so vega and
+/// rho are per unit (not per 1%) of volatility and rate and theta is the change per year
+/// of calendar time (divide by 365 for a daily theta). A zero expiry or volatility gives
+/// the limits of the discounted intrinsic value (no gamma or vega).
+#[pyfunction]
+#[pyo3(signature = (spot, strike, expiry, rate, volatility, option_type="call", dividend=Param::Value(0.0)))]
+#[allow(clippy::too_many_arguments)]
+pub fn bs_greeks_rust<'py>(
+    py: Python<'py>,
+    spot: Param<'py>,
+    strike: Param<'py>,
+    expiry: Param<'py>,
+    rate: Param<'py>,
+    volatility: Param<'py>,
+    option_type: &str,
+    dividend: Param<'py>
+) -> PyResult<Bound<'py, PyDict>> {
+    let option_type = OptionType::parse(option_type)?;
+    let chain = Chain::new([
+        ("spot", spot),
+        ("strike", strike),
+        ("expiry", expiry),
+        ("rate", rate),
+        ("volatility", volatility),
+        ("dividend", dividend),
+    ])?;
+    let valuations = py.allow_threads(|| chain.map(|row| value(Contract::from_row(row), option_type)));
+    let result = PyDict::new_bound(py);
+    let greek = |f: fn(&Valuation) -> f64| valuations.iter().map(f).collect::<Vec<f64>>();
+    for (name, values) in [
+        ("delta", greek(|v| v.delta)),
+        ("gamma", greek(|v| v.gamma)),
+        ("vega", greek(|v| v.vega)),
+        ("theta", greek(|v| v.theta)),
+        ("rho", greek(|v| v.rho)),
+    ] {
+        result.set_item(name, chain.wrap(py, values))?;
+    }
+    Ok(result)
+}
+
+/// Volatility at which the option is worth `price`, by Newton's method on vega kept
+/// inside a bracket that every evaluation narrows, bisecting whenever a step would leave it
+fn implied_vol(price: f64, contract: Contract, option_type: OptionType, tolerance: f64, max_iter: usize) -> f64 {
+    let c = contract;
+    if price.is_nan() || c.spot.is_nan() || c.spot <= 0.0 || c.strike.is_nan() || c.strike <= 0.0 {
+        return f64::NAN;
+    }
+    if c.expiry.is_nan() || c.expiry <= 0.0 {
+        return f64::NAN;
+    }
+    let (low, high) = c.price_bounds(option_type);
+    if price < low - tolerance || price >= high {
+        return f64::NAN;
+    }
+    if price <= low {
+        return 0.0;
+    }
+    let at = |volatility: f64| value(Contract { volatility, ..c }, option_type);
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    while at(hi).price < price {
+        lo = hi;
+        hi *= 2.0;
+        if hi > 1e3 {
+            return f64::NAN;
+        }
+    }
+    // Manaster-Koehler's guess, the inflection point of the price curve, or
+    // Brenner-Subrahmanyam's at the money
+    let moneyness = ((c.spot / c.strike).ln() + (c.rate - c.dividend) * c.expiry).abs();
+    let atm = (2.0 * std::f64::consts::PI / c.expiry).sqrt() * price / (c.spot * (-c.dividend * c.expiry).exp());
+    let mut sigma = (2.0 * moneyness / c.expiry).sqrt().max(atm);
+    if sigma <= lo || sigma >= hi {
+        sigma = 0.5 * (lo + hi);
+    }
+    for _ in 0..max_iter {
+        let v = at(sigma);
+        let diff = v.price - price;
+        if diff.abs() <= tolerance {
+            return sigma;
+        }
+        if diff > 0.0 {
+            hi = sigma;
+        } else {
+            lo = sigma;
+        }
+        let newton = sigma - diff / v.vega;
+        sigma = if v.vega > 0.0 && newton > lo && newton < hi { newton } else { 0.5 * (lo + hi) };
+        if hi - lo <= f64::EPSILON * hi {
+            return sigma;
+        }
+    }
+    f64::NAN
+}
+
+/// Black-Scholes-Merton implied volatility of European option prices.
+///
+/// `price`, `spot`, `strike`, `tte` (time to expiry in years), `rate` and `dividend` are
+/// numbers or arrays broadcast as for `bs_price_rust`, and `flag` is "call" or "put" (or
+/// "c" and "p"), so a whole chain solves in one parallel call. Each volatility is found by
+/// Newton steps on vega safeguarded by a bracket, falling back to bisection where Newton
+/// would leave it, until the model price is within `tolerance` of `price`. Returns a float
+/// when every input is a number, else an array. Prices at the discounted intrinsic value
+/// give 0; prices outside the no-arbitrage bounds, a non-positive `tte` or no convergence
+/// within `max_iter` steps give NaN.
+#[pyfunction]
+#[pyo3(signature = (price, spot, strike, tte, rate, flag="call", dividend=Param::Value(0.0), tolerance=1e-10, max_iter=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn implied_vol_rust<'py>(
+    py: Python<'py>,
+    price: Param<'py>,
+    spot: Param<'py>,
+    strike: Param<'py>,
+    tte: Param<'py>,
+    rate: Param<'py>,
+    flag: &str,
+    dividend: Param<'py>,
+    tolerance: f64,
+    max_iter: usize
+) -> PyResult<PyObject> {
+    let option_type = OptionType::parse(flag)?;
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tolerance must be > 0"
+        ));
+    }
+    let chain = Chain::new([
+        ("price", price),
+        ("spot", spot),
+        ("strike", strike),
+        ("tte", tte),
+        ("rate", rate),
+        ("dividend", dividend),
+    ])?;
+    let vols = py.allow_threads(|| {
+        chain.map(|[price, spot, strike, expiry, rate, dividend]| {
+            let contract = Contract { spot, strike, expiry, rate, volatility: f64::NAN, dividend };
+            implied_vol(price, contract, option_type, tolerance, max_iter)
+        })
+    });
+    Ok(chain.wrap(py, vols))
+}
+
+/// Lattice used to price American options
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tree {
+    /// Cox-Ross-Rubinstein binomial tree
+    Binomial,
+    /// Boyle's trinomial tree
+    Trinomial,
+}
+
+impl Tree {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "binomial" | "crr" => Ok(Tree::Binomial),
+            "trinomial" => Ok(Tree::Trinomial),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'binomial' or 'trinomial'", name
+            ))),
+        }
+    }
+}
+
+/// American option price by backward induction over a recombining tree, exercising
+/// wherever the payoff beats the discounted continuation value
+fn american_price(c: Contract, option_type: OptionType, steps: usize, tree: Tree) -> f64 {
+    if c.spot.is_nan() || c.spot <= 0.0 || c.strike.is_nan() || c.strike <= 0.0 {
+        return f64::NAN;
+    }
+    if c.expiry.is_nan() || c.expiry < 0.0 || c.volatility.is_nan() || c.volatility <= 0.0 {
+        return f64::NAN;
+    }
+    let phi = option_type.sign();
+    let payoff = |spot: f64| (phi * (spot - c.strike)).max(0.0);
+    if c.expiry == 0.0 {
+        return payoff(c.spot);
+    }
+    let dt = c.expiry / steps as f64;
+    let discount = (-c.rate * dt).exp();
+    let growth = ((c.rate - c.dividend) * dt).exp();
+
+    // After `step` steps the node at `level` (from the lowest) is the spot moved
+    // `stride * level - step` times by the factor `up`; a step adds `span` levels
+    let (up, span, stride, probabilities) = match tree {
+        Tree::Binomial => {
+            let up = (c.volatility * dt.sqrt()).exp();
+            let p = (growth - 1.0 / up) / (up - 1.0 / up);
+            (up, 1, 2, vec![1.0 - p, p])
+        }
+        Tree::Trinomial => {
+            let half = (c.volatility * (dt / 2.0).sqrt()).exp();
+            let root = growth.sqrt();
+            let p_up = ((root - 1.0 / half) / (half - 1.0 / half)).powi(2);
+            let p_down = ((half - root) / (half - 1.0 / half)).powi(2);
+            (half * half, 2, 1, vec![p_down, 1.0 - p_up - p_down, p_up])
+        }
+    };
+    // Too little volatility for the drift leaves no risk-neutral probabilities
+    if probabilities.iter().any(|&p| !(0.0..=1.0).contains(&p)) {
+        return f64::NAN;
+    }
+    let spot_at = |step: usize, level: usize| c.spot * up.powi((stride * level) as i32 - step as i32);
+    let mut values: Vec<f64> = (0..=steps * span).map(|level| payoff(spot_at(steps, level))).collect();
+    for step in (0..steps).rev() {
+        for level in 0..=step * span {
+            let continuation: f64 = probabilities.iter().enumerate().map(|(k, p)| p * values[level + k]).sum();
+            values[level] = (discount * continuation).max(payoff(spot_at(step, level)));
+        }
+        values.truncate(step * span + 1);
+    }
+    values[0]
+}
+
+/// American option prices on a binomial or trinomial tree.
+///
+/// `spot`, `strike`, `tte` (time to expiry in years), `rate`, the continuous dividend
+/// yield `div` and `vol` are numbers or arrays broadcast as for `bs_price_rust`, and
+/// `flag` is "call" or "put"; contracts are priced in parallel. `method="binomial"` (the
+/// default) uses the Cox-Ross-Rubinstein tree, "trinomial" Boyle's, which converges with
+/// fewer `steps`; each costs O(steps²) per contract. Early exercise is checked at every
+/// node. Returns a float when every input is a number, else an array. A zero `tte` gives
+/// the intrinsic value; invalid inputs, a zero `vol` or too few steps for the drift (no
+/// valid risk-neutral probabilities) give NaN.
+#[pyfunction]
+#[pyo3(signature = (spot, strike, tte, rate, div, vol, steps=500, method="binomial", flag="put"))]
+#[allow(clippy::too_many_arguments)]
+pub fn american_price_rust<'py>(
+    py: Python<'py>,
+    spot: Param<'py>,
+    strike: Param<'py>,
+    tte: Param<'py>,
+    rate: Param<'py>,
+    div: Param<'py>,
+    vol: Param<'py>,
+    steps: usize,
+    method: &str,
+    flag: &str
+) -> PyResult<PyObject> {
+    let tree = Tree::parse(method)?;
+    let option_type = OptionType::parse(flag)?;
+    if steps == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "steps must be > 0"
+        ));
+    }
+    let chain = Chain::new([
+        ("spot", spot),
+        ("strike", strike),
+        ("tte", tte),
+        ("rate", rate),
+        ("div", div),
+        ("vol", vol),
+    ])?;
+    let prices = py.allow_threads(|| {
+        chain.map(|[spot, strike, expiry, rate, dividend, volatility]| {
+            american_price(Contract { spot, strike, expiry, rate, volatility, dividend }, option_type, steps, tree)
+        })
+    });
+    Ok(chain.wrap(py, prices))
+}
+
+/// One leg of an option strategy, held to expiry
+struct Leg {
+    /// `None` for the underlying itself
+    option_type: Option<OptionType>,
+    strike: f64,
+    /// Signed number of units, negative for short legs, times the contract multiplier
+    units: f64,
+    /// Premium per unit for options, entry price for the underlying
+    cost: f64,
+}
+
+impl Leg {
+    /// Parse a dict with `type` ("call", "put" or "stock"), `strike` (options) or `price`
+    /// (stock), and optional `quantity` (1), `premium` (0) and `multiplier` (1)
+    fn parse(index: usize, leg: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let number = |key: &str, default: Option<f64>| -> PyResult<f64> {
+            match (leg.get_item(key)?, default) {
+                (Some(value), _) => value.extract(),
+                (None, Some(default)) => Ok(default),
+                (None, None) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "leg {} needs a '{}' key", index, key
+                ))),
+            }
+        };
+        let kind: String = match leg.get_item("type")? {
+            Some(kind) => kind.extract()?,
+            None => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "leg {} needs a 'type' key", index
+                )))
+            }
+        };
+        let units = number("quantity", Some(1.0))? * number("multiplier", Some(1.0))?;
+        match kind.to_ascii_lowercase().as_str() {
+            "stock" | "underlying" | "future" => {
+                Ok(Leg { option_type: None, strike: 0.0, units, cost: number("price", None)? })
+            }
+            option_type => Ok(Leg {
+                option_type: Some(OptionType::parse(option_type)?),
+                strike: number("strike", None)?,
+                units,
+                cost: number("premium", Some(0.0))?,
+            }),
+        }
+    }
+
+    fn payoff(&self, spot: f64) -> f64 {
+        let value = match self.option_type {
+            Some(option_type) => (option_type.sign() * (spot - self.strike)).max(0.0),
+            None => spot,
+        };
+        self.units * value
+    }
+
+    /// Change of the payoff per unit of spot above every strike
+    fn tail_slope(&self) -> f64 {
+        match self.option_type {
+            Some(OptionType::Put) => 0.0,
+            _ => self.units,
+        }
+    }
+}
+
+/// Strategy P&L at expiry: piecewise linear in the spot with kinks at the strikes
+struct Strategy {
+    legs: Vec<Leg>,
+    cost: f64,
+}
+
+impl Strategy {
+    fn payoff(&self, spot: f64) -> f64 {
+        self.legs.iter().map(|leg| leg.payoff(spot)).sum()
+    }
+
+    fn pnl(&self, spot: f64) -> f64 {
+        self.payoff(spot) - self.cost
+    }
+
+    /// Zero spot, then every distinct strike ascending: the ends of the linear pieces
+    fn kinks(&self) -> Vec<f64> {
+        let mut kinks: Vec<f64> = std::iter::once(0.0)
+            .chain(self.legs.iter().filter(|l| l.option_type.is_some()).map(|l| l.strike).filter(|&k| k > 0.0))
+            .collect();
+        kinks.sort_by(f64::total_cmp);
+        kinks.dedup();
+        kinks
+    }
+
+    /// Spots where the P&L crosses zero, exactly from the linear pieces
+    fn breakevens(&self) -> Vec<f64> {
+        let kinks = self.kinks();
+        let mut roots = Vec::new();
+        for pair in kinks.windows(2) {
+            let (a, b) = (self.pnl(pair[0]), self.pnl(pair[1]));
+            if a == 0.0 {
+                roots.push(pair[0]);
+            } else if a * b < 0.0 {
+                roots.push(pair[0] + (pair[1] - pair[0]) * a / (a - b));
+            }
+        }
+        let last = kinks[kinks.len() - 1];
+        let (value, slope) = (self.pnl(last), self.legs.iter().map(Leg::tail_slope).sum::<f64>());
+        if value == 0.0 {
+            roots.push(last);
+        } else if slope != 0.0 && -value / slope > 0.0 {
+            roots.push(last - value / slope);
+        }
+        roots
+    }
+
+    /// Largest gain and loss over all spots >= 0, infinite where the last piece keeps
+    /// rising or falling
+    fn extremes(&self) -> (f64, f64) {
+        let values: Vec<f64> = self.kinks().iter().map(|&s| self.pnl(s)).collect();
+        let mut gain = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mut loss = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let slope: f64 = self.legs.iter().map(Leg::tail_slope).sum();
+        if slope > 0.0 {
+            gain = f64::INFINITY;
+        } else if slope < 0.0 {
+            loss = f64::NEG_INFINITY;
+        }
+        (gain, loss)
+    }
+}
+
+/// Expiry payoff and P&L of a multi-leg option strategy over a grid of spots.
+///
+/// `legs` is a list of dicts with `type` ("call", "put" or "stock"), `strike` for options
+/// or the entry `price` for stock, and optional `quantity` (negative for short legs,
+/// default 1), `premium` paid per unit (received when short; default 0) and `multiplier`
+/// (default 1). Returns a dict with `payoff` and `pnl` (payoff minus the net premium and
+/// stock cost) at each spot of `spot_grid`, and, exactly from the piecewise-linear P&L
+/// rather than the grid, the `breakevens` at spots >= 0 and the `max_gain` and `max_loss`
+/// (the most negative P&L), infinite when the P&L is unbounded as the spot rises.
+#[pyfunction]
+pub fn strategy_payoff_rust<'py>(
+    py: Python<'py>,
+    legs: Vec<Bound<'py, PyDict>>,
+    spot_grid: Column<'py, f64>
+) -> PyResult<Bound<'py, PyDict>> {
+    if legs.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "legs must not be empty"
+        ));
+    }
+    let legs = legs.iter().enumerate().map(|(i, leg)| Leg::parse(i, leg)).collect::<PyResult<Vec<_>>>()?;
+    let cost = legs.iter().map(|leg| leg.units * leg.cost).sum();
+    let strategy = Strategy { legs, cost };
+    let spots = spot_grid.values()?;
+    let (payoff, pnl, breakevens, (max_gain, max_loss)) = py.allow_threads(|| {
+        let payoff: Vec<f64> = spots.iter().map(|&s| strategy.payoff(s)).collect();
+        let pnl = payoff.iter().map(|p| p - strategy.cost).collect::<Vec<f64>>();
+        (payoff, pnl, strategy.breakevens(), strategy.extremes())
+    });
+    let result = PyDict::new_bound(py);
+    result.set_item("payoff", PyArray1::from_vec_bound(py, payoff))?;
+    result.set_item("pnl", PyArray1::from_vec_bound(py, pnl))?;
+    result.set_item("breakevens", PyArray1::from_vec_bound(py, breakevens))?;
+    result.set_item("max_gain", max_gain)?;
+    result.set_item("max_loss", max_loss)?;
+    Ok(result)
+}
+
+/// Populate the `fast_math.options` submodule
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(bs_price_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(bs_greeks_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(implied_vol_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(american_price_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(strategy_payoff_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::montecarlo::mc_price_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::montecarlo::gbm_paths_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::montecarlo::correlated_paths_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::montecarlo::heston_paths_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::montecarlo::jump_diffusion_paths_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::svi::svi_fit_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(crate::svi::surface_interp_rust, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract() -> Contract {
+        Contract { spot: 100.0, strike: 95.0, expiry: 0.75, rate: 0.03, volatility: 0.25, dividend: 0.01 }
+    }
+
+    #[test]
+    fn prices_satisfy_put_call_parity() {
+        for strike in [60.0, 95.0, 100.0, 140.0] {
+            let c = Contract { strike, ..contract() };
+            let call = value(c, OptionType::Call).price;
+            let put = value(c, OptionType::Put).price;
+            let forward = c.spot * (-c.dividend * c.expiry).exp() - strike * (-c.rate * c.expiry).exp();
+            assert!((call - put - forward).abs() < 1e-10, "strike {}", strike);
+        }
+        // At-the-money call without rates or dividends, from the standard tables
+        let atm = Contract { strike: 100.0, expiry: 1.0, rate: 0.0, volatility: 0.2, dividend: 0.0, ..contract() };
+        assert!((value(atm, OptionType::Call).price - 7.965_567_455_405_804).abs() < 1e-9);
+    }
+
+    #[test]
+    fn greeks_match_finite_differences() {
+        let c = contract();
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let v = value(c, option_type);
+            let price = |c: Contract| value(c, option_type).price;
+            let central = |up: Contract, down: Contract, h: f64| (price(up) - price(down)) / (2.0 * h);
+            let h = 1e-4;
+            let delta = central(Contract { spot: c.spot + h, ..c }, Contract { spot: c.spot - h, ..c }, h);
+            let gamma = (price(Contract { spot: c.spot + h, ..c }) - 2.0 * v.price + price(Contract { spot: c.spot - h, ..c })) / (h * h);
+            let vega = central(Contract { volatility: c.volatility + h, ..c }, Contract { volatility: c.volatility - h, ..c }, h);
+            // Theta is the change as time passes, so against a shrinking expiry
+            let theta = -central(Contract { expiry: c.expiry + h, ..c }, Contract { expiry: c.expiry - h, ..c }, h);
+            let rho = central(Contract { rate: c.rate + h, ..c }, Contract { rate: c.rate - h, ..c }, h);
+            for (name, analytic, numeric) in [
+                ("delta", v.delta, delta),
+                ("gamma", v.gamma, gamma),
+                ("vega", v.vega, vega),
+                ("theta", v.theta, theta),
+                ("rho", v.rho, rho),
+            ] {
+                assert!((analytic - numeric).abs() < 1e-4 * analytic.abs().max(1.0), "{}: {} vs {}", name, analytic, numeric);
+            }
+        }
+    }
+
+    #[test]
+    fn implied_vol_inverts_the_price() {
+        for (strike, option_type) in [(70.0, OptionType::Put), (95.0, OptionType::Call), (130.0, OptionType::Call)] {
+            for volatility in [0.15, 0.25, 1.5] {
+                let c = Contract { strike, volatility, ..contract() };
+                let price = value(c, option_type).price;
+                let solved = implied_vol(price, Contract { volatility: f64::NAN, ..c }, option_type, 1e-12, 100);
+                assert!((solved - volatility).abs() < 1e-6, "strike {} vol {}: {}", strike, volatility, solved);
+            }
+        }
+        let c = contract();
+        let (low, high) = c.price_bounds(OptionType::Call);
+        // Below intrinsic or at the discounted spot no volatility fits
+        assert!(implied_vol(low - 1.0, c, OptionType::Call, 1e-10, 100).is_nan());
+        assert!(implied_vol(high, c, OptionType::Call, 1e-10, 100).is_nan());
+        assert_eq!(implied_vol(low, c, OptionType::Call, 1e-10, 100), 0.0);
+        assert!(implied_vol(5.0, Contract { expiry: 0.0, ..c }, OptionType::Call, 1e-10, 100).is_nan());
+    }
+
+    #[test]
+    fn american_options_are_worth_at_least_european_ones() {
+        let c = Contract { strike: 110.0, dividend: 0.0, rate: 0.06, ..contract() };
+        for tree in [Tree::Binomial, Tree::Trinomial] {
+            let american_put = american_price(c, OptionType::Put, 400, tree);
+            let european_put = value(c, OptionType::Put).price;
+            assert!(american_put > european_put + 0.05, "{} vs {}", american_put, european_put);
+            // Early exercise never pays for a call without dividends
+            let american_call = american_price(c, OptionType::Call, 400, tree);
+            let european_call = value(c, OptionType::Call).price;
+            assert!((american_call - european_call).abs() < 0.02, "{} vs {}", american_call, european_call);
+        }
+        assert_eq!(american_price(Contract { expiry: 0.0, ..c }, OptionType::Put, 100, Tree::Binomial), 10.0);
+    }
+
+    fn call(strike: f64, quantity: f64, premium: f64) -> Leg {
+        Leg { option_type: Some(OptionType::Call), strike, units: quantity, cost: premium }
+    }
+
+    #[test]
+    fn vertical_spread_breakeven_and_extremes() {
+        // Long the 100 call for 5, short the 110 call for 2: a net debit of 3
+        let legs = vec![call(100.0, 1.0, 5.0), call(110.0, -1.0, 2.0)];
+        let cost = legs.iter().map(|leg| leg.units * leg.cost).sum();
+        let spread = Strategy { legs, cost };
+        assert_eq!(spread.cost, 3.0);
+        assert_eq!(spread.breakevens(), vec![103.0]);
+        assert_eq!(spread.extremes(), (7.0, -3.0));
+        assert_eq!((spread.pnl(90.0), spread.pnl(105.0), spread.pnl(200.0)), (-3.0, 2.0, 7.0));
+
+        // Without the short leg the gain is unbounded
+        let long = Strategy { legs: vec![call(100.0, 1.0, 5.0)], cost: 5.0 };
+        assert_eq!(long.breakevens(), vec![105.0]);
+        assert_eq!(long.extremes(), (f64::INFINITY, -5.0));
+    }
+}
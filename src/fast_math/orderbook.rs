@@ -0,0 +1,481 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array2, ArrayView1, ArrayView2};
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// Bid side marker in delta streams
+pub const BID: i8 = 1;
+/// Ask side marker in delta streams
+pub const ASK: i8 = -1;
+
+/// Snapshot row, side, price and new size (0 = level removed) for each delta, plus the
+/// number of snapshots diffed
+pub type DeltaArrays<'py> = (
+    Bound<'py, PyArray1<i64>>,
+    Bound<'py, PyArray1<i8>>,
+    Bound<'py, PyArray1<f64>>,
+    Bound<'py, PyArray1<f64>>,
+    usize,
+);
+
+/// Delta stream columns: snapshot row, side, price and new size
+type Deltas = (Vec<i64>, Vec<i8>, Vec<f64>, Vec<f64>);
+
+/// Bid prices, bid sizes, ask prices and ask sizes, one row per snapshot
+pub type BookArrays<'py> = (
+    Bound<'py, PyArray2<f64>>,
+    Bound<'py, PyArray2<f64>>,
+    Bound<'py, PyArray2<f64>>,
+    Bound<'py, PyArray2<f64>>,
+);
+
+/// Collect the populated levels of one side (finite price, positive size), sorted by price
+fn side_levels(prices: ArrayView1<f64>, sizes: ArrayView1<f64>) -> Vec<(f64, f64)> {
+    let mut levels: Vec<(f64, f64)> = prices
+        .iter()
+        .zip(sizes.iter())
+        .filter(|(p, s)| p.is_finite() && **s > 0.0)
+        .map(|(&p, &s)| (p, s))
+        .collect();
+    levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+    levels
+}
+
+/// Merge two sorted level lists, emitting (price, size) for every changed, added or removed level
+fn diff_levels(old: &[(f64, f64)], new: &[(f64, f64)], mut emit: impl FnMut(f64, f64)) {
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if j == new.len() || (i < old.len() && old[i].0 < new[j].0) {
+            emit(old[i].0, 0.0);
+            i += 1;
+        } else if i == old.len() || new[j].0 < old[i].0 {
+            emit(new[j].0, new[j].1);
+            j += 1;
+        } else {
+            if old[i].1 != new[j].1 {
+                emit(new[j].0, new[j].1);
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+}
+
+/// Apply a single level update to a sorted side
+fn apply_level(levels: &mut Vec<(f64, f64)>, price: f64, size: f64) {
+    match levels.binary_search_by(|l| l.0.total_cmp(&price)) {
+        Ok(pos) if size > 0.0 => levels[pos].1 = size,
+        Ok(pos) => {
+            levels.remove(pos);
+        }
+        Err(pos) if size > 0.0 => levels.insert(pos, (price, size)),
+        Err(_) => {}
+    }
+}
+
+/// Write the best `depth` levels of a side into a snapshot row, padding with NaN / 0
+fn write_side<'a>(levels: impl Iterator<Item = &'a (f64, f64)>, prices: &mut [f64], sizes: &mut [f64]) {
+    prices.fill(f64::NAN);
+    sizes.fill(0.0);
+    for ((price, size), (p, s)) in levels.zip(prices.iter_mut().zip(sizes.iter_mut())) {
+        *p = *price;
+        *s = *size;
+    }
+}
+
+/// Diff each snapshot row against the previous one
+fn diff_books(
+    bid_prices: &ArrayView2<f64>,
+    bid_sizes: &ArrayView2<f64>,
+    ask_prices: &ArrayView2<f64>,
+    ask_sizes: &ArrayView2<f64>
+) -> Deltas {
+    let mut rows = Vec::new();
+    let mut sides = Vec::new();
+    let mut prices = Vec::new();
+    let mut sizes = Vec::new();
+
+    let n = bid_prices.nrows();
+    if n > 0 {
+        let mut prev_bids = side_levels(bid_prices.row(0), bid_sizes.row(0));
+        let mut prev_asks = side_levels(ask_prices.row(0), ask_sizes.row(0));
+
+        for row in 1..n {
+            let bids = side_levels(bid_prices.row(row), bid_sizes.row(row));
+            let asks = side_levels(ask_prices.row(row), ask_sizes.row(row));
+
+            for (side, old, new) in [(BID, &prev_bids, &bids), (ASK, &prev_asks, &asks)] {
+                diff_levels(old, new, |price, size| {
+                    rows.push(row as i64);
+                    sides.push(side);
+                    prices.push(price);
+                    sizes.push(size);
+                });
+            }
+
+            prev_bids = bids;
+            prev_asks = asks;
+        }
+    }
+    (rows, sides, prices, sizes)
+}
+
+/// Replay `deltas` on top of the base snapshot `base` (bid prices, bid sizes, ask prices,
+/// ask sizes) into `n_snapshots` rows; deltas must be sorted by row and below `n_snapshots`
+fn replay_books(base: [&[f64]; 4], deltas: (&[i64], &[i8], &[f64], &[f64]), n_snapshots: usize) -> [Array2<f64>; 4] {
+    let (rows, sides, prices, sizes) = deltas;
+    let depth = base[0].len();
+    let mut out = std::array::from_fn(|_| Array2::<f64>::zeros((n_snapshots, depth)));
+    let [out_bid_prices, out_bid_sizes, out_ask_prices, out_ask_sizes] = &mut out;
+
+    let mut bids = side_levels(ArrayView1::from(base[0]), ArrayView1::from(base[1]));
+    let mut asks = side_levels(ArrayView1::from(base[2]), ArrayView1::from(base[3]));
+    let mut next = 0;
+
+    for row in 0..n_snapshots {
+        while next < rows.len() && rows[next] as usize == row {
+            let side = if sides[next] == BID { &mut bids } else { &mut asks };
+            apply_level(side, prices[next], sizes[next]);
+            next += 1;
+        }
+
+        write_side(
+            bids.iter().rev(),
+            out_bid_prices.row_mut(row).as_slice_mut().unwrap(),
+            out_bid_sizes.row_mut(row).as_slice_mut().unwrap(),
+        );
+        write_side(
+            asks.iter(),
+            out_ask_prices.row_mut(row).as_slice_mut().unwrap(),
+            out_ask_sizes.row_mut(row).as_slice_mut().unwrap(),
+        );
+    }
+    out
+}
+
+fn check_book_shapes(arrays: [&ArrayView2<f64>; 4]) -> PyResult<()> {
+    let shape = arrays[0].shape();
+    if arrays.iter().any(|a| a.shape() != shape) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "bid/ask price and size arrays must have the same shape"
+        ));
+    }
+    Ok(())
+}
+
+fn check_delta_rows(rows: &[i64], n_snapshots: usize) -> PyResult<()> {
+    if n_snapshots < 1 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "n_snapshots must be >= 1"
+        ));
+    }
+    if rows.iter().any(|&r| r < 1 || r as u64 >= n_snapshots as u64) || rows.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Delta rows must be in [1, n_snapshots) and sorted ascending"
+        ));
+    }
+    Ok(())
+}
+
+/// Diff consecutive L2 snapshots into a delta stream.
+///
+/// Inputs are 2D arrays of shape (n_snapshots, levels); empty levels are NaN prices or
+/// zero sizes. Row 0 is the base snapshot; every delta carries the row it produces when
+/// applied on top of the previous row. Also returns the number of snapshots, which
+/// `apply_book_deltas_rust` needs to reproduce trailing snapshots that carry no deltas.
+#[pyfunction]
+pub fn diff_book_snapshots_rust<'py>(
+    py: Python<'py>,
+    bid_prices: PyReadonlyArray2<'py, f64>,
+    bid_sizes: PyReadonlyArray2<'py, f64>,
+    ask_prices: PyReadonlyArray2<'py, f64>,
+    ask_sizes: PyReadonlyArray2<'py, f64>
+) -> PyResult<DeltaArrays<'py>> {
+    let bid_prices = bid_prices.as_array();
+    let bid_sizes = bid_sizes.as_array();
+    let ask_prices = ask_prices.as_array();
+    let ask_sizes = ask_sizes.as_array();
+    check_book_shapes([&bid_prices, &bid_sizes, &ask_prices, &ask_sizes])?;
+
+    let n = bid_prices.nrows();
+    let (rows, sides, prices, sizes) = py.allow_threads(|| diff_books(&bid_prices, &bid_sizes, &ask_prices, &ask_sizes));
+
+    Ok((
+        PyArray1::from_vec_bound(py, rows),
+        PyArray1::from_vec_bound(py, sides),
+        PyArray1::from_vec_bound(py, prices),
+        PyArray1::from_vec_bound(py, sizes),
+        n,
+    ))
+}
+
+/// Replay a delta stream on top of a base snapshot.
+///
+/// The base snapshot is given as 1D level arrays and becomes row 0 of the output; deltas
+/// must be ordered by row and below `n_snapshots`. Returns 2D arrays of shape
+/// (n_snapshots, levels) with the same depth as the base snapshot, bids best-first
+/// (descending) and asks best-first (ascending).
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub fn apply_book_deltas_rust<'py>(
+    py: Python<'py>,
+    base_bid_prices: Column<'py, f64>,
+    base_bid_sizes: Column<'py, f64>,
+    base_ask_prices: Column<'py, f64>,
+    base_ask_sizes: Column<'py, f64>,
+    rows: Column<'py, i64>,
+    sides: Column<'py, i8>,
+    prices: Column<'py, f64>,
+    sizes: Column<'py, f64>,
+    n_snapshots: usize
+) -> PyResult<BookArrays<'py>> {
+    let base_bid_prices = base_bid_prices.values()?;
+    let base_bid_sizes = base_bid_sizes.values()?;
+    let base_ask_prices = base_ask_prices.values()?;
+    let base_ask_sizes = base_ask_sizes.values()?;
+    let rows = rows.values()?;
+    let sides = sides.values()?;
+    let prices = prices.values()?;
+    let sizes = sizes.values()?;
+
+    let depth = base_bid_prices.len();
+    if base_bid_sizes.len() != depth || base_ask_prices.len() != depth || base_ask_sizes.len() != depth {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "Base snapshot arrays must have the same length"
+        ));
+    }
+    if sides.len() != rows.len() || prices.len() != rows.len() || sizes.len() != rows.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "rows, sides, prices and sizes must have the same length"
+        ));
+    }
+    if sides.iter().any(|&s| s != BID && s != ASK) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Delta sides must be 1 (bid) or -1 (ask)"
+        ));
+    }
+    check_delta_rows(&rows, n_snapshots)?;
+
+    let [out_bid_prices, out_bid_sizes, out_ask_prices, out_ask_sizes] = py.allow_threads(|| {
+        replay_books(
+            [&base_bid_prices, &base_bid_sizes, &base_ask_prices, &base_ask_sizes],
+            (&rows, &sides, &prices, &sizes),
+            n_snapshots,
+        )
+    });
+
+    Ok((
+        PyArray2::from_owned_array_bound(py, out_bid_prices),
+        PyArray2::from_owned_array_bound(py, out_bid_sizes),
+        PyArray2::from_owned_array_bound(py, out_ask_prices),
+        PyArray2::from_owned_array_bound(py, out_ask_sizes),
+    ))
+}
+
+/// Features of one snapshot for `book_features_rust`
+struct BookFeatures {
+    mid: f64,
+    spread: f64,
+    microprice: f64,
+    imbalance: f64,
+    weighted_spread: f64,
+    bid_depth: f64,
+    ask_depth: f64,
+    bid_slope: f64,
+    ask_slope: f64,
+    /// Bid share of the size at each level, best first
+    level_ratios: Vec<f64>,
+}
+
+/// Size-weighted mean price of `levels`
+fn weighted_price(levels: &[(f64, f64)]) -> f64 {
+    let size: f64 = levels.iter().map(|l| l.1).sum();
+    levels.iter().map(|l| l.0 * l.1).sum::<f64>() / size
+}
+
+/// Least-squares slope of the cumulative size against the distance from `mid`, NaN for
+/// fewer than two levels
+fn depth_slope(levels: &[(f64, f64)], mid: f64) -> f64 {
+    if levels.len() < 2 {
+        return f64::NAN;
+    }
+    let mut cumulative = 0.0;
+    let points: Vec<(f64, f64)> = levels
+        .iter()
+        .map(|&(price, size)| {
+            cumulative += size;
+            ((price - mid).abs(), cumulative)
+        })
+        .collect();
+    let n = points.len() as f64;
+    let (mx, my) = (points.iter().map(|p| p.0).sum::<f64>() / n, points.iter().map(|p| p.1).sum::<f64>() / n);
+    let sxy: f64 = points.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum();
+    let sxx: f64 = points.iter().map(|p| (p.0 - mx) * (p.0 - mx)).sum();
+    if sxx > 0.0 { sxy / sxx } else { f64::NAN }
+}
+
+/// Features from the best `depth` levels of each side, both sorted best first
+fn book_features(bids: &[(f64, f64)], asks: &[(f64, f64)], depth: usize) -> BookFeatures {
+    let (bids, asks) = (&bids[..bids.len().min(depth)], &asks[..asks.len().min(depth)]);
+    let bid_depth: f64 = bids.iter().map(|l| l.1).sum();
+    let ask_depth: f64 = asks.iter().map(|l| l.1).sum();
+    let level_ratios = (0..depth)
+        .map(|k| match (bids.get(k), asks.get(k)) {
+            (Some(b), Some(a)) => b.1 / (b.1 + a.1),
+            (Some(_), None) => 1.0,
+            (None, Some(_)) => 0.0,
+            (None, None) => f64::NAN,
+        })
+        .collect();
+    let (Some(&(bid, bid_size)), Some(&(ask, ask_size))) = (bids.first(), asks.first()) else {
+        return BookFeatures {
+            mid: f64::NAN,
+            spread: f64::NAN,
+            microprice: f64::NAN,
+            imbalance: if bid_depth + ask_depth > 0.0 { (bid_depth - ask_depth) / (bid_depth + ask_depth) } else { f64::NAN },
+            weighted_spread: f64::NAN,
+            bid_depth,
+            ask_depth,
+            bid_slope: f64::NAN,
+            ask_slope: f64::NAN,
+            level_ratios,
+        };
+    };
+    let mid = 0.5 * (bid + ask);
+    BookFeatures {
+        mid,
+        spread: ask - bid,
+        microprice: (bid * ask_size + ask * bid_size) / (bid_size + ask_size),
+        imbalance: (bid_depth - ask_depth) / (bid_depth + ask_depth),
+        weighted_spread: weighted_price(asks) - weighted_price(bids),
+        bid_depth,
+        ask_depth,
+        bid_slope: depth_slope(bids, mid),
+        ask_slope: depth_slope(asks, mid),
+        level_ratios,
+    }
+}
+
+/// Order book features for machine learning, one row per L2 snapshot.
+///
+/// Snapshots are 2D arrays of shape (n_snapshots, levels) as for
+/// `diff_book_snapshots_rust`, in any level order; empty levels are NaN prices or zero
+/// sizes. Over the best `levels` levels of each side (default all) the features are:
+/// `mid` and `spread` of the best quotes, the `microprice` (the best prices weighted by
+/// the opposite side's size), depth `imbalance` (bid minus ask size over their total,
+/// in [-1, 1]), `weighted_spread` (size-weighted mean ask price minus bid price),
+/// `bid_depth` and `ask_depth`, `bid_slope` and `ask_slope` (least-squares slope of
+/// cumulative size against distance from the mid, so flatter books have larger slopes)
+/// and `level_ratios`, the bid share of the size at each level (a (n_snapshots, levels)
+/// array). Quote features are NaN when a side is empty. Snapshots are processed in
+/// parallel; returns a dict of arrays.
+#[pyfunction]
+#[pyo3(signature = (bid_prices, bid_sizes, ask_prices, ask_sizes, levels=None))]
+pub fn book_features_rust<'py>(
+    py: Python<'py>,
+    bid_prices: PyReadonlyArray2<'py, f64>,
+    bid_sizes: PyReadonlyArray2<'py, f64>,
+    ask_prices: PyReadonlyArray2<'py, f64>,
+    ask_sizes: PyReadonlyArray2<'py, f64>,
+    levels: Option<usize>
+) -> PyResult<Bound<'py, PyDict>> {
+    let bid_prices = bid_prices.as_array();
+    let bid_sizes = bid_sizes.as_array();
+    let ask_prices = ask_prices.as_array();
+    let ask_sizes = ask_sizes.as_array();
+    check_book_shapes([&bid_prices, &bid_sizes, &ask_prices, &ask_sizes])?;
+    if levels == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "levels must be >= 1"
+        ));
+    }
+    let depth = levels.unwrap_or(bid_prices.ncols()).min(bid_prices.ncols());
+    let features: Vec<BookFeatures> = py.allow_threads(|| {
+        threads::install(|| {
+            (0..bid_prices.nrows())
+                .into_par_iter()
+                .map(|row| {
+                    let mut bids = side_levels(bid_prices.row(row), bid_sizes.row(row));
+                    bids.reverse();
+                    let asks = side_levels(ask_prices.row(row), ask_sizes.row(row));
+                    book_features(&bids, &asks, depth)
+                })
+                .collect()
+        })
+    });
+    let level_ratios = Array2::from_shape_fn((features.len(), depth), |(i, k)| features[i].level_ratios[k]);
+
+    let result = PyDict::new_bound(py);
+    let column = |field: fn(&BookFeatures) -> f64| PyArray1::from_iter_bound(py, features.iter().map(field));
+    result.set_item("mid", column(|f| f.mid))?;
+    result.set_item("spread", column(|f| f.spread))?;
+    result.set_item("microprice", column(|f| f.microprice))?;
+    result.set_item("imbalance", column(|f| f.imbalance))?;
+    result.set_item("weighted_spread", column(|f| f.weighted_spread))?;
+    result.set_item("bid_depth", column(|f| f.bid_depth))?;
+    result.set_item("ask_depth", column(|f| f.ask_depth))?;
+    result.set_item("bid_slope", column(|f| f.bid_slope))?;
+    result.set_item("ask_slope", column(|f| f.ask_slope))?;
+    result.set_item("level_ratios", PyArray2::from_owned_array_bound(py, level_ratios))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::array;
+
+    fn round_trip(books: [Array2<f64>; 4]) -> [Array2<f64>; 4] {
+        let [bp, bs, ap, as_] = &books;
+        let (rows, sides, prices, sizes) = diff_books(&bp.view(), &bs.view(), &ap.view(), &as_.view());
+        let base = [bp, bs, ap, as_].map(|a| a.row(0).to_vec());
+        replay_books(
+            [&base[0], &base[1], &base[2], &base[3]],
+            (&rows, &sides, &prices, &sizes),
+            bp.nrows(),
+        )
+    }
+
+    #[test]
+    fn replay_reproduces_unchanged_trailing_snapshots() {
+        let books = [
+            Array2::from_shape_fn((5, 2), |(_, k)| 100.0 - k as f64),
+            Array2::from_elem((5, 2), 1.0),
+            Array2::from_shape_fn((5, 2), |(_, k)| 101.0 + k as f64),
+            Array2::from_elem((5, 2), 2.0),
+        ];
+        assert_eq!(round_trip(books.clone()), books);
+    }
+
+    #[test]
+    fn replay_reproduces_changing_books() {
+        let books = [
+            array![[100.0, 99.0], [100.0, f64::NAN], [100.5, 100.0], [100.5, 100.0]],
+            array![[1.0, 2.0], [3.0, 0.0], [1.0, 3.0], [1.0, 3.0]],
+            array![[101.0, 102.0], [101.0, 102.0], [101.0, 101.5], [101.0, 101.5]],
+            array![[1.0, 1.0], [1.0, 1.0], [2.0, 4.0], [2.0, 4.0]],
+        ];
+        let out = round_trip(books.clone());
+        assert_eq!(out[1], books[1]);
+        assert_eq!(out[2], books[2]);
+        assert_eq!(out[3], books[3]);
+        assert!(out[0][[1, 1]].is_nan());
+        assert_eq!(out[0][[2, 0]], 100.5);
+        assert_eq!(out[0].nrows(), 4);
+    }
+
+    #[test]
+    fn delta_rows_must_fall_inside_the_snapshots() {
+        pyo3::prepare_freethreaded_python();
+        assert!(check_delta_rows(&[1, 2], 3).is_ok());
+        assert!(check_delta_rows(&[1, 3], 3).is_err());
+        assert!(check_delta_rows(&[2, 1], 3).is_err());
+        assert!(check_delta_rows(&[0], 3).is_err());
+        assert!(check_delta_rows(&[], 0).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2};
+use rayon::prelude::*;
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::alignment::Alignment;
+use crate::column::Column;
+use crate::config::FastMathConfig;
+use crate::linalg;
+use crate::montecarlo::{simulate_paths, stack_paths};
+use crate::rng::{self, SplitMix};
+use crate::threads;
+
+/// Ornstein-Uhlenbeck process `dx = theta (mu - x) dt + sigma dW`
+#[derive(Clone, Copy)]
+struct Ou {
+    theta: f64,
+    mu: f64,
+    sigma: f64,
+}
+
+impl Ou {
+    /// Exact maximum-likelihood fit from observations `dt` apart: the sampled process is
+    /// an AR(1) `x[t+1] = a + b x[t] + e` with `b = exp(-theta dt)`, fitted by least
+    /// squares over consecutive pairs without NaN. `theta <= 0` when the fitted `b >= 1`
+    /// (no mean reversion, so `mu` and `sigma` are NaN); everything is NaN when `b <= 0` or
+    /// there are fewer than 3 pairs.
+    fn fit(values: &[f64], dt: f64) -> Self {
+        let (x, y): (Vec<f64>, Vec<f64>) = values
+            .windows(2)
+            .map(|w| (w[0], w[1]))
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .unzip();
+        let nan = Ou { theta: f64::NAN, mu: f64::NAN, sigma: f64::NAN };
+        let n = x.len();
+        let Some(fit) = linalg::ols(vec![vec![1.0; n], x], y) else {
+            return nan;
+        };
+        let (a, b) = (fit.params[0], fit.params[1]);
+        if n < 3 || b.is_nan() || b <= 0.0 {
+            return nan;
+        }
+        let theta = -b.ln() / dt;
+        if b >= 1.0 {
+            return Ou { theta, ..nan };
+        }
+        // Residual variance of the step is sigma² (1 - b²) / (2 theta)
+        let variance = fit.ssr / n as f64;
+        Ou { theta, mu: a / (1.0 - b), sigma: (variance * 2.0 * theta / (1.0 - b * b)).sqrt() }
+    }
+
+    /// Time for a deviation from `mu` to halve, infinite without mean reversion
+    fn half_life(&self) -> f64 {
+        if self.theta > 0.0 {
+            std::f64::consts::LN_2 / self.theta
+        } else if self.theta.is_nan() {
+            f64::NAN
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// Path of `n_steps + 1` values from `x0`, exact at each step
+    fn path(&self, x0: f64, n_steps: usize, dt: f64, rng: &mut SplitMix) -> Vec<f64> {
+        let decay = (-self.theta * dt).exp();
+        let spread = if self.theta > 0.0 {
+            self.sigma * ((1.0 - decay * decay) / (2.0 * self.theta)).sqrt()
+        } else {
+            self.sigma * dt.sqrt()
+        };
+        let mut path = Vec::with_capacity(n_steps + 1);
+        let mut x = x0;
+        path.push(x0);
+        while path.len() <= n_steps {
+            let (a, b) = rng.normal_pair();
+            for z in [a, b].into_iter().take(n_steps + 1 - path.len()) {
+                x = self.mu + (x - self.mu) * decay + spread * z;
+                path.push(x);
+            }
+        }
+        path
+    }
+}
+
+/// Fit an Ornstein-Uhlenbeck process to a spread, for pairs-trading entry and exit bands.
+///
+/// Models `spread` (e.g. the residual of a cointegrating regression) as `dx = theta (mu -
+/// x) dt + sigma dW` sampled every `dt` time units, by exact maximum likelihood: the
+/// least-squares AR(1) regression of each value on the previous one, pairs with a NaN
+/// skipped. Returns `(theta, mu, sigma)`, the mean-reversion speed per unit of `dt`, the
+/// long-run mean and the volatility. A spread that does not mean-revert gives
+/// `theta <= 0` and NaN `mu` and `sigma`; fewer than 3 pairs, or an anti-persistent
+/// spread the model cannot represent, give NaN throughout.
+#[pyfunction]
+#[pyo3(signature = (spread, dt=1.0))]
+pub fn ou_fit_rust(py: Python<'_>, spread: Column<'_, f64>, dt: f64) -> PyResult<(f64, f64, f64)> {
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    let values = spread.values()?;
+    let ou = py.allow_threads(|| Ou::fit(&values, dt));
+    Ok((ou.theta, ou.mu, ou.sigma))
+}
+
+/// Half-life of mean reversion of a spread, `ln(2) / theta` from the Ornstein-Uhlenbeck
+/// fit of `ou_fit_rust`, in the units of `dt` (bars by default).
+///
+/// A typical holding period for a pairs trade and a natural lookback for its rolling
+/// z-score. Infinite when the spread does not mean-revert, NaN when it cannot be fitted.
+#[pyfunction]
+#[pyo3(signature = (spread, dt=1.0))]
+pub fn ou_half_life_rust(py: Python<'_>, spread: Column<'_, f64>, dt: f64) -> PyResult<f64> {
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    let values = spread.values()?;
+    Ok(py.allow_threads(|| Ou::fit(&values, dt).half_life()))
+}
+
+/// AR(1) estimate of a spread's half-life with its confidence interval
+#[derive(Clone, Copy)]
+struct HalfLife {
+    half_life: f64,
+    lower: f64,
+    upper: f64,
+    /// AR(1) coefficient `b` of `x[t+1] = a + b x[t] + e`
+    ar_coef: f64,
+    std_err: f64,
+    n_obs: usize,
+}
+
+impl HalfLife {
+    const NAN: HalfLife =
+        HalfLife { half_life: f64::NAN, lower: f64::NAN, upper: f64::NAN, ar_coef: f64::NAN, std_err: f64::NAN, n_obs: 0 };
+
+    /// Least-squares AR(1) fit over consecutive pairs without NaN. The half-life
+    /// `-ln(2) dt / ln(b)` rises with `b`, so the interval maps the normal interval of `b`
+    /// through it: 0 below when it reaches `b <= 0`, infinite above when it reaches `b >= 1`.
+    fn fit(values: &[f64], dt: f64, z: f64) -> Self {
+        let (x, y): (Vec<f64>, Vec<f64>) = values
+            .windows(2)
+            .map(|w| (w[0], w[1]))
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .unzip();
+        let n = x.len();
+        let fit = match linalg::ols(vec![vec![1.0; n], x], y) {
+            Some(fit) if n >= 3 => fit,
+            _ => return HalfLife { n_obs: n, ..HalfLife::NAN },
+        };
+        let b = fit.params[1];
+        let std_err = (fit.ssr / (n - 2) as f64 * fit.inverse_diag[1]).sqrt();
+        let half_life = |b: f64| {
+            if b <= 0.0 {
+                0.0
+            } else if b >= 1.0 {
+                f64::INFINITY
+            } else {
+                -std::f64::consts::LN_2 * dt / b.ln()
+            }
+        };
+        HalfLife {
+            half_life: if b > 0.0 { half_life(b) } else { f64::NAN },
+            lower: half_life(b - z * std_err),
+            upper: half_life(b + z * std_err),
+            ar_coef: b,
+            std_err,
+            n_obs: n,
+        }
+    }
+}
+
+/// Half-life of mean reversion of a spread from its AR(1) regression, with a confidence
+/// interval, overall or over a rolling window.
+///
+/// Regresses each value of `spread` on the previous one, `x[t+1] = a + b x[t] + e`
+/// (pairs with a NaN skipped), and reports the half-life `-ln(2) / ln(b)` in units of
+/// `dt` (bars by default): infinite when `b >= 1` (no mean reversion) and NaN when
+/// `b <= 0` or there are fewer than 3 pairs. `lower` and `upper` bound it at
+/// `confidence` from the normal interval of `b`, so a wide interval warns that the
+/// half-life is poorly determined; `upper` is infinite when the interval admits a unit
+/// root. Returns a dict with `half_life`, `lower`, `upper`, the AR coefficient `ar_coef`,
+/// its `std_err` and `n_obs`, the number of pairs fitted. With `window`, each entry is an
+/// array holding the fit over the `window` values ending at each bar, NaN (and 0 pairs)
+/// until the window fills, computed in parallel for screening many candidate pairs;
+/// `align` ("valid", "same" or an integer shift) lines these arrays up as for the other
+/// rolling functions.
+#[pyfunction]
+#[pyo3(signature = (spread, window=None, confidence=0.95, dt=1.0, align=None, config=None))]
+pub fn half_life_rust<'py>(
+    py: Python<'py>,
+    spread: Column<'py, f64>,
+    window: Option<usize>,
+    confidence: f64,
+    dt: f64,
+    align: Option<Alignment>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    if confidence.is_nan() || confidence <= 0.0 || confidence >= 1.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "confidence must be between 0 and 1"
+        ));
+    }
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    if let Some(window) = window {
+        crate::errors::require_window(window)?;
+    }
+    let z = Normal::new(0.0, 1.0).unwrap().inverse_cdf(0.5 + confidence / 2.0);
+    let values = spread.values()?;
+
+    let result = PyDict::new_bound(py);
+    let Some(window) = window else {
+        let fit = py.allow_threads(|| HalfLife::fit(&values, dt, z));
+        result.set_item("half_life", fit.half_life)?;
+        result.set_item("lower", fit.lower)?;
+        result.set_item("upper", fit.upper)?;
+        result.set_item("ar_coef", fit.ar_coef)?;
+        result.set_item("std_err", fit.std_err)?;
+        result.set_item("n_obs", fit.n_obs)?;
+        return Ok(result);
+    };
+    let fits: Vec<HalfLife> = py.allow_threads(|| {
+        threads::install(|| {
+            (0..values.len())
+                .into_par_iter()
+                .map(|t| if t + 1 < window { HalfLife::NAN } else { HalfLife::fit(&values[t + 1 - window..=t], dt, z) })
+                .collect()
+        })
+    });
+    let fits = align.trim(fits, window - 1, HalfLife::NAN);
+    result.set_item("half_life", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.half_life)))?;
+    result.set_item("lower", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.lower)))?;
+    result.set_item("upper", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.upper)))?;
+    result.set_item("ar_coef", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.ar_coef)))?;
+    result.set_item("std_err", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.std_err)))?;
+    result.set_item("n_obs", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.n_obs as i64)))?;
+    Ok(result)
+}
+
+/// Simulate Ornstein-Uhlenbeck paths, e.g. spreads for testing a pairs-trading rule.
+///
+/// `params` is a dict with `theta`, `mu` and `sigma` as returned by `ou_fit_rust`, and an
+/// optional starting value `x0` (default `mu`). Each of the `n_paths` paths takes
+/// `n_steps` steps of `dt`, sampled exactly from the Gaussian transition (a random walk
+/// when `theta` is 0), and is seeded from `seed` (or `set_seed`'s) and its index, so
+/// results are reproducible whatever the thread count. Returns an array of shape
+/// `(n_paths, n_steps + 1)` with `x0` in the first column.
+#[pyfunction]
+#[pyo3(signature = (params, n_steps=252, n_paths=10_000, seed=None, dt=1.0, config=None))]
+pub fn ou_simulate_rust<'py>(
+    py: Python<'py>,
+    params: &Bound<'py, PyDict>,
+    n_steps: usize,
+    n_paths: usize,
+    seed: Option<u64>,
+    dt: f64,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let seed = rng::resolve_seed(seed);
+    let number = |key: &str| -> PyResult<Option<f64>> { params.get_item(key)?.map(|v| v.extract()).transpose() };
+    let required = |key: &str| {
+        number(key)?.ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("params needs a '{}' key", key))
+        })
+    };
+    let ou = Ou { theta: required("theta")?, mu: required("mu")?, sigma: required("sigma")? };
+    let x0 = number("x0")?.unwrap_or(ou.mu);
+    let negative = |v: f64| v.is_nan() || v < 0.0;
+    if negative(ou.theta) || negative(ou.sigma) || !ou.mu.is_finite() || !x0.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "params theta and sigma must be >= 0, and mu and x0 finite"
+        ));
+    }
+    if dt.is_nan() || dt <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dt must be > 0"
+        ));
+    }
+    let paths = py.allow_threads(|| {
+        stack_paths(simulate_paths(n_paths, seed, |rng| ou.path(x0, n_steps, dt, rng)), n_steps + 1)
+    });
+    Ok(PyArray2::from_owned_array_bound(py, paths))
+}
@@ -0,0 +1,148 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::alignment::Alignment;
+use crate::column::Column;
+use crate::compensated;
+use crate::config::FastMathConfig;
+
+fn check_lengths(x: &[f64], y: &[f64]) -> PyResult<()> {
+    if x.len() != y.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "Arrays must have the same length"
+        ));
+    }
+    Ok(())
+}
+
+fn spread(x: &[f64], y: &[f64], hedge_ratio: f64) -> Vec<f64> {
+    x.iter().zip(y).map(|(x, y)| y - hedge_ratio * x).collect()
+}
+
+/// Rolling z-score of `values` over `window` values with the sample standard deviation,
+/// NaN until the window fills and wherever it holds a NaN or a constant
+fn rolling_zscore(values: &[f64], window: usize) -> PyResult<Vec<f64>> {
+    crate::errors::check_ddof(1, window)?;
+    let means = compensated::moving_average(values, window, window)?;
+    let stds = compensated::rolling_std(values, window, 1)?;
+    let mut z = vec![f64::NAN; values.len()];
+    for (i, (mean, std)) in means.iter().zip(&stds).enumerate() {
+        let t = i + window - 1;
+        if *std > 0.0 {
+            z[t] = (values[t] - mean) / std;
+        }
+    }
+    Ok(z)
+}
+
+/// Target spread position at each bar: short the spread (-1) once its z-score rises above
+/// `entry_z`, long (1) once it falls below `-entry_z`, flat again once it comes back
+/// within `exit_z` of zero. A bar that exits may enter the other side; NaN z-scores hold.
+fn positions(z: &[f64], entry_z: f64, exit_z: f64) -> Vec<i8> {
+    let mut position = 0i8;
+    z.iter()
+        .map(|&z| {
+            if z.is_nan() {
+                return position;
+            }
+            if (position == 1 && z >= -exit_z) || (position == -1 && z <= exit_z) {
+                position = 0;
+            }
+            if position == 0 {
+                if z > entry_z {
+                    position = -1;
+                } else if z < -entry_z {
+                    position = 1;
+                }
+            }
+            position
+        })
+        .collect()
+}
+
+/// Spread of a pair, `y - hedge_ratio * x`.
+///
+/// With the hedge ratio from `engle_granger_rust(x, y)` (the slope of `y` on `x`) this is
+/// the cointegrating residual up to its constant. NaN in either leg gives NaN.
+#[pyfunction]
+pub fn spread_rust<'py>(
+    py: Python<'py>,
+    x: Column<'py, f64>,
+    y: Column<'py, f64>,
+    hedge_ratio: f64
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let (x, y) = (x.values()?, y.values()?);
+    check_lengths(&x, &y)?;
+    Ok(PyArray1::from_vec_bound(py, spread(&x, &y, hedge_ratio)))
+}
+
+/// Log spread of a pair, `ln(y) - ln(x)`: the log price ratio.
+///
+/// Suits pairs whose prices move in proportion rather than by the same amount.
+/// Non-positive prices give NaN.
+#[pyfunction]
+pub fn log_spread_rust<'py>(
+    py: Python<'py>,
+    x: Column<'py, f64>,
+    y: Column<'py, f64>
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let (x, y) = (x.values()?, y.values()?);
+    check_lengths(&x, &y)?;
+    let log = |v: f64| if v > 0.0 { v.ln() } else { f64::NAN };
+    Ok(PyArray1::from_iter_bound(py, x.iter().zip(y.iter()).map(|(&x, &y)| log(y) - log(x))))
+}
+
+/// Entry and exit signals of a mean-reversion pairs trade on `y - hedge_ratio * x`.
+///
+/// The spread's z-score is taken against its rolling mean and sample standard deviation
+/// over the last `window` bars (NaN before the window fills). The position goes short the
+/// spread (-1: sell `y`, buy `hedge_ratio` of `x`) when the z-score rises above `entry_z`
+/// and long (1) when it falls below `-entry_z`, and back to flat when it returns within
+/// `exit_z` of zero; a z-score crossing straight from one band to the other reverses the
+/// position. Returns a dict with the `spread`, its `zscore`, the `position` held after
+/// each bar and the `signal`, the change in position on that bar (so 1 or -1 on entries
+/// and exits and 2 or -2 on reversals). `align` applies to all four: "valid" starts them
+/// at the first full window, "same" keeps every bar and an integer shifts them later,
+/// NaN-filled (flat for `position` and `signal`).
+#[pyfunction]
+#[pyo3(signature = (x, y, window, entry_z=2.0, exit_z=0.5, hedge_ratio=1.0, align=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn pair_signal_rust<'py>(
+    py: Python<'py>,
+    x: Column<'py, f64>,
+    y: Column<'py, f64>,
+    window: usize,
+    entry_z: f64,
+    exit_z: f64,
+    hedge_ratio: f64,
+    align: Option<Alignment>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    if entry_z.is_nan() || exit_z.is_nan() || exit_z < 0.0 || exit_z > entry_z {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Thresholds must satisfy 0 <= exit_z <= entry_z"
+        ));
+    }
+    let (x, y) = (x.values()?, y.values()?);
+    check_lengths(&x, &y)?;
+    let (spread, z, position) = py.allow_threads(|| -> PyResult<_> {
+        let spread = spread(&x, &y, hedge_ratio);
+        let z = rolling_zscore(&spread, window)?;
+        let position = positions(&z, entry_z, exit_z);
+        Ok((spread, z, position))
+    })?;
+    let signal: Vec<i8> = position.iter().scan(0i8, |previous, &p| Some(p - std::mem::replace(previous, p))).collect();
+    let warmup = window - 1;
+    let (spread, z) = (align.trim(spread, warmup, f64::NAN), align.trim(z, warmup, f64::NAN));
+    let (position, signal) = (align.trim(position, warmup, 0), align.trim(signal, warmup, 0));
+
+    let result = PyDict::new_bound(py);
+    result.set_item("spread", PyArray1::from_vec_bound(py, spread))?;
+    result.set_item("zscore", PyArray1::from_vec_bound(py, z))?;
+    result.set_item("position", PyArray1::from_vec_bound(py, position))?;
+    result.set_item("signal", PyArray1::from_vec_bound(py, signal))?;
+    Ok(result)
+}
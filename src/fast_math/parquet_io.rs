@@ -0,0 +1,224 @@
+use std::fs::File;
+use std::sync::Arc;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use arrow::array::{Array, ArrayRef, AsArray, Float64Array, Int64Array};
+use arrow::datatypes::{DataType, Field, Float64Type, Int64Type, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use rayon::prelude::*;
+
+use crate::column::{Column, NumericColumn};
+use crate::threads;
+
+/// Values of one column accumulated across row groups
+enum ColumnData {
+    Float(Vec<f64>),
+    Int(Vec<i64>),
+}
+
+fn to_py_err(e: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyIOError::new_err(e.to_string())
+}
+
+/// Whether a Parquet column is read back as int64 (integers and timestamps) or float64
+fn is_integral(data_type: &DataType) -> PyResult<bool> {
+    match data_type {
+        t if t.is_integer() => Ok(true),
+        DataType::Timestamp(_, _) => Ok(true),
+        DataType::Float16 | DataType::Float32 | DataType::Float64 | DataType::Boolean => Ok(false),
+        t => Err(pyo3::exceptions::PyTypeError::new_err(format!(
+            "Unsupported Parquet column type {}", t
+        ))),
+    }
+}
+
+/// Append the values of an Arrow column to `out`, casting to int64 or float64
+fn append_column(out: &mut ColumnData, array: &ArrayRef) -> PyResult<()> {
+    match out {
+        ColumnData::Int(values) => {
+            // Timestamps keep their stored unit; only the Arrow type is dropped
+            let array = match array.data_type() {
+                DataType::Int64 => array.clone(),
+                _ => arrow::compute::cast(array, &DataType::Int64).map_err(to_py_err)?,
+            };
+            let array = array.as_primitive::<Int64Type>();
+            if array.null_count() > 0 {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Null values are not supported in integer columns"
+                ));
+            }
+            values.extend_from_slice(array.values());
+        }
+        ColumnData::Float(values) => {
+            let array = arrow::compute::cast(array, &DataType::Float64).map_err(to_py_err)?;
+            let array = array.as_primitive::<Float64Type>();
+            if array.null_count() > 0 {
+                values.extend(array.iter().map(|v| v.unwrap_or(f64::NAN)));
+            } else {
+                values.extend_from_slice(array.values());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read columns of a Parquet file into numpy arrays.
+///
+/// Row groups are decoded in parallel. Integer and timestamp columns are returned as
+/// int64 (timestamps in their stored unit), floating-point and boolean columns as
+/// float64 with nulls as NaN. `columns` selects and orders the output; all columns are
+/// read when omitted.
+#[pyfunction]
+#[pyo3(signature = (path, columns=None))]
+pub fn read_parquet_rust<'py>(
+    py: Python<'py>,
+    path: &str,
+    columns: Option<Vec<String>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?).map_err(to_py_err)?;
+    let schema = builder.schema().clone();
+    let n_row_groups = builder.metadata().num_row_groups();
+
+    let names = columns.unwrap_or_else(|| schema.fields().iter().map(|f| f.name().clone()).collect());
+    let mut indices = Vec::with_capacity(names.len());
+    for name in &names {
+        let index = schema.index_of(name).map_err(|_| {
+            pyo3::exceptions::PyKeyError::new_err(format!("Column '{}' not found in {}", name, path))
+        })?;
+        is_integral(schema.field(index).data_type())?;
+        indices.push(index);
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices.iter().copied());
+
+    let groups: Vec<Vec<ColumnData>> = py.allow_threads(|| {
+        threads::install(|| {
+            (0..n_row_groups)
+                .into_par_iter()
+                .map(|group| -> PyResult<Vec<ColumnData>> {
+                    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)
+                        .map_err(to_py_err)?
+                        .with_projection(mask.clone())
+                        .with_row_groups(vec![group])
+                        .build()
+                        .map_err(to_py_err)?;
+
+                    let mut data: Vec<ColumnData> = indices
+                        .iter()
+                        .map(|&i| match is_integral(schema.field(i).data_type()) {
+                            Ok(true) => ColumnData::Int(Vec::new()),
+                            _ => ColumnData::Float(Vec::new()),
+                        })
+                        .collect();
+                    for batch in reader {
+                        let batch = batch.map_err(to_py_err)?;
+                        for (out, name) in data.iter_mut().zip(&names) {
+                            let array = batch.column_by_name(name).ok_or_else(|| {
+                                pyo3::exceptions::PyKeyError::new_err(format!("Column '{}' missing from batch", name))
+                            })?;
+                            append_column(out, array)?;
+                        }
+                    }
+                    Ok(data)
+                })
+                .collect::<PyResult<_>>()
+        })
+    })?;
+
+    let result = PyDict::new_bound(py);
+    for (col, name) in names.iter().enumerate() {
+        match groups.first().map(|g| &g[col]) {
+            Some(ColumnData::Int(_)) => {
+                let mut values = Vec::new();
+                for group in &groups {
+                    if let ColumnData::Int(v) = &group[col] {
+                        values.extend_from_slice(v);
+                    }
+                }
+                result.set_item(name, PyArray1::from_vec_bound(py, values))?;
+            }
+            Some(ColumnData::Float(_)) => {
+                let mut values = Vec::new();
+                for group in &groups {
+                    if let ColumnData::Float(v) = &group[col] {
+                        values.extend_from_slice(v);
+                    }
+                }
+                result.set_item(name, PyArray1::from_vec_bound(py, values))?;
+            }
+            None => {
+                let empty = if is_integral(schema.field(indices[col]).data_type())? {
+                    PyArray1::<i64>::zeros_bound(py, 0, false).into_any()
+                } else {
+                    PyArray1::<f64>::zeros_bound(py, 0, false).into_any()
+                };
+                result.set_item(name, empty)?;
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn arrow_column<T: crate::column::ColumnElement>(column: &Column<'_, T>) -> PyResult<Vec<T>> {
+    Ok(column.values()?.into_owned())
+}
+
+/// Write a dict of equal-length 1D columns to a Parquet file.
+///
+/// int64 columns (or Arrow integer / timestamp columns) are stored as INT64, everything
+/// else as DOUBLE. `compression` is one of "snappy", "zstd" or "none".
+#[pyfunction]
+#[pyo3(signature = (path, arrays, compression="snappy", row_group_size=None))]
+pub fn write_parquet_rust(
+    path: &str,
+    arrays: &Bound<'_, PyDict>,
+    compression: &str,
+    row_group_size: Option<usize>
+) -> PyResult<()> {
+    let compression = match compression {
+        "snappy" => Compression::SNAPPY,
+        "zstd" => Compression::ZSTD(ZstdLevel::default()),
+        "none" => Compression::UNCOMPRESSED,
+        other => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown compression '{}', expected 'snappy', 'zstd' or 'none'", other
+            )))
+        }
+    };
+
+    let mut fields = Vec::with_capacity(arrays.len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(arrays.len());
+    for (name, value) in arrays.iter() {
+        let name: String = name.extract()?;
+        let (data_type, array): (DataType, ArrayRef) = match value.extract::<NumericColumn>()? {
+            NumericColumn::Int(column) => (DataType::Int64, Arc::new(Int64Array::from(arrow_column(&column)?))),
+            NumericColumn::Float(column) => (DataType::Float64, Arc::new(Float64Array::from(arrow_column(&column)?))),
+        };
+        if let Some(first) = columns.first() {
+            if first.len() != array.len() {
+                return Err(crate::errors::LengthMismatchError::new_err(
+                    "All columns must have the same length"
+                ));
+            }
+        }
+        fields.push(Field::new(name, data_type, false));
+        columns.push(array);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(to_py_err)?;
+
+    let mut props = WriterProperties::builder().set_compression(compression);
+    if let Some(size) = row_group_size {
+        props = props.set_max_row_group_size(size.max(1));
+    }
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema, Some(props.build())).map_err(to_py_err)?;
+    writer.write(&batch).map_err(to_py_err)?;
+    writer.close().map_err(to_py_err)?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use pyo3::prelude::*;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+
+use crate::linalg;
+
+/// Leading principal components of the columns of `returns`
+struct Pca {
+    /// One component per row, in the symbols' space
+    components: Array2<f64>,
+    explained_variance: Vec<f64>,
+    explained_variance_ratio: Vec<f64>,
+    /// Projection of each centred observation on the components
+    scores: Array2<f64>,
+}
+
+impl Pca {
+    fn nan(t: usize, n: usize, k: usize) -> Self {
+        Pca {
+            components: Array2::from_elem((k, n), f64::NAN),
+            explained_variance: vec![f64::NAN; k],
+            explained_variance_ratio: vec![f64::NAN; k],
+            scores: Array2::from_elem((t, k), f64::NAN),
+        }
+    }
+}
+
+fn pca(returns: ArrayView2<'_, f64>, k: usize) -> Pca {
+    let (t, n) = returns.dim();
+    if t < 2 || returns.iter().any(|v| v.is_nan()) {
+        return Pca::nan(t, n, k);
+    }
+    let mean = returns.mean_axis(Axis(0)).unwrap_or_else(|| Array1::zeros(n));
+    let centred = &returns - &mean;
+    let covariance = centred.t().dot(&centred) / (t - 1) as f64;
+    let total: f64 = covariance.diag().sum();
+
+    let (values, vectors) = linalg::symmetric_eigen(covariance);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+    order.truncate(k);
+
+    let mut components = vectors.select(Axis(1), &order).reversed_axes();
+    // Eigenvectors are only defined up to sign; make each one's largest loading positive,
+    // as scikit-learn does
+    for mut component in components.rows_mut() {
+        let largest = component.iter().copied().max_by(|a, b| a.abs().total_cmp(&b.abs())).unwrap_or(0.0);
+        if largest < 0.0 {
+            component.mapv_inplace(|v| -v);
+        }
+    }
+    // Rounding can leave the smallest eigenvalues of a singular covariance just below 0
+    let explained_variance: Vec<f64> = order.iter().map(|&i| values[i].max(0.0)).collect();
+    let explained_variance_ratio = explained_variance.iter().map(|v| v / total).collect();
+    let scores = centred.dot(&components.t());
+    Pca { components, explained_variance, explained_variance_ratio, scores }
+}
+
+/// Principal component analysis of the columns of `returns_2d` (observations in rows,
+/// one symbol per column), like scikit-learn's `PCA(n_components).fit(returns_2d)`.
+///
+/// The columns are centred and the sample covariance (`ddof=1`) diagonalised; its leading
+/// `n_components` eigenvectors (default: all) are the components, each signed so its
+/// largest loading is positive. Returns `(components, explained_variance,
+/// explained_variance_ratio, scores)`: components of shape `(n_components, symbols)`, the
+/// variance along each and its share of the total, and the factor scores of shape
+/// `(observations, n_components)`. Fewer than 2 observations or any NaN give NaN results.
+#[pyfunction]
+#[pyo3(signature = (returns_2d, n_components=None))]
+pub fn pca_rust<'py>(
+    py: Python<'py>,
+    returns_2d: PyReadonlyArray2<'py, f64>,
+    n_components: Option<usize>
+) -> PyResult<PyObject> {
+    let returns = returns_2d.as_array();
+    let n = returns.ncols();
+    let k = n_components.unwrap_or(n);
+    if k == 0 || k > n {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "n_components must be between 1 and the number of columns ({})", n
+        )));
+    }
+
+    let result = py.allow_threads(|| pca(returns, k));
+    Ok((
+        PyArray2::from_owned_array_bound(py, result.components),
+        PyArray1::from_vec_bound(py, result.explained_variance),
+        PyArray1::from_vec_bound(py, result.explained_variance_ratio),
+        PyArray2::from_owned_array_bound(py, result.scores),
+    )
+        .into_py(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::array;
+
+    #[test]
+    fn components_are_orthonormal_and_ordered_by_variance() {
+        let mut rng = crate::rng::SplitMix::new(3, 0);
+        let returns = Array2::from_shape_fn((200, 3), |(_, j)| rng.normal_pair().0 * [3.0, 1.0, 0.3][j]);
+        let fit = pca(returns.view(), 3);
+        let gram = fit.components.dot(&fit.components.t());
+        assert!((gram - Array2::<f64>::eye(3)).iter().all(|d| d.abs() < 1e-10));
+        assert!(fit.explained_variance.windows(2).all(|w| w[0] >= w[1]));
+        assert!((fit.explained_variance_ratio.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        // The first component is the high-variance column, signed positive
+        assert!(fit.components[[0, 0]] > 0.99);
+    }
+
+    #[test]
+    fn a_single_factor_explains_everything() {
+        let returns = array![[1.0, 2.0], [-1.0, -2.0], [0.5, 1.0], [2.0, 4.0]];
+        let fit = pca(returns.view(), 1);
+        assert!((fit.explained_variance_ratio[0] - 1.0).abs() < 1e-12);
+        let expected = [1.0 / 5f64.sqrt(), 2.0 / 5f64.sqrt()];
+        assert!(fit.components.iter().zip(expected).all(|(c, e)| (c - e).abs() < 1e-10));
+        // Scores are the centred observations projected on the component
+        let mean = [0.625, 1.25];
+        for (i, row) in returns.rows().into_iter().enumerate() {
+            let projected = (row[0] - mean[0]) * expected[0] + (row[1] - mean[1]) * expected[1];
+            assert!((fit.scores[[i, 0]] - projected).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn too_few_rows_or_nan_give_nan() {
+        assert!(pca(array![[1.0, 2.0]].view(), 2).explained_variance.iter().all(|v| v.is_nan()));
+        let nan = array![[1.0, f64::NAN], [2.0, 1.0], [3.0, 0.0]];
+        assert!(pca(nan.view(), 1).components.iter().all(|v| v.is_nan()));
+    }
+}
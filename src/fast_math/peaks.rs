@@ -0,0 +1,128 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Local maxima of `x`, as scipy's `_local_maxima_1d`: values above their left neighbour
+/// and then above the first differing value to their right; a flat top counts once, at
+/// its middle (rounded down). The ends are never peaks.
+fn local_maxima(x: &[f64]) -> Vec<usize> {
+    let mut peaks = Vec::new();
+    let mut i = 1;
+    while i + 1 < x.len() {
+        if x[i - 1] < x[i] {
+            let mut ahead = i + 1;
+            while ahead + 1 < x.len() && x[ahead] == x[i] {
+                ahead += 1;
+            }
+            if x[ahead] < x[i] {
+                peaks.push((i + ahead - 1) / 2);
+                i = ahead;
+            }
+        }
+        i += 1;
+    }
+    peaks
+}
+
+/// Keep the highest of peaks closer than `distance`, as scipy's `_select_by_peak_distance`:
+/// peaks are taken from the highest down (the later one first among equals), each
+/// removing the lower peaks within `distance - 1` of it
+fn select_by_distance(x: &[f64], peaks: &[usize], distance: usize) -> Vec<bool> {
+    let mut keep = vec![true; peaks.len()];
+    let mut order: Vec<usize> = (0..peaks.len()).collect();
+    order.sort_by(|&a, &b| x[peaks[a]].total_cmp(&x[peaks[b]]).then(a.cmp(&b)));
+    for &i in order.iter().rev() {
+        if !keep[i] {
+            continue;
+        }
+        let mut j = i;
+        while j > 0 && peaks[i] - peaks[j - 1] < distance {
+            keep[j - 1] = false;
+            j -= 1;
+        }
+        let mut j = i + 1;
+        while j < peaks.len() && peaks[j] - peaks[i] < distance {
+            keep[j] = false;
+            j += 1;
+        }
+    }
+    keep
+}
+
+/// Prominence of the peak at `peak` and its left and right bases, as scipy's
+/// `peak_prominences` without `wlen`: the lowest points on each side before the signal
+/// next rises above the peak, the higher of which the prominence is measured from
+fn prominence(x: &[f64], peak: usize) -> (f64, usize, usize) {
+    let (mut left_base, mut left_min) = (peak, x[peak]);
+    for i in (0..peak).rev() {
+        if x[i] > x[peak] {
+            break;
+        }
+        if x[i] < left_min {
+            left_min = x[i];
+            left_base = i;
+        }
+    }
+    let (mut right_base, mut right_min) = (peak, x[peak]);
+    for (i, &value) in x.iter().enumerate().skip(peak + 1) {
+        if value > x[peak] {
+            break;
+        }
+        if value < right_min {
+            right_min = value;
+            right_base = i;
+        }
+    }
+    (x[peak] - left_min.max(right_min), left_base, right_base)
+}
+
+/// Find peaks in a series, following `scipy.signal.find_peaks`.
+///
+/// Peaks are local maxima (a flat top counts once, at its middle; the first and last
+/// values never do), then filtered in scipy's order: by a minimum `height`, by a minimum
+/// `distance` in samples between neighbouring peaks, the higher one surviving, and by a
+/// minimum `prominence`, how far the peak stands above the higher of the lowest points
+/// on either side before the series climbs above it again. Call it on the negated series
+/// for troughs. Returns a dict with the ascending `peaks` indices and their `heights`,
+/// `prominences`, `left_bases` and `right_bases`. NaN values are never peaks.
+#[pyfunction]
+#[pyo3(signature = (data, prominence=None, distance=None, height=None))]
+pub fn find_peaks_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    prominence: Option<f64>,
+    distance: Option<usize>,
+    height: Option<f64>
+) -> PyResult<Bound<'py, PyDict>> {
+    if distance == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "distance must be >= 1"
+        ));
+    }
+    let x = data.values()?;
+    let (peaks, prominences): (Vec<usize>, Vec<(f64, usize, usize)>) = py.allow_threads(|| {
+        let mut peaks = local_maxima(&x);
+        if let Some(height) = height {
+            peaks.retain(|&p| x[p] >= height);
+        }
+        if let Some(distance) = distance.filter(|&d| d > 1) {
+            let keep = select_by_distance(&x, &peaks, distance);
+            peaks = peaks.into_iter().zip(keep).filter(|&(_, k)| k).map(|(p, _)| p).collect();
+        }
+        peaks
+            .into_iter()
+            .map(|p| (p, self::prominence(&x, p)))
+            .filter(|(_, (value, _, _))| prominence.is_none_or(|min| *value >= min))
+            .unzip()
+    });
+
+    let result = PyDict::new_bound(py);
+    result.set_item("peaks", PyArray1::from_iter_bound(py, peaks.iter().map(|&p| p as i64)))?;
+    result.set_item("heights", PyArray1::from_iter_bound(py, peaks.iter().map(|&p| x[p])))?;
+    result.set_item("prominences", PyArray1::from_iter_bound(py, prominences.iter().map(|p| p.0)))?;
+    result.set_item("left_bases", PyArray1::from_iter_bound(py, prominences.iter().map(|p| p.1 as i64)))?;
+    result.set_item("right_bases", PyArray1::from_iter_bound(py, prominences.iter().map(|p| p.2 as i64)))?;
+    Ok(result)
+}
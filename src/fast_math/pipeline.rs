@@ -0,0 +1,869 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString, PyTuple};
+use numpy::PyArray2;
+use ndarray::Array2;
+
+use crate::calendar::TradingCalendar;
+use crate::column::Column;
+use crate::resample::{bin_indices, resample, Binning};
+use crate::results::ResultSet;
+use crate::streaming::{true_range, RollingWindow, StreamingATR, StreamingEMA, StreamingRSI};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Sma(usize),
+    Std(usize),
+    Zscore(usize),
+    BollingerUpper(usize),
+    BollingerLower(usize),
+    Min(usize),
+    Max(usize),
+    Roc(usize),
+    Return,
+    Ema(usize),
+    Rsi(usize),
+    Atr(usize),
+    Natr(usize),
+    Macd(usize, usize),
+}
+
+/// Band width of `bb_upper` / `bb_lower`, in standard deviations
+const BOLLINGER_WIDTH: f64 = 2.0;
+
+struct Spec {
+    name: String,
+    kind: Kind,
+    /// Signal thresholds used by `process_stream_rust`
+    above: Option<f64>,
+    below: Option<f64>,
+}
+
+impl Spec {
+    fn build(indicator: &str, periods: &[usize], name: Option<String>) -> PyResult<Self> {
+        let period = |i: usize, default: Option<usize>| -> PyResult<usize> {
+            let period = periods.get(i).copied().or(default).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Indicator '{}' needs a period", indicator
+                ))
+            })?;
+            crate::errors::require_period(period)?;
+            Ok(period)
+        };
+        let kind = match indicator {
+            "sma" | "moving_average" => Kind::Sma(period(0, None)?),
+            "std" => Kind::Std(period(0, None)?),
+            "zscore" => Kind::Zscore(period(0, None)?),
+            "bb_upper" => Kind::BollingerUpper(period(0, Some(20))?),
+            "bb_lower" => Kind::BollingerLower(period(0, Some(20))?),
+            "min" => Kind::Min(period(0, None)?),
+            "max" => Kind::Max(period(0, None)?),
+            "roc" => Kind::Roc(period(0, None)?),
+            "return" => Kind::Return,
+            "ema" => Kind::Ema(period(0, None)?),
+            "rsi" => Kind::Rsi(period(0, Some(14))?),
+            "atr" => Kind::Atr(period(0, Some(14))?),
+            "natr" => Kind::Natr(period(0, Some(14))?),
+            "macd" => Kind::Macd(period(0, Some(12))?, period(1, Some(26))?),
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown indicator '{}', expected one of sma, std, zscore, bb_upper, bb_lower, \
+                     min, max, roc, return, ema, rsi, atr, natr, macd",
+                    other
+                )))
+            }
+        };
+        let name = name.unwrap_or_else(|| match kind {
+            Kind::Macd(fast, slow) => format!("macd_{}_{}", fast, slow),
+            Kind::Return => indicator.to_string(),
+            Kind::Sma(p) | Kind::Std(p) | Kind::Zscore(p) | Kind::BollingerUpper(p) | Kind::BollingerLower(p)
+            | Kind::Min(p) | Kind::Max(p) | Kind::Roc(p) | Kind::Ema(p) | Kind::Rsi(p) | Kind::Atr(p)
+            | Kind::Natr(p) => format!("{}_{}", indicator, p),
+        });
+        Ok(Spec { name, kind, above: None, below: None })
+    }
+
+    /// Parse `"rsi:14"` / `"macd:12:26"`, or a dict with `indicator`, `period` (or `fast` and
+    /// `slow` for MACD), an optional output `name` and optional `above` / `below` thresholds
+    fn parse(spec: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(text) = spec.downcast::<PyString>() {
+            let text = text.to_str()?;
+            let mut parts = text.split(':');
+            let indicator = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let periods = parts
+                .map(|p| p.trim().parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Invalid indicator spec '{}'", text
+                    ))
+                })?;
+            return Self::build(&indicator, &periods, None);
+        }
+
+        let spec = spec.downcast::<PyDict>().map_err(|_| {
+            pyo3::exceptions::PyTypeError::new_err(
+                "Indicator specs must be strings or dicts"
+            )
+        })?;
+        let indicator: String = match spec.get_item("indicator")? {
+            Some(value) => value.extract()?,
+            None => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Indicator spec dicts need an 'indicator' key"
+                ))
+            }
+        };
+        let mut periods = Vec::new();
+        for key in ["period", "fast", "slow"] {
+            if let Some(value) = spec.get_item(key)? {
+                periods.push(value.extract()?);
+            }
+        }
+        let name = spec.get_item("name")?.map(|n| n.extract()).transpose()?;
+        let mut parsed = Self::build(&indicator.to_ascii_lowercase(), &periods, name)?;
+        parsed.above = spec.get_item("above")?.map(|v| v.extract()).transpose()?;
+        parsed.below = spec.get_item("below")?.map(|v| v.extract()).transpose()?;
+        Ok(parsed)
+    }
+
+    /// Build `indicator` from params given as None (the defaults), a period, a list of
+    /// periods or a dict with `period` (or `fast` and `slow`) and `name`; also returns
+    /// whether the spec was named explicitly
+    fn with_params(indicator: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<(Self, bool)> {
+        let (periods, name) = match params.filter(|p| !p.is_none()) {
+            None => (Vec::new(), None),
+            Some(params) => match params.downcast::<PyDict>() {
+                Ok(params) => {
+                    let mut periods = Vec::new();
+                    for key in ["period", "fast", "slow"] {
+                        if let Some(value) = params.get_item(key)? {
+                            periods.push(value.extract()?);
+                        }
+                    }
+                    (periods, params.get_item("name")?.map(|n| n.extract::<String>()).transpose()?)
+                }
+                Err(_) => match params.extract::<usize>() {
+                    Ok(period) => (vec![period], None),
+                    Err(_) => (params.extract()?, None),
+                },
+            },
+        };
+        let named = name.is_some();
+        Ok((Self::build(&indicator.to_ascii_lowercase(), &periods, name)?, named))
+    }
+}
+
+fn check_names(specs: &[Spec]) -> PyResult<()> {
+    for (i, spec) in specs.iter().enumerate() {
+        if specs[..i].iter().any(|s| s.name == spec.name) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Duplicate indicator name '{}'", spec.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn parse_specs(specs: &[Bound<'_, PyAny>]) -> PyResult<Vec<Spec>> {
+    let specs = specs.iter().map(Spec::parse).collect::<PyResult<Vec<_>>>()?;
+    check_names(&specs)?;
+    Ok(specs)
+}
+
+/// Parse a `build_feature_matrix_rust` entry and its lag: an `(indicator, params, lag)`
+/// tuple, where params is None, a period, a list of periods or a dict with `period` (or
+/// `fast` and `slow`) and `name`, and params and lag may be left off; or a spec string or
+/// dict as for `Spec::parse`, the dict with an optional `lag`. Lagged features without an
+/// explicit name get a `_lag{lag}` suffix.
+fn parse_lagged(entry: &Bound<'_, PyAny>) -> PyResult<(Spec, usize)> {
+    let (mut spec, named, lag) = match entry.downcast::<PyTuple>() {
+        Ok(tuple) => {
+            if tuple.is_empty() || tuple.len() > 3 {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Feature tuples must be (indicator, params, lag)"
+                ));
+            }
+            let indicator: String = tuple.get_item(0)?.extract()?;
+            let params = if tuple.len() > 1 { Some(tuple.get_item(1)?) } else { None };
+            let lag = if tuple.len() > 2 { tuple.get_item(2)?.extract()? } else { 0 };
+            let (spec, named) = Spec::with_params(&indicator, params.as_ref())?;
+            (spec, named, lag)
+        }
+        Err(_) => {
+            let dict = entry.downcast::<PyDict>().ok();
+            let lag = match dict {
+                Some(dict) => dict.get_item("lag")?.map(|v| v.extract()).transpose()?.unwrap_or(0),
+                None => 0,
+            };
+            let named = match dict {
+                Some(dict) => dict.contains("name")?,
+                None => false,
+            };
+            (Spec::parse(entry)?, named, lag)
+        }
+    };
+    if lag > 0 && !named {
+        spec.name = format!("{}_lag{}", spec.name, lag);
+    }
+    Ok((spec, lag))
+}
+
+/// Indicator states keyed by period, so specs with the same period share one state
+struct Pool<S> {
+    periods: Vec<usize>,
+    states: Vec<S>,
+}
+
+impl<S> Pool<S> {
+    fn new() -> Self {
+        Pool { periods: Vec::new(), states: Vec::new() }
+    }
+
+    fn get(&mut self, period: usize, make: fn(usize) -> PyResult<S>) -> PyResult<usize> {
+        if let Some(index) = self.periods.iter().position(|&p| p == period) {
+            return Ok(index);
+        }
+        self.states.push(make(period)?);
+        self.periods.push(period);
+        Ok(self.states.len() - 1)
+    }
+}
+
+/// Where an output column reads its value from after each row
+enum Output {
+    Mean(usize),
+    Std(usize),
+    Zscore(usize),
+    Band(usize, f64),
+    Min(usize),
+    Max(usize),
+    /// Percent change across a window one longer than the period
+    Roc(usize),
+    Return,
+    Ema(usize),
+    Rsi(usize),
+    Atr(usize),
+    Natr(usize),
+    Macd(usize, usize),
+}
+
+/// Shared indicator states for one pass over the data
+struct Plan {
+    windows: Pool<RollingWindow>,
+    emas: Pool<StreamingEMA>,
+    rsis: Pool<StreamingRSI>,
+    atrs: Pool<StreamingATR>,
+    outputs: Vec<Output>,
+    /// The latest close and the one before it
+    close: Option<f64>,
+    prev_close: Option<f64>,
+}
+
+impl Plan {
+    fn new(specs: &[Spec]) -> PyResult<Self> {
+        let mut plan = Plan {
+            windows: Pool::new(),
+            emas: Pool::new(),
+            rsis: Pool::new(),
+            atrs: Pool::new(),
+            outputs: Vec::with_capacity(specs.len()),
+            close: None,
+            prev_close: None,
+        };
+        for spec in specs {
+            let window = |plan: &mut Plan, p| plan.windows.get(p, RollingWindow::with_capacity);
+            let output = match spec.kind {
+                Kind::Sma(p) => Output::Mean(window(&mut plan, p)?),
+                Kind::Std(p) => Output::Std(window(&mut plan, p)?),
+                Kind::Zscore(p) => Output::Zscore(window(&mut plan, p)?),
+                Kind::BollingerUpper(p) => Output::Band(window(&mut plan, p)?, BOLLINGER_WIDTH),
+                Kind::BollingerLower(p) => Output::Band(window(&mut plan, p)?, -BOLLINGER_WIDTH),
+                Kind::Min(p) => Output::Min(window(&mut plan, p)?),
+                Kind::Max(p) => Output::Max(window(&mut plan, p)?),
+                Kind::Roc(p) => Output::Roc(window(&mut plan, p + 1)?),
+                Kind::Return => Output::Return,
+                Kind::Ema(p) => Output::Ema(plan.emas.get(p, StreamingEMA::new)?),
+                Kind::Rsi(p) => Output::Rsi(plan.rsis.get(p, StreamingRSI::new)?),
+                Kind::Atr(p) => Output::Atr(plan.atrs.get(p, StreamingATR::new)?),
+                Kind::Natr(p) => Output::Natr(plan.atrs.get(p, StreamingATR::new)?),
+                Kind::Macd(fast, slow) => {
+                    if fast >= slow {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "MACD fast period must be shorter than the slow period"
+                        ));
+                    }
+                    Output::Macd(plan.emas.get(fast, StreamingEMA::new)?, plan.emas.get(slow, StreamingEMA::new)?)
+                }
+            };
+            plan.outputs.push(output);
+        }
+        Ok(plan)
+    }
+
+    fn needs_range(&self) -> bool {
+        !self.atrs.states.is_empty()
+    }
+
+    /// Feed one row to every shared state
+    fn step(&mut self, close: f64, high: f64, low: f64) {
+        for window in &mut self.windows.states {
+            window.push_value(close);
+        }
+        for ema in &mut self.emas.states {
+            ema.update(close);
+        }
+        if let Some(prev) = self.close {
+            for rsi in &mut self.rsis.states {
+                rsi.push_change(close - prev);
+            }
+        }
+        if self.needs_range() {
+            let range = true_range(high, low, self.close);
+            for atr in &mut self.atrs.states {
+                atr.push_true_range(range);
+            }
+        }
+        self.prev_close = self.close.replace(close);
+    }
+
+    /// Current value of each output
+    fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        let windows = &self.windows.states;
+        let close = self.close.unwrap_or(f64::NAN);
+        self.outputs.iter().map(move |output| match *output {
+            Output::Mean(w) if windows[w].is_full() => windows[w].mean(),
+            Output::Std(w) if windows[w].is_full() => windows[w].std(1),
+            Output::Zscore(w) if windows[w].is_full() => (close - windows[w].mean()) / windows[w].std(1),
+            Output::Band(w, width) if windows[w].is_full() => windows[w].mean() + width * windows[w].std(1),
+            Output::Min(w) if windows[w].is_full() => windows[w].min(),
+            Output::Max(w) if windows[w].is_full() => windows[w].max(),
+            Output::Roc(w) if windows[w].is_full() => 100.0 * (close / windows[w].as_slice()[0] - 1.0),
+            Output::Mean(_) | Output::Std(_) | Output::Zscore(_) | Output::Band(..) | Output::Min(_)
+            | Output::Max(_) | Output::Roc(_) => f64::NAN,
+            Output::Return => self.prev_close.map_or(f64::NAN, |prev| (close / prev).ln()),
+            Output::Ema(e) => self.emas.states[e].value(),
+            Output::Rsi(r) => self.rsis.states[r].value(),
+            Output::Atr(a) => self.atrs.states[a].value(),
+            Output::Natr(a) => 100.0 * self.atrs.states[a].value() / close,
+            Output::Macd(fast, slow) => self.emas.states[fast].value() - self.emas.states[slow].value(),
+        })
+    }
+
+    /// Feed every row once and collect the output columns
+    fn run(mut self, close: &[f64], high: &[f64], low: &[f64]) -> Vec<Vec<f64>> {
+        let n = close.len();
+        let mut columns = vec![Vec::with_capacity(n); self.outputs.len()];
+        for i in 0..n {
+            self.step(close[i], high[i], low[i]);
+            for (column, value) in columns.iter_mut().zip(self.values()) {
+                column.push(value);
+            }
+        }
+        columns
+    }
+}
+
+/// Run `specs` over the columns in one pass with the GIL released, returning one output
+/// column per spec
+fn compute_specs<'py>(
+    py: Python<'py>,
+    specs: &[Spec],
+    close: &Column<'py, f64>,
+    high: Option<Column<'py, f64>>,
+    low: Option<Column<'py, f64>>
+) -> PyResult<Vec<Vec<f64>>> {
+    let plan = Plan::new(specs)?;
+    let close = close.values()?;
+    let (high, low) = match (&high, &low) {
+        (Some(high), Some(low)) => (high.values()?, low.values()?),
+        _ if plan.needs_range() => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "ATR needs high and low columns"
+            ))
+        }
+        _ => (close.clone(), close.clone()),
+    };
+    if high.len() != close.len() || low.len() != close.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "All columns must have the same length"
+        ));
+    }
+
+    Ok(py.allow_threads(|| plan.run(&close, &high, &low)))
+}
+
+/// `compute_specs` as a dict of output arrays keyed by name, of the same type as `close`
+fn run_specs<'py>(
+    py: Python<'py>,
+    specs: &[Spec],
+    close: Column<'py, f64>,
+    high: Option<Column<'py, f64>>,
+    low: Option<Column<'py, f64>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let columns = compute_specs(py, specs, &close, high, low)?;
+    let kind = close.kind();
+    let result = PyDict::new_bound(py);
+    for (spec, column) in specs.iter().zip(columns) {
+        result.set_item(&spec.name, kind.wrap(py, column)?)?;
+    }
+    Ok(result)
+}
+
+/// Computes several indicators in a single pass over the data.
+///
+/// `specs` is a list of strings such as `"sma:20"`, `"rsi:14"` or `"macd:12:26"`, or dicts
+/// like `{"indicator": "ema", "period": 50, "name": "trend"}`. Indicators sharing an
+/// intermediate (the same rolling window, EMA or the bar's true range) compute it once.
+/// Every output has the input's length, with NaN during warm-up.
+#[pyclass(module = "fast_math")]
+pub struct IndicatorPipeline {
+    specs: Vec<Spec>,
+}
+
+#[pymethods]
+impl IndicatorPipeline {
+    #[new]
+    fn new(specs: Vec<Bound<'_, PyAny>>) -> PyResult<Self> {
+        let specs = parse_specs(&specs)?;
+        // Validate periods up front rather than on the first run
+        Plan::new(&specs)?;
+        Ok(IndicatorPipeline { specs })
+    }
+
+    /// Output names, in spec order
+    #[getter]
+    fn names(&self) -> Vec<String> {
+        self.specs.iter().map(|s| s.name.clone()).collect()
+    }
+
+    /// Run all indicators over `close` (and `high` / `low`, required for ATR) and return a
+    /// dict of output arrays keyed by name
+    #[pyo3(signature = (close, high=None, low=None))]
+    fn run<'py>(
+        &self,
+        py: Python<'py>,
+        close: Column<'py, f64>,
+        high: Option<Column<'py, f64>>,
+        low: Option<Column<'py, f64>>
+    ) -> PyResult<Bound<'py, PyDict>> {
+        run_specs(py, &self.specs, close, high, low)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("IndicatorPipeline({})", self.names().join(", "))
+    }
+}
+
+/// Close, high and low columns; high and low are optional
+type PriceColumns<'py> = (Column<'py, f64>, Option<Column<'py, f64>>, Option<Column<'py, f64>>);
+
+/// The "close" column of `ohlcv` and its "high" and "low" columns where it has them
+fn price_columns<'py>(py: Python<'py>, ohlcv: &Bound<'py, PyAny>) -> PyResult<PriceColumns<'py>> {
+    let column = |name: &str| -> PyResult<Option<Column<'py, f64>>> {
+        match ohlcv.get_item(name) {
+            Ok(values) => values.extract().map(Some),
+            Err(err) if err.is_instance_of::<pyo3::exceptions::PyKeyError>(py) => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    let close = column("close")?.ok_or_else(|| {
+        pyo3::exceptions::PyKeyError::new_err("ohlcv needs a 'close' column")
+    })?;
+    Ok((close, column("high")?, column("low")?))
+}
+
+/// Compute a set of features from OHLCV bars in one fused pass.
+///
+/// `ohlcv` is anything indexable by column name (an `OhlcvFrame`, a dict of arrays or a
+/// DataFrame) with a "close" column, plus "high" and "low" when ATR or NATR is requested.
+/// `feature_spec` lists specs as for `IndicatorPipeline`; besides sma, std, ema, rsi, atr
+/// and macd it accepts zscore, bb_upper / bb_lower (2 standard deviations), rolling min /
+/// max, roc (percent rate of change), return (1-bar log return) and natr (ATR as a percent
+/// of close). Features sharing a rolling window, EMA or true range compute it once, and the
+/// price columns are read a single time. Returns a dict of arrays keyed by feature name,
+/// each with the input's length and NaN during warm-up.
+#[pyfunction]
+pub fn compute_features_rust<'py>(
+    py: Python<'py>,
+    ohlcv: &Bound<'py, PyAny>,
+    feature_spec: Vec<Bound<'py, PyAny>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let specs = parse_specs(&feature_spec)?;
+    let (close, high, low) = price_columns(py, ohlcv)?;
+    run_specs(py, &specs, close, high, low)
+}
+
+/// Compute the same features for many symbols at once.
+///
+/// `ohlcv_by_symbol` maps each symbol to its bars, read as for `compute_features_rust`,
+/// and `feature_spec` lists specs as for that function. Each symbol is computed in one
+/// fused pass. Returns a `ResultSet` mapping every symbol to its features by name, in
+/// the order of the mapping.
+#[pyfunction]
+pub fn compute_features_batch_rust<'py>(
+    py: Python<'py>,
+    ohlcv_by_symbol: &Bound<'py, PyDict>,
+    feature_spec: Vec<Bound<'py, PyAny>>
+) -> PyResult<ResultSet> {
+    let specs = parse_specs(&feature_spec)?;
+    let mut results = Vec::with_capacity(ohlcv_by_symbol.len());
+    for (symbol, ohlcv) in ohlcv_by_symbol.iter() {
+        let symbol: String = symbol.extract()?;
+        let (close, high, low) = price_columns(py, &ohlcv)?;
+        let columns = compute_specs(py, &specs, &close, high, low)?;
+        results.push((symbol, specs.iter().map(|s| s.name.clone()).zip(columns).collect()));
+    }
+    Ok(ResultSet::new(results))
+}
+
+/// Assemble a 2D feature matrix for model inference from declarative indicator specs.
+///
+/// `ohlcv` is read as for `compute_features_rust`. Each entry of `spec` is an
+/// `(indicator, params, lag)` tuple such as `("rsi", 14, 1)` or `("macd", [12, 26], 0)`:
+/// any indicator of `compute_features_rust`, its params as None (the defaults), a
+/// period, a list of periods or a dict with `period` (or `fast` and `slow`) and an
+/// optional `name`, and the number of bars to lag it by, so row `t` holds the value from
+/// bar `t - lag`; params and lag may be left off. Spec strings and dicts (with an
+/// optional `lag` key) are accepted too. Lagged features are named with a `_lag{lag}`
+/// suffix unless named explicitly. All indicators are computed in one fused pass. With
+/// `trim` (the default) the leading rows are dropped up to the first where every feature
+/// has warmed up, so no column starts with NaN; NaN in the input can still leave NaN
+/// later on. Returns a dict with the C-contiguous `matrix` (one row per bar from
+/// `start`, one column per entry), the feature `names` in column order and `start`, the
+/// bar index of the first row.
+#[pyfunction]
+#[pyo3(signature = (ohlcv, spec, trim=true))]
+pub fn build_feature_matrix_rust<'py>(
+    py: Python<'py>,
+    ohlcv: &Bound<'py, PyAny>,
+    spec: Vec<Bound<'py, PyAny>>,
+    trim: bool
+) -> PyResult<Bound<'py, PyDict>> {
+    let (specs, lags): (Vec<Spec>, Vec<usize>) =
+        spec.iter().map(parse_lagged).collect::<PyResult<Vec<_>>>()?.into_iter().unzip();
+    if specs.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "spec must list at least one feature"
+        ));
+    }
+    check_names(&specs)?;
+    let (close, high, low) = price_columns(py, ohlcv)?;
+    let columns = compute_specs(py, &specs, &close, high, low)?;
+    let (matrix, start) = py.allow_threads(|| {
+        let n = columns[0].len();
+        let start = if trim {
+            columns
+                .iter()
+                .zip(&lags)
+                .map(|(column, &lag)| column.iter().position(|v| !v.is_nan()).map_or(n, |first| (first + lag).min(n)))
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let matrix = Array2::from_shape_fn((n - start, columns.len()), |(t, j)| {
+            let bar = t + start;
+            if bar >= lags[j] { columns[j][bar - lags[j]] } else { f64::NAN }
+        });
+        (matrix, start)
+    });
+
+    let result = PyDict::new_bound(py);
+    result.set_item("matrix", PyArray2::from_owned_array_bound(py, matrix))?;
+    result.set_item("names", specs.iter().map(|s| s.name.as_str()).collect::<Vec<_>>())?;
+    result.set_item("start", start)?;
+    Ok(result)
+}
+
+/// A threshold crossing: output index, tick index, value and whether it crossed above
+type Signal = (usize, usize, f64, bool);
+
+/// Per-output crossing detection against the specs' `above` / `below` thresholds
+struct Crossings {
+    thresholds: Vec<(Option<f64>, Option<f64>)>,
+    last: Vec<f64>,
+}
+
+impl Crossings {
+    fn new(specs: &[Spec]) -> Self {
+        Crossings {
+            thresholds: specs.iter().map(|s| (s.above, s.below)).collect(),
+            last: vec![f64::NAN; specs.len()],
+        }
+    }
+
+    fn check(&mut self, plan: &Plan, tick: usize, signals: &mut Vec<Signal>) {
+        for (output, value) in plan.values().enumerate() {
+            let last = std::mem::replace(&mut self.last[output], value);
+            if last.is_nan() || value.is_nan() {
+                continue;
+            }
+            let (above, below) = self.thresholds[output];
+            if above.is_some_and(|t| last <= t && value > t) {
+                signals.push((output, tick, value, true));
+            }
+            if below.is_some_and(|t| last >= t && value < t) {
+                signals.push((output, tick, value, false));
+            }
+        }
+    }
+}
+
+/// Feed a stream of ticks through a set of indicators, calling back only on signals.
+///
+/// `iterator` yields closes (floats), `(high, low, close)` tuples, or 1-D arrays of closes;
+/// arrays are processed with the GIL released. `indicators` are specs as for
+/// `IndicatorPipeline`, where dict specs may set `above` and/or `below` thresholds.
+/// `callback(name, index, value, direction)` is called with direction "above" or "below"
+/// each time an indicator crosses one of its thresholds; returning False stops the stream.
+/// Returns the number of ticks processed.
+#[pyfunction]
+pub fn process_stream_rust<'py>(
+    py: Python<'py>,
+    iterator: &Bound<'py, PyAny>,
+    indicators: Vec<Bound<'py, PyAny>>,
+    callback: &Bound<'py, PyAny>
+) -> PyResult<usize> {
+    let specs = parse_specs(&indicators)?;
+    let mut plan = Plan::new(&specs)?;
+    let mut crossings = Crossings::new(&specs);
+    let mut signals = Vec::new();
+    let mut ticks = 0usize;
+
+    for item in iterator.iter()? {
+        let item = item?;
+        if let Ok((high, low, close)) = item.extract::<(f64, f64, f64)>() {
+            plan.step(close, high, low);
+            crossings.check(&plan, ticks, &mut signals);
+            ticks += 1;
+        } else if plan.needs_range() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "ATR needs (high, low, close) ticks"
+            ));
+        } else if let Ok(close) = item.extract::<f64>() {
+            plan.step(close, close, close);
+            crossings.check(&plan, ticks, &mut signals);
+            ticks += 1;
+        } else {
+            let chunk: Column<'py, f64> = item.extract()?;
+            let chunk = chunk.values()?;
+            py.allow_threads(|| {
+                for &close in chunk.iter() {
+                    plan.step(close, close, close);
+                    crossings.check(&plan, ticks, &mut signals);
+                    ticks += 1;
+                }
+            });
+        }
+
+        for (output, index, value, above) in signals.drain(..) {
+            let direction = if above { "above" } else { "below" };
+            let result = callback.call1((&specs[output].name, index, value, direction))?;
+            if result.extract::<bool>().is_ok_and(|keep| !keep) {
+                return Ok(ticks);
+            }
+        }
+    }
+    Ok(ticks)
+}
+
+/// Compute an indicator on a higher timeframe and forward-fill it onto the base bars.
+///
+/// `ohlcv_1m` is read as for `compute_features_rust`, plus its "timestamp" column
+/// (ascending UTC nanoseconds) unless `timestamps` are given. The bars are resampled to
+/// `higher_tf` (such as "15min", "1h" or "1d") with the bins of `resample_ohlcv_rust`,
+/// including its `closed`, `offset` and `calendar`, and `indicator` with `params` (None
+/// for the defaults, a period, a list of periods or a dict, as in
+/// `build_feature_matrix_rust`) runs over the higher-timeframe close, high and low. Each
+/// base bar gets the value of the last higher-timeframe bar completed before it, so a
+/// bar never sees the bin it is still part of: the value of a bin first appears on the
+/// bar after the bin ends. Returns one value per base bar, NaN until the first higher
+/// bar has completed and the indicator warmed up.
+#[pyfunction]
+#[pyo3(signature = (ohlcv_1m, indicator, params, higher_tf, closed="left", offset=0, calendar=None, timestamps=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn mtf_indicator_rust<'py>(
+    py: Python<'py>,
+    ohlcv_1m: &Bound<'py, PyAny>,
+    indicator: &str,
+    params: Option<Bound<'py, PyAny>>,
+    higher_tf: &str,
+    closed: &str,
+    offset: i64,
+    calendar: Option<PyRef<'py, TradingCalendar>>,
+    timestamps: Option<Column<'py, i64>>
+) -> PyResult<PyObject> {
+    let (spec, _) = Spec::with_params(indicator, params.as_ref())?;
+    let plan = Plan::new(std::slice::from_ref(&spec))?;
+    let binning = Binning::new(higher_tf, offset, closed, "left")?;
+    let (close, high, low) = price_columns(py, ohlcv_1m)?;
+    let timestamps = match timestamps {
+        Some(timestamps) => timestamps,
+        None => match ohlcv_1m.get_item("timestamp") {
+            Ok(values) => values.extract()?,
+            Err(err) if err.is_instance_of::<pyo3::exceptions::PyKeyError>(py) => {
+                return Err(pyo3::exceptions::PyKeyError::new_err(
+                    "ohlcv_1m needs a 'timestamp' column unless timestamps are given"
+                ))
+            }
+            Err(err) => return Err(err),
+        },
+    };
+    let (timestamps, kind, close) = (timestamps.values()?, close.kind(), close.values()?);
+    let (high, low) = match (&high, &low) {
+        (Some(high), Some(low)) => (high.values()?, low.values()?),
+        _ if plan.needs_range() => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "ATR needs high and low columns"
+            ))
+        }
+        _ => (close.clone(), close.clone()),
+    };
+    if [timestamps.len(), high.len(), low.len()].iter().any(|&n| n != close.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "All columns must have the same length"
+        ));
+    }
+    if timestamps.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Timestamps must be sorted ascending"
+        ));
+    }
+    let calendar = calendar.map(|c| c.clone());
+
+    let values = py.allow_threads(|| {
+        // Open is not read by any indicator; the close stands in for it
+        let ohlc = Array2::from_shape_fn((close.len(), 4), |(i, j)| match j {
+            1 => high[i],
+            2 => low[i],
+            _ => close[i],
+        });
+        let bars = resample(&timestamps, ohlc.view(), &binning, calendar.as_ref());
+        let higher = plan.run(&bars.close, &bars.high, &bars.low).swap_remove(0);
+        // A bin is complete once a row falls in a later bin or outside every session
+        let mut last_bin = None;
+        bin_indices(&timestamps, &binning, calendar.as_ref())
+            .into_iter()
+            .map(|bin| match bin {
+                Some(k) => {
+                    last_bin = Some(k);
+                    if k > 0 { higher[k - 1] } else { f64::NAN }
+                }
+                None => last_bin.map_or(f64::NAN, |k| higher[k]),
+            })
+            .collect::<Vec<f64>>()
+    });
+    kind.wrap(py, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Float64Array;
+
+    use crate::streaming::StreamingATR;
+
+    fn prices(n: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let close: Vec<f64> = (0..n).map(|i| 100.0 + (i as f64 * 0.37).sin() * 5.0 + i as f64 * 0.05).collect();
+        let high = close.iter().enumerate().map(|(i, c)| c + 0.5 + (i % 3) as f64 * 0.25).collect();
+        let low = close.iter().enumerate().map(|(i, c)| c - 0.5 - (i % 4) as f64 * 0.2).collect();
+        (close, high, low)
+    }
+
+    fn column(values: &[f64]) -> Column<'static, f64> {
+        Column::Polars(Float64Array::from(values.to_vec()))
+    }
+
+    /// Compare an output column with `expected`, which starts at row `offset`; NaN matches NaN
+    fn assert_matches(name: &str, actual: &[f64], expected: &[f64], offset: usize) {
+        assert_eq!(actual.len(), expected.len() + offset, "{}", name);
+        assert!(actual[..offset].iter().all(|v| v.is_nan()), "{} warm-up", name);
+        for (i, (a, e)) in actual[offset..].iter().zip(expected).enumerate() {
+            let same = (a - e).abs() < 1e-9 * e.abs().max(1.0) || (a.is_nan() && e.is_nan());
+            assert!(same, "{} row {}: {} != {}", name, i + offset, a, e);
+        }
+    }
+
+    #[test]
+    fn pipeline_outputs_match_the_standalone_kernels() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let specs = [
+                "sma:5", "std:5", "zscore:5", "bb_lower:5", "min:5", "max:5", "roc:3", "return", "ema:4", "rsi:6",
+                "atr:5", "natr:5", "macd:3:8",
+            ];
+            let mut specs: Vec<Bound<'_, PyAny>> = specs.iter().map(|s| PyString::new_bound(py, s).into_any()).collect();
+            let trend = PyDict::new_bound(py);
+            trend.set_item("indicator", "EMA").unwrap();
+            trend.set_item("period", 4).unwrap();
+            trend.set_item("name", "trend").unwrap();
+            specs.push(trend.into_any());
+            let pipeline = IndicatorPipeline::new(specs).unwrap();
+            assert_eq!(pipeline.names()[12..], ["macd_3_8".to_string(), "trend".to_string()]);
+
+            let (close, high, low) = prices(300);
+            let outputs =
+                compute_specs(py, &pipeline.specs, &column(&close), Some(column(&high)), Some(column(&low))).unwrap();
+            let output = |name: &str| &outputs[pipeline.names().iter().position(|n| n == name).unwrap()];
+
+            let sma = crate::moving_average(&close, 5).unwrap();
+            let std = crate::rolling_std(&close, 5, 1).unwrap();
+            assert_matches("sma_5", output("sma_5"), &sma, 4);
+            assert_matches("std_5", output("std_5"), &std, 4);
+            let zscore: Vec<f64> = (0..sma.len()).map(|i| (close[i + 4] - sma[i]) / std[i]).collect();
+            assert_matches("zscore_5", output("zscore_5"), &zscore, 4);
+            let lower: Vec<f64> = sma.iter().zip(&std).map(|(m, s)| m - 2.0 * s).collect();
+            assert_matches("bb_lower_5", output("bb_lower_5"), &lower, 4);
+            let min: Vec<f64> = close.windows(5).map(|w| w.iter().copied().fold(f64::INFINITY, f64::min)).collect();
+            let max: Vec<f64> = close.windows(5).map(|w| w.iter().copied().fold(f64::NEG_INFINITY, f64::max)).collect();
+            assert_matches("min_5", output("min_5"), &min, 4);
+            assert_matches("max_5", output("max_5"), &max, 4);
+            let roc: Vec<f64> = close.windows(4).map(|w| 100.0 * (w[3] / w[0] - 1.0)).collect();
+            assert_matches("roc_3", output("roc_3"), &roc, 3);
+            let returns: Vec<f64> = close.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+            assert_matches("return", output("return"), &returns, 1);
+            assert_matches("rsi_6", output("rsi_6"), &crate::rsi(&close, 6).unwrap(), 6);
+
+            let streamed = |period: usize| -> Vec<f64> {
+                let mut ema = StreamingEMA::new(period).unwrap();
+                close.iter().map(|&c| ema.update(c)).collect()
+            };
+            assert_matches("ema_4", output("ema_4"), &streamed(4), 0);
+            assert_matches("trend", output("trend"), output("ema_4"), 0);
+            let macd: Vec<f64> = streamed(3).iter().zip(streamed(8)).map(|(f, s)| f - s).collect();
+            assert_matches("macd_3_8", output("macd_3_8"), &macd, 0);
+
+            let mut atr = StreamingATR::new(5).unwrap();
+            let atr: Vec<f64> = (0..close.len()).map(|i| atr.update(high[i], low[i], close[i])).collect();
+            assert_matches("atr_5", output("atr_5"), &atr[4..], 4);
+            let natr: Vec<f64> = (4..close.len()).map(|i| 100.0 * atr[i] / close[i]).collect();
+            assert_matches("natr_5", output("natr_5"), &natr, 4);
+        });
+    }
+
+    #[test]
+    fn window_extremes_are_nan_with_the_mean() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let specs = ["sma:3", "min:3", "max:3"].map(|s| PyString::new_bound(py, s).into_any());
+            let specs = parse_specs(&specs).unwrap();
+            let close = [1.0, f64::NAN, 0.0, 2.0, 5.0, 3.0];
+            let outputs = compute_specs(py, &specs, &column(&close), None, None).unwrap();
+            let nan = f64::NAN;
+            assert_matches("sma_3", &outputs[0], &[nan, nan, 7.0 / 3.0, 10.0 / 3.0], 2);
+            assert_matches("min_3", &outputs[1], &[nan, nan, 0.0, 2.0], 2);
+            assert_matches("max_3", &outputs[2], &[nan, nan, 5.0, 5.0], 2);
+        });
+    }
+
+    #[test]
+    fn atr_without_high_and_low_is_rejected() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let specs = parse_specs(&[PyString::new_bound(py, "atr:5").into_any()]).unwrap();
+            assert!(compute_specs(py, &specs, &column(&[1.0, 2.0]), None, None).is_err());
+            let duplicate = [PyString::new_bound(py, "sma:5").into_any(), PyString::new_bound(py, "sma:5").into_any()];
+            assert!(parse_specs(&duplicate).is_err());
+        });
+    }
+}
@@ -0,0 +1,120 @@
+// Per-thread pools of scratch vectors, bucketed by power-of-two capacity, so kernels called
+// thousands of times a second reuse their temporaries instead of hitting the allocator.
+// Each Rayon worker has its own pool, so taking and returning a buffer never locks.
+
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+/// Buffers kept per size class and thread
+const PER_CLASS: usize = 4;
+/// Capacity above which buffers go straight back to the allocator
+const MAX_POOLED: usize = 1 << 24;
+
+/// Free vectors of one element type, indexed by log2 of their capacity
+pub struct Pool<T> {
+    classes: Vec<Vec<Vec<T>>>,
+}
+
+impl<T> Pool<T> {
+    const fn new() -> Self {
+        Pool { classes: Vec::new() }
+    }
+
+    /// An empty vector with capacity for at least `len` values
+    fn take(&mut self, len: usize) -> Vec<T> {
+        let class = len.max(1).next_power_of_two().trailing_zeros() as usize;
+        self.classes
+            .get_mut(class)
+            .and_then(Vec::pop)
+            .unwrap_or_else(|| Vec::with_capacity(1 << class))
+    }
+
+    fn give(&mut self, mut vec: Vec<T>) {
+        let capacity = vec.capacity();
+        if capacity == 0 || capacity > MAX_POOLED {
+            return;
+        }
+        // Round down, so every buffer in a class can hold a request of that class
+        let class = (usize::BITS - 1 - capacity.leading_zeros()) as usize;
+        if self.classes.len() <= class {
+            self.classes.resize_with(class + 1, Vec::new);
+        }
+        if self.classes[class].len() < PER_CLASS {
+            vec.clear();
+            self.classes[class].push(vec);
+        }
+    }
+}
+
+/// Element types with a per-thread pool
+pub trait Poolable: Copy + Default + 'static {
+    /// Run `f` on this thread's pool; `None` during thread teardown, once it is gone
+    fn with_pool<R>(f: impl FnOnce(&mut Pool<Self>) -> R) -> Option<R>;
+}
+
+macro_rules! poolable {
+    ($($t:ty),*) => {
+        $(
+            impl Poolable for $t {
+                fn with_pool<R>(f: impl FnOnce(&mut Pool<Self>) -> R) -> Option<R> {
+                    thread_local! {
+                        static POOL: RefCell<Pool<$t>> = const { RefCell::new(Pool::new()) };
+                    }
+                    POOL.try_with(|pool| f(&mut pool.borrow_mut())).ok()
+                }
+            }
+        )*
+    };
+}
+
+poolable!(f64, f32);
+
+/// A scratch vector taken from the current thread's pool and returned to it on drop
+pub struct Buffer<T: Poolable> {
+    vec: Vec<T>,
+}
+
+impl<T: Poolable> Buffer<T> {
+    /// An empty buffer with capacity for at least `capacity` values
+    pub fn with_capacity(capacity: usize) -> Self {
+        let vec = T::with_pool(|pool| pool.take(capacity));
+        Buffer { vec: vec.unwrap_or_else(|| Vec::with_capacity(capacity)) }
+    }
+
+    /// A buffer of `len` default (zero) values
+    pub fn zeroed(len: usize) -> Self {
+        let mut buffer = Self::with_capacity(len);
+        buffer.vec.resize(len, T::default());
+        buffer
+    }
+}
+
+impl<T: Poolable> FromIterator<T> for Buffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut buffer = Self::with_capacity(iter.size_hint().0);
+        buffer.vec.extend(iter);
+        buffer
+    }
+}
+
+impl<T: Poolable> Deref for Buffer<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.vec
+    }
+}
+
+impl<T: Poolable> DerefMut for Buffer<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.vec
+    }
+}
+
+impl<T: Poolable> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        let vec = std::mem::take(&mut self.vec);
+        T::with_pool(|pool| pool.give(vec));
+    }
+}
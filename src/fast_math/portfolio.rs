@@ -0,0 +1,417 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+use crate::costs::CostModel;
+
+/// Fractions of a lot below this are rounding residue, and close it
+const DUST: f64 = 1e-9;
+
+/// How the cost of a closing fill is matched against the open position
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CostMethod {
+    /// Against the oldest open lots first
+    Fifo,
+    /// Against the average cost of the whole position
+    Average,
+}
+
+impl CostMethod {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fifo" => Ok(CostMethod::Fifo),
+            "average" | "average_cost" | "avg" => Ok(CostMethod::Average),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'fifo' or 'average'", name
+            ))),
+        }
+    }
+}
+
+/// One symbol's position as open lots of signed quantity and entry price, all on the
+/// same side; the average-cost method keeps a single lot
+#[derive(Default)]
+struct Book {
+    lots: VecDeque<(f64, f64)>,
+    realized: f64,
+    fees: f64,
+    /// Funding and borrow paid for holding the position
+    financing: f64,
+    /// Latest mark, or the last fill price before any mark
+    mark: f64,
+    /// Whether `mark` was set explicitly, after which fills leave it alone
+    marked: bool,
+}
+
+impl Book {
+    fn position(&self) -> f64 {
+        self.lots.iter().map(|(q, _)| q).sum()
+    }
+
+    fn average_cost(&self) -> f64 {
+        let position = self.position();
+        if position == 0.0 {
+            return f64::NAN;
+        }
+        self.lots.iter().map(|(q, p)| q * p).sum::<f64>() / position
+    }
+
+    fn unrealized(&self) -> f64 {
+        self.lots.iter().map(|(q, p)| q * (self.mark - p)).sum()
+    }
+
+    /// Apply a fill of signed `qty` at `price`, returning the P&L it realises net of `fee`
+    fn fill(&mut self, qty: f64, price: f64, fee: f64, method: CostMethod) -> f64 {
+        let mut remaining = qty;
+        let mut realized = -fee;
+        // Close against open lots on the other side first
+        while remaining != 0.0 {
+            let Some(lot) = self.lots.front_mut() else { break };
+            if lot.0.signum() == remaining.signum() {
+                break;
+            }
+            let closed = remaining.abs().min(lot.0.abs()) * lot.0.signum();
+            realized += closed * (price - lot.1);
+            lot.0 -= closed;
+            remaining += closed;
+            if lot.0.abs() <= DUST * closed.abs() {
+                self.lots.pop_front();
+            }
+            if remaining.abs() <= DUST * qty.abs() {
+                remaining = 0.0;
+            }
+        }
+        // Whatever is left opens or adds to the position
+        if remaining != 0.0 {
+            match (method, self.lots.front_mut()) {
+                (CostMethod::Average, Some(lot)) => {
+                    lot.1 = (lot.0 * lot.1 + remaining * price) / (lot.0 + remaining);
+                    lot.0 += remaining;
+                }
+                _ => self.lots.push_back((remaining, price)),
+            }
+        }
+        self.realized += realized;
+        self.fees += fee;
+        if !self.marked {
+            self.mark = price;
+        }
+        realized
+    }
+}
+
+/// Position and P&L accounting across symbols from fills and mark prices.
+///
+/// Fills are signed (positive buys, negative sells) and may flip a position through zero.
+/// `method="fifo"` (the default) realises P&L on closing fills against the oldest open
+/// lots first, "average" against the position's average cost; both give the same total
+/// P&L, only its split between realised and unrealised differs. Fees are deducted from
+/// realised P&L: given with each fill, or else charged by `cost_model`, a `CostModel`
+/// (whose tier volume the fills then count towards), which `accrue` also uses to charge
+/// funding and borrow on the open positions. Unrealised P&L and exposure use the latest
+/// mark of each symbol, or its last fill price until it is marked. Quantities and prices
+/// are in the instrument's own units: scale prices by a contract multiplier before
+/// passing them in.
+#[pyclass(module = "fast_math")]
+pub struct Portfolio {
+    method: CostMethod,
+    books: BTreeMap<String, Book>,
+    cost_model: Option<Py<CostModel>>,
+}
+
+impl Portfolio {
+    /// `fee` if given, else the cost model's fee for the fill, else 0
+    fn fee(&self, py: Python<'_>, fee: Option<f64>, qty: f64, price: f64, maker: bool) -> f64 {
+        match (fee, &self.cost_model) {
+            (Some(fee), _) => fee,
+            (None, Some(model)) => model.borrow_mut(py).fee(qty, price, maker),
+            (None, None) => 0.0,
+        }
+    }
+
+    /// Sum of `f` over `symbol`'s book, or over every book
+    fn total(&self, symbol: Option<&str>, f: impl Fn(&Book) -> f64) -> f64 {
+        match symbol {
+            Some(symbol) => self.books.get(symbol).map_or(0.0, f),
+            None => self.books.values().map(f).sum(),
+        }
+    }
+}
+
+fn check_fill(qty: f64, price: f64, fee: Option<f64>) -> PyResult<()> {
+    if !qty.is_finite() || !price.is_finite() || fee.is_some_and(|f| !f.is_finite()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Fill quantity, price and fee must be finite"
+        ));
+    }
+    Ok(())
+}
+
+#[pymethods]
+impl Portfolio {
+    #[new]
+    #[pyo3(signature = (method="fifo", cost_model=None))]
+    fn new(method: &str, cost_model: Option<Py<CostModel>>) -> PyResult<Self> {
+        Ok(Portfolio { method: CostMethod::parse(method)?, books: BTreeMap::new(), cost_model })
+    }
+
+    /// Apply a fill of signed `qty` at `price` and return the P&L it realises, net of
+    /// `fee` (default: the cost model's, as maker when `maker`)
+    #[pyo3(signature = (symbol, qty, price, fee=None, maker=false))]
+    fn fill(
+        &mut self,
+        py: Python<'_>,
+        symbol: &str,
+        qty: f64,
+        price: f64,
+        fee: Option<f64>,
+        maker: bool
+    ) -> PyResult<f64> {
+        check_fill(qty, price, fee)?;
+        let fee = self.fee(py, fee, qty, price, maker);
+        let method = self.method;
+        Ok(self.books.entry(symbol.to_string()).or_default().fill(qty, price, fee, method))
+    }
+
+    /// Apply fills in order from parallel columns and return the total P&L they realise;
+    /// without `fee` the cost model charges them as taker fills
+    #[pyo3(signature = (symbols, qty, price, fee=None))]
+    fn fill_many(
+        &mut self,
+        py: Python<'_>,
+        symbols: Vec<String>,
+        qty: Column<'_, f64>,
+        price: Column<'_, f64>,
+        fee: Option<Column<'_, f64>>
+    ) -> PyResult<f64> {
+        let qty = qty.values()?;
+        let price = price.values()?;
+        let fee = fee.as_ref().map(|f| f.values()).transpose()?;
+        let n = symbols.len();
+        if qty.len() != n || price.len() != n || fee.as_ref().is_some_and(|f| f.len() != n) {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "symbols, qty, price and fee must have the same length"
+            ));
+        }
+        for i in 0..n {
+            check_fill(qty[i], price[i], fee.as_ref().map(|f| f[i]))?;
+        }
+        let method = self.method;
+        let mut realized = 0.0;
+        for (i, symbol) in symbols.into_iter().enumerate() {
+            let fee = self.fee(py, fee.as_ref().map(|f| f[i]), qty[i], price[i], false);
+            realized += self.books.entry(symbol).or_default().fill(qty[i], price[i], fee, method);
+        }
+        Ok(realized)
+    }
+
+    /// Set the mark price of `symbol`
+    fn mark(&mut self, symbol: &str, price: f64) -> PyResult<()> {
+        if !price.is_finite() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Mark price must be finite"
+            ));
+        }
+        let book = self.books.entry(symbol.to_string()).or_default();
+        book.mark = price;
+        book.marked = true;
+        Ok(())
+    }
+
+    /// Set the mark prices of several symbols from a `{symbol: price}` dict
+    fn mark_many(&mut self, prices: BTreeMap<String, f64>) -> PyResult<()> {
+        for (symbol, price) in prices {
+            self.mark(&symbol, price)?;
+        }
+        Ok(())
+    }
+
+    /// Charge the cost model's funding and borrow for holding every open position at its
+    /// mark for `years`, and return the total charged
+    fn accrue(&mut self, py: Python<'_>, years: f64) -> PyResult<f64> {
+        let Some(model) = &self.cost_model else {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "accrue needs a Portfolio created with a cost_model"
+            ));
+        };
+        let model = model.borrow(py);
+        let mut total = 0.0;
+        for book in self.books.values_mut() {
+            let cost = model.holding_cost(book.position(), book.mark, years);
+            book.financing += cost;
+            book.realized -= cost;
+            total += cost;
+        }
+        Ok(total)
+    }
+
+    /// Signed position in `symbol`, 0 if it was never traded
+    fn position(&self, symbol: &str) -> f64 {
+        self.books.get(symbol).map_or(0.0, Book::position)
+    }
+
+    /// Average entry price of the open position in `symbol`, NaN when flat
+    fn average_cost(&self, symbol: &str) -> f64 {
+        self.books.get(symbol).map_or(f64::NAN, Book::average_cost)
+    }
+
+    /// Realised P&L net of fees and financing, of `symbol` or the whole portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn realized_pnl(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, |b| b.realized)
+    }
+
+    /// Unrealised P&L of the open positions at their marks, of `symbol` or the whole
+    /// portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn unrealized_pnl(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, Book::unrealized)
+    }
+
+    /// Realised plus unrealised P&L, of `symbol` or the whole portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn total_pnl(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, |b| b.realized + b.unrealized())
+    }
+
+    /// Fees paid, of `symbol` or the whole portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn fees(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, |b| b.fees)
+    }
+
+    /// Funding and borrow charged by `accrue`, of `symbol` or the whole portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn financing(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, |b| b.financing)
+    }
+
+    /// Gross exposure, the sum of absolute market values, of `symbol` or the whole
+    /// portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn exposure(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, |b| (b.position() * b.mark).abs())
+    }
+
+    /// Net exposure, the sum of signed market values, of `symbol` or the whole portfolio
+    #[pyo3(signature = (symbol=None))]
+    fn net_exposure(&self, symbol: Option<&str>) -> f64 {
+        self.total(symbol, |b| b.position() * b.mark)
+    }
+
+    /// Symbols traded or marked so far, sorted
+    #[getter]
+    fn symbols(&self) -> Vec<String> {
+        self.books.keys().cloned().collect()
+    }
+
+    /// Per-symbol state as a dict of columns (for `pandas.DataFrame`): `symbol`,
+    /// `position`, `average_cost`, `mark`, `market_value`, `realized_pnl`,
+    /// `unrealized_pnl`, `fees` and `financing`, one row per symbol in sorted order
+    fn positions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let column = |f: &dyn Fn(&Book) -> f64| PyArray1::from_vec_bound(py, self.books.values().map(f).collect());
+        let result = PyDict::new_bound(py);
+        result.set_item("symbol", self.symbols())?;
+        result.set_item("position", column(&Book::position))?;
+        result.set_item("average_cost", column(&Book::average_cost))?;
+        result.set_item("mark", column(&|b| b.mark))?;
+        result.set_item("market_value", column(&|b| b.position() * b.mark))?;
+        result.set_item("realized_pnl", column(&|b| b.realized))?;
+        result.set_item("unrealized_pnl", column(&Book::unrealized))?;
+        result.set_item("fees", column(&|b| b.fees))?;
+        result.set_item("financing", column(&|b| b.financing))?;
+        Ok(result)
+    }
+
+    /// Forget every position, mark and P&L
+    fn reset(&mut self) {
+        self.books.clear();
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Portfolio(symbols={}, realized_pnl={}, unrealized_pnl={})",
+            self.books.len(),
+            self.realized_pnl(None),
+            self.unrealized_pnl(None)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_only_mark_until_a_mark_is_set() {
+        let mut book = Book::default();
+        book.fill(10.0, 100.0, 0.0, CostMethod::Fifo);
+        book.fill(5.0, 102.0, 0.0, CostMethod::Fifo);
+        assert_eq!(book.mark, 102.0);
+        assert_eq!(book.unrealized(), 20.0);
+
+        book.mark = 101.0;
+        book.marked = true;
+        book.fill(-5.0, 104.0, 0.0, CostMethod::Fifo);
+        assert_eq!((book.mark, book.realized), (101.0, 20.0));
+        // 5 @ 100 and 5 @ 102 left open, valued at the explicit mark
+        assert_eq!(book.unrealized(), 0.0);
+    }
+
+    #[test]
+    fn a_flip_realises_the_closed_part_and_opens_the_rest() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let cases = [("fifo", 250.0, 50.0, 23.0), ("average", 225.0, 75.0, 48.0)];
+            for (method, realized, unrealized, flip) in cases {
+                let mut portfolio = Portfolio::new(method, None).unwrap();
+                portfolio.fill(py, "ES", 10.0, 100.0, None, false).unwrap();
+                portfolio.fill(py, "ES", 10.0, 110.0, None, false).unwrap();
+                assert_eq!(portfolio.fill(py, "ES", -15.0, 120.0, None, false).unwrap(), realized);
+                assert_eq!(portfolio.unrealized_pnl(None), unrealized);
+                assert_eq!(portfolio.total_pnl(Some("ES")), 300.0);
+
+                // Selling 10 out of a long 5 closes it and leaves 5 short at the fill price
+                assert_eq!(portfolio.fill(py, "ES", -10.0, 115.0, Some(2.0), false).unwrap(), flip);
+                assert_eq!(portfolio.position("ES"), -5.0);
+                assert_eq!(portfolio.average_cost("ES"), 115.0);
+                assert_eq!(portfolio.unrealized_pnl(None), 0.0);
+                assert_eq!(portfolio.realized_pnl(None), 273.0);
+                assert_eq!(portfolio.total_pnl(None), 273.0);
+                assert_eq!(portfolio.fees(None), 2.0);
+
+                portfolio.mark("ES", 111.0).unwrap();
+                assert_eq!(portfolio.unrealized_pnl(Some("ES")), 20.0);
+                assert_eq!(portfolio.net_exposure(None), -555.0);
+            }
+        });
+    }
+
+    #[test]
+    fn an_explicit_mark_outlives_later_fills() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut portfolio = Portfolio::new("fifo", None).unwrap();
+            portfolio.mark_many(BTreeMap::from([("ES".to_string(), 105.0), ("NQ".to_string(), 200.0)])).unwrap();
+            assert_eq!(portfolio.symbols(), vec!["ES", "NQ"]);
+            assert_eq!(portfolio.position("NQ"), 0.0);
+
+            portfolio.fill(py, "ES", 4.0, 100.0, None, false).unwrap();
+            portfolio.fill(py, "ES", 2.0, 103.0, None, false).unwrap();
+            assert_eq!(portfolio.unrealized_pnl(Some("ES")), 24.0);
+            assert_eq!(portfolio.exposure(None), 630.0);
+
+            // Marks only move through mark()
+            portfolio.fill(py, "ES", -3.0, 110.0, None, false).unwrap();
+            assert_eq!(portfolio.realized_pnl(None), 30.0);
+            assert_eq!(portfolio.unrealized_pnl(None), 9.0);
+            portfolio.mark("ES", 101.0).unwrap();
+            assert_eq!(portfolio.unrealized_pnl(None), -3.0);
+            assert!(portfolio.mark("ES", f64::NAN).is_err());
+            assert_eq!(portfolio.exposure(Some("ES")), 303.0);
+        });
+    }
+}
@@ -0,0 +1,490 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::calendar::TradingCalendar;
+use crate::column::{Column, NumericColumn};
+use crate::resample::parse_rule;
+
+/// A data-quality problem at one row
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Issue {
+    /// The timestamp is earlier than the one before
+    OutOfOrder,
+    /// The timestamp repeats the one before
+    Duplicate,
+    /// The row follows an interval longer than `max_gap`
+    Gap,
+    /// The price is NaN
+    MissingPrice,
+    NonPositivePrice,
+    NegativeVolume,
+    /// The price has not changed for `stale_count` rows or more
+    Stale,
+    /// The log return into the row is an outlier by its robust z-score
+    Outlier,
+}
+
+impl Issue {
+    const ALL: [Issue; 8] = [
+        Issue::OutOfOrder,
+        Issue::Duplicate,
+        Issue::Gap,
+        Issue::MissingPrice,
+        Issue::NonPositivePrice,
+        Issue::NegativeVolume,
+        Issue::Stale,
+        Issue::Outlier,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Issue::OutOfOrder => "out_of_order",
+            Issue::Duplicate => "duplicate",
+            Issue::Gap => "gap",
+            Issue::MissingPrice => "missing_price",
+            Issue::NonPositivePrice => "non_positive_price",
+            Issue::NegativeVolume => "negative_volume",
+            Issue::Stale => "stale",
+            Issue::Outlier => "outlier",
+        }
+    }
+}
+
+/// Thresholds of the scan
+struct Limits {
+    max_gap: Option<i64>,
+    stale_count: usize,
+    outlier_threshold: f64,
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let middle = values.len() / 2;
+    let (_, median, _) = values.select_nth_unstable_by(middle, f64::total_cmp);
+    *median
+}
+
+/// Median of the positive intervals between timestamps, 0 without any
+fn typical_interval(timestamps: &[i64]) -> i64 {
+    let mut intervals: Vec<i64> = timestamps.windows(2).map(|w| w[1] - w[0]).filter(|&d| d > 0).collect();
+    if intervals.is_empty() {
+        return 0;
+    }
+    let middle = intervals.len() / 2;
+    *intervals.select_nth_unstable(middle).1
+}
+
+/// Rows of log-return outliers: |r - median| above `threshold` robust standard
+/// deviations (1.4826 MAD). A spike and its reversal only flag the spike.
+fn outliers(prices: &[f64], threshold: f64) -> Vec<usize> {
+    // Returns between consecutive valid prices, at the row they lead into
+    let mut returns = Vec::new();
+    let mut last: Option<f64> = None;
+    for (i, &p) in prices.iter().enumerate() {
+        if p.is_nan() || p <= 0.0 {
+            continue;
+        }
+        if let Some(previous) = last {
+            returns.push((i, (p / previous).ln()));
+        }
+        last = Some(p);
+    }
+    let center = median(&mut returns.iter().map(|r| r.1).collect::<Vec<_>>());
+    let mad = median(&mut returns.iter().map(|r| (r.1 - center).abs()).collect::<Vec<_>>());
+    let scale = 1.4826 * mad;
+    if scale.is_nan() || scale <= 0.0 {
+        return Vec::new();
+    }
+    let z: Vec<f64> = returns.iter().map(|r| (r.1 - center) / scale).collect();
+    let mut rows = Vec::new();
+    let mut k = 0;
+    while k < z.len() {
+        if z[k].abs() > threshold {
+            rows.push(returns[k].0);
+            if k + 1 < z.len() && z[k + 1].abs() > threshold && z[k + 1].signum() != z[k].signum() {
+                k += 1;
+            }
+        }
+        k += 1;
+    }
+    rows
+}
+
+fn scan(timestamps: &[i64], prices: &[f64], volumes: Option<&[f64]>, limits: &Limits) -> Vec<(usize, Issue)> {
+    let mut issues = Vec::new();
+    let max_gap = limits.max_gap.unwrap_or_else(|| 10 * typical_interval(timestamps));
+    for (i, w) in timestamps.windows(2).enumerate() {
+        let interval = w[1] - w[0];
+        if interval < 0 {
+            issues.push((i + 1, Issue::OutOfOrder));
+        } else if interval == 0 {
+            issues.push((i + 1, Issue::Duplicate));
+        } else if max_gap > 0 && interval > max_gap {
+            issues.push((i + 1, Issue::Gap));
+        }
+    }
+    for (i, &p) in prices.iter().enumerate() {
+        if p.is_nan() {
+            issues.push((i, Issue::MissingPrice));
+        } else if p <= 0.0 {
+            issues.push((i, Issue::NonPositivePrice));
+        }
+    }
+    if let Some(volumes) = volumes {
+        issues.extend(volumes.iter().enumerate().filter(|(_, &v)| v < 0.0).map(|(i, _)| (i, Issue::NegativeVolume)));
+    }
+    // Every row of a run of equal prices from its `stale_count`-th on
+    let mut run = 1;
+    for i in 1..prices.len() {
+        run = if prices[i] == prices[i - 1] { run + 1 } else { 1 };
+        if limits.stale_count > 1 && run >= limits.stale_count {
+            issues.push((i, Issue::Stale));
+        }
+    }
+    issues.extend(outliers(prices, limits.outlier_threshold).into_iter().map(|i| (i, Issue::Outlier)));
+    issues.sort_unstable();
+    issues
+}
+
+/// Scan a price series for data-quality problems before it enters the pipeline.
+///
+/// Flags timestamps that go backwards ("out_of_order") or repeat ("duplicate"), rows
+/// after an interval longer than `max_gap` ("gap"; by default 10 times the median
+/// interval, 0 to disable), NaN ("missing_price") and zero or negative prices
+/// ("non_positive_price"), negative `volumes` ("negative_volume"), prices unchanged for
+/// `stale_count` rows or more ("stale", from the `stale_count`-th row of the run on; below
+/// 2 disables it) and
+/// log returns more than `outlier_threshold` robust standard deviations (1.4826 times the
+/// median absolute deviation) from the median ("outlier"; a spike and its immediate
+/// reversal flag only the spike). Returns a dict with the `index` and `issue` name of
+/// every problem, ordered by index, a row appearing once per issue, and the `counts` of
+/// each issue type.
+#[pyfunction]
+#[pyo3(signature = (timestamps, prices, volumes=None, max_gap=None, stale_count=10, outlier_threshold=8.0))]
+pub fn quality_check_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    volumes: Option<Column<'py, f64>>,
+    max_gap: Option<i64>,
+    stale_count: usize,
+    outlier_threshold: f64
+) -> PyResult<Bound<'py, PyDict>> {
+    let timestamps = timestamps.values()?;
+    let prices = prices.values()?;
+    let volumes = volumes.as_ref().map(|c| c.values()).transpose()?;
+    if prices.len() != timestamps.len() || volumes.as_ref().is_some_and(|v| v.len() != timestamps.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps, prices and volumes must have the same length"
+        ));
+    }
+    if max_gap.is_some_and(|g| g < 0) || outlier_threshold.is_nan() || outlier_threshold <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "max_gap must be >= 0 and outlier_threshold > 0"
+        ));
+    }
+    let limits = Limits { max_gap, stale_count, outlier_threshold };
+    let issues = py.allow_threads(|| scan(&timestamps, &prices, volumes.as_deref(), &limits));
+
+    let counts = PyDict::new_bound(py);
+    for issue in Issue::ALL {
+        counts.set_item(issue.name(), issues.iter().filter(|(_, i)| *i == issue).count())?;
+    }
+    let result = PyDict::new_bound(py);
+    result.set_item("index", PyArray1::from_iter_bound(py, issues.iter().map(|&(i, _)| i as i64)))?;
+    result.set_item("issue", issues.iter().map(|(_, i)| i.name()).collect::<Vec<_>>())?;
+    result.set_item("counts", counts)?;
+    Ok(result)
+}
+
+/// How the values of missing bars are imputed
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FillMethod {
+    /// The last value before the gap
+    Ffill,
+    /// Linear in time between the values either side of the gap
+    Interpolate,
+    /// Missing bars are inserted as NaN
+    Nan,
+}
+
+impl FillMethod {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ffill" | "pad" => Ok(FillMethod::Ffill),
+            "interpolate" | "linear" => Ok(FillMethod::Interpolate),
+            "nan" | "none" => Ok(FillMethod::Nan),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'ffill', 'interpolate' or 'nan'", name
+            ))),
+        }
+    }
+}
+
+/// A run of missing bars between two observed rows
+struct Gap {
+    /// The observed row after the gap; the one before is `next - 1`
+    next: usize,
+    stamps: Vec<i64>,
+}
+
+/// Expected bar times within `[first, last]`: every `freq` from the first timestamp, or
+/// with a calendar from each session's open until its close
+fn expected_bars(first: i64, last: i64, freq: i64, calendar: Option<&TradingCalendar>) -> Vec<i64> {
+    let sessions = match calendar {
+        Some(calendar) => calendar.sessions_between(first, last),
+        None => vec![(first, last.saturating_add(1))],
+    };
+    let mut bars = Vec::new();
+    for (open, close) in sessions {
+        // The first bar of the session at or after `first`
+        let skip = if open < first { (first - open + freq - 1) / freq } else { 0 };
+        let mut t = open + skip * freq;
+        while t < close && t <= last {
+            bars.push(t);
+            t += freq;
+        }
+    }
+    bars
+}
+
+/// Runs of expected bars with no observed row in `[bar, bar + freq)`
+fn find_gaps(timestamps: &[i64], expected: &[i64], freq: i64) -> Vec<Gap> {
+    let mut gaps: Vec<Gap> = Vec::new();
+    for &bar in expected {
+        let next = timestamps.partition_point(|&t| t < bar);
+        if next < timestamps.len() && timestamps[next] < bar + freq {
+            continue;
+        }
+        match gaps.last_mut() {
+            Some(gap) if gap.next == next => gap.stamps.push(bar),
+            _ => gaps.push(Gap { next, stamps: vec![bar] }),
+        }
+    }
+    gaps
+}
+
+/// Detect missing bars against an expected frequency and fill them.
+///
+/// `timestamps` are strictly ascending UTC nanoseconds and `values` one value each. Bars
+/// are expected every `expected_freq` (such as "1min" or "1d", units as for
+/// `resample_ohlcv_rust`) from the first timestamp or, with a `calendar`, from each
+/// session's open until its close, so nights, weekends and holidays are not gaps. An
+/// expected bar is missing when no row falls within `expected_freq` of it. Missing bars
+/// are inserted and filled by `method`: "ffill" (the default) with the last value before
+/// the gap, "interpolate" linearly in time between the values either side, or "nan".
+/// Gaps of more than `max_fill` bars are inserted but left NaN. Returns a dict with the
+/// filled `timestamp` and `values` (the array type of `values`), `imputed`, a bool array
+/// marking the inserted rows, and the gap report: the `gap_start` and `gap_end` times of
+/// the first and last missing bar of each gap, its `gap_size` in bars and whether it was
+/// `gap_filled`.
+#[pyfunction]
+#[pyo3(signature = (timestamps, values, expected_freq, method="ffill", max_fill=None, calendar=None))]
+pub fn fill_gaps_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    values: Column<'py, f64>,
+    expected_freq: &str,
+    method: &str,
+    max_fill: Option<usize>,
+    calendar: Option<PyRef<'py, TradingCalendar>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let freq = parse_rule(expected_freq)?;
+    let method = FillMethod::parse(method)?;
+    let kind = values.kind();
+    let (timestamps, values) = (timestamps.values()?, values.values()?);
+    if timestamps.len() != values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps and values must have the same length"
+        ));
+    }
+    if timestamps.windows(2).any(|w| w[1] <= w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Timestamps must be strictly ascending"
+        ));
+    }
+    let calendar = calendar.map(|c| c.clone());
+
+    let (gaps, filled_stamps, filled_values, imputed) = py.allow_threads(|| {
+        let gaps = match (timestamps.first(), timestamps.last()) {
+            (Some(&first), Some(&last)) => {
+                find_gaps(&timestamps, &expected_bars(first, last, freq, calendar.as_ref()), freq)
+            }
+            _ => Vec::new(),
+        };
+        let inserted: usize = gaps.iter().map(|g| g.stamps.len()).sum();
+        let mut stamps = Vec::with_capacity(timestamps.len() + inserted);
+        let mut filled = Vec::with_capacity(timestamps.len() + inserted);
+        let mut imputed = Vec::with_capacity(timestamps.len() + inserted);
+        let mut row = 0;
+        for gap in &gaps {
+            stamps.extend_from_slice(&timestamps[row..gap.next]);
+            filled.extend_from_slice(&values[row..gap.next]);
+            imputed.resize(stamps.len(), false);
+            row = gap.next;
+            let (before, after) = (gap.next - 1, gap.next);
+            let fill = max_fill.is_none_or(|m| gap.stamps.len() <= m);
+            for &t in &gap.stamps {
+                let value = match method {
+                    _ if !fill => f64::NAN,
+                    FillMethod::Ffill => values[before],
+                    FillMethod::Interpolate => {
+                        let (t0, t1) = (timestamps[before], timestamps[after]);
+                        let w = (t - t0) as f64 / (t1 - t0) as f64;
+                        values[before] + w * (values[after] - values[before])
+                    }
+                    FillMethod::Nan => f64::NAN,
+                };
+                stamps.push(t);
+                filled.push(value);
+                imputed.push(true);
+            }
+        }
+        stamps.extend_from_slice(&timestamps[row..]);
+        filled.extend_from_slice(&values[row..]);
+        imputed.resize(stamps.len(), false);
+        (gaps, stamps, filled, imputed)
+    });
+
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_vec_bound(py, filled_stamps))?;
+    result.set_item("values", kind.wrap(py, filled_values)?)?;
+    result.set_item("imputed", PyArray1::from_vec_bound(py, imputed))?;
+    result.set_item("gap_start", PyArray1::from_iter_bound(py, gaps.iter().map(|g| g.stamps[0])))?;
+    result.set_item("gap_end", PyArray1::from_iter_bound(py, gaps.iter().map(|g| g.stamps[g.stamps.len() - 1])))?;
+    result.set_item("gap_size", PyArray1::from_iter_bound(py, gaps.iter().map(|g| g.stamps.len() as i64)))?;
+    let filled = gaps.iter().map(|g| method != FillMethod::Nan && max_fill.is_none_or(|m| g.stamps.len() <= m));
+    result.set_item("gap_filled", PyArray1::from_iter_bound(py, filled))?;
+    Ok(result)
+}
+
+/// What `repair` did to a tick stream
+#[derive(Default)]
+struct Repair {
+    /// Original row of each kept tick, in time order
+    kept: Vec<usize>,
+    duplicates: usize,
+    /// Kept ticks that arrived after a later one and were moved back into place
+    reordered: usize,
+    /// Ticks earlier than one already seen by more than the tolerance
+    dropped: usize,
+}
+
+/// Stable-sort ticks into time order, dropping those more than `tolerance` older than a
+/// tick already seen, then drop repeats of the same timestamp, price and size
+fn repair(timestamps: &[i64], prices: &[f64], sizes: &[f64], tolerance: Option<i64>) -> Repair {
+    let mut result = Repair::default();
+    let mut latest = i64::MIN;
+    let mut kept: Vec<usize> = Vec::with_capacity(timestamps.len());
+    for (i, &t) in timestamps.iter().enumerate() {
+        if t < latest {
+            if tolerance.is_some_and(|tol| latest - t > tol) {
+                result.dropped += 1;
+                continue;
+            }
+            result.reordered += 1;
+        }
+        latest = latest.max(t);
+        kept.push(i);
+    }
+    kept.sort_by_key(|&i| timestamps[i]);
+
+    let mut start = 0;
+    for k in 0..kept.len() {
+        let i = kept[k];
+        if k > 0 && timestamps[kept[k - 1]] != timestamps[i] {
+            start = result.kept.len();
+        }
+        // Compare against the ticks of the same timestamp kept so far; NaN matches NaN
+        let same = |a: f64, b: f64| a == b || (a.is_nan() && b.is_nan());
+        if result.kept[start..].iter().any(|&j| same(prices[j], prices[i]) && same(sizes[j], sizes[i])) {
+            result.duplicates += 1;
+        } else {
+            result.kept.push(i);
+        }
+    }
+    result
+}
+
+/// Clean a raw tick stream: drop exact duplicates, put out-of-order ticks back in time
+/// order and flag crossed or locked quotes.
+///
+/// Ticks arriving earlier than one already seen are moved into place by a stable sort,
+/// keeping the arrival order of ticks with equal timestamps; with `tolerance` (in
+/// timestamp units), ticks older than the latest seen by more than that are dropped as
+/// bad instead. Ticks repeating the timestamp, price and size of another then count as
+/// duplicates and only the first is kept. With quote columns `bids` and `asks`, each kept
+/// tick is flagged `crossed` when the bid is above the ask and `locked` when they are
+/// equal. Returns a dict with the cleaned `timestamp`, `price`, `size` (and `bid` and
+/// `ask`), `index`, the original row of each kept tick, the `crossed` and `locked` flags
+/// and `stats`: counts of the `input` and `output` ticks, `duplicates`, `reordered` and
+/// `dropped_out_of_order` ticks, and `crossed` and `locked` quotes.
+#[pyfunction]
+#[pyo3(signature = (timestamps, prices, sizes, tolerance=None, bids=None, asks=None))]
+pub fn repair_ticks_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    sizes: NumericColumn<'py>,
+    tolerance: Option<i64>,
+    bids: Option<Column<'py, f64>>,
+    asks: Option<Column<'py, f64>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let (timestamps, prices) = (timestamps.values()?, prices.values()?);
+    let sizes: Vec<f64> = match &sizes {
+        NumericColumn::Float(column) => column.values()?.into_owned(),
+        NumericColumn::Int(column) => column.values()?.iter().map(|&v| v as f64).collect(),
+    };
+    let quotes = match (&bids, &asks) {
+        (Some(bids), Some(asks)) => Some((bids.values()?, asks.values()?)),
+        (None, None) => None,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "bids and asks must be given together"
+            ))
+        }
+    };
+    let n = timestamps.len();
+    if prices.len() != n || sizes.len() != n || quotes.as_ref().is_some_and(|(b, a)| b.len() != n || a.len() != n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps, prices, sizes, bids and asks must have the same length"
+        ));
+    }
+    if tolerance.is_some_and(|t| t < 0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tolerance must be >= 0"
+        ));
+    }
+
+    let repaired = py.allow_threads(|| repair(&timestamps, &prices, &sizes, tolerance));
+    let kept = &repaired.kept;
+    let (crossed, locked): (Vec<bool>, Vec<bool>) = match &quotes {
+        Some((bids, asks)) => kept.iter().map(|&i| (bids[i] > asks[i], bids[i] == asks[i])).unzip(),
+        None => (vec![false; kept.len()], vec![false; kept.len()]),
+    };
+
+    let stats = PyDict::new_bound(py);
+    stats.set_item("input", n)?;
+    stats.set_item("output", kept.len())?;
+    stats.set_item("duplicates", repaired.duplicates)?;
+    stats.set_item("reordered", repaired.reordered)?;
+    stats.set_item("dropped_out_of_order", repaired.dropped)?;
+    stats.set_item("crossed", crossed.iter().filter(|&&c| c).count())?;
+    stats.set_item("locked", locked.iter().filter(|&&l| l).count())?;
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_iter_bound(py, kept.iter().map(|&i| timestamps[i])))?;
+    result.set_item("price", PyArray1::from_iter_bound(py, kept.iter().map(|&i| prices[i])))?;
+    result.set_item("size", PyArray1::from_iter_bound(py, kept.iter().map(|&i| sizes[i])))?;
+    if let Some((bids, asks)) = &quotes {
+        result.set_item("bid", PyArray1::from_iter_bound(py, kept.iter().map(|&i| bids[i])))?;
+        result.set_item("ask", PyArray1::from_iter_bound(py, kept.iter().map(|&i| asks[i])))?;
+    }
+    result.set_item("index", PyArray1::from_iter_bound(py, kept.iter().map(|&i| i as i64)))?;
+    result.set_item("crossed", PyArray1::from_vec_bound(py, crossed))?;
+    result.set_item("locked", PyArray1::from_vec_bound(py, locked))?;
+    result.set_item("stats", stats)?;
+    Ok(result)
+}
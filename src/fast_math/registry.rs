@@ -0,0 +1,56 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple, PyType};
+
+use crate::bars::BarBuilder;
+use crate::chunked::ChunkedProcessor;
+use crate::streaming::{
+    RollingWindow, StreamingATR, StreamingCorrelation, StreamingCovariance, StreamingDrawdown, StreamingEMA,
+    StreamingOutlierDetector, StreamingRSI, StreamingSMA, StreamingVWAP,
+};
+
+/// Factory for one registered indicator class
+type Factory = for<'py> fn(Python<'py>) -> Bound<'py, PyType>;
+
+/// Indicator names (and aliases) accepted by `create_indicator`
+const REGISTRY: &[(&str, Factory)] = &[
+    ("sma", |py| py.get_type_bound::<StreamingSMA>()),
+    ("moving_average", |py| py.get_type_bound::<StreamingSMA>()),
+    ("ema", |py| py.get_type_bound::<StreamingEMA>()),
+    ("rsi", |py| py.get_type_bound::<StreamingRSI>()),
+    ("atr", |py| py.get_type_bound::<StreamingATR>()),
+    ("vwap", |py| py.get_type_bound::<StreamingVWAP>()),
+    ("covariance", |py| py.get_type_bound::<StreamingCovariance>()),
+    ("correlation", |py| py.get_type_bound::<StreamingCorrelation>()),
+    ("drawdown", |py| py.get_type_bound::<StreamingDrawdown>()),
+    ("outlier", |py| py.get_type_bound::<StreamingOutlierDetector>()),
+    ("rolling_window", |py| py.get_type_bound::<RollingWindow>()),
+    ("bar_builder", |py| py.get_type_bound::<BarBuilder>()),
+    ("chunked", |py| py.get_type_bound::<ChunkedProcessor>()),
+];
+
+/// Construct a native indicator by name, e.g. `create_indicator("rsi", period=14)`.
+///
+/// Names are case-insensitive; remaining arguments are passed to the indicator's
+/// constructor, so strategies defined in config files need no Python-side dispatch table.
+#[pyfunction]
+#[pyo3(signature = (name, *args, **kwargs))]
+pub fn create_indicator<'py>(
+    py: Python<'py>,
+    name: &str,
+    args: &Bound<'py, PyTuple>,
+    kwargs: Option<&Bound<'py, PyDict>>
+) -> PyResult<Bound<'py, PyAny>> {
+    let key = name.to_ascii_lowercase();
+    match REGISTRY.iter().find(|(n, _)| *n == key) {
+        Some((_, factory)) => factory(py).call(args, kwargs),
+        None => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown indicator '{}', expected one of: {}", name, available_indicators().join(", ")
+        ))),
+    }
+}
+
+/// Names accepted by `create_indicator`
+#[pyfunction]
+pub fn available_indicators() -> Vec<&'static str> {
+    REGISTRY.iter().map(|(name, _)| *name).collect()
+}
@@ -0,0 +1,428 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+
+use crate::column::{self, Column, Series};
+use crate::linalg;
+
+/// Coefficients, standard errors, t-statistics and R² of a fit
+struct Summary {
+    coefficients: Vec<f64>,
+    std_errors: Vec<f64>,
+    t_stats: Vec<f64>,
+    r_squared: f64,
+}
+
+impl Summary {
+    fn nan(k: usize) -> Self {
+        Summary { coefficients: vec![f64::NAN; k], std_errors: vec![f64::NAN; k], t_stats: vec![f64::NAN; k], r_squared: f64::NAN }
+    }
+
+    fn new(coefficients: Vec<f64>, std_errors: Vec<f64>, r_squared: f64) -> Self {
+        let t_stats = coefficients.iter().zip(&std_errors).map(|(b, se)| b / se).collect();
+        Summary { coefficients, std_errors, t_stats, r_squared }
+    }
+
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        (
+            PyArray1::from_vec_bound(py, self.coefficients),
+            PyArray1::from_vec_bound(py, self.std_errors),
+            PyArray1::from_vec_bound(py, self.t_stats),
+            self.r_squared,
+        )
+            .into_py(py)
+    }
+}
+
+/// Total sum of squares: about the mean with an intercept, about zero without (as
+/// statsmodels does for models without a constant)
+fn total_ss(y: &[f64], intercept: bool) -> f64 {
+    let centre = if intercept { y.iter().sum::<f64>() / y.len() as f64 } else { 0.0 };
+    y.iter().map(|v| (v - centre) * (v - centre)).sum()
+}
+
+fn check_rows(x: &ArrayView2<'_, f64>, y: &[f64]) -> PyResult<()> {
+    if x.nrows() != y.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "x_2d must have one row per value of y"
+        ));
+    }
+    Ok(())
+}
+
+fn ols(x: ArrayView2<'_, f64>, y: &[f64], intercept: bool) -> Summary {
+    let (n, features) = x.dim();
+    let k = features + intercept as usize;
+    let mut columns: Vec<Vec<f64>> = x.columns().into_iter().map(|c| c.to_vec()).collect();
+    if intercept {
+        columns.insert(0, vec![1.0; n]);
+    }
+    let Some(fit) = linalg::ols(columns, y.to_vec()) else {
+        return Summary::nan(k);
+    };
+    let sigma2 = fit.ssr / (n - k) as f64;
+    let std_errors = fit.inverse_diag.iter().map(|d| (sigma2 * d).sqrt()).collect();
+    Summary::new(fit.params, std_errors, 1.0 - fit.ssr / total_ss(y, intercept))
+}
+
+fn ridge(x: ArrayView2<'_, f64>, y: &[f64], lambda: f64, intercept: bool) -> Summary {
+    let (n, features) = x.dim();
+    let k = features + intercept as usize;
+    // The intercept is not penalised: centre the features and target and recover it after
+    let (x_mean, y_mean) = if intercept {
+        (x.mean_axis(Axis(0)).unwrap_or_else(|| Array1::zeros(features)), y.iter().sum::<f64>() / n as f64)
+    } else {
+        (Array1::zeros(features), 0.0)
+    };
+    let xc = &x - &x_mean;
+    let yc = Array1::from_iter(y.iter().map(|v| v - y_mean));
+
+    let gram = xc.t().dot(&xc);
+    let penalised = &gram + &(Array2::<f64>::eye(features) * lambda);
+    let Some(l) = linalg::cholesky(&penalised) else {
+        return Summary::nan(k);
+    };
+    // (X'X + λI)^-1 from the Cholesky factor
+    let inverse = linalg::solve_upper(&l, &linalg::solve_lower(&l, &Array2::eye(features)));
+    let beta = inverse.dot(&xc.t().dot(&yc));
+    let residuals = &yc - &xc.dot(&beta);
+    let ssr = residuals.dot(&residuals);
+
+    // Effective degrees of freedom trace(X (X'X + λI)^-1 X') = trace((X'X + λI)^-1 X'X)
+    let df = (0..features).map(|i| inverse.row(i).dot(&gram.column(i))).sum::<f64>() + (k - features) as f64;
+    let sigma2 = ssr / (n as f64 - df);
+    let covariance = inverse.dot(&gram).dot(&inverse) * sigma2;
+
+    let mut coefficients = beta.to_vec();
+    let mut std_errors: Vec<f64> = covariance.diag().iter().map(|v| v.sqrt()).collect();
+    if intercept {
+        coefficients.insert(0, y_mean - x_mean.dot(&beta));
+        std_errors.insert(0, (sigma2 / n as f64 + x_mean.dot(&covariance.dot(&x_mean))).sqrt());
+    }
+    Summary::new(coefficients, std_errors, 1.0 - ssr / total_ss(y, intercept))
+}
+
+/// Ordinary least squares of `y` on the columns of `x_2d` (one row per observation).
+///
+/// Solved by Householder QR, which stays accurate for badly scaled or nearly collinear
+/// features. With `intercept=True` (the default) a constant is added, and its coefficient
+/// comes first. Returns `(coefficients, std_errors, t_stats, r_squared)` with classical
+/// (homoskedastic) standard errors from `ssr / (n - k)`; R² is centred with an intercept,
+/// uncentred without, as in statsmodels. Rank-deficient designs, NaN inputs or no more
+/// observations than coefficients give NaN.
+#[pyfunction]
+#[pyo3(signature = (x_2d, y, intercept=true))]
+pub fn ols_rust<'py>(
+    py: Python<'py>,
+    x_2d: PyReadonlyArray2<'py, f64>,
+    y: Column<'py, f64>,
+    intercept: bool
+) -> PyResult<PyObject> {
+    let (x, y) = (x_2d.as_array(), y.values()?);
+    check_rows(&x, &y)?;
+    Ok(py.allow_threads(|| ols(x, &y, intercept)).into_py(py))
+}
+
+/// Ridge regression of `y` on the columns of `x_2d`, minimising `||y - X b||² + lambda_ *
+/// ||b||²`.
+///
+/// Solved through the Cholesky factor of `X'X + lambda_ * I`; the intercept (added by
+/// default, coefficient first) is not penalised. Returns `(coefficients, std_errors,
+/// t_stats, r_squared)` like `ols_rust`, the standard errors from the sandwich
+/// `sigma2 (X'X + λI)^-1 X'X (X'X + λI)^-1` with `sigma2` taken over the effective degrees
+/// of freedom `trace(X (X'X + λI)^-1 X')`. `lambda_=0` reduces to OLS (solved less
+/// accurately than `ols_rust`). NaN inputs or a singular system give NaN.
+#[pyfunction]
+#[pyo3(signature = (x_2d, y, lambda_=1.0, intercept=true))]
+pub fn ridge_rust<'py>(
+    py: Python<'py>,
+    x_2d: PyReadonlyArray2<'py, f64>,
+    y: Column<'py, f64>,
+    lambda_: f64,
+    intercept: bool
+) -> PyResult<PyObject> {
+    if lambda_.is_nan() || lambda_ < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "lambda_ must be >= 0"
+        ));
+    }
+    let (x, y) = (x_2d.as_array(), y.values()?);
+    check_rows(&x, &y)?;
+    Ok(py.allow_threads(|| ridge(x, &y, lambda_, intercept)).into_py(py))
+}
+
+/// Constraint on the weights of `factor_regression_rust`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Constraint {
+    None,
+    SumToOne,
+    NonNegative,
+    /// Sum to one and non-negative: Sharpe's returns-based style analysis
+    Style,
+}
+
+impl Constraint {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Ok(Constraint::None),
+            "sum_to_one" => Ok(Constraint::SumToOne),
+            "non_negative" => Ok(Constraint::NonNegative),
+            "style" => Ok(Constraint::Style),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown constraint '{}', expected 'none', 'sum_to_one', 'non_negative' or 'style'", name
+            ))),
+        }
+    }
+
+    fn sums_to_one(self) -> bool {
+        matches!(self, Constraint::SumToOne | Constraint::Style)
+    }
+
+    fn non_negative(self) -> bool {
+        matches!(self, Constraint::NonNegative | Constraint::Style)
+    }
+}
+
+/// Minimiser of `w'Gw / 2 - c'w` over the weights in `free` (the others held at 0),
+/// summing to one when `budget`; also returns the budget's multiplier
+fn equality_qp(gram: &Array2<f64>, c: &[f64], free: &[usize], budget: bool) -> Option<(Vec<f64>, f64)> {
+    let m = free.len() + budget as usize;
+    let mut a = vec![vec![0.0; m]; m];
+    let mut b = vec![0.0; m];
+    for (r, &i) in free.iter().enumerate() {
+        for (s, &j) in free.iter().enumerate() {
+            a[r][s] = gram[[i, j]];
+        }
+        b[r] = c[i];
+        if budget {
+            a[r][free.len()] = -1.0;
+            a[free.len()][r] = 1.0;
+        }
+    }
+    if budget {
+        b[free.len()] = 1.0;
+    }
+    let solution = linalg::solve(a, b)?;
+    let mut weights = vec![0.0; gram.nrows()];
+    for (r, &i) in free.iter().enumerate() {
+        weights[i] = solution[r];
+    }
+    Some((weights, if budget { solution[free.len()] } else { 0.0 }))
+}
+
+/// Least squares `min ||y - X w||²` under `constraint`, given `X'X` and `X'y`. The sign
+/// constraints are handled by a primal active-set method, starting from equal weights
+/// (with the budget) or all weights at 0.
+fn constrained_ls(gram: &Array2<f64>, c: &[f64], constraint: Constraint) -> Option<Vec<f64>> {
+    let k = c.len();
+    let budget = constraint.sums_to_one();
+    if !constraint.non_negative() {
+        return equality_qp(gram, c, &(0..k).collect::<Vec<_>>(), budget).map(|(w, _)| w);
+    }
+    let mut weights = vec![if budget { 1.0 / k as f64 } else { 0.0 }; k];
+    let mut fixed = vec![!budget; k];
+    let tolerance = 1e-12 * c.iter().fold(1.0f64, |m, v| m.max(v.abs()));
+    // Each working set is visited at most once, but cap the iterations against cycling
+    for _ in 0..10 * k + 10 {
+        let free: Vec<usize> = (0..k).filter(|&i| !fixed[i]).collect();
+        let (target, multiplier) = equality_qp(gram, c, &free, budget)?;
+        // Step towards the working set's optimum, stopping at the first weight to reach 0
+        let mut step = 1.0;
+        let mut blocking = None;
+        for &i in &free {
+            if target[i] < weights[i] && target[i] < 0.0 {
+                let t = weights[i] / (weights[i] - target[i]);
+                if t < step {
+                    step = t;
+                    blocking = Some(i);
+                }
+            }
+        }
+        for i in 0..k {
+            weights[i] += step * (target[i] - weights[i]);
+        }
+        if let Some(i) = blocking {
+            weights[i] = 0.0;
+            fixed[i] = true;
+            continue;
+        }
+        // At the working set's optimum: release the weight whose bound binds the wrong way
+        let release = (0..k)
+            .filter(|&i| fixed[i])
+            .map(|i| {
+                let gradient: f64 = (0..k).map(|j| gram[[i, j]] * weights[j]).sum::<f64>() - c[i];
+                (i, gradient - multiplier)
+            })
+            .filter(|&(_, lagrange)| lagrange < -tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match release {
+            Some((i, _)) => fixed[i] = false,
+            None => return Some(weights),
+        }
+    }
+    Some(weights)
+}
+
+/// Factor weights of one asset and the fit of the mimicking portfolio
+struct StyleFit {
+    weights: Vec<f64>,
+    /// Asset minus factor-portfolio return, NaN on the rows left out
+    residuals: Vec<f64>,
+    r_squared: f64,
+    alpha: f64,
+    tracking_error: f64,
+}
+
+/// Fit over the rows where the asset and every factor are known
+fn style_fit(y: &[f64], factors: ArrayView2<'_, f64>, constraint: Constraint) -> StyleFit {
+    let k = factors.ncols();
+    let rows: Vec<usize> = (0..y.len()).filter(|&t| !y[t].is_nan() && factors.row(t).iter().all(|v| !v.is_nan())).collect();
+    let x = factors.select(Axis(0), &rows);
+    let target = Array1::from_iter(rows.iter().map(|&t| y[t]));
+    let weights = if rows.len() > k { constrained_ls(&x.t().dot(&x), &x.t().dot(&target).to_vec(), constraint) } else { None };
+    let Some(weights) = weights.filter(|w| w.iter().all(|v| v.is_finite())) else {
+        return StyleFit {
+            weights: vec![f64::NAN; k],
+            residuals: vec![f64::NAN; y.len()],
+            r_squared: f64::NAN,
+            alpha: f64::NAN,
+            tracking_error: f64::NAN,
+        };
+    };
+    let errors = &target - &x.dot(&Array1::from_vec(weights.clone()));
+    let n = rows.len() as f64;
+    let alpha = errors.sum() / n;
+    let error_var = errors.iter().map(|e| (e - alpha) * (e - alpha)).sum::<f64>() / (n - 1.0);
+    let mean = target.sum() / n;
+    let target_var = target.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (n - 1.0);
+    let mut residuals = vec![f64::NAN; y.len()];
+    for (&t, &e) in rows.iter().zip(errors.iter()) {
+        residuals[t] = e;
+    }
+    StyleFit { weights, residuals, r_squared: 1.0 - error_var / target_var, alpha, tracking_error: error_var.sqrt() }
+}
+
+/// Returns-based style analysis: regress asset returns on factor returns under weight
+/// constraints.
+///
+/// Finds the weights `w` minimising `||asset_returns - factor_returns_2d @ w||²`, where
+/// `factor_returns_2d` holds one row per period and one column per factor (such as style
+/// or asset-class index returns), without an intercept. `constraint` is "style" (the
+/// default; weights sum to one and are non-negative, Sharpe's (1992) style analysis, the
+/// asset read as a long-only mix of the factors), "sum_to_one", "non_negative" or "none"
+/// (plain least squares); the sign constraints are solved exactly by an active-set
+/// method. Periods where the asset or a factor is NaN are left out. Returns a dict with
+/// the factor `weights`, the `residuals` (the selection return, NaN on periods left out),
+/// `r_squared` (`1 - var(residuals) / var(asset_returns)`, the share of the variance the
+/// style explains), `alpha` (the mean residual) and `tracking_error` (the residuals'
+/// standard deviation). A 2D `asset_returns` holds one asset or book per row and is
+/// fitted row by row in parallel, giving a row of weights and residuals per asset and
+/// arrays of the statistics. Too few periods or collinear factors give NaN.
+#[pyfunction]
+#[pyo3(signature = (asset_returns, factor_returns_2d, constraint="style"))]
+pub fn factor_regression_rust<'py>(
+    py: Python<'py>,
+    asset_returns: Series<'py, f64>,
+    factor_returns_2d: PyReadonlyArray2<'py, f64>,
+    constraint: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let constraint = Constraint::parse(constraint)?;
+    let factors = factor_returns_2d.as_array();
+    if factors.ncols() == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "factor_returns_2d must have at least one column"
+        ));
+    }
+    let result = PyDict::new_bound(py);
+    match asset_returns {
+        Series::Single(column) => {
+            let y = column.values()?;
+            check_rows(&factors, &y)?;
+            let fit = py.allow_threads(|| style_fit(&y, factors, constraint));
+            result.set_item("weights", PyArray1::from_vec_bound(py, fit.weights))?;
+            result.set_item("residuals", PyArray1::from_vec_bound(py, fit.residuals))?;
+            result.set_item("r_squared", fit.r_squared)?;
+            result.set_item("alpha", fit.alpha)?;
+            result.set_item("tracking_error", fit.tracking_error)?;
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            if batch.ncols() != factors.nrows() {
+                return Err(crate::errors::LengthMismatchError::new_err(
+                    "factor_returns_2d must have one row per column of asset_returns"
+                ));
+            }
+            let fits = py.allow_threads(|| column::map_rows(batch, |_, row| Ok(style_fit(row, factors, constraint))))?;
+            let weights = Array2::from_shape_fn((fits.len(), factors.ncols()), |(a, j)| fits[a].weights[j]);
+            let residuals = Array2::from_shape_fn((fits.len(), batch.ncols()), |(a, t)| fits[a].residuals[t]);
+            result.set_item("weights", PyArray2::from_owned_array_bound(py, weights))?;
+            result.set_item("residuals", PyArray2::from_owned_array_bound(py, residuals))?;
+            result.set_item("r_squared", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.r_squared)))?;
+            result.set_item("alpha", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.alpha)))?;
+            result.set_item("tracking_error", PyArray1::from_iter_bound(py, fits.iter().map(|f| f.tracking_error)))?;
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::array;
+
+    #[test]
+    fn ols_recovers_an_exact_fit_with_an_intercept() {
+        let x = array![[1.0, 0.5], [2.0, -1.0], [3.0, 0.0], [4.0, 2.0], [5.0, 1.5]];
+        let y: Vec<f64> = x.rows().into_iter().map(|r| 1.0 + 2.0 * r[0] - 3.0 * r[1]).collect();
+        let fit = ols(x.view(), &y, true);
+        for (b, expected) in fit.coefficients.iter().zip([1.0, 2.0, -3.0]) {
+            assert!((b - expected).abs() < 1e-10);
+        }
+        assert!((fit.r_squared - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ols_standard_errors_match_the_simple_regression_formula() {
+        let x = array![[0.0], [1.0], [2.0], [3.0], [4.0], [5.0]];
+        let y = [0.1, 1.2, 1.8, 3.3, 3.9, 5.2];
+        let fit = ols(x.view(), &y, true);
+        let n = y.len() as f64;
+        let residuals: Vec<f64> = (0..6).map(|i| y[i] - fit.coefficients[0] - fit.coefficients[1] * i as f64).collect();
+        let sigma2 = residuals.iter().map(|r| r * r).sum::<f64>() / (n - 2.0);
+        // Sxx about the mean 2.5
+        let sxx: f64 = (0..6).map(|i| (i as f64 - 2.5).powi(2)).sum();
+        assert!((fit.std_errors[1] - (sigma2 / sxx).sqrt()).abs() < 1e-12);
+        assert!((fit.t_stats[1] - fit.coefficients[1] / fit.std_errors[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ols_rank_deficient_designs_give_nan() {
+        let x = array![[1.0, 2.0], [2.0, 4.0], [3.0, 6.0], [4.0, 8.0]];
+        let fit = ols(x.view(), &[1.0, 2.0, 3.0, 4.0], false);
+        assert!(fit.coefficients.iter().all(|b| b.is_nan()));
+    }
+
+    #[test]
+    fn ridge_at_zero_lambda_matches_ols_and_shrinks_as_lambda_grows() {
+        let x = array![[1.0, 0.5], [2.0, -1.0], [3.0, 0.0], [4.0, 2.0], [5.0, 1.5], [6.0, -0.5]];
+        let y = [3.1, 8.2, 6.9, 3.0, 6.4, 14.1];
+        let exact = ols(x.view(), &y, true);
+        let unpenalised = ridge(x.view(), &y, 0.0, true);
+        for (a, b) in exact.coefficients.iter().zip(&unpenalised.coefficients) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        let norm = |s: &Summary| s.coefficients[1..].iter().map(|b| b * b).sum::<f64>();
+        let mut previous = norm(&unpenalised);
+        for lambda in [0.1, 1.0, 10.0, 100.0] {
+            let fit = ridge(x.view(), &y, lambda, true);
+            assert!(norm(&fit) < previous);
+            previous = norm(&fit);
+        }
+        // The unpenalised intercept tends to the mean of y
+        let heavy = ridge(x.view(), &y, 1e12, true);
+        assert!((heavy.coefficients[0] - y.iter().sum::<f64>() / 6.0).abs() < 1e-6);
+    }
+}
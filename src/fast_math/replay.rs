@@ -0,0 +1,496 @@
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use numpy::PyArray1;
+
+use crate::column::{Column, NumericColumn};
+use crate::strategy::{Bar, Fill, NativeStrategy, Order, Tick};
+use crate::telemetry;
+
+/// What a stream records, and where the columns native strategies need are
+enum Layout {
+    Tick { price: usize, size: Option<usize> },
+    Bar { open: usize, high: usize, low: usize, close: usize, volume: Option<usize> },
+    Fill { quantity: usize, price: usize },
+    /// Book updates and anything else, only seen by Python handlers
+    Other(&'static str),
+}
+
+impl Layout {
+    fn parse(kind: &str, stream: &str, names: &[String]) -> PyResult<Self> {
+        let find = |name: &str| names.iter().position(|n| n == name);
+        let required = |name: &str| {
+            find(name).ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!(
+                    "{} stream '{}' needs a '{}' column", kind, stream, name
+                ))
+            })
+        };
+        match kind.to_ascii_lowercase().as_str() {
+            "tick" | "trade" => Ok(Layout::Tick { price: required("price")?, size: find("size") }),
+            "bar" => Ok(Layout::Bar {
+                open: required("open")?,
+                high: required("high")?,
+                low: required("low")?,
+                close: required("close")?,
+                volume: find("volume"),
+            }),
+            "fill" => Ok(Layout::Fill { quantity: required("quantity")?, price: required("price")? }),
+            "book" => Ok(Layout::Other("book")),
+            "event" => Ok(Layout::Other("event")),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown stream kind '{}', expected 'tick', 'bar', 'fill', 'book' or 'event'", kind
+            ))),
+        }
+    }
+}
+
+/// One recorded stream: its timestamps (ascending), value columns and replay position
+struct Stream {
+    name: String,
+    layout: Layout,
+    timestamps: Vec<i64>,
+    names: Vec<String>,
+    columns: Vec<Vec<f64>>,
+    cursor: usize,
+}
+
+impl Stream {
+    fn value(&self, column: usize, row: usize) -> f64 {
+        self.columns[column][row]
+    }
+
+    fn optional(&self, column: Option<usize>, row: usize) -> f64 {
+        column.map_or(f64::NAN, |j| self.value(j, row))
+    }
+}
+
+/// What a subscription drives
+enum Handler {
+    /// Fed natively through `on_tick`, `on_bar` or `on_fill` by the stream's kind
+    Native(Py<NativeStrategy>),
+    /// Any object with an `update` method, called with `fields` of each event
+    Indicator { target: PyObject, fields: Vec<usize>, outputs: Vec<PyObject> },
+    /// A callable taking `(stream, timestamp, row)`, `row` a dict of the event's values
+    Callback(PyObject),
+}
+
+struct Subscription {
+    stream: usize,
+    handler: Handler,
+}
+
+/// Replay recorded streams (ticks, bars, book updates, fills) merged in timestamp order
+/// through registered indicators and strategies, for deterministic rehearsals of the
+/// live stack.
+///
+/// Events with equal timestamps are delivered in the order their streams were added,
+/// and each event reaches its stream's subscribers in subscription order, so every replay
+/// of the same recording is identical. `speed=None` replays as fast as possible; a
+/// positive `speed` paces events on the wall clock at that multiple of real time
+/// (1.0 for real time), reading timestamps as nanoseconds. `on_order`, when given, is
+/// called with `(subscription, timestamp, quantity, limit_price)` for each order a native
+/// strategy asks for, so it can be routed as it would be live.
+///
+/// If a handler raises, its event still counts as replayed (subscribers before it have
+/// already seen it) and the exception propagates from `run`; the next `run` resumes after
+/// that event and also returns the orders gathered before the error.
+#[pyclass(module = "fast_math")]
+pub struct Replayer {
+    streams: Vec<Stream>,
+    subscriptions: Vec<Subscription>,
+    speed: Option<f64>,
+    on_order: Option<PyObject>,
+    now: Option<i64>,
+    /// Native orders not yet returned by `run`, as (subscription, timestamp, order)
+    orders: Vec<(usize, i64, Order)>,
+}
+
+fn check_speed(speed: Option<f64>) -> PyResult<()> {
+    if speed.is_some_and(|s| !s.is_finite() || s <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "speed must be None (as fast as possible) or a finite value > 0"
+        ));
+    }
+    Ok(())
+}
+
+impl Replayer {
+    /// The stream holding the next event: earliest timestamp, earlier-added streams first
+    fn next_stream(&self, until: Option<i64>) -> Option<usize> {
+        // A handful of streams, so a scan beats maintaining a heap across runs
+        let mut best: Option<(i64, usize)> = None;
+        for (i, stream) in self.streams.iter().enumerate() {
+            if let Some(&t) = stream.timestamps.get(stream.cursor) {
+                if until.is_none_or(|u| t <= u) && best.is_none_or(|(b, _)| t < b) {
+                    best = Some((t, i));
+                }
+            }
+        }
+        best.map(|(_, i)| i)
+    }
+
+    /// Replay events up to `until`, at most `max_events`, pacing them by `speed`; returns
+    /// how many were replayed
+    fn advance(&mut self, py: Python<'_>, until: Option<i64>, max_events: Option<usize>) -> PyResult<usize> {
+        let mut events = 0;
+        let mut anchor: Option<(Instant, i64)> = None;
+        while max_events.is_none_or(|m| events < m) {
+            let Some(s) = self.next_stream(until) else {
+                break;
+            };
+            let row = self.streams[s].cursor;
+            let timestamp = self.streams[s].timestamps[row];
+            if let Some(speed) = self.speed {
+                let (start, origin) = *anchor.get_or_insert((Instant::now(), timestamp));
+                let due = Duration::from_nanos(((timestamp - origin) as f64 / speed) as u64);
+                // Sleep in slices so Ctrl-C still interrupts long gaps
+                while let Some(wait) = due.checked_sub(start.elapsed()) {
+                    py.allow_threads(|| std::thread::sleep(wait.min(Duration::from_millis(100))));
+                    py.check_signals()?;
+                }
+            }
+            if events % 1024 == 0 {
+                py.check_signals()?;
+            }
+            self.now = Some(timestamp);
+            self.streams[s].cursor += 1;
+            self.dispatch(py, s, row)?;
+            events += 1;
+        }
+        Ok(events)
+    }
+
+    /// Deliver row `row` of stream `s` to its subscribers, appending native orders
+    fn dispatch(&mut self, py: Python<'_>, s: usize, row: usize) -> PyResult<()> {
+        let stream = &self.streams[s];
+        let timestamp = stream.timestamps[row];
+        let mut emitted = Vec::new();
+        for (id, subscription) in self.subscriptions.iter_mut().enumerate().filter(|(_, sub)| sub.stream == s) {
+            match &mut subscription.handler {
+                Handler::Native(strategy) => {
+                    let mut guard = strategy.try_borrow_mut(py)?;
+                    let strategy = guard.strategy_mut();
+                    match stream.layout {
+                        Layout::Tick { price, size } => {
+                            let tick = Tick { timestamp, price: stream.value(price, row), size: stream.optional(size, row) };
+                            strategy.on_tick(&tick, &mut emitted);
+                        }
+                        Layout::Bar { open, high, low, close, volume } => {
+                            let bar = Bar {
+                                timestamp,
+                                open: stream.value(open, row),
+                                high: stream.value(high, row),
+                                low: stream.value(low, row),
+                                close: stream.value(close, row),
+                                volume: stream.optional(volume, row),
+                            };
+                            strategy.on_bar(&bar, &mut emitted);
+                        }
+                        Layout::Fill { quantity, price } => {
+                            let fill = Fill { timestamp, quantity: stream.value(quantity, row), price: stream.value(price, row) };
+                            strategy.on_fill(&fill, &mut emitted);
+                        }
+                        Layout::Other(_) => {}
+                    }
+                    guard.check()?;
+                    drop(guard);
+                    for order in emitted.drain(..) {
+                        if let Some(on_order) = &self.on_order {
+                            on_order.call1(py, (id, timestamp, order.quantity, order.limit_price))?;
+                        }
+                        self.orders.push((id, timestamp, order));
+                    }
+                }
+                Handler::Indicator { target, fields, outputs } => {
+                    let args = PyTuple::new_bound(py, fields.iter().map(|&j| stream.value(j, row)));
+                    outputs.push(target.call_method1(py, "update", args)?);
+                }
+                Handler::Callback(callback) => {
+                    let values = PyDict::new_bound(py);
+                    for (j, name) in stream.names.iter().enumerate() {
+                        values.set_item(name, stream.value(j, row))?;
+                    }
+                    callback.call1(py, (stream.name.as_str(), timestamp, values))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Replayer {
+    #[new]
+    #[pyo3(signature = (speed=None, on_order=None))]
+    fn new(speed: Option<f64>, on_order: Option<PyObject>) -> PyResult<Self> {
+        check_speed(speed)?;
+        Ok(Replayer {
+            streams: Vec::new(),
+            subscriptions: Vec::new(),
+            speed,
+            on_order,
+            now: None,
+            orders: Vec::new(),
+        })
+    }
+
+    /// Add a recorded stream named `name`: a mapping (dict or DataFrame) with an
+    /// ascending `timestamp` column and numeric value columns. `kind` is "tick" (`price`
+    /// and optionally `size`), "bar" (`open`, `high`, `low`, `close`, optionally
+    /// `volume`), "fill" (signed `quantity` and `price`), "book" or "event"; only the
+    /// first three can drive native strategies
+    #[pyo3(signature = (name, data, kind="event"))]
+    fn add_stream(&mut self, name: &str, data: &Bound<'_, PyAny>, kind: &str) -> PyResult<()> {
+        if self.streams.iter().any(|s| s.name == name) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Stream '{}' already exists", name
+            )));
+        }
+        let timestamps = data
+            .get_item("timestamp")
+            .map_err(|_| {
+                pyo3::exceptions::PyKeyError::new_err(format!("stream '{}' needs a 'timestamp' column", name))
+            })?
+            .extract::<Column<i64>>()?
+            .values()?
+            .into_owned();
+        if timestamps.windows(2).any(|w| w[1] < w[0]) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Timestamps of stream '{}' must be sorted ascending", name
+            )));
+        }
+        let (mut names, mut columns) = (Vec::new(), Vec::new());
+        for key in data.call_method0("keys")?.iter()? {
+            let key: String = key?.extract()?;
+            if key == "timestamp" {
+                continue;
+            }
+            let values = match data.get_item(key.as_str())?.extract::<NumericColumn>()? {
+                NumericColumn::Float(column) => column.values()?.into_owned(),
+                NumericColumn::Int(column) => column.values()?.iter().map(|&v| v as f64).collect(),
+            };
+            if values.len() != timestamps.len() {
+                return Err(crate::errors::LengthMismatchError::new_err(format!(
+                    "Column '{}' of stream '{}' must have one value per timestamp", key, name
+                )));
+            }
+            names.push(key);
+            columns.push(values);
+        }
+        let layout = Layout::parse(kind, name, &names)?;
+        self.streams.push(Stream { name: name.to_string(), layout, timestamps, names, columns, cursor: 0 });
+        Ok(())
+    }
+
+    /// Drive `handler` with the events of `stream` and return the subscription's id. A
+    /// `NativeStrategy` is fed natively by the stream's kind; with `fields` (column
+    /// names), any object's `update` method is called with those values of each event and
+    /// its results are returned by `run`; any other callable is called with
+    /// `(stream, timestamp, row)`, `row` a dict of the event's values
+    #[pyo3(signature = (stream, handler, fields=None))]
+    fn subscribe(&mut self, stream: &str, handler: &Bound<'_, PyAny>, fields: Option<Vec<String>>) -> PyResult<usize> {
+        let Some(index) = self.streams.iter().position(|s| s.name == stream) else {
+            return Err(pyo3::exceptions::PyKeyError::new_err(format!(
+                "Unknown stream '{}'", stream
+            )));
+        };
+        let source = &self.streams[index];
+        let handler = if let Ok(strategy) = handler.downcast::<NativeStrategy>() {
+            if let Layout::Other(kind) = source.layout {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Native strategies cannot subscribe to {} stream '{}'", kind, stream
+                )));
+            }
+            Handler::Native(strategy.clone().unbind())
+        } else if let Some(fields) = fields {
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    source.names.iter().position(|n| n == field).ok_or_else(|| {
+                        pyo3::exceptions::PyKeyError::new_err(format!(
+                            "Stream '{}' has no column '{}'", stream, field
+                        ))
+                    })
+                })
+                .collect::<PyResult<_>>()?;
+            if !handler.hasattr("update")? {
+                return Err(pyo3::exceptions::PyTypeError::new_err(
+                    "Handlers given fields need an update method"
+                ));
+            }
+            Handler::Indicator { target: handler.clone().unbind(), fields, outputs: Vec::new() }
+        } else if handler.is_callable() {
+            Handler::Callback(handler.clone().unbind())
+        } else {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "handler must be a NativeStrategy, a callable, or an object with update given fields"
+            ));
+        };
+        self.subscriptions.push(Subscription { stream: index, handler });
+        Ok(self.subscriptions.len() - 1)
+    }
+
+    /// Replay events up to and including `until` (all remaining when None), at most
+    /// `max_events` of them; a later `run` resumes where this one stopped. Returns a dict
+    /// with the number of `events` replayed, the `orders` of native strategies (arrays of
+    /// `subscription`, `timestamp`, `quantity` and `limit_price`), the `outputs` of each
+    /// `update` subscription keyed by id, and whether the recording is `done`
+    #[pyo3(signature = (until=None, max_events=None))]
+    fn run<'py>(&mut self, py: Python<'py>, until: Option<i64>, max_events: Option<usize>) -> PyResult<Bound<'py, PyDict>> {
+        telemetry::traced("Replayer.run", || {
+            let events = self.advance(py, until, max_events)?;
+            let orders = std::mem::take(&mut self.orders);
+
+            let result = PyDict::new_bound(py);
+            result.set_item("events", events)?;
+            let order_arrays = PyDict::new_bound(py);
+            order_arrays.set_item("subscription", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.0 as i64)))?;
+            order_arrays.set_item("timestamp", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.1)))?;
+            order_arrays.set_item("quantity", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.2.quantity)))?;
+            order_arrays.set_item("limit_price", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.2.limit_price)))?;
+            result.set_item("orders", order_arrays)?;
+            let outputs = PyDict::new_bound(py);
+            for (id, subscription) in self.subscriptions.iter_mut().enumerate() {
+                if let Handler::Indicator { outputs: values, .. } = &mut subscription.handler {
+                    outputs.set_item(id, std::mem::take(values))?;
+                }
+            }
+            result.set_item("outputs", outputs)?;
+            result.set_item("done", self.pending() == 0)?;
+            Ok(result)
+        })
+    }
+
+    /// Rewind every stream to its start, dropping orders not yet returned; subscribed
+    /// indicators and strategies keep their state, so recreate them for a fresh rehearsal
+    fn reset(&mut self) {
+        for stream in &mut self.streams {
+            stream.cursor = 0;
+        }
+        self.now = None;
+        self.orders.clear();
+    }
+
+    /// Timestamp of the last event replayed, None before the first
+    #[getter]
+    fn now(&self) -> Option<i64> {
+        self.now
+    }
+
+    /// Events left to replay across all streams
+    #[getter]
+    fn pending(&self) -> usize {
+        self.streams.iter().map(|s| s.timestamps.len() - s.cursor).sum()
+    }
+
+    #[getter]
+    fn speed(&self) -> Option<f64> {
+        self.speed
+    }
+
+    #[setter]
+    fn set_speed(&mut self, speed: Option<f64>) -> PyResult<()> {
+        check_speed(speed)?;
+        self.speed = speed;
+        Ok(())
+    }
+
+    #[getter]
+    fn streams(&self) -> Vec<String> {
+        self.streams.iter().map(|s| s.name.clone()).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Replayer(streams={}, subscriptions={}, pending={}, speed={:?})",
+            self.streams.len(), self.subscriptions.len(), self.pending(), self.speed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDLERS: &str = "log = []
+
+def record(stream, timestamp, row):
+    if row['boom']:
+        raise ValueError('boom')
+    log.append((stream, timestamp))
+";
+
+    /// A replayer over event streams `a` and `b`, recording each event through a callback
+    fn replayer<'py>(py: Python<'py>, b_boom: &[f64]) -> (Replayer, Bound<'py, PyModule>) {
+        let module = PyModule::from_code_bound(py, HANDLERS, "handlers.py", "handlers").unwrap();
+        let stream = |name: &str, timestamps: Vec<i64>, boom: Vec<f64>| Stream {
+            name: name.to_string(),
+            layout: Layout::Other("event"),
+            timestamps,
+            names: vec!["boom".to_string()],
+            columns: vec![boom],
+            cursor: 0,
+        };
+        let mut replayer = Replayer::new(None, None).unwrap();
+        replayer.streams.push(stream("a", vec![1, 2, 3], vec![0.0; 3]));
+        replayer.streams.push(stream("b", vec![2, 3], b_boom.to_vec()));
+        let record = module.getattr("record").unwrap().unbind();
+        for s in 0..2 {
+            replayer.subscriptions.push(Subscription { stream: s, handler: Handler::Callback(record.clone_ref(py)) });
+        }
+        (replayer, module)
+    }
+
+    fn log(module: &Bound<'_, PyModule>) -> Vec<(String, i64)> {
+        let log = module.getattr("log").unwrap();
+        let events = log.extract().unwrap();
+        log.call_method0("clear").unwrap();
+        events
+    }
+
+    fn event(stream: &str, timestamp: i64) -> (String, i64) {
+        (stream.to_string(), timestamp)
+    }
+
+    #[test]
+    fn equal_timestamps_follow_stream_order_and_runs_resume() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let (mut replayer, module) = replayer(py, &[0.0, 0.0]);
+            let all = vec![event("a", 1), event("a", 2), event("b", 2), event("a", 3), event("b", 3)];
+
+            assert_eq!(replayer.advance(py, Some(2), None).unwrap(), 3);
+            assert_eq!((replayer.now(), replayer.pending()), (Some(2), 2));
+            assert_eq!(replayer.advance(py, None, Some(1)).unwrap(), 1);
+            assert_eq!(replayer.advance(py, None, None).unwrap(), 1);
+            assert_eq!(replayer.pending(), 0);
+            assert_eq!(log(&module), all);
+
+            replayer.reset();
+            assert_eq!((replayer.now(), replayer.pending()), (None, 5));
+            assert_eq!(replayer.advance(py, None, None).unwrap(), 5);
+            assert_eq!(log(&module), all);
+        });
+    }
+
+    #[test]
+    fn a_raising_handler_consumes_its_event() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let (mut replayer, module) = replayer(py, &[1.0, 0.0]);
+            replayer.orders.push((0, 1, Order { quantity: 1.0, limit_price: f64::NAN }));
+            assert!(replayer.advance(py, None, None).is_err());
+            assert_eq!((replayer.now(), replayer.pending()), (Some(2), 2));
+            assert_eq!(log(&module), vec![event("a", 1), event("a", 2)]);
+            // Orders from before the error wait for the next run
+            assert_eq!(replayer.orders.len(), 1);
+
+            assert_eq!(replayer.advance(py, None, None).unwrap(), 2);
+            assert_eq!(log(&module), vec![event("a", 3), event("b", 3)]);
+            replayer.reset();
+            assert!(replayer.orders.is_empty());
+        });
+    }
+}
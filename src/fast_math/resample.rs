@@ -0,0 +1,256 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyReadonlyArray2};
+use ndarray::ArrayView2;
+
+use crate::bars::Bars;
+use crate::calendar::TradingCalendar;
+use crate::column::Column;
+use crate::csv_reader::NAT;
+
+/// Parse a frequency such as "30s", "5min", "1h" or "1d" into nanoseconds. Units are ns,
+/// us, ms, s, min (or T), h, d and w; a missing count means 1.
+pub fn parse_rule(rule: &str) -> PyResult<i64> {
+    let rule = rule.trim();
+    let split = rule.find(|c: char| !c.is_ascii_digit()).unwrap_or(rule.len());
+    let (count, unit) = rule.split_at(split);
+    let count: i64 = if count.is_empty() { 1 } else { count.parse().unwrap_or(0) };
+    let unit_ns = match unit.to_ascii_lowercase().as_str() {
+        "ns" => Some(1),
+        "us" => Some(1_000),
+        "ms" => Some(1_000_000),
+        "s" | "sec" => Some(1_000_000_000),
+        "min" | "t" => Some(60_000_000_000),
+        "h" => Some(3_600_000_000_000),
+        "d" => Some(86_400_000_000_000),
+        "w" => Some(7 * 86_400_000_000_000),
+        _ => None,
+    };
+    match unit_ns.and_then(|u| count.checked_mul(u)).filter(|&ns| ns > 0) {
+        Some(ns) => Ok(ns),
+        None => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid rule '{}', expected a positive count and a unit: ns, us, ms, s, min, h, d or w", rule
+        ))),
+    }
+}
+
+/// Which edge of its interval a bin is closed on, and which it is stamped with
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Left,
+    Right,
+}
+
+impl Edge {
+    fn parse(argument: &str, name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "left" => Ok(Edge::Left),
+            "right" => Ok(Edge::Right),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown {} '{}', expected 'left' or 'right'", argument, name
+            ))),
+        }
+    }
+}
+
+/// The bin settings of one resample
+pub(crate) struct Binning {
+    width: i64,
+    /// Start of some bin: bins tile time from here when there is no calendar
+    origin: i64,
+    closed: Edge,
+    label: Edge,
+}
+
+impl Binning {
+    /// Bins of `rule` tiling time from the epoch shifted by `offset`, from its first
+    /// Monday for whole weeks
+    pub(crate) fn new(rule: &str, offset: i64, closed: &str, label: &str) -> PyResult<Self> {
+        let width = parse_rule(rule)?;
+        // The epoch is a Thursday; weekly bins start on Mondays
+        let week = 7 * 86_400_000_000_000;
+        Ok(Binning {
+            width,
+            origin: if width % week == 0 { offset + 4 * 86_400_000_000_000 } else { offset },
+            closed: Edge::parse("closed", closed)?,
+            label: Edge::parse("label", label)?,
+        })
+    }
+
+    /// `[start, end)` of the bin holding `t`, counting `t` as just before itself when
+    /// bins are closed on the right; with a calendar bins start at each session's open,
+    /// the last one ending at its close, and times outside sessions have none. NaT has
+    /// no bin either.
+    fn bin(&self, t: i64, calendar: Option<&TradingCalendar>) -> Option<(i64, i64)> {
+        if t == NAT {
+            return None;
+        }
+        let t = if self.closed == Edge::Right { t - 1 } else { t };
+        match calendar {
+            None => {
+                let start = self.origin + (t - self.origin).div_euclid(self.width) * self.width;
+                Some((start, start + self.width))
+            }
+            Some(calendar) => {
+                let (open, close) = calendar.session_at(t)?;
+                let start = open + (t - open).div_euclid(self.width) * self.width;
+                Some((start, (start + self.width).min(close)))
+            }
+        }
+    }
+}
+
+/// Aggregate bars in ascending time order into bins: first open, highest high, lowest
+/// low, last close and summed volume, ignoring NaN; empty bins are skipped
+pub(crate) fn resample(timestamps: &[i64], ohlcv: ArrayView2<'_, f64>, binning: &Binning, calendar: Option<&TradingCalendar>) -> Bars {
+    let mut bars = Bars::default();
+    let has_volume = ohlcv.ncols() > 4;
+    let mut current: Option<((i64, i64), [f64; 5])> = None;
+    let emit = |bars: &mut Bars, ((start, end), [open, high, low, close, volume]): ((i64, i64), [f64; 5])| {
+        let stamp = if binning.label == Edge::Left { start } else { end };
+        let high = if high == f64::NEG_INFINITY { f64::NAN } else { high };
+        let low = if low == f64::INFINITY { f64::NAN } else { low };
+        bars.push(stamp, open, high, low, close, volume);
+    };
+    for (i, &t) in timestamps.iter().enumerate() {
+        let Some(bin) = binning.bin(t, calendar) else { continue };
+        let row = ohlcv.row(i);
+        let volume = if has_volume { row[4] } else { 0.0 };
+        match &mut current {
+            Some((open_bin, values)) if *open_bin == bin => {
+                let [open, high, low, close, total] = values;
+                if open.is_nan() {
+                    *open = row[0];
+                }
+                *high = high.max(row[1]);
+                *low = low.min(row[2]);
+                if !row[3].is_nan() {
+                    *close = row[3];
+                }
+                if !volume.is_nan() {
+                    *total += volume;
+                }
+            }
+            _ => {
+                if let Some(done) = current.take() {
+                    emit(&mut bars, done);
+                }
+                // max and min against the infinities turn a NaN high or low into "none yet"
+                let values = [
+                    row[0],
+                    row[1].max(f64::NEG_INFINITY),
+                    row[2].min(f64::INFINITY),
+                    row[3],
+                    if volume.is_nan() { 0.0 } else { volume },
+                ];
+                current = Some((bin, values));
+            }
+        }
+    }
+    if let Some(done) = current {
+        emit(&mut bars, done);
+    }
+    bars
+}
+
+/// Index of the resampled bar each row of `resample` falls in, None outside sessions
+pub(crate) fn bin_indices(timestamps: &[i64], binning: &Binning, calendar: Option<&TradingCalendar>) -> Vec<Option<usize>> {
+    let mut current: Option<(i64, i64)> = None;
+    let mut count = 0;
+    timestamps
+        .iter()
+        .map(|&t| {
+            let bin = binning.bin(t, calendar)?;
+            if current != Some(bin) {
+                current = Some(bin);
+                count += 1;
+            }
+            Some(count - 1)
+        })
+        .collect()
+}
+
+/// Resample OHLCV bars to a coarser frequency, replacing pandas' `resample`.
+///
+/// `timestamps` are the ascending bar times (integer nanoseconds since the epoch, UTC)
+/// and `ohlcv` a 2D array with open, high, low, close and optionally volume columns.
+/// `rule` is the new bar length, such as "15min", "1h" or "1d" (units ns, us, ms, s, min,
+/// h, d and w). Bins tile time from the epoch (from its first Monday for whole weeks)
+/// shifted by `offset` nanoseconds or, with a `calendar`, from each session's open, the
+/// last bin of a session ending at its close ("1d" then gives one bar per session) and
+/// bars outside sessions being dropped, as are bars stamped NaT. `closed="left"` (the
+/// default) puts a bar stamped on a bin edge into the bin starting there, as for bars
+/// stamped with their open time; "right" puts it into the bin ending there, as for bars
+/// stamped with their close.
+/// `label` stamps each new bar with its bin's "left" (start, the default) or "right"
+/// (end) edge. Each bin takes the first open, highest high, lowest low and last close and
+/// sums the volume, ignoring NaN; bins without bars are left out. Returns a dict with
+/// `timestamp`, `open`, `high`, `low`, `close` and `volume`.
+#[pyfunction]
+#[pyo3(signature = (timestamps, ohlcv, rule, label="left", closed="left", offset=0, calendar=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn resample_ohlcv_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    ohlcv: PyReadonlyArray2<'py, f64>,
+    rule: &str,
+    label: &str,
+    closed: &str,
+    offset: i64,
+    calendar: Option<PyRef<'py, TradingCalendar>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let binning = Binning::new(rule, offset, closed, label)?;
+    let timestamps = timestamps.values()?;
+    let ohlcv = ohlcv.as_array();
+    if !(4..=5).contains(&ohlcv.ncols()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "ohlcv must have open, high, low, close and optionally volume columns"
+        ));
+    }
+    if ohlcv.nrows() != timestamps.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "ohlcv must have one row per timestamp"
+        ));
+    }
+    let stamped: Vec<i64> = timestamps.iter().copied().filter(|&t| t != NAT).collect();
+    if stamped.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Timestamps must be sorted ascending"
+        ));
+    }
+    let calendar = calendar.map(|c| c.clone());
+    let bars = py.allow_threads(|| resample(&timestamps, ohlcv, &binning, calendar.as_ref()));
+    let result = PyDict::new_bound(py);
+    result.set_item("timestamp", PyArray1::from_vec_bound(py, bars.timestamps))?;
+    result.set_item("open", PyArray1::from_vec_bound(py, bars.open))?;
+    result.set_item("high", PyArray1::from_vec_bound(py, bars.high))?;
+    result.set_item("low", PyArray1::from_vec_bound(py, bars.low))?;
+    result.set_item("close", PyArray1::from_vec_bound(py, bars.close))?;
+    result.set_item("volume", PyArray1::from_vec_bound(py, bars.volume))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000_000_000;
+
+    #[test]
+    fn nat_bars_are_dropped() {
+        let binning = Binning::new("2min", 0, "right", "left").unwrap();
+        let timestamps = [NAT, MINUTE, 2 * MINUTE, 3 * MINUTE, NAT];
+        let ohlcv = ndarray::array![
+            [9.0, 99.0, 0.0, 9.0, 100.0],
+            [1.0, 2.0, 0.5, 1.5, 10.0],
+            [1.5, 3.0, 1.0, 2.5, 20.0],
+            [2.5, 2.8, 2.0, 2.2, 5.0],
+            [9.0, 99.0, 0.0, 9.0, 100.0],
+        ];
+        let bars = resample(&timestamps, ohlcv.view(), &binning, None);
+        assert_eq!(bars.timestamps, vec![0, 2 * MINUTE]);
+        assert_eq!((bars.open, bars.high, bars.low), (vec![1.0, 2.5], vec![3.0, 2.8], vec![0.5, 2.0]));
+        assert_eq!((bars.close, bars.volume), (vec![2.5, 2.2], vec![30.0, 5.0]));
+        assert_eq!(bin_indices(&timestamps, &binning, None), vec![None, Some(0), Some(0), Some(1), None]);
+    }
+}
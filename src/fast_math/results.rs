@@ -0,0 +1,185 @@
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict, PyTuple};
+use numpy::{PyArray1, PyArray2};
+use ndarray::{Array1, Array2};
+
+use crate::column::NumericColumn;
+
+/// The named outputs computed for one symbol, in the order they were produced
+type Outputs = Vec<(String, Array1<f64>)>;
+
+/// One symbol and the values of its named outputs, as batch functions produce them
+pub type SymbolResults = (String, Vec<(String, Vec<f64>)>);
+
+/// Per-symbol results of a batch computation: each symbol maps to named output arrays.
+///
+/// Outputs are held in Rust memory and handed out as read-only numpy views without
+/// copying, `result_set["AAPL"]` giving a dict of a symbol's outputs and
+/// `result_set["AAPL", "rsi_14"]` a single one. `stack` lines one output up across
+/// symbols and `stack_features` a symbol's outputs side by side, as 2D arrays. A result
+/// set is immutable once built; `ResultSet(results)` builds one from a nested mapping of
+/// symbol to name to array.
+#[pyclass(module = "fast_math")]
+pub struct ResultSet {
+    symbols: Vec<String>,
+    outputs: Vec<Outputs>,
+}
+
+impl ResultSet {
+    pub fn new(results: Vec<SymbolResults>) -> Self {
+        let (symbols, outputs) = results
+            .into_iter()
+            .map(|(symbol, outputs)| (symbol, outputs.into_iter().map(|(name, values)| (name, Array1::from(values))).collect()))
+            .unzip();
+        ResultSet { symbols, outputs }
+    }
+
+    fn symbol(&self, symbol: &str) -> PyResult<&Outputs> {
+        self.symbols
+            .iter()
+            .position(|s| s == symbol)
+            .map(|i| &self.outputs[i])
+            .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err(symbol.to_string()))
+    }
+
+    fn output<'a>(&'a self, symbol: &str, name: &str) -> PyResult<&'a Array1<f64>> {
+        self.symbol(symbol)?.iter().find(|(n, _)| n == name).map(|(_, values)| values).ok_or_else(|| {
+            pyo3::exceptions::PyKeyError::new_err(format!(
+                "Symbol '{}' has no output '{}'", symbol, name
+            ))
+        })
+    }
+
+    /// Read-only numpy view of `values`, one of this result set's outputs
+    fn view<'py>(slf: &Bound<'py, Self>, values: &Array1<f64>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        // Safety: the view's base object is the result set, whose arrays are never modified
+        // or reallocated once built
+        let view = unsafe { PyArray1::borrow_from_array_bound(values, slf.clone().into_any()) };
+        view.call_method("setflags", (), Some(&[("write", false)].into_py_dict_bound(slf.py())))?;
+        Ok(view)
+    }
+
+    /// A dict of views of `outputs`, keyed by output name
+    fn views<'py>(slf: &Bound<'py, Self>, outputs: &Outputs) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new_bound(slf.py());
+        for (name, values) in outputs {
+            result.set_item(name, Self::view(slf, values)?)?;
+        }
+        Ok(result)
+    }
+}
+
+/// Stack `columns` of equal length side by side, or fail naming what differs
+fn column_stack(columns: &[&Array1<f64>], what: &str) -> PyResult<Array2<f64>> {
+    let rows = columns.first().map_or(0, |c| c.len());
+    if columns.iter().any(|c| c.len() != rows) {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "Cannot stack {} of different lengths", what
+        )));
+    }
+    Ok(Array2::from_shape_fn((rows, columns.len()), |(t, j)| columns[j][t]))
+}
+
+#[pymethods]
+impl ResultSet {
+    /// Build a result set from a mapping of symbol to a mapping of output name to array;
+    /// the arrays are copied into Rust memory
+    #[new]
+    fn from_mapping(results: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let mut parsed = Vec::new();
+        for item in results.call_method0("items")?.iter()? {
+            let (symbol, outputs): (String, Bound<'_, PyAny>) = item?.extract()?;
+            let mut named = Vec::new();
+            for output in outputs.call_method0("items")?.iter()? {
+                let (name, values): (String, NumericColumn) = output?.extract()?;
+                let values = match values {
+                    NumericColumn::Float(column) => column.values()?.into_owned(),
+                    NumericColumn::Int(column) => column.values()?.iter().map(|&v| v as f64).collect(),
+                };
+                named.push((name, values));
+            }
+            parsed.push((symbol, named));
+        }
+        Ok(ResultSet::new(parsed))
+    }
+
+    /// `result_set[symbol]` is a dict of the symbol's outputs, `result_set[symbol, name]`
+    /// one output
+    fn __getitem__(slf: &Bound<'_, Self>, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let py = slf.py();
+        let this = slf.borrow();
+        if let Ok(pair) = key.downcast::<PyTuple>() {
+            let (symbol, name): (String, String) = pair.extract()?;
+            return Ok(Self::view(slf, this.output(&symbol, &name)?)?.into_any().unbind());
+        }
+        let symbol: String = key.extract()?;
+        Ok(Self::views(slf, this.symbol(&symbol)?)?.into_py(py))
+    }
+
+    /// View of output `name` of `symbol`
+    fn get<'py>(slf: &Bound<'py, Self>, symbol: &str, name: &str) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Self::view(slf, slf.borrow().output(symbol, name)?)
+    }
+
+    /// A nested dict of symbol to output name to view, in place of a hand-built one
+    fn to_dict<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyDict>> {
+        let this = slf.borrow();
+        let result = PyDict::new_bound(slf.py());
+        for (symbol, outputs) in this.symbols.iter().zip(&this.outputs) {
+            result.set_item(symbol, Self::views(slf, outputs)?)?;
+        }
+        Ok(result)
+    }
+
+    /// Output `name` of each of `symbols` (all by default) as the columns of a 2D array,
+    /// one row per timestamp and one column per symbol as the cross-sectional functions
+    /// expect; the outputs must have equal lengths
+    #[pyo3(signature = (name, symbols=None))]
+    fn stack<'py>(&self, py: Python<'py>, name: &str, symbols: Option<Vec<String>>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        let symbols = symbols.unwrap_or_else(|| self.symbols.clone());
+        let columns = symbols.iter().map(|symbol| self.output(symbol, name)).collect::<PyResult<Vec<_>>>()?;
+        let what = format!("'{}' outputs", name);
+        Ok(PyArray2::from_owned_array_bound(py, py.allow_threads(|| column_stack(&columns, &what))?))
+    }
+
+    /// The outputs `names` of `symbol` (all, in order, by default) as the columns of a 2D
+    /// array, one row per timestamp, e.g. as a model's feature matrix
+    #[pyo3(signature = (symbol, names=None))]
+    fn stack_features<'py>(&self, py: Python<'py>, symbol: &str, names: Option<Vec<String>>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        let columns = match names {
+            Some(names) => names.iter().map(|name| self.output(symbol, name)).collect::<PyResult<Vec<_>>>()?,
+            None => self.symbol(symbol)?.iter().map(|(_, values)| values).collect(),
+        };
+        let what = format!("outputs of '{}'", symbol);
+        Ok(PyArray2::from_owned_array_bound(py, py.allow_threads(|| column_stack(&columns, &what))?))
+    }
+
+    /// The symbols, in the order they were added
+    fn keys(&self) -> Vec<String> {
+        self.symbols.clone()
+    }
+
+    /// Output names across all symbols, in order of first appearance
+    #[getter]
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (name, _) in self.outputs.iter().flatten() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    fn __contains__(&self, symbol: &str) -> bool {
+        self.symbols.iter().any(|s| s == symbol)
+    }
+
+    fn __len__(&self) -> usize {
+        self.symbols.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ResultSet(symbols={}, names={})", self.symbols.len(), self.names().len())
+    }
+}
@@ -0,0 +1,136 @@
+use pyo3::prelude::*;
+
+use crate::alignment::Alignment;
+use crate::column::{map_series, FloatElement, SeriesInput};
+use crate::config::FastMathConfig;
+use crate::skipna::NanPolicy;
+
+/// `change(price, earlier)` of each price against the one `periods` observations earlier,
+/// for the positions that have one. Under `Omit`, observations are the non-NaN prices, so
+/// a return spans any NaNs in between and NaN positions stay NaN.
+fn returns<T: FloatElement>(
+    slice: &[T],
+    periods: usize,
+    policy: NanPolicy,
+    change: impl Fn(T, T) -> T
+) -> PyResult<Vec<T>> {
+    let valid = crate::errors::check_period(periods, slice.len())?;
+    if policy != NanPolicy::Omit {
+        return Ok((0..valid).map(|i| change(slice[i + periods], slice[i])).collect());
+    }
+    let mut result = vec![T::nan(); slice.len()];
+    let observed: Vec<usize> = (0..slice.len()).filter(|&i| !slice[i].is_nan()).collect();
+    for k in periods..observed.len() {
+        let (now, earlier) = (observed[k], observed[k - periods]);
+        result[now] = change(slice[now], slice[earlier]);
+    }
+    result.drain(..slice.len() - valid);
+    Ok(result)
+}
+
+fn simple_return<T: FloatElement>(now: T, earlier: T) -> T {
+    now / earlier - T::one()
+}
+
+fn log_return<T: FloatElement>(now: T, earlier: T) -> T {
+    (now / earlier).ln()
+}
+
+/// Running total of `returns`, compounded for simple returns and summed for log returns.
+/// Under `Omit` a NaN return adds nothing and its position is NaN; otherwise everything
+/// from the first NaN on is NaN.
+fn cumulative<T: FloatElement>(slice: &[T], log: bool, policy: NanPolicy) -> Vec<T> {
+    // Simple returns compound through the growth of one unit rather than the return itself
+    let mut total = if log { T::zero() } else { T::one() };
+    slice
+        .iter()
+        .map(|&r| {
+            if r.is_nan() && policy == NanPolicy::Omit {
+                return T::nan();
+            }
+            if log {
+                total = total + r;
+                total
+            } else {
+                total = total * (T::one() + r);
+                total - T::one()
+            }
+        })
+        .collect()
+}
+
+/// Simple returns `x[t] / x[t - periods] - 1` of a price series, like pandas' `pct_change`.
+///
+/// `periods` compounds over that many bars at once (`periods=0` raises
+/// `InvalidWindowError`). Accepts a single series or a 2D array with one series per row.
+/// The first `periods` positions have no return, so the result is that much shorter than
+/// the input; `align="same"` NaN-pads them back to the input's length and an integer
+/// `align=k` also shifts the result `k` positions later. With the default
+/// `nan_policy="propagate"` returns touching a NaN price are NaN; with "omit" each price
+/// is compared with the one `periods` valid prices earlier, skipping the NaNs, whose own
+/// positions stay NaN; "raise" rejects NaN inputs.
+#[pyfunction]
+#[pyo3(signature = (data, periods=1, align=None, nan_policy=None, config=None))]
+pub fn simple_returns_rust<'py>(
+    py: Python<'py>,
+    data: SeriesInput<'py>,
+    periods: usize,
+    align: Option<Alignment>,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let policy = NanPolicy::resolve(nan_policy, false)?;
+    policy.check_input(&data)?;
+    map_series!(py, data, |slice| {
+        returns(slice, periods, policy, simple_return).map(|r| align.pad(r, slice.len()))
+    })
+}
+
+/// Log returns `ln(x[t] / x[t - periods])` of a price series.
+///
+/// Takes the same `periods`, `align` and `nan_policy` arguments as `simple_returns_rust`.
+/// Log returns add up over time, so `periods=k` equals the sum of `k` one-bar log returns.
+/// Non-positive prices give NaN or infinite returns.
+#[pyfunction]
+#[pyo3(signature = (data, periods=1, align=None, nan_policy=None, config=None))]
+pub fn log_returns_rust<'py>(
+    py: Python<'py>,
+    data: SeriesInput<'py>,
+    periods: usize,
+    align: Option<Alignment>,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let policy = NanPolicy::resolve(nan_policy, false)?;
+    policy.check_input(&data)?;
+    map_series!(py, data, |slice| {
+        returns(slice, periods, policy, log_return).map(|r| align.pad(r, slice.len()))
+    })
+}
+
+/// Cumulative returns of a return series, the growth of one unit invested minus one.
+///
+/// Simple returns are compounded, `(1 + r_1) ... (1 + r_t) - 1`; with `log=True` the
+/// input is log returns and the result their running sum, the cumulative log return.
+/// Accepts a single series or a 2D array with one series per row; the result has the
+/// input's shape. With the default `nan_policy="propagate"` everything from the first
+/// NaN return on is NaN; with "omit" a NaN return counts as no change and only its own
+/// position is NaN, like pandas' `cumprod`; "raise" rejects NaN inputs.
+#[pyfunction]
+#[pyo3(signature = (data, log=false, nan_policy=None, config=None))]
+pub fn cumulative_returns_rust<'py>(
+    py: Python<'py>,
+    data: SeriesInput<'py>,
+    log: bool,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let policy = NanPolicy::resolve(nan_policy, false)?;
+    policy.check_input(&data)?;
+    map_series!(py, data, |slice| Ok(cumulative(slice, log, policy)))
+}
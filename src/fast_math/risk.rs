@@ -0,0 +1,339 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+
+/// Outcome of a pre-trade check: accepted, or rejected for one or more reasons
+#[pyclass(module = "fast_math")]
+#[derive(Clone)]
+pub struct RiskDecision {
+    #[pyo3(get)]
+    pub accepted: bool,
+    /// Codes of the limits the order breaks: "invalid_order", "max_order_qty",
+    /// "max_notional", "max_position", "price_collar", "no_reference_price" or "rate_limit"
+    #[pyo3(get)]
+    pub reasons: Vec<&'static str>,
+    /// Human-readable explanation of each reason, in the same order
+    #[pyo3(get)]
+    pub messages: Vec<String>,
+}
+
+#[pymethods]
+impl RiskDecision {
+    fn __bool__(&self) -> bool {
+        self.accepted
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RiskDecision(accepted={}, reasons={:?})", self.accepted, self.reasons)
+    }
+}
+
+impl RiskDecision {
+    fn reject(&mut self, reason: &'static str, message: String) {
+        self.accepted = false;
+        self.reasons.push(reason);
+        self.messages.push(message);
+    }
+}
+
+/// A NaN position would make every later position check pass
+fn check_quantity(qty: f64) -> PyResult<()> {
+    if !qty.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Position quantity must be finite"
+        ));
+    }
+    Ok(())
+}
+
+fn now_ns() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as i64)
+}
+
+/// Pre-trade risk checks against configurable limits.
+///
+/// Each limit is off until set: `max_order_qty` caps the size of one order,
+/// `max_notional` its size times price, `max_position` the absolute position it may build
+/// (orders that reduce a position always pass), `price_collar` the fractional distance of
+/// a limit price from the symbol's reference price, and `max_orders` the number of orders
+/// accepted per `rate_window_ns` (per gate, across symbols). Market orders, and the
+/// notional check for them, use the reference price, set by `set_reference_price`; an
+/// order the notional or collar check cannot price is rejected. Positions are the gate's
+/// own view, fed by `record_fill` or `set_position`. `check` evaluates every limit and
+/// returns a `RiskDecision` listing all the breaches, not just the first; only accepted
+/// orders count towards the rate limit.
+#[pyclass(module = "fast_math")]
+pub struct RiskGate {
+    max_order_qty: Option<f64>,
+    max_notional: Option<f64>,
+    max_position: Option<f64>,
+    price_collar: Option<f64>,
+    max_orders: Option<usize>,
+    rate_window: i64,
+    positions: HashMap<String, f64>,
+    references: HashMap<String, f64>,
+    /// Timestamps of the accepted orders still inside the rate window
+    recent: VecDeque<i64>,
+}
+
+#[pymethods]
+impl RiskGate {
+    #[new]
+    #[pyo3(signature = (
+        max_order_qty=None,
+        max_notional=None,
+        max_position=None,
+        price_collar=None,
+        max_orders=None,
+        rate_window_ns=1_000_000_000
+    ))]
+    fn new(
+        max_order_qty: Option<f64>,
+        max_notional: Option<f64>,
+        max_position: Option<f64>,
+        price_collar: Option<f64>,
+        max_orders: Option<usize>,
+        rate_window_ns: i64
+    ) -> PyResult<Self> {
+        let limits = [max_order_qty, max_notional, max_position, price_collar];
+        if limits.iter().flatten().any(|l| l.is_nan() || *l < 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Risk limits must be >= 0"
+            ));
+        }
+        if rate_window_ns <= 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "rate_window_ns must be > 0"
+            ));
+        }
+        Ok(RiskGate {
+            max_order_qty,
+            max_notional,
+            max_position,
+            price_collar,
+            max_orders,
+            rate_window: rate_window_ns,
+            positions: HashMap::new(),
+            references: HashMap::new(),
+            recent: VecDeque::new(),
+        })
+    }
+
+    /// Check an order of `qty > 0` on `side` ("buy" or "sell") at the limit `price`, or at
+    /// the market without one. `timestamp_ns` (default: now) drives the rate limit.
+    #[pyo3(signature = (symbol, side, qty, price=None, timestamp_ns=None))]
+    fn check(
+        &mut self,
+        symbol: &str,
+        side: &str,
+        qty: f64,
+        price: Option<f64>,
+        timestamp_ns: Option<i64>
+    ) -> RiskDecision {
+        let mut decision = RiskDecision { accepted: true, reasons: Vec::new(), messages: Vec::new() };
+        let sign = match side.to_ascii_lowercase().as_str() {
+            "buy" | "b" => 1.0,
+            "sell" | "s" => -1.0,
+            _ => 0.0,
+        };
+        let bad_price = price.is_some_and(|p| !p.is_finite() || p <= 0.0);
+        if sign == 0.0 || !qty.is_finite() || qty <= 0.0 || bad_price {
+            decision.reject(
+                "invalid_order",
+                format!("Order needs side 'buy' or 'sell', qty > 0 and a positive price, got {} {} @ {:?}", side, qty, price),
+            );
+            return decision;
+        }
+        let reference = self.references.get(symbol).copied();
+
+        if let Some(limit) = self.max_order_qty {
+            if qty > limit {
+                decision.reject("max_order_qty", format!("Order qty {} exceeds the limit of {}", qty, limit));
+            }
+        }
+        if let Some(limit) = self.max_notional {
+            match price.or(reference) {
+                Some(p) if qty * p > limit => decision.reject(
+                    "max_notional",
+                    format!("Order notional {} exceeds the limit of {}", qty * p, limit),
+                ),
+                Some(_) => {}
+                None => decision.reject(
+                    "no_reference_price",
+                    format!("Market order in '{}' has no reference price for the notional check", symbol),
+                ),
+            }
+        }
+        if let Some(limit) = self.max_position {
+            let current = self.positions.get(symbol).copied().unwrap_or(0.0);
+            let after = current + sign * qty;
+            if after.abs() > limit && after.abs() > current.abs() {
+                decision.reject(
+                    "max_position",
+                    format!("Position in '{}' would reach {}, beyond the limit of {}", symbol, after, limit),
+                );
+            }
+        }
+        if let (Some(collar), Some(price)) = (self.price_collar, price) {
+            match reference {
+                Some(reference) if (price / reference - 1.0).abs() > collar => decision.reject(
+                    "price_collar",
+                    format!("Price {} is more than {} away from the reference {}", price, collar, reference),
+                ),
+                Some(_) => {}
+                None => decision.reject(
+                    "no_reference_price",
+                    format!("'{}' has no reference price for the price collar", symbol),
+                ),
+            }
+        }
+        if let Some(limit) = self.max_orders {
+            let now = timestamp_ns.unwrap_or_else(now_ns);
+            while self.recent.front().is_some_and(|&t| t <= now - self.rate_window) {
+                self.recent.pop_front();
+            }
+            if self.recent.len() >= limit {
+                decision.reject(
+                    "rate_limit",
+                    format!("{} orders already accepted in the last {} ns", self.recent.len(), self.rate_window),
+                );
+            } else if decision.accepted {
+                self.recent.push_back(now);
+            }
+        }
+        decision
+    }
+
+    /// Set the reference price of `symbol` (e.g. its last trade or mid) for market orders
+    /// and price collars
+    fn set_reference_price(&mut self, symbol: &str, price: f64) -> PyResult<()> {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Reference price must be > 0"
+            ));
+        }
+        self.references.insert(symbol.to_string(), price);
+        Ok(())
+    }
+
+    /// Add a signed fill (positive bought) to the gate's position in `symbol`
+    fn record_fill(&mut self, symbol: &str, qty: f64) -> PyResult<()> {
+        check_quantity(qty)?;
+        *self.positions.entry(symbol.to_string()).or_insert(0.0) += qty;
+        Ok(())
+    }
+
+    /// Overwrite the gate's position in `symbol`, e.g. from a broker reconciliation
+    fn set_position(&mut self, symbol: &str, qty: f64) -> PyResult<()> {
+        check_quantity(qty)?;
+        self.positions.insert(symbol.to_string(), qty);
+        Ok(())
+    }
+
+    /// The gate's signed position in `symbol`
+    fn position(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).copied().unwrap_or(0.0)
+    }
+
+    /// Forget positions, reference prices and the rate-limit history
+    fn reset(&mut self) {
+        self.positions.clear();
+        self.references.clear();
+        self.recent.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate(
+        max_order_qty: Option<f64>,
+        max_notional: Option<f64>,
+        max_position: Option<f64>,
+        price_collar: Option<f64>,
+        max_orders: Option<usize>
+    ) -> RiskGate {
+        RiskGate::new(max_order_qty, max_notional, max_position, price_collar, max_orders, 1_000).unwrap()
+    }
+
+    fn reasons(gate: &mut RiskGate, side: &str, qty: f64, price: Option<f64>) -> Vec<&'static str> {
+        gate.check("ES", side, qty, price, Some(0)).reasons
+    }
+
+    #[test]
+    fn max_order_qty() {
+        let mut gate = gate(Some(10.0), None, None, None, None);
+        assert!(reasons(&mut gate, "buy", 10.0, None).is_empty());
+        assert_eq!(reasons(&mut gate, "sell", 11.0, None), ["max_order_qty"]);
+        assert_eq!(reasons(&mut gate, "hold", 1.0, None), ["invalid_order"]);
+    }
+
+    #[test]
+    fn max_notional_uses_the_limit_or_reference_price() {
+        pyo3::prepare_freethreaded_python();
+        let mut gate = gate(None, Some(1_000.0), None, None, None);
+        assert!(reasons(&mut gate, "buy", 10.0, Some(100.0)).is_empty());
+        assert_eq!(reasons(&mut gate, "buy", 10.0, Some(101.0)), ["max_notional"]);
+        gate.set_reference_price("ES", 200.0).unwrap();
+        assert_eq!(reasons(&mut gate, "buy", 10.0, None), ["max_notional"]);
+        assert!(reasons(&mut gate, "buy", 5.0, None).is_empty());
+    }
+
+    #[test]
+    fn max_position_lets_reducing_orders_through() {
+        pyo3::prepare_freethreaded_python();
+        let mut gate = gate(None, None, Some(10.0), None, None);
+        gate.record_fill("ES", 8.0).unwrap();
+        assert!(reasons(&mut gate, "buy", 2.0, None).is_empty());
+        assert_eq!(reasons(&mut gate, "buy", 3.0, None), ["max_position"]);
+        assert!(reasons(&mut gate, "sell", 15.0, None).is_empty());
+        assert_eq!(reasons(&mut gate, "sell", 19.0, None), ["max_position"]);
+        gate.set_position("ES", -10.0).unwrap();
+        assert_eq!(reasons(&mut gate, "sell", 1.0, None), ["max_position"]);
+    }
+
+    #[test]
+    fn non_finite_positions_are_rejected() {
+        pyo3::prepare_freethreaded_python();
+        let mut gate = gate(None, None, Some(10.0), None, None);
+        gate.record_fill("ES", 8.0).unwrap();
+        assert!(gate.record_fill("ES", f64::NAN).is_err());
+        assert!(gate.set_position("ES", f64::INFINITY).is_err());
+        assert_eq!(gate.position("ES"), 8.0);
+        // The position check still bites after the bad fill
+        assert_eq!(reasons(&mut gate, "buy", 3.0, None), ["max_position"]);
+    }
+
+    #[test]
+    fn price_collar() {
+        pyo3::prepare_freethreaded_python();
+        let mut gate = gate(None, None, None, Some(0.05), None);
+        gate.set_reference_price("ES", 100.0).unwrap();
+        assert!(reasons(&mut gate, "buy", 1.0, Some(104.0)).is_empty());
+        assert_eq!(reasons(&mut gate, "buy", 1.0, Some(106.0)), ["price_collar"]);
+        assert_eq!(reasons(&mut gate, "sell", 1.0, Some(94.0)), ["price_collar"]);
+        // Market orders have no limit price to collar
+        assert!(reasons(&mut gate, "buy", 1.0, None).is_empty());
+    }
+
+    #[test]
+    fn rate_window_counts_accepted_orders() {
+        let mut gate = gate(Some(5.0), None, None, None, Some(2));
+        assert!(gate.check("ES", "buy", 1.0, None, Some(0)).accepted);
+        // Rejected orders do not count
+        assert!(!gate.check("ES", "buy", 6.0, None, Some(100)).accepted);
+        assert!(gate.check("NQ", "buy", 1.0, None, Some(500)).accepted);
+        assert_eq!(gate.check("ES", "buy", 1.0, None, Some(999)).reasons, ["rate_limit"]);
+        // The first order leaves the window after rate_window_ns
+        assert!(gate.check("ES", "buy", 1.0, None, Some(1_000)).accepted);
+    }
+
+    #[test]
+    fn unpriced_checks_reject_without_a_reference() {
+        let mut gate = gate(None, Some(1_000.0), None, Some(0.05), None);
+        assert_eq!(reasons(&mut gate, "buy", 1.0, None), ["no_reference_price"]);
+        assert_eq!(reasons(&mut gate, "buy", 1.0, Some(100.0)), ["no_reference_price"]);
+    }
+}
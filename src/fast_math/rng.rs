@@ -0,0 +1,150 @@
+// Small seedable random number generator for the resampling and simulation kernels.
+// Every replicate or batch of paths gets its own stream derived from the seed and its
+// index, so results are reproducible and do not depend on how work is spread over threads.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use pyo3::prelude::*;
+use statrs::function::gamma::ln_gamma;
+
+const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+/// Poisson means above which `SplitMix::poisson` switches from inversion to PTRS
+const PTRS_MEAN: f64 = 30.0;
+
+/// Seed of stochastic functions called without `seed=`
+static DEFAULT_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// `seed`, or when None the seed of the `FastMathConfig` block this thread is in, else
+/// the crate-wide seed set by `set_seed`
+pub fn resolve_seed(seed: Option<u64>) -> u64 {
+    seed.or_else(crate::config::scoped_seed).unwrap_or_else(|| DEFAULT_SEED.load(Ordering::Relaxed))
+}
+
+/// Set the seed used by every stochastic function (bootstrap, Monte Carlo pricing, path
+/// simulation) called without its own `seed=`.
+///
+/// The default is 0. Random numbers come from counter-based streams: each resample, batch
+/// or path draws from a stream keyed by the seed and its own index, never from state
+/// shared between threads, so a given seed gives exactly the same results whatever the
+/// thread count, and repeated calls with the same seed repeat them.
+#[pyfunction]
+pub fn set_seed(seed: u64) {
+    DEFAULT_SEED.store(seed, Ordering::Relaxed);
+}
+
+/// Seed used by stochastic functions called without `seed=`
+#[pyfunction]
+pub fn get_seed() -> u64 {
+    resolve_seed(None)
+}
+
+/// SplitMix64's output function, a bijective mix of the bits of `z`
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// SplitMix64
+pub struct SplitMix(u64);
+
+impl SplitMix {
+    /// Generator for stream `stream` of `seed`. The starting state is hashed from both:
+    /// the state advances by a constant per draw, so starting states a multiple of it
+    /// apart (as `seed ^ stream * GAMMA` would give) would replay each other's draws.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        SplitMix(mix(mix(seed) ^ stream.wrapping_mul(GAMMA)))
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(GAMMA);
+        mix(self.0)
+    }
+
+    /// Uniform index below `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Uniform in `[0, 1)` with 53 random bits
+    pub fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Two independent standard normals by the Box-Muller transform
+    pub fn normal_pair(&mut self) -> (f64, f64) {
+        // 1 - u is in (0, 1], keeping the logarithm finite
+        let radius = (-2.0 * (1.0 - self.uniform()).ln()).sqrt();
+        let (sin, cos) = (2.0 * std::f64::consts::PI * self.uniform()).sin_cos();
+        (radius * cos, radius * sin)
+    }
+
+    /// Poisson count with mean `mean`: by inversion, which takes about `mean` steps, for
+    /// small means, and by Hörmann's transformed rejection (PTRS) above `PTRS_MEAN`, where
+    /// `exp(-mean)` would also underflow for means past about 745
+    pub fn poisson(&mut self, mean: f64) -> u64 {
+        if mean > PTRS_MEAN && mean.is_finite() {
+            return self.poisson_ptrs(mean);
+        }
+        let u = self.uniform();
+        let (mut k, mut p) = (0u64, (-mean).exp());
+        let mut cumulative = p;
+        while u >= cumulative && p > 0.0 {
+            k += 1;
+            p *= mean / k as f64;
+            cumulative += p;
+        }
+        k
+    }
+
+    /// PTRS sampler (Hörmann 1993), O(1) expected draws for `mean >= 10`
+    fn poisson_ptrs(&mut self, mean: f64) -> u64 {
+        let (root, log_mean) = (mean.sqrt(), mean.ln());
+        let b = 0.931 + 2.53 * root;
+        let a = -0.059 + 0.02483 * b;
+        let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+        let v_r = 0.9277 - 3.6224 / (b - 2.0);
+        loop {
+            let u = self.uniform() - 0.5;
+            let v = self.uniform();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + mean + 0.43).floor();
+            if us >= 0.07 && v <= v_r {
+                return k as u64;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            let log_accept = (v * inv_alpha / (a / (us * us) + b)).ln();
+            if log_accept <= -mean + k * log_mean - ln_gamma(k + 1.0) {
+                return k as u64;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample mean and variance of `n` Poisson draws
+    fn moments(mean: f64, n: usize) -> (f64, f64) {
+        let mut rng = SplitMix::new(11, 0);
+        let draws = (0..n).map(|_| rng.poisson(mean) as f64).collect::<Vec<_>>();
+        let average = draws.iter().sum::<f64>() / n as f64;
+        let variance = draws.iter().map(|d| (d - average).powi(2)).sum::<f64>() / (n - 1) as f64;
+        (average, variance)
+    }
+
+    #[test]
+    fn poisson_moments_match_on_both_sides_of_the_cutoff() {
+        let n = 200_000;
+        for mean in [0.5, 4.0, 29.0, 31.0, 800.0, 1e6] {
+            let (average, variance) = moments(mean, n);
+            // Four standard errors of the mean, and a loose bound on the variance
+            assert!((average - mean).abs() < 4.0 * (mean / n as f64).sqrt(), "mean {}: {}", mean, average);
+            assert!((variance / mean - 1.0).abs() < 0.03, "mean {}: variance {}", mean, variance);
+        }
+        assert_eq!(SplitMix::new(1, 0).poisson(0.0), 0);
+    }
+}
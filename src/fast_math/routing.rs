@@ -0,0 +1,190 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Fees charged by one venue for taking liquidity
+#[derive(Clone, Copy, Default)]
+struct Fees {
+    /// Fraction of notional
+    taker: f64,
+    /// Per unit traded
+    per_share: f64,
+}
+
+impl Fees {
+    /// A number (the taker fee rate) or a dict with optional `taker` and `per_share`
+    fn parse(venue: &str, entry: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(taker) = entry.extract::<f64>() {
+            return Ok(Fees { taker, per_share: 0.0 });
+        }
+        let entry = entry.downcast::<PyDict>().map_err(|_| {
+            pyo3::exceptions::PyTypeError::new_err(format!(
+                "Fee schedule of '{}' must be a number or a dict", venue
+            ))
+        })?;
+        let number = |key: &str| -> PyResult<f64> {
+            Ok(entry.get_item(key)?.map(|v| v.extract()).transpose()?.unwrap_or(0.0))
+        };
+        Ok(Fees { taker: number("taker")?, per_share: number("per_share")? })
+    }
+
+    /// Cost of one unit at `price`, fees included, as a buyer (`sign` 1) or the proceeds
+    /// as a seller (`sign` -1)
+    fn effective(&self, price: f64, sign: f64) -> f64 {
+        price * (1.0 + sign * self.taker) + sign * self.per_share
+    }
+}
+
+/// One price level of a venue's book
+struct Level {
+    venue: usize,
+    price: f64,
+    size: f64,
+    effective: f64,
+}
+
+/// Levels of one book side: finite prices and positive sizes
+fn levels(book: &Bound<'_, PyDict>, venue: &str, side: &str) -> PyResult<Vec<(f64, f64)>> {
+    let column = |key: String| -> PyResult<Vec<f64>> {
+        match book.get_item(&key)? {
+            Some(values) => Ok(values.extract::<Column<f64>>()?.values()?.into_owned()),
+            None => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Book of '{}' needs a '{}' column", venue, key
+            ))),
+        }
+    };
+    let prices = column(format!("{}_prices", side))?;
+    let sizes = column(format!("{}_sizes", side))?;
+    if prices.len() != sizes.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(format!(
+            "Book of '{}' has {} {} prices but {} sizes", venue, prices.len(), side, sizes.len()
+        )));
+    }
+    Ok(prices.into_iter().zip(sizes).filter(|(p, s)| p.is_finite() && *s > 0.0).collect())
+}
+
+/// Children `(venue, price, qty, effective price)` of an order of `qty` taking `levels`
+/// cheapest first, fees included, up to the `limit` price; `sign` is 1 for a buy
+fn sweep(mut levels: Vec<Level>, qty: f64, sign: f64, limit: Option<f64>) -> Vec<(usize, f64, f64, f64)> {
+    levels.sort_by(|a, b| {
+        (sign * a.effective).total_cmp(&(sign * b.effective)).then(a.venue.cmp(&b.venue))
+    });
+    let mut remaining = qty;
+    let mut children = Vec::new();
+    for level in &levels {
+        if remaining <= 0.0 {
+            break;
+        }
+        // Levels past the limit may still follow cheaper ones after fees, so skip them
+        if limit.is_some_and(|l| sign * (level.price - l) > 0.0) {
+            continue;
+        }
+        let take = remaining.min(level.size);
+        children.push((level.venue, level.price, take, level.effective));
+        remaining -= take;
+    }
+    children
+}
+
+/// Split an order across the books of several venues at the lowest expected cost, for
+/// pre-trade TCA and routing research.
+///
+/// `order` is a dict with `side` ("buy" or "sell"), `qty` and an optional `limit_price`.
+/// `venue_books` maps each venue to a snapshot of its book, a dict of `bid_prices`,
+/// `bid_sizes`, `ask_prices` and `ask_sizes` arrays (as in `diff_book_snapshots_rust`;
+/// only the side the order takes from is needed), and `fee_schedule` maps venues to their
+/// taker fee, a fraction of notional or a dict with `taker` and `per_share` fees (missing
+/// venues are free). The displayed liquidity of all venues is swept in order of
+/// fee-inclusive price, which minimises the total cost since each level's cost is linear,
+/// skipping levels beyond `limit_price`, until the order is filled. Returns a dict with the child
+/// orders (`venue`, `price` and `qty` in sweep order), `allocation` (quantity per
+/// venue), `filled` and `unfilled` quantities, the `average_price` before and
+/// `effective_price` after fees, the `fees`, and `slippage_bps`, the fee-inclusive cost
+/// relative to the mid of the best bid and ask across venues (NaN without both).
+#[pyfunction]
+pub fn route_order_rust<'py>(
+    py: Python<'py>,
+    order: &Bound<'py, PyDict>,
+    venue_books: &Bound<'py, PyDict>,
+    fee_schedule: &Bound<'py, PyDict>
+) -> PyResult<Bound<'py, PyDict>> {
+    let required = |key: &str| {
+        order.get_item(key)?.ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("order needs a '{}' key", key))
+        })
+    };
+    let side: String = required("side")?.extract()?;
+    let (sign, taken) = match side.to_ascii_lowercase().as_str() {
+        "buy" | "b" => (1.0, "ask"),
+        "sell" | "s" => (-1.0, "bid"),
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown side '{}', expected 'buy' or 'sell'", side
+            )))
+        }
+    };
+    let qty: f64 = required("qty")?.extract()?;
+    let limit: Option<f64> = order.get_item("limit_price")?.map(|v| v.extract()).transpose()?;
+    if !qty.is_finite() || qty <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "order qty must be > 0"
+        ));
+    }
+
+    let mut venues = Vec::new();
+    let mut book_levels = Vec::new();
+    let (mut best_bid, mut best_ask) = (f64::NEG_INFINITY, f64::INFINITY);
+    for (venue, book) in venue_books.iter() {
+        let venue: String = venue.extract()?;
+        let book = book.downcast::<PyDict>().map_err(|_| {
+            pyo3::exceptions::PyTypeError::new_err(format!("Book of '{}' must be a dict", venue))
+        })?;
+        let fees = match fee_schedule.get_item(&venue)? {
+            Some(entry) => Fees::parse(&venue, &entry)?,
+            None => Fees::default(),
+        };
+        // The mid needs both sides; a book may carry only the side being taken
+        for (side, best) in [("bid", &mut best_bid), ("ask", &mut best_ask)] {
+            if side == taken || book.contains(format!("{}_prices", side))? {
+                for (price, _) in levels(book, &venue, side)? {
+                    *best = if side == "bid" { best.max(price) } else { best.min(price) };
+                }
+            }
+        }
+        for (price, size) in levels(book, &venue, taken)? {
+            book_levels.push(Level { venue: venues.len(), price, size, effective: fees.effective(price, sign) });
+        }
+        venues.push(venue);
+    }
+
+    let routed = py.allow_threads(|| sweep(book_levels, qty, sign, limit));
+
+    let filled: f64 = routed.iter().map(|c| c.2).sum();
+    let notional: f64 = routed.iter().map(|c| c.1 * c.2).sum();
+    let effective: f64 = routed.iter().map(|c| c.3 * c.2).sum();
+    let mut allocation = vec![0.0; venues.len()];
+    for child in &routed {
+        allocation[child.0] += child.2;
+    }
+    let mid = if best_bid.is_finite() && best_ask.is_finite() { 0.5 * (best_bid + best_ask) } else { f64::NAN };
+    let effective_price = effective / filled;
+
+    let result = PyDict::new_bound(py);
+    result.set_item("venue", routed.iter().map(|c| venues[c.0].as_str()).collect::<Vec<_>>())?;
+    result.set_item("price", PyArray1::from_vec_bound(py, routed.iter().map(|c| c.1).collect()))?;
+    result.set_item("qty", PyArray1::from_vec_bound(py, routed.iter().map(|c| c.2).collect()))?;
+    let by_venue = PyDict::new_bound(py);
+    for (venue, allocated) in venues.iter().zip(allocation) {
+        by_venue.set_item(venue, allocated)?;
+    }
+    result.set_item("allocation", by_venue)?;
+    result.set_item("filled", filled)?;
+    result.set_item("unfilled", qty - filled)?;
+    result.set_item("average_price", notional / filled)?;
+    result.set_item("effective_price", effective_price)?;
+    result.set_item("fees", sign * (effective - notional))?;
+    result.set_item("slippage_bps", sign * (effective_price - mid) / mid * 1e4)?;
+    Ok(result)
+}
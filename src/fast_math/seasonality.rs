@@ -0,0 +1,115 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use chrono::{TimeZone, Timelike};
+use chrono_tz::Tz;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::csv_reader::NAT;
+use crate::timezone::parse_tz;
+use crate::threads;
+
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+/// Statistics of the values falling in one time-of-day bucket
+struct BucketStats {
+    count: usize,
+    mean: f64,
+    median: f64,
+    std: f64,
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let n = values.len();
+    if n == 0 {
+        f64::NAN
+    } else if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        0.5 * (values[n / 2 - 1] + values[n / 2])
+    }
+}
+
+fn bucket_stats(mut values: Vec<f64>) -> BucketStats {
+    let count = values.len();
+    let mean = if count > 0 { values.iter().sum::<f64>() / count as f64 } else { f64::NAN };
+    let std = if count > 1 {
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1) as f64).sqrt()
+    } else {
+        f64::NAN
+    };
+    BucketStats { count, mean, median: median(&mut values), std }
+}
+
+/// Time-of-day bucket of a UTC nanosecond timestamp in local time, None for NaT
+fn bucket_of(tz: &Tz, timestamp: i64, bucket_minutes: usize) -> Option<usize> {
+    if timestamp == NAT {
+        return None;
+    }
+    let local = tz.timestamp_nanos(timestamp).time();
+    Some((local.hour() * 60 + local.minute()) as usize / bucket_minutes)
+}
+
+/// Intraday seasonality profile: mean, median and standard deviation of `values` in each
+/// time-of-day bucket, pooled across all the sessions in the data.
+///
+/// `timestamps` are UTC nanoseconds and are bucketed by their local wall-clock time in
+/// `tz`, so the profile follows the exchange's hours across daylight-saving changes; the
+/// day is cut into buckets of `bucket_minutes` from local midnight, the last one shorter
+/// when the minutes do not divide a day. NaN values and NaT timestamps are skipped.
+/// Returns a dict with per-bucket arrays of the `minute` of the day each bucket starts
+/// at, the `count` of values, their `mean`, `median` and `std` (sample, ddof=1), every
+/// bucket of the day included (empty ones with count 0 and NaN statistics), plus
+/// `normalized`, each value divided by the mean of its bucket, such as volume relative to
+/// its usual level at that time of day (NaN where the mean is 0 or missing).
+#[pyfunction]
+#[pyo3(signature = (timestamps, values, bucket_minutes=30, tz="UTC"))]
+pub fn seasonality_profile_rust<'py>(
+    py: Python<'py>,
+    timestamps: Column<'py, i64>,
+    values: Column<'py, f64>,
+    bucket_minutes: usize,
+    tz: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    if bucket_minutes == 0 || bucket_minutes > MINUTES_PER_DAY {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "bucket_minutes must be between 1 and 1440"
+        ));
+    }
+    let tz = parse_tz(tz)?;
+    let (timestamps, values) = (timestamps.values()?, values.values()?);
+    if timestamps.len() != values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps and values must have the same length"
+        ));
+    }
+    let n_buckets = MINUTES_PER_DAY.div_ceil(bucket_minutes);
+    let (buckets, stats): (Vec<Option<usize>>, Vec<BucketStats>) = py.allow_threads(|| {
+        let buckets: Vec<Option<usize>> = threads::install(|| {
+            timestamps.par_iter().map(|&t| bucket_of(&tz, t, bucket_minutes)).collect()
+        });
+        let mut grouped = vec![Vec::new(); n_buckets];
+        for (bucket, &value) in buckets.iter().zip(values.iter()) {
+            if let Some(b) = bucket.filter(|_| !value.is_nan()) {
+                grouped[b].push(value);
+            }
+        }
+        let stats = threads::install(|| grouped.into_par_iter().map(bucket_stats).collect());
+        (buckets, stats)
+    });
+    let normalized = buckets.iter().zip(values.iter()).map(|(bucket, &value)| match bucket {
+        Some(b) if stats[*b].mean != 0.0 => value / stats[*b].mean,
+        _ => f64::NAN,
+    });
+
+    let result = PyDict::new_bound(py);
+    result.set_item("minute", PyArray1::from_iter_bound(py, (0..n_buckets).map(|b| (b * bucket_minutes) as i64)))?;
+    result.set_item("count", PyArray1::from_iter_bound(py, stats.iter().map(|s| s.count as i64)))?;
+    result.set_item("mean", PyArray1::from_iter_bound(py, stats.iter().map(|s| s.mean)))?;
+    result.set_item("median", PyArray1::from_iter_bound(py, stats.iter().map(|s| s.median)))?;
+    result.set_item("std", PyArray1::from_iter_bound(py, stats.iter().map(|s| s.std)))?;
+    result.set_item("normalized", PyArray1::from_iter_bound(py, normalized))?;
+    Ok(result)
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use numpy::{Element, PyArray1};
+use ndarray::ArrayView1;
+use memmap2::{Mmap, MmapOptions};
+
+use crate::column::NumericColumn;
+
+/// File signature, including a format version byte
+const MAGIC: &[u8; 8] = b"FSERIES\x01";
+/// Magic (8), committed length in bytes (8), reserved (16)
+const HEADER_BYTES: usize = 32;
+/// Key length u32, dtype u8, padding (3), element count u64; the key follows, padded to
+/// 8 bytes so every series starts 8-byte aligned, then the little-endian values
+const RECORD_BYTES: usize = 16;
+/// Mappings start at multiples of this, the largest allocation granularity (Windows')
+const MAP_ALIGN: usize = 65536;
+
+/// Element type of a stored series; `Removed` records delete their key
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Dtype {
+    Removed = 0,
+    Float64 = 1,
+    Int64 = 2,
+}
+
+impl Dtype {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Dtype::Removed),
+            1 => Some(Dtype::Float64),
+            2 => Some(Dtype::Int64),
+            _ => None,
+        }
+    }
+}
+
+fn format_error(path: &str, message: &str) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("{}: {}", path, message))
+}
+
+fn padded(n: usize) -> usize {
+    n.div_ceil(8) * 8
+}
+
+/// Where a series lives in a store file
+#[derive(Clone, Copy)]
+struct Located {
+    /// Index of the mapping holding it
+    map: usize,
+    dtype: Dtype,
+    /// Start of the values within that mapping
+    offset: usize,
+    len: usize,
+}
+
+/// An append-only log of series in a file, mapped into memory. Each `put` appends a
+/// record and then advances the committed length in the header, so readers in other
+/// processes never see a partial record; the latest record of a key wins.
+struct FileStore {
+    path: String,
+    file: File,
+    /// Every mapping made so far, kept alive for the views into them
+    maps: Vec<Mmap>,
+    index: HashMap<String, Located>,
+    /// Bytes of the log already indexed
+    scanned: usize,
+}
+
+impl FileStore {
+    fn open(path: &str, create: bool) -> PyResult<Self> {
+        let file = if create {
+            // A fresh file rather than a truncated one: processes still mapping the old
+            // file keep valid (if stale) views instead of faulting
+            if std::path::Path::new(path).exists() {
+                std::fs::remove_file(path)?;
+            }
+            let mut file = OpenOptions::new().read(true).write(true).create_new(true).open(path)?;
+            file.write_all(&Self::header(HEADER_BYTES as u64))?;
+            file
+        } else {
+            OpenOptions::new().read(true).write(true).open(path)?
+        };
+        let mut store = FileStore { path: path.to_string(), file, maps: Vec::new(), index: HashMap::new(), scanned: HEADER_BYTES };
+        store.refresh()?;
+        Ok(store)
+    }
+
+    fn header(committed: u64) -> [u8; HEADER_BYTES] {
+        let mut header = [0u8; HEADER_BYTES];
+        header[..8].copy_from_slice(MAGIC);
+        header[8..16].copy_from_slice(&committed.to_le_bytes());
+        header
+    }
+
+    fn committed(&mut self) -> PyResult<usize> {
+        let mut head = [0u8; HEADER_BYTES];
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_exact(&mut head).map_err(|_| format_error(&self.path, "not a series store"))?;
+        if &head[..8] != MAGIC {
+            return Err(format_error(&self.path, "not a series store"));
+        }
+        Ok(u64::from_le_bytes(head[8..16].try_into().unwrap()) as usize)
+    }
+
+    /// Map and index the records committed since the last refresh, by this process or
+    /// another
+    fn refresh(&mut self) -> PyResult<()> {
+        let committed = self.committed()?;
+        if committed <= self.scanned {
+            return Ok(());
+        }
+        // Mapping past the end of the file would fault on access instead of raising
+        if (self.file.metadata()?.len() as usize) < committed {
+            return Err(format_error(&self.path, "file is shorter than its committed length"));
+        }
+        // Only the new part of the log is mapped, from an offset aligned for any platform
+        let base = self.scanned / MAP_ALIGN * MAP_ALIGN;
+        // Safety: the log is append-only and records are never rewritten once committed
+        let map = unsafe { MmapOptions::new().offset(base as u64).len(committed - base).map(&self.file)? };
+        let corrupt = || format_error(&self.path, "corrupt record");
+        let map_index = self.maps.len();
+        let mut at = self.scanned;
+        while at < committed {
+            if at + RECORD_BYTES > committed {
+                return Err(corrupt());
+            }
+            let record = &map[at - base..at - base + RECORD_BYTES];
+            let key_len = u32::from_le_bytes(record[0..4].try_into().unwrap()) as usize;
+            let dtype = Dtype::from_byte(record[4]).ok_or_else(corrupt)?;
+            let len = u64::from_le_bytes(record[8..16].try_into().unwrap()) as usize;
+            let key_start = at + RECORD_BYTES;
+            // Lengths come from the file, so a corrupt one must not wrap past the mapping
+            let offset = key_start.checked_add(padded(key_len)).ok_or_else(corrupt)?;
+            let end = len.checked_mul(8).and_then(|bytes| offset.checked_add(bytes)).ok_or_else(corrupt)?;
+            if end > committed {
+                return Err(corrupt());
+            }
+            let key = std::str::from_utf8(&map[key_start - base..key_start - base + key_len])
+                .map_err(|_| corrupt())?
+                .to_string();
+            if dtype == Dtype::Removed {
+                self.index.remove(&key);
+            } else {
+                self.index.insert(key, Located { map: map_index, dtype, offset: offset - base, len });
+            }
+            at = end;
+        }
+        self.maps.push(map);
+        self.scanned = committed;
+        Ok(())
+    }
+
+    /// Append a record of `bytes` under `key` and commit it
+    fn append(&mut self, key: &str, dtype: Dtype, len: usize, bytes: &[u8]) -> PyResult<()> {
+        // Pick up other writers' records first, so the append goes after them
+        self.refresh()?;
+        let mut record = Vec::with_capacity(RECORD_BYTES + padded(key.len()) + bytes.len());
+        record.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        record.extend_from_slice(&[dtype as u8, 0, 0, 0]);
+        record.extend_from_slice(&(len as u64).to_le_bytes());
+        record.extend_from_slice(key.as_bytes());
+        record.resize(RECORD_BYTES + padded(key.len()), 0);
+        record.extend_from_slice(bytes);
+        self.file.seek(SeekFrom::Start(self.scanned as u64))?;
+        self.file.write_all(&record)?;
+        // The committed length only moves once the whole record is written
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&Self::header((self.scanned + record.len()) as u64))?;
+        self.refresh()
+    }
+}
+
+/// Series held by a store
+enum Backing {
+    /// Read-only numpy arrays owning Rust-allocated buffers
+    Memory(HashMap<String, PyObject>),
+    File(FileStore),
+}
+
+/// Named price and timestamp series stored once in Rust memory and handed out as numpy
+/// views without copying.
+///
+/// `put` copies a series (float64, or int64 for integer and `datetime64` input) into the
+/// store once; `store[key]` then returns a read-only numpy view of it, which every kernel
+/// accepts without marshalling the data again. With `path` the series live in a
+/// memory-mapped file instead, e.g. under `/dev/shm` for shared memory, and every process
+/// opening the same path sees the same buffers: each maps the file as it grows, and
+/// series put by any of them become visible to the others on their next lookup. The file
+/// is an append-only log, so views stay valid when a key is replaced or removed;
+/// `create=True` starts a new, empty file. Concurrent writers to one file are not
+/// supported.
+#[pyclass(module = "fast_math")]
+pub struct SharedSeriesStore {
+    backing: Backing,
+}
+
+impl SharedSeriesStore {
+    /// Read-only numpy view of `len` values at `data`, inside one of the store's mappings
+    /// and kept alive by `slf`
+    fn file_view<'py, T: Element>(slf: &Bound<'py, Self>, data: *const u8, len: usize) -> PyResult<PyObject> {
+        // Safety: `data` is 8-byte aligned (mappings are page aligned and records keep
+        // series at multiples of 8) and holds `len` little-endian values of `T`; the view's
+        // base object is the store, which keeps every mapping alive and never rewrites it
+        let values = unsafe { ArrayView1::from_shape_ptr(len, data as *const T) };
+        let view = unsafe { PyArray1::borrow_from_array_bound(&values, slf.clone().into_any()) };
+        view.call_method("setflags", (), Some(&[("write", false)].into_py_dict_bound(slf.py())))?;
+        Ok(view.into_any().unbind())
+    }
+}
+
+#[pymethods]
+impl SharedSeriesStore {
+    #[new]
+    #[pyo3(signature = (path=None, create=false))]
+    fn new(path: Option<&str>, create: bool) -> PyResult<Self> {
+        let backing = match path {
+            Some(path) => Backing::File(FileStore::open(path, create)?),
+            None => Backing::Memory(HashMap::new()),
+        };
+        Ok(SharedSeriesStore { backing })
+    }
+
+    /// Store a copy of `values` under `key`, replacing any series already there
+    fn put(&mut self, py: Python<'_>, key: &str, values: NumericColumn<'_>) -> PyResult<()> {
+        match &mut self.backing {
+            Backing::Memory(series) => {
+                let array = match values {
+                    NumericColumn::Float(column) => PyArray1::from_vec_bound(py, column.values()?.into_owned()).into_any(),
+                    NumericColumn::Int(column) => PyArray1::from_vec_bound(py, column.values()?.into_owned()).into_any(),
+                };
+                array.call_method("setflags", (), Some(&[("write", false)].into_py_dict_bound(py)))?;
+                series.insert(key.to_string(), array.unbind());
+            }
+            Backing::File(file) => {
+                let (dtype, bytes, len) = match values {
+                    NumericColumn::Float(column) => {
+                        let values = column.values()?;
+                        (Dtype::Float64, values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>(), values.len())
+                    }
+                    NumericColumn::Int(column) => {
+                        let values = column.values()?;
+                        (Dtype::Int64, values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>(), values.len())
+                    }
+                };
+                file.append(key, dtype, len, &bytes)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read-only numpy view of the series under `key`
+    fn get(slf: &Bound<'_, Self>, key: &str) -> PyResult<PyObject> {
+        let missing = || pyo3::exceptions::PyKeyError::new_err(key.to_string());
+        let (dtype, data, len) = {
+            let mut store = slf.borrow_mut();
+            match &mut store.backing {
+                Backing::Memory(series) => return series.get(key).map(|a| a.clone_ref(slf.py())).ok_or_else(missing),
+                Backing::File(file) => {
+                    file.refresh()?;
+                    let located = file.index.get(key).copied().ok_or_else(missing)?;
+                    (located.dtype, file.maps[located.map][located.offset..].as_ptr(), located.len)
+                }
+            }
+        };
+        match dtype {
+            Dtype::Int64 => Self::file_view::<i64>(slf, data, len),
+            _ => Self::file_view::<f64>(slf, data, len),
+        }
+    }
+
+    fn __getitem__(slf: &Bound<'_, Self>, key: &str) -> PyResult<PyObject> {
+        Self::get(slf, key)
+    }
+
+    fn __setitem__(&mut self, py: Python<'_>, key: &str, values: NumericColumn<'_>) -> PyResult<()> {
+        self.put(py, key, values)
+    }
+
+    /// Remove the series under `key`; views already taken stay valid
+    fn __delitem__(&mut self, key: &str) -> PyResult<()> {
+        let missing = || pyo3::exceptions::PyKeyError::new_err(key.to_string());
+        match &mut self.backing {
+            Backing::Memory(series) => series.remove(key).map(|_| ()).ok_or_else(missing),
+            Backing::File(file) => {
+                file.refresh()?;
+                if !file.index.contains_key(key) {
+                    return Err(missing());
+                }
+                file.append(key, Dtype::Removed, 0, &[])
+            }
+        }
+    }
+
+    fn __contains__(&mut self, key: &str) -> PyResult<bool> {
+        Ok(match &mut self.backing {
+            Backing::Memory(series) => series.contains_key(key),
+            Backing::File(file) => {
+                file.refresh()?;
+                file.index.contains_key(key)
+            }
+        })
+    }
+
+    fn __len__(&mut self) -> PyResult<usize> {
+        Ok(self.keys()?.len())
+    }
+
+    /// The stored keys, sorted
+    fn keys(&mut self) -> PyResult<Vec<String>> {
+        let mut keys: Vec<String> = match &mut self.backing {
+            Backing::Memory(series) => series.keys().cloned().collect(),
+            Backing::File(file) => {
+                file.refresh()?;
+                file.index.keys().cloned().collect()
+            }
+        };
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// The backing file, or None for an in-process store
+    #[getter]
+    fn path(&self) -> Option<String> {
+        match &self.backing {
+            Backing::Memory(_) => None,
+            Backing::File(file) => Some(file.path.clone()),
+        }
+    }
+
+    fn __repr__(&mut self) -> PyResult<String> {
+        let n = self.__len__()?;
+        Ok(match self.path() {
+            Some(path) => format!("SharedSeriesStore({:?}, series={})", path, n),
+            None => format!("SharedSeriesStore(series={})", n),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("fast_math_{}_{}.series", name, std::process::id()));
+        path.to_str().unwrap().to_string()
+    }
+
+    fn put(store: &mut FileStore, key: &str, values: &[f64]) {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        store.append(key, Dtype::Float64, values.len(), &bytes).unwrap();
+    }
+
+    fn get(store: &FileStore, key: &str) -> Option<Vec<f64>> {
+        let located = store.index.get(key)?;
+        let bytes = &store.maps[located.map][located.offset..located.offset + 8 * located.len];
+        Some(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect())
+    }
+
+    #[test]
+    fn series_survive_replacement_removal_and_reopening() {
+        pyo3::prepare_freethreaded_python();
+        let path = temp_path("round_trip");
+        let mut store = FileStore::open(&path, true).unwrap();
+        put(&mut store, "close", &[1.0, 2.0, 3.0]);
+        put(&mut store, "a_key_longer_than_eight", &[4.0]);
+        put(&mut store, "close", &[5.0, 6.0]);
+        store.append("a_key_longer_than_eight", Dtype::Removed, 0, &[]).unwrap();
+        put(&mut store, "empty", &[]);
+        assert_eq!(get(&store, "close"), Some(vec![5.0, 6.0]));
+        assert_eq!(get(&store, "a_key_longer_than_eight"), None);
+
+        let reopened = FileStore::open(&path, false).unwrap();
+        let mut keys: Vec<_> = reopened.index.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, ["close", "empty"]);
+        assert_eq!(get(&reopened, "close"), Some(vec![5.0, 6.0]));
+        assert_eq!(get(&reopened, "empty"), Some(vec![]));
+        // A second handle sees what the first appends on its next refresh
+        let mut reader = reopened;
+        put(&mut store, "volume", &[7.0]);
+        reader.refresh().unwrap();
+        assert_eq!(get(&reader, "volume"), Some(vec![7.0]));
+        drop((store, reader));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        pyo3::prepare_freethreaded_python();
+        let path = temp_path("truncated");
+        let mut store = FileStore::open(&path, true).unwrap();
+        put(&mut store, "close", &[1.0; 100]);
+        drop(store);
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(HEADER_BYTES as u64 + 64).unwrap();
+        drop(file);
+        let err = FileStore::open(&path, false).err().unwrap();
+        assert!(err.to_string().contains("shorter than its committed length"), "{}", err);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// Thresholds and limits of the entry / exit state machine
+struct Hysteresis {
+    entry: f64,
+    exit: f64,
+    size: f64,
+    cooldown: usize,
+    allow_short: bool,
+}
+
+impl Hysteresis {
+    /// Direction (-1, 0 or 1) held after each bar. A position closes once the signal falls
+    /// back through the exit threshold and a new one opens past the entry threshold,
+    /// `cooldown` bars or more after the last close; NaN holds the current direction.
+    fn run(&self, signal: &[f64]) -> Vec<f64> {
+        let mut direction = 0.0;
+        let mut closed_at: Option<usize> = None;
+        signal
+            .iter()
+            .enumerate()
+            .map(|(t, &s)| {
+                if s.is_nan() {
+                    return direction * self.size;
+                }
+                if (direction > 0.0 && s <= self.exit) || (direction < 0.0 && s >= -self.exit) {
+                    direction = 0.0;
+                    closed_at = Some(t);
+                }
+                let ready = closed_at.is_none_or(|c| t >= c + self.cooldown);
+                if direction == 0.0 && ready {
+                    if s > self.entry {
+                        direction = 1.0;
+                    } else if s < -self.entry && self.allow_short {
+                        direction = -1.0;
+                    }
+                }
+                direction * self.size
+            })
+            .collect()
+    }
+}
+
+/// Turn a signal into target positions with entry / exit hysteresis.
+///
+/// A long of `max_position` opens when `signal` rises above `entry_threshold` and is
+/// held until it falls to `exit_threshold` or below; a short opens below
+/// `-entry_threshold` (unless `allow_short` is false) and closes at `-exit_threshold` or
+/// above. Between the thresholds the position is held, so a signal hovering at one
+/// threshold does not flip the position every bar. After a position closes no new one
+/// opens for `cooldown` bars, so with the default 0 a position can close and reverse in
+/// the same bar. NaN signals hold the position. Returns the position after each bar,
+/// ready to be lagged by one bar and passed to a backtest.
+#[pyfunction]
+#[pyo3(signature = (signal, entry_threshold, exit_threshold=0.0, max_position=1.0, cooldown=0, allow_short=true))]
+pub fn signals_to_positions_rust<'py>(
+    py: Python<'py>,
+    signal: Column<'py, f64>,
+    entry_threshold: f64,
+    exit_threshold: f64,
+    max_position: f64,
+    cooldown: usize,
+    allow_short: bool
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    if entry_threshold.is_nan() || exit_threshold.is_nan() || exit_threshold > entry_threshold {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "exit_threshold must be <= entry_threshold"
+        ));
+    }
+    if max_position.is_nan() || max_position <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "max_position must be > 0"
+        ));
+    }
+    let signal = signal.values()?;
+    let machine = Hysteresis {
+        entry: entry_threshold,
+        exit: exit_threshold,
+        size: max_position,
+        cooldown,
+        allow_short,
+    };
+    let positions = py.allow_threads(|| machine.run(&signal));
+    Ok(PyArray1::from_vec_bound(py, positions))
+}
@@ -0,0 +1,227 @@
+// Sum and dot-product inner loops with explicit SIMD paths, chosen at runtime: AVX2 on
+// x86_64, NEON on aarch64, scalar everywhere else. Every path keeps the same fixed set of
+// lane accumulators and combines them in the same order (the scalar module spells it
+// out), so results are bit-identical whichever path runs, on any machine. They can still
+// differ from a sequential sum in the last bits.
+
+/// Sum of `values`
+pub fn sum_f64(values: &[f64]) -> f64 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: AVX2 support was just checked
+        return unsafe { x86::sum_f64(values) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        // Safety: NEON support was just checked
+        return unsafe { arm::sum_f64(values) };
+    }
+    scalar::sum_f64(values)
+}
+
+/// Dot product of two slices of the same length
+pub fn dot_f64(x: &[f64], y: &[f64]) -> f64 {
+    assert_eq!(x.len(), y.len());
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: AVX2 support was just checked
+        return unsafe { x86::dot_f64(x, y) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        // Safety: NEON support was just checked
+        return unsafe { arm::dot_f64(x, y) };
+    }
+    scalar::dot_f64(x, y)
+}
+
+/// Sum of `values`
+pub fn sum_f32(values: &[f32]) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: AVX2 support was just checked
+        return unsafe { x86::sum_f32(values) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        // Safety: NEON support was just checked
+        return unsafe { arm::sum_f32(values) };
+    }
+    scalar::sum_f32(values)
+}
+
+/// The reference order. Sums keep `2 * HALF` lanes, lane `i` taking every element at an
+/// index `≡ i` modulo the lane count; lane `j` and `j + HALF` are then added, the `HALF`
+/// results summed left to right, and the leftover tail (summed left to right) added last.
+/// Dot products do the same with `DOT_LANES` lanes and no halving step.
+mod scalar {
+    /// Half the lanes of an f64 sum
+    const HALF_F64: usize = 4;
+    /// Half the lanes of an f32 sum
+    const HALF_F32: usize = 8;
+    /// Lanes of an f64 dot product
+    const DOT_LANES: usize = 4;
+
+    pub fn sum_f64(values: &[f64]) -> f64 {
+        let chunks = values.chunks_exact(2 * HALF_F64);
+        let tail: f64 = chunks.remainder().iter().sum();
+        let mut acc = [0.0; 2 * HALF_F64];
+        for chunk in chunks {
+            acc.iter_mut().zip(chunk).for_each(|(a, &v)| *a += v);
+        }
+        let lanes: [f64; HALF_F64] = std::array::from_fn(|j| acc[j] + acc[j + HALF_F64]);
+        lanes.iter().sum::<f64>() + tail
+    }
+
+    pub fn dot_f64(x: &[f64], y: &[f64]) -> f64 {
+        let (xs, ys) = (x.chunks_exact(DOT_LANES), y.chunks_exact(DOT_LANES));
+        let tail: f64 = xs.remainder().iter().zip(ys.remainder()).map(|(a, b)| a * b).sum();
+        let mut acc = [0.0; DOT_LANES];
+        for (cx, cy) in xs.zip(ys) {
+            acc.iter_mut().zip(cx.iter().zip(cy)).for_each(|(a, (u, v))| *a += u * v);
+        }
+        acc.iter().sum::<f64>() + tail
+    }
+
+    pub fn sum_f32(values: &[f32]) -> f32 {
+        let chunks = values.chunks_exact(2 * HALF_F32);
+        let tail: f32 = chunks.remainder().iter().sum();
+        let mut acc = [0.0; 2 * HALF_F32];
+        for chunk in chunks {
+            acc.iter_mut().zip(chunk).for_each(|(a, &v)| *a += v);
+        }
+        let lanes: [f32; HALF_F32] = std::array::from_fn(|j| acc[j] + acc[j + HALF_F32]);
+        lanes.iter().sum::<f32>() + tail
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn hsum_pd(v: __m256d) -> f64 {
+        let mut lanes = [0.0; 4];
+        _mm256_storeu_pd(lanes.as_mut_ptr(), v);
+        lanes.iter().sum()
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn hsum_ps(v: __m256) -> f32 {
+        let mut lanes = [0.0; 8];
+        _mm256_storeu_ps(lanes.as_mut_ptr(), v);
+        lanes.iter().sum()
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sum_f64(values: &[f64]) -> f64 {
+        let chunks = values.chunks_exact(8);
+        let tail: f64 = chunks.remainder().iter().sum();
+        let (mut a, mut b) = (_mm256_setzero_pd(), _mm256_setzero_pd());
+        for chunk in chunks {
+            a = _mm256_add_pd(a, _mm256_loadu_pd(chunk.as_ptr()));
+            b = _mm256_add_pd(b, _mm256_loadu_pd(chunk.as_ptr().add(4)));
+        }
+        hsum_pd(_mm256_add_pd(a, b)) + tail
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn dot_f64(x: &[f64], y: &[f64]) -> f64 {
+        let (xs, ys) = (x.chunks_exact(4), y.chunks_exact(4));
+        let tail: f64 = xs.remainder().iter().zip(ys.remainder()).map(|(a, b)| a * b).sum();
+        let mut acc = _mm256_setzero_pd();
+        for (cx, cy) in xs.zip(ys) {
+            acc = _mm256_add_pd(acc, _mm256_mul_pd(_mm256_loadu_pd(cx.as_ptr()), _mm256_loadu_pd(cy.as_ptr())));
+        }
+        hsum_pd(acc) + tail
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sum_f32(values: &[f32]) -> f32 {
+        let chunks = values.chunks_exact(16);
+        let tail: f32 = chunks.remainder().iter().sum();
+        let (mut a, mut b) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+        for chunk in chunks {
+            a = _mm256_add_ps(a, _mm256_loadu_ps(chunk.as_ptr()));
+            b = _mm256_add_ps(b, _mm256_loadu_ps(chunk.as_ptr().add(8)));
+        }
+        hsum_ps(_mm256_add_ps(a, b)) + tail
+    }
+}
+
+// NEON registers are half as wide as AVX2's, so each AVX2 register is split across two
+#[cfg(target_arch = "aarch64")]
+mod arm {
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    unsafe fn hsum_pd(lo: float64x2_t, hi: float64x2_t) -> f64 {
+        let mut lanes = [0.0; 4];
+        vst1q_f64(lanes.as_mut_ptr(), lo);
+        vst1q_f64(lanes.as_mut_ptr().add(2), hi);
+        lanes.iter().sum()
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn hsum_ps(lo: float32x4_t, hi: float32x4_t) -> f32 {
+        let mut lanes = [0.0; 8];
+        vst1q_f32(lanes.as_mut_ptr(), lo);
+        vst1q_f32(lanes.as_mut_ptr().add(4), hi);
+        lanes.iter().sum()
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn sum_f64(values: &[f64]) -> f64 {
+        let chunks = values.chunks_exact(8);
+        let tail: f64 = chunks.remainder().iter().sum();
+        let mut acc = [vdupq_n_f64(0.0); 4];
+        for chunk in chunks {
+            for (k, lane) in acc.iter_mut().enumerate() {
+                *lane = vaddq_f64(*lane, vld1q_f64(chunk.as_ptr().add(2 * k)));
+            }
+        }
+        hsum_pd(vaddq_f64(acc[0], acc[2]), vaddq_f64(acc[1], acc[3])) + tail
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot_f64(x: &[f64], y: &[f64]) -> f64 {
+        let (xs, ys) = (x.chunks_exact(4), y.chunks_exact(4));
+        let tail: f64 = xs.remainder().iter().zip(ys.remainder()).map(|(a, b)| a * b).sum();
+        let (mut lo, mut hi) = (vdupq_n_f64(0.0), vdupq_n_f64(0.0));
+        for (cx, cy) in xs.zip(ys) {
+            lo = vaddq_f64(lo, vmulq_f64(vld1q_f64(cx.as_ptr()), vld1q_f64(cy.as_ptr())));
+            hi = vaddq_f64(hi, vmulq_f64(vld1q_f64(cx.as_ptr().add(2)), vld1q_f64(cy.as_ptr().add(2))));
+        }
+        hsum_pd(lo, hi) + tail
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn sum_f32(values: &[f32]) -> f32 {
+        let chunks = values.chunks_exact(16);
+        let tail: f32 = chunks.remainder().iter().sum();
+        let mut acc = [vdupq_n_f32(0.0); 4];
+        for chunk in chunks {
+            for (k, lane) in acc.iter_mut().enumerate() {
+                *lane = vaddq_f32(*lane, vld1q_f32(chunk.as_ptr().add(4 * k)));
+            }
+        }
+        hsum_ps(vaddq_f32(acc[0], acc[2]), vaddq_f32(acc[1], acc[3])) + tail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_identical_to_scalar_order() {
+        for n in [0, 1, 3, 7, 8, 15, 16, 17, 100, 1001] {
+            let x: Vec<f64> = (0..n).map(|i| (i as f64 * 0.37).sin() * 1e3).collect();
+            let y: Vec<f64> = (0..n).map(|i| (i as f64 * 0.11).cos()).collect();
+            let xf: Vec<f32> = x.iter().map(|&v| v as f32).collect();
+            assert_eq!(sum_f64(&x).to_bits(), scalar::sum_f64(&x).to_bits(), "n = {}", n);
+            assert_eq!(dot_f64(&x, &y).to_bits(), scalar::dot_f64(&x, &y).to_bits(), "n = {}", n);
+            assert_eq!(sum_f32(&xf).to_bits(), scalar::sum_f32(&xf).to_bits(), "n = {}", n);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use pyo3::prelude::*;
+use numpy::PyArray1;
+use rayon::prelude::*;
+
+use crate::column::{self, Column, Series};
+use crate::threads;
+
+/// How `find_analogs_rust` compares windows
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Euclidean,
+    /// One minus the Pearson correlation
+    Correlation,
+    Dtw,
+}
+
+impl Metric {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "euclidean" => Ok(Metric::Euclidean),
+            "correlation" => Ok(Metric::Correlation),
+            "dtw" => Ok(Metric::Dtw),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown metric '{}', expected 'euclidean', 'correlation' or 'dtw'", name
+            ))),
+        }
+    }
+}
+
+/// Dynamic time warping distance between `a` and `b`: the square root of the smallest
+/// total squared difference over warping paths, with cells `(i, j)` limited to
+/// `|i - j| <= window` (Sakoe-Chiba), widened to the length difference so a path exists.
+/// Two rows of the cost matrix are kept. NaN anywhere gives NaN.
+pub fn dtw(a: &[f64], b: &[f64], window: Option<usize>) -> f64 {
+    let (n, m) = (a.len(), b.len());
+    if n == 0 || m == 0 {
+        return if n == m { 0.0 } else { f64::INFINITY };
+    }
+    let band = window.unwrap_or(n.max(m)).max(n.abs_diff(m));
+    let mut previous = vec![f64::INFINITY; m + 1];
+    let mut current = vec![f64::INFINITY; m + 1];
+    previous[0] = 0.0;
+    for i in 1..=n {
+        current.fill(f64::INFINITY);
+        let (lo, hi) = (i.saturating_sub(band).max(1), (i + band).min(m));
+        for j in lo..=hi {
+            let cost = (a[i - 1] - b[j - 1]) * (a[i - 1] - b[j - 1]);
+            current[j] = cost + previous[j - 1].min(previous[j]).min(current[j - 1]);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[m].sqrt()
+}
+
+/// Dynamic time warping distance between two series, for shape-based pattern matching.
+///
+/// The distance is the square root of the least total squared difference over all
+/// alignments that warp the series in time, so two series of equal length with
+/// `window=0` give their Euclidean distance. `window` is the Sakoe-Chiba band: aligned
+/// points may be at most that many steps apart (widened to the length difference of the
+/// series); None allows any warping. Narrow bands are faster and stop pathological
+/// alignments. `b` may be a 2D array with one candidate per row, such as thousands of
+/// historical windows, compared with `a` in parallel and returned as one distance per
+/// row. NaN values give NaN.
+#[pyfunction]
+#[pyo3(signature = (a, b, window=None))]
+pub fn dtw_rust<'py>(
+    py: Python<'py>,
+    a: Column<'py, f64>,
+    b: Series<'py, f64>,
+    window: Option<usize>
+) -> PyResult<PyObject> {
+    let a = a.values()?;
+    let distance = |row: &[f64]| {
+        if a.iter().chain(row).any(|v| v.is_nan()) { f64::NAN } else { dtw(&a, row, window) }
+    };
+    match b {
+        Series::Single(column) => {
+            let b = column.values()?;
+            Ok(py.allow_threads(|| distance(&b)).into_py(py))
+        }
+        batch => {
+            let input = batch.rows()?;
+            let batch = input.view();
+            let rows = py.allow_threads(|| column::map_rows(batch, |_, row| Ok(distance(row))))?;
+            Ok(PyArray1::from_vec_bound(py, rows).into_any().unbind())
+        }
+    }
+}
+
+/// `values` shifted and scaled to mean 0 and standard deviation 1; a constant window
+/// becomes all zeros
+fn znormalize(values: &[f64]) -> Vec<f64> {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let std = (values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n).sqrt();
+    let scale = if std > 0.0 { 1.0 / std } else { 0.0 };
+    values.iter().map(|v| (v - mean) * scale).collect()
+}
+
+/// Distance from `window` to `query`, which is already z-normalised when `window` is to be
+/// (with `normalize` or for correlation)
+fn distance(query: &[f64], window: &[f64], metric: Metric, normalize: bool, band: Option<usize>) -> f64 {
+    if window.iter().any(|v| v.is_nan()) {
+        return f64::NAN;
+    }
+    let normalized;
+    let window = if normalize || metric == Metric::Correlation {
+        normalized = znormalize(window);
+        &normalized[..]
+    } else {
+        window
+    };
+    match metric {
+        Metric::Euclidean => query.iter().zip(window).map(|(q, w)| (q - w) * (q - w)).sum::<f64>().sqrt(),
+        // Z-normalised series have a correlation of their mean product
+        Metric::Correlation => {
+            1.0 - query.iter().zip(window).map(|(q, w)| q * w).sum::<f64>() / query.len() as f64
+        }
+        Metric::Dtw => dtw(query, window, band),
+    }
+}
+
+/// Find the `top_k` stretches of history that look most like a query window.
+///
+/// Every window of `history` as long as `query_window` is compared with it in parallel
+/// by `metric`: "euclidean" (the default) distance, "correlation" distance (one minus
+/// the Pearson correlation, between 0 and 2, insensitive to level and scale) or "dtw",
+/// the dynamic time warping distance of `dtw_rust` within a Sakoe-Chiba band of `band`
+/// steps (None for unconstrained). With `normalize=True` (the default) the query and
+/// each window are z-normalised first, so shapes are matched whatever the price level.
+/// Matches are picked best first, skipping any starting within `exclusion` bars of one
+/// already picked (default the query length, so matches do not overlap), and windows
+/// holding NaN are ignored. Returns `(indices, distances)`: the start index of each
+/// match in `history` and its distance, nearest first.
+#[pyfunction]
+#[pyo3(signature = (query_window, history, top_k=10, metric="euclidean", normalize=true, band=None, exclusion=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn find_analogs_rust<'py>(
+    py: Python<'py>,
+    query_window: Column<'py, f64>,
+    history: Column<'py, f64>,
+    top_k: usize,
+    metric: &str,
+    normalize: bool,
+    band: Option<usize>,
+    exclusion: Option<usize>
+) -> PyResult<PyObject> {
+    let metric = Metric::parse(metric)?;
+    let (query, history) = (query_window.values()?, history.values()?);
+    let m = query.len();
+    if m < 2 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "query_window must hold at least 2 values"
+        ));
+    }
+    if query.iter().any(|v| v.is_nan()) {
+        return Err(crate::errors::NaNInputError::new_err(
+            "query_window contains NaN"
+        ));
+    }
+    let exclusion = exclusion.unwrap_or(m);
+    let (indices, distances): (Vec<i64>, Vec<f64>) = py.allow_threads(|| {
+        let query = if normalize || metric == Metric::Correlation { znormalize(&query) } else { query.to_vec() };
+        let starts = (history.len() + 1).saturating_sub(m);
+        let mut scored: Vec<(usize, f64)> = threads::install(|| {
+            (0..starts)
+                .into_par_iter()
+                .map(|i| (i, distance(&query, &history[i..i + m], metric, normalize, band)))
+                .filter(|(_, d)| !d.is_nan())
+                .collect()
+        });
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        let mut picked: Vec<(usize, f64)> = Vec::with_capacity(top_k);
+        for (start, d) in scored {
+            if picked.len() == top_k {
+                break;
+            }
+            if picked.iter().all(|&(p, _)| p.abs_diff(start) >= exclusion) {
+                picked.push((start, d));
+            }
+        }
+        picked.into_iter().map(|(i, d)| (i as i64, d)).unzip()
+    });
+    Ok((PyArray1::from_vec_bound(py, indices), PyArray1::from_vec_bound(py, distances)).into_py(py))
+}
@@ -0,0 +1,319 @@
+use pyo3::prelude::*;
+
+use crate::column::{check_out, FloatElement, SeriesInput};
+use crate::pool::Buffer;
+use crate::DRIFT_INTERVAL;
+
+/// What a kernel does with NaN inputs, given as `nan_policy`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Reject inputs containing NaN with a `ValueError`
+    Raise,
+    /// NaN inputs make every result that depends on them NaN
+    Propagate,
+    /// NaN inputs are skipped, subject to `min_periods`
+    Omit,
+}
+
+impl NanPolicy {
+    pub fn name(self) -> &'static str {
+        match self {
+            NanPolicy::Raise => "raise",
+            NanPolicy::Propagate => "propagate",
+            NanPolicy::Omit => "omit",
+        }
+    }
+
+    /// The policy from `nan_policy`, or from the older `skipna` flag when it is not given,
+    /// or the configured default when neither is
+    pub fn resolve(policy: Option<NanPolicy>, skipna: bool) -> PyResult<NanPolicy> {
+        match policy {
+            None if skipna => Ok(NanPolicy::Omit),
+            None => Ok(crate::config::default_nan_policy()),
+            Some(policy) if skipna && policy != NanPolicy::Omit => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "skipna=True conflicts with nan_policy='{}'", policy.name()
+            ))),
+            Some(policy) => Ok(policy),
+        }
+    }
+
+    /// Under `Raise`, fail if `values` contain NaN
+    pub fn check<T: FloatElement>(self, values: &[T]) -> PyResult<()> {
+        if self == NanPolicy::Raise {
+            if let Some(i) = values.iter().position(|v| v.is_nan()) {
+                return Err(crate::errors::NaNInputError::new_err(format!(
+                    "Input contains NaN at index {} (nan_policy='raise')", i
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Under `Raise`, fail if a series or batch contains NaN
+    pub fn check_input(self, data: &SeriesInput<'_>) -> PyResult<()> {
+        if self == NanPolicy::Raise && data.has_nan()? {
+            return Err(crate::errors::NaNInputError::new_err(
+                "Input contains NaN (nan_policy='raise')"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<'py> FromPyObject<'py> for NanPolicy {
+    fn extract_bound(value: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let name: String = value.extract()?;
+        match name.to_ascii_lowercase().as_str() {
+            "raise" => Ok(NanPolicy::Raise),
+            "propagate" => Ok(NanPolicy::Propagate),
+            "omit" => Ok(NanPolicy::Omit),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown nan_policy '{}', expected 'raise', 'propagate' or 'omit'", name
+            ))),
+        }
+    }
+}
+
+/// Resolve `min_periods`, defaulting to `default` and bounded by the window length
+pub fn min_periods(min_periods: Option<usize>, default: usize, window: usize) -> PyResult<usize> {
+    let min_periods = min_periods.unwrap_or(default);
+    if min_periods == 0 || min_periods > window {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "min_periods must be between 1 and {}", window
+        )));
+    }
+    Ok(min_periods)
+}
+
+/// Moving average over the non-NaN values of every full window; NaN where a window has
+/// fewer than `min_periods` valid values
+pub fn moving_average<T: FloatElement>(slice: &[T], window: usize, min_periods: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); (slice.len() + 1).saturating_sub(window)];
+    moving_average_into(slice, window, min_periods, &mut result)?;
+    Ok(result)
+}
+
+/// `moving_average` written into `out`, which must hold `len - window + 1` values
+pub fn moving_average_into<T: FloatElement>(
+    slice: &[T],
+    window: usize,
+    min_periods: usize,
+    out: &mut [T]
+) -> PyResult<()> {
+    let n = slice.len();
+    let valid = crate::errors::check_window(window, n)?;
+    check_out(out, valid)?;
+    if window == 1 {
+        // A one-value window is the input itself, which sliding sums would round
+        out.copy_from_slice(slice);
+        return Ok(());
+    }
+
+    let mut sum = T::zero();
+    let mut count = 0usize;
+    for i in 0..n {
+        if !slice[i].is_nan() {
+            sum = sum + slice[i];
+            count += 1;
+        }
+        if i >= window && !slice[i - window].is_nan() {
+            sum = sum - slice[i - window];
+            count -= 1;
+        }
+        if i + 1 >= window {
+            out[i + 1 - window] = if count >= min_periods { sum / T::from(count).unwrap() } else { T::nan() };
+        }
+    }
+    crate::validate::check("skipna.moving_average", out, || {
+        crate::validate::reference::moving_average(slice, window, min_periods)
+    });
+    Ok(())
+}
+
+/// Rolling standard deviation over the non-NaN values of every full window; NaN where a
+/// window has fewer than `min_periods` valid values, or no more than `ddof`
+pub fn rolling_std<T: FloatElement>(slice: &[T], window: usize, ddof: usize, min_periods: usize) -> PyResult<Vec<T>> {
+    let n = slice.len();
+    let valid = crate::errors::check_window(window, n)?;
+    crate::errors::check_ddof(ddof, window)?;
+    if valid == 0 {
+        return Ok(Vec::new());
+    }
+    if window == 1 {
+        // One value has no spread, but sliding sums would leave rounding residue
+        return Ok(slice.iter().map(|v| if v.is_nan() { T::nan() } else { T::zero() }).collect());
+    }
+
+    let sums = |values: &[T], shift: T| {
+        values.iter().filter(|v| !v.is_nan()).fold((T::zero(), T::zero(), 0usize), |(sum, sum_sq, count), &v| {
+            let d = v - shift;
+            (sum + d, sum_sq + d * d, count + 1)
+        })
+    };
+    let mut result = Vec::with_capacity(valid);
+    let (mut shift, mut sum, mut sum_sq, mut count) = (T::zero(), T::zero(), T::zero(), 0usize);
+    for i in 0..=n - window {
+        if i % DRIFT_INTERVAL == 0 {
+            // Shifting by a value near the window keeps the sum of squares well conditioned
+            let values = &slice[i..i + window];
+            shift = values.iter().copied().find(|v| !v.is_nan()).unwrap_or(T::zero());
+            (sum, sum_sq, count) = sums(values, shift);
+        } else {
+            let (old, new) = (slice[i - 1] - shift, slice[i + window - 1] - shift);
+            if !new.is_nan() {
+                sum = sum + new;
+                sum_sq = sum_sq + new * new;
+                count += 1;
+            }
+            if !old.is_nan() {
+                sum = sum - old;
+                sum_sq = sum_sq - old * old;
+                count -= 1;
+            }
+        }
+        result.push(if count < min_periods || count <= ddof {
+            T::nan()
+        } else {
+            let (total, dof) = (T::from(count).unwrap(), T::from(count - ddof).unwrap());
+            ((sum_sq - sum * sum / total) / dof).max(T::zero()).sqrt()
+        });
+    }
+    crate::validate::check("skipna.rolling_std", &result, || {
+        crate::validate::reference::rolling_std(slice, window, ddof, min_periods)
+    });
+    Ok(result)
+}
+
+/// RSI over the valid changes (both prices non-NaN) of the last `period` changes; NaN
+/// where fewer than `min_periods` changes are valid
+pub fn rsi<T: FloatElement>(slice: &[T], period: usize, min_periods: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); slice.len().saturating_sub(period)];
+    rsi_into(slice, period, min_periods, &mut result)?;
+    Ok(result)
+}
+
+/// `rsi` written into `out`, which must hold `len - period` values
+pub fn rsi_into<T: FloatElement>(slice: &[T], period: usize, min_periods: usize, out: &mut [T]) -> PyResult<()> {
+    let n = slice.len();
+    let valid = crate::errors::check_period(period, n)?;
+    check_out(out, valid)?;
+
+    let changes: Buffer<T> = slice.windows(2).map(|w| w[1] - w[0]).collect();
+    let hundred = T::from(100.0).unwrap();
+    let (mut gains, mut losses, mut count) = (T::zero(), T::zero(), 0usize);
+
+    for (i, &change) in changes.iter().enumerate() {
+        if !change.is_nan() {
+            if change > T::zero() {
+                gains = gains + change;
+            } else {
+                losses = losses - change;
+            }
+            count += 1;
+        }
+        if i >= period {
+            let old = changes[i - period];
+            if !old.is_nan() {
+                if old > T::zero() {
+                    gains = gains - old;
+                } else {
+                    losses = losses + old;
+                }
+                count -= 1;
+            }
+        }
+        if i + 1 >= period {
+            out[i + 1 - period] = if count < min_periods {
+                T::nan()
+            } else if losses <= T::zero() {
+                hundred
+            } else {
+                hundred - hundred / (T::one() + gains / losses)
+            };
+        }
+    }
+    crate::validate::check("skipna.rsi", out, || crate::validate::reference::rsi(slice, period, min_periods));
+    Ok(())
+}
+
+/// Wilder-smoothed RSI skipping changes that touch a NaN, which leave the averages
+/// unchanged. The averages are the mean of the valid changes seen so far until `period`
+/// of them have been seen, then recursive; NaN until `min_periods` valid changes.
+pub fn wilder_rsi<T: FloatElement>(slice: &[T], period: usize, min_periods: usize) -> PyResult<Vec<T>> {
+    let mut result = vec![T::zero(); slice.len().saturating_sub(period)];
+    wilder_rsi_into(slice, period, min_periods, &mut result)?;
+    Ok(result)
+}
+
+/// `wilder_rsi` written into `out`, which must hold `len - period` values
+pub fn wilder_rsi_into<T: FloatElement>(slice: &[T], period: usize, min_periods: usize, out: &mut [T]) -> PyResult<()> {
+    let n = slice.len();
+    let valid = crate::errors::check_period(period, n)?;
+    check_out(out, valid)?;
+
+    let hundred = T::from(100.0).unwrap();
+    let (mut avg_gain, mut avg_loss, mut seen) = (T::zero(), T::zero(), 0usize);
+    for i in 1..n {
+        let change = slice[i] - slice[i - 1];
+        if !change.is_nan() {
+            let (gain, loss) = (change.max(T::zero()), (-change).max(T::zero()));
+            seen += 1;
+            // A running mean while seeding, then (avg * (period - 1) + value) / period
+            let weight = T::from(seen.min(period)).unwrap();
+            avg_gain = avg_gain + (gain - avg_gain) / weight;
+            avg_loss = avg_loss + (loss - avg_loss) / weight;
+        }
+        if i >= period {
+            out[i - period] = if seen < min_periods {
+                T::nan()
+            } else if avg_loss <= T::zero() {
+                hundred
+            } else {
+                hundred - hundred / (T::one() + avg_gain / avg_loss)
+            };
+        }
+    }
+    crate::validate::check("skipna.wilder_rsi", out, || {
+        crate::validate::reference::wilder_rsi(slice, period, min_periods, false)
+    });
+    Ok(())
+}
+
+/// Pearson correlation over pairwise-complete observations; NaN with fewer than
+/// `min_periods` complete pairs
+pub fn correlation<T: FloatElement>(x: &[T], y: &[T], min_periods: usize) -> PyResult<T> {
+    if x.len() != y.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "Arrays must have the same length"
+        ));
+    }
+
+    // Accumulated in f64 whatever the input precision
+    let pairs = || {
+        x.iter()
+            .zip(y.iter())
+            .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+            .map(|(a, b)| (a.to_f64().unwrap(), b.to_f64().unwrap()))
+    };
+    let count = pairs().count();
+    if count < min_periods.max(2) {
+        return Ok(T::nan());
+    }
+    let n = count as f64;
+    let mean_x = pairs().map(|(a, _)| a).sum::<f64>() / n;
+    let mean_y = pairs().map(|(_, b)| b).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (a, b) in pairs() {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+
+    let denominator = (var_x * var_y).sqrt();
+    let result = if denominator == 0.0 { T::zero() } else { T::from(cov / denominator).unwrap() };
+    crate::validate::check("skipna.correlation", &[result], || {
+        crate::validate::reference::correlation(x, y, Some(min_periods))
+    });
+    Ok(result)
+}
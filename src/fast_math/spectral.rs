@@ -0,0 +1,171 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use rayon::prelude::*;
+
+use crate::alignment::Alignment;
+use crate::column::Column;
+use crate::config::FastMathConfig;
+use crate::{fft, threads};
+
+/// What is removed from a signal before its spectrum is taken
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Detrend {
+    None,
+    /// The mean
+    Constant,
+    /// The least-squares line
+    Linear,
+}
+
+impl Detrend {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Ok(Detrend::None),
+            "constant" | "mean" => Ok(Detrend::Constant),
+            "linear" => Ok(Detrend::Linear),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown detrend '{}', expected 'none', 'constant' or 'linear'", name
+            ))),
+        }
+    }
+
+    fn apply(self, values: &mut [f64]) {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        match self {
+            Detrend::None => {}
+            Detrend::Constant => values.iter_mut().for_each(|v| *v -= mean),
+            Detrend::Linear => {
+                let centre = (n - 1.0) / 2.0;
+                let (mut sxy, mut sxx) = (0.0, 0.0);
+                for (t, v) in values.iter().enumerate() {
+                    let x = t as f64 - centre;
+                    sxy += x * (v - mean);
+                    sxx += x * x;
+                }
+                let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+                for (t, v) in values.iter_mut().enumerate() {
+                    *v -= mean + slope * (t as f64 - centre);
+                }
+            }
+        }
+    }
+}
+
+/// One-sided periodogram `|X_k|^2 / n` for `k = 0..=n/2` of `values`, detrended and, with
+/// `taper`, multiplied by a Hann window first
+fn periodogram(values: &[f64], detrend: Detrend, taper: bool) -> Vec<f64> {
+    let n = values.len();
+    let mut re = values.to_vec();
+    detrend.apply(&mut re);
+    if taper && n > 1 {
+        for (t, v) in re.iter_mut().enumerate() {
+            *v *= 0.5 - 0.5 * (2.0 * std::f64::consts::PI * t as f64 / (n - 1) as f64).cos();
+        }
+    }
+    let mut im = vec![0.0; n];
+    fft::dft(&mut re, &mut im);
+    (0..=n / 2).map(|k| (re[k] * re[k] + im[k] * im[k]) / n as f64).collect()
+}
+
+/// Period, in bars, of the strongest cycle of `values` between `min_period` and
+/// `max_period`, refined between frequency bins by a parabola through the peak and its
+/// neighbours; NaN for a window holding NaN or without power in that band
+fn dominant_period(values: &[f64], min_period: f64, max_period: f64) -> f64 {
+    if values.iter().any(|v| v.is_nan()) {
+        return f64::NAN;
+    }
+    let n = values.len() as f64;
+    let power = periodogram(values, Detrend::Linear, true);
+    let peak = (1..power.len())
+        .filter(|&k| (min_period..=max_period).contains(&(n / k as f64)))
+        .max_by(|&a, &b| power[a].total_cmp(&power[b]));
+    let Some(k) = peak.filter(|&k| power[k] > 0.0) else { return f64::NAN };
+    let mut frequency = k as f64;
+    if k + 1 < power.len() {
+        let (left, centre, right) = (power[k - 1], power[k], power[k + 1]);
+        let curvature = left - 2.0 * centre + right;
+        if curvature < 0.0 {
+            frequency += 0.5 * (left - right) / curvature;
+        }
+    }
+    (n / frequency).clamp(min_period, max_period)
+}
+
+/// Power spectrum (periodogram) of a series, for finding the cycles in a price series.
+///
+/// Returns a dict with the `frequency` of each bin, `k / (n * spacing)` for `k = 0..=n/2`
+/// in cycles per unit of `spacing` (per bar by default, so a bin's period in bars is
+/// `1 / frequency`), and its `power`, `|X_k|^2 / n` for the discrete Fourier transform `X`
+/// of the series after `detrend` removes its "constant" mean (the default), its "linear"
+/// least-squares trend or, with "none", nothing. Any length is transformed exactly, not
+/// zero-padded. NaN values make every power NaN.
+#[pyfunction]
+#[pyo3(signature = (data, spacing=1.0, detrend="constant"))]
+pub fn power_spectrum_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    spacing: f64,
+    detrend: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let detrend = Detrend::parse(detrend)?;
+    if spacing.is_nan() || spacing <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "spacing must be > 0"
+        ));
+    }
+    let values = data.values()?;
+    let n = values.len();
+    let power = if n == 0 { Vec::new() } else { py.allow_threads(|| periodogram(&values, detrend, false)) };
+    let result = PyDict::new_bound(py);
+    result.set_item("frequency", PyArray1::from_iter_bound(py, (0..power.len()).map(|k| k as f64 / (n as f64 * spacing))))?;
+    result.set_item("power", PyArray1::from_vec_bound(py, power))?;
+    Ok(result)
+}
+
+/// Rolling dominant cycle length of a series, in bars, for adaptive indicators.
+///
+/// Each window of `window` bars is linearly detrended and Hann-tapered, and its
+/// strongest spectral peak with a period between `min_period` (default 2) and
+/// `max_period` (default `window`) bars, interpolated between bins, gives the cycle
+/// length; windows holding NaN, or without power in that band, give NaN. Periods near the
+/// window length are poorly resolved, so a window of two or more of the longest cycles
+/// sought is best. The first `window - 1` positions have no full window; `align` is
+/// "valid", "same" or an integer shift, as for `moving_average_rust`.
+#[pyfunction]
+#[pyo3(signature = (data, window, min_period=2.0, max_period=None, align=None, config=None))]
+pub fn dominant_cycle_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    window: usize,
+    min_period: f64,
+    max_period: Option<f64>,
+    align: Option<Alignment>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let values = data.values()?;
+    let valid = crate::errors::check_window(window, values.len())?;
+    if window < 4 {
+        return Err(crate::errors::InvalidWindowError::new_err(
+            "Window size must be >= 4 to resolve a cycle"
+        ));
+    }
+    let max_period = max_period.unwrap_or(window as f64);
+    if min_period.is_nan() || max_period.is_nan() || min_period < 2.0 || max_period < min_period {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Periods must satisfy 2 <= min_period <= max_period"
+        ));
+    }
+    let periods: Vec<f64> = py.allow_threads(|| {
+        threads::install(|| {
+            (0..valid)
+                .into_par_iter()
+                .map(|i| dominant_period(&values[i..i + window], min_period, max_period))
+                .collect()
+        })
+    });
+    Ok(PyArray1::from_vec_bound(py, align.pad(periods, values.len())))
+}
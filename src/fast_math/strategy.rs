@@ -0,0 +1,680 @@
+use std::collections::VecDeque;
+use std::ffi::{c_char, c_void, CString};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::{PyArray1, PyReadonlyArray2};
+
+use crate::column::Column;
+use crate::streaming::{StreamingRSI, StreamingSMA};
+
+/// One OHLCV bar as passed to strategies, laid out for the plugin ABI
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Bar {
+    pub timestamp: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// NaN when the bars carry no volume
+    pub volume: f64,
+}
+
+/// One trade or quote update
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Tick {
+    pub timestamp: i64,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// An execution of one of the strategy's orders, quantity signed (negative sells)
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Fill {
+    pub timestamp: i64,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+/// An order asked for by a strategy: signed quantity and limit price, NaN for market
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Order {
+    pub quantity: f64,
+    pub limit_price: f64,
+}
+
+impl Order {
+    pub fn market(quantity: f64) -> Self {
+        Order { quantity, limit_price: f64::NAN }
+    }
+}
+
+/// A strategy driven by market events, run entirely inside the engine. Each callback
+/// appends the orders it wants to `orders`; execution and routing are the caller's job
+pub trait Strategy: Send {
+    fn on_bar(&mut self, bar: &Bar, orders: &mut Vec<Order>);
+
+    fn on_tick(&mut self, _tick: &Tick, _orders: &mut Vec<Order>) {}
+
+    fn on_fill(&mut self, _fill: &Fill, _orders: &mut Vec<Order>) {}
+
+    /// The contract the last callback broke, such as a plugin overrunning its order
+    /// buffer, taken once so the caller can raise it
+    fn take_error(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// Keyword parameters of a built-in strategy, consumed as they are read so unknown
+/// names can be rejected
+struct Params<'py> {
+    strategy: &'static str,
+    values: Option<Bound<'py, PyDict>>,
+}
+
+impl<'py> Params<'py> {
+    fn get<T: FromPyObject<'py>>(&self, name: &str, default: T) -> PyResult<T> {
+        let Some(values) = &self.values else {
+            return Ok(default);
+        };
+        match values.get_item(name)? {
+            Some(value) => {
+                values.del_item(name)?;
+                value.extract()
+            }
+            None => Ok(default),
+        }
+    }
+
+    fn finish(self) -> PyResult<()> {
+        match self.values.and_then(|values| values.keys().iter().next()) {
+            Some(name) => Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                "Unknown parameter '{}' for strategy '{}'", name, self.strategy
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn require_size(size: f64) -> PyResult<f64> {
+    if !size.is_finite() || size <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "size must be a finite value > 0"
+        ));
+    }
+    Ok(size)
+}
+
+/// The position a reference strategy has asked for so far, turning target positions
+/// into orders for the difference
+#[derive(Default)]
+struct Target {
+    sent: f64,
+}
+
+impl Target {
+    fn move_to(&mut self, target: f64, orders: &mut Vec<Order>) {
+        if target != self.sent {
+            orders.push(Order::market(target - self.sent));
+            self.sent = target;
+        }
+    }
+}
+
+/// Long `size` while the fast average of closes is above the slow one, short below it
+struct SmaCross {
+    fast: StreamingSMA,
+    slow: StreamingSMA,
+    size: f64,
+    target: Target,
+}
+
+impl SmaCross {
+    fn create(params: &Params<'_>) -> PyResult<Box<dyn Strategy>> {
+        let (fast, slow) = (params.get("fast", 10)?, params.get("slow", 30)?);
+        if fast >= slow {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "fast must be shorter than slow"
+            ));
+        }
+        Ok(Box::new(SmaCross {
+            fast: StreamingSMA::new(fast)?,
+            slow: StreamingSMA::new(slow)?,
+            size: require_size(params.get("size", 1.0)?)?,
+            target: Target::default(),
+        }))
+    }
+}
+
+impl Strategy for SmaCross {
+    fn on_bar(&mut self, bar: &Bar, orders: &mut Vec<Order>) {
+        let (fast, slow) = (self.fast.update(bar.close), self.slow.update(bar.close));
+        if fast > slow {
+            self.target.move_to(self.size, orders);
+        } else if fast < slow {
+            self.target.move_to(-self.size, orders);
+        }
+    }
+}
+
+/// Long `size` on a close above the highest high of the previous `window` bars, short on
+/// a close below their lowest low
+struct Breakout {
+    window: usize,
+    bars: VecDeque<(f64, f64)>,
+    size: f64,
+    target: Target,
+}
+
+impl Breakout {
+    fn create(params: &Params<'_>) -> PyResult<Box<dyn Strategy>> {
+        let window = params.get("window", 20)?;
+        crate::errors::require_window(window)?;
+        Ok(Box::new(Breakout {
+            window,
+            bars: VecDeque::with_capacity(window + 1),
+            size: require_size(params.get("size", 1.0)?)?,
+            target: Target::default(),
+        }))
+    }
+}
+
+impl Strategy for Breakout {
+    fn on_bar(&mut self, bar: &Bar, orders: &mut Vec<Order>) {
+        if self.bars.len() == self.window {
+            let high = self.bars.iter().map(|b| b.0).fold(f64::NEG_INFINITY, f64::max);
+            let low = self.bars.iter().map(|b| b.1).fold(f64::INFINITY, f64::min);
+            if bar.close > high {
+                self.target.move_to(self.size, orders);
+            } else if bar.close < low {
+                self.target.move_to(-self.size, orders);
+            }
+            self.bars.pop_front();
+        }
+        self.bars.push_back((bar.high, bar.low));
+    }
+}
+
+/// Long `size` when the RSI of closes drops below `lower`, short above `upper`, flat
+/// again once it crosses back over 50
+struct RsiReversion {
+    rsi: StreamingRSI,
+    lower: f64,
+    upper: f64,
+    size: f64,
+    target: Target,
+}
+
+impl RsiReversion {
+    fn create(params: &Params<'_>) -> PyResult<Box<dyn Strategy>> {
+        let (lower, upper) = (params.get("lower", 30.0)?, params.get("upper", 70.0)?);
+        if !(0.0 < lower && lower < 50.0 && 50.0 < upper && upper < 100.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Thresholds must satisfy 0 < lower < 50 < upper < 100"
+            ));
+        }
+        Ok(Box::new(RsiReversion {
+            rsi: StreamingRSI::new(params.get("period", 14)?)?,
+            lower,
+            upper,
+            size: require_size(params.get("size", 1.0)?)?,
+            target: Target::default(),
+        }))
+    }
+}
+
+impl Strategy for RsiReversion {
+    fn on_bar(&mut self, bar: &Bar, orders: &mut Vec<Order>) {
+        let rsi = self.rsi.update(bar.close);
+        let current = self.target.sent;
+        if rsi < self.lower {
+            self.target.move_to(self.size, orders);
+        } else if rsi > self.upper {
+            self.target.move_to(-self.size, orders);
+        } else if (current > 0.0 && rsi >= 50.0) || (current < 0.0 && rsi <= 50.0) {
+            self.target.move_to(0.0, orders);
+        }
+    }
+}
+
+/// Factory for one built-in strategy
+type Factory = fn(&Params<'_>) -> PyResult<Box<dyn Strategy>>;
+
+/// Built-in reference strategies accepted by `NativeStrategy(name, ...)`
+const REGISTRY: &[(&str, Factory)] = &[
+    ("sma_cross", SmaCross::create),
+    ("breakout", Breakout::create),
+    ("rsi_reversion", RsiReversion::create),
+];
+
+/// Names accepted by `NativeStrategy`
+#[pyfunction]
+pub fn available_strategies() -> Vec<&'static str> {
+    REGISTRY.iter().map(|(name, _)| *name).collect()
+}
+
+/// Version of the plugin ABI; libraries reporting another version are rejected
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Name of the entry point a plugin library exports, declared as
+/// `extern "C" fn() -> *const PluginVTable`
+const PLUGIN_SYMBOL: &[u8] = b"fortress_strategy_plugin\0";
+
+/// Most orders a plugin callback may return for one event
+const MAX_PLUGIN_ORDERS: usize = 64;
+
+/// Writes up to `capacity` orders for `event` to `orders` and returns how many it wrote
+pub type PluginCallback<E> = unsafe extern "C" fn(state: *mut c_void, event: *const E, orders: *mut Order, capacity: usize) -> usize;
+
+/// C ABI of a compiled strategy plugin (a cdylib), returned by its
+/// `fortress_strategy_plugin` entry point. `create` receives the keyword arguments of
+/// `NativeStrategy.load` as a NUL-terminated JSON object and returns the strategy's
+/// state, or null to reject them; `destroy` frees that state. Callbacks left null ignore
+/// their events. The table must be static: libraries stay loaded for the life of the
+/// process, like Python extension modules
+#[repr(C)]
+pub struct PluginVTable {
+    pub abi_version: u32,
+    pub create: unsafe extern "C" fn(config: *const c_char) -> *mut c_void,
+    pub destroy: unsafe extern "C" fn(state: *mut c_void),
+    pub on_bar: Option<PluginCallback<Bar>>,
+    pub on_tick: Option<PluginCallback<Tick>>,
+    pub on_fill: Option<PluginCallback<Fill>>,
+}
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{c_char, c_int, c_void};
+
+    /// Resolve every symbol at load time, so a broken plugin fails in `load` rather than mid-run
+    pub const RTLD_NOW: c_int = 2;
+
+    extern "C" {
+        pub fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        pub fn dlerror() -> *const c_char;
+    }
+
+    /// The loader's description of the last failure
+    pub fn last_error() -> String {
+        // Safety: dlerror returns null or a NUL-terminated string valid until the next dl call
+        let message = unsafe { dlerror() };
+        if message.is_null() {
+            return "unknown error".to_string();
+        }
+        unsafe { std::ffi::CStr::from_ptr(message) }.to_string_lossy().into_owned()
+    }
+}
+
+/// Load the plugin library at `path` and return its function table
+#[cfg(unix)]
+fn load_plugin(path: &str) -> PyResult<&'static PluginVTable> {
+    let c_path = CString::new(path).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err("Plugin path must not contain NUL bytes")
+    })?;
+    // Safety: loading runs the library's initialisers; plugins are trusted native code
+    let handle = unsafe { dl::dlopen(c_path.as_ptr(), dl::RTLD_NOW) };
+    if handle.is_null() {
+        return Err(pyo3::exceptions::PyOSError::new_err(format!(
+            "Cannot load strategy plugin '{}': {}", path, dl::last_error()
+        )));
+    }
+    let symbol = unsafe { dl::dlsym(handle, PLUGIN_SYMBOL.as_ptr().cast()) };
+    if symbol.is_null() {
+        return Err(pyo3::exceptions::PyOSError::new_err(format!(
+            "'{}' is not a strategy plugin: {}", path, dl::last_error()
+        )));
+    }
+    // Safety: the plugin ABI declares the symbol as `extern "C" fn() -> *const PluginVTable`,
+    // and the library is never unloaded, so the table it returns lives for the process
+    let entry: extern "C" fn() -> *const PluginVTable = unsafe { std::mem::transmute(symbol) };
+    let vtable = unsafe { entry().as_ref() }.ok_or_else(|| {
+        pyo3::exceptions::PyOSError::new_err(format!(
+            "Strategy plugin '{}' returned no function table", path
+        ))
+    })?;
+    if vtable.abi_version != PLUGIN_ABI_VERSION {
+        return Err(pyo3::exceptions::PyOSError::new_err(format!(
+            "Strategy plugin '{}' targets ABI version {}, expected {}", path, vtable.abi_version, PLUGIN_ABI_VERSION
+        )));
+    }
+    Ok(vtable)
+}
+
+#[cfg(not(unix))]
+fn load_plugin(_path: &str) -> PyResult<&'static PluginVTable> {
+    Err(pyo3::exceptions::PyOSError::new_err(
+        "Strategy plugins can only be loaded on Unix platforms"
+    ))
+}
+
+/// A strategy instance living inside a loaded plugin library
+struct PluginStrategy {
+    vtable: &'static PluginVTable,
+    state: *mut c_void,
+    buffer: Vec<Order>,
+    error: Option<String>,
+}
+
+// Safety: the state is owned by one `PluginStrategy` and only used through `&mut self`,
+// so calls never overlap; plugins must not tie their state to the creating thread
+unsafe impl Send for PluginStrategy {}
+
+impl PluginStrategy {
+    fn create(path: &str, config: &str) -> PyResult<Self> {
+        let vtable = load_plugin(path)?;
+        let config = CString::new(config).map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err("Plugin config must not contain NUL bytes")
+        })?;
+        // Safety: `config` is a NUL-terminated string that outlives the call
+        let state = unsafe { (vtable.create)(config.as_ptr()) };
+        if state.is_null() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Strategy plugin '{}' rejected its config", path
+            )));
+        }
+        Ok(PluginStrategy { vtable, state, buffer: vec![Order::market(0.0); MAX_PLUGIN_ORDERS], error: None })
+    }
+
+    fn call<E>(&mut self, callback: Option<PluginCallback<E>>, event: &E, orders: &mut Vec<Order>) {
+        let Some(callback) = callback else {
+            return;
+        };
+        // Safety: `state` came from this plugin's `create`, and the buffer holds `MAX_PLUGIN_ORDERS` orders
+        let count = unsafe { callback(self.state, event, self.buffer.as_mut_ptr(), MAX_PLUGIN_ORDERS) };
+        if count > MAX_PLUGIN_ORDERS {
+            // The plugin broke the ABI, so none of what it wrote can be trusted
+            self.error = Some(format!(
+                "Strategy plugin returned {} orders for one event, more than the {} it has room for",
+                count, MAX_PLUGIN_ORDERS
+            ));
+            return;
+        }
+        orders.extend_from_slice(&self.buffer[..count]);
+    }
+}
+
+impl Strategy for PluginStrategy {
+    fn on_bar(&mut self, bar: &Bar, orders: &mut Vec<Order>) {
+        self.call(self.vtable.on_bar, bar, orders);
+    }
+
+    fn on_tick(&mut self, tick: &Tick, orders: &mut Vec<Order>) {
+        self.call(self.vtable.on_tick, tick, orders);
+    }
+
+    fn on_fill(&mut self, fill: &Fill, orders: &mut Vec<Order>) {
+        self.call(self.vtable.on_fill, fill, orders);
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+}
+
+impl Drop for PluginStrategy {
+    fn drop(&mut self) {
+        // Safety: `state` came from this plugin's `create` and is not used after this
+        unsafe { (self.vtable.destroy)(self.state) };
+    }
+}
+
+/// A latency-critical strategy run inside the engine, either a built-in reference
+/// strategy created by name or one loaded from a compiled plugin with `load`.
+///
+/// Feed it events with `on_bar`, `on_tick` and `on_fill`, each returning the orders it
+/// asks for as `(quantity, limit_price)` tuples with signed quantities and NaN limits for
+/// market orders, or replay a whole history with `run_bars` without returning to Python
+/// between bars. Python keeps the orchestration: routing the orders and reporting fills.
+/// A plugin that breaks the ABI, such as by returning more orders than it was given room
+/// for, raises `RuntimeError`.
+#[pyclass(module = "fast_math")]
+pub struct NativeStrategy {
+    name: String,
+    inner: Box<dyn Strategy>,
+    orders: Vec<Order>,
+}
+
+impl NativeStrategy {
+    fn emit(&mut self, event: impl FnOnce(&mut dyn Strategy, &mut Vec<Order>)) -> PyResult<Vec<(f64, f64)>> {
+        self.orders.clear();
+        event(self.inner.as_mut(), &mut self.orders);
+        self.check()?;
+        Ok(self.orders.iter().map(|o| (o.quantity, o.limit_price)).collect())
+    }
+
+    pub(crate) fn strategy_mut(&mut self) -> &mut dyn Strategy {
+        self.inner.as_mut()
+    }
+
+    /// Raise the error the strategy's last callback ran into, if any
+    pub(crate) fn check(&mut self) -> PyResult<()> {
+        match self.inner.take_error() {
+            Some(error) => Err(pyo3::exceptions::PyRuntimeError::new_err(error)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[pymethods]
+impl NativeStrategy {
+    /// Create a built-in strategy by name (see `available_strategies`), with its
+    /// parameters as keyword arguments: "sma_cross" (fast=10, slow=30, size=1.0),
+    /// "breakout" (window=20, size=1.0) or "rsi_reversion" (period=14, lower=30.0,
+    /// upper=70.0, size=1.0)
+    #[new]
+    #[pyo3(signature = (name, **params))]
+    fn new(name: &str, params: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let key = name.to_ascii_lowercase();
+        let Some(&(strategy, factory)) = REGISTRY.iter().find(|(n, _)| *n == key) else {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown strategy '{}', expected one of: {}", name, available_strategies().join(", ")
+            )));
+        };
+        let params = Params { strategy, values: params.map(|p| p.copy()).transpose()? };
+        let inner = factory(&params)?;
+        params.finish()?;
+        Ok(NativeStrategy { name: strategy.to_string(), inner, orders: Vec::new() })
+    }
+
+    /// Load a strategy from the compiled plugin library at `path`, passing the keyword
+    /// arguments to it as a JSON object
+    #[staticmethod]
+    #[pyo3(signature = (path, **config))]
+    fn load(py: Python<'_>, path: &str, config: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let config: String = match config {
+            Some(config) => py.import_bound("json")?.call_method1("dumps", (config,))?.extract()?,
+            None => "{}".to_string(),
+        };
+        let inner = PluginStrategy::create(path, &config)?;
+        Ok(NativeStrategy { name: path.to_string(), inner: Box::new(inner), orders: Vec::new() })
+    }
+
+    #[pyo3(signature = (timestamp, open, high, low, close, volume=f64::NAN))]
+    fn on_bar(&mut self, timestamp: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> PyResult<Vec<(f64, f64)>> {
+        let bar = Bar { timestamp, open, high, low, close, volume };
+        self.emit(|strategy, orders| strategy.on_bar(&bar, orders))
+    }
+
+    #[pyo3(signature = (timestamp, price, size=0.0))]
+    fn on_tick(&mut self, timestamp: i64, price: f64, size: f64) -> PyResult<Vec<(f64, f64)>> {
+        let tick = Tick { timestamp, price, size };
+        self.emit(|strategy, orders| strategy.on_tick(&tick, orders))
+    }
+
+    fn on_fill(&mut self, timestamp: i64, quantity: f64, price: f64) -> PyResult<Vec<(f64, f64)>> {
+        let fill = Fill { timestamp, quantity, price };
+        self.emit(|strategy, orders| strategy.on_fill(&fill, orders))
+    }
+
+    /// Feed every bar of `ohlcv` (open, high, low, close and optionally volume columns,
+    /// one row per timestamp) to the strategy in order, without the GIL. The arrays are read
+    /// in place, so other threads must not write to them during the run. Returns a dict of
+    /// the orders asked for: the `bar` index and `timestamp` each came on, its `quantity`
+    /// and `limit_price`
+    fn run_bars<'py>(&mut self, py: Python<'py>, timestamps: Column<'py, i64>, ohlcv: PyReadonlyArray2<'py, f64>) -> PyResult<Bound<'py, PyDict>> {
+        let timestamps = timestamps.values()?;
+        let ohlcv = ohlcv.as_array();
+        if !(4..=5).contains(&ohlcv.ncols()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "ohlcv must have open, high, low, close and optionally volume columns"
+            ));
+        }
+        if ohlcv.nrows() != timestamps.len() {
+            return Err(crate::errors::LengthMismatchError::new_err(
+                "ohlcv must have one row per timestamp"
+            ));
+        }
+
+        let strategy = self.inner.as_mut();
+        let (bars, orders) = py.allow_threads(|| {
+            let (mut bars, mut orders) = (Vec::new(), Vec::new());
+            for (i, row) in ohlcv.rows().into_iter().enumerate() {
+                let volume = if ohlcv.ncols() > 4 { row[4] } else { f64::NAN };
+                let bar = Bar { timestamp: timestamps[i], open: row[0], high: row[1], low: row[2], close: row[3], volume };
+                strategy.on_bar(&bar, &mut orders);
+                if let Some(error) = strategy.take_error() {
+                    return Err(format!("{} (bar {})", error, i));
+                }
+                bars.resize(orders.len(), i as i64);
+            }
+            Ok((bars, orders))
+        }).map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+
+        let result = PyDict::new_bound(py);
+        let times: Vec<i64> = bars.iter().map(|&i| timestamps[i as usize]).collect();
+        result.set_item("bar", PyArray1::from_vec_bound(py, bars))?;
+        result.set_item("timestamp", PyArray1::from_vec_bound(py, times))?;
+        result.set_item("quantity", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.quantity)))?;
+        result.set_item("limit_price", PyArray1::from_iter_bound(py, orders.iter().map(|o| o.limit_price)))?;
+        Ok(result)
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn __repr__(&self) -> String {
+        format!("NativeStrategy('{}')", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(strategy: &mut dyn Strategy, closes: &[f64]) -> Vec<(usize, f64)> {
+        let mut orders = Vec::new();
+        let mut emitted = Vec::new();
+        for (i, &close) in closes.iter().enumerate() {
+            let bar = Bar { timestamp: i as i64, open: close, high: close, low: close, close, volume: f64::NAN };
+            strategy.on_bar(&bar, &mut orders);
+            emitted.extend(orders.drain(..).map(|o| (i, o.quantity)));
+        }
+        emitted
+    }
+
+    #[test]
+    fn targets_emit_only_the_change() {
+        let (mut target, mut orders) = (Target::default(), Vec::new());
+        for position in [1.0, 1.0, -1.0, -1.0, 0.0] {
+            target.move_to(position, &mut orders);
+        }
+        let quantities: Vec<f64> = orders.iter().map(|o| o.quantity).collect();
+        assert_eq!(quantities, vec![1.0, -2.0, 1.0]);
+        assert!(orders.iter().all(|o| o.limit_price.is_nan()));
+    }
+
+    #[test]
+    fn sma_cross_follows_the_crossovers_after_warm_up() {
+        let mut strategy = SmaCross {
+            fast: StreamingSMA::new(2).unwrap(),
+            slow: StreamingSMA::new(3).unwrap(),
+            size: 1.0,
+            target: Target::default(),
+        };
+        // Nothing until the slow average has a full window; then long, then short on the cross
+        assert_eq!(run(&mut strategy, &[1.0, 2.0, 3.0, 4.0, 0.0, 0.0]), vec![(2, 1.0), (4, -2.0)]);
+    }
+
+    #[test]
+    fn breakout_trades_closes_outside_the_previous_range() {
+        let mut strategy = Breakout { window: 2, bars: VecDeque::new(), size: 2.0, target: Target::default() };
+        assert_eq!(run(&mut strategy, &[10.0, 11.0, 12.0, 11.5, 9.0, 8.0]), vec![(2, 2.0), (4, -4.0)]);
+    }
+
+    #[test]
+    fn rsi_reversion_enters_at_the_thresholds_and_exits_at_50() {
+        let mut strategy = RsiReversion {
+            rsi: StreamingRSI::new(2).unwrap(),
+            lower: 30.0,
+            upper: 70.0,
+            size: 1.0,
+            target: Target::default(),
+        };
+        // RSI: NaN, NaN, 0 (long), 50 (flat), 100 (short), 100, then a skipped NaN close
+        let closes = [10.0, 9.0, 8.0, 9.0, 10.0, 11.0, f64::NAN];
+        assert_eq!(run(&mut strategy, &closes), vec![(2, 1.0), (3, -1.0), (4, -1.0)]);
+    }
+
+    #[test]
+    fn unknown_parameters_are_rejected() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let params = PyDict::new_bound(py);
+            params.set_item("fast", 5).unwrap();
+            params.set_item("sizee", 2.0).unwrap();
+            let error = NativeStrategy::new("sma_cross", Some(&params)).err().unwrap();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyTypeError>(py));
+            assert!(error.to_string().contains("'sizee'"));
+            // The caller's dict is left alone
+            assert_eq!(params.len(), 2);
+
+            params.del_item("sizee").unwrap();
+            let mut strategy = NativeStrategy::new("SMA_CROSS", Some(&params)).unwrap();
+            assert_eq!(strategy.name(), "sma_cross");
+            assert!(strategy.on_bar(0, 1.0, 1.0, 1.0, 1.0, f64::NAN).unwrap().is_empty());
+        });
+    }
+
+    unsafe extern "C" fn create(_config: *const c_char) -> *mut c_void {
+        std::ptr::NonNull::<u8>::dangling().as_ptr().cast()
+    }
+
+    unsafe extern "C" fn destroy(_state: *mut c_void) {}
+
+    /// Asks for one order per unit of the bar's volume, ignoring the buffer's capacity
+    unsafe extern "C" fn on_bar(_state: *mut c_void, bar: *const Bar, orders: *mut Order, capacity: usize) -> usize {
+        let count = (*bar).volume as usize;
+        for i in 0..count.min(capacity) {
+            *orders.add(i) = Order::market(1.0);
+        }
+        count
+    }
+
+    static VTABLE: PluginVTable =
+        PluginVTable { abi_version: PLUGIN_ABI_VERSION, create, destroy, on_bar: Some(on_bar), on_tick: None, on_fill: None };
+
+    #[test]
+    fn plugins_returning_too_many_orders_raise() {
+        pyo3::prepare_freethreaded_python();
+        let plugin = PluginStrategy {
+            vtable: &VTABLE,
+            state: unsafe { create(std::ptr::null()) },
+            buffer: vec![Order::market(0.0); MAX_PLUGIN_ORDERS],
+            error: None,
+        };
+        let mut strategy = NativeStrategy { name: "test".to_string(), inner: Box::new(plugin), orders: Vec::new() };
+        assert_eq!(strategy.on_bar(0, 1.0, 1.0, 1.0, 1.0, 3.0).unwrap().len(), 3);
+        assert_eq!(strategy.on_bar(1, 1.0, 1.0, 1.0, 1.0, MAX_PLUGIN_ORDERS as f64).unwrap().len(), MAX_PLUGIN_ORDERS);
+        let error = strategy.on_bar(2, 1.0, 1.0, 1.0, 1.0, (MAX_PLUGIN_ORDERS + 1) as f64).unwrap_err();
+        assert!(error.to_string().contains("65 orders"));
+        // Events without a callback are ignored, and the error is raised only once
+        assert!(strategy.on_tick(3, 1.0, 1.0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1531 @@
+use std::collections::VecDeque;
+
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyBytes};
+use numpy::PyArray1;
+use ndarray::{s, Array1};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::column::{ArrayKind, Column};
+use crate::pool::Buffer;
+use crate::telemetry;
+use crate::DRIFT_INTERVAL;
+
+/// Run a per-element update over equal-length input columns with the GIL released,
+/// collecting each result
+fn batch<'py, const N: usize, R, F>(py: Python<'py>, columns: [Column<'py, f64>; N], mut update: F) -> PyResult<(ArrayKind, Vec<R>)>
+where
+    R: Send,
+    F: FnMut([f64; N]) -> R + Send,
+{
+    let kind = ArrayKind::of(&columns.each_ref().map(|c| c.kind()));
+    let values = columns.iter().map(|c| c.values()).collect::<PyResult<Vec<_>>>()?;
+    let n = values.first().map_or(0, |v| v.len());
+    if values.iter().any(|v| v.len() != n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "All inputs must have the same length"
+        ));
+    }
+    let result = py.allow_threads(|| (0..n).map(|i| update(std::array::from_fn(|j| values[j][i]))).collect());
+    Ok((kind, result))
+}
+
+/// Serialize indicator state for `__getstate__`, so pickled indicators resume without a
+/// warm-up period
+pub fn dump_state<'py, T: Serialize>(py: Python<'py>, state: &T) -> PyResult<Bound<'py, PyBytes>> {
+    let bytes = rmp_serde::to_vec_named(state).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Failed to serialize state: {}", e))
+    })?;
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Indicator state that can be restored by `__setstate__`
+pub trait State: DeserializeOwned {
+    /// Check the invariants the indicator relies on, which a decoded state from an
+    /// untrusted or corrupt pickle need not satisfy
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub fn load_state<T: State>(state: &[u8]) -> PyResult<T> {
+    let state: T = rmp_serde::from_slice(state).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid indicator state: {}", e))
+    })?;
+    state.check().map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid indicator state: {}", e))
+    })?;
+    Ok(state)
+}
+
+/// Fixed-capacity ring buffer of the most recent values with O(1) mean / std and
+/// amortized O(1) min / max.
+///
+/// Values are stored twice (at `i` and `i + capacity`) so the current window is always
+/// a contiguous slice and can be exported to numpy without reordering. The running sums
+/// cover finite values only, kept relative to a recent value and recomputed every
+/// `DRIFT_INTERVAL` pushes to bound drift; while a NaN or infinite value is in the window,
+/// the mean and sum are computed from the window itself and the std is NaN.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct RollingWindow {
+    buffer: Array1<f64>,
+    capacity: usize,
+    /// Total number of values ever pushed; the next write goes to `pushed % capacity`
+    pushed: usize,
+    len: usize,
+    /// NaN and infinite values in the window, which the sums leave out
+    nonfinite: usize,
+    shift: f64,
+    /// Sum and sum of squares of the finite values' deviations from `shift`
+    sum: f64,
+    sum_sq: f64,
+    /// Monotonic (position, value) queues for the running min and max
+    mins: VecDeque<(usize, f64)>,
+    maxs: VecDeque<(usize, f64)>,
+}
+
+impl RollingWindow {
+    pub fn with_capacity(capacity: usize) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Capacity must be > 0"
+            ));
+        }
+        Ok(RollingWindow {
+            buffer: Array1::from_elem(2 * capacity, f64::NAN),
+            capacity,
+            pushed: 0,
+            len: 0,
+            nonfinite: 0,
+            shift: 0.0,
+            sum: 0.0,
+            sum_sq: 0.0,
+            mins: VecDeque::new(),
+            maxs: VecDeque::new(),
+        })
+    }
+
+    /// Append a value, returning the one evicted from a full window
+    pub fn push_value(&mut self, value: f64) -> Option<f64> {
+        let slot = self.pushed % self.capacity;
+        let evicted = (self.len == self.capacity).then(|| self.buffer[slot]);
+        self.buffer[slot] = value;
+        self.buffer[slot + self.capacity] = value;
+
+        match evicted {
+            Some(old) if old.is_finite() => {
+                self.sum -= old - self.shift;
+                self.sum_sq -= (old - self.shift) * (old - self.shift);
+            }
+            Some(_) => self.nonfinite -= 1,
+            None => self.len += 1,
+        }
+        if !value.is_finite() {
+            self.nonfinite += 1;
+        } else {
+            if self.len - 1 == self.nonfinite {
+                // No finite values left, so re-centre on this one
+                (self.shift, self.sum, self.sum_sq) = (value, 0.0, 0.0);
+            }
+            self.sum += value - self.shift;
+            self.sum_sq += (value - self.shift) * (value - self.shift);
+        }
+
+        let position = self.pushed;
+        let oldest = (position + 1).saturating_sub(self.capacity);
+        // NaN compares false against everything, so non-finite values stay out of the
+        // queues; `min` and `max` are NaN while one is in the window
+        if value.is_finite() {
+            while self.mins.back().is_some_and(|&(_, v)| v >= value) {
+                self.mins.pop_back();
+            }
+            self.mins.push_back((position, value));
+            while self.maxs.back().is_some_and(|&(_, v)| v <= value) {
+                self.maxs.pop_back();
+            }
+            self.maxs.push_back((position, value));
+        }
+        while self.mins.front().is_some_and(|&(p, _)| p < oldest) {
+            self.mins.pop_front();
+        }
+        while self.maxs.front().is_some_and(|&(p, _)| p < oldest) {
+            self.maxs.pop_front();
+        }
+
+        self.pushed += 1;
+        if self.pushed.is_multiple_of(DRIFT_INTERVAL) {
+            self.recompute();
+        }
+        evicted
+    }
+
+    /// Recompute the running sums exactly, centred on the oldest finite value
+    fn recompute(&mut self) {
+        let buffer = self.buffer.as_slice().unwrap();
+        let start = if self.len == self.capacity { self.pushed % self.capacity } else { 0 };
+        let finite = buffer[start..start + self.len].iter().filter(|v| v.is_finite());
+        self.shift = finite.clone().next().copied().unwrap_or(0.0);
+        self.sum = finite.clone().map(|v| v - self.shift).sum();
+        self.sum_sq = finite.map(|v| (v - self.shift) * (v - self.shift)).sum();
+    }
+
+    /// The current window, oldest value first
+    pub fn as_slice(&self) -> &[f64] {
+        let start = if self.len == self.capacity { self.pushed % self.capacity } else { 0 };
+        &self.buffer.as_slice().unwrap()[start..start + self.len]
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+}
+
+impl State for RollingWindow {
+    fn check(&self) -> Result<(), String> {
+        if self.capacity == 0 {
+            return Err("capacity must be > 0".to_string());
+        }
+        if self.buffer.len() != 2 * self.capacity || self.buffer.as_slice().is_none() {
+            return Err(format!("buffer must hold {} values", 2 * self.capacity));
+        }
+        if self.len > self.capacity || self.len > self.pushed {
+            return Err(format!("length {} exceeds the capacity or values pushed", self.len));
+        }
+        if self.nonfinite > self.len {
+            return Err(format!("{} non-finite values exceed the length {}", self.nonfinite, self.len));
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl RollingWindow {
+    #[new]
+    fn new(capacity: usize) -> PyResult<Self> {
+        Self::with_capacity(capacity)
+    }
+
+    /// Append a value; returns the value evicted from a full window, else None
+    fn push(&mut self, value: f64) -> Option<f64> {
+        self.push_value(value)
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[getter]
+    fn full(&self) -> bool {
+        self.is_full()
+    }
+
+    pub fn sum(&self) -> f64 {
+        if self.nonfinite > 0 {
+            return self.as_slice().iter().sum();
+        }
+        self.sum + self.len as f64 * self.shift
+    }
+
+    /// Mean of the window; NaN when empty
+    pub fn mean(&self) -> f64 {
+        match self.len {
+            0 => f64::NAN,
+            len if self.nonfinite > 0 => self.as_slice().iter().sum::<f64>() / len as f64,
+            len => self.shift + self.sum / len as f64,
+        }
+    }
+
+    /// Standard deviation with `ddof` delta degrees of freedom (sample std by default)
+    #[pyo3(signature = (ddof=1))]
+    pub fn std(&self, ddof: usize) -> f64 {
+        if self.len <= ddof || self.nonfinite > 0 {
+            return f64::NAN;
+        }
+        let n = self.len as f64;
+        // Running sums can make the variance marginally negative for constant windows
+        let var = (self.sum_sq - self.sum * self.sum / n).max(0.0) / (self.len - ddof) as f64;
+        var.sqrt()
+    }
+
+    /// Minimum of the window; NaN when empty or holding a NaN or infinite value
+    pub fn min(&self) -> f64 {
+        if self.nonfinite > 0 {
+            return f64::NAN;
+        }
+        self.mins.front().map_or(f64::NAN, |&(_, v)| v)
+    }
+
+    /// Maximum of the window; NaN when empty or holding a NaN or infinite value
+    pub fn max(&self) -> f64 {
+        if self.nonfinite > 0 {
+            return f64::NAN;
+        }
+        self.maxs.front().map_or(f64::NAN, |&(_, v)| v)
+    }
+
+    /// Most recent value; NaN when empty
+    fn last(&self) -> f64 {
+        self.as_slice().last().copied().unwrap_or(f64::NAN)
+    }
+
+    fn clear(&mut self) {
+        self.pushed = 0;
+        self.len = 0;
+        self.nonfinite = 0;
+        self.shift = 0.0;
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+        self.mins.clear();
+        self.maxs.clear();
+    }
+
+    /// The window as a numpy array, oldest value first.
+    ///
+    /// With `copy=False` a read-only view into the buffer is returned instead; it is only
+    /// valid until the next `push`, which overwrites the slots it refers to.
+    #[pyo3(signature = (copy=true))]
+    fn to_numpy<'py>(slf: &Bound<'py, Self>, copy: bool) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let window = slf.borrow();
+        if copy {
+            return Ok(PyArray1::from_slice_bound(slf.py(), window.as_slice()));
+        }
+        let start = if window.is_full() { window.pushed % window.capacity } else { 0 };
+        let view = window.buffer.slice(s![start..start + window.len]);
+        // Safety: the buffer is never reallocated (`__setstate__` copies into it) and the
+        // view's base object keeps it alive
+        let array = unsafe { PyArray1::borrow_from_array_bound(&view, slf.clone().into_any()) };
+        array.call_method("setflags", (), Some(&[("write", false)].into_py_dict_bound(slf.py())))?;
+        Ok(array)
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    /// Restore a pickled window. The state is copied into the existing buffer rather than
+    /// replacing it, so views returned by `to_numpy(copy=False)` stay valid
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        let state: RollingWindow = load_state(state)?;
+        if state.capacity != self.capacity {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid indicator state: capacity {} does not match this window's {}", state.capacity, self.capacity
+            )));
+        }
+        self.buffer.assign(&state.buffer);
+        self.pushed = state.pushed;
+        self.len = state.len;
+        self.nonfinite = state.nonfinite;
+        self.shift = state.shift;
+        self.sum = state.sum;
+        self.sum_sq = state.sum_sq;
+        self.mins = state.mins;
+        self.maxs = state.maxs;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.capacity,)
+    }
+}
+
+/// Simple moving average updated in O(1) per value; NaN until `window` values are seen.
+/// NaN values are skipped: they leave the window unchanged and return the current average.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingSMA {
+    window: RollingWindow,
+}
+
+impl State for StreamingSMA {
+    fn check(&self) -> Result<(), String> {
+        self.window.check()
+    }
+}
+
+impl StreamingSMA {
+    pub fn update(&mut self, value: f64) -> f64 {
+        if !value.is_nan() {
+            self.window.push_value(value);
+        }
+        self.value()
+    }
+}
+
+#[pymethods]
+impl StreamingSMA {
+    #[new]
+    pub fn new(window: usize) -> PyResult<Self> {
+        crate::errors::require_window(window)?;
+        Ok(StreamingSMA { window: RollingWindow::with_capacity(window)? })
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, value: f64) -> f64 {
+        let _span = telemetry::span("StreamingSMA.update").entered();
+        self.update(value)
+    }
+
+    #[getter]
+    fn value(&self) -> f64 {
+        if self.window.is_full() {
+            self.window.mean()
+        } else {
+            f64::NAN
+        }
+    }
+
+    #[getter]
+    fn ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    /// Update with each of `values` in one call and return the value after each update
+    fn update_batch<'py>(&mut self, py: Python<'py>, values: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingSMA.update_batch", || {
+            let (kind, result) = batch(py, [values], |[value]| self.update(value))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.window.capacity,)
+    }
+}
+
+/// Exponential moving average with `alpha = 2 / (period + 1)`, seeded with the simple
+/// average of the first `period` values; NaN until then. NaN values are skipped and
+/// return the current average.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingEMA {
+    period: usize,
+    alpha: f64,
+    count: usize,
+    seed_sum: f64,
+    ema: f64,
+}
+
+impl State for StreamingEMA {
+    fn check(&self) -> Result<(), String> {
+        if self.period == 0 {
+            return Err("period must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl StreamingEMA {
+    pub fn update(&mut self, value: f64) -> f64 {
+        if value.is_nan() {
+            return self.ema;
+        }
+        self.count += 1;
+        if self.count < self.period {
+            self.seed_sum += value;
+        } else if self.count == self.period {
+            self.ema = (self.seed_sum + value) / self.period as f64;
+        } else {
+            self.ema += self.alpha * (value - self.ema);
+        }
+        self.ema
+    }
+}
+
+#[pymethods]
+impl StreamingEMA {
+    #[new]
+    pub fn new(period: usize) -> PyResult<Self> {
+        crate::errors::require_period(period)?;
+        Ok(StreamingEMA { period, alpha: 2.0 / (period as f64 + 1.0), count: 0, seed_sum: 0.0, ema: f64::NAN })
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, value: f64) -> f64 {
+        let _span = telemetry::span("StreamingEMA.update").entered();
+        self.update(value)
+    }
+
+    #[getter]
+    pub fn value(&self) -> f64 {
+        self.ema
+    }
+
+    #[getter]
+    fn ready(&self) -> bool {
+        self.count >= self.period
+    }
+
+    /// Update with each of `values` in one call and return the value after each update
+    fn update_batch<'py>(&mut self, py: Python<'py>, values: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingEMA.update_batch", || {
+            let (kind, result) = batch(py, [values], |[value]| self.update(value))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+        self.seed_sum = 0.0;
+        self.ema = f64::NAN;
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.period,)
+    }
+}
+
+/// RSI over simple averages of the last `period` changes, matching
+/// `rsi_rust(smoothing="simple")`; NaN until `period` changes are seen. NaN prices are
+/// skipped, so the next change is taken from the last valid price.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingRSI {
+    last: Option<f64>,
+    changes: RollingWindow,
+    gains: f64,
+    losses: f64,
+}
+
+impl StreamingRSI {
+    /// Add a price change directly, for callers that already track the previous close;
+    /// NaN changes are skipped
+    pub fn push_change(&mut self, change: f64) {
+        if change.is_nan() {
+            return;
+        }
+        if change > 0.0 {
+            self.gains += change;
+        } else {
+            self.losses -= change;
+        }
+        match self.changes.push_value(change) {
+            // Subtracting an infinite change would leave NaN, so recompute the sums
+            Some(old) if !old.is_finite() => self.recompute(),
+            Some(old) if old > 0.0 => self.gains -= old,
+            Some(old) => self.losses += old,
+            None => {}
+        }
+        if self.changes.pushed.is_multiple_of(DRIFT_INTERVAL) {
+            self.recompute();
+        }
+    }
+
+    /// Recompute the gain and loss sums exactly from the window
+    fn recompute(&mut self) {
+        let changes = self.changes.as_slice();
+        self.gains = changes.iter().filter(|&&c| c > 0.0).sum();
+        self.losses = -changes.iter().filter(|&&c| c <= 0.0).sum::<f64>();
+    }
+
+    pub fn update(&mut self, value: f64) -> f64 {
+        if value.is_nan() {
+            return self.value();
+        }
+        if let Some(last) = self.last.replace(value) {
+            self.push_change(value - last);
+        }
+        self.value()
+    }
+}
+
+impl State for StreamingRSI {
+    fn check(&self) -> Result<(), String> {
+        self.changes.check()
+    }
+}
+
+#[pymethods]
+impl StreamingRSI {
+    #[new]
+    pub fn new(period: usize) -> PyResult<Self> {
+        crate::errors::require_period(period)?;
+        Ok(StreamingRSI { last: None, changes: RollingWindow::with_capacity(period)?, gains: 0.0, losses: 0.0 })
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, value: f64) -> f64 {
+        let _span = telemetry::span("StreamingRSI.update").entered();
+        self.update(value)
+    }
+
+    #[getter]
+    pub fn value(&self) -> f64 {
+        if !self.changes.is_full() {
+            return f64::NAN;
+        }
+        // Running sums can drift slightly below zero after many removals
+        let period = self.changes.capacity as f64;
+        let avg_gain = self.gains.max(0.0) / period;
+        let avg_loss = self.losses.max(0.0) / period;
+        if avg_loss == 0.0 {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+        }
+    }
+
+    #[getter]
+    fn ready(&self) -> bool {
+        self.changes.is_full()
+    }
+
+    /// Update with each of `values` in one call and return the value after each update
+    fn update_batch<'py>(&mut self, py: Python<'py>, values: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingRSI.update_batch", || {
+            let (kind, result) = batch(py, [values], |[value]| self.update(value))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+        self.changes.clear();
+        self.gains = 0.0;
+        self.losses = 0.0;
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.changes.capacity,)
+    }
+}
+
+/// Average true range with Wilder smoothing, seeded with the mean of the first `period`
+/// true ranges; NaN until then. Call `reset()` at a session boundary to drop the
+/// previous close so the overnight gap does not count as range. Bars with a NaN high, low
+/// or close are skipped and return the current ATR.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingATR {
+    period: usize,
+    prev_close: Option<f64>,
+    count: usize,
+    seed_sum: f64,
+    atr: f64,
+}
+
+/// True range of a bar given the previous close, or the bar's range for the first bar
+pub fn true_range(high: f64, low: f64, prev_close: Option<f64>) -> f64 {
+    match prev_close {
+        Some(prev) => (high - low).max((high - prev).abs()).max((low - prev).abs()),
+        None => high - low,
+    }
+}
+
+impl StreamingATR {
+    /// Add a precomputed true range and return the current ATR; NaN ranges are skipped
+    pub fn push_true_range(&mut self, true_range: f64) -> f64 {
+        if true_range.is_nan() {
+            return self.atr;
+        }
+        self.count += 1;
+        if self.count < self.period {
+            self.seed_sum += true_range;
+        } else if self.count == self.period {
+            self.atr = (self.seed_sum + true_range) / self.period as f64;
+        } else {
+            self.atr = (self.atr * (self.period - 1) as f64 + true_range) / self.period as f64;
+        }
+        self.atr
+    }
+
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> f64 {
+        if high.is_nan() || low.is_nan() || close.is_nan() {
+            return self.atr;
+        }
+        let true_range = true_range(high, low, self.prev_close.replace(close));
+        self.push_true_range(true_range)
+    }
+}
+
+impl State for StreamingATR {
+    fn check(&self) -> Result<(), String> {
+        if self.period == 0 {
+            return Err("period must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl StreamingATR {
+    #[new]
+    pub fn new(period: usize) -> PyResult<Self> {
+        crate::errors::require_period(period)?;
+        Ok(StreamingATR { period, prev_close: None, count: 0, seed_sum: 0.0, atr: f64::NAN })
+    }
+
+    /// Add a bar and return the current ATR
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, high: f64, low: f64, close: f64) -> f64 {
+        let _span = telemetry::span("StreamingATR.update").entered();
+        self.update(high, low, close)
+    }
+
+    #[getter]
+    pub fn value(&self) -> f64 {
+        self.atr
+    }
+
+    #[getter]
+    fn ready(&self) -> bool {
+        self.count >= self.period
+    }
+
+    /// Update with each bar in one call and return the ATR after each bar
+    fn update_batch<'py>(
+        &mut self,
+        py: Python<'py>,
+        high: Column<'py, f64>,
+        low: Column<'py, f64>,
+        close: Column<'py, f64>
+    ) -> PyResult<PyObject> {
+        telemetry::traced("StreamingATR.update_batch", || {
+            let (kind, result) = batch(py, [high, low, close], |[h, l, c]| self.update(h, l, c))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.count = 0;
+        self.seed_sum = 0.0;
+        self.atr = f64::NAN;
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.period,)
+    }
+}
+
+/// Session volume-weighted average price, accumulated tick by tick (or bar by bar with a
+/// typical price) until `reset()` is called at the next session boundary. Ticks with NaN
+/// price or volume are ignored.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingVWAP {
+    volume: f64,
+    price_volume: f64,
+    price_sq_volume: f64,
+}
+
+impl State for StreamingVWAP {}
+
+impl StreamingVWAP {
+    pub fn update(&mut self, price: f64, volume: f64) -> f64 {
+        if !price.is_nan() && !volume.is_nan() {
+            self.volume += volume;
+            self.price_volume += price * volume;
+            self.price_sq_volume += price * price * volume;
+        }
+        self.value()
+    }
+}
+
+#[pymethods]
+impl StreamingVWAP {
+    #[new]
+    fn new() -> Self {
+        StreamingVWAP { volume: 0.0, price_volume: 0.0, price_sq_volume: 0.0 }
+    }
+
+    /// Add a trade and return the current VWAP
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, price: f64, volume: f64) -> f64 {
+        let _span = telemetry::span("StreamingVWAP.update").entered();
+        self.update(price, volume)
+    }
+
+    /// Current VWAP; NaN before any volume has traded
+    #[getter]
+    fn value(&self) -> f64 {
+        if self.volume > 0.0 {
+            self.price_volume / self.volume
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Volume-weighted standard deviation of price around the VWAP, for VWAP bands
+    #[getter]
+    fn std(&self) -> f64 {
+        let vwap = self.value();
+        (self.price_sq_volume / self.volume - vwap * vwap).max(0.0).sqrt()
+    }
+
+    /// Volume traded in the current session
+    #[getter]
+    fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    #[getter]
+    fn ready(&self) -> bool {
+        self.volume > 0.0
+    }
+
+    /// Update with each trade in one call and return the VWAP after each trade
+    fn update_batch<'py>(&mut self, py: Python<'py>, prices: Column<'py, f64>, volumes: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingVWAP.update_batch", || {
+            let (kind, result) = batch(py, [prices, volumes], |[p, v]| self.update(p, v))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.volume = 0.0;
+        self.price_volume = 0.0;
+        self.price_sq_volume = 0.0;
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+}
+
+/// Running means and co-moments of (x, y) pairs, updated with Welford's algorithm
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+struct Comoments {
+    count: usize,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64,
+    m2_y: f64,
+    c_xy: f64,
+}
+
+impl Comoments {
+    /// Add a pair; pairs containing NaN are ignored
+    fn update(&mut self, x: f64, y: f64) {
+        if x.is_nan() || y.is_nan() {
+            return;
+        }
+        self.count += 1;
+        let n = self.count as f64;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / n;
+        let dy = y - self.mean_y;
+        self.mean_y += dy / n;
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += dy * (y - self.mean_y);
+        self.c_xy += dx * (y - self.mean_y);
+    }
+
+    fn covariance(&self, ddof: usize) -> f64 {
+        if self.count <= ddof {
+            return f64::NAN;
+        }
+        self.c_xy / (self.count - ddof) as f64
+    }
+
+    fn correlation(&self) -> f64 {
+        if self.count < 2 {
+            return f64::NAN;
+        }
+        let denominator = (self.m2_x * self.m2_y).sqrt();
+        if denominator == 0.0 {
+            0.0
+        } else {
+            self.c_xy / denominator
+        }
+    }
+}
+
+/// Covariance of all (x, y) pairs seen so far, without storing history; `ddof=1` gives
+/// the sample covariance. Pairs containing NaN are ignored.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingCovariance {
+    moments: Comoments,
+    ddof: usize,
+}
+
+impl State for StreamingCovariance {}
+
+impl StreamingCovariance {
+    pub fn update(&mut self, x: f64, y: f64) -> f64 {
+        self.moments.update(x, y);
+        self.value()
+    }
+}
+
+#[pymethods]
+impl StreamingCovariance {
+    #[new]
+    #[pyo3(signature = (ddof=1))]
+    fn new(ddof: usize) -> Self {
+        StreamingCovariance { moments: Comoments::default(), ddof }
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, x: f64, y: f64) -> f64 {
+        let _span = telemetry::span("StreamingCovariance.update").entered();
+        self.update(x, y)
+    }
+
+    #[getter]
+    fn value(&self) -> f64 {
+        self.moments.covariance(self.ddof)
+    }
+
+    #[getter]
+    fn count(&self) -> usize {
+        self.moments.count
+    }
+
+    #[getter]
+    fn mean_x(&self) -> f64 {
+        self.moments.mean_x
+    }
+
+    #[getter]
+    fn mean_y(&self) -> f64 {
+        self.moments.mean_y
+    }
+
+    /// Update with each (x, y) pair in one call and return the estimate after each pair
+    fn update_batch<'py>(&mut self, py: Python<'py>, x: Column<'py, f64>, y: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingCovariance.update_batch", || {
+            let (kind, result) = batch(py, [x, y], |[x, y]| self.update(x, y))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.moments = Comoments::default();
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.ddof,)
+    }
+}
+
+/// Pearson correlation of all (x, y) pairs seen so far, without storing history. Pairs
+/// containing NaN are ignored; NaN until two pairs are seen.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingCorrelation {
+    moments: Comoments,
+}
+
+impl State for StreamingCorrelation {}
+
+impl StreamingCorrelation {
+    pub fn update(&mut self, x: f64, y: f64) -> f64 {
+        self.moments.update(x, y);
+        self.value()
+    }
+}
+
+#[pymethods]
+impl StreamingCorrelation {
+    #[new]
+    fn new() -> Self {
+        StreamingCorrelation { moments: Comoments::default() }
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, x: f64, y: f64) -> f64 {
+        let _span = telemetry::span("StreamingCorrelation.update").entered();
+        self.update(x, y)
+    }
+
+    #[getter]
+    fn value(&self) -> f64 {
+        self.moments.correlation()
+    }
+
+    /// Sample covariance of the pairs seen so far
+    #[getter]
+    fn covariance(&self) -> f64 {
+        self.moments.covariance(1)
+    }
+
+    #[getter]
+    fn count(&self) -> usize {
+        self.moments.count
+    }
+
+    /// Update with each (x, y) pair in one call and return the estimate after each pair
+    fn update_batch<'py>(&mut self, py: Python<'py>, x: Column<'py, f64>, y: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingCorrelation.update_batch", || {
+            let (kind, result) = batch(py, [x, y], |[x, y]| self.update(x, y))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.moments = Comoments::default();
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+}
+
+/// Running peak, current drawdown and maximum drawdown of an equity curve, for live risk
+/// limits. Drawdowns are fractions of the running peak (0.1 = 10% below the peak); NaN
+/// equity updates are ignored.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingDrawdown {
+    equity: f64,
+    peak: f64,
+    max_drawdown: f64,
+    /// Updates since the last peak, and the longest such stretch
+    duration: usize,
+    max_duration: usize,
+}
+
+impl State for StreamingDrawdown {}
+
+impl StreamingDrawdown {
+    pub fn update(&mut self, equity: f64) -> f64 {
+        if equity.is_nan() {
+            return self.drawdown();
+        }
+        self.equity = equity;
+        if self.peak.is_nan() || equity >= self.peak {
+            self.peak = equity;
+            self.duration = 0;
+        } else {
+            self.duration += 1;
+            self.max_duration = self.max_duration.max(self.duration);
+        }
+        let drawdown = self.drawdown();
+        if drawdown > self.max_drawdown {
+            self.max_drawdown = drawdown;
+        }
+        drawdown
+    }
+}
+
+#[pymethods]
+impl StreamingDrawdown {
+    #[new]
+    fn new() -> Self {
+        StreamingDrawdown { equity: f64::NAN, peak: f64::NAN, max_drawdown: 0.0, duration: 0, max_duration: 0 }
+    }
+
+    /// Add an equity value and return the current drawdown
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, equity: f64) -> f64 {
+        let _span = telemetry::span("StreamingDrawdown.update").entered();
+        self.update(equity)
+    }
+
+    /// Current drawdown as a fraction of the peak; NaN before the first update or while
+    /// the peak is not positive
+    #[getter]
+    fn drawdown(&self) -> f64 {
+        if self.peak > 0.0 {
+            (self.peak - self.equity) / self.peak
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Current drawdown in equity units
+    #[getter]
+    fn drawdown_amount(&self) -> f64 {
+        self.peak - self.equity
+    }
+
+    #[getter]
+    fn max_drawdown(&self) -> f64 {
+        self.max_drawdown
+    }
+
+    #[getter]
+    fn peak(&self) -> f64 {
+        self.peak
+    }
+
+    #[getter]
+    fn equity(&self) -> f64 {
+        self.equity
+    }
+
+    /// Number of updates since the last peak
+    #[getter]
+    fn duration(&self) -> usize {
+        self.duration
+    }
+
+    #[getter]
+    fn max_duration(&self) -> usize {
+        self.max_duration
+    }
+
+    /// Update with each of `equity` in one call and return the value after each update
+    fn update_batch<'py>(&mut self, py: Python<'py>, equity: Column<'py, f64>) -> PyResult<PyObject> {
+        telemetry::traced("StreamingDrawdown.update_batch", || {
+            let (kind, result) = batch(py, [equity], |[value]| self.update(value))?;
+            kind.wrap(py, result)
+        })
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum OutlierMethod {
+    /// Distance from the rolling median in units of the scaled median absolute deviation
+    Mad,
+    /// Distance from an exponentially weighted mean in units of its weighted std
+    Ewma,
+}
+
+/// Flags bad prints in real time: `update(price)` returns True when the price is more than
+/// `threshold` robust standard deviations from recent prices.
+///
+/// `method="mad"` compares against the median and 1.4826 × MAD of the last `window`
+/// accepted prices; `method="ewma"` uses an exponentially weighted mean and variance with
+/// `alpha = 2 / (window + 1)`. Flagged prices are not added to the history, so a burst of
+/// bad prints does not drag the baseline. Nothing is flagged until `window` prices are seen.
+/// After `reset_after` consecutive flagged prices (None to never reset) the price is taken
+/// to have moved to a new level: the history restarts from those prices and the detector
+/// warms up again.
+#[pyclass(module = "fast_math")]
+#[derive(Serialize, Deserialize)]
+pub struct StreamingOutlierDetector {
+    method: OutlierMethod,
+    threshold: f64,
+    history: RollingWindow,
+    alpha: f64,
+    mean: f64,
+    var: f64,
+    score: f64,
+    flagged: usize,
+    reset_after: Option<usize>,
+    /// Consecutive flagged prices since the last accepted one
+    streak: Vec<f64>,
+}
+
+impl StreamingOutlierDetector {
+    fn mad_score(&self, price: f64) -> f64 {
+        let mut values: Buffer<f64> = self.history.as_slice().iter().copied().collect();
+        let mid = values.len() / 2;
+        let median = *values.select_nth_unstable_by(mid, f64::total_cmp).1;
+        for v in values.iter_mut() {
+            *v = (*v - median).abs();
+        }
+        let mad = *values.select_nth_unstable_by(mid, f64::total_cmp).1 * 1.4826;
+        scaled_distance(price - median, mad)
+    }
+
+    pub fn update(&mut self, price: f64) -> bool {
+        if price.is_nan() {
+            self.flagged += 1;
+            return true;
+        }
+        self.score = if !self.history.is_full() {
+            f64::NAN
+        } else {
+            match self.method {
+                OutlierMethod::Mad => self.mad_score(price),
+                OutlierMethod::Ewma => scaled_distance(price - self.mean, self.var.sqrt()),
+            }
+        };
+        if self.score > self.threshold {
+            self.flagged += 1;
+            self.streak.push(price);
+            if self.reset_after.is_some_and(|k| self.streak.len() >= k) {
+                self.rebase();
+            }
+            return true;
+        }
+
+        self.streak.clear();
+        self.accept(price);
+        false
+    }
+
+    fn accept(&mut self, price: f64) {
+        self.history.push_value(price);
+        if self.mean.is_nan() {
+            self.mean = price;
+        } else {
+            let delta = price - self.mean;
+            self.mean += self.alpha * delta;
+            self.var = (1.0 - self.alpha) * (self.var + self.alpha * delta * delta);
+        }
+    }
+
+    /// Restart the baseline from the current streak of flagged prices
+    fn rebase(&mut self) {
+        self.history.clear();
+        self.mean = f64::NAN;
+        self.var = 0.0;
+        for price in std::mem::take(&mut self.streak) {
+            self.accept(price);
+        }
+    }
+}
+
+/// `distance / scale`, treating any move on a zero-scale (flat) history as infinite
+fn scaled_distance(distance: f64, scale: f64) -> f64 {
+    if scale > 0.0 {
+        distance.abs() / scale
+    } else if distance == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY
+    }
+}
+
+impl State for StreamingOutlierDetector {
+    fn check(&self) -> Result<(), String> {
+        self.history.check()
+    }
+}
+
+#[pymethods]
+impl StreamingOutlierDetector {
+    #[new]
+    #[pyo3(signature = (window=50, threshold=5.0, method="mad", reset_after=Some(10)))]
+    fn new(window: usize, threshold: f64, method: &str, reset_after: Option<usize>) -> PyResult<Self> {
+        let method = match method {
+            "mad" => OutlierMethod::Mad,
+            "ewma" => OutlierMethod::Ewma,
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown method '{}', expected 'mad' or 'ewma'", other
+                )))
+            }
+        };
+        crate::errors::require_window(window)?;
+        if threshold.is_nan() || threshold <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Threshold must be > 0"
+            ));
+        }
+        if reset_after == Some(0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "reset_after must be >= 1"
+            ));
+        }
+        Ok(StreamingOutlierDetector {
+            method,
+            threshold,
+            history: RollingWindow::with_capacity(window)?,
+            alpha: 2.0 / (window as f64 + 1.0),
+            mean: f64::NAN,
+            var: 0.0,
+            score: f64::NAN,
+            flagged: 0,
+            reset_after,
+            streak: Vec::new(),
+        })
+    }
+
+    /// Score a price against recent history; returns True for an outlier. NaN prices are
+    /// always flagged.
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, price: f64) -> bool {
+        let _span = telemetry::span("StreamingOutlierDetector.update").entered();
+        self.update(price)
+    }
+
+    /// Score of the last price (deviations from the baseline); NaN during warm-up
+    #[getter]
+    fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// Number of prices flagged so far
+    #[getter]
+    fn flagged(&self) -> usize {
+        self.flagged
+    }
+
+    #[getter]
+    fn ready(&self) -> bool {
+        self.history.is_full()
+    }
+
+    /// Score each of `prices` in one call; returns a boolean numpy array of outlier flags
+    fn update_batch<'py>(&mut self, py: Python<'py>, prices: Column<'py, f64>) -> PyResult<Bound<'py, PyArray1<bool>>> {
+        telemetry::traced("StreamingOutlierDetector.update_batch", || {
+            let (_, flags) = batch(py, [prices], |[price]| self.update(price))?;
+            Ok(PyArray1::from_vec_bound(py, flags))
+        })
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.mean = f64::NAN;
+        self.var = 0.0;
+        self.score = f64::NAN;
+        self.flagged = 0;
+        self.streak.clear();
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (usize,) {
+        (self.history.capacity,)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nan_values_stay_out_of_the_window_extremes() {
+        let mut window = RollingWindow::with_capacity(3).unwrap();
+        let mut extremes = Vec::new();
+        for value in [1.0, f64::NAN, 0.0, 2.0, 5.0, 3.0] {
+            window.push_value(value);
+            extremes.push((window.min(), window.max()));
+        }
+        assert_eq!(extremes[0], (1.0, 1.0));
+        assert!(extremes[1..4].iter().all(|(min, max)| min.is_nan() && max.is_nan()));
+        assert_eq!(&extremes[4..], &[(0.0, 5.0), (2.0, 5.0)]);
+    }
+
+    #[test]
+    fn nan_values_are_skipped_by_averages() {
+        let mut sma = StreamingSMA::new(2).unwrap();
+        let values: Vec<f64> = [1.0, f64::NAN, 3.0, 5.0].iter().map(|&v| sma.update(v)).collect();
+        assert!(values[0].is_nan() && values[1].is_nan());
+        assert_eq!(&values[2..], &[2.0, 4.0]);
+
+        let mut ema = StreamingEMA::new(2).unwrap();
+        let values: Vec<f64> = [1.0, 3.0, f64::NAN, 6.0].iter().map(|&v| ema.update(v)).collect();
+        assert_eq!(&values[1..3], &[2.0, 2.0]);
+        assert!((values[3] - (2.0 + 2.0 / 3.0 * 4.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rsi_takes_changes_from_the_last_valid_price() {
+        let mut rsi = StreamingRSI::new(2).unwrap();
+        let values: Vec<f64> = [10.0, 11.0, f64::NAN, 10.0, 12.0].iter().map(|&v| rsi.update(v)).collect();
+        assert!(values[..3].iter().all(|v| v.is_nan()));
+        // Changes +1, -1, then +2 against the price before the NaN
+        assert_eq!(values[3], 50.0);
+        assert!((values[4] - 200.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn atr_skips_bars_with_nan() {
+        let mut atr = StreamingATR::new(2).unwrap();
+        atr.update(11.0, 9.0, 10.0);
+        assert!(atr.update(f64::NAN, 9.0, 10.0).is_nan());
+        assert_eq!(atr.update(12.0, 10.0, 11.0), 2.0);
+        assert_eq!(atr.update(11.0, 10.0, f64::NAN), 2.0);
+        assert_eq!(atr.update(14.0, 11.0, 13.0), 2.5);
+        assert!(atr.ready());
+    }
+
+    #[test]
+    fn rolling_window_recovers_once_non_finite_values_leave() {
+        let mut window = RollingWindow::with_capacity(2).unwrap();
+        for value in [1.0, f64::INFINITY] {
+            window.push_value(value);
+        }
+        assert_eq!(window.mean(), f64::INFINITY);
+        assert!(window.std(1).is_nan());
+        window.push_value(3.0);
+        assert_eq!(window.mean(), f64::INFINITY);
+        window.push_value(5.0);
+        assert_eq!((window.mean(), window.sum(), window.std(0)), (4.0, 8.0, 1.0));
+        window.push_value(f64::NAN);
+        assert!(window.mean().is_nan());
+        window.push_value(7.0);
+        window.push_value(9.0);
+        assert_eq!(window.mean(), 8.0);
+    }
+
+    #[test]
+    fn rolling_window_std_survives_large_offsets_and_long_runs() {
+        let mut window = RollingWindow::with_capacity(3).unwrap();
+        for value in [1e9, 1e9 + 1.0, 1e9 + 2.0] {
+            window.push_value(value);
+        }
+        assert_eq!(window.std(1), 1.0);
+
+        let mut window = RollingWindow::with_capacity(50).unwrap();
+        for i in 0..10_000 {
+            window.push_value(1e6 + ((i * 7919) % 1000) as f64 * 0.37);
+        }
+        let values = window.as_slice();
+        let mean = values.iter().sum::<f64>() / 50.0;
+        let std = (values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / 49.0).sqrt();
+        assert!((window.mean() - mean).abs() < 1e-8);
+        assert!((window.std(1) - std).abs() < 1e-8);
+    }
+
+    #[test]
+    fn rsi_recovers_after_an_infinite_price() {
+        let mut rsi = StreamingRSI::new(2).unwrap();
+        for value in [10.0, 11.0, f64::INFINITY, 12.0, 13.0, 12.0] {
+            rsi.update(value);
+        }
+        assert_eq!(rsi.value(), 50.0);
+    }
+
+    #[test]
+    fn pickled_state_round_trips() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut sma = StreamingSMA::new(3).unwrap();
+            for value in [1.0, 5.0, 2.0, 4.0] {
+                sma.update(value);
+            }
+            let state = sma.__getstate__(py).unwrap();
+            let mut restored = StreamingSMA::new(3).unwrap();
+            restored.__setstate__(state.as_bytes()).unwrap();
+            assert_eq!(restored.window.as_slice(), &[5.0, 2.0, 4.0]);
+            assert_eq!(restored.update(6.0), sma.update(6.0));
+
+            let mut window = RollingWindow::with_capacity(3).unwrap();
+            for value in [1.0, 5.0, 2.0, 4.0] {
+                window.push_value(value);
+            }
+            let state = window.__getstate__(py).unwrap();
+            let mut restored = RollingWindow::with_capacity(3).unwrap();
+            restored.__setstate__(state.as_bytes()).unwrap();
+            assert_eq!(restored.max(), 5.0);
+            restored.push_value(0.0);
+            assert_eq!(restored.as_slice(), &[2.0, 4.0, 0.0]);
+            assert!(RollingWindow::with_capacity(4).unwrap().__setstate__(state.as_bytes()).is_err());
+        });
+    }
+
+    #[test]
+    fn inconsistent_state_is_rejected() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            assert!(load_state::<RollingWindow>(b"junk").is_err());
+            let mut window = RollingWindow::with_capacity(3).unwrap();
+            window.push_value(1.0);
+            window.len = 4;
+            let state = dump_state(py, &window).unwrap();
+            assert!(load_state::<RollingWindow>(state.as_bytes()).is_err());
+            window.len = 1;
+            window.buffer = Array1::zeros(2);
+            let state = dump_state(py, &window).unwrap();
+            assert!(load_state::<RollingWindow>(state.as_bytes()).is_err());
+        });
+    }
+
+    #[test]
+    fn outlier_baseline_follows_a_level_shift() {
+        let mut detector = StreamingOutlierDetector::new(5, 5.0, "mad", Some(10)).unwrap();
+        for price in [100.0, 101.0, 100.0, 101.0, 100.0] {
+            assert!(!detector.update(price));
+        }
+        let flags: Vec<bool> = (0..15).map(|_| detector.update(200.0)).collect();
+        assert!(flags[..10].iter().all(|&f| f));
+        assert!(flags[10..].iter().all(|&f| !f));
+        assert!(detector.update(100.0));
+        assert_eq!(detector.flagged(), 11);
+
+        let mut detector = StreamingOutlierDetector::new(5, 5.0, "ewma", None).unwrap();
+        for price in [100.0, 101.0, 100.0, 101.0, 100.0] {
+            detector.update(price);
+        }
+        assert!((0..50).all(|_| detector.update(200.0)));
+    }
+
+    #[test]
+    fn outlier_baseline_recovers_from_a_flat_history() {
+        let mut detector = StreamingOutlierDetector::new(3, 5.0, "mad", Some(2)).unwrap();
+        for price in [5.0, 5.0, 5.0] {
+            assert!(!detector.update(price));
+        }
+        // MAD is 0, so any move is flagged until the baseline restarts at the new price
+        let flags: Vec<bool> = [6.0, 6.0, 6.0, 6.0, 6.0].iter().map(|&p| detector.update(p)).collect();
+        assert_eq!(flags, vec![true, true, false, false, false]);
+        // A single bad print between good ones does not reset anything
+        assert!(detector.update(f64::NAN));
+        assert!(detector.update(60.0));
+        assert!(!detector.update(6.0));
+        assert!(detector.update(60.0));
+        assert!(!detector.update(6.0));
+    }
+}
@@ -0,0 +1,186 @@
+use pyo3::prelude::*;
+use numpy::{PyArray1, PyReadonlyArray2};
+
+use crate::column::Column;
+use crate::options::{Chain, Param};
+use crate::{linalg, optimize};
+
+/// Raw SVI parameters of one expiry: total implied variance `w(k) = a + b (rho (k - m)
+/// + sqrt((k - m)² + sigma²))` at log-moneyness `k`
+#[derive(Clone, Copy)]
+struct Svi {
+    a: f64,
+    b: f64,
+    rho: f64,
+    m: f64,
+    sigma: f64,
+}
+
+impl Svi {
+    fn from_slice(params: &[f64]) -> Self {
+        Svi { a: params[0], b: params[1], rho: params[2], m: params[3], sigma: params[4] }
+    }
+
+    fn total_variance(&self, k: f64) -> f64 {
+        let x = k - self.m;
+        self.a + self.b * (self.rho * x + (x * x + self.sigma * self.sigma).sqrt())
+    }
+
+    /// Best slice for a fixed `m` and `sigma`, where the smile is linear in `a`, `b rho
+    /// sigma` and `b sigma` (Zeliade's quasi-explicit step), projected onto `b >= 0`,
+    /// `|rho| <= 1` and a non-negative minimum variance
+    fn inner(k: &[f64], w: &[f64], m: f64, sigma: f64) -> Self {
+        let y: Vec<f64> = k.iter().map(|k| (k - m) / sigma).collect();
+        let z: Vec<f64> = y.iter().map(|y| (y * y + 1.0).sqrt()).collect();
+        let (mut d, mut c) = match linalg::ols(vec![vec![1.0; k.len()], y.clone(), z.clone()], w.to_vec()) {
+            Some(fit) => (fit.params[1], fit.params[2]),
+            None => (0.0, 0.0),
+        };
+        c = c.max(0.0);
+        d = d.clamp(-c, c);
+        let n = k.len() as f64;
+        let a = w.iter().zip(&y).zip(&z).map(|((w, y), z)| w - d * y - c * z).sum::<f64>() / n;
+        let rho = if c > 0.0 { d / c } else { 0.0 };
+        let a = a.max(-c * (1.0 - rho * rho).sqrt());
+        Svi { a, b: c / sigma, rho, m, sigma }
+    }
+}
+
+fn fit(k: &[f64], w: &[f64]) -> (Svi, f64) {
+    let sse = |svi: &Svi| k.iter().zip(w).map(|(&k, &w)| (svi.total_variance(k) - w).powi(2)).sum::<f64>();
+    let lowest = k.iter().zip(w).min_by(|a, b| a.1.total_cmp(b.1)).map_or(0.0, |(&k, _)| k);
+    let spread = k.iter().fold(0.0f64, |s, k| s.max(k.abs())).max(1e-3);
+    let outer = |x: &[f64]| sse(&Svi::inner(k, w, x[0], x[1].exp()));
+    // The outer problem over (m, ln sigma) has local minima; start from a few smile widths
+    let (best, _) = [0.05, 0.2, 0.5]
+        .iter()
+        .map(|width| optimize::nelder_mead(outer, &[lowest, (width * spread).ln()], &[0.1 * spread, 0.5], 1e-12, 2000))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    let svi = Svi::inner(k, w, best[0], best[1].exp());
+    (svi, sse(&svi))
+}
+
+/// Fit a raw SVI smile (Gatheral) to one expiry's implied volatilities.
+///
+/// `strikes` are turned into log-moneyness `ln(strike / forward)`, or taken as
+/// log-moneyness already when no `forward` is given; `ivs` are the implied volatilities at
+/// them and `tte` the time to expiry in years. The total variance `iv² * tte` is fitted by
+/// least squares with Zeliade's quasi-explicit method: the slice is linear in three of the
+/// five parameters for a fixed `m` and `sigma`, so only those two are searched by
+/// Nelder-Mead, from a few starting widths. The fit keeps `b >= 0`, `|rho| <= 1` and a
+/// non-negative minimum variance. Returns `(params, rmse)`: `[a, b, rho, m, sigma]` and
+/// the root mean squared implied volatility error. Points with a NaN strike or
+/// volatility are skipped; at least 5 must remain.
+#[pyfunction]
+#[pyo3(signature = (strikes, ivs, tte, forward=None))]
+pub fn svi_fit_rust<'py>(
+    py: Python<'py>,
+    strikes: Column<'py, f64>,
+    ivs: Column<'py, f64>,
+    tte: f64,
+    forward: Option<f64>
+) -> PyResult<(Bound<'py, PyArray1<f64>>, f64)> {
+    let strikes = strikes.values()?;
+    let ivs = ivs.values()?;
+    if strikes.len() != ivs.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "strikes and ivs must have the same length"
+        ));
+    }
+    if tte.is_nan() || tte <= 0.0 || forward.is_some_and(|f| f.is_nan() || f <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "tte and forward must be > 0"
+        ));
+    }
+    let (k, w): (Vec<f64>, Vec<f64>) = strikes
+        .iter()
+        .zip(ivs.iter())
+        .map(|(&strike, &iv)| (forward.map_or(strike, |f| (strike / f).ln()), iv * iv * tte))
+        .filter(|(k, w)| !k.is_nan() && !w.is_nan())
+        .unzip();
+    if k.len() < 5 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "SVI needs at least 5 valid quotes"
+        ));
+    }
+
+    let (svi, _) = py.allow_threads(|| fit(&k, &w));
+    let squared: f64 = k
+        .iter()
+        .zip(&w)
+        .map(|(&k, &w)| ((svi.total_variance(k).max(0.0) / tte).sqrt() - (w / tte).sqrt()).powi(2))
+        .sum();
+    let params = [svi.a, svi.b, svi.rho, svi.m, svi.sigma];
+    Ok((PyArray1::from_slice_bound(py, &params), (squared / k.len() as f64).sqrt()))
+}
+
+/// Implied volatility at log-moneyness `k` and expiry `tte` from SVI slices at the
+/// ascending `ttes`, interpolating total variance linearly in time
+fn interpolate(slices: &[Svi], ttes: &[f64], k: f64, tte: f64) -> f64 {
+    if k.is_nan() || tte.is_nan() || tte <= 0.0 {
+        return f64::NAN;
+    }
+    let after = ttes.partition_point(|&t| t < tte);
+    let w = if after == 0 {
+        // Before the first expiry the first slice's volatility holds
+        slices[0].total_variance(k) * tte / ttes[0]
+    } else if after == ttes.len() {
+        slices[after - 1].total_variance(k) * tte / ttes[after - 1]
+    } else {
+        let (t0, t1) = (ttes[after - 1], ttes[after]);
+        let weight = (tte - t0) / (t1 - t0);
+        (1.0 - weight) * slices[after - 1].total_variance(k) + weight * slices[after].total_variance(k)
+    };
+    (w.max(0.0) / tte).sqrt()
+}
+
+/// Implied volatilities from a surface of SVI slices at arbitrary `(strike, tte)` points.
+///
+/// `params_2d` holds one `[a, b, rho, m, sigma]` row per expiry, as returned by
+/// `svi_fit_rust`, for the strictly ascending expiries `ttes`. `strikes` and `tte` (the
+/// query points) are numbers or arrays broadcast as in the options functions, as is
+/// `forward`: strikes become `ln(strike / forward)`, or are taken as log-moneyness when no
+/// forward is given. Between expiries total variance is interpolated linearly in time at
+/// the same log-moneyness, which adds no calendar arbitrage when the slices do not cross;
+/// before the first and after the last expiry the nearest slice's implied volatility is
+/// held. Returns a float when every query input is a number, else an array; non-positive
+/// `tte` gives NaN.
+#[pyfunction]
+#[pyo3(signature = (params_2d, ttes, strikes, tte, forward=None))]
+pub fn surface_interp_rust<'py>(
+    py: Python<'py>,
+    params_2d: PyReadonlyArray2<'py, f64>,
+    ttes: Column<'py, f64>,
+    strikes: Param<'py>,
+    tte: Param<'py>,
+    forward: Option<Param<'py>>
+) -> PyResult<PyObject> {
+    let params = params_2d.as_array();
+    let ttes = ttes.values()?;
+    if params.ncols() != 5 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "params_2d must have 5 columns: a, b, rho, m, sigma"
+        ));
+    }
+    if params.nrows() != ttes.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "params_2d must have one row per expiry in ttes"
+        ));
+    }
+    if ttes.is_empty() || ttes[0].is_nan() || ttes[0] <= 0.0 || ttes.windows(2).any(|t| t[1].is_nan() || t[1] <= t[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "ttes must be positive and strictly ascending"
+        ));
+    }
+    let slices: Vec<Svi> = params.rows().into_iter().map(|row| Svi::from_slice(&row.to_vec())).collect();
+    let log_moneyness = forward.is_none();
+    let chain = Chain::new([("strikes", strikes), ("tte", tte), ("forward", forward.unwrap_or(Param::Value(1.0)))])?;
+    let vols = py.allow_threads(|| {
+        chain.map(|[strike, tte, forward]| {
+            let k = if log_moneyness { strike } else { (strike / forward).ln() };
+            interpolate(&slices, &ttes, k, tte)
+        })
+    });
+    Ok(chain.wrap(py, vols))
+}
@@ -0,0 +1,476 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde::{Deserialize, Serialize};
+
+use crate::alignment::Alignment;
+use crate::column::{map_series, Column, FloatElement, SeriesInput};
+use crate::config::FastMathConfig;
+use crate::skipna::NanPolicy;
+use crate::streaming::{dump_state, load_state, State};
+use crate::telemetry;
+
+/// Raw values buffered per unit of compression before they are merged into centroids
+const BUFFER_FACTOR: f64 = 5.0;
+/// Smallest block of a rolling window that gets its own digest
+const MIN_BLOCK: usize = 32;
+/// Most digests kept per rolling window, which bounds memory for huge windows
+const MAX_BLOCKS: usize = 4096;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+fn check_compression(compression: f64) -> PyResult<()> {
+    if !(compression >= 1.0 && compression.is_finite()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "compression must be a finite number >= 1"
+        ));
+    }
+    Ok(())
+}
+
+fn check_quantile(q: f64) -> PyResult<()> {
+    if !(0.0..=1.0).contains(&q) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "q must be between 0 and 1"
+        ));
+    }
+    Ok(())
+}
+
+/// Merging t-digest (Dunning & Ertl) of a stream of weighted values.
+///
+/// Values are buffered and periodically merged into centroids whose size is bounded by
+/// the arcsine scale function, so the tails are kept at much finer resolution than the
+/// middle and quantile error is smallest where it matters most for risk statistics.
+/// Memory is O(`compression`) regardless of how many values are added; larger
+/// compression trades memory and speed for accuracy. The exact minimum and maximum are
+/// tracked, and digests built separately (e.g. per thread or per day) can be merged.
+#[pyclass(module = "fast_math")]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    count: f64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    pub fn with_compression(compression: f64) -> Self {
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Add `value` with `weight`; NaN values and non-positive weights are ignored
+    pub fn add(&mut self, value: f64, weight: f64) {
+        if value.is_nan() || weight <= 0.0 {
+            return;
+        }
+        self.buffer.push(Centroid { mean: value, weight });
+        self.count += weight;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() as f64 >= self.compression * BUFFER_FACTOR {
+            self.compress();
+        }
+    }
+
+    /// Fold the centroids of `other` into this digest
+    pub fn merge_from(&mut self, other: &TDigest) {
+        self.buffer.extend(other.centroids.iter().chain(&other.buffer));
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    /// Position of quantile `q` on the scale where every centroid spans at most one unit
+    fn scale(&self, q: f64) -> f64 {
+        self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn inverse_scale(&self, k: f64) -> f64 {
+        let angle = (k * 2.0 * std::f64::consts::PI / self.compression).min(std::f64::consts::FRAC_PI_2);
+        (angle.sin() + 1.0) / 2.0
+    }
+
+    /// Merge buffered values into the centroids
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.buffer);
+        all.append(&mut self.centroids);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total = self.count;
+        let mut merged = Vec::with_capacity(self.compression.ceil() as usize);
+        let mut current = all[0];
+        let mut before = 0.0;
+        let mut limit = total * self.inverse_scale(self.scale(0.0) + 1.0);
+        for &next in &all[1..] {
+            if before + current.weight + next.weight <= limit {
+                let weight = current.weight + next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                merged.push(current);
+                limit = total * self.inverse_scale(self.scale(before / total) + 1.0);
+                current = next;
+            }
+        }
+        merged.push(current);
+
+        self.centroids = merged;
+        all.clear();
+        self.buffer = all;
+    }
+
+    /// Estimated `q` quantile, interpolating between centroid centres and the exact
+    /// extremes; NaN when empty
+    pub fn value_at(&mut self, q: f64) -> f64 {
+        self.compress();
+        if self.centroids.is_empty() {
+            return f64::NAN;
+        }
+        if q <= 0.0 {
+            return self.min;
+        }
+        if q >= 1.0 {
+            return self.max;
+        }
+
+        let target = q * self.count;
+        let mut before = 0.0;
+        for (i, c) in self.centroids.iter().enumerate() {
+            let centre = before + c.weight / 2.0;
+            if target < centre {
+                if i == 0 {
+                    return self.min + (c.mean - self.min) * target / centre;
+                }
+                let prev = self.centroids[i - 1];
+                let prev_centre = before - prev.weight / 2.0;
+                return prev.mean + (c.mean - prev.mean) * (target - prev_centre) / (centre - prev_centre);
+            }
+            before += c.weight;
+        }
+        let last = self.centroids[self.centroids.len() - 1];
+        let centre = self.count - last.weight / 2.0;
+        last.mean + (self.max - last.mean) * (target - centre) / (self.count - centre)
+    }
+
+    /// Estimated fraction of the weight at or below `x`; NaN when empty
+    pub fn fraction_below(&mut self, x: f64) -> f64 {
+        self.compress();
+        if self.centroids.is_empty() || x.is_nan() {
+            return f64::NAN;
+        }
+        if x < self.min {
+            return 0.0;
+        }
+        if x >= self.max {
+            return 1.0;
+        }
+
+        let first = self.centroids[0];
+        if x < first.mean {
+            let share = (x - self.min) / (first.mean - self.min);
+            return share * first.weight / 2.0 / self.count;
+        }
+        let mut before = 0.0;
+        for pair in self.centroids.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if x < b.mean {
+                let (from, to) = (before + a.weight / 2.0, before + a.weight + b.weight / 2.0);
+                let share = (x - a.mean) / (b.mean - a.mean);
+                return (from + share * (to - from)) / self.count;
+            }
+            before += a.weight;
+        }
+        let last = self.centroids[self.centroids.len() - 1];
+        let centre = self.count - last.weight / 2.0;
+        let share = (x - last.mean) / (self.max - last.mean);
+        (centre + share * (self.count - centre)) / self.count
+    }
+}
+
+impl State for TDigest {
+    fn check(&self) -> Result<(), String> {
+        if !(self.compression >= 1.0 && self.compression.is_finite()) {
+            return Err("compression must be a finite number >= 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl TDigest {
+    #[new]
+    #[pyo3(signature = (compression=100.0))]
+    fn new(compression: f64) -> PyResult<Self> {
+        check_compression(compression)?;
+        Ok(TDigest::with_compression(compression))
+    }
+
+    /// Add one value; NaN is ignored
+    #[pyo3(signature = (value, weight=1.0))]
+    fn update(&mut self, value: f64, weight: f64) -> PyResult<()> {
+        telemetry::traced("TDigest.update", || {
+            if !(weight >= 0.0 && weight.is_finite()) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "weight must be a finite non-negative number"
+                ));
+            }
+            self.add(value, weight);
+            Ok(())
+        })
+    }
+
+    /// Add every value of `values` with unit weight, with the GIL released
+    fn update_batch(&mut self, py: Python<'_>, values: Column<'_, f64>) -> PyResult<()> {
+        telemetry::traced("TDigest.update_batch", || {
+            let values = values.values()?;
+            py.allow_threads(|| values.iter().for_each(|&v| self.add(v, 1.0)));
+            Ok(())
+        })
+    }
+
+    /// Merge another digest into this one, as if its values had been added here
+    fn merge(&mut self, other: &TDigest) {
+        self.merge_from(other);
+    }
+
+    /// Estimated `q` quantile (0 <= q <= 1); NaN when empty
+    fn quantile(&mut self, q: f64) -> PyResult<f64> {
+        check_quantile(q)?;
+        Ok(self.value_at(q))
+    }
+
+    /// Estimated fraction of the added weight at or below `x`; NaN when empty
+    fn cdf(&mut self, x: f64) -> f64 {
+        self.fraction_below(x)
+    }
+
+    /// Total weight added
+    #[getter]
+    fn count(&self) -> f64 {
+        self.count
+    }
+
+    #[getter]
+    fn min(&self) -> f64 {
+        if self.count > 0.0 { self.min } else { f64::NAN }
+    }
+
+    #[getter]
+    fn max(&self) -> f64 {
+        if self.count > 0.0 { self.max } else { f64::NAN }
+    }
+
+    #[getter]
+    fn compression(&self) -> f64 {
+        self.compression
+    }
+
+    fn reset(&mut self) {
+        *self = TDigest::with_compression(self.compression);
+    }
+
+    fn __getstate__<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.compress();
+        dump_state(py, self)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = load_state(state)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (f64,) {
+        (self.compression,)
+    }
+}
+
+/// Approximate `q` quantile of every full window, skipping NaN unless `propagate`, in
+/// which case windows holding a NaN are NaN.
+///
+/// Digests cannot forget values, so windows are served as a queue of two stacks: when
+/// the window start passes the front stack, the values since then are split into blocks
+/// and a digest of each block's suffix is built, back to front. Each window is then the
+/// suffix digest after its start block, the few raw values of the start block and a
+/// running digest of everything added since, so each step costs O(block + compression)
+/// however long the window.
+fn rolling_quantile<T: FloatElement>(
+    slice: &[T],
+    window: usize,
+    q: f64,
+    compression: f64,
+    propagate: bool
+) -> PyResult<Vec<T>> {
+    let n = slice.len();
+    let valid = crate::errors::check_window(window, n)?;
+    check_quantile(q)?;
+    check_compression(compression)?;
+
+    let values = slice.iter().map(|v| v.to_f64().unwrap()).collect::<Vec<_>>();
+    let block = (window / MAX_BLOCKS).max(MIN_BLOCK);
+    let mut suffixes = Vec::new();
+    let (mut front_start, mut front_end) = (0, 0);
+    let mut back = TDigest::with_compression(compression);
+    let mut result = Vec::with_capacity(valid);
+    let mut nans = 0usize;
+    for end in 0..n {
+        back.add(values[end], 1.0);
+        nans += values[end].is_nan() as usize;
+        if end + 1 < window {
+            continue;
+        }
+        let start = end + 1 - window;
+        if start > 0 {
+            nans -= values[start - 1].is_nan() as usize;
+        }
+        if start >= front_end {
+            // Everything still in the window moves to the front stack
+            front_start = start;
+            front_end = end + 1;
+            let mut suffix = TDigest::with_compression(compression);
+            suffixes = (front_start..front_end)
+                .step_by(block)
+                .rev()
+                .map(|from| {
+                    values[from..(from + block).min(front_end)].iter().for_each(|&v| suffix.add(v, 1.0));
+                    suffix.compress();
+                    suffix.clone()
+                })
+                .collect();
+            suffixes.reverse();
+            back = TDigest::with_compression(compression);
+        }
+
+        if propagate && nans > 0 {
+            result.push(T::nan());
+            continue;
+        }
+        let k = (start - front_start) / block;
+        let boundary = (front_start + (k + 1) * block).min(front_end);
+        let mut digest = match suffixes.get(k + 1) {
+            Some(suffix) => suffix.clone(),
+            None => TDigest::with_compression(compression),
+        };
+        values[start..boundary].iter().for_each(|&v| digest.add(v, 1.0));
+        digest.merge_from(&back);
+        result.push(T::from(digest.value_at(q)).unwrap());
+    }
+    Ok(result)
+}
+
+/// Approximate rolling quantile over every full window, using t-digests
+///
+/// For huge windows where exact rolling quantiles are too slow. With the default
+/// `nan_policy="propagate"` windows holding a NaN are NaN; "raise" rejects NaN inputs
+/// and "omit" skips NaN values (all-NaN windows are NaN). Accepts a single series or a
+/// 2D array with one series per row. Accuracy follows `TDigest(compression)`: relative
+/// rank error is smallest in the tails. `align` is "valid", "same" or an integer shift,
+/// as for `moving_average_rust`.
+#[pyfunction]
+#[pyo3(signature = (data, window, q, compression=100.0, align=None, nan_policy=None, config=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn rolling_quantile_approx_rust<'py>(
+    py: Python<'py>,
+    data: SeriesInput<'py>,
+    window: usize,
+    q: f64,
+    compression: f64,
+    align: Option<Alignment>,
+    nan_policy: Option<NanPolicy>,
+    config: Option<PyRef<'py, FastMathConfig>>
+) -> PyResult<PyObject> {
+    let _config = crate::config::CallScope::enter(config)?;
+    let align = crate::config::resolve_align(align);
+    let policy = NanPolicy::resolve(nan_policy, false)?;
+    policy.check_input(&data)?;
+    let propagate = policy == NanPolicy::Propagate;
+    map_series!(py, data, |slice| {
+        rolling_quantile(slice, window, q, compression, propagate).map(|r| align.pad(r, slice.len()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SplitMix;
+
+    fn normals(n: usize, seed: u64) -> Vec<f64> {
+        let mut rng = SplitMix::new(seed, 0);
+        (0..n / 2).flat_map(|_| <[f64; 2]>::from(rng.normal_pair())).collect()
+    }
+
+    fn digest(values: &[f64]) -> TDigest {
+        let mut digest = TDigest::with_compression(100.0);
+        values.iter().for_each(|&v| digest.add(v, 1.0));
+        digest
+    }
+
+    /// How far the estimated `q` quantile sits from `q` in rank among `sorted`
+    fn rank_error(digest: &mut TDigest, sorted: &[f64], q: f64) -> f64 {
+        let estimate = digest.value_at(q);
+        let rank = sorted.partition_point(|&v| v < estimate) as f64 / sorted.len() as f64;
+        (rank - q).abs()
+    }
+
+    #[test]
+    fn quantiles_track_the_exact_ranks() {
+        let values = normals(100_000, 5);
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+        let mut digest = digest(&values);
+        for q in [0.001, 0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99, 0.999] {
+            assert!(rank_error(&mut digest, &sorted, q) < 1e-3, "q={}", q);
+        }
+        assert_eq!((digest.value_at(0.0), digest.value_at(1.0)), (sorted[0], sorted[sorted.len() - 1]));
+        assert!((digest.fraction_below(sorted[50_000]) - 0.5).abs() < 5e-3);
+        assert!(digest.centroids.len() <= 100);
+    }
+
+    #[test]
+    fn merged_digests_match_one_built_from_everything() {
+        let values = normals(20_000, 9);
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+        let mut merged = digest(&values[..7_000]);
+        merged.merge_from(&digest(&values[7_000..]));
+        assert_eq!(merged.count, 20_000.0);
+        assert_eq!((merged.min, merged.max), (sorted[0], sorted[sorted.len() - 1]));
+        for q in [0.01, 0.1, 0.5, 0.9, 0.99] {
+            assert!(rank_error(&mut merged, &sorted, q) < 5e-3, "q={}", q);
+        }
+    }
+
+    #[test]
+    fn serialized_state_round_trips() {
+        pyo3::prepare_freethreaded_python();
+        let mut digest = digest(&normals(5_000, 3));
+        // A state still holding buffered values restores as well as a compressed one
+        digest.add(10.0, 2.0);
+        let bytes = rmp_serde::to_vec_named(&digest).unwrap();
+        let mut restored: TDigest = load_state(&bytes).unwrap();
+        assert_eq!((restored.count, restored.min, restored.max), (digest.count, digest.min, digest.max));
+        for q in [0.0, 0.01, 0.5, 0.99, 1.0] {
+            assert_eq!(restored.value_at(q), digest.value_at(q));
+        }
+        restored.compression = 0.5;
+        let bytes = rmp_serde::to_vec_named(&restored).unwrap();
+        assert!(load_state::<TDigest>(&bytes).is_err());
+    }
+}
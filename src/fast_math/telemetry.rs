@@ -0,0 +1,422 @@
+// Call tracing for live monitoring. Kernels and the class methods of the streaming
+// indicators, `OhlcvFrame` and `Replayer` open a `tracing` span per call (target
+// "fast_math"); module functions get theirs from a wrapper that
+// `fast_math.tracing.enable_tracing()` swaps in for every function of the module and its
+// submodules. A subscriber installed on first use turns each closed span into a call count,
+// an error count and a log2 latency histogram per kernel. "off" puts the original functions
+// back and disables the spans, so an untraced process pays one atomic load per method call.
+// With `level="debug"` each call is also appended to the export file as a JSON line.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use serde_json::json;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata};
+
+use crate::cache::{restore, CachedFunction, Installed};
+
+/// How much is recorded
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Off = 0,
+    /// Counters and latency histograms
+    Info = 1,
+    /// Also every call, to the export file
+    Debug = 2,
+}
+
+impl Level {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" | "none" => Ok(Level::Off),
+            "info" => Ok(Level::Info),
+            "debug" | "trace" => Ok(Level::Debug),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown level '{}', expected 'off', 'info' or 'debug'", name
+            ))),
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static METRICS: Mutex<BTreeMap<String, Stats>> = Mutex::new(BTreeMap::new());
+static EXPORT: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+/// The original functions replaced by wrappers: `(module, attribute, function)`
+static INSTALLED: Mutex<Vec<Installed>> = Mutex::new(Vec::new());
+/// The `fast_math` module, whose functions get traced
+static ROOT: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+
+/// Latency buckets: bucket `k` holds calls taking `[2^k, 2^(k+1))` nanoseconds
+const BUCKETS: usize = 48;
+
+/// Counters and latency histogram of one kernel
+struct Stats {
+    calls: u64,
+    errors: u64,
+    total_ns: u64,
+    min_ns: u64,
+    max_ns: u64,
+    buckets: [u64; BUCKETS],
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats { calls: 0, errors: 0, total_ns: 0, min_ns: u64::MAX, max_ns: 0, buckets: [0; BUCKETS] }
+    }
+}
+
+impl Stats {
+    fn record(&mut self, ns: u64, error: bool) {
+        self.calls += 1;
+        self.errors += error as u64;
+        self.total_ns = self.total_ns.saturating_add(ns);
+        self.min_ns = self.min_ns.min(ns);
+        self.max_ns = self.max_ns.max(ns);
+        let bucket = (u64::BITS - ns.max(1).leading_zeros() - 1) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+    }
+
+    /// Upper bound of the bucket holding quantile `q`, capped at the slowest call
+    fn quantile(&self, q: f64) -> u64 {
+        let rank = (q * self.calls as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (k, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return (1u64 << (k + 1)).min(self.max_ns);
+            }
+        }
+        self.max_ns
+    }
+
+    fn to_json(&self, kernel: &str) -> serde_json::Value {
+        json!({
+            "type": "metrics",
+            "kernel": kernel,
+            "calls": self.calls,
+            "errors": self.errors,
+            "total_ns": self.total_ns,
+            "min_ns": self.min_ns,
+            "max_ns": self.max_ns,
+            "p50_ns": self.quantile(0.5),
+            "p90_ns": self.quantile(0.9),
+            "p99_ns": self.quantile(0.99),
+        })
+    }
+}
+
+fn unix_ns() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64)
+}
+
+fn record(kernel: &str, elapsed: Duration, error: bool) {
+    let ns = elapsed.as_nanos() as u64;
+    METRICS.lock().unwrap().entry(kernel.to_string()).or_default().record(ns, error);
+    if LEVEL.load(Ordering::Relaxed) >= Level::Debug as u8 {
+        if let Some(out) = EXPORT.lock().unwrap().as_mut() {
+            let line = json!({
+                "type": "call",
+                "kernel": kernel,
+                "end_ns": unix_ns(),
+                "latency_ns": ns,
+                "error": error,
+            });
+            // Tracing must never fail the traced call; a full disk only loses lines
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+}
+
+/// Open the span of one call of `kernel`; a no-op unless tracing is enabled. Callers
+/// enter it for the duration of the call and record `error = true` when it fails
+pub fn span(kernel: &str) -> tracing::Span {
+    tracing::info_span!(target: "fast_math", "call", kernel, error = tracing::field::Empty)
+}
+
+/// Run `call` inside the span of `kernel`, recording whether it failed
+pub fn traced<T>(kernel: &str, call: impl FnOnce() -> PyResult<T>) -> PyResult<T> {
+    let span = span(kernel);
+    let result = span.in_scope(call);
+    if result.is_err() {
+        span.record("error", true);
+    }
+    result
+}
+
+/// A span still open: its kernel, when it opened, whether it failed and how many handles
+/// to it are alive
+struct Open {
+    kernel: String,
+    start: Instant,
+    error: bool,
+    refs: usize,
+}
+
+/// Reads the `kernel` and `error` fields of a span
+struct Fields<'a>(&'a mut Open);
+
+impl Visit for Fields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "kernel" {
+            self.0.kernel = value.to_string();
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == "error" {
+            self.0.error = value;
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "kernel" {
+            self.0.kernel = format!("{:?}", value);
+        }
+    }
+}
+
+/// The subscriber turning closed "fast_math" spans into metrics
+#[derive(Default)]
+struct Collector {
+    next: AtomicU64,
+    open: Mutex<HashMap<u64, Open>>,
+}
+
+impl tracing::Subscriber for Collector {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.target().starts_with("fast_math") {
+            Interest::sometimes()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        LEVEL.load(Ordering::Relaxed) >= Level::Info as u8 && metadata.target().starts_with("fast_math")
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut open = Open { kernel: String::new(), start: Instant::now(), error: false, refs: 1 };
+        attributes.record(&mut Fields(&mut open));
+        let id = self.next.fetch_add(1, Ordering::Relaxed) + 1;
+        self.open.lock().unwrap().insert(id, open);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(open) = self.open.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut Fields(open));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(open) = self.open.lock().unwrap().get_mut(&span.into_u64()) {
+            open.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.open.lock().unwrap();
+        let Some(open) = spans.get_mut(&span.into_u64()) else { return false };
+        open.refs -= 1;
+        if open.refs > 0 {
+            return false;
+        }
+        let open = spans.remove(&span.into_u64()).unwrap();
+        drop(spans);
+        record(&open.kernel, open.start.elapsed(), open.error);
+        true
+    }
+}
+
+/// Make the collector the process-wide subscriber, once. Fails if the embedding process
+/// installed its own subscriber first
+fn install_collector() -> PyResult<()> {
+    static ONCE: Once = Once::new();
+    let mut result = Ok(());
+    ONCE.call_once(|| result = tracing::subscriber::set_global_default(Collector::default()));
+    result.map_err(|err| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!(
+            "Cannot install the fast_math tracing subscriber: {}", err
+        ))
+    })
+}
+
+/// A traced stand-in for one `fast_math` function
+#[pyclass(module = "fast_math.tracing")]
+pub struct TracedFunction {
+    kernel: String,
+    pub(crate) inner: PyObject,
+}
+
+#[pymethods]
+impl TracedFunction {
+    #[pyo3(signature = (*args, **kwargs))]
+    fn __call__(&self, py: Python<'_>, args: &Bound<'_, PyTuple>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+        traced(&self.kernel, || self.inner.call_bound(py, args, kwargs))
+    }
+
+    /// The original function
+    #[getter]
+    fn __wrapped__(&self, py: Python<'_>) -> PyObject {
+        self.inner.clone_ref(py)
+    }
+
+    fn __getattr__(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        self.inner.getattr(py, name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TracedFunction({})", self.kernel)
+    }
+}
+
+/// Replace the functions of `module` (and, from the root, its submodules other than this
+/// one and `cache`, whose functions manage the cache rather than compute) by wrappers,
+/// remembering the wrappers. Functions already wrapped by the cache
+/// are wrapped again, so both apply
+fn install(py: Python<'_>, module: &Bound<'_, PyModule>, prefix: &str, installed: &mut Vec<Installed>) -> PyResult<()> {
+    for name in module.dir().iter() {
+        let name: String = name.extract()?;
+        let value = module.getattr(name.as_str())?;
+        if value.is_instance_of::<PyCFunction>() || value.is_instance_of::<CachedFunction>() {
+            let wrapper = TracedFunction { kernel: format!("{}{}", prefix, name), inner: value.clone().unbind() };
+            let wrapper = Py::new(py, wrapper)?.into_any();
+            module.setattr(name.as_str(), &wrapper)?;
+            installed.push((module.clone().unbind(), name, wrapper));
+        } else if prefix.is_empty() && name != "tracing" && name != "cache" {
+            if let Ok(submodule) = value.downcast::<PyModule>() {
+                install(py, submodule, &format!("{}.", name), installed)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn uninstall(py: Python<'_>) -> PyResult<()> {
+    for installed in INSTALLED.lock().unwrap().drain(..) {
+        restore(py, installed)?;
+    }
+    Ok(())
+}
+
+/// Append every kernel's metrics to the export file and flush it
+fn export_metrics() -> PyResult<()> {
+    if let Some(out) = EXPORT.lock().unwrap().as_mut() {
+        for (kernel, stats) in METRICS.lock().unwrap().iter() {
+            writeln!(out, "{}", stats.to_json(kernel))?;
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Trace every `fast_math` kernel call, for monitoring inside the live trading process.
+///
+/// `level="info"` (the default) counts calls and errors and records a latency histogram
+/// (power-of-two nanosecond buckets) per kernel, read back with `metrics()`;
+/// "debug" also appends every call to `path` as a JSON line with its kernel, end time
+/// (Unix nanoseconds), latency and whether it raised; "off" stops tracing. Each call is a
+/// `tracing` span with target "fast_math", collected by a subscriber this installs as
+/// the process-wide default the first time tracing is enabled. Methods of the streaming
+/// indicators, `OhlcvFrame` and `Replayer` open their spans themselves (kernel
+/// "Class.method"); functions are traced by replacing the module attributes with
+/// wrappers, so names imported with `from fast_math import ...` before enabling stay
+/// untraced, and "off" restores the originals. `path`, when given, is opened for appending; `flush()`
+/// and turning tracing off append one JSON line of metrics per kernel to it. The metrics
+/// are kept when tracing stops, until `reset()`.
+#[pyfunction]
+#[pyo3(signature = (level="info", path=None))]
+fn enable_tracing(py: Python<'_>, level: &str, path: Option<&str>) -> PyResult<()> {
+    let level = Level::parse(level)?;
+    export_metrics()?;
+    uninstall(py)?;
+    *EXPORT.lock().unwrap() = None;
+    LEVEL.store(level as u8, Ordering::Relaxed);
+    if level == Level::Off {
+        return Ok(());
+    }
+    install_collector()?;
+    if let Some(path) = path {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        *EXPORT.lock().unwrap() = Some(BufWriter::new(file));
+    }
+    let Some(root) = ROOT.get(py) else { return Ok(()) };
+    let mut installed = INSTALLED.lock().unwrap();
+    install(py, root.bind(py), "", &mut installed)
+}
+
+/// Per-kernel metrics since the last `reset`: a dict mapping kernel names to `calls`,
+/// `errors`, `total_ns`, `mean_ns`, `min_ns`, `max_ns`, the `p50_ns`, `p90_ns` and
+/// `p99_ns` latency quantiles (bucket upper bounds) and the `histogram`, a list of
+/// `(upper_bound_ns, count)` for the non-empty buckets
+#[pyfunction]
+fn metrics<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+    let result = PyDict::new_bound(py);
+    for (kernel, stats) in METRICS.lock().unwrap().iter() {
+        let entry = PyDict::new_bound(py);
+        entry.set_item("calls", stats.calls)?;
+        entry.set_item("errors", stats.errors)?;
+        entry.set_item("total_ns", stats.total_ns)?;
+        entry.set_item("mean_ns", stats.total_ns as f64 / stats.calls.max(1) as f64)?;
+        entry.set_item("min_ns", stats.min_ns)?;
+        entry.set_item("max_ns", stats.max_ns)?;
+        entry.set_item("p50_ns", stats.quantile(0.5))?;
+        entry.set_item("p90_ns", stats.quantile(0.9))?;
+        entry.set_item("p99_ns", stats.quantile(0.99))?;
+        let histogram: Vec<(u64, u64)> = stats
+            .buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(k, &count)| (1u64 << (k + 1), count))
+            .collect();
+        entry.set_item("histogram", histogram)?;
+        result.set_item(kernel, entry)?;
+    }
+    Ok(result)
+}
+
+/// Clear the metrics
+#[pyfunction]
+fn reset() {
+    METRICS.lock().unwrap().clear();
+}
+
+/// Append the current metrics to the export file and flush it
+#[pyfunction]
+fn flush() -> PyResult<()> {
+    export_metrics()
+}
+
+/// Populate the `fast_math.tracing` submodule; `root` is the module whose functions
+/// `enable_tracing` wraps
+pub fn register(m: &Bound<'_, PyModule>, root: &Bound<'_, PyModule>) -> PyResult<()> {
+    let _ = ROOT.set(m.py(), root.clone().unbind());
+    m.add_class::<TracedFunction>()?;
+    m.add_function(wrap_pyfunction!(enable_tracing, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(reset, m)?)?;
+    m.add_function(wrap_pyfunction!(flush, m)?)?;
+    Ok(())
+}
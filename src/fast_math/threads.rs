@@ -0,0 +1,115 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use pyo3::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+/// Environment variable overriding the default number of worker threads
+const NUM_THREADS_VAR: &str = "FAST_MATH_NUM_THREADS";
+
+/// Worker pool for all parallel kernels, kept separate from Rayon's global pool so it can
+/// be resized and does not compete with other Rayon users in the process
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+/// Pools of the sizes `FastMathConfig` scopes asked for, built on first use and kept so
+/// entering a scope does not start and join a new set of threads every time
+static SIZED: Mutex<Option<HashMap<usize, Arc<ThreadPool>>>> = Mutex::new(None);
+
+thread_local! {
+    /// Pools of the `FastMathConfig` blocks entered on this thread, innermost last
+    static SCOPED: RefCell<Vec<Arc<ThreadPool>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Build a pool of `n` threads; 0 means `FAST_MATH_NUM_THREADS`, then Rayon's own default
+/// (`RAYON_NUM_THREADS` or one thread per core)
+fn build_pool(n: usize) -> PyResult<ThreadPool> {
+    let n = match n {
+        0 => std::env::var(NUM_THREADS_VAR).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(0),
+        n => n,
+    };
+    ThreadPoolBuilder::new()
+        .num_threads(n)
+        .thread_name(|i| format!("fast_math-{}", i))
+        .build()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
+fn pool() -> Arc<ThreadPool> {
+    if let Some(pool) = SCOPED.with(|scoped| scoped.borrow().last().cloned()) {
+        return pool;
+    }
+    if let Some(pool) = POOL.read().unwrap().as_ref() {
+        return pool.clone();
+    }
+    let mut slot = POOL.write().unwrap();
+    slot.get_or_insert_with(|| Arc::new(build_pool(0).expect("failed to start the fast_math thread pool")))
+        .clone()
+}
+
+/// Run this thread's calls on a pool of `n` threads (the process pool for 0) until the
+/// matching `exit_scope`; scopes of the same size reuse one pool
+pub fn enter_scope(n: usize) -> PyResult<()> {
+    let pool = match n {
+        0 => pool(),
+        n => {
+            let mut sized = SIZED.lock().unwrap();
+            match sized.get_or_insert_with(HashMap::new).entry(n) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.get().clone(),
+                std::collections::hash_map::Entry::Vacant(entry) => entry.insert(Arc::new(build_pool(n)?)).clone(),
+            }
+        }
+    };
+    SCOPED.with(|scoped| scoped.borrow_mut().push(pool));
+    Ok(())
+}
+
+pub fn exit_scope() {
+    SCOPED.with(|scoped| scoped.borrow_mut().pop());
+}
+
+/// Run `op` (and any Rayon parallelism inside it) on the fast_math pool
+pub fn install<OP, R>(op: OP) -> R
+where
+    OP: FnOnce() -> R + Send,
+    R: Send,
+{
+    pool().install(op)
+}
+
+/// Set the number of worker threads used by parallel kernels.
+///
+/// 0 restores the default: `FAST_MATH_NUM_THREADS` if set, else `RAYON_NUM_THREADS`, else
+/// one thread per core. Calls already running finish on the previous pool.
+///
+/// Results never depend on the thread count: parallel kernels split their work into
+/// pieces fixed by the input (rows, tiles, windows), collect the results in order and
+/// never reduce across threads.
+#[pyfunction]
+pub fn set_num_threads(n: usize) -> PyResult<()> {
+    let pool = build_pool(n)?;
+    *POOL.write().unwrap() = Some(Arc::new(pool));
+    Ok(())
+}
+
+/// Number of worker threads used by parallel kernels
+#[pyfunction]
+pub fn get_num_threads() -> usize {
+    pool().current_num_threads()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_of_one_size_share_a_pool() {
+        enter_scope(3).unwrap();
+        let first = pool();
+        exit_scope();
+        enter_scope(3).unwrap();
+        assert!(Arc::ptr_eq(&first, &pool()));
+        assert_eq!(get_num_threads(), 3);
+        exit_scope();
+    }
+}
@@ -0,0 +1,257 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+
+use pyo3::prelude::*;
+use numpy::PyArray1;
+use memmap2::Mmap;
+
+use crate::column::Column;
+
+/// File signature, including a format version byte
+const MAGIC: &[u8; 8] = b"FTICKS\x00\x01";
+/// Magic (8), record size (4), reserved (4), record count (8), reserved (8)
+const HEADER_BYTES: usize = 32;
+/// Timestamp i64, price f64, size f64, flags u32, padding u32; all little-endian
+const RECORD_BYTES: usize = 32;
+
+/// Timestamps, prices, sizes and flags of a range of ticks
+pub type TickArrays<'py> = (
+    Bound<'py, PyArray1<i64>>,
+    Bound<'py, PyArray1<f64>>,
+    Bound<'py, PyArray1<f64>>,
+    Bound<'py, PyArray1<u32>>,
+);
+
+fn format_error(path: &str, message: &str) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("{}: {}", path, message))
+}
+
+fn header(count: u64) -> [u8; HEADER_BYTES] {
+    let mut header = [0u8; HEADER_BYTES];
+    header[..8].copy_from_slice(MAGIC);
+    header[8..12].copy_from_slice(&(RECORD_BYTES as u32).to_le_bytes());
+    header[16..24].copy_from_slice(&count.to_le_bytes());
+    header
+}
+
+/// Validate a header and return the record count it declares
+fn parse_header(path: &str, bytes: &[u8]) -> PyResult<u64> {
+    if bytes.len() < HEADER_BYTES || &bytes[..8] != MAGIC {
+        return Err(format_error(path, "not a tick file"));
+    }
+    let record_bytes = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+    if record_bytes != RECORD_BYTES {
+        return Err(format_error(path, "unsupported record size"));
+    }
+    Ok(u64::from_le_bytes(bytes[16..24].try_into().unwrap()))
+}
+
+fn read_i64(record: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(record[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_f64(record: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(record[offset..offset + 8].try_into().unwrap())
+}
+
+/// Write ticks to a binary tick file, or append them to an existing one.
+///
+/// Timestamps must be sorted ascending (and, when appending, not precede the last tick
+/// already in the file) so that `TickFile.read_range` can binary-search them. `flags`
+/// defaults to 0 and must fit in an unsigned 32-bit integer.
+#[pyfunction]
+#[pyo3(signature = (path, timestamps, prices, sizes, flags=None, append=false))]
+pub fn write_ticks_rust<'py>(
+    path: &str,
+    timestamps: Column<'py, i64>,
+    prices: Column<'py, f64>,
+    sizes: Column<'py, f64>,
+    flags: Option<Column<'py, i64>>,
+    append: bool
+) -> PyResult<()> {
+    let timestamps = timestamps.values()?;
+    let prices = prices.values()?;
+    let sizes = sizes.values()?;
+    let flags = flags.as_ref().map(Column::values).transpose()?;
+
+    let n = timestamps.len();
+    if prices.len() != n || sizes.len() != n || flags.as_ref().is_some_and(|f| f.len() != n) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "timestamps, prices, sizes and flags must have the same length"
+        ));
+    }
+    if timestamps.windows(2).any(|w| w[1] < w[0]) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Timestamps must be sorted ascending"
+        ));
+    }
+    if flags.as_ref().is_some_and(|f| f.iter().any(|&v| !(0..=u32::MAX as i64).contains(&v))) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Flags must fit in an unsigned 32-bit integer"
+        ));
+    }
+
+    let exists = std::path::Path::new(path).exists();
+    let mut file = if append && exists {
+        OpenOptions::new().read(true).write(true).open(path)?
+    } else {
+        File::create(path)?
+    };
+
+    let mut existing = 0u64;
+    if append && exists {
+        let mut head = [0u8; HEADER_BYTES];
+        file.read_exact(&mut head)?;
+        existing = parse_header(path, &head)?;
+        if existing > 0 {
+            let mut last = [0u8; 8];
+            file.seek(SeekFrom::Start((HEADER_BYTES + (existing as usize - 1) * RECORD_BYTES) as u64))?;
+            file.read_exact(&mut last)?;
+            if timestamps.first().is_some_and(|&t| t < i64::from_le_bytes(last)) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Appended ticks must not precede the last tick in the file"
+                ));
+            }
+        }
+        // Drop any partially written records left behind by an interrupted append
+        file.set_len((HEADER_BYTES + existing as usize * RECORD_BYTES) as u64)?;
+        file.seek(SeekFrom::End(0))?;
+    } else {
+        file.write_all(&header(0))?;
+    }
+
+    let mut writer = BufWriter::new(&mut file);
+    for i in 0..n {
+        let mut record = [0u8; RECORD_BYTES];
+        record[0..8].copy_from_slice(&timestamps[i].to_le_bytes());
+        record[8..16].copy_from_slice(&prices[i].to_le_bytes());
+        record[16..24].copy_from_slice(&sizes[i].to_le_bytes());
+        let flag = flags.as_ref().map_or(0, |f| f[i] as u32);
+        record[24..28].copy_from_slice(&flag.to_le_bytes());
+        writer.write_all(&record)?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    // The count is only updated once every record is on disk
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header(existing + n as u64))?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Memory-mapped reader over a binary tick file written by `write_ticks_rust`.
+///
+/// Only the pages touched by a query are loaded, so multi-gigabyte histories can be
+/// scanned without reading them into memory.
+#[pyclass(module = "fast_math")]
+pub struct TickFile {
+    path: String,
+    mmap: Mmap,
+    len: usize,
+}
+
+impl TickFile {
+    fn record(&self, i: usize) -> &[u8] {
+        let start = HEADER_BYTES + i * RECORD_BYTES;
+        &self.mmap[start..start + RECORD_BYTES]
+    }
+
+    fn timestamp(&self, i: usize) -> i64 {
+        read_i64(self.record(i), 0)
+    }
+
+    /// Index of the first tick with timestamp >= `t`
+    fn lower_bound(&self, t: i64) -> usize {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.timestamp(mid) < t {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    fn decode<'py>(&self, py: Python<'py>, start: usize, end: usize) -> TickArrays<'py> {
+        let (timestamps, prices, sizes, flags) = py.allow_threads(|| {
+            let n = end - start;
+            let mut timestamps = Vec::with_capacity(n);
+            let mut prices = Vec::with_capacity(n);
+            let mut sizes = Vec::with_capacity(n);
+            let mut flags = Vec::with_capacity(n);
+            let bytes = &self.mmap[HEADER_BYTES + start * RECORD_BYTES..HEADER_BYTES + end * RECORD_BYTES];
+            for record in bytes.chunks_exact(RECORD_BYTES) {
+                timestamps.push(read_i64(record, 0));
+                prices.push(read_f64(record, 8));
+                sizes.push(read_f64(record, 16));
+                flags.push(u32::from_le_bytes(record[24..28].try_into().unwrap()));
+            }
+            (timestamps, prices, sizes, flags)
+        });
+        (
+            PyArray1::from_vec_bound(py, timestamps),
+            PyArray1::from_vec_bound(py, prices),
+            PyArray1::from_vec_bound(py, sizes),
+            PyArray1::from_vec_bound(py, flags),
+        )
+    }
+}
+
+#[pymethods]
+impl TickFile {
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        let file = File::open(path)?;
+        // Safety: the file is mapped read-only; writers only append past the declared count
+        let mmap = unsafe { Mmap::map(&file)? };
+        let len = parse_header(path, &mmap)? as usize;
+        if mmap.len() < HEADER_BYTES + len * RECORD_BYTES {
+            return Err(format_error(path, "file is shorter than its declared record count"));
+        }
+        Ok(TickFile { path: path.to_string(), mmap, len })
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TickFile({:?}, ticks={})", self.path, self.len)
+    }
+
+    /// Timestamp of the first tick, or None for an empty file
+    #[getter]
+    fn start_time(&self) -> Option<i64> {
+        (self.len > 0).then(|| self.timestamp(0))
+    }
+
+    /// Timestamp of the last tick, or None for an empty file
+    #[getter]
+    fn end_time(&self) -> Option<i64> {
+        (self.len > 0).then(|| self.timestamp(self.len - 1))
+    }
+
+    /// Read `count` ticks starting at index `offset` (all remaining ticks by default)
+    #[pyo3(signature = (offset=0, count=None))]
+    fn read<'py>(&self, py: Python<'py>, offset: usize, count: Option<usize>) -> TickArrays<'py> {
+        let start = offset.min(self.len);
+        let end = count.map_or(self.len, |c| start.saturating_add(c).min(self.len));
+        self.decode(py, start, end)
+    }
+
+    /// Read the ticks with `start <= timestamp < end`; either bound may be omitted
+    #[pyo3(signature = (start=None, end=None))]
+    fn read_range<'py>(&self, py: Python<'py>, start: Option<i64>, end: Option<i64>) -> TickArrays<'py> {
+        let first = start.map_or(0, |t| self.lower_bound(t));
+        let last = end.map_or(self.len, |t| self.lower_bound(t)).max(first);
+        self.decode(py, first, last)
+    }
+
+    /// Index of the first tick at or after `timestamp`
+    fn index_of(&self, timestamp: i64) -> usize {
+        self.lower_bound(timestamp)
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList, PyString};
+use numpy::PyArray1;
+use rayon::prelude::*;
+use serde_json::Value;
+
+use crate::csv_reader::{epoch_to_ns, parse_utc_timestamp};
+use crate::threads;
+
+/// One normalized tick or bar: timestamp (UTC ns), symbol and the configured numeric fields
+pub struct Record {
+    pub timestamp: i64,
+    pub symbol: String,
+    pub values: Vec<f64>,
+}
+
+/// How raw exchange payloads map onto normalized records
+pub struct Schema {
+    timestamp: Option<String>,
+    symbol: Option<String>,
+    /// Output column name and JSON pointer of each numeric field
+    values: Vec<(String, String)>,
+}
+
+/// Convert a dotted key (`data.p`) to a JSON pointer (`/data/p`)
+fn pointer(key: &str) -> String {
+    format!("/{}", key.replace('.', "/"))
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => fast_float::parse(s.trim()).ok(),
+        _ => None,
+    }
+}
+
+fn as_timestamp(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|v| v as i64)).and_then(epoch_to_ns),
+        Value::String(s) => parse_utc_timestamp(s.as_bytes()),
+        _ => None,
+    }
+}
+
+fn receive_time() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as i64)
+}
+
+impl Schema {
+    /// Build a schema from a mapping of output column to (dotted) payload key.
+    ///
+    /// `timestamp` and `symbol` are optional keys; every other entry is a numeric column.
+    /// Defaults to the Binance-style trade layout `T` / `s` / `p` / `q`.
+    pub fn from_fields(fields: Option<HashMap<String, String>>) -> PyResult<Self> {
+        let mut fields = fields.unwrap_or_else(|| {
+            [("timestamp", "T"), ("symbol", "s"), ("price", "p"), ("size", "q")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        });
+        let timestamp = fields.remove("timestamp").map(|k| pointer(&k));
+        let symbol = fields.remove("symbol").map(|k| pointer(&k));
+        let mut values: Vec<(String, String)> = fields.into_iter().map(|(name, key)| (name, pointer(&key))).collect();
+        values.sort();
+        if values.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "fields must map at least one numeric column"
+            ));
+        }
+        Ok(Schema { timestamp, symbol, values })
+    }
+
+    /// Normalize one object; messages missing a numeric field (acks, heartbeats) yield None
+    fn normalize(&self, message: &Value) -> Option<Record> {
+        let values = self
+            .values
+            .iter()
+            .map(|(_, key)| message.pointer(key).and_then(as_f64))
+            .collect::<Option<Vec<f64>>>()?;
+        let timestamp = match &self.timestamp {
+            Some(key) => as_timestamp(message.pointer(key)?)?,
+            None => receive_time(),
+        };
+        let symbol = match &self.symbol {
+            Some(key) => match message.pointer(key)? {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            },
+            None => String::new(),
+        };
+        Some(Record { timestamp, symbol, values })
+    }
+
+    /// Normalize a decoded payload: an object or an array of objects
+    fn extend(&self, value: &Value, out: &mut Vec<Record>) {
+        match value {
+            Value::Array(items) => out.extend(items.iter().filter_map(|item| self.normalize(item))),
+            item => out.extend(self.normalize(item)),
+        }
+    }
+
+    /// Parse a JSON payload into records
+    pub fn parse(&self, payload: &[u8], out: &mut Vec<Record>) -> Result<(), String> {
+        let value: Value = serde_json::from_slice(payload).map_err(|e| e.to_string())?;
+        self.extend(&value, out);
+        Ok(())
+    }
+
+    /// Parse a msgpack payload into records
+    pub fn parse_msgpack(&self, payload: &[u8], out: &mut Vec<Record>) -> Result<(), String> {
+        let value: Value = rmp_serde::from_slice(payload).map_err(|e| e.to_string())?;
+        self.extend(&value, out);
+        Ok(())
+    }
+
+    /// Column arrays: `timestamp` (int64 UTC ns), `symbol` (list of str) and one float64
+    /// array per numeric field
+    pub fn to_columns<'py>(&self, py: Python<'py>, records: Vec<Record>) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new_bound(py);
+        result.set_item("timestamp", PyArray1::from_iter_bound(py, records.iter().map(|r| r.timestamp)))?;
+        for (col, (name, _)) in self.values.iter().enumerate() {
+            result.set_item(name, PyArray1::from_iter_bound(py, records.iter().map(|r| r.values[col])))?;
+        }
+        let symbols: Vec<String> = records.into_iter().map(|r| r.symbol).collect();
+        result.set_item("symbol", symbols)?;
+        Ok(result)
+    }
+}
+
+fn payload_bytes<'a>(payload: &'a Bound<'_, PyAny>) -> PyResult<&'a [u8]> {
+    if let Ok(bytes) = payload.downcast::<PyBytes>() {
+        return Ok(bytes.as_bytes());
+    }
+    if let Ok(text) = payload.downcast::<PyString>() {
+        return Ok(text.to_str()?.as_bytes());
+    }
+    Err(pyo3::exceptions::PyTypeError::new_err(
+        "Payloads must be bytes or str"
+    ))
+}
+
+/// Decode payloads in parallel without the GIL, keeping their order
+fn parse_payloads<'py, F>(
+    py: Python<'py>,
+    payloads: &[&[u8]],
+    schema: &Schema,
+    parse: F
+) -> PyResult<Vec<Record>>
+where
+    F: Fn(&Schema, &[u8], &mut Vec<Record>) -> Result<(), String> + Sync,
+{
+    let chunks: Vec<Vec<Record>> = py
+        .allow_threads(|| {
+            threads::install(|| {
+                payloads
+                    .par_iter()
+                    .enumerate()
+                    .map(|(i, payload)| {
+                        let mut out = Vec::new();
+                        parse(schema, payload, &mut out).map_err(|e| format!("Payload {}: {}", i, e))?;
+                        Ok(out)
+                    })
+                    .collect::<Result<_, String>>()
+            })
+        })
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+/// Bulk-deserialize JSON tick payloads into column arrays.
+///
+/// `lines` is a list of JSON documents (bytes or str), or a single newline-delimited
+/// string. Each document is an object or an array of objects; `fields` maps output
+/// columns to (dotted) keys as for `MarketDataClient`. Documents missing a numeric
+/// field are skipped; invalid JSON raises ValueError.
+#[pyfunction]
+#[pyo3(signature = (lines, fields=None))]
+pub fn parse_ticks_json_rust<'py>(
+    py: Python<'py>,
+    lines: &Bound<'py, PyAny>,
+    fields: Option<HashMap<String, String>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let schema = Schema::from_fields(fields)?;
+    let (items, ndjson) = match lines.downcast::<PyList>() {
+        Ok(list) => (list.iter().collect::<Vec<_>>(), false),
+        Err(_) => (vec![lines.clone()], true),
+    };
+
+    let mut payloads = Vec::with_capacity(items.len());
+    for item in &items {
+        let bytes = payload_bytes(item)?;
+        if ndjson {
+            payloads.extend(bytes.split(|&b| b == b'\n').filter(|l| !l.trim_ascii().is_empty()));
+        } else {
+            payloads.push(bytes);
+        }
+    }
+
+    let records = parse_payloads(py, &payloads, &schema, Schema::parse)?;
+    schema.to_columns(py, records)
+}
+
+/// Bulk-deserialize msgpack tick payloads (a list of bytes) into column arrays; see
+/// `parse_ticks_json_rust` for `fields`
+#[pyfunction]
+#[pyo3(signature = (payloads, fields=None))]
+pub fn parse_ticks_msgpack_rust<'py>(
+    py: Python<'py>,
+    payloads: Vec<Bound<'py, PyBytes>>,
+    fields: Option<HashMap<String, String>>
+) -> PyResult<Bound<'py, PyDict>> {
+    let schema = Schema::from_fields(fields)?;
+    let payloads: Vec<&[u8]> = payloads.iter().map(|p| p.as_bytes()).collect();
+    let records = parse_payloads(py, &payloads, &schema, Schema::parse_msgpack)?;
+    schema.to_columns(py, records)
+}
@@ -0,0 +1,311 @@
+use pyo3::prelude::*;
+use numpy::PyReadonlyArray2;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// Slack for prices and quantities that are multiples of the increment up to float error,
+/// such as `0.3 / 0.1 = 2.9999999999999996`
+const EPSILON: f64 = 1e-9;
+
+/// Which way a value is rounded to a multiple of its increment
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Rounding {
+    /// To the closest multiple, ties away from zero
+    Nearest,
+    /// To the closest multiple, ties to the even multiple
+    HalfEven,
+    Down,
+    Up,
+    /// Down for buys and up for sells, keeping limit orders on their side of the book
+    Passive,
+    /// Up for buys and down for sells, so fills are never better than the price
+    Aggressive,
+}
+
+impl Rounding {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nearest" | "round" => Ok(Rounding::Nearest),
+            "half_even" | "bankers" => Ok(Rounding::HalfEven),
+            "down" | "floor" => Ok(Rounding::Down),
+            "up" | "ceil" => Ok(Rounding::Up),
+            "passive" => Ok(Rounding::Passive),
+            "aggressive" => Ok(Rounding::Aggressive),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown mode '{}', expected 'nearest', 'half_even', 'down', 'up', 'passive' or 'aggressive'", name
+            ))),
+        }
+    }
+
+    /// The plain direction for an order on `side` (positive buys)
+    fn for_side(self, side: f64) -> Self {
+        match self {
+            Rounding::Passive if side > 0.0 => Rounding::Down,
+            Rounding::Passive if side < 0.0 => Rounding::Up,
+            Rounding::Aggressive if side > 0.0 => Rounding::Up,
+            Rounding::Aggressive if side < 0.0 => Rounding::Down,
+            Rounding::Passive | Rounding::Aggressive => Rounding::Nearest,
+            plain => plain,
+        }
+    }
+
+    /// The multiple of the increment `units` (the value in increments) rounds to
+    fn apply(self, units: f64) -> f64 {
+        match self {
+            Rounding::Down => (units + EPSILON).floor(),
+            Rounding::Up => (units - EPSILON).ceil(),
+            Rounding::HalfEven => {
+                let floor = units.floor();
+                let fraction = units - floor;
+                if (fraction - 0.5).abs() <= EPSILON {
+                    if floor % 2.0 == 0.0 { floor } else { floor + 1.0 }
+                } else {
+                    units.round()
+                }
+            }
+            _ => units.signum() * (units.abs() + 0.5 + EPSILON).floor(),
+        }
+    }
+}
+
+/// Powers of ten that turn `increment` into a whole number, if a small one does; used to
+/// strip float noise such as `0.30000000000000004` from rounded values
+fn decimal_scale(increment: f64) -> Option<f64> {
+    (0..=12).map(|d| 10f64.powi(d)).find(|scale| {
+        let scaled = increment * scale;
+        (scaled - scaled.round()).abs() <= 1e-6
+    })
+}
+
+/// `units` multiples of `increment`, cleaned of float noise
+fn multiple(units: f64, increment: f64, scale: Option<f64>) -> f64 {
+    let value = units * increment;
+    match scale {
+        Some(scale) => (value * scale).round() / scale,
+        None => value,
+    }
+}
+
+/// One band of a tick table: the tick size of prices from `min_price` up to the next band
+#[derive(Clone, Copy)]
+struct Band {
+    min_price: f64,
+    tick: f64,
+    scale: Option<f64>,
+}
+
+/// Exchange tick tables by name, as `(min_price, tick)` bands
+const PRESETS: &[(&str, &[(f64, f64)])] = &[
+    // SEC Rule 612: sub-penny ticks only below $1
+    ("us_equity", &[(0.0, 0.0001), (1.0, 0.01)]),
+    ("hkex", &[
+        (0.0, 0.001), (0.25, 0.005), (0.5, 0.01), (10.0, 0.02), (20.0, 0.05), (100.0, 0.1),
+        (200.0, 0.2), (500.0, 0.5), (1000.0, 1.0), (2000.0, 2.0), (5000.0, 5.0),
+    ]),
+    // Tokyo Stock Exchange, standard table
+    ("tse", &[
+        (0.0, 1.0), (3000.0, 5.0), (5000.0, 10.0), (30000.0, 50.0), (50000.0, 100.0),
+        (300000.0, 500.0), (500000.0, 1000.0), (3e6, 5000.0), (5e6, 10000.0),
+        (3e7, 50000.0), (5e7, 100000.0),
+    ]),
+];
+
+/// Tick sizes by price band: a single tick size or an exchange's tick table
+struct TickTable {
+    bands: Vec<Band>,
+}
+
+impl TickTable {
+    /// A tick size, the name of a preset table, a list of `(min_price, tick)` pairs or a
+    /// 2D array with those two columns
+    fn parse(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let bands: Vec<(f64, f64)> = if let Ok(tick) = value.extract::<f64>() {
+            vec![(f64::NEG_INFINITY, tick)]
+        } else if let Ok(name) = value.extract::<&str>() {
+            let key = name.to_ascii_lowercase();
+            match PRESETS.iter().find(|(n, _)| *n == key) {
+                Some((_, bands)) => bands.to_vec(),
+                None => {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "Unknown tick table '{}', expected one of: {}",
+                        name,
+                        PRESETS.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+                    )))
+                }
+            }
+        } else if let Ok(table) = value.extract::<PyReadonlyArray2<'_, f64>>() {
+            let table = table.as_array();
+            if table.ncols() != 2 {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Tick tables must have min_price and tick columns"
+                ));
+            }
+            table.rows().into_iter().map(|row| (row[0], row[1])).collect()
+        } else {
+            value.extract()?
+        };
+        TickTable::new(bands)
+    }
+
+    fn new(bands: Vec<(f64, f64)>) -> PyResult<Self> {
+        if bands.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Tick tables need at least one band"
+            ));
+        }
+        if bands.iter().any(|&(_, tick)| !tick.is_finite() || tick <= 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Tick sizes must be finite and > 0"
+            ));
+        }
+        if bands.windows(2).any(|w| w[1].0 <= w[0].0) || bands.iter().any(|b| b.0.is_nan()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Tick table min prices must be strictly ascending"
+            ));
+        }
+        // Keeps rounding within a band from stepping off the grid at its lower edge
+        if bands.iter().any(|&(min, tick)| min.is_finite() && ((min / tick) - (min / tick).round()).abs() > EPSILON) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Each band's min price must be a multiple of its tick"
+            ));
+        }
+        let bands = bands.into_iter().map(|(min_price, tick)| Band { min_price, tick, scale: decimal_scale(tick) }).collect();
+        Ok(TickTable { bands })
+    }
+
+    /// The band of `price`; prices below the first band use its tick
+    fn band(&self, price: f64) -> Band {
+        let above = self.bands.partition_point(|b| b.min_price <= price.abs());
+        self.bands[above.saturating_sub(1)]
+    }
+
+    fn tick(&self, price: f64) -> f64 {
+        if price.is_nan() { f64::NAN } else { self.band(price).tick }
+    }
+
+    fn round(&self, price: f64, rounding: Rounding) -> f64 {
+        if !price.is_finite() {
+            return price;
+        }
+        let band = self.band(price);
+        multiple(rounding.apply(price / band.tick), band.tick, band.scale)
+    }
+}
+
+/// Round prices to valid ticks, for order generation and fill simulation alike.
+///
+/// `tick_size` is one tick size, the name of an exchange tick table ("us_equity",
+/// "hkex" or "tse"), or a table of its own as a list of `(min_price, tick)` pairs or a
+/// 2D array of them, where each band's tick applies from its min price up to the next
+/// band (by absolute price, so negative spreads round like their magnitude). `mode` is
+/// "nearest" (ties away from zero), "half_even", "down" or "up"; "passive" rounds buys
+/// down and sells up, keeping limit prices on their side of the book, and "aggressive"
+/// the reverse, so simulated fills are never better than the raw price. Both take the
+/// side of each price from the sign of `sides` (positive buys), rounding to the nearest
+/// tick where it is 0. Values that are ticks up to float error are kept, and results are
+/// cleaned of float noise. Returns an array of the input's type.
+#[pyfunction]
+#[pyo3(signature = (prices, tick_size, mode="nearest", sides=None))]
+pub fn round_to_tick_rust<'py>(
+    py: Python<'py>,
+    prices: Column<'py, f64>,
+    tick_size: &Bound<'py, PyAny>,
+    mode: &str,
+    sides: Option<Column<'py, f64>>
+) -> PyResult<PyObject> {
+    let rounding = Rounding::parse(mode)?;
+    let table = TickTable::parse(tick_size)?;
+    let values = prices.values()?;
+    let sides = sides.as_ref().map(|s| s.values()).transpose()?;
+    if matches!(rounding, Rounding::Passive | Rounding::Aggressive) && sides.is_none() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "mode '{}' needs sides", mode
+        )));
+    }
+    if sides.as_ref().is_some_and(|s| s.len() != values.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "sides must have one entry per price"
+        ));
+    }
+    let result: Vec<f64> = py.allow_threads(|| {
+        threads::install(|| {
+            values
+                .par_iter()
+                .enumerate()
+                .map(|(i, &price)| {
+                    let side = sides.as_ref().map_or(0.0, |s| s[i]);
+                    table.round(price, rounding.for_side(side))
+                })
+                .collect()
+        })
+    });
+    prices.kind().wrap(py, result)
+}
+
+/// The tick size at each of `prices` under `tick_size`, given as for
+/// `round_to_tick_rust`, e.g. to express slippage in ticks; NaN for NaN prices.
+#[pyfunction]
+pub fn tick_sizes_rust<'py>(py: Python<'py>, prices: Column<'py, f64>, tick_size: &Bound<'py, PyAny>) -> PyResult<PyObject> {
+    let table = TickTable::parse(tick_size)?;
+    let values = prices.values()?;
+    let result: Vec<f64> = py.allow_threads(|| values.iter().map(|&p| table.tick(p)).collect());
+    prices.kind().wrap(py, result)
+}
+
+/// Round order quantities to whole lots.
+///
+/// `lot_size` is one lot size or a column with one per quantity (e.g. per symbol).
+/// Quantities are signed and rounded by magnitude: `mode="down"` (the default) never
+/// trades more than asked, "up" covers the full quantity and "nearest" or "half_even"
+/// go to the closest lot. Rounded quantities smaller than `min_qty` become 0, and NaN
+/// quantities stay NaN. Returns an array of the input's type.
+#[pyfunction]
+#[pyo3(signature = (quantities, lot_size, mode="down", min_qty=None))]
+pub fn round_to_lot_rust<'py>(
+    py: Python<'py>,
+    quantities: Column<'py, f64>,
+    lot_size: &Bound<'py, PyAny>,
+    mode: &str,
+    min_qty: Option<f64>
+) -> PyResult<PyObject> {
+    let rounding = Rounding::parse(mode)?;
+    if matches!(rounding, Rounding::Passive | Rounding::Aggressive) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Lots round by magnitude; mode must be 'down', 'up', 'nearest' or 'half_even'"
+        ));
+    }
+    let values = quantities.values()?;
+    let lots: Vec<f64> = match lot_size.extract::<f64>() {
+        Ok(lot) => vec![lot; values.len()],
+        Err(_) => lot_size.extract::<Column<'_, f64>>()?.values()?.into_owned(),
+    };
+    if lots.len() != values.len() {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "lot_size must be a number or have one entry per quantity"
+        ));
+    }
+    if lots.iter().any(|&lot| !lot.is_finite() || lot <= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Lot sizes must be finite and > 0"
+        ));
+    }
+    let min_qty = min_qty.unwrap_or(0.0);
+    let result: Vec<f64> = py.allow_threads(|| {
+        threads::install(|| {
+            values
+                .par_iter()
+                .zip(lots.par_iter())
+                .map(|(&qty, &lot)| {
+                    if qty.is_nan() {
+                        return qty;
+                    }
+                    let size = multiple(rounding.apply(qty.abs() / lot), lot, decimal_scale(lot));
+                    if size < min_qty { 0.0 } else { qty.signum() * size }
+                })
+                .collect()
+        })
+    });
+    quantities.kind().wrap(py, result)
+}
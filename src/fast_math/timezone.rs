@@ -0,0 +1,197 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike};
+use chrono_tz::Tz;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::csv_reader::NAT;
+use crate::threads;
+
+/// Parse an IANA timezone name such as "America/New_York" or "UTC"
+pub fn parse_tz(name: &str) -> PyResult<Tz> {
+    name.parse::<Tz>()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Unknown timezone: {}", e)))
+}
+
+/// Parse an "HH:MM" or "HH:MM:SS" local time of day, naming the argument in errors
+pub fn parse_time(name: &str, value: &str) -> PyResult<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value.trim(), "%H:%M"))
+        .map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid {} '{}', expected HH:MM or HH:MM:SS", name, value
+            ))
+        })
+}
+
+/// Which instant a wall-clock time repeated by a daylight-saving change maps to
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Ambiguous {
+    Earliest,
+    Latest,
+    Nat,
+}
+
+impl Ambiguous {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "earliest" => Ok(Ambiguous::Earliest),
+            "latest" => Ok(Ambiguous::Latest),
+            "nat" => Ok(Ambiguous::Nat),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown ambiguous '{}', expected 'earliest', 'latest' or 'nat'", name
+            ))),
+        }
+    }
+}
+
+/// What a wall-clock time skipped by a daylight-saving change maps to
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Nonexistent {
+    Nat,
+    /// The first instant after the gap
+    ShiftForward,
+}
+
+impl Nonexistent {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nat" => Ok(Nonexistent::Nat),
+            "shift_forward" => Ok(Nonexistent::ShiftForward),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown nonexistent '{}', expected 'nat' or 'shift_forward'", name
+            ))),
+        }
+    }
+}
+
+fn naive(ns: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp(ns.div_euclid(1_000_000_000), ns.rem_euclid(1_000_000_000) as u32).map(|dt| dt.naive_utc())
+}
+
+fn naive_ns(local: NaiveDateTime) -> i64 {
+    local.and_utc().timestamp_nanos_opt().unwrap_or(NAT)
+}
+
+/// UTC nanoseconds of the wall-clock time `local` in `tz`, NaT when it cannot be resolved
+pub fn to_utc(tz: &Tz, local: NaiveDateTime, ambiguous: Ambiguous, nonexistent: Nonexistent) -> i64 {
+    let resolved = match tz.from_local_datetime(&local) {
+        LocalResult::Single(t) => Some(t),
+        LocalResult::Ambiguous(earliest, latest) => match ambiguous {
+            Ambiguous::Earliest => Some(earliest),
+            Ambiguous::Latest => Some(latest),
+            Ambiguous::Nat => None,
+        },
+        LocalResult::None => match nonexistent {
+            Nonexistent::Nat => None,
+            // Gaps start and end on whole minutes, so the first valid minute is their end
+            Nonexistent::ShiftForward => {
+                let minute = local.with_second(0).and_then(|l| l.with_nanosecond(0)).unwrap_or(local);
+                (1..=24 * 60).find_map(|m| tz.from_local_datetime(&(minute + TimeDelta::minutes(m))).earliest())
+            }
+        },
+    };
+    resolved.and_then(|t| t.timestamp_nanos_opt()).unwrap_or(NAT)
+}
+
+/// Convert wall-clock timestamps from one timezone to another, DST-aware.
+///
+/// `timestamps_ns` are integer nanoseconds read as wall-clock (naive) times in `from_tz`
+/// and come back as the wall-clock times of the same instants in `to_tz`; with
+/// `from_tz="UTC"` this turns UTC timestamps into exchange-local ones, and with
+/// `to_tz="UTC"` back. Times that occur twice when clocks fall back take the
+/// `ambiguous="earliest"` (default) or "latest" instant, or NaT with "nat"; times
+/// skipped when clocks spring forward are NaT with `nonexistent="nat"` (the default) or
+/// move to the end of the gap with "shift_forward". NaT (the minimum int64) stays NaT.
+#[pyfunction]
+#[pyo3(signature = (timestamps_ns, from_tz, to_tz, ambiguous="earliest", nonexistent="nat"))]
+pub fn convert_tz_rust<'py>(
+    py: Python<'py>,
+    timestamps_ns: Column<'py, i64>,
+    from_tz: &str,
+    to_tz: &str,
+    ambiguous: &str,
+    nonexistent: &str
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    let (from_tz, to_tz) = (parse_tz(from_tz)?, parse_tz(to_tz)?);
+    let (ambiguous, nonexistent) = (Ambiguous::parse(ambiguous)?, Nonexistent::parse(nonexistent)?);
+    let timestamps = timestamps_ns.values()?;
+    let converted: Vec<i64> = py.allow_threads(|| {
+        threads::install(|| {
+            timestamps
+                .par_iter()
+                .map(|&t| {
+                    let Some(local) = naive(t).filter(|_| t != NAT) else { return NAT };
+                    let utc = to_utc(&from_tz, local, ambiguous, nonexistent);
+                    if utc == NAT {
+                        return NAT;
+                    }
+                    naive(utc).map_or(NAT, |u| naive_ns(to_tz.from_utc_datetime(&u).naive_local()))
+                })
+                .collect()
+        })
+    });
+    Ok(PyArray1::from_vec_bound(py, converted))
+}
+
+/// Exchange-local session view of UTC timestamps
+struct Session {
+    tz: Tz,
+    open: NaiveTime,
+    close: NaiveTime,
+}
+
+impl Session {
+    /// Local wall-clock time, trading date, time since that date's open and whether the
+    /// session is in progress. Overnight sessions (close not after open) belong to the
+    /// date they close on.
+    fn localize(&self, utc: i64) -> (i64, i64, i64, bool) {
+        let Some(instant) = naive(utc).filter(|_| utc != NAT) else { return (NAT, NAT, NAT, false) };
+        let local = self.tz.from_utc_datetime(&instant).naive_local();
+        let overnight = self.close <= self.open;
+        let mut date = local.date();
+        if overnight && local.time() >= self.open {
+            date = date.succ_opt().unwrap_or(date);
+        }
+        let open_date = if overnight { date.pred_opt().unwrap_or(date) } else { date };
+        let open = to_utc(&self.tz, open_date.and_time(self.open), Ambiguous::Earliest, Nonexistent::ShiftForward);
+        let close = to_utc(&self.tz, date.and_time(self.close), Ambiguous::Earliest, Nonexistent::ShiftForward);
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let days = (date - epoch).num_days();
+        (naive_ns(local), days, utc - open, open <= utc && utc < close)
+    }
+}
+
+/// Place UTC timestamps within an exchange's local trading day, DST-aware.
+///
+/// For each of `timestamps_ns` (integer UTC nanoseconds) returns, as a dict, its `local`
+/// wall-clock time in `tz`, its trading `date` (days since 1970-01-01; view as
+/// `datetime64[D]`), the `elapsed` nanoseconds since that date's `open` (negative before
+/// it) and whether it falls `in_session`, in the session from `open` to `close` local
+/// time ("HH:MM[:SS]"). Open and close are resolved in the exchange's timezone each day,
+/// so a session keeps its local hours across daylight-saving changes. When `close` is not
+/// after `open` the session is overnight and times from the open on belong to the next
+/// date. NaT input gives NaT and false. For weekends and holidays see `TradingCalendar`.
+#[pyfunction]
+#[pyo3(signature = (timestamps_ns, tz, open="09:30", close="16:00"))]
+pub fn localize_session_rust<'py>(
+    py: Python<'py>,
+    timestamps_ns: Column<'py, i64>,
+    tz: &str,
+    open: &str,
+    close: &str
+) -> PyResult<Bound<'py, PyDict>> {
+    let session = Session { tz: parse_tz(tz)?, open: parse_time("open", open)?, close: parse_time("close", close)? };
+    let timestamps = timestamps_ns.values()?;
+    let rows: Vec<(i64, i64, i64, bool)> =
+        py.allow_threads(|| threads::install(|| timestamps.par_iter().map(|&t| session.localize(t)).collect()));
+
+    let result = PyDict::new_bound(py);
+    result.set_item("local", PyArray1::from_iter_bound(py, rows.iter().map(|r| r.0)))?;
+    result.set_item("date", PyArray1::from_iter_bound(py, rows.iter().map(|r| r.1)))?;
+    result.set_item("elapsed", PyArray1::from_iter_bound(py, rows.iter().map(|r| r.2)))?;
+    result.set_item("in_session", PyArray1::from_iter_bound(py, rows.iter().map(|r| r.3)))?;
+    Ok(result)
+}
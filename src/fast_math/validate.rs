@@ -0,0 +1,286 @@
+// Validation mode for certifying releases: with the `validate` feature built and
+// `fast_math.validation.set_validate(True)`, every call of an optimized kernel recomputes
+// its output with a straightforward scalar reference (per-window loops, sequential f64
+// sums, textbook formulas) and records the largest deviation in a per-kernel report.
+// Without the feature `check` is an empty inline function, so release builds pay nothing.
+
+use crate::column::FloatElement;
+
+#[cfg(feature = "validate")]
+mod enabled {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    use crate::column::FloatElement;
+
+    static VALIDATE: AtomicBool = AtomicBool::new(false);
+    static REPORT: Mutex<BTreeMap<&'static str, Stats>> = Mutex::new(BTreeMap::new());
+
+    /// Deviations of one kernel from its reference, over every validated call
+    #[derive(Default)]
+    struct Stats {
+        calls: u64,
+        values: u64,
+        max_deviation: f64,
+        /// Positions where exactly one of the kernel and the reference is NaN
+        nan_mismatches: u64,
+    }
+
+    pub fn check<T: FloatElement>(kernel: &'static str, values: &[T], reference: impl FnOnce() -> Vec<f64>) {
+        if !VALIDATE.load(Ordering::Relaxed) {
+            return;
+        }
+        let expected = reference();
+        let mut report = REPORT.lock().unwrap();
+        let stats = report.entry(kernel).or_default();
+        stats.calls += 1;
+        stats.values += values.len() as u64;
+        if expected.len() != values.len() {
+            // A length mismatch makes every value suspect
+            stats.nan_mismatches += values.len().max(expected.len()) as u64;
+            return;
+        }
+        for (actual, expected) in values.iter().map(|v| v.to_f64().unwrap()).zip(expected) {
+            match (actual.is_nan(), expected.is_nan()) {
+                (true, true) => {}
+                (false, false) => stats.max_deviation = stats.max_deviation.max((actual - expected).abs()),
+                _ => stats.nan_mismatches += 1,
+            }
+        }
+    }
+
+    /// Turn validation of every optimized kernel call on or off
+    #[pyfunction]
+    #[pyo3(signature = (validate=true))]
+    fn set_validate(validate: bool) {
+        VALIDATE.store(validate, Ordering::Relaxed);
+    }
+
+    /// Per-kernel validation results since the last `reset`: a dict mapping kernel names
+    /// to `calls`, `values` checked, `max_deviation` (largest absolute difference from
+    /// the reference) and `nan_mismatches` (positions NaN in only one of the two)
+    #[pyfunction]
+    fn report<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new_bound(py);
+        for (kernel, stats) in REPORT.lock().unwrap().iter() {
+            let entry = PyDict::new_bound(py);
+            entry.set_item("calls", stats.calls)?;
+            entry.set_item("values", stats.values)?;
+            entry.set_item("max_deviation", stats.max_deviation)?;
+            entry.set_item("nan_mismatches", stats.nan_mismatches)?;
+            result.set_item(*kernel, entry)?;
+        }
+        Ok(result)
+    }
+
+    /// Clear the validation report
+    #[pyfunction]
+    fn reset() {
+        REPORT.lock().unwrap().clear();
+    }
+
+    /// Populate the `fast_math.validation` submodule
+    pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        m.add_function(wrap_pyfunction!(set_validate, m)?)?;
+        m.add_function(wrap_pyfunction!(report, m)?)?;
+        m.add_function(wrap_pyfunction!(reset, m)?)?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn check_reports_deviations() {
+            VALIDATE.store(true, Ordering::Relaxed);
+            check("test_kernel", &[1.0f64, 2.0, f64::NAN], || vec![1.0, 2.5, 3.0]);
+            VALIDATE.store(false, Ordering::Relaxed);
+            let report = REPORT.lock().unwrap();
+            let stats = &report["test_kernel"];
+            assert_eq!((stats.calls, stats.values, stats.nan_mismatches), (1, 3, 1));
+            assert_eq!(stats.max_deviation, 0.5);
+        }
+    }
+}
+
+#[cfg(feature = "validate")]
+pub use enabled::register;
+
+/// Compare a kernel's `values` with its `reference` output when validation is on
+#[cfg(feature = "validate")]
+pub fn check<T: FloatElement>(kernel: &'static str, values: &[T], reference: impl FnOnce() -> Vec<f64>) {
+    enabled::check(kernel, values, reference)
+}
+
+#[cfg(not(feature = "validate"))]
+#[inline(always)]
+pub fn check<T: FloatElement>(_kernel: &'static str, _values: &[T], _reference: impl FnOnce() -> Vec<f64>) {}
+
+/// The scalar references. Each recomputes every output from scratch in f64, so they are
+/// slow (O(n * window)) but have no sliding state to drift.
+pub mod reference {
+    use ndarray::ArrayView2;
+
+    use crate::column::FloatElement;
+
+    fn valid<T: FloatElement>(values: &[T]) -> Vec<f64> {
+        values.iter().map(|v| v.to_f64().unwrap()).filter(|v| !v.is_nan()).collect()
+    }
+
+    fn mean(values: &[f64]) -> f64 {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+
+    /// Mean of the valid values of each full window, NaN with fewer than `min_periods`
+    pub fn moving_average<T: FloatElement>(slice: &[T], window: usize, min_periods: usize) -> Vec<f64> {
+        slice
+            .windows(window)
+            .map(|w| {
+                let values = valid(w);
+                if values.len() < min_periods { f64::NAN } else { mean(&values) }
+            })
+            .collect()
+    }
+
+    /// Two-pass standard deviation of the valid values of each full window
+    pub fn rolling_std<T: FloatElement>(slice: &[T], window: usize, ddof: usize, min_periods: usize) -> Vec<f64> {
+        slice
+            .windows(window)
+            .map(|w| {
+                let values = valid(w);
+                if values.len() < min_periods || values.len() <= ddof {
+                    return f64::NAN;
+                }
+                let m = mean(&values);
+                let ss: f64 = values.iter().map(|v| (v - m) * (v - m)).sum();
+                (ss / (values.len() - ddof) as f64).sqrt()
+            })
+            .collect()
+    }
+
+    fn rsi_from(avg_gain: f64, avg_loss: f64) -> f64 {
+        if avg_loss <= 0.0 { 100.0 } else { 100.0 - 100.0 / (1.0 + avg_gain / avg_loss) }
+    }
+
+    fn changes<T: FloatElement>(slice: &[T]) -> Vec<f64> {
+        slice.windows(2).map(|w| w[1].to_f64().unwrap() - w[0].to_f64().unwrap()).collect()
+    }
+
+    /// RSI over simple averages of the valid changes among the last `period`
+    pub fn rsi<T: FloatElement>(slice: &[T], period: usize, min_periods: usize) -> Vec<f64> {
+        changes(slice)
+            .windows(period)
+            .map(|w| {
+                let valid: Vec<f64> = w.iter().copied().filter(|c| !c.is_nan()).collect();
+                if valid.len() < min_periods {
+                    return f64::NAN;
+                }
+                let gains: f64 = valid.iter().filter(|&&c| c > 0.0).sum();
+                let losses: f64 = valid.iter().filter(|&&c| c < 0.0).map(|c| -c).sum();
+                rsi_from(gains / period as f64, losses / period as f64)
+            })
+            .collect()
+    }
+
+    /// Wilder's RSI in its textbook form: the averages are the simple mean of the first
+    /// changes, then `(avg * (period - 1) + change) / period`. NaN changes restart the
+    /// seeding when `restart`, else they are skipped; NaN until `min_periods` valid
+    /// changes count.
+    pub fn wilder_rsi<T: FloatElement>(slice: &[T], period: usize, min_periods: usize, restart: bool) -> Vec<f64> {
+        let p = period as f64;
+        let (mut gains, mut losses, mut seen) = (Vec::new(), Vec::new(), 0usize);
+        let (mut avg_gain, mut avg_loss) = (0.0, 0.0);
+        let mut result = Vec::new();
+        for (i, change) in changes(slice).into_iter().enumerate() {
+            if change.is_nan() {
+                if restart {
+                    (gains, losses, seen) = (Vec::new(), Vec::new(), 0);
+                }
+            } else {
+                let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+                seen += 1;
+                if seen <= period {
+                    gains.push(gain);
+                    losses.push(loss);
+                    (avg_gain, avg_loss) = (mean(&gains), mean(&losses));
+                } else {
+                    avg_gain = (avg_gain * (p - 1.0) + gain) / p;
+                    avg_loss = (avg_loss * (p - 1.0) + loss) / p;
+                }
+            }
+            if i + 1 >= period {
+                result.push(if seen < min_periods { f64::NAN } else { rsi_from(avg_gain, avg_loss) });
+            }
+        }
+        result
+    }
+
+    /// Two-pass Pearson correlation of the pairs where both values are valid; any NaN
+    /// makes it NaN unless `min_periods` is given
+    pub fn correlation<T: FloatElement>(x: &[T], y: &[T], min_periods: Option<usize>) -> Vec<f64> {
+        let pairs: Vec<(f64, f64)> = x
+            .iter()
+            .zip(y)
+            .map(|(a, b)| (a.to_f64().unwrap(), b.to_f64().unwrap()))
+            .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+            .collect();
+        let enough = match min_periods {
+            None => pairs.len() == x.len() && pairs.len() >= 2,
+            Some(min_periods) => pairs.len() >= min_periods.max(2),
+        };
+        if !enough {
+            return vec![f64::NAN];
+        }
+        let n = pairs.len() as f64;
+        let mx = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+        let my = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+        let cov: f64 = pairs.iter().map(|(a, b)| (a - mx) * (b - my)).sum();
+        let vx: f64 = pairs.iter().map(|(a, _)| (a - mx) * (a - mx)).sum();
+        let vy: f64 = pairs.iter().map(|(_, b)| (b - my) * (b - my)).sum();
+        let denominator = (vx * vy).sqrt();
+        vec![if denominator == 0.0 { 0.0 } else { cov / denominator }]
+    }
+
+    /// Pairwise `correlation` of the columns of `returns`, row-major
+    pub fn correlation_matrix(returns: ArrayView2<'_, f64>) -> Vec<f64> {
+        let columns: Vec<Vec<f64>> = returns.columns().into_iter().map(|c| c.to_vec()).collect();
+        columns
+            .iter()
+            .flat_map(|x| columns.iter().map(move |y| correlation(x, y, None)[0]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Vec<f64>, expected: Vec<f64>) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!(a.is_nan() == e.is_nan() && (a.is_nan() || (a - e).abs() < 1e-8), "{} vs {}", a, e);
+        }
+    }
+
+    #[test]
+    fn references_match_kernels() {
+        let x: Vec<f64> = (0..3000).map(|i| 100.0 + (i as f64 * 0.37).sin() * 5.0 + i as f64 * 0.01).collect();
+        let mut y = x.clone();
+        y[10] = f64::NAN;
+        y[500] = f64::NAN;
+        for s in [&x, &y] {
+            assert_close(crate::moving_average(s, 20).unwrap(), reference::moving_average(s, 20, 20));
+            assert_close(crate::skipna::moving_average(s, 20, 5).unwrap(), reference::moving_average(s, 20, 5));
+            assert_close(crate::rolling_std(s, 20, 1).unwrap(), reference::rolling_std(s, 20, 1, 20));
+            assert_close(crate::rsi(s, 14).unwrap(), reference::rsi(s, 14, 14));
+            assert_close(crate::wilder_rsi(s, 14).unwrap(), reference::wilder_rsi(s, 14, 14, true));
+            assert_close(crate::skipna::wilder_rsi(s, 14, 5).unwrap(), reference::wilder_rsi(s, 14, 5, false));
+            assert_close(vec![crate::correlation(s, &x).unwrap()], reference::correlation(s, &x, None));
+        }
+    }
+}
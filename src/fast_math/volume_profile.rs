@@ -0,0 +1,168 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use numpy::PyArray1;
+use rayon::prelude::*;
+
+use crate::column::Column;
+use crate::threads;
+
+/// Volume traded in each price bin of one session, with its point of control and value area
+struct Profile {
+    /// Lower edge of each bin, ascending and contiguous
+    prices: Vec<f64>,
+    volumes: Vec<f64>,
+    poc: f64,
+    value_area_low: f64,
+    value_area_high: f64,
+}
+
+impl Profile {
+    fn empty() -> Self {
+        Profile { prices: Vec::new(), volumes: Vec::new(), poc: f64::NAN, value_area_low: f64::NAN, value_area_high: f64::NAN }
+    }
+}
+
+/// Bin `k` covers `[k * bin_size, (k + 1) * bin_size)`; the tolerance keeps prices on the
+/// bin grid, such as 100.3 with 0.1 bins, from landing a bin low
+fn bin_of(price: f64, bin_size: f64) -> i64 {
+    (price / bin_size + 1e-9).floor() as i64
+}
+
+/// The volume profile of one session. The value area grows from the point of control
+/// (the bin with the most volume, the lowest on ties) one bin at a time towards the
+/// larger neighbouring bin (the upper on ties) until it holds `value_area` of the volume.
+fn profile(prices: &[f64], volumes: &[f64], bin_size: f64, value_area: f64) -> Profile {
+    let bars: Vec<(i64, f64)> = prices
+        .iter()
+        .zip(volumes)
+        .filter(|(p, v)| p.is_finite() && !v.is_nan())
+        .map(|(&p, &v)| (bin_of(p, bin_size), v))
+        .collect();
+    let (Some(lo), Some(hi)) = (bars.iter().map(|b| b.0).min(), bars.iter().map(|b| b.0).max()) else {
+        return Profile::empty();
+    };
+    let mut histogram = vec![0.0; (hi - lo + 1) as usize];
+    for (bin, volume) in bars {
+        histogram[(bin - lo) as usize] += volume;
+    }
+    let total: f64 = histogram.iter().sum();
+    let level = |i: usize| (lo + i as i64) as f64 * bin_size;
+    let prices: Vec<f64> = (0..histogram.len()).map(level).collect();
+    if total <= 0.0 {
+        return Profile { prices, volumes: histogram, ..Profile::empty() };
+    }
+    let poc = histogram.iter().enumerate().fold(0, |best, (i, &v)| if v > histogram[best] { i } else { best });
+    let (mut low, mut high) = (poc, poc);
+    let mut covered = histogram[poc];
+    while covered < value_area * total && (low > 0 || high + 1 < histogram.len()) {
+        let below = if low > 0 { histogram[low - 1] } else { f64::NEG_INFINITY };
+        let above = if high + 1 < histogram.len() { histogram[high + 1] } else { f64::NEG_INFINITY };
+        if above >= below {
+            high += 1;
+            covered += above;
+        } else {
+            low -= 1;
+            covered += below;
+        }
+    }
+    Profile {
+        poc: level(poc),
+        value_area_low: level(low),
+        value_area_high: level(high),
+        prices,
+        volumes: histogram,
+    }
+}
+
+/// Start and end of each run of equal `sessions` labels
+fn session_runs(sessions: &[i64]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=sessions.len() {
+        if i == sessions.len() || sessions[i] != sessions[start] {
+            runs.push((start, i));
+            start = i;
+        }
+    }
+    runs
+}
+
+/// Volume profile (market profile by volume): the volume traded at each price level,
+/// its point of control and value area.
+///
+/// Each bar's volume goes to the bin of its price (e.g. the close or typical price, or
+/// trade prices and sizes), bin `k` covering `[k * bin_size, (k + 1) * bin_size)` so
+/// sessions share one grid. The point of control (POC) is the bin with the most volume;
+/// the value area grows from it a bin at a time towards the busier neighbour until it
+/// holds `value_area` (default 70%) of the volume. Prices are reported as the lower edges
+/// of their bins; NaN bars are skipped. Returns a dict with the `price` and `volume`
+/// histogram (every bin from the lowest to the highest price, empty ones 0), `poc`,
+/// `value_area_low` and `value_area_high`. With `sessions`, one label per bar such as a
+/// session date, a profile is built for each run of equal labels, in parallel, and the
+/// dict holds per-session arrays of the `session` label, `poc`, `value_area_low`,
+/// `value_area_high` and `total_volume`, plus `profiles`, a list of `(price, volume)`
+/// histograms.
+#[pyfunction]
+#[pyo3(signature = (prices, volumes, bin_size, value_area=0.7, sessions=None))]
+pub fn volume_profile_rust<'py>(
+    py: Python<'py>,
+    prices: Column<'py, f64>,
+    volumes: Column<'py, f64>,
+    bin_size: f64,
+    value_area: f64,
+    sessions: Option<Column<'py, i64>>
+) -> PyResult<Bound<'py, PyDict>> {
+    if !bin_size.is_finite() || bin_size <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "bin_size must be a finite value > 0"
+        ));
+    }
+    if !(value_area > 0.0 && value_area <= 1.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "value_area must be in (0, 1]"
+        ));
+    }
+    let (prices, volumes) = (prices.values()?, volumes.values()?);
+    let sessions = sessions.as_ref().map(|s| s.values()).transpose()?;
+    if volumes.len() != prices.len() || sessions.as_ref().is_some_and(|s| s.len() != prices.len()) {
+        return Err(crate::errors::LengthMismatchError::new_err(
+            "prices, volumes and sessions must have the same length"
+        ));
+    }
+    if volumes.iter().any(|&v| v < 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "volumes must be >= 0"
+        ));
+    }
+
+    let result = PyDict::new_bound(py);
+    let Some(sessions) = sessions else {
+        let profile = py.allow_threads(|| profile(&prices, &volumes, bin_size, value_area));
+        result.set_item("price", PyArray1::from_vec_bound(py, profile.prices))?;
+        result.set_item("volume", PyArray1::from_vec_bound(py, profile.volumes))?;
+        result.set_item("poc", profile.poc)?;
+        result.set_item("value_area_low", profile.value_area_low)?;
+        result.set_item("value_area_high", profile.value_area_high)?;
+        return Ok(result);
+    };
+    let runs = session_runs(&sessions);
+    let profiles: Vec<Profile> = py.allow_threads(|| {
+        threads::install(|| {
+            runs.par_iter()
+                .map(|&(start, end)| profile(&prices[start..end], &volumes[start..end], bin_size, value_area))
+                .collect()
+        })
+    });
+    result.set_item("session", PyArray1::from_iter_bound(py, runs.iter().map(|&(start, _)| sessions[start])))?;
+    result.set_item("poc", PyArray1::from_iter_bound(py, profiles.iter().map(|p| p.poc)))?;
+    result.set_item("value_area_low", PyArray1::from_iter_bound(py, profiles.iter().map(|p| p.value_area_low)))?;
+    result.set_item("value_area_high", PyArray1::from_iter_bound(py, profiles.iter().map(|p| p.value_area_high)))?;
+    let totals = profiles.iter().map(|p| p.volumes.iter().sum::<f64>());
+    result.set_item("total_volume", PyArray1::from_iter_bound(py, totals))?;
+    let histograms = PyList::empty_bound(py);
+    for profile in profiles {
+        histograms.append((PyArray1::from_vec_bound(py, profile.prices), PyArray1::from_vec_bound(py, profile.volumes)))?;
+    }
+    result.set_item("profiles", histograms)?;
+    Ok(result)
+}
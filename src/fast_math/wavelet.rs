@@ -0,0 +1,223 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use numpy::PyArray1;
+
+use crate::column::Column;
+
+/// An orthogonal wavelet, by its scaling (low-pass) filter
+#[derive(Clone, Copy)]
+struct Wavelet {
+    low: &'static [f64],
+}
+
+impl Wavelet {
+    fn parse(name: &str) -> PyResult<Self> {
+        // Reconstruction low-pass filters, the decomposition filters reversed
+        let low: &'static [f64] = match name.to_ascii_lowercase().as_str() {
+            "haar" | "db1" => &[FRAC_1_SQRT_2, FRAC_1_SQRT_2],
+            "db2" => &[0.48296291314469025, 0.836516303737469, 0.22414386804185735, -0.12940952255092145],
+            "db3" => &[
+                0.3326705529509569, 0.8068915093133388, 0.4598775021193313,
+                -0.13501102001039084, -0.08544127388224149, 0.035226291882100656,
+            ],
+            "db4" => &[
+                0.23037781330885523, 0.7148465705525415, 0.6308807679295904, -0.02798376941698385,
+                -0.18703481171888114, 0.030841381835986965, 0.032883011666982945, -0.010597401784997278,
+            ],
+            "sym4" => &[
+                0.0322231006040427, -0.012603967262037833, -0.09921954357684722, 0.29785779560527736,
+                0.8037387518059161, 0.49761866763201545, -0.02963552764599851, -0.07576571478927333,
+            ],
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown wavelet '{}', expected 'haar', 'db2', 'db3', 'db4' or 'sym4'", name
+            ))),
+        };
+        Ok(Wavelet { low })
+    }
+
+    /// The matching high-pass (wavelet) filter, by the quadrature mirror relation
+    fn high(&self, k: usize) -> f64 {
+        (-1f64).powi(k as i32) * self.low[self.low.len() - 1 - k]
+    }
+
+    /// Largest useful number of levels for `len` values, as PyWavelets' `dwt_max_level`:
+    /// while the signal is still at least one filter length long
+    fn max_level(&self, len: usize) -> usize {
+        let width = self.low.len() - 1;
+        if len < width + 1 {
+            return 0;
+        }
+        ((len / width) as f64).log2().floor() as usize
+    }
+
+    /// One level of the periodic transform: approximation and detail coefficients, each
+    /// half as long as `x` (rounded up; an odd signal repeats its last value)
+    fn step(&self, x: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let mut x = x.to_vec();
+        if x.len() % 2 == 1 {
+            x.push(x[x.len() - 1]);
+        }
+        let n = x.len();
+        (0..n / 2)
+            .map(|i| {
+                self.low.iter().enumerate().fold((0.0, 0.0), |(a, d), (k, &lo)| {
+                    let v = x[(2 * i + k) % n];
+                    (a + lo * v, d + self.high(k) * v)
+                })
+            })
+            .unzip()
+    }
+
+    /// Invert `step`, returning `len` values
+    fn inverse_step(&self, approx: &[f64], detail: &[f64], len: usize) -> Vec<f64> {
+        let n = 2 * approx.len();
+        let mut x = vec![0.0; n];
+        for (i, (a, d)) in approx.iter().zip(detail).enumerate() {
+            for (k, &lo) in self.low.iter().enumerate() {
+                x[(2 * i + k) % n] += lo * a + self.high(k) * d;
+            }
+        }
+        x.truncate(len);
+        x
+    }
+
+    /// `levels` steps: the coarsest approximation, then the details from coarsest to
+    /// finest, and the length of the signal entering each step, finest first
+    fn decompose(&self, x: &[f64], levels: usize) -> (Vec<Vec<f64>>, Vec<usize>) {
+        let mut details = Vec::with_capacity(levels);
+        let mut lengths = Vec::with_capacity(levels);
+        let mut approx = x.to_vec();
+        for _ in 0..levels {
+            lengths.push(approx.len());
+            let (a, d) = self.step(&approx);
+            details.push(d);
+            approx = a;
+        }
+        details.push(approx);
+        details.reverse();
+        (details, lengths)
+    }
+
+    fn reconstruct(&self, coeffs: &[Vec<f64>], lengths: &[usize]) -> Vec<f64> {
+        let mut approx = coeffs[0].clone();
+        for (detail, &len) in coeffs[1..].iter().zip(lengths.iter().rev()) {
+            approx = self.inverse_step(&approx, detail, len);
+        }
+        approx
+    }
+}
+
+fn resolve_levels(wavelet: &Wavelet, len: usize, levels: Option<usize>) -> PyResult<usize> {
+    // Signals shorter than the filter still get one level, wrapping around it
+    let max = wavelet.max_level(len).max(1);
+    match levels {
+        None => Ok(max),
+        Some(0) => Err(pyo3::exceptions::PyValueError::new_err(
+            "levels must be > 0"
+        )),
+        Some(levels) if levels > max => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "levels must be at most {} for {} values with this wavelet", max, len
+        ))),
+        Some(levels) => Ok(levels),
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let n = values.len();
+    if n % 2 == 1 { values[n / 2] } else { 0.5 * (values[n / 2 - 1] + values[n / 2]) }
+}
+
+/// Multilevel discrete wavelet transform of a series, like PyWavelets' `wavedec`.
+///
+/// `wavelet` is "haar" (or "db1"), "db2", "db3", "db4" (the default) or "sym4". Returns a
+/// list `[cA_n, cD_n, ..., cD_1]`: the approximation coefficients at the coarsest of the
+/// `levels` scales, then the detail coefficients from coarsest to finest. `levels`
+/// defaults to the deepest level at which the signal is still a filter length long
+/// (PyWavelets' `dwt_max_level`), and at least 1. The signal is extended periodically, as
+/// in PyWavelets' "periodization" mode, so each level halves the length (an odd length
+/// first repeats its last value) and, for even lengths, the transform is orthogonal and
+/// preserves energy; `wavelet_denoise_rust` with a zero threshold gives the input back.
+/// NaN values spread to the coefficients whose filters touch them.
+#[pyfunction]
+#[pyo3(signature = (data, wavelet="db4", levels=None))]
+pub fn dwt_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    wavelet: &str,
+    levels: Option<usize>
+) -> PyResult<Bound<'py, PyList>> {
+    let wavelet = Wavelet::parse(wavelet)?;
+    let values = data.values()?;
+    if values.len() < 2 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "dwt needs at least 2 values"
+        ));
+    }
+    let levels = resolve_levels(&wavelet, values.len(), levels)?;
+    let (coeffs, _) = py.allow_threads(|| wavelet.decompose(&values, levels));
+    Ok(PyList::new_bound(py, coeffs.into_iter().map(|c| PyArray1::from_vec_bound(py, c))))
+}
+
+/// Wavelet shrinkage denoising of a series, keeping its multi-scale structure.
+///
+/// The series is decomposed as by `dwt_rust` over `levels` scales, every detail
+/// coefficient is thresholded and the series is rebuilt from the result, with the input's
+/// length. `threshold` defaults to the universal threshold `sigma * sqrt(2 ln n)`, with
+/// the noise level `sigma` estimated from the finest details as their median absolute
+/// value over 0.6745. `mode="soft"` (the default) shrinks every coefficient towards zero
+/// by the threshold, giving smoother output; "hard" zeroes the ones below it and keeps
+/// the rest. The coarsest approximation is kept as is. NaN values are not supported and
+/// make the output NaN around them.
+#[pyfunction]
+#[pyo3(signature = (data, wavelet="db4", threshold=None, levels=None, mode="soft"))]
+pub fn wavelet_denoise_rust<'py>(
+    py: Python<'py>,
+    data: Column<'py, f64>,
+    wavelet: &str,
+    threshold: Option<f64>,
+    levels: Option<usize>,
+    mode: &str
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let wavelet = Wavelet::parse(wavelet)?;
+    let soft = match mode.to_ascii_lowercase().as_str() {
+        "soft" => true,
+        "hard" => false,
+        _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown mode '{}', expected 'soft' or 'hard'", mode
+        ))),
+    };
+    if threshold.is_some_and(|t| t.is_nan() || t < 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "threshold must be >= 0"
+        ));
+    }
+    let values = data.values()?;
+    if values.len() < 2 {
+        return Ok(PyArray1::from_slice_bound(py, &values));
+    }
+    let levels = resolve_levels(&wavelet, values.len(), levels)?;
+    let denoised = py.allow_threads(|| {
+        let (mut coeffs, lengths) = wavelet.decompose(&values, levels);
+        let threshold = threshold.unwrap_or_else(|| {
+            let mut finest: Vec<f64> = coeffs[coeffs.len() - 1].iter().map(|c| c.abs()).collect();
+            let sigma = median(&mut finest) / 0.6745;
+            sigma * (2.0 * (values.len() as f64).ln()).sqrt()
+        });
+        for detail in &mut coeffs[1..] {
+            for c in detail.iter_mut() {
+                *c = if c.abs() < threshold {
+                    0.0
+                } else if soft {
+                    c.signum() * (c.abs() - threshold)
+                } else {
+                    *c
+                };
+            }
+        }
+        wavelet.reconstruct(&coeffs, &lengths)
+    });
+    Ok(PyArray1::from_vec_bound(py, denoised))
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use futures_util::{SinkExt, StreamExt};
+use tokio::runtime::Runtime;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::tick_json::{Record, Schema};
+
+/// Longest wait between reconnection attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// State shared between the connection task and Python
+#[derive(Default)]
+struct Shared {
+    buffer: VecDeque<Record>,
+    dropped: u64,
+    skipped: u64,
+    connected: bool,
+    last_error: Option<String>,
+}
+
+impl Shared {
+    fn push(&mut self, record: Record, capacity: usize) {
+        if self.buffer.len() == capacity {
+            self.buffer.pop_front();
+            self.dropped += 1;
+        }
+        self.buffer.push_back(record);
+    }
+}
+
+fn lock(shared: &Mutex<Shared>) -> std::sync::MutexGuard<'_, Shared> {
+    // A panic inside the connection task must not make the buffer unreadable
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Read and buffer messages until the connection closes, fails or `stop` fires
+async fn read_messages<S>(
+    stream: &mut S,
+    schema: &Schema,
+    shared: &Mutex<Shared>,
+    capacity: usize,
+    stop: &mut watch::Receiver<bool>,
+    records: &mut Vec<Record>
+) where
+    S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        let message = tokio::select! {
+            _ = stop.changed() => return,
+            message = stream.next() => message,
+        };
+        let payload = match message {
+            Some(Ok(Message::Text(text))) => text.into_bytes(),
+            Some(Ok(Message::Binary(bytes))) => bytes,
+            Some(Ok(Message::Close(_))) | None => return,
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => {
+                lock(shared).last_error = Some(e.to_string());
+                return;
+            }
+        };
+
+        // Parsing happens before the lock is taken so Python readers are never blocked on JSON
+        records.clear();
+        let result = schema.parse(&payload, records);
+        let mut state = lock(shared);
+        match result {
+            Ok(()) if records.is_empty() => state.skipped += 1,
+            Ok(()) => {
+                for record in records.drain(..) {
+                    state.push(record, capacity);
+                }
+            }
+            Err(e) => {
+                state.skipped += 1;
+                state.last_error = Some(e);
+            }
+        }
+    }
+}
+
+async fn run(
+    url: String,
+    subscribe: Vec<String>,
+    schema: Arc<Schema>,
+    shared: Arc<Mutex<Shared>>,
+    capacity: usize,
+    reconnect: bool,
+    mut stop: watch::Receiver<bool>
+) {
+    let mut backoff = Duration::from_secs(1);
+    let mut records = Vec::new();
+
+    while !*stop.borrow() {
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((mut stream, _)) => {
+                let mut sent = Ok(());
+                for message in &subscribe {
+                    sent = stream.send(Message::Text(message.clone())).await;
+                    if sent.is_err() {
+                        break;
+                    }
+                }
+                match sent {
+                    Ok(()) => {
+                        lock(&shared).connected = true;
+                        backoff = Duration::from_secs(1);
+                        read_messages(&mut stream, &schema, &shared, capacity, &mut stop, &mut records).await;
+                        lock(&shared).connected = false;
+                    }
+                    Err(e) => lock(&shared).last_error = Some(e.to_string()),
+                }
+                let _ = stream.close(None).await;
+            }
+            Err(e) => lock(&shared).last_error = Some(e.to_string()),
+        }
+
+        if !reconnect || *stop.borrow() {
+            break;
+        }
+        tokio::select! {
+            _ = stop.changed() => break,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// WebSocket market-data client running on a background tokio runtime.
+///
+/// JSON payloads are parsed off the GIL and normalized into records using `fields`,
+/// a mapping of output column to (dotted) JSON key. `timestamp` and `symbol` are
+/// optional keys; every other entry is a numeric column, e.g. `price` / `size` for
+/// ticks or `open` ... `volume` for bars. Payloads missing a numeric field are
+/// skipped. Records are kept in a ring buffer of `capacity`; when it is full the
+/// oldest records are dropped and counted in `dropped`.
+#[pyclass(module = "fast_math")]
+pub struct MarketDataClient {
+    url: String,
+    subscribe: Vec<String>,
+    schema: Arc<Schema>,
+    capacity: usize,
+    reconnect: bool,
+    shared: Arc<Mutex<Shared>>,
+    runtime: Option<(Runtime, watch::Sender<bool>)>,
+}
+
+#[pymethods]
+impl MarketDataClient {
+    #[new]
+    #[pyo3(signature = (url, fields=None, subscribe=None, capacity=65536, reconnect=true))]
+    fn new(
+        url: String,
+        fields: Option<HashMap<String, String>>,
+        subscribe: Option<Vec<String>>,
+        capacity: usize,
+        reconnect: bool
+    ) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Buffer capacity must be > 0"
+            ));
+        }
+        Ok(MarketDataClient {
+            url,
+            subscribe: subscribe.unwrap_or_default(),
+            schema: Arc::new(Schema::from_fields(fields)?),
+            capacity,
+            reconnect,
+            shared: Arc::new(Mutex::new(Shared::default())),
+            runtime: None,
+        })
+    }
+
+    /// Connect in the background; subscription messages are sent on every (re)connect
+    fn start(&mut self) -> PyResult<()> {
+        if self.runtime.is_some() {
+            return Ok(());
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("fast_math-ws")
+            .enable_all()
+            .build()?;
+        let (stop_tx, stop_rx) = watch::channel(false);
+        runtime.spawn(run(
+            self.url.clone(),
+            self.subscribe.clone(),
+            self.schema.clone(),
+            self.shared.clone(),
+            self.capacity,
+            self.reconnect,
+            stop_rx,
+        ));
+        self.runtime = Some((runtime, stop_tx));
+        Ok(())
+    }
+
+    /// Disconnect and shut the background runtime down; buffered records are kept
+    fn stop(&mut self, py: Python<'_>) {
+        if let Some((runtime, stop)) = self.runtime.take() {
+            let _ = stop.send(true);
+            py.allow_threads(|| runtime.shutdown_timeout(Duration::from_secs(5)));
+        }
+        lock(&self.shared).connected = false;
+    }
+
+    /// Drain up to `max_records` buffered records (all by default) into column arrays:
+    /// `timestamp` (int64 UTC ns), `symbol` (list of str) and one float64 array per field
+    #[pyo3(signature = (max_records=None))]
+    fn poll<'py>(&self, py: Python<'py>, max_records: Option<usize>) -> PyResult<Bound<'py, PyDict>> {
+        let records: Vec<Record> = {
+            let mut state = lock(&self.shared);
+            let n = max_records.map_or(state.buffer.len(), |m| m.min(state.buffer.len()));
+            state.buffer.drain(..n).collect()
+        };
+
+        self.schema.to_columns(py, records)
+    }
+
+    fn __len__(&self) -> usize {
+        lock(&self.shared).buffer.len()
+    }
+
+    fn __enter__(mut slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        slf.start()?;
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, py: Python<'_>, _args: &Bound<'_, pyo3::types::PyTuple>) {
+        self.stop(py);
+    }
+
+    #[getter]
+    fn is_connected(&self) -> bool {
+        lock(&self.shared).connected
+    }
+
+    /// Records discarded because the buffer was full
+    #[getter]
+    fn dropped(&self) -> u64 {
+        lock(&self.shared).dropped
+    }
+
+    /// Payloads that were not valid JSON or did not contain the configured fields
+    #[getter]
+    fn skipped(&self) -> u64 {
+        lock(&self.shared).skipped
+    }
+
+    #[getter]
+    fn last_error(&self) -> Option<String> {
+        lock(&self.shared).last_error.clone()
+    }
+}
+
+impl Drop for MarketDataClient {
+    fn drop(&mut self) {
+        if let Some((runtime, stop)) = self.runtime.take() {
+            let _ = stop.send(true);
+            runtime.shutdown_background();
+        }
+    }
+}